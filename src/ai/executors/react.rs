@@ -244,6 +244,9 @@ impl ReActExecutor {
                         MCPToolCall::AppendToNote { name, .. } => (true, Some(name.clone())),
                         MCPToolCall::UpdateNote { name, .. } => (true, Some(name.clone())),
                         MCPToolCall::CreateNote { name, .. } => (true, Some(name.clone())),
+                        MCPToolCall::CreateNoteFromTemplate { name, .. } => {
+                            (true, Some(name.clone()))
+                        }
                         _ => (false, None),
                     };
 
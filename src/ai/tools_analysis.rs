@@ -4,6 +4,7 @@
 
 use crate::ai::tools::ToolError;
 use crate::core::database::NotesDatabase;
+use crate::core::outline::Outline;
 use anyhow::Result;
 use rig::tool::Tool;
 use serde::Deserialize;
@@ -128,20 +129,12 @@ impl Tool for GenerateToc {
                     std::fs::read_to_string(&meta.path).map_err(|e| anyhow::anyhow!(e))?;
 
                 let mut toc = String::from("Table of Contents:\n\n");
-                let mut found_headings = false;
+                let outline = Outline::parse(&content);
 
-                for line in content.lines() {
-                    if line.starts_with('#') {
-                        found_headings = true;
-                        let level = line.chars().take_while(|&c| c == '#').count();
-                        let title = line.trim_start_matches('#').trim();
-                        let indent = "  ".repeat(level.saturating_sub(1));
-                        toc.push_str(&format!("{}- {}\n", indent, title));
-                    }
-                }
-
-                if !found_headings {
+                if outline.is_empty() {
                     toc.push_str("(No headings found in this note)");
+                } else {
+                    toc.push_str(&outline.to_markdown_list());
                 }
 
                 Ok(toc)
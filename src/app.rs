@@ -4,15 +4,24 @@ use pulldown_cmark::{Options, Parser, html};
 use regex::Regex;
 use relm4::gtk::prelude::*;
 use relm4::{ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent, component, gtk};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::sync::LazyLock;
 
 use crate::base_ui::{BaseTableWidget, GtkThemeColors};
+use crate::core::base_writer::{add_tag_to_content, remove_tag_from_content};
+use crate::core::ex_command::{LineRange, complete as complete_ex_command, completion_context};
+use crate::core::registers::UNNAMED_REGISTER;
+use crate::core::wikilink::{anchor_line, heading_id, reference_at, update_links_after_rename};
 use crate::core::{
-    Base, BaseWriter, CommandParser, EditorAction, EditorMode, HtmlRenderer, InlinePropertyParser,
-    KeyModifiers, MarkdownParser, NoteBuffer, NoteFile, NotesConfig, NotesDatabase, NotesDirectory,
-    PreviewColors, PreviewTheme, SearchResult, StyleType, extract_all_tags,
+    Base, BaseWriter, CommandParser, CommitInfo, CompletionKind, Conflict, EditorAction,
+    EditorMode, ExCommand, ExCommandKind, ExContext, FoldCommand, Folds, GitCommand, HtmlRenderer,
+    InlinePropertyParser, KeyModifiers, MarkdownParser, Misspelling, Motion, NoteBuffer, NoteFile,
+    NotesConfig, NotesDatabase, NotesDirectory, Operator, OperatorTarget, Outline, PeriodicNote,
+    PeriodicTarget, PreviewColors, PreviewOutline, PreviewTheme, PromptField, PullOutcome,
+    Registers, Resolution, SearchResult, SpellChecker, StyleType, TableEditResult, Template,
+    TemplateContext, VaultGit, WikiLink, edit_section, edit_table, extract_all_tags, key_to_char,
+    move_to_cell,
 };
 use crate::i18n::{I18n, Language};
 use crate::mcp::{MCPToolCall, MCPToolResult};
@@ -41,6 +50,9 @@ static YOUTUBE_SHORTS_ID_RE: LazyLock<Regex> = LazyLock::new(|| {
 /// Regex para links internos [[Nombre]]
 static WIKI_LINK_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[\[([^\]]+)\]\]").unwrap());
 
+/// Prefijo de las claves de carpetas virtuales (búsquedas guardadas) en el sidebar
+const SAVED_SEARCH_PREFIX: &str = "saved-search:";
+
 /// Regex para links markdown [texto](url)
 static MD_LINK_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\(([^)]+)\)").unwrap());
@@ -131,9 +143,19 @@ pub enum SidebarPanel {
     #[default]
     Notes,
     Bases,
+    Outline,
     AiChat,
 }
 
+/// Dónde se escribe el resultado de una plantilla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateTarget {
+    /// Nota nueva (nombre con carpeta, como en `CreateNewNote`)
+    NewNote(String),
+    /// Posición del cursor en la nota actual (`:template`)
+    Cursor,
+}
+
 /// Formatos de Markdown que se pueden insertar desde la toolbar
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MarkdownFormat {
@@ -163,7 +185,16 @@ pub struct MainApp {
     buffer: NoteBuffer,
     mode: Rc<RefCell<EditorMode>>,
     command_parser: CommandParser,
+    /// Registros vim cambiados desde el último guardado en la configuración
+    registers_dirty: bool,
     cursor_position: usize,
+    /// Inicio del texto escrito en modo Insert (para `.` y para grabar macros)
+    insert_start: Option<usize>,
+    /// Línea de comando ex que se está escribiendo (sin el `:`)
+    command_line: String,
+    /// Candidatos de Tab para la línea de comando y el que se muestra
+    command_completions: Vec<String>,
+    command_completion_index: usize,
     text_buffer: gtk::TextBuffer,
     mode_label: gtk::Label,
     stats_label: gtk::Label,
@@ -191,8 +222,30 @@ pub struct MainApp {
     sidebar_stack: gtk::Stack,
     notes_panel_button: gtk::Button,
     bases_panel_button: gtk::Button,
+    outline_panel_button: gtk::Button,
     ai_chat_panel_button: gtk::Button,
     bases_list: gtk::ListBox,
+    // Panel de outline (encabezados de la nota actual)
+    sidebar_outline_label: gtk::Label,
+    outline_list: gtk::ListBox,
+    /// Secciones plegadas en el preview (za, zM, zR)
+    folds: Folds,
+    /// Encabezado al que saltar en el próximo render del preview
+    pending_heading_scroll: Cell<Option<usize>>,
+    /// Bloque (`^id`) al que saltar en el próximo render del preview
+    pending_block_scroll: RefCell<Option<String>>,
+    /// Corrector ortográfico (diccionarios Hunspell y diccionario personal)
+    spell_checker: RefCell<SpellChecker>,
+    /// Palabras mal escritas subrayadas en el TextView
+    misspellings: Rc<RefCell<Vec<Misspelling>>>,
+    /// Debounce de la revisión ortográfica mientras se escribe
+    spell_check_timeout: Rc<RefCell<Option<gtk::glib::SourceId>>>,
+    /// Repositorio Git del vault (si el historial Git está activado)
+    vault_git: Option<VaultGit>,
+    /// Debounce del commit automático tras guardar
+    git_commit_timeout: Rc<RefCell<Option<gtk::glib::SourceId>>>,
+    /// Actualiza el diálogo de conflictos abierto cuando termina una resolución
+    git_conflict_resolved: Rc<RefCell<Option<ConflictResolvedFn>>>,
     // Vista de Base (tabla de notas con propiedades)
     base_view_container: gtk::Box,
     base_view_title: gtk::Label,
@@ -352,6 +405,9 @@ pub struct MainApp {
     webview_load_completed: Rc<RefCell<bool>>,
 }
 
+/// Aviso al diálogo de conflictos: fichero y resultado de resolverlo
+type ConflictResolvedFn = Rc<dyn Fn(&str, Result<(), String>)>;
+
 #[derive(Debug, Clone)]
 pub enum AppMsg {
     ToggleTheme,
@@ -366,6 +422,8 @@ pub enum AppMsg {
     // Panel del sidebar (estilo VS Code)
     SwitchToPanel(SidebarPanel),
     RefreshBasesPanel,
+    RefreshOutlinePanel,
+    GoToHeading(usize), // Línea del encabezado elegido en el panel de outline
     CreateNewBase,
     LoadBase(String),
     DeleteBase(String),
@@ -411,6 +469,18 @@ pub enum AppMsg {
         name: String,
     },
     CreateNewNote(String),
+    /// Campos preguntados y portapapeles leído: rellenar la plantilla
+    ApplyTemplate {
+        template: Template,
+        target: TemplateTarget,
+        values: std::collections::HashMap<String, String>,
+        clipboard: Option<String>,
+    },
+    ShowFolderTemplateDialog(String),
+    SetFolderTemplate {
+        folder: String,
+        template: Option<String>,
+    },
     UpdateCursorPosition(usize),
     GtkInsertText {
         offset: usize,
@@ -420,6 +490,18 @@ pub enum AppMsg {
         start: usize,
         end: usize,
     },
+    RunSpellCheck,
+    ShowSpellingMenu {
+        x: f64,
+        y: f64,
+        offset: usize,
+    },
+    ReplaceMisspelling {
+        range: std::ops::Range<usize>,
+        word: String,
+        replacement: String,
+    },
+    AddToPersonalDictionary(String),
     AddTag(String),
     RemoveTag(String),
     RefreshTags,
@@ -445,7 +527,14 @@ pub enum AppMsg {
     PerformFloatingSearch(String),   // Ejecutar búsqueda después del debounce
     ExecuteFloatingSearch(String),   // Ejecutar búsqueda real después de mostrar "Buscando..."
     LoadNoteFromFloatingSearch(String), // Cargar nota desde resultado flotante
-    SaveAndSearchTag(String),        // Guardar nota actual y luego buscar tag
+    SaveFloatingSearch,              // Guardar la búsqueda actual como carpeta virtual
+    EditSavedSearch(String),         // Renombrar o cambiar la query de una carpeta virtual
+    StoreSavedSearch {
+        original: Option<String>, // None = búsqueda nueva
+        name: String,
+        query: String,
+    },
+    SaveAndSearchTag(String), // Guardar nota actual y luego buscar tag
     ShowPreferences,
     ShowKeyboardShortcuts,
     ShowAboutDialog,
@@ -480,7 +569,12 @@ pub enum AppMsg {
         video_id: String,
         transcript: String,
     }, // Actualizar con transcripción obtenida
-    ScrollToAnchor(String),    // Hacer scroll a un heading por su ID (anchor link)
+    ScrollToAnchor(String),    // Hacer scroll a un heading por su ID (anchor link) o a un ^bloque
+    OpenNoteLink(String),      // Abrir un enlace interno (Nota, Nota#Encabezado, Nota#^bloque)
+    UpdateNoteLinks {
+        old_name: String,
+        new_name: String,
+    }, // Actualizar los enlaces tras renombrar una nota
     MoveNoteToFolder {
         note_name: String,
         folder_name: Option<String>,
@@ -603,6 +697,32 @@ pub enum AppMsg {
         note_name: String,
         history_path: String,
     }, // Restaurar versión específica del historial
+    RestoreGitVersion {
+        note_name: String,
+        commit: CommitInfo,
+    }, // Restaurar una versión del historial Git
+    GitVersionRestored {
+        note_name: String,
+        commit: CommitInfo,
+        result: Result<String, String>,
+    }, // Resultado de restaurar una versión Git (contenido restaurado)
+    ShowGitDiff {
+        title: String,
+        result: Result<String, String>,
+    }, // Mostrar los cambios de una versión Git ya leídos
+    ShowGitHistory {
+        note_name: String,
+        commits: Vec<CommitInfo>,
+    }, // Mostrar las versiones Git ya leídas de una nota
+    GitCommand(GitCommand),  // Ejecutar un subcomando de :git
+    GitSyncFinished(Result<PullOutcome, String>), // Resultado de pull/push/sync
+    ExportFinished(Result<std::path::PathBuf, String>), // Resultado de :export
+    ShowGitConflicts(Vec<Conflict>), // Abrir la resolución de conflictos
+    GitConflictResolved {
+        path: String,
+        result: Result<(), String>,
+    }, // Resultado de resolver un fichero en conflicto
+    GitFinishMerge,          // Confirmar la fusión con los conflictos resueltos
 
     // === Mensajes de la Barra de Formato ===
     InsertMarkdownFormat(MarkdownFormat), // Insertar formato markdown en el texto
@@ -670,6 +790,16 @@ impl SimpleComponent for MainApp {
                             },
                         },
 
+                        append = outline_panel_button = &gtk::Button {
+                            set_icon_name: "view-list-symbolic",
+                            set_tooltip_text: Some("Outline"),
+                            add_css_class: "activity-bar-button",
+                            add_css_class: "flat",
+                            connect_clicked[sender] => move |_btn| {
+                                sender.input(AppMsg::SwitchToPanel(SidebarPanel::Outline));
+                            },
+                        },
+
                         append = ai_chat_panel_button = &gtk::Button {
                             set_icon_name: "user-available-symbolic",
                             set_tooltip_text: Some("AI Chat"),
@@ -814,6 +944,40 @@ impl SimpleComponent for MainApp {
                                         },
                                     },
                                 },
+
+                                // Panel de Outline
+                                add_named[Some("outline")] = &gtk::Box {
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_spacing: 0,
+                                    set_hexpand: false,
+                                    set_width_request: 200,
+
+                                    append = sidebar_outline_label = &gtk::Label {
+                                        set_label: "Outline",
+                                        set_xalign: 0.0,
+                                        set_margin_start: 10,
+                                        set_margin_end: 10,
+                                        set_margin_top: 8,
+                                        set_margin_bottom: 8,
+                                        add_css_class: "heading",
+                                    },
+
+                                    append = &gtk::ScrolledWindow {
+                                        set_vexpand: true,
+                                        set_hexpand: true,
+                                        set_policy: (gtk::PolicyType::Never, gtk::PolicyType::Automatic),
+
+                                        #[wrap(Some)]
+                                        set_child = outline_list = &gtk::ListBox {
+                                            add_css_class: "navigation-sidebar",
+                                            set_hexpand: true,
+                                            set_selection_mode: gtk::SelectionMode::Single,
+                                            set_activate_on_single_click: true,
+                                            set_can_focus: true,
+                                            set_focus_on_click: true,
+                                        },
+                                    },
+                                },
                             },
                         },
 
@@ -863,6 +1027,14 @@ impl SimpleComponent for MainApp {
                                             set_margin_end: 4,
                                         },
 
+                                        append = &gtk::Button {
+                                            set_icon_name: "folder-saved-search-symbolic",
+                                            set_tooltip_text: Some("Guardar búsqueda como carpeta virtual"),
+                                            add_css_class: "flat",
+                                            add_css_class: "circular",
+                                            connect_clicked => AppMsg::SaveFloatingSearch,
+                                        },
+
                                         append = &gtk::Button {
                                             set_icon_name: "window-close-symbolic",
                                             set_tooltip_text: Some("Cerrar (Esc)"),
@@ -3241,12 +3413,32 @@ Enjoy taking notes! 📓
             }
         };
 
+        // Historial Git del vault (si está activado)
+        let vault_git = if notes_config.borrow().git.enabled {
+            let root = notes_dir.root();
+            let git = if VaultGit::is_repository(root) {
+                VaultGit::open(root)
+            } else {
+                VaultGit::init(root)
+            };
+            git.map_err(|e| eprintln!("Error abriendo el historial Git: {}", e))
+                .ok()
+        } else {
+            None
+        };
+
         let mut model = MainApp {
             theme,
             buffer: initial_buffer,
             mode: mode.clone(),
-            command_parser: CommandParser::new(),
+            command_parser: CommandParser::new()
+                .with_registers(notes_config.borrow().get_vim_registers().clone()),
+            registers_dirty: false,
             cursor_position: 0,
+            insert_start: None,
+            command_line: String::new(),
+            command_completions: Vec::new(),
+            command_completion_index: 0,
             text_buffer: text_buffer.clone(),
             mode_label: widgets.mode_label.clone(),
             stats_label: widgets.stats_label.clone(),
@@ -3272,8 +3464,24 @@ Enjoy taking notes! 📓
             sidebar_stack: widgets.sidebar_stack.clone(),
             notes_panel_button: widgets.notes_panel_button.clone(),
             bases_panel_button: widgets.bases_panel_button.clone(),
+            outline_panel_button: widgets.outline_panel_button.clone(),
             ai_chat_panel_button: widgets.ai_chat_panel_button.clone(),
             bases_list: widgets.bases_list.clone(),
+            sidebar_outline_label: widgets.sidebar_outline_label.clone(),
+            outline_list: widgets.outline_list.clone(),
+            folds: Folds::new(),
+            pending_heading_scroll: Cell::new(None),
+            pending_block_scroll: RefCell::new(None),
+            spell_checker: RefCell::new(
+                SpellChecker::new(SpellChecker::default_search_dirs()).with_personal_dictionary(
+                    NotesConfig::dictionaries_dir().join("personal_dictionary.txt"),
+                ),
+            ),
+            misspellings: Rc::new(RefCell::new(Vec::new())),
+            spell_check_timeout: Rc::new(RefCell::new(None)),
+            vault_git,
+            git_commit_timeout: Rc::new(RefCell::new(None)),
+            git_conflict_resolved: Rc::new(RefCell::new(None)),
             base_view_container: base_view_container.clone(),
             base_view_title: base_view_title.clone(),
             base_view_grid: base_view_grid.clone(),
@@ -3366,10 +3574,7 @@ Enjoy taking notes! 📓
                 label.connect_activate_link(move |_label, uri| {
                     // Si el link tiene el esquema note://, cargar la nota
                     if let Some(note_name) = uri.strip_prefix("note://") {
-                        sender_clone.input(AppMsg::LoadNote {
-                            name: note_name.to_string(),
-                            highlight_text: None,
-                        });
+                        sender_clone.input(AppMsg::OpenNoteLink(note_name.to_string()));
                         return gtk::glib::Propagation::Stop;
                     }
                     gtk::glib::Propagation::Proceed
@@ -3487,12 +3692,10 @@ Enjoy taking notes! 📓
                                     }
                                 }
                                 "open-note" => {
-                                    // args: [note_name]
+                                    // args: [note_name] (puede llevar #Encabezado o #^bloque)
                                     if let Some(note_name) = args.get(0).and_then(|v| v.as_str()) {
-                                        sender_clone.input(AppMsg::LoadNote {
-                                            name: note_name.to_string(),
-                                            highlight_text: None,
-                                        });
+                                        sender_clone
+                                            .input(AppMsg::OpenNoteLink(note_name.to_string()));
                                     }
                                 }
                                 "search-tag" => {
@@ -3558,6 +3761,9 @@ Enjoy taking notes! 📓
         // Configurar el sender en el reminder_notifier
         model.reminder_notifier.set_app_sender(sender.clone());
 
+        // Recuperar el historial de undo de la nota inicial
+        model.restore_undo_history();
+
         // Inicializar RouterAgent para el sistema multi-agente
         // Crear cliente de IA para el router (usa misma configuración que chat)
         let api_key = notes_config
@@ -3752,6 +3958,32 @@ Enjoy taking notes! 📓
             }
         ));
 
+        // Acción para asignar la plantilla de una carpeta
+        let folder_template_action = gtk::gio::SimpleAction::new("folder_template", None);
+        folder_template_action.connect_activate(gtk::glib::clone!(
+            #[strong]
+            sender,
+            #[strong(rename_to = item_name)]
+            model.context_item_name,
+            move |_, _| {
+                sender.input(AppMsg::ShowFolderTemplateDialog(item_name.borrow().clone()));
+            }
+        ));
+
+        // Acción para editar una búsqueda guardada (carpeta virtual)
+        let edit_saved_search_action = gtk::gio::SimpleAction::new("edit_saved_search", None);
+        edit_saved_search_action.connect_activate(gtk::glib::clone!(
+            #[strong]
+            sender,
+            #[strong(rename_to = item_name)]
+            model.context_item_name,
+            move |_, _| {
+                if let Some(name) = item_name.borrow().strip_prefix(SAVED_SEARCH_PREFIX) {
+                    sender.input(AppMsg::EditSavedSearch(name.to_string()));
+                }
+            }
+        ));
+
         // Acción para ver historial de la nota
         let show_history_action = gtk::gio::SimpleAction::new("show_history", None);
         show_history_action.connect_activate(gtk::glib::clone!(
@@ -3769,6 +4001,8 @@ Enjoy taking notes! 📓
         action_group.add_action(&delete_action);
         action_group.add_action(&open_folder_action);
         action_group.add_action(&change_icon_action);
+        action_group.add_action(&folder_template_action);
+        action_group.add_action(&edit_saved_search_action);
         action_group.add_action(&show_history_action);
         context_menu.insert_action_group("item", Some(&action_group));
 
@@ -3984,7 +4218,7 @@ Enjoy taking notes! 📓
                     shift: modifiers.contains(gtk::gdk::ModifierType::SHIFT_MASK),
                 };
 
-                // En modo Insert, interceptar teclas especiales (Escape, Tab, Return)
+                // En modo Insert, interceptar teclas especiales (Escape, Tab, Shift-Tab, Return)
                 // Dejar que GTK maneje el resto para permitir composición de acentos
                 if current_mode == EditorMode::Insert {
                     if key_mods.ctrl {
@@ -3995,7 +4229,7 @@ Enjoy taking notes! 📓
                         gtk::glib::Propagation::Stop
                     } else {
                         match key_name.as_str() {
-                            "Escape" | "Tab" | "Return" => {
+                            "Escape" | "Tab" | "ISO_Left_Tab" | "Return" => {
                                 sender.input(AppMsg::KeyPress {
                                     key: key_name,
                                     modifiers: key_mods,
@@ -4123,8 +4357,8 @@ Enjoy taking notes! 📓
                     shift: modifiers.contains(gtk::gdk::ModifierType::SHIFT_MASK),
                 };
 
-                // En modo Normal, procesar otros keybindings
-                if current_mode == EditorMode::Normal {
+                // En modo Normal (y en la línea de comando), procesar otros keybindings
+                if matches!(current_mode, EditorMode::Normal | EditorMode::Command) {
                     sender.input(AppMsg::KeyPress {
                         key: key_name,
                         modifiers: key_mods,
@@ -4283,6 +4517,37 @@ Enjoy taking notes! 📓
         ));
         text_view_actual.add_controller(click_controller);
 
+        // Clic derecho sobre una palabra mal escrita: sugerencias del corrector
+        let spelling_click = gtk::GestureClick::new();
+        spelling_click.set_button(3);
+        spelling_click.set_propagation_phase(gtk::PropagationPhase::Capture);
+        let misspellings_for_click = model.misspellings.clone();
+        let spelling_text_view = text_view_actual.clone();
+        spelling_click.connect_pressed(gtk::glib::clone!(
+            #[strong]
+            sender,
+            move |gesture, _n_press, x, y| {
+                let (buffer_x, buffer_y) = spelling_text_view.window_to_buffer_coords(
+                    gtk::TextWindowType::Widget,
+                    x as i32,
+                    y as i32,
+                );
+                let Some(iter) = spelling_text_view.iter_at_location(buffer_x, buffer_y) else {
+                    return;
+                };
+                let offset = iter.offset() as usize;
+                if misspellings_for_click
+                    .borrow()
+                    .iter()
+                    .any(|misspelling| misspelling.range.contains(&offset))
+                {
+                    gesture.set_state(gtk::EventSequenceState::Claimed);
+                    sender.input(AppMsg::ShowSpellingMenu { x, y, offset });
+                }
+            }
+        ));
+        text_view_actual.add_controller(spelling_click);
+
         // Controller para cerrar sidebar cuando se hace click en el área del editor
         // Lo ponemos en el editor_stack para capturar clicks tanto en modo Normal (WebView) como Insert (TextView)
         let editor_stack_for_click = model.editor_stack.clone();
@@ -4748,6 +5013,22 @@ Enjoy taking notes! 📓
             }
         ));
 
+        // Click en el panel de outline: saltar al encabezado
+        widgets
+            .outline_list
+            .connect_row_activated(gtk::glib::clone!(
+                #[strong]
+                sender,
+                move |_list_box, row| {
+                    let heading_line =
+                        unsafe { row.data::<usize>("heading_line").map(|data| *data.as_ref()) };
+
+                    if let Some(line) = heading_line {
+                        sender.input(AppMsg::GoToHeading(line));
+                    }
+                }
+            ));
+
         // Agregar click derecho para menú contextual en bases
         let bases_right_click = gtk::GestureClick::new();
         bases_right_click.set_button(3); // Botón derecho
@@ -5527,6 +5808,10 @@ Enjoy taking notes! 📓
                 .reminders_button
                 .set_tooltip_text(Some(&i18n.t("reminder_tooltip")));
             model.sidebar_notes_label.set_label(&i18n.t("notes"));
+            model.sidebar_outline_label.set_label(&i18n.t("outline"));
+            model
+                .outline_panel_button
+                .set_tooltip_text(Some(&i18n.t("outline")));
             model
                 .floating_search_entry
                 .set_placeholder_text(Some(&i18n.t("search_placeholder")));
@@ -5860,6 +6145,7 @@ Enjoy taking notes! 📓
                     SidebarPanel::Notes => {
                         self.notes_panel_button.add_css_class("active");
                         self.bases_panel_button.remove_css_class("active");
+                        self.outline_panel_button.remove_css_class("active");
                         self.ai_chat_panel_button.remove_css_class("active");
                         self.sidebar_stack.set_visible_child_name("notes");
                         // Solo cambiar a editor si estamos en el chat, NO si estamos viendo una base
@@ -5873,6 +6159,7 @@ Enjoy taking notes! 📓
                     SidebarPanel::Bases => {
                         self.notes_panel_button.remove_css_class("active");
                         self.bases_panel_button.add_css_class("active");
+                        self.outline_panel_button.remove_css_class("active");
                         self.ai_chat_panel_button.remove_css_class("active");
                         self.sidebar_stack.set_visible_child_name("bases");
                         // Refrescar lista de bases al cambiar al panel
@@ -5884,9 +6171,23 @@ Enjoy taking notes! 📓
                             self.sync_to_view_no_focus();
                         }
                     }
+                    SidebarPanel::Outline => {
+                        self.notes_panel_button.remove_css_class("active");
+                        self.bases_panel_button.remove_css_class("active");
+                        self.outline_panel_button.add_css_class("active");
+                        self.ai_chat_panel_button.remove_css_class("active");
+                        self.sidebar_stack.set_visible_child_name("outline");
+                        self.refresh_outline_panel();
+                        // El outline es de la nota abierta: volver siempre al editor
+                        if current_view != "editor" {
+                            self.content_stack.set_visible_child_name("editor");
+                            self.sync_to_view_no_focus();
+                        }
+                    }
                     SidebarPanel::AiChat => {
                         self.notes_panel_button.remove_css_class("active");
                         self.bases_panel_button.remove_css_class("active");
+                        self.outline_panel_button.remove_css_class("active");
                         self.ai_chat_panel_button.add_css_class("active");
                         // Cerrar el sidebar cuando se abre el chat AI
                         if self.sidebar_visible {
@@ -5899,7 +6200,7 @@ Enjoy taking notes! 📓
                     }
                 }
 
-                // Asegurar que el sidebar esté visible (solo para Notes y Bases)
+                // Asegurar que el sidebar esté visible (Notes, Bases y Outline)
                 if !self.sidebar_visible {
                     self.sidebar_visible = true;
                     // Ancho del sidebar VS Code style (activity_bar 48px + panel 200px)
@@ -5907,6 +6208,20 @@ Enjoy taking notes! 📓
                 }
             }
 
+            AppMsg::RefreshOutlinePanel => {
+                self.refresh_outline_panel();
+            }
+
+            AppMsg::GoToHeading(line) => {
+                if let Some(pos) = self.buffer.line_col_to_char(line, 0) {
+                    self.cursor_position = pos;
+                    self.reveal_heading(line);
+                    self.sync_to_view();
+                    self.update_status_bar(&sender);
+                    self.refresh_outline_panel();
+                }
+            }
+
             AppMsg::RefreshBasesPanel => {
                 // Limpiar lista actual
                 while let Some(child) = self.bases_list.first_child() {
@@ -6167,6 +6482,18 @@ Enjoy taking notes! 📓
                                             }
                                         }
                                     });
+
+                                    // Configurar callback para operaciones masivas (aplicar/deshacer)
+                                    let sender_clone = sender.clone();
+                                    let base_id_for_reload = base_id.clone();
+                                    widget.on_bulk_applied(move |message| {
+                                        sender_clone
+                                            .input(AppMsg::ShowNotification(message.to_string()));
+                                        sender_clone
+                                            .input(AppMsg::LoadBase(base_id_for_reload.clone()));
+                                        // Los movimientos de carpeta cambian el sidebar
+                                        sender_clone.input(AppMsg::RefreshSidebar);
+                                    });
                                 }
                                 Err(e) => {
                                     eprintln!("Error al parsear config de base: {}", e);
@@ -6500,11 +6827,7 @@ Enjoy taking notes! 📓
                 let action = match current_mode {
                     EditorMode::Normal => self.command_parser.parse_normal_mode(&key, modifiers),
                     EditorMode::Insert => self.command_parser.parse_insert_mode(&key, modifiers),
-                    EditorMode::Command => {
-                        // En modo comando, acumular input hasta Enter
-                        // Por ahora, simplificamos
-                        EditorAction::None
-                    }
+                    EditorMode::Command => self.handle_command_line_key(&key, modifiers, &sender),
                     EditorMode::Visual => EditorAction::None,
                     EditorMode::ChatAI => {
                         // En modo Chat AI, Escape sale del modo
@@ -6682,34 +7005,83 @@ Enjoy taking notes! 📓
                     .trim()
                     .to_string();
 
-                let is_folder_only = clean_name.ends_with('/');
+                // Carpeta con plantilla: preguntar sus campos antes de crear la nota
+                if !clean_name.ends_with('/') {
+                    let folder = clean_name
+                        .trim_start_matches('/')
+                        .rsplit_once('/')
+                        .map(|(folder, _)| folder.to_string());
+                    let template = self
+                        .notes_config
+                        .borrow()
+                        .template_for_folder(folder.as_deref())
+                        .map(str::to_string);
+                    if let Some(template) = template {
+                        self.start_template(
+                            &template,
+                            TemplateTarget::NewNote(clean_name),
+                            &sender,
+                        );
+                        return;
+                    }
+                }
 
-                if let Err(e) = self.create_new_note(&clean_name) {
-                    eprintln!(
-                        "Error creando '{}' (original: '{}'): {}",
-                        clean_name, name, e
-                    );
-                } else if is_folder_only {
-                    // Solo se creó una carpeta, refrescar sidebar
-                    self.populate_notes_list(&sender);
-                    *self.is_populating_list.borrow_mut() = false;
-                    println!("📂 Carpeta creada y sidebar actualizado");
-                } else {
-                    // Se creó una nota, hacer el proceso completo
-                    self.sync_to_view();
-                    self.update_status_bar(&sender);
-                    self.refresh_tags_display_with_sender(&sender);
-                    self.refresh_todos_summary();
-                    self.window_title.set_label(&clean_name);
+                self.finish_new_note(&clean_name, None, &sender);
+            }
 
-                    // Refrescar lista de notas en el sidebar
-                    self.populate_notes_list(&sender);
-                    *self.is_populating_list.borrow_mut() = false;
+            AppMsg::ApplyTemplate {
+                template,
+                target,
+                values,
+                clipboard,
+            } => {
+                let context = TemplateContext::new(String::new())
+                    .with_clipboard(clipboard)
+                    .with_language(self.i18n.borrow().current_language().code())
+                    .with_values(values);
+                match target {
+                    TemplateTarget::NewNote(name) => {
+                        self.finish_new_note(&name, Some((&template, context)), &sender);
+                    }
+                    TemplateTarget::Cursor => {
+                        let note_name = self
+                            .current_note
+                            .as_ref()
+                            .map(|note| note.name().to_string())
+                            .unwrap_or_default();
+                        let (folder, title) = match note_name.rsplit_once('/') {
+                            Some((folder, title)) => (Some(folder.to_string()), title.to_string()),
+                            None => (None, note_name),
+                        };
+                        let mut context = context.with_folder(folder);
+                        context.title = title;
+                        let rendered = template.render(&context);
+
+                        let position = self.cursor_position.min(self.buffer.len_chars());
+                        self.buffer.insert(position, &rendered.content);
+                        self.cursor_position = position
+                            + rendered
+                                .cursor
+                                .unwrap_or_else(|| rendered.content.chars().count());
+                        self.has_unsaved_changes = true;
+                        self.sync_to_view();
+                        self.update_status_bar(&sender);
+                    }
+                }
+            }
+
+            AppMsg::ShowFolderTemplateDialog(folder) => {
+                self.show_folder_template_dialog(&folder, &sender);
+            }
 
-                    // Cambiar a modo Insert para empezar a escribir
-                    *self.mode.borrow_mut() = EditorMode::Insert;
+            AppMsg::SetFolderTemplate { folder, template } => {
+                let mut config = self.notes_config.borrow_mut();
+                config.set_folder_template(&folder, template);
+                if let Err(e) = config.save(NotesConfig::default_path()) {
+                    eprintln!("Error guardando configuración: {}", e);
                 }
             }
+
             AppMsg::UpdateCursorPosition(pos) => {
                 // Actualizar la posición del cursor cuando el usuario hace clic
                 // En modo Normal, GTK muestra texto limpio, entonces pos es posición display
@@ -6726,26 +7098,94 @@ Enjoy taking notes! 📓
             }
 
             AppMsg::ScrollToAnchor(anchor_id) => {
-                // Buscar el heading con el ID especificado
+                // Buscar la línea del heading o del bloque (^id) en la nota
+                let Some(line) = anchor_line(&self.buffer.to_string(), &anchor_id) else {
+                    eprintln!("⚠️ No se encontró el heading con ID: #{}", anchor_id);
+                    return;
+                };
+
+                // Mover el cursor y llevar el preview hasta allí
+                if let Some(pos) = self.buffer.line_col_to_char(line, 0) {
+                    self.cursor_position = pos;
+                }
+                self.reveal_heading(line);
+                self.pending_block_scroll
+                    .replace(anchor_id.strip_prefix('^').map(str::to_string));
+                self.sync_to_view();
+                self.update_status_bar(&sender);
+
+                // En el TextView, hacer scroll a la posición del heading
                 if let Some(anchor) = self
                     .heading_anchors
                     .borrow()
                     .iter()
-                    .find(|a| a.id == anchor_id)
-                    .cloned()
+                    .find(|a| a.id == heading_id(&anchor_id))
                 {
-                    // Crear un iterador en la posición del heading
                     let mut iter = self.text_buffer.start_iter();
                     iter.set_offset(anchor.line_offset);
-
-                    // Hacer scroll a esa posición
                     self.text_view
                         .scroll_to_iter(&mut iter, 0.0, true, 0.0, 0.1);
-
-                    // Opcionalmente, mostrar una notificación
                     println!("📍 Navegando a: {}", anchor.text);
-                } else {
-                    eprintln!("⚠️ No se encontró el heading con ID: #{}", anchor_id);
+                }
+            }
+
+            AppMsg::OpenNoteLink(destination) => {
+                let link = WikiLink::parse(&destination);
+                if !link.note.is_empty() {
+                    sender.input(AppMsg::LoadNote {
+                        name: link.note.clone(),
+                        highlight_text: None,
+                    });
+                }
+                if let Some(target) = &link.target {
+                    sender.input(AppMsg::ScrollToAnchor(target.anchor()));
+                }
+            }
+
+            AppMsg::UpdateNoteLinks { old_name, new_name } => {
+                // Guardar antes para no perder cambios si la nota abierta enlaza a la renombrada
+                if self.current_note.is_some() && self.has_unsaved_changes {
+                    self.save_current_note(false);
+                }
+
+                let changed = match update_links_after_rename(&self.notes_dir, &old_name, &new_name)
+                {
+                    Ok(changed) => changed,
+                    Err(e) => {
+                        eprintln!("Error actualizando enlaces a '{}': {}", old_name, e);
+                        return;
+                    }
+                };
+                let current_path = self
+                    .current_note
+                    .as_ref()
+                    .map(|note| note.path().to_path_buf());
+                for (note, content) in &changed {
+                    let folder = self.notes_dir.relative_folder(note.path());
+                    if let Err(e) = self.notes_db.index_note(
+                        note.name(),
+                        note.path().to_str().unwrap_or(""),
+                        content,
+                        folder.as_deref(),
+                    ) {
+                        eprintln!("Error indexando '{}': {}", note.name(), e);
+                    }
+                    if current_path.as_deref() == Some(note.path()) {
+                        // El archivo ya se escribió con los enlaces nuevos
+                        self.buffer.set_text(content);
+                        self.buffer.mark_saved();
+                        self.cursor_position = self.cursor_position.min(self.buffer.len_chars());
+                        self.has_unsaved_changes = false;
+                        self.sync_to_view();
+                    }
+                }
+                if !changed.is_empty() {
+                    sender.input(AppMsg::ShowNotification(
+                        self.i18n
+                            .borrow()
+                            .t("links_updated")
+                            .replace("{}", &changed.len().to_string()),
+                    ));
                 }
             }
 
@@ -6881,6 +7321,24 @@ Enjoy taking notes! 📓
                 let i18n = self.i18n.borrow();
                 let menu = gtk::gio::Menu::new();
 
+                // Las carpetas virtuales (búsquedas guardadas) solo se editan o eliminan
+                let is_saved_search = is_folder && item_name.starts_with(SAVED_SEARCH_PREFIX);
+                if is_saved_search {
+                    menu.append(Some(&i18n.t("rename")), Some("item.rename"));
+                    menu.append(
+                        Some(&i18n.t("edit_saved_search")),
+                        Some("item.edit_saved_search"),
+                    );
+                    menu.append(Some(&i18n.t("delete")), Some("item.delete"));
+                    self.context_menu.set_menu_model(Some(&menu));
+                    self.context_menu.set_parent(&self.notes_list);
+
+                    let rect = gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1);
+                    self.context_menu.set_pointing_to(Some(&rect));
+                    self.context_menu.popup();
+                    return;
+                }
+
                 // Agregar opción de "Abrir en explorador"
                 menu.append(
                     Some(&i18n.t("open_in_file_manager")),
@@ -6889,6 +7347,14 @@ Enjoy taking notes! 📓
                 menu.append(Some(&i18n.t("change_icon")), Some("item.change_icon"));
                 menu.append(Some(&i18n.t("rename")), Some("item.rename"));
 
+                // Plantilla por defecto de las notas nuevas de la carpeta
+                if is_folder {
+                    menu.append(
+                        Some(&i18n.t("folder_template")),
+                        Some("item.folder_template"),
+                    );
+                }
+
                 // Solo mostrar historial para notas, no carpetas
                 if !is_folder {
                     menu.append(Some(&i18n.t("view_history")), Some("item.show_history"));
@@ -6909,6 +7375,20 @@ Enjoy taking notes! 📓
                 self.context_menu.popdown();
                 self.context_menu.unparent();
 
+                // Carpeta virtual: eliminar solo la búsqueda guardada, no las notas
+                if let Some(search_name) = item_name
+                    .strip_prefix(SAVED_SEARCH_PREFIX)
+                    .filter(|_| is_folder)
+                {
+                    if let Err(e) = self.notes_db.delete_saved_search(search_name) {
+                        eprintln!("Error eliminando búsqueda guardada: {}", e);
+                    }
+                    self.expanded_folders.remove(&item_name);
+                    self.populate_notes_list(&sender);
+                    *self.is_populating_list.borrow_mut() = false;
+                    return;
+                }
+
                 if is_folder {
                     println!("Eliminar carpeta: {}", item_name);
 
@@ -6994,6 +7474,15 @@ Enjoy taking notes! 📓
             }
 
             AppMsg::RenameItem(item_name, is_folder) => {
+                // Las carpetas virtuales se renombran desde su diálogo de búsqueda
+                if let Some(search_name) = item_name
+                    .strip_prefix(SAVED_SEARCH_PREFIX)
+                    .filter(|_| is_folder)
+                {
+                    sender.input(AppMsg::EditSavedSearch(search_name.to_string()));
+                    return;
+                }
+
                 self.context_menu.popdown();
                 self.context_menu.unparent();
 
@@ -7209,6 +7698,7 @@ Enjoy taking notes! 📓
             AppMsg::QuitApp => {
                 println!("👋 Cerrando aplicación completamente...");
                 sender.input(AppMsg::SaveCurrentNote);
+                self.persist_registers();
 
                 // Limpiar archivos temporales
                 let _ = std::fs::remove_file("/tmp/notnative.lock");
@@ -7354,8 +7844,9 @@ Enjoy taking notes! 📓
                                             note.name()
                                         );
 
-                                        // Recargar contenido en el buffer
-                                        self.buffer = crate::core::NoteBuffer::from_text(&content);
+                                        // Recargar contenido en el buffer (el cambio externo se puede deshacer)
+                                        self.buffer.set_text(&content);
+                                        self.buffer.mark_saved();
                                         self.cursor_position = 0;
 
                                         // sync_to_view se encarga de todo: renderizar markdown si está en Normal,
@@ -7408,6 +7899,7 @@ Enjoy taking notes! 📓
                 println!("DEBUG: Enviando CheckNoteMention desde GtkInsertText");
                 sender.input(AppMsg::CheckNoteMention);
                 sender.input(AppMsg::CheckPropertyCompletion);
+                self.schedule_spell_check(&sender);
             }
 
             AppMsg::GtkDeleteRange { start, end } => {
@@ -7424,9 +7916,114 @@ Enjoy taking notes! 📓
 
                     self.update_status_bar(&sender);
                     sender.input(AppMsg::RefreshTags);
+                    self.schedule_spell_check(&sender);
                 }
             }
 
+            AppMsg::RunSpellCheck => {
+                self.refresh_spelling();
+            }
+
+            AppMsg::ShowSpellingMenu { x, y, offset } => {
+                let misspelling = self
+                    .misspellings
+                    .borrow()
+                    .iter()
+                    .find(|misspelling| misspelling.range.contains(&offset))
+                    .cloned();
+                if let Some(misspelling) = misspelling {
+                    let language = self.spell_language();
+                    let suggestions = self
+                        .spell_checker
+                        .borrow_mut()
+                        .suggest(&misspelling.word, &language);
+                    let i18n = self.i18n.borrow();
+
+                    let suggestions_section = gtk::gio::Menu::new();
+                    if suggestions.is_empty() {
+                        suggestions_section.append(Some(&i18n.t("spell_no_suggestions")), None);
+                    }
+                    for suggestion in &suggestions {
+                        let item = gtk::gio::MenuItem::new(Some(suggestion), None);
+                        item.set_action_and_target_value(
+                            Some("spell.replace"),
+                            Some(&suggestion.to_variant()),
+                        );
+                        suggestions_section.append_item(&item);
+                    }
+                    let dictionary_section = gtk::gio::Menu::new();
+                    dictionary_section
+                        .append(Some(&i18n.t("spell_add_to_dictionary")), Some("spell.add"));
+                    let menu = gtk::gio::Menu::new();
+                    menu.append_section(None, &suggestions_section);
+                    menu.append_section(None, &dictionary_section);
+
+                    let popover = gtk::PopoverMenu::from_model(Some(&menu));
+                    popover.set_parent(&self.text_view);
+                    popover.set_has_arrow(true);
+                    let rect = gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1);
+                    popover.set_pointing_to(Some(&rect));
+
+                    let action_group = gtk::gio::SimpleActionGroup::new();
+
+                    // Reemplazar por la sugerencia elegida
+                    let replace_action =
+                        gtk::gio::SimpleAction::new("replace", Some(gtk::glib::VariantTy::STRING));
+                    let sender_clone = sender.clone();
+                    let popover_clone = popover.clone();
+                    let misspelling_clone = misspelling.clone();
+                    replace_action.connect_activate(move |_, parameter| {
+                        popover_clone.popdown();
+                        popover_clone.unparent();
+                        if let Some(replacement) = parameter.and_then(|p| p.get::<String>()) {
+                            sender_clone.input(AppMsg::ReplaceMisspelling {
+                                range: misspelling_clone.range.clone(),
+                                word: misspelling_clone.word.clone(),
+                                replacement,
+                            });
+                        }
+                    });
+                    action_group.add_action(&replace_action);
+
+                    // Añadir al diccionario personal
+                    let add_action = gtk::gio::SimpleAction::new("add", None);
+                    let sender_clone = sender.clone();
+                    let popover_clone = popover.clone();
+                    add_action.connect_activate(move |_, _| {
+                        popover_clone.popdown();
+                        popover_clone.unparent();
+                        sender_clone
+                            .input(AppMsg::AddToPersonalDictionary(misspelling.word.clone()));
+                    });
+                    action_group.add_action(&add_action);
+
+                    popover.insert_action_group("spell", Some(&action_group));
+                    popover.popup();
+                }
+            }
+
+            AppMsg::ReplaceMisspelling {
+                range,
+                word,
+                replacement,
+            } => {
+                // La palabra puede haber cambiado mientras el menú estaba abierto
+                if self.buffer.slice(range.clone()).as_deref() == Some(word.as_str()) {
+                    self.buffer.replace(range.clone(), &replacement);
+                    self.cursor_position = range.start + replacement.chars().count();
+                    self.has_unsaved_changes = true;
+                    self.sync_to_view();
+                    self.update_status_bar(&sender);
+                }
+            }
+
+            AppMsg::AddToPersonalDictionary(word) => {
+                if let Err(e) = self.spell_checker.borrow_mut().add_to_personal(&word) {
+                    eprintln!("Error guardando el diccionario personal: {}", e);
+                }
+                self.refresh_spelling();
+            }
+
             AppMsg::AddTag(tag) => {
                 if let Some(ref note) = self.current_note {
                     let content = self.buffer.to_string();
@@ -7445,7 +8042,7 @@ Enjoy taking notes! 📓
                             Err(_) => content.clone(),
                         };
 
-                        self.buffer = NoteBuffer::from_text(&new_content);
+                        self.buffer.set_text(&new_content);
                         self.sync_to_view();
 
                         // Guardar y actualizar base de datos
@@ -7475,7 +8072,7 @@ Enjoy taking notes! 📓
                         Err(_) => content.clone(),
                     };
 
-                    self.buffer = NoteBuffer::from_text(&new_content);
+                    self.buffer.set_text(&new_content);
                     self.sync_to_view();
 
                     // Guardar y actualizar base de datos
@@ -8424,10 +9021,94 @@ Enjoy taking notes! 📓
                 self.perform_floating_search(&query, &sender);
             }
 
-            AppMsg::LoadNoteFromFloatingSearch(name) => {
-                // Si estamos en modo búsqueda dentro de la nota, ir a siguiente coincidencia
+            AppMsg::SaveFloatingSearch => {
                 if *self.floating_search_in_current_note.borrow() {
-                    // El Enter en modo búsqueda en nota va a la siguiente coincidencia
+                    return;
+                }
+
+                let text = self.floating_search_entry.text().trim().to_string();
+                let query = crate::core::SearchQuery::parse(&text);
+                if query.is_empty() {
+                    return;
+                }
+
+                // Pedir un nombre para la carpeta virtual (por defecto, el texto buscado)
+                self.show_saved_search_dialog(None, &text, &text, &sender);
+            }
+
+            AppMsg::EditSavedSearch(name) => {
+                self.context_menu.popdown();
+                self.context_menu.unparent();
+
+                match self.notes_db.get_saved_search(&name) {
+                    Ok(Some(saved)) => {
+                        let query = saved.query.to_string();
+                        self.show_saved_search_dialog(Some(&name), &name, &query, &sender);
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Error leyendo búsqueda guardada: {}", e),
+                }
+            }
+
+            AppMsg::StoreSavedSearch {
+                original,
+                name,
+                query,
+            } => {
+                let name = name.trim().to_string();
+                let parsed = crate::core::SearchQuery::parse(&query);
+                if name.is_empty() || parsed.is_empty() {
+                    return;
+                }
+
+                let renamed = original.as_deref() != Some(name.as_str());
+                if renamed && matches!(self.notes_db.get_saved_search(&name), Ok(Some(_))) {
+                    self.show_notification(&self.i18n.borrow().t("saved_search_exists"));
+                    return;
+                }
+
+                let result = match &original {
+                    Some(old) => (if renamed {
+                        self.notes_db.rename_saved_search(old, &name)
+                    } else {
+                        Ok(())
+                    })
+                    .and_then(|_| self.notes_db.update_saved_search(&name, &parsed)),
+                    None => self
+                        .notes_db
+                        .create_saved_search(&name, &parsed)
+                        .map(|_| ()),
+                };
+
+                match result {
+                    Ok(_) => {
+                        // Mostrar la carpeta virtual expandida con su nombre actual
+                        if let Some(old) = &original {
+                            self.expanded_folders
+                                .remove(&format!("{}{}", SAVED_SEARCH_PREFIX, old));
+                        }
+                        self.expanded_folders
+                            .insert(format!("{}{}", SAVED_SEARCH_PREFIX, name));
+                        self.populate_notes_list(&sender);
+                        *self.is_populating_list.borrow_mut() = false;
+
+                        if original.is_none() {
+                            let message = format!(
+                                "{} {}",
+                                self.i18n.borrow().t("saved_search_created"),
+                                name
+                            );
+                            self.show_notification(&message);
+                        }
+                    }
+                    Err(e) => eprintln!("Error guardando búsqueda: {}", e),
+                }
+            }
+
+            AppMsg::LoadNoteFromFloatingSearch(name) => {
+                // Si estamos en modo búsqueda dentro de la nota, ir a siguiente coincidencia
+                if *self.floating_search_in_current_note.borrow() {
+                    // El Enter en modo búsqueda en nota va a la siguiente coincidencia
                     // (El Shift+Enter se maneja por separado con InNoteSearchPrev)
                     self.go_to_next_match();
                 } else {
@@ -9581,8 +10262,9 @@ Enjoy taking notes! 📓
                                 note.name()
                             );
 
-                            // Recargar contenido en el buffer
-                            self.buffer = crate::core::NoteBuffer::from_text(&content);
+                            // Recargar contenido en el buffer (los cambios de la IA se pueden deshacer)
+                            self.buffer.set_text(&content);
+                            self.buffer.mark_saved();
                             self.cursor_position = 0;
 
                             // Sincronizar a la vista para renderizar markdown correctamente
@@ -10976,7 +11658,16 @@ Para ello debes usar la herramienta semantic_search para encontrar las notas ade
                 }
 
                 if let Some(current) = &self.current_note {
-                    if current.path().to_str().unwrap_or("") == path {
+                    if current.path().to_str().unwrap_or("") != path {
+                        // Otra nota cambió: si la actual embebe Bases (```base, ```chart)
+                        // re-renderizar el preview para reflejar los nuevos resultados
+                        if *self.mode.borrow() == EditorMode::Normal
+                            && self.markdown_enabled
+                            && HtmlRenderer::has_live_blocks(&self.buffer.to_string())
+                        {
+                            self.sync_to_view_no_focus();
+                        }
+                    } else {
                         // Verificar si el contenido realmente cambió en disco
                         // Esto evita recargas innecesarias que resetean el cursor (ej: autoguardado)
                         if let Ok(disk_content) = std::fs::read_to_string(path) {
@@ -11271,7 +11962,161 @@ Para ello debes usar la herramienta semantic_search para encontrar las notas ade
             AppMsg::ShowNoteHistory(note_name) => {
                 self.context_menu.popdown();
                 self.context_menu.unparent();
-                self.show_note_history_dialog(&note_name, &sender);
+                match self.vault_git.clone() {
+                    Some(git) => self.load_git_history(note_name, git, &sender),
+                    None => self.show_note_history_dialog(&note_name, &sender),
+                }
+            }
+
+            AppMsg::ShowGitHistory { note_name, commits } => {
+                if let Some(git) = self.vault_git.clone() {
+                    self.show_git_history_dialog(&note_name, commits, &git, &sender);
+                }
+            }
+
+            AppMsg::RestoreGitVersion { note_name, commit } => {
+                let Some(git) = self.vault_git.clone() else {
+                    return;
+                };
+                let (name, version) = (note_name.clone(), commit.clone());
+                Self::spawn_git(
+                    git,
+                    &sender,
+                    move |git| git.restore_note(&name, &version),
+                    move |result| AppMsg::GitVersionRestored {
+                        note_name,
+                        commit,
+                        result: result.map_err(|e| e.to_string()),
+                    },
+                );
+            }
+
+            AppMsg::GitVersionRestored {
+                note_name,
+                commit,
+                result,
+            } => match result {
+                Ok(content) => {
+                    sender.input(AppMsg::ShowNotification(
+                        self.i18n
+                            .borrow()
+                            .t("git_restored")
+                            .replace("{}", &commit.short_id),
+                    ));
+                    // La restauración queda en el historial con el próximo commit
+                    self.schedule_git_commit();
+                    if self
+                        .current_note
+                        .as_ref()
+                        .is_some_and(|current| current.name() == note_name)
+                    {
+                        // El fichero ya tiene la versión restaurada: recargar la
+                        // nota guardaría antes el buffer abierto encima de ella
+                        self.buffer.load_saved(&content);
+                        self.cursor_position = self.cursor_position.min(self.buffer.len_chars());
+                        self.has_unsaved_changes = false;
+                        self.sync_to_view();
+                    }
+                }
+                Err(e) => sender.input(AppMsg::ShowNotification(format!(
+                    "{}: {}",
+                    self.i18n.borrow().t("ex_error"),
+                    e
+                ))),
+            },
+
+            AppMsg::ShowGitDiff { title, result } => match result {
+                Ok(diff) => {
+                    // Encima del historial, que es la ventana activa
+                    let parent = self
+                        .main_window
+                        .application()
+                        .and_then(|app| app.active_window())
+                        .unwrap_or_else(|| self.main_window.clone().upcast());
+                    Self::show_git_diff_preview(&parent, &title, &diff)
+                }
+                Err(e) => sender.input(AppMsg::ShowNotification(format!(
+                    "{}: {}",
+                    self.i18n.borrow().t("ex_error"),
+                    e
+                ))),
+            },
+
+            AppMsg::GitCommand(command) => {
+                self.run_git_command(command, &sender);
+            }
+
+            AppMsg::GitSyncFinished(result) => {
+                let i18n = self.i18n.borrow();
+                match result {
+                    Ok(PullOutcome::UpToDate) => {
+                        sender.input(AppMsg::ShowNotification(i18n.t("git_synced")));
+                    }
+                    Ok(PullOutcome::Updated(notes)) => {
+                        // El file watcher reindexa y recarga las notas que cambiaron
+                        sender.input(AppMsg::RefreshSidebar);
+                        sender.input(AppMsg::ShowNotification(
+                            i18n.t("git_pulled").replace("{}", &notes.len().to_string()),
+                        ));
+                    }
+                    Ok(PullOutcome::Conflicts(conflicts)) => {
+                        sender.input(AppMsg::ShowNotification(
+                            i18n.t("git_conflicts")
+                                .replace("{}", &conflicts.len().to_string()),
+                        ));
+                        sender.input(AppMsg::ShowGitConflicts(conflicts));
+                    }
+                    Err(e) => {
+                        sender.input(AppMsg::ShowNotification(format!(
+                            "{}: {}",
+                            i18n.t("ex_error"),
+                            e
+                        )));
+                    }
+                }
+            }
+
+            AppMsg::ExportFinished(result) => {
+                let i18n = self.i18n.borrow();
+                let message = match result {
+                    Ok(path) => i18n
+                        .t("note_exported")
+                        .replace("{}", &path.to_string_lossy()),
+                    Err(e) => format!("{}: {}", i18n.t("ex_error"), e),
+                };
+                sender.input(AppMsg::ShowNotification(message));
+            }
+
+            AppMsg::ShowGitConflicts(conflicts) => {
+                if let Some(git) = self.vault_git.clone() {
+                    self.show_git_conflicts_dialog(conflicts, &git, &sender);
+                }
+            }
+
+            AppMsg::GitConflictResolved { path, result } => {
+                // Sin diálogo abierto no hay nada que actualizar
+                let on_resolved = self.git_conflict_resolved.borrow().clone();
+                if let Some(on_resolved) = on_resolved {
+                    on_resolved(&path, result);
+                }
+            }
+
+            AppMsg::GitFinishMerge => {
+                let Some(git) = self.vault_git.clone() else {
+                    return;
+                };
+                let finished = self.i18n.borrow().t("git_merge_finished");
+                let error_prefix = self.i18n.borrow().t("ex_error");
+                let refresh = sender.clone();
+                Self::spawn_git(git, &sender, VaultGit::finish_merge, move |result| {
+                    AppMsg::ShowNotification(match result {
+                        Ok(_) => {
+                            refresh.input(AppMsg::RefreshSidebar);
+                            finished
+                        }
+                        Err(e) => format!("{}: {}", error_prefix, e),
+                    })
+                });
             }
 
             AppMsg::RestoreNoteVersion {
@@ -11500,10 +12345,7 @@ impl MainApp {
                 if uri.starts_with("http") {
                     let _ = open::that(uri);
                 } else {
-                    sender.input(AppMsg::LoadNote {
-                        name: uri.to_string(),
-                        highlight_text: None,
-                    });
+                    sender.input(AppMsg::OpenNoteLink(uri.to_string()));
                 }
                 glib::Propagation::Stop
             });
@@ -11828,6 +12670,20 @@ impl MainApp {
         let selection_bounds = self.text_buffer.selection_bounds();
         let has_selection = selection_bounds.is_some();
 
+        // Cada comando es un solo paso de undo; lo escrito en modo Insert se agrupa por rachas
+        let typing = *self.mode.borrow() == EditorMode::Insert
+            && matches!(
+                action,
+                EditorAction::InsertChar(_)
+                    | EditorAction::InsertNewline
+                    | EditorAction::DeleteCharBefore
+                    | EditorAction::DeleteCharAfter
+            );
+        let undo_group = !typing && !matches!(action, EditorAction::Undo | EditorAction::Redo);
+        if undo_group {
+            self.buffer.begin_undo_group();
+        }
+
         match action {
             EditorAction::ChangeMode(new_mode) => {
                 // Si cambiamos a ChatAI, usar el mensaje apropiado
@@ -11847,6 +12703,13 @@ impl MainApp {
                         self.cursor_position =
                             self.map_display_pos_to_buffer(&buffer_text, display_pos);
 
+                        // Guardar lo escrito para `.` y para la macro en grabación
+                        if let Some(start) = self.insert_start.take() {
+                            if let Some(inserted) = self.buffer.slice(start..self.cursor_position) {
+                                self.command_parser.record_inserted_text(&inserted);
+                            }
+                        }
+
                         sender.input(AppMsg::ParseRemindersInNote);
                     } else if old_mode == EditorMode::Normal && new_mode == EditorMode::Insert {
                         // Entrar a Insert: Mover cursor visual de GTK a la posición lógica actual
@@ -11857,6 +12720,7 @@ impl MainApp {
                         let mut iter = self.text_buffer.start_iter();
                         iter.set_offset(display_pos as i32);
                         self.text_buffer.place_cursor(&iter);
+                        self.insert_start = Some(self.cursor_position);
 
                         // Scroll to cursor to ensure visibility
                         let mark = self.text_buffer.create_mark(None, &iter, false);
@@ -11899,6 +12763,16 @@ impl MainApp {
                             let show_toolbar = self.notes_config.borrow().show_format_toolbar();
                             self.format_toolbar.set_visible(show_toolbar);
                         }
+                        EditorMode::Command => {
+                            // Con una selección activa, el comando empieza con su rango
+                            self.command_line = if self.text_buffer.has_selection() {
+                                "'<,'>".to_string()
+                            } else {
+                                String::new()
+                            };
+                            self.command_completions.clear();
+                            self.command_parser.command_history().reset();
+                        }
                         _ => {}
                     }
                 }
@@ -11921,6 +12795,32 @@ impl MainApp {
                 self.cursor_position += 1;
                 self.has_unsaved_changes = true;
             }
+            EditorAction::Tab { backwards } => {
+                if *self.mode.borrow() == EditorMode::Insert {
+                    let iter = self
+                        .text_buffer
+                        .iter_at_mark(&self.text_buffer.get_insert());
+                    self.cursor_position = iter.offset() as usize;
+                }
+
+                let (line, column) = self
+                    .buffer
+                    .char_to_line_col(self.cursor_position)
+                    .unwrap_or((0, 0));
+                if let Some(result) =
+                    move_to_cell(&self.buffer.to_string(), line, column, backwards)
+                {
+                    self.apply_table_edit(result);
+                } else if !backwards {
+                    // Fuera de una tabla, Tab escribe un tabulador
+                    if has_selection {
+                        self.delete_selection();
+                    }
+                    self.buffer.insert(self.cursor_position, "\t");
+                    self.cursor_position += 1;
+                    self.has_unsaved_changes = true;
+                }
+            }
             EditorAction::InsertNewline => {
                 // IMPORTANTE: Sincronizar posición del cursor desde GTK antes de operar
                 // El usuario puede haber movido el cursor con mouse/flechas en modo INSERT
@@ -12011,7 +12911,18 @@ impl MainApp {
                     // Borrar selección
                     self.delete_selection();
                 } else if self.cursor_position < self.buffer.len_chars() {
-                    // Borrar un carácter después del cursor
+                    // Borrar un carácter después del cursor (`x` lo guarda en los registros)
+                    if current_mode == EditorMode::Normal {
+                        if let Some(deleted) = self
+                            .buffer
+                            .slice(self.cursor_position..self.cursor_position + 1)
+                        {
+                            self.command_parser
+                                .registers_mut()
+                                .delete(None, &deleted, false);
+                            self.registers_dirty = true;
+                        }
+                    }
                     self.buffer
                         .delete(self.cursor_position..self.cursor_position + 1);
                     self.has_unsaved_changes = true;
@@ -12185,7 +13096,7 @@ impl MainApp {
                         "Undo ejecutado. Puede rehacer ahora: {}",
                         self.buffer.can_redo()
                     );
-                    self.has_unsaved_changes = true;
+                    self.has_unsaved_changes = !self.buffer.is_at_saved_state();
                 }
             }
             EditorAction::Redo => {
@@ -12195,7 +13106,7 @@ impl MainApp {
                 );
                 if self.buffer.redo() {
                     println!("Redo exitoso");
-                    self.has_unsaved_changes = true;
+                    self.has_unsaved_changes = !self.buffer.is_at_saved_state();
                 } else {
                     println!("Redo falló - no hay nada para rehacer");
                 }
@@ -12287,6 +13198,15 @@ impl MainApp {
             EditorAction::Save => {
                 sender.input(AppMsg::SaveCurrentNote);
             }
+            EditorAction::Search(text) => {
+                self.highlight_and_scroll_to_text(&text);
+            }
+            EditorAction::Ex(command) => {
+                self.run_ex_command(command, sender);
+            }
+            EditorAction::CommandError(message) => {
+                sender.input(AppMsg::ShowNotification(message));
+            }
             EditorAction::OpenSidebar => {
                 sender.input(AppMsg::OpenSidebarAndFocus);
             }
@@ -12313,94 +13233,799 @@ impl MainApp {
             EditorAction::InsertImage => {
                 sender.input(AppMsg::InsertImage);
             }
+            EditorAction::OpenBasesSidebar => {
+                sender.input(AppMsg::SwitchToPanel(SidebarPanel::Bases));
+            }
+            EditorAction::Motion { motion, count } => {
+                self.cursor_position =
+                    self.buffer
+                        .motion_target(self.cursor_position, motion, count);
+                if matches!(motion, Motion::SectionForward | Motion::SectionBackward)
+                    && let Some((line, _)) = self.buffer.char_to_line_col(self.cursor_position)
+                {
+                    self.reveal_heading(line);
+                }
+            }
+            EditorAction::Fold(command) => {
+                let outline = Outline::parse(&self.buffer.to_string());
+                if outline.is_empty() {
+                    let i18n = self.i18n.borrow();
+                    sender.input(AppMsg::ShowNotification(i18n.t("outline_no_headings")));
+                } else {
+                    let line = self
+                        .buffer
+                        .char_to_line_col(self.cursor_position)
+                        .map_or(0, |(line, _)| line);
+                    self.folds.apply(command, &outline, line);
+                    // Mantener a la vista el encabezado de la sección del cursor
+                    if command != FoldCommand::OpenAll
+                        && let Some(index) = outline.section_at(line)
+                    {
+                        self.pending_heading_scroll
+                            .set(Some(outline.headings()[index].line));
+                    }
+                }
+            }
+            EditorAction::EditSection { edit, count } => {
+                let text = self.buffer.to_string();
+                let line = self
+                    .buffer
+                    .char_to_line_col(self.cursor_position)
+                    .map_or(0, |(line, _)| line);
+                if let Some(result) = edit_section(&text, line, edit, count) {
+                    self.buffer.set_text(&result.text);
+                    self.cursor_position = self
+                        .buffer
+                        .line_col_to_char(result.heading_line, 0)
+                        .unwrap_or(0);
+                    // Los pliegues se identifican por línea: tras mover secciones se abren
+                    self.folds = Folds::new();
+                    self.pending_heading_scroll.set(Some(result.heading_line));
+                    self.has_unsaved_changes = true;
+                }
+            }
+            EditorAction::DeleteLine => {
+                self.apply_vim_operator(
+                    Operator::Delete,
+                    &OperatorTarget::Line,
+                    1,
+                    None,
+                    None,
+                    sender,
+                );
+            }
+            EditorAction::Operate {
+                operator,
+                target,
+                count,
+            } => {
+                self.apply_vim_operator(operator, &target, count, None, None, sender);
+            }
+            EditorAction::RepeatChange { action, inserted } => {
+                let (register, action) = match *action {
+                    EditorAction::UseRegister { register, action } => (Some(register), *action),
+                    action => (None, action),
+                };
+                if let EditorAction::Operate {
+                    operator,
+                    target,
+                    count,
+                } = action
+                {
+                    self.apply_vim_operator(
+                        operator,
+                        &target,
+                        count,
+                        register,
+                        Some(&inserted),
+                        sender,
+                    );
+                }
+            }
+            EditorAction::Put { before, count } => {
+                self.put_register(UNNAMED_REGISTER, before, count, sender);
+            }
+            EditorAction::UseRegister { register, action } => match *action {
+                EditorAction::Operate {
+                    operator,
+                    target,
+                    count,
+                } => {
+                    self.apply_vim_operator(operator, &target, count, Some(register), None, sender);
+                }
+                EditorAction::Put { before, count } => {
+                    self.put_register(register, before, count, sender);
+                }
+                _ => {}
+            },
+            EditorAction::InsertText(text) => {
+                self.buffer.insert(self.cursor_position, &text);
+                self.cursor_position += text.chars().count();
+                self.has_unsaved_changes = true;
+            }
+            EditorAction::Sequence(actions) => {
+                for action in actions {
+                    self.execute_action(action, sender);
+                }
+            }
+            EditorAction::StartMacro(_) => {}
+            EditorAction::StopMacro(register) => {
+                self.registers_dirty = true;
+                self.persist_registers();
+                let i18n = self.i18n.borrow();
+                sender.input(AppMsg::ShowNotification(
+                    i18n.t("vim_macro_recorded")
+                        .replace("{}", &register.to_string()),
+                ));
+            }
             _ => {
                 println!("Acción no implementada: {:?}", action);
             }
         }
 
+        if undo_group {
+            self.buffer.end_undo_group();
+        }
+
         // Sincronizar el buffer con GTK TextView
         self.sync_to_view();
 
         // Actualizar barra de estado
         self.update_status_bar(sender);
-    }
 
-    /// Actualiza el estado de un TODO en el buffer interno
-    fn update_todo_in_buffer(&mut self, line_pos: usize, new_state: bool) {
-        let text = self.buffer.to_string();
-        let chars: Vec<char> = text.chars().collect();
+        // Resaltar la sección actual en el panel de outline
+        if self.sidebar_visible && self.active_panel == SidebarPanel::Outline {
+            self.refresh_outline_panel();
+        }
+    }
 
-        // Verificar que la posición es válida
-        if line_pos >= chars.len() {
-            return;
+    /// Abrir los pliegues que ocultan el encabezado de `line` y llevar el preview hasta él
+    fn reveal_heading(&mut self, line: usize) {
+        let outline = Outline::parse(&self.buffer.to_string());
+        self.folds.reveal(&outline, line);
+        if outline.heading_at(line).is_some() {
+            self.pending_heading_scroll.set(Some(line));
         }
+    }
 
-        // Verificar que hay un TODO en esa posición
-        if line_pos + 4 >= chars.len() {
-            return;
+    /// Reconstruir el panel de outline con los encabezados de la nota actual
+    fn refresh_outline_panel(&self) {
+        while let Some(child) = self.outline_list.first_child() {
+            self.outline_list.remove(&child);
         }
 
-        if chars[line_pos] == '-'
-            && chars[line_pos + 1] == ' '
-            && chars[line_pos + 2] == '['
-            && chars[line_pos + 4] == ']'
-        {
-            let current_char = chars[line_pos + 3];
-            let should_be_checked = new_state;
-            let is_currently_checked = current_char == 'x' || current_char == 'X';
+        let outline = Outline::parse(&self.buffer.to_string());
+        let current_section = self
+            .buffer
+            .char_to_line_col(self.cursor_position)
+            .and_then(|(line, _)| outline.section_at(line));
 
-            // Solo actualizar si el estado cambió
-            if should_be_checked != is_currently_checked {
-                let new_char = if should_be_checked { "x" } else { " " };
+        for (index, heading) in outline.headings().iter().enumerate() {
+            let row = gtk::ListBoxRow::new();
+            let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+            hbox.set_margin_top(4);
+            hbox.set_margin_bottom(4);
+            hbox.set_margin_end(8);
+            // Sangría según el nivel del encabezado
+            hbox.set_margin_start(8 + 12 * (heading.level as i32 - 1));
+
+            if self.folds.is_folded(heading.line) {
+                let icon = gtk::Image::from_icon_name("pan-end-symbolic");
+                icon.add_css_class("dim-label");
+                hbox.append(&icon);
+            }
 
-                // Reemplazar el carácter en la posición correcta
-                self.buffer.delete(line_pos + 3..line_pos + 4);
-                self.buffer.insert(line_pos + 3, new_char);
+            let label = gtk::Label::new(Some(&heading.title));
+            label.set_xalign(0.0);
+            label.set_hexpand(true);
+            label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+            if heading.level > 2 {
+                label.add_css_class("dim-label");
+            }
+            hbox.append(&label);
 
-                // Marcar como no guardado
-                self.has_unsaved_changes = true;
+            row.set_child(Some(&hbox));
+            unsafe {
+                row.set_data("heading_line", heading.line);
             }
-        }
-    }
+            self.outline_list.append(&row);
 
-    /// Toggle TODO checkbox en una línea específica (desde WebView)
-    /// El `checkbox_num` es el número secuencial del checkbox (1-indexado), no el número de línea
-    fn toggle_todo_at_line(&mut self, checkbox_num: usize, checked: bool) {
-        let text = self.buffer.to_string();
-        let lines: Vec<&str> = text.lines().collect();
+            if current_section == Some(index) {
+                self.outline_list.select_row(Some(&row));
+            }
+        }
 
-        if checkbox_num == 0 {
-            return;
+        // Si la nota no tiene encabezados, mostrar mensaje
+        if outline.is_empty() {
+            let row = gtk::ListBoxRow::new();
+            row.set_selectable(false);
+            row.set_activatable(false);
+            let label = gtk::Label::new(Some(&self.i18n.borrow().t("outline_no_headings")));
+            label.add_css_class("dim-label");
+            label.set_wrap(true);
+            label.set_margin_all(16);
+            row.set_child(Some(&label));
+            self.outline_list.append(&row);
         }
+    }
 
-        // Buscar el N-ésimo checkbox en el documento
-        let mut checkbox_count = 0;
-        let mut char_offset = 0;
+    /// Aplicar un operador vim (d, c, y, >, <) en la posición del cursor
+    ///
+    /// `inserted` se usa al repetir un cambio con `.`: en lugar de entrar en modo
+    /// Insert se escribe directamente el texto de la última vez.
+    fn apply_vim_operator(
+        &mut self,
+        operator: Operator,
+        target: &OperatorTarget,
+        count: usize,
+        register: Option<char>,
+        inserted: Option<&str>,
+        sender: &ComponentSender<Self>,
+    ) {
+        let Some(outcome) =
+            self.buffer
+                .apply_operator(self.cursor_position, operator, target, count)
+        else {
+            return;
+        };
+        self.cursor_position = outcome.cursor;
 
-        for line in lines.iter() {
-            // Buscar patrones de TODO en esta línea
-            let todo_patterns = ["- [ ]", "- [x]", "- [X]"];
-            for pattern in &todo_patterns {
-                if let Some(todo_pos) = line.find(pattern) {
-                    checkbox_count += 1;
-                    if checkbox_count == checkbox_num {
-                        let buffer_pos = char_offset + todo_pos;
-                        self.update_todo_in_buffer(buffer_pos, checked);
-                        return;
+        match operator {
+            Operator::Yank => {
+                self.store_in_register(register, &outcome.text, outcome.linewise, true);
+            }
+            Operator::Change => {
+                self.store_in_register(register, &outcome.text, outcome.linewise, false);
+                self.has_unsaved_changes = true;
+                match inserted {
+                    Some(text) => {
+                        self.buffer.insert(self.cursor_position, text);
+                        self.cursor_position += text.chars().count();
+                    }
+                    None => {
+                        sender.input(AppMsg::ProcessAction(EditorAction::ChangeMode(
+                            EditorMode::Insert,
+                        )));
                     }
-                    break; // Solo un checkbox por línea
                 }
             }
-            char_offset += line.chars().count() + 1; // +1 for newline
+            Operator::Delete => {
+                self.store_in_register(register, &outcome.text, outcome.linewise, false);
+                self.has_unsaved_changes = true;
+            }
+            Operator::Indent | Operator::Outdent => {
+                self.has_unsaved_changes = true;
+            }
         }
     }
 
-    /// Renderiza el contenido actual como HTML y lo carga en el WebView de preview
-    fn render_preview_html(&self) {
-        let buffer_text = self.buffer.to_string();
-
-        // Determinar el tema basado en la preferencia
-        let preview_theme = match self.theme {
+    /// Guardar texto copiado o borrado en un registro vim
+    ///
+    /// `"+` y `"*` van al portapapeles del sistema; las copias sin registro
+    /// explícito también se copian al portapapeles, como antes de existir los registros.
+    fn store_in_register(
+        &mut self,
+        register: Option<char>,
+        text: &str,
+        linewise: bool,
+        yank: bool,
+    ) {
+        let to_clipboard = match register {
+            Some(name) => Registers::is_clipboard(name),
+            None => yank,
+        };
+        if to_clipboard {
+            if let Some(display) = gtk::gdk::Display::default() {
+                display.clipboard().set_text(text);
+            }
+        }
+        if register.is_some_and(Registers::is_clipboard) {
+            return;
+        }
+
+        let registers = self.command_parser.registers_mut();
+        if yank {
+            registers.yank(register, text, linewise);
+        } else {
+            registers.delete(register, text, linewise);
+        }
+        self.registers_dirty = true;
+    }
+
+    /// Pegar un registro vim (`p`/`P`) en la posición del cursor
+    fn put_register(
+        &mut self,
+        register: char,
+        before: bool,
+        count: usize,
+        sender: &ComponentSender<Self>,
+    ) {
+        // El portapapeles del sistema se lee de forma asíncrona con el pegado normal
+        if Registers::is_clipboard(register) {
+            self.execute_action(EditorAction::Paste, sender);
+            return;
+        }
+        let Some(content) = self.command_parser.registers().get(register).cloned() else {
+            return;
+        };
+        self.cursor_position = self.buffer.put(
+            self.cursor_position,
+            &content.text,
+            content.linewise,
+            before,
+            count,
+        );
+        self.has_unsaved_changes = true;
+    }
+
+    /// Editar la línea de comando ex tecla a tecla (Enter ejecuta, Tab completa, ↑/↓ historial)
+    fn handle_command_line_key(
+        &mut self,
+        key: &str,
+        modifiers: KeyModifiers,
+        sender: &ComponentSender<Self>,
+    ) -> EditorAction {
+        if key != "Tab" && key != "ISO_Left_Tab" {
+            self.command_completions.clear();
+        }
+
+        match key {
+            "Escape" => return EditorAction::ChangeMode(EditorMode::Normal),
+            "Return" | "KP_Enter" => {
+                let line = std::mem::take(&mut self.command_line);
+                let action = self.command_parser.parse_command_mode(&line);
+                return EditorAction::Sequence(vec![
+                    EditorAction::ChangeMode(EditorMode::Normal),
+                    action,
+                ]);
+            }
+            "BackSpace" => {
+                if self.command_line.pop().is_none() {
+                    return EditorAction::ChangeMode(EditorMode::Normal);
+                }
+                self.command_parser.command_history().reset();
+            }
+            "Up" => {
+                if let Some(previous) = self
+                    .command_parser
+                    .command_history()
+                    .older(&self.command_line)
+                {
+                    self.command_line = previous;
+                }
+            }
+            "Down" => {
+                if let Some(next) = self.command_parser.command_history().newer() {
+                    self.command_line = next;
+                }
+            }
+            "Tab" | "ISO_Left_Tab" => self.complete_command_line(key == "ISO_Left_Tab"),
+            // Ctrl+U borra la línea, como en vim
+            "u" if modifiers.ctrl => self.command_line.clear(),
+            _ if modifiers.ctrl => {}
+            _ => {
+                if let Some(ch) = key_to_char(key) {
+                    self.command_line.push(ch);
+                    self.command_parser.command_history().reset();
+                }
+            }
+        }
+
+        self.update_status_bar(sender);
+        EditorAction::None
+    }
+
+    /// Tab: completar comandos, notas (`:e`), carpetas (`:move`) y bases (`:base`)
+    fn complete_command_line(&mut self, backwards: bool) {
+        if self.command_completions.is_empty() {
+            let candidates: Vec<String> = match completion_context(&self.command_line) {
+                Some((CompletionKind::Note, _)) => self
+                    .notes_dir
+                    .list_notes()
+                    .map(|notes| notes.iter().map(|n| n.name().to_string()).collect())
+                    .unwrap_or_default(),
+                Some((CompletionKind::Folder, _)) => {
+                    self.notes_db.get_all_folders().unwrap_or_default()
+                }
+                Some((CompletionKind::Base, _)) => self
+                    .notes_db
+                    .list_bases()
+                    .map(|bases| bases.into_iter().map(|(_, name, _, _)| name).collect())
+                    .unwrap_or_default(),
+                Some((CompletionKind::Template, _)) => self
+                    .notes_config
+                    .borrow()
+                    .template_library(self.notes_dir.root())
+                    .list(),
+                _ => Vec::new(),
+            };
+            let mut completions = complete_ex_command(&self.command_line, &candidates);
+            if completions.is_empty() {
+                return;
+            }
+            // El último elemento permite volver a lo que se había escrito
+            completions.push(self.command_line.clone());
+            self.command_completions = completions;
+            self.command_completion_index = 0;
+        } else {
+            let len = self.command_completions.len();
+            self.command_completion_index = if backwards {
+                (self.command_completion_index + len - 1) % len
+            } else {
+                (self.command_completion_index + 1) % len
+            };
+        }
+        self.command_line = self.command_completions[self.command_completion_index].clone();
+    }
+
+    /// Líneas de la selección actual del editor (para `'<,'>`)
+    fn visual_line_range(&self) -> Option<(usize, usize)> {
+        let (start, end) = self.text_buffer.selection_bounds()?;
+        let text = self.buffer.to_string();
+        let start = self.map_display_pos_to_buffer(&text, start.offset() as usize);
+        let end = self.map_display_pos_to_buffer(&text, end.offset() as usize);
+        let (first, _) = self.buffer.char_to_line_col(start)?;
+        let (last, _) = self
+            .buffer
+            .char_to_line_col(end.saturating_sub(1).max(start))?;
+        Some((first, last))
+    }
+
+    /// Ejecutar un comando ex ya interpretado
+    fn run_ex_command(&mut self, command: ExCommand, sender: &ComponentSender<Self>) {
+        let ctx = ExContext {
+            cursor_line: self
+                .buffer
+                .char_to_line_col(self.cursor_position)
+                .map(|(line, _)| line)
+                .unwrap_or(0),
+            visual: self.visual_line_range(),
+        };
+        let error_prefix = self.i18n.borrow().t("ex_error");
+
+        if command.edits_text() {
+            match command.apply_to_text(&self.buffer.to_string(), &ctx) {
+                Ok(Some(edit)) => {
+                    let len = self.buffer.len_chars();
+                    self.buffer.replace(0..len, &edit.text);
+                    self.cursor_position = self
+                        .buffer
+                        .line_col_to_char(edit.cursor_line, 0)
+                        .unwrap_or(0);
+                    self.has_unsaved_changes = true;
+                    sender.input(AppMsg::ShowNotification(
+                        self.i18n
+                            .borrow()
+                            .t("ex_changes")
+                            .replace("{}", &edit.changes.to_string()),
+                    ));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    sender.input(AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)));
+                }
+            }
+            return;
+        }
+
+        match command.kind {
+            ExCommandKind::GotoLine => {
+                let range = command.range.unwrap_or(LineRange::All);
+                match range.resolve(&ctx, self.buffer.len_lines()) {
+                    Ok((_, line)) => {
+                        self.cursor_position = self.buffer.line_col_to_char(line, 0).unwrap_or(0);
+                    }
+                    Err(e) => {
+                        sender.input(AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)));
+                    }
+                }
+            }
+            ExCommandKind::Search(text) => self.highlight_and_scroll_to_text(&text),
+            ExCommandKind::NoHighlight => self.highlight_and_scroll_to_text(""),
+            ExCommandKind::Edit(name) => match self.notes_dir.find_note(&name) {
+                Ok(Some(note)) => sender.input(AppMsg::LoadNote {
+                    name: note.name().to_string(),
+                    highlight_text: None,
+                }),
+                _ => sender.input(AppMsg::ShowNotification(
+                    self.i18n
+                        .borrow()
+                        .t("ex_note_not_found")
+                        .replace("{}", &name),
+                )),
+            },
+            ExCommandKind::Move(folder) => {
+                if let Some(note) = &self.current_note {
+                    let folder = folder.trim().trim_matches('/');
+                    sender.input(AppMsg::MoveNoteToFolder {
+                        note_name: note.name().to_string(),
+                        folder_name: (!folder.is_empty()).then(|| folder.to_string()),
+                    });
+                }
+            }
+            ExCommandKind::Tag { add, tag } => {
+                let text = self.buffer.to_string();
+                let updated = if add {
+                    add_tag_to_content(&text, &tag)
+                } else {
+                    remove_tag_from_content(&text, &tag)
+                };
+                match updated {
+                    Ok(updated) if updated != text => {
+                        let len = self.buffer.len_chars();
+                        self.buffer.replace(0..len, &updated);
+                        self.cursor_position = self.cursor_position.min(self.buffer.len_chars());
+                        self.has_unsaved_changes = true;
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        sender.input(AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)));
+                    }
+                }
+            }
+            ExCommandKind::Base(name) => match self.notes_db.get_base_by_name(&name) {
+                Ok(Some((id, ..))) => {
+                    sender.input(AppMsg::SwitchToPanel(SidebarPanel::Bases));
+                    sender.input(AppMsg::LoadBase(id.to_string()));
+                }
+                _ => sender.input(AppMsg::ShowNotification(
+                    self.i18n
+                        .borrow()
+                        .t("ex_base_not_found")
+                        .replace("{}", &name),
+                )),
+            },
+            ExCommandKind::Mcp { tool, args } => {
+                let call = serde_json::from_value::<MCPToolCall>(serde_json::json!({
+                    "tool": tool,
+                    "args": args,
+                }));
+                let message = match call {
+                    Ok(call) => match self.mcp_executor.borrow().execute(call) {
+                        Ok(result) if result.success => {
+                            sender.input(AppMsg::RefreshSidebar);
+                            let data = result.data.map(|data| data.to_string()).unwrap_or_default();
+                            let data: String = data.chars().take(200).collect();
+                            format!("✓ {} {}", tool, data)
+                        }
+                        Ok(result) => {
+                            format!("{}: {}", error_prefix, result.error.unwrap_or_default())
+                        }
+                        Err(e) => format!("{}: {}", error_prefix, e),
+                    },
+                    Err(e) => format!("{}: {} ({})", error_prefix, tool, e),
+                };
+                sender.input(AppMsg::ShowNotification(message));
+            }
+            ExCommandKind::Template(name) => {
+                self.start_template(&name, TemplateTarget::Cursor, sender);
+            }
+            ExCommandKind::CopyLink => {
+                let Some(note_name) = self.current_note.as_ref().map(|n| n.name().to_string())
+                else {
+                    return;
+                };
+                let Some((target, updated)) =
+                    reference_at(&self.buffer.to_string(), ctx.cursor_line)
+                else {
+                    sender.input(AppMsg::ShowNotification(
+                        self.i18n.borrow().t("copylink_empty_line"),
+                    ));
+                    return;
+                };
+                // La línea no tenía ID de bloque: se le añade uno al final
+                if let Some(updated) = updated {
+                    let len = self.buffer.len_chars();
+                    self.buffer.replace(0..len, &updated);
+                    self.has_unsaved_changes = true;
+                }
+                let link = WikiLink {
+                    note: note_name,
+                    target: Some(target),
+                    alias: None,
+                    embed: false,
+                }
+                .to_markdown();
+                if let Some(display) = gtk::gdk::Display::default() {
+                    display.clipboard().set_text(&link);
+                }
+                sender.input(AppMsg::ShowNotification(
+                    self.i18n.borrow().t("link_copied").replace("{}", &link),
+                ));
+            }
+            ExCommandKind::Export { format, path } => {
+                let Some(note_name) = self.current_note.as_ref().map(|n| n.name().to_string())
+                else {
+                    return;
+                };
+                // Se exporta lo que hay en disco: guardar antes
+                if self.has_unsaved_changes {
+                    self.save_current_note(false);
+                }
+                let job = self.mcp_executor.borrow().prepare_note_export(
+                    &note_name,
+                    format.extension(),
+                    path.as_deref(),
+                    &Default::default(),
+                );
+                match job {
+                    // Renderizar (y sobre todo generar el PDF) puede tardar: fuera del hilo de GTK
+                    Ok(job) => {
+                        let sender = sender.clone();
+                        std::thread::spawn(move || {
+                            let result = job.run().map_err(|e| e.to_string());
+                            sender.input(AppMsg::ExportFinished(result));
+                        });
+                    }
+                    Err(e) => {
+                        sender.input(AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)));
+                    }
+                }
+            }
+            ExCommandKind::Git(command) => self.run_git_command(command, sender),
+            ExCommandKind::Periodic { period, target } => {
+                let note = {
+                    let config = self.notes_config.borrow();
+                    let periodic = &config.periodic_notes;
+                    match target {
+                        PeriodicTarget::Offset(offset) => {
+                            periodic.shifted(&periodic.today(period), offset)
+                        }
+                        PeriodicTarget::Date(date) => Ok(periodic.note(period, date)),
+                    }
+                };
+                match note {
+                    Ok(note) => self.open_periodic_note(&note, sender),
+                    Err(e) => {
+                        sender.input(AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)));
+                    }
+                }
+            }
+            kind @ (ExCommandKind::PeriodicStep(_) | ExCommandKind::PeriodicParent) => {
+                let target = {
+                    let config = self.notes_config.borrow();
+                    let periodic = &config.periodic_notes;
+                    self.current_note
+                        .as_ref()
+                        .and_then(|note| periodic.note_for_name(note.name()))
+                        .and_then(|current| match kind {
+                            ExCommandKind::PeriodicStep(offset) => {
+                                Some(periodic.shifted(&current, offset))
+                            }
+                            _ => periodic.parent(&current).map(Ok),
+                        })
+                };
+                match target {
+                    Some(Ok(note)) => self.open_periodic_note(&note, sender),
+                    Some(Err(e)) => {
+                        sender.input(AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)));
+                    }
+                    None => sender.input(AppMsg::ShowNotification(
+                        self.i18n.borrow().t("periodic_not_open"),
+                    )),
+                }
+            }
+            ExCommandKind::Table(edit) => {
+                let (line, column) = self
+                    .buffer
+                    .char_to_line_col(self.cursor_position)
+                    .unwrap_or((0, 0));
+                match edit_table(&self.buffer.to_string(), line, column, edit) {
+                    Ok(result) => self.apply_table_edit(result),
+                    Err(e) => {
+                        sender.input(AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sustituir el texto tras editar una tabla y dejar el cursor en la celda resultante
+    fn apply_table_edit(&mut self, result: TableEditResult) {
+        if result.text != self.buffer.to_string() {
+            self.buffer.set_text(&result.text);
+            self.has_unsaved_changes = true;
+        }
+        self.cursor_position = self
+            .buffer
+            .line_col_to_char(result.line, result.column)
+            .unwrap_or(self.cursor_position);
+    }
+
+    /// Guardar los registros vim en la configuración para la próxima sesión
+    ///
+    /// Las copias y borrados solo marcan los registros como cambiados; se guardan
+    /// al guardar o cambiar de nota y al salir, no en cada pulsación.
+    fn persist_registers(&mut self) {
+        if !self.registers_dirty {
+            return;
+        }
+        self.registers_dirty = false;
+        self.notes_config
+            .borrow_mut()
+            .set_vim_registers(self.command_parser.registers().persisted());
+        if let Err(e) = self.notes_config.borrow().save(NotesConfig::default_path()) {
+            eprintln!("Error guardando registros vim: {}", e);
+        }
+    }
+
+    /// Actualiza el estado de un TODO en el buffer interno
+    fn update_todo_in_buffer(&mut self, line_pos: usize, new_state: bool) {
+        let text = self.buffer.to_string();
+        let chars: Vec<char> = text.chars().collect();
+
+        // Verificar que la posición es válida
+        if line_pos >= chars.len() {
+            return;
+        }
+
+        // Verificar que hay un TODO en esa posición
+        if line_pos + 4 >= chars.len() {
+            return;
+        }
+
+        if chars[line_pos] == '-'
+            && chars[line_pos + 1] == ' '
+            && chars[line_pos + 2] == '['
+            && chars[line_pos + 4] == ']'
+        {
+            let current_char = chars[line_pos + 3];
+            let should_be_checked = new_state;
+            let is_currently_checked = current_char == 'x' || current_char == 'X';
+
+            // Solo actualizar si el estado cambió
+            if should_be_checked != is_currently_checked {
+                let new_char = if should_be_checked { "x" } else { " " };
+
+                // Reemplazar el carácter en la posición correcta
+                self.buffer.delete(line_pos + 3..line_pos + 4);
+                self.buffer.insert(line_pos + 3, new_char);
+
+                // Marcar como no guardado
+                self.has_unsaved_changes = true;
+            }
+        }
+    }
+
+    /// Toggle TODO checkbox en una línea específica (desde WebView)
+    /// El `checkbox_num` es el número secuencial del checkbox (1-indexado), no el número de línea
+    fn toggle_todo_at_line(&mut self, checkbox_num: usize, checked: bool) {
+        let text = self.buffer.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+
+        if checkbox_num == 0 {
+            return;
+        }
+
+        // Buscar el N-ésimo checkbox en el documento
+        let mut checkbox_count = 0;
+        let mut char_offset = 0;
+
+        for line in lines.iter() {
+            // Buscar patrones de TODO en esta línea
+            let todo_patterns = ["- [ ]", "- [x]", "- [X]"];
+            for pattern in &todo_patterns {
+                if let Some(todo_pos) = line.find(pattern) {
+                    checkbox_count += 1;
+                    if checkbox_count == checkbox_num {
+                        let buffer_pos = char_offset + todo_pos;
+                        self.update_todo_in_buffer(buffer_pos, checked);
+                        return;
+                    }
+                    break; // Solo un checkbox por línea
+                }
+            }
+            char_offset += line.chars().count() + 1; // +1 for newline
+        }
+    }
+
+    /// Renderiza el contenido actual como HTML y lo carga en el WebView de preview
+    fn render_preview_html(&self) {
+        let buffer_text = self.buffer.to_string();
+
+        // Determinar el tema basado en la preferencia
+        let preview_theme = match self.theme {
             ThemePreference::Light => PreviewTheme::Light,
             ThemePreference::Dark | ThemePreference::FollowSystem => PreviewTheme::Dark,
         };
@@ -12421,7 +14046,21 @@ impl MainApp {
             border: gtk_colors.border,
         };
 
-        let renderer = HtmlRenderer::with_colors(preview_theme, notes_base_path, preview_colors);
+        let mut renderer =
+            HtmlRenderer::with_colors(preview_theme, notes_base_path, preview_colors);
+        // Solo abrir conexión si algún bloque consulta Bases (aquí o en una transclusión);
+        // sin ella esos bloques muestran el error en lugar de romper el preview
+        if HtmlRenderer::has_live_blocks(&buffer_text) || buffer_text.contains("![[") {
+            match self.notes_db.try_clone_connection() {
+                Ok(db) => renderer.set_database(db),
+                Err(e) => eprintln!("⚠️ Preview sin acceso a la base de datos: {}", e),
+            }
+        }
+        renderer.set_outline(PreviewOutline {
+            folded: self.folds.lines().collect(),
+            scroll_to: self.pending_heading_scroll.take(),
+            scroll_to_block: self.pending_block_scroll.take(),
+        });
         let html = renderer.render(&buffer_text);
 
         // Cargar en el WebView
@@ -12437,6 +14076,62 @@ impl MainApp {
         self.sync_to_view_internal(false);
     }
 
+    /// Idioma del corrector: `lang` del frontmatter o el idioma de la interfaz
+    fn spell_language(&self) -> String {
+        crate::core::spellcheck::note_language(&self.buffer.to_string())
+            .unwrap_or_else(|| self.i18n.borrow().current_language().code().to_string())
+    }
+
+    /// Revisar la ortografía tras una pausa al escribir
+    fn schedule_spell_check(&self, sender: &ComponentSender<Self>) {
+        if let Some(source_id) = self.spell_check_timeout.borrow_mut().take() {
+            source_id.remove();
+        }
+        let timeout_id = self.spell_check_timeout.clone();
+        let sender = sender.clone();
+        let source_id =
+            gtk::glib::timeout_add_local_once(std::time::Duration::from_millis(400), move || {
+                *timeout_id.borrow_mut() = None;
+                sender.input(AppMsg::RunSpellCheck);
+            });
+        *self.spell_check_timeout.borrow_mut() = Some(source_id);
+    }
+
+    /// Subrayar las palabras mal escritas del TextView
+    fn refresh_spelling(&self) {
+        let buffer = &self.text_buffer;
+        let tag_table = buffer.tag_table();
+        let tag = if let Some(existing_tag) = tag_table.lookup("misspelled") {
+            existing_tag
+        } else {
+            let new_tag = gtk::TextTag::new(Some("misspelled"));
+            new_tag.set_underline(gtk::pango::Underline::Error);
+            tag_table.add(&new_tag);
+            new_tag
+        };
+        buffer.remove_tag_by_name("misspelled", &buffer.start_iter(), &buffer.end_iter());
+
+        // En el preview (WebView) no hay nada que subrayar
+        if self.editor_stack.visible_child_name().as_deref() != Some("editor") {
+            self.misspellings.borrow_mut().clear();
+            return;
+        }
+
+        let language = self.spell_language();
+        let misspellings = self
+            .spell_checker
+            .borrow_mut()
+            .misspellings(&self.buffer.to_string(), &language);
+        for misspelling in &misspellings {
+            let mut start_iter = buffer.start_iter();
+            let mut end_iter = buffer.start_iter();
+            start_iter.set_offset(misspelling.range.start as i32);
+            end_iter.set_offset(misspelling.range.end as i32);
+            buffer.apply_tag(&tag, &start_iter, &end_iter);
+        }
+        *self.misspellings.borrow_mut() = misspellings;
+    }
+
     fn sync_to_view_internal(&self, grab_focus: bool) {
         // Activar flag para evitar que los handlers GTK nos sincronicen de vuelta
         *self.is_syncing_to_gtk.borrow_mut() = true;
@@ -12553,6 +14248,9 @@ impl MainApp {
 
             self.text_buffer.end_user_action();
 
+            // El contenido se ha reemplazado: volver a subrayar las faltas
+            self.refresh_spelling();
+
             // Hacer scroll para mantener el cursor visible
             let text_view = self.text_view.clone();
             for delay_ms in [10, 50, 150] {
@@ -13523,30 +15221,6 @@ impl MainApp {
         (result, display_char_count)
     }
 
-    /// Genera un ID de anchor para un heading al estilo markdown
-    /// Convierte "Conexión al MCP Server" → "conexión-al-mcp-server"
-    fn generate_heading_id(text: &str) -> String {
-        text.to_lowercase()
-            .chars()
-            .map(|c| {
-                if c.is_alphanumeric() {
-                    c
-                } else if c.is_whitespace() || c == '-' {
-                    '-'
-                } else {
-                    // Eliminar caracteres especiales
-                    '\0'
-                }
-            })
-            .filter(|&c| c != '\0')
-            .collect::<String>()
-            // Eliminar guiones duplicados
-            .split('-')
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<_>>()
-            .join("-")
-    }
-
     /// Aplica estilos markdown al texto limpio (sin símbolos)
     fn apply_markdown_styles_to_clean_text(&self, clean_text: &str) {
         let start = self.text_buffer.start_iter();
@@ -13622,7 +15296,7 @@ impl MainApp {
             } else if original_line.starts_with("### ") {
                 // Recolectar heading nivel 3
                 let heading_text = original_line[4..].trim();
-                let heading_id = Self::generate_heading_id(heading_text);
+                let heading_id = heading_id(heading_text);
                 self.heading_anchors.borrow_mut().push(HeadingAnchor {
                     id: heading_id,
                     line_offset: line_start.offset(),
@@ -13632,7 +15306,7 @@ impl MainApp {
             } else if original_line.starts_with("## ") {
                 // Recolectar heading nivel 2
                 let heading_text = original_line[3..].trim();
-                let heading_id = Self::generate_heading_id(heading_text);
+                let heading_id = heading_id(heading_text);
                 self.heading_anchors.borrow_mut().push(HeadingAnchor {
                     id: heading_id,
                     line_offset: line_start.offset(),
@@ -13642,7 +15316,7 @@ impl MainApp {
             } else if original_line.starts_with("# ") {
                 // Recolectar heading nivel 1
                 let heading_text = original_line[2..].trim();
-                let heading_id = Self::generate_heading_id(heading_text);
+                let heading_id = heading_id(heading_text);
                 self.heading_anchors.borrow_mut().push(HeadingAnchor {
                     id: heading_id,
                     line_offset: line_start.offset(),
@@ -15142,6 +16816,14 @@ impl MainApp {
         let word_count = self.buffer.to_string().split_whitespace().count();
         let current_mode = *self.mode.borrow();
 
+        // En modo Comando la etiqueta muestra la línea que se está escribiendo
+        if current_mode == EditorMode::Command {
+            self.mode_label.set_markup(&format!(
+                "<b>:</b>{}",
+                gtk::glib::markup_escape_text(&self.command_line)
+            ));
+        }
+
         // Actualizar etiqueta de modo
         let mode_text = match current_mode {
             EditorMode::Normal => "<b>NORMAL</b>",
@@ -15150,7 +16832,15 @@ impl MainApp {
             EditorMode::Visual => "<b>VISUAL</b>",
             EditorMode::ChatAI => "<b>CHAT AI</b>",
         };
-        self.mode_label.set_markup(mode_text);
+        match self.command_parser.recording_macro() {
+            _ if current_mode == EditorMode::Command => {}
+            Some(register) => self.mode_label.set_markup(&format!(
+                "{} <small>{}</small>",
+                mode_text,
+                i18n.t("vim_recording").replace("{}", &register.to_string())
+            )),
+            None => self.mode_label.set_markup(mode_text),
+        }
 
         // Actualizar estadísticas con indicador de cambios sin guardar
         let unsaved_indicator = if self.has_unsaved_changes { " •" } else { "" };
@@ -16368,6 +18058,7 @@ impl MainApp {
 
     /// Guarda la nota actual en su archivo .md
     fn save_current_note(&mut self, generate_embeddings: bool) {
+        self.persist_registers();
         if let Some(note) = &self.current_note {
             // Obtener contenido anterior y nuevo
             let old_content = note.read().unwrap_or_default();
@@ -16378,6 +18069,7 @@ impl MainApp {
             if old_content == new_content {
                 // println!("Nota sin cambios. Omitiendo guardado.");
                 self.has_unsaved_changes = false;
+                self.buffer.mark_saved();
                 return;
             }
 
@@ -16392,6 +18084,9 @@ impl MainApp {
             } else {
                 println!("Nota guardada: {}", note.name());
                 self.has_unsaved_changes = false;
+                self.buffer.mark_saved();
+                self.persist_undo_history();
+                self.schedule_git_commit();
 
                 // Limpiar imágenes no referenciadas
                 self.cleanup_unused_images(&old_content, &new_content);
@@ -16444,7 +18139,7 @@ impl MainApp {
             // Si no hay nota actual, crear una nueva con timestamp
             let timestamp = chrono::Utc::now().format("%Y-%m-%d_%H-%M-%S");
             let name = format!("nota_{}", timestamp);
-            if let Err(e) = self.create_new_note(&name) {
+            if let Err(e) = self.create_new_note(&name, None) {
                 eprintln!("Error creando nota automática: {}", e);
             }
         }
@@ -16529,9 +18224,19 @@ impl MainApp {
             .ok_or_else(|| anyhow::anyhow!("Nota no encontrada: {}", name))?;
 
         let content = note.read()?;
+
+        // Conservar el historial de undo y los registros vim de la nota que se deja
+        self.persist_undo_history();
+        self.persist_registers();
+
         self.buffer = NoteBuffer::from_text(&content);
         self.cursor_position = 0;
         self.current_note = Some(note);
+        self.restore_undo_history();
+        self.folds = Folds::new();
+        if self.active_panel == SidebarPanel::Outline {
+            self.refresh_outline_panel();
+        }
 
         // Guardar como última nota abierta
         self.notes_config
@@ -16545,8 +18250,364 @@ impl MainApp {
         Ok(())
     }
 
+    /// Guardar en la base de datos el historial de undo de la nota actual
+    fn persist_undo_history(&self) {
+        if let Some(note) = &self.current_note {
+            let path = note.path().to_string_lossy();
+            if let Err(e) = self
+                .notes_db
+                .save_undo_history(&path, &self.buffer.history())
+            {
+                eprintln!("Error guardando historial de undo: {}", e);
+            }
+        }
+    }
+
+    /// Recuperar el historial de undo guardado de la nota actual
+    fn restore_undo_history(&mut self) {
+        if let Some(note) = &self.current_note {
+            let path = note.path().to_string_lossy();
+            match self.notes_db.load_undo_history(&path) {
+                Ok(Some(history)) => {
+                    if !self.buffer.restore_history(history) {
+                        // La nota cambió fuera del editor: el historial ya no corresponde
+                        let _ = self.notes_db.delete_undo_history(&path);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("Error cargando historial de undo: {}", e),
+            }
+        }
+    }
+
     /// Crea una nueva nota
-    fn create_new_note(&mut self, name: &str) -> anyhow::Result<()> {
+    /// Crear una nota (o carpeta, si el nombre acaba en `/`) y mostrarla
+    fn finish_new_note(
+        &mut self,
+        clean_name: &str,
+        template: Option<(&Template, TemplateContext)>,
+        sender: &ComponentSender<Self>,
+    ) {
+        let is_folder_only = clean_name.ends_with('/');
+
+        if let Err(e) = self.create_new_note(clean_name, template) {
+            eprintln!("Error creando '{}': {}", clean_name, e);
+        } else if is_folder_only {
+            // Solo se creó una carpeta, refrescar sidebar
+            self.populate_notes_list(sender);
+            *self.is_populating_list.borrow_mut() = false;
+            println!("📂 Carpeta creada y sidebar actualizado");
+        } else {
+            // Se creó una nota, hacer el proceso completo
+            self.sync_to_view();
+            self.update_status_bar(sender);
+            self.refresh_tags_display_with_sender(sender);
+            self.refresh_todos_summary();
+            self.window_title.set_label(clean_name);
+
+            // Refrescar lista de notas en el sidebar
+            self.populate_notes_list(sender);
+            *self.is_populating_list.borrow_mut() = false;
+
+            // Cambiar a modo Insert para empezar a escribir
+            *self.mode.borrow_mut() = EditorMode::Insert;
+        }
+    }
+
+    /// Abrir una nota periódica, creándola con su plantilla si aún no existe
+    fn open_periodic_note(&self, note: &PeriodicNote, sender: &ComponentSender<Self>) {
+        let language = self.i18n.borrow().current_language().code().to_string();
+        let ensured = {
+            let config = self.notes_config.borrow();
+            let library = config.template_library(self.notes_dir.root());
+            config
+                .periodic_notes
+                .ensure(self.notes_dir.root(), &library, note, &language)
+        };
+        let ensured = match ensured {
+            Ok(ensured) => ensured,
+            Err(e) => {
+                sender.input(AppMsg::ShowNotification(
+                    self.i18n
+                        .borrow()
+                        .t("periodic_error")
+                        .replace("{}", &e.to_string()),
+                ));
+                return;
+            }
+        };
+
+        if ensured.created {
+            if let Err(e) = self.notes_db.index_note(
+                &ensured.note.title,
+                ensured.path.to_str().unwrap_or(""),
+                &ensured.content,
+                ensured.note.folder.as_deref(),
+            ) {
+                eprintln!("Error indexando nota periódica: {}", e);
+            }
+            sender.input(AppMsg::RefreshSidebar);
+        }
+
+        let name = match self.notes_dir.find_note(&ensured.note.name()) {
+            Ok(Some(found)) => found.name().to_string(),
+            _ => ensured.note.name(),
+        };
+        sender.input(AppMsg::LoadNote {
+            name,
+            highlight_text: None,
+        });
+    }
+
+    /// Cargar una plantilla, leer el portapapeles si lo usa y preguntar sus campos
+    fn start_template(&self, name: &str, target: TemplateTarget, sender: &ComponentSender<Self>) {
+        let library = self
+            .notes_config
+            .borrow()
+            .template_library(self.notes_dir.root());
+        let template = match library.load(name) {
+            Ok(template) => template,
+            Err(e) => {
+                sender.input(AppMsg::ShowNotification(
+                    self.i18n
+                        .borrow()
+                        .t("template_error")
+                        .replace("{}", &e.to_string()),
+                ));
+                return;
+            }
+        };
+
+        let prompts = template.prompts();
+        let uses_clipboard = template.uses_clipboard();
+        let main_window = self.main_window.clone();
+        let i18n = self.i18n.clone();
+        let sender = sender.clone();
+        let ask = move |clipboard: Option<String>| {
+            Self::show_template_prompts(&main_window, &i18n.borrow(), &prompts, move |values| {
+                sender.input(AppMsg::ApplyTemplate {
+                    template: template.clone(),
+                    target: target.clone(),
+                    values,
+                    clipboard: clipboard.clone(),
+                });
+            });
+        };
+
+        if uses_clipboard {
+            self.main_window.clipboard().read_text_async(
+                None::<&gtk::gio::Cancellable>,
+                move |result| {
+                    ask(result.ok().flatten().map(|text| text.to_string()));
+                },
+            );
+        } else {
+            ask(None);
+        }
+    }
+
+    /// Diálogo con un campo de texto por cada `{{prompt:...}}` de la plantilla
+    fn show_template_prompts(
+        window: &gtk::ApplicationWindow,
+        i18n: &I18n,
+        prompts: &[PromptField],
+        on_accept: impl Fn(std::collections::HashMap<String, String>) + 'static,
+    ) {
+        if prompts.is_empty() {
+            on_accept(Default::default());
+            return;
+        }
+
+        let dialog = gtk::Dialog::builder()
+            .title(i18n.t("template_fields"))
+            .modal(true)
+            .transient_for(window)
+            .default_width(400)
+            .build();
+
+        let content = dialog.content_area();
+        content.set_spacing(12);
+        content.set_margin_all(16);
+
+        let grid = gtk::Grid::builder()
+            .row_spacing(8)
+            .column_spacing(12)
+            .build();
+        let mut entries = Vec::new();
+        for (row, field) in prompts.iter().enumerate() {
+            let label = gtk::Label::builder()
+                .label(&field.name)
+                .halign(gtk::Align::End)
+                .build();
+            let entry = gtk::Entry::builder()
+                .text(field.default.as_deref().unwrap_or(""))
+                .hexpand(true)
+                .activates_default(true)
+                .build();
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(&entry, 1, row as i32, 1, 1);
+            entries.push((field.name.clone(), entry));
+        }
+        content.append(&grid);
+
+        dialog.add_button(&i18n.t("cancel"), gtk::ResponseType::Cancel);
+        dialog.add_button(&i18n.t("create"), gtk::ResponseType::Accept);
+        dialog.set_default_response(gtk::ResponseType::Accept);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                let values = entries
+                    .iter()
+                    .map(|(name, entry)| (name.clone(), entry.text().to_string()))
+                    .collect();
+                on_accept(values);
+            }
+            dialog.close();
+        });
+
+        dialog.present();
+    }
+
+    /// Elegir la plantilla por defecto de las notas nuevas de una carpeta
+    fn show_folder_template_dialog(&self, folder: &str, sender: &ComponentSender<Self>) {
+        let config = self.notes_config.borrow();
+        let library = config.template_library(self.notes_dir.root());
+        let templates = library.list();
+        let current = config.folder_templates.get(folder).cloned();
+        let i18n = self.i18n.borrow();
+
+        let dialog = gtk::Dialog::builder()
+            .title(i18n.t("folder_template"))
+            .modal(true)
+            .transient_for(&self.main_window)
+            .default_width(400)
+            .build();
+
+        let content = dialog.content_area();
+        content.set_spacing(12);
+        content.set_margin_all(16);
+
+        let hint = gtk::Label::builder()
+            .label(i18n.t("folder_template_hint").replace("{}", folder))
+            .wrap(true)
+            .xalign(0.0)
+            .build();
+        content.append(&hint);
+
+        let mut options = vec![i18n.t("no_template")];
+        options.extend(templates.iter().cloned());
+        let option_refs: Vec<&str> = options.iter().map(String::as_str).collect();
+        let dropdown = gtk::DropDown::from_strings(&option_refs);
+        let selected = current
+            .as_ref()
+            .and_then(|current| templates.iter().position(|t| t == current))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        dropdown.set_selected(selected as u32);
+        content.append(&dropdown);
+
+        let location = gtk::Label::builder()
+            .label(
+                i18n.t("templates_location")
+                    .replace("{}", &library.dir().display().to_string()),
+            )
+            .wrap(true)
+            .xalign(0.0)
+            .build();
+        location.add_css_class("dim-label");
+        content.append(&location);
+
+        dialog.add_button(&i18n.t("cancel"), gtk::ResponseType::Cancel);
+        dialog.add_button(&i18n.t("save"), gtk::ResponseType::Accept);
+        dialog.set_default_response(gtk::ResponseType::Accept);
+
+        let sender = sender.clone();
+        let folder = folder.to_string();
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                let template = match dropdown.selected() as usize {
+                    0 => None,
+                    index => templates.get(index - 1).cloned(),
+                };
+                sender.input(AppMsg::SetFolderTemplate {
+                    folder: folder.clone(),
+                    template,
+                });
+            }
+            dialog.close();
+        });
+
+        dialog.present();
+    }
+
+    /// Diálogo para guardar o editar una búsqueda guardada: nombre y query
+    fn show_saved_search_dialog(
+        &self,
+        original: Option<&str>,
+        name: &str,
+        query: &str,
+        sender: &ComponentSender<Self>,
+    ) {
+        let i18n = self.i18n.borrow();
+
+        let dialog = gtk::Dialog::builder()
+            .title(i18n.t("saved_search_dialog_title"))
+            .modal(true)
+            .transient_for(&self.main_window)
+            .default_width(400)
+            .build();
+
+        let content = dialog.content_area();
+        content.set_spacing(12);
+        content.set_margin_all(16);
+
+        let name_label = gtk::Label::builder()
+            .label(i18n.t("saved_search_name"))
+            .xalign(0.0)
+            .build();
+        content.append(&name_label);
+        let name_entry = gtk::Entry::builder()
+            .text(name)
+            .activates_default(true)
+            .build();
+        content.append(&name_entry);
+
+        let query_label = gtk::Label::builder()
+            .label(i18n.t("saved_search_query"))
+            .xalign(0.0)
+            .build();
+        content.append(&query_label);
+        let query_entry = gtk::Entry::builder()
+            .text(query)
+            .activates_default(true)
+            .build();
+        content.append(&query_entry);
+
+        dialog.add_button(&i18n.t("cancel"), gtk::ResponseType::Cancel);
+        dialog.add_button(&i18n.t("save"), gtk::ResponseType::Accept);
+        dialog.set_default_response(gtk::ResponseType::Accept);
+
+        let sender = sender.clone();
+        let original = original.map(str::to_string);
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                sender.input(AppMsg::StoreSavedSearch {
+                    original: original.clone(),
+                    name: name_entry.text().to_string(),
+                    query: query_entry.text().to_string(),
+                });
+            }
+            dialog.close();
+        });
+
+        dialog.present();
+    }
+
+    fn create_new_note(
+        &mut self,
+        name: &str,
+        template: Option<(&Template, TemplateContext)>,
+    ) -> anyhow::Result<()> {
         // Limpiar el nombre: quitar / del inicio y espacios extra
         let clean_name = name.trim().trim_start_matches('/').trim();
 
@@ -16592,8 +18653,23 @@ impl MainApp {
             unique_name.clone()
         };
 
-        // Contenido inicial vacío para nueva nota
-        let initial_content = format!("# {}\n\n", unique_name);
+        // Contenido inicial: la plantilla de la carpeta o un encabezado con el nombre
+        let (initial_content, initial_cursor) = match template {
+            Some((template, mut context)) => {
+                context.title = unique_name.clone();
+                context.folder = folder.map(str::to_string);
+                let rendered = template.render(&context);
+                let cursor = rendered
+                    .cursor
+                    .unwrap_or_else(|| rendered.content.chars().count());
+                (rendered.content, cursor)
+            }
+            None => {
+                let content = format!("# {}\n\n", unique_name);
+                let cursor = content.chars().count();
+                (content, cursor)
+            }
+        };
 
         let note = if let Some(folder_path) = folder {
             // Crear en carpeta
@@ -16619,7 +18695,7 @@ impl MainApp {
 
         // Cargar la nueva nota en el buffer
         self.buffer = NoteBuffer::from_text(&initial_content);
-        self.cursor_position = initial_content.len();
+        self.cursor_position = initial_cursor;
         self.current_note = Some(note.clone());
         self.has_unsaved_changes = false;
 
@@ -16795,6 +18871,123 @@ impl MainApp {
         row.add_controller(drop_target);
     }
 
+    /// Añade al sidebar las búsquedas guardadas como carpetas virtuales.
+    /// Su contenido se recalcula con `NotesDatabase::search` en cada repoblación.
+    fn populate_saved_searches(&self) {
+        let saved_searches = match self.notes_db.list_saved_searches() {
+            Ok(searches) => searches,
+            Err(e) => {
+                eprintln!("Error cargando búsquedas guardadas: {}", e);
+                return;
+            }
+        };
+
+        for saved in saved_searches {
+            let key = format!("{}{}", SAVED_SEARCH_PREFIX, saved.name);
+            let results = self.notes_db.search(&saved.query).unwrap_or_default();
+            let is_expanded = self.expanded_folders.contains(&key);
+
+            let folder_row = gtk::Box::builder()
+                .orientation(gtk::Orientation::Horizontal)
+                .spacing(6)
+                .margin_start(8)
+                .margin_end(12)
+                .margin_top(6)
+                .margin_bottom(4)
+                .build();
+
+            let arrow = gtk::Image::builder()
+                .icon_name(if is_expanded {
+                    "pan-down-symbolic"
+                } else {
+                    "pan-end-symbolic"
+                })
+                .pixel_size(12)
+                .build();
+            let icon = gtk::Image::builder()
+                .icon_name(
+                    saved
+                        .icon
+                        .as_deref()
+                        .unwrap_or("folder-saved-search-symbolic"),
+                )
+                .pixel_size(16)
+                .build();
+            let label = gtk::Label::builder()
+                .label(&saved.name)
+                .xalign(0.0)
+                .hexpand(true)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(30)
+                .build();
+            label.add_css_class("heading");
+            let count = gtk::Label::new(Some(results.len().to_string().as_str()));
+            count.add_css_class("dim-label");
+
+            folder_row.append(&arrow);
+            folder_row.append(&icon);
+            folder_row.append(&label);
+            folder_row.append(&count);
+
+            let list_row = gtk::ListBoxRow::builder()
+                .selectable(true)
+                .activatable(true)
+                .child(&folder_row)
+                .tooltip_text(&saved.name)
+                .build();
+
+            // Se comporta como carpeta (expandir/contraer), sin drag-and-drop
+            unsafe {
+                list_row.set_data("folder_name", key.clone());
+                list_row.set_data("is_folder", true);
+            }
+            self.notes_list.append(&list_row);
+
+            if !is_expanded {
+                continue;
+            }
+
+            for result in results {
+                let row = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Horizontal)
+                    .spacing(8)
+                    .margin_start(24)
+                    .margin_end(12)
+                    .margin_top(3)
+                    .margin_bottom(3)
+                    .build();
+
+                let note_icon = gtk::Image::builder()
+                    .icon_name("text-x-generic-symbolic")
+                    .pixel_size(14)
+                    .build();
+                let note_label = gtk::Label::builder()
+                    .label(&result.note_name)
+                    .xalign(0.0)
+                    .hexpand(true)
+                    .ellipsize(gtk::pango::EllipsizeMode::End)
+                    .max_width_chars(40)
+                    .tooltip_text(&result.note_name)
+                    .build();
+
+                row.append(&note_icon);
+                row.append(&note_label);
+
+                let note_row = gtk::ListBoxRow::builder()
+                    .selectable(true)
+                    .activatable(true)
+                    .child(&row)
+                    .build();
+
+                unsafe {
+                    note_row.set_data("note_name", result.note_name.clone());
+                    note_row.set_data("is_folder", false);
+                }
+                self.notes_list.append(&note_row);
+            }
+        }
+    }
+
     /// Rellena la lista de notas en el sidebar
     fn populate_notes_list(&self, sender: &ComponentSender<Self>) {
         use std::collections::HashMap;
@@ -16943,6 +19136,9 @@ impl MainApp {
                 });
             }
 
+            // Carpetas virtuales (búsquedas guardadas) al principio del árbol
+            self.populate_saved_searches();
+
             for folder in folders {
                 // Ocultar carpeta .history y otras ocultas de la interfaz
                 if folder != ".trash" && folder.split('/').any(|p| p.starts_with('.')) {
@@ -17333,6 +19529,16 @@ impl MainApp {
                                             ) {
                                                 eprintln!("⚠️ Error actualizando BD después de renombrar: {}", e);
                                             }
+
+                                            // Actualizar los [[enlaces]] que apuntaban a la nota
+                                            let full_name = |name: &str| match &folder {
+                                                Some(folder) => format!("{}/{}", folder, name),
+                                                None => name.to_string(),
+                                            };
+                                            sender_clone.input(AppMsg::UpdateNoteLinks {
+                                                old_name: full_name(&old_name),
+                                                new_name: full_name(&new_name),
+                                            });
                                         }
                                     }
                                 }
@@ -18946,7 +21152,7 @@ impl MainApp {
         if let Some(pos) = content.find(&loading_pattern) {
             // Reemplazar el mensaje de carga con la transcripción
             let new_content = content.replace(&loading_pattern, &replacement);
-            self.buffer = NoteBuffer::from_text(&new_content);
+            self.buffer.set_text(&new_content);
             self.has_unsaved_changes = true;
 
             // Sincronizar vista
@@ -22595,6 +24801,11 @@ impl MainApp {
             MCPToolCall::ReadNote { .. } => "Leyendo nota...".to_string(),
             MCPToolCall::UpdateNote { .. } => "Actualizando nota...".to_string(),
             MCPToolCall::CreateNote { .. } => "Creando nota...".to_string(),
+            MCPToolCall::CreateNoteFromTemplate { .. } => {
+                "Creando nota desde plantilla...".to_string()
+            }
+            MCPToolCall::CreatePeriodicNote { .. } => "Abriendo nota periódica...".to_string(),
+            MCPToolCall::ListPeriodicNotes { .. } => "Listando notas periódicas...".to_string(),
             MCPToolCall::DeleteNote { .. } => "Eliminando nota...".to_string(),
             MCPToolCall::RenameNote { .. } => "Renombrando nota...".to_string(),
             MCPToolCall::DuplicateNote { .. } => "Duplicando nota...".to_string(),
@@ -22653,6 +24864,9 @@ impl MainApp {
             // Exportación
             MCPToolCall::ExportNote { .. } => "Exportando nota...".to_string(),
             MCPToolCall::ExportMultipleNotes { .. } => "Exportando notas...".to_string(),
+            MCPToolCall::ExportFolder { .. } => "Exportando carpeta...".to_string(),
+            MCPToolCall::ExportBook { .. } => "Compilando libro...".to_string(),
+            MCPToolCall::ImportNotes { .. } => "Importando notas...".to_string(),
             MCPToolCall::BackupNotes { .. } => "Creando respaldo...".to_string(),
             MCPToolCall::ImportFromUrl { .. } => "Importando desde URL...".to_string(),
 
@@ -23301,6 +25515,774 @@ impl MainApp {
         }
     }
 
+    /// Commit automático del vault tras una pausa sin guardar
+    fn schedule_git_commit(&self) {
+        let Some(git) = self.vault_git.clone() else {
+            return;
+        };
+        self.cancel_git_commit();
+        let config = self.notes_config.borrow().git.clone();
+        let timeout_id = self.git_commit_timeout.clone();
+        let source_id = gtk::glib::timeout_add_local_once(
+            std::time::Duration::from_secs(config.debounce_secs),
+            move || {
+                *timeout_id.borrow_mut() = None;
+                // git (y sobre todo el push) puede tardar: fuera del hilo de la interfaz
+                std::thread::spawn(move || {
+                    git.exclusive(|git| match git.auto_commit(&config.message_template) {
+                        Ok(Some(id)) => {
+                            println!("📚 Commit automático: {}", id);
+                            if config.push_on_commit {
+                                if let Err(e) = git.push(config.branch.as_deref()) {
+                                    eprintln!("Error en el push automático: {}", e);
+                                }
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(e) => eprintln!("Error en el commit automático: {}", e),
+                    })
+                });
+            },
+        );
+        *self.git_commit_timeout.borrow_mut() = Some(source_id);
+    }
+
+    /// Ejecutar una operación git fuera del hilo de la interfaz, cuando terminen
+    /// las que estén en curso, y enviar el mensaje con su resultado
+    fn spawn_git<T: 'static>(
+        git: VaultGit,
+        sender: &ComponentSender<Self>,
+        operation: impl FnOnce(&VaultGit) -> T + Send + 'static,
+        message: impl FnOnce(T) -> AppMsg + Send + 'static,
+    ) {
+        let sender = sender.clone();
+        std::thread::spawn(move || {
+            let result = git.exclusive(operation);
+            sender.input(message(result));
+        });
+    }
+
+    /// Leer el historial Git de una nota en segundo plano y mostrarlo
+    fn load_git_history(&self, note_name: String, git: VaultGit, sender: &ComponentSender<Self>) {
+        let error_prefix = self.i18n.borrow().t("ex_error");
+        let name = note_name.clone();
+        Self::spawn_git(
+            git,
+            sender,
+            move |git| git.note_log(&name),
+            move |result| match result {
+                Ok(commits) => AppMsg::ShowGitHistory { note_name, commits },
+                Err(e) => AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)),
+            },
+        );
+    }
+
+    /// Cancelar el commit automático pendiente
+    fn cancel_git_commit(&self) {
+        if let Some(source_id) = self.git_commit_timeout.borrow_mut().take() {
+            source_id.remove();
+        }
+    }
+
+    /// Ejecutar un subcomando de `:git`
+    fn run_git_command(&mut self, command: GitCommand, sender: &ComponentSender<Self>) {
+        let error_prefix = self.i18n.borrow().t("ex_error");
+        if command == GitCommand::Init {
+            let message = match VaultGit::init(self.notes_dir.root()) {
+                Ok(git) => {
+                    self.vault_git = Some(git);
+                    self.notes_config.borrow_mut().git.enabled = true;
+                    if let Err(e) = self.notes_config.borrow().save(NotesConfig::default_path()) {
+                        eprintln!("Error guardando configuración: {}", e);
+                    }
+                    self.i18n.borrow().t("git_enabled")
+                }
+                Err(e) => format!("{}: {}", error_prefix, e),
+            };
+            sender.input(AppMsg::ShowNotification(message));
+            return;
+        }
+        let Some(git) = self.vault_git.clone() else {
+            sender.input(AppMsg::ShowNotification(
+                self.i18n.borrow().t("git_not_enabled"),
+            ));
+            return;
+        };
+        // Lo que hay en el editor entra en el commit
+        if self.has_unsaved_changes {
+            self.save_current_note(false);
+        }
+        let config = self.notes_config.borrow().git.clone();
+
+        match command {
+            GitCommand::Init => {}
+            GitCommand::Commit(message) => {
+                self.cancel_git_commit();
+                let committed = self.i18n.borrow().t("git_committed");
+                let nothing = self.i18n.borrow().t("git_nothing_to_commit");
+                Self::spawn_git(
+                    git,
+                    sender,
+                    move |git| match message {
+                        Some(message) => git.commit_all(&message),
+                        None => git.auto_commit(&config.message_template),
+                    },
+                    move |result| {
+                        AppMsg::ShowNotification(match result {
+                            Ok(Some(id)) => committed.replace("{}", id.get(..7).unwrap_or(&id)),
+                            Ok(None) => nothing,
+                            Err(e) => format!("{}: {}", error_prefix, e),
+                        })
+                    },
+                );
+            }
+            GitCommand::Log => {
+                if let Some(note) = &self.current_note {
+                    self.load_git_history(note.name().to_string(), git, sender);
+                }
+            }
+            GitCommand::Remote(url) => {
+                let url = match url.strip_prefix("~/") {
+                    Some(rest) => dirs::home_dir()
+                        .unwrap_or_default()
+                        .join(rest)
+                        .to_string_lossy()
+                        .to_string(),
+                    None => url,
+                };
+                let remote_set = self.i18n.borrow().t("git_remote_set").replace("{}", &url);
+                Self::spawn_git(
+                    git,
+                    sender,
+                    move |git| git.set_remote(&url),
+                    move |result| {
+                        AppMsg::ShowNotification(match result {
+                            Ok(()) => remote_set,
+                            Err(e) => format!("{}: {}", error_prefix, e),
+                        })
+                    },
+                );
+            }
+            GitCommand::Pull | GitCommand::Push | GitCommand::Sync => {
+                self.cancel_git_commit();
+                sender.input(AppMsg::ShowNotification(
+                    self.i18n.borrow().t("git_syncing"),
+                ));
+                Self::spawn_git(
+                    git,
+                    sender,
+                    move |git| {
+                        let branch = config.branch.as_deref();
+                        match command {
+                            GitCommand::Pull => git
+                                .auto_commit(&config.message_template)
+                                .and_then(|_| git.pull(branch)),
+                            GitCommand::Push => git
+                                .auto_commit(&config.message_template)
+                                .and_then(|_| git.push(branch))
+                                .map(|_| PullOutcome::UpToDate),
+                            _ => git.sync(&config),
+                        }
+                    },
+                    |result| AppMsg::GitSyncFinished(result.map_err(|e| e.to_string())),
+                );
+            }
+            GitCommand::Conflicts => {
+                let no_conflicts = self.i18n.borrow().t("git_no_conflicts");
+                Self::spawn_git(
+                    git,
+                    sender,
+                    VaultGit::conflicts,
+                    move |result| match result {
+                        Ok(conflicts) if !conflicts.is_empty() => {
+                            AppMsg::ShowGitConflicts(conflicts)
+                        }
+                        Ok(_) => AppMsg::ShowNotification(no_conflicts),
+                        Err(e) => AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)),
+                    },
+                );
+            }
+            GitCommand::Abort => {
+                let aborted = self.i18n.borrow().t("git_merge_aborted");
+                let refresh = sender.clone();
+                Self::spawn_git(git, sender, VaultGit::abort_merge, move |result| {
+                    AppMsg::ShowNotification(match result {
+                        Ok(()) => {
+                            refresh.input(AppMsg::RefreshSidebar);
+                            aborted
+                        }
+                        Err(e) => format!("{}: {}", error_prefix, e),
+                    })
+                });
+            }
+        }
+    }
+
+    /// Muestra el historial Git de una nota: cada versión con sus cambios y la
+    /// opción de restaurarla
+    fn show_git_history_dialog(
+        &self,
+        note_name: &str,
+        commits: Vec<CommitInfo>,
+        git: &VaultGit,
+        sender: &ComponentSender<Self>,
+    ) {
+        let i18n = self.i18n.borrow();
+
+        let dialog = gtk::Window::builder()
+            .title(&format!("{} - {}", i18n.t("view_history"), note_name))
+            .modal(true)
+            .transient_for(&self.main_window)
+            .default_width(520)
+            .default_height(450)
+            .resizable(true)
+            .build();
+        dialog.add_css_class("note-history-dialog");
+
+        // Cerrar con ESC
+        let key_controller = gtk::EventControllerKey::new();
+        let dialog_weak = dialog.downgrade();
+        key_controller.connect_key_pressed(move |_, keyval, _, _| {
+            if keyval == gtk::gdk::Key::Escape {
+                if let Some(d) = dialog_weak.upgrade() {
+                    d.close();
+                }
+                return gtk::glib::Propagation::Stop;
+            }
+            gtk::glib::Propagation::Proceed
+        });
+        dialog.add_controller(key_controller);
+
+        let main_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .margin_top(16)
+            .margin_bottom(16)
+            .margin_start(16)
+            .margin_end(16)
+            .build();
+
+        let header = gtk::Label::builder()
+            .label(&format!("📜 {} versiones (Git)", commits.len()))
+            .halign(gtk::Align::Start)
+            .build();
+        header.add_css_class("title-4");
+        main_box.append(&header);
+
+        if commits.is_empty() {
+            let empty_label = gtk::Label::builder()
+                .label(&i18n.t("git_no_versions"))
+                .halign(gtk::Align::Center)
+                .valign(gtk::Align::Center)
+                .vexpand(true)
+                .wrap(true)
+                .build();
+            empty_label.add_css_class("dim-label");
+            main_box.append(&empty_label);
+        } else {
+            let scrolled = gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Never)
+                .vscrollbar_policy(gtk::PolicyType::Automatic)
+                .vexpand(true)
+                .build();
+
+            let list_box = gtk::ListBox::builder()
+                .selection_mode(gtk::SelectionMode::None)
+                .build();
+            list_box.add_css_class("boxed-list");
+
+            for commit in commits {
+                let row = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Horizontal)
+                    .spacing(12)
+                    .margin_top(8)
+                    .margin_bottom(8)
+                    .margin_start(12)
+                    .margin_end(12)
+                    .build();
+
+                let icon = gtk::Image::from_icon_name("document-revert-symbolic");
+                icon.add_css_class("dim-label");
+                row.append(&icon);
+
+                let info_box = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Vertical)
+                    .spacing(2)
+                    .hexpand(true)
+                    .build();
+
+                let message_label = gtk::Label::builder()
+                    .label(&commit.message)
+                    .halign(gtk::Align::Start)
+                    .build();
+                message_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+                message_label.add_css_class("heading");
+
+                let date = commit.date.with_timezone(&chrono::Local);
+                let details_label = gtk::Label::builder()
+                    .label(&format!(
+                        "{} · {} · {}",
+                        date.format("%d %b %Y, %H:%M"),
+                        commit.author,
+                        commit.short_id
+                    ))
+                    .halign(gtk::Align::Start)
+                    .build();
+                details_label.add_css_class("dim-label");
+                details_label.add_css_class("caption");
+
+                info_box.append(&message_label);
+                info_box.append(&details_label);
+                row.append(&info_box);
+
+                let actions_box = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Horizontal)
+                    .spacing(4)
+                    .build();
+
+                // Ver los cambios de esta versión
+                let diff_btn = gtk::Button::new();
+                diff_btn.set_icon_name("document-open-symbolic");
+                diff_btn.set_tooltip_text(Some(&i18n.t("git_view_diff")));
+                diff_btn.add_css_class("flat");
+                diff_btn.add_css_class("circular");
+
+                let git_clone = git.clone();
+                let commit_clone = commit.clone();
+                let note_name_clone = note_name.to_string();
+                let sender_clone = sender.clone();
+                diff_btn.connect_clicked(move |_| {
+                    let commit = commit_clone.clone();
+                    let title = format!("{} ({})", note_name_clone, commit.short_id);
+                    Self::spawn_git(
+                        git_clone.clone(),
+                        &sender_clone,
+                        move |git| git.note_diff(&commit),
+                        move |result| AppMsg::ShowGitDiff {
+                            title,
+                            result: result.map_err(|e| e.to_string()),
+                        },
+                    );
+                });
+                actions_box.append(&diff_btn);
+
+                // Restaurar esta versión
+                let restore_btn = gtk::Button::new();
+                restore_btn.set_icon_name("edit-undo-symbolic");
+                restore_btn.set_tooltip_text(Some(&i18n.t("git_restore_version")));
+                restore_btn.add_css_class("flat");
+                restore_btn.add_css_class("circular");
+                restore_btn.add_css_class("suggested-action");
+
+                let sender_clone = sender.clone();
+                let note_name_for_restore = note_name.to_string();
+                let dialog_weak = dialog.downgrade();
+                restore_btn.connect_clicked(move |_| {
+                    sender_clone.input(AppMsg::RestoreGitVersion {
+                        note_name: note_name_for_restore.clone(),
+                        commit: commit.clone(),
+                    });
+                    if let Some(d) = dialog_weak.upgrade() {
+                        d.close();
+                    }
+                });
+                actions_box.append(&restore_btn);
+
+                row.append(&actions_box);
+                list_box.append(&row);
+            }
+
+            scrolled.set_child(Some(&list_box));
+            main_box.append(&scrolled);
+        }
+
+        let close_btn = gtk::Button::builder()
+            .label("Cerrar")
+            .halign(gtk::Align::End)
+            .build();
+        close_btn.add_css_class("pill");
+
+        let dialog_weak = dialog.downgrade();
+        close_btn.connect_clicked(move |_| {
+            if let Some(d) = dialog_weak.upgrade() {
+                d.close();
+            }
+        });
+        main_box.append(&close_btn);
+
+        dialog.set_child(Some(&main_box));
+        dialog.present();
+    }
+
+    /// Muestra un diff unificado con las líneas añadidas y quitadas en color
+    fn show_git_diff_preview(parent: &gtk::Window, title: &str, diff: &str) {
+        let preview_dialog = gtk::Window::builder()
+            .title(title)
+            .modal(true)
+            .transient_for(parent)
+            .default_width(700)
+            .default_height(500)
+            .build();
+
+        let main_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(8)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Automatic)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .vexpand(true)
+            .build();
+
+        let text_view = gtk::TextView::builder()
+            .editable(false)
+            .wrap_mode(gtk::WrapMode::Word)
+            .monospace(true)
+            .left_margin(8)
+            .right_margin(8)
+            .top_margin(8)
+            .bottom_margin(8)
+            .build();
+        text_view.add_css_class("view");
+
+        let buffer = text_view.buffer();
+        buffer.set_text(diff);
+        let tag_table = buffer.tag_table();
+        for (name, color) in [
+            ("diff-added", "#2ea043"),
+            ("diff-removed", "#d73a49"),
+            ("diff-hunk", "#8250df"),
+        ] {
+            let tag = gtk::TextTag::new(Some(name));
+            tag.set_foreground(Some(color));
+            tag_table.add(&tag);
+        }
+        for (index, line) in diff.lines().enumerate() {
+            let tag = if line.starts_with("+++") || line.starts_with("---") {
+                continue;
+            } else if line.starts_with('+') {
+                "diff-added"
+            } else if line.starts_with('-') {
+                "diff-removed"
+            } else if line.starts_with("@@") {
+                "diff-hunk"
+            } else {
+                continue;
+            };
+            let Some(start) = buffer.iter_at_line(index as i32) else {
+                continue;
+            };
+            let mut end = start;
+            end.forward_to_line_end();
+            buffer.apply_tag_by_name(tag, &start, &end);
+        }
+
+        scrolled.set_child(Some(&text_view));
+        main_box.append(&scrolled);
+
+        let close_btn = gtk::Button::builder()
+            .label("Cerrar")
+            .halign(gtk::Align::End)
+            .build();
+        close_btn.add_css_class("pill");
+
+        let dialog_weak = preview_dialog.downgrade();
+        close_btn.connect_clicked(move |_| {
+            if let Some(d) = dialog_weak.upgrade() {
+                d.close();
+            }
+        });
+        main_box.append(&close_btn);
+
+        preview_dialog.set_child(Some(&main_box));
+        preview_dialog.present();
+    }
+
+    /// Resolución de conflictos tras un pull: cada fichero con mi versión, la
+    /// remota y el resultado editable (con las marcas de conflicto de Git)
+    fn show_git_conflicts_dialog(
+        &self,
+        conflicts: Vec<Conflict>,
+        git: &VaultGit,
+        sender: &ComponentSender<Self>,
+    ) {
+        let i18n = self.i18n.borrow();
+
+        let dialog = gtk::Window::builder()
+            .title(&i18n.t("git_conflicts_title"))
+            .modal(true)
+            .transient_for(&self.main_window)
+            .default_width(1000)
+            .default_height(650)
+            .resizable(true)
+            .build();
+
+        let main_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .margin_top(16)
+            .margin_bottom(16)
+            .margin_start(16)
+            .margin_end(16)
+            .build();
+
+        let text_view = |editable: bool| {
+            let view = gtk::TextView::builder()
+                .editable(editable)
+                .wrap_mode(gtk::WrapMode::Word)
+                .monospace(true)
+                .left_margin(8)
+                .right_margin(8)
+                .top_margin(8)
+                .bottom_margin(8)
+                .build();
+            view.add_css_class("view");
+            view
+        };
+        let labeled = |title: &str, view: &gtk::TextView| {
+            let section = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .spacing(4)
+                .hexpand(true)
+                .vexpand(true)
+                .build();
+            let label = gtk::Label::builder()
+                .label(title)
+                .halign(gtk::Align::Start)
+                .build();
+            label.add_css_class("heading");
+            let scrolled = gtk::ScrolledWindow::builder()
+                .hscrollbar_policy(gtk::PolicyType::Automatic)
+                .vscrollbar_policy(gtk::PolicyType::Automatic)
+                .vexpand(true)
+                .build();
+            scrolled.set_child(Some(view));
+            section.append(&label);
+            section.append(&scrolled);
+            section
+        };
+
+        let ours_view = text_view(false);
+        let theirs_view = text_view(false);
+        let merged_view = text_view(true);
+
+        // Ficheros en conflicto
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::Single)
+            .build();
+        list_box.add_css_class("boxed-list");
+        for conflict in &conflicts {
+            let label = gtk::Label::builder()
+                .label(&conflict.path)
+                .halign(gtk::Align::Start)
+                .margin_top(8)
+                .margin_bottom(8)
+                .margin_start(8)
+                .margin_end(8)
+                .build();
+            label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+            list_box.append(&label);
+        }
+        let list_scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .vscrollbar_policy(gtk::PolicyType::Automatic)
+            .build();
+        list_scrolled.set_child(Some(&list_box));
+
+        // Versiones del fichero seleccionado
+        let versions = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(8)
+            .homogeneous(true)
+            .vexpand(true)
+            .build();
+        versions.append(&labeled(&i18n.t("git_ours"), &ours_view));
+        versions.append(&labeled(&i18n.t("git_theirs"), &theirs_view));
+
+        let resolve_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(8)
+            .halign(gtk::Align::End)
+            .build();
+        let ours_btn = gtk::Button::with_label(&i18n.t("git_use_ours"));
+        let theirs_btn = gtk::Button::with_label(&i18n.t("git_use_theirs"));
+        let merged_btn = gtk::Button::with_label(&i18n.t("git_use_merged"));
+        resolve_box.append(&ours_btn);
+        resolve_box.append(&theirs_btn);
+        resolve_box.append(&merged_btn);
+
+        let detail = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(8)
+            .build();
+        detail.append(&versions);
+        detail.append(&labeled(&i18n.t("git_merged"), &merged_view));
+        detail.append(&resolve_box);
+
+        let paned = gtk::Paned::new(gtk::Orientation::Horizontal);
+        paned.set_start_child(Some(&list_scrolled));
+        paned.set_end_child(Some(&detail));
+        paned.set_position(240);
+        paned.set_vexpand(true);
+        main_box.append(&paned);
+
+        // Pie: cancelar o terminar la fusión
+        let footer = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(8)
+            .halign(gtk::Align::End)
+            .build();
+        let abort_btn = gtk::Button::with_label(&i18n.t("git_abort_merge"));
+        abort_btn.add_css_class("destructive-action");
+        let finish_btn = gtk::Button::with_label(&i18n.t("git_finish_merge"));
+        finish_btn.add_css_class("suggested-action");
+        finish_btn.set_sensitive(false);
+        footer.append(&abort_btn);
+        footer.append(&finish_btn);
+        main_box.append(&footer);
+
+        let conflicts = Rc::new(conflicts);
+        let resolved = Rc::new(RefCell::new(vec![false; conflicts.len()]));
+
+        // Al elegir un fichero, mostrar sus versiones
+        {
+            let conflicts = conflicts.clone();
+            let ours_view = ours_view.clone();
+            let theirs_view = theirs_view.clone();
+            let merged_view = merged_view.clone();
+            let deleted = i18n.t("git_deleted");
+            list_box.connect_row_selected(move |_, row| {
+                let Some(conflict) = row.and_then(|row| conflicts.get(row.index() as usize)) else {
+                    return;
+                };
+                ours_view
+                    .buffer()
+                    .set_text(conflict.ours.as_deref().unwrap_or(&deleted));
+                theirs_view
+                    .buffer()
+                    .set_text(conflict.theirs.as_deref().unwrap_or(&deleted));
+                merged_view.buffer().set_text(&conflict.merged);
+            });
+        }
+
+        // Al terminar una resolución: marcar el fichero y pasar al siguiente
+        let on_resolved: ConflictResolvedFn = {
+            let list_box = list_box.clone();
+            let conflicts = conflicts.clone();
+            let resolved = resolved.clone();
+            let resolve_box = resolve_box.clone();
+            let finish_btn = finish_btn.clone();
+            let sender = sender.clone();
+            let error_prefix = i18n.t("ex_error");
+            Rc::new(move |path, result| {
+                resolve_box.set_sensitive(true);
+                if let Err(e) = result {
+                    sender.input(AppMsg::ShowNotification(format!("{}: {}", error_prefix, e)));
+                    return;
+                }
+                let Some(index) = conflicts.iter().position(|conflict| conflict.path == path)
+                else {
+                    return;
+                };
+                resolved.borrow_mut()[index] = true;
+                if let Some(label) = list_box
+                    .row_at_index(index as i32)
+                    .and_then(|row| row.child())
+                    .and_then(|child| child.downcast::<gtk::Label>().ok())
+                {
+                    label.set_label(&format!("✓ {}", path));
+                }
+                let next = resolved.borrow().iter().position(|done| !done);
+                finish_btn.set_sensitive(next.is_none());
+                // Pasar al siguiente fichero sin resolver
+                if let Some(next) = next {
+                    list_box.select_row(list_box.row_at_index(next as i32).as_ref());
+                }
+            })
+        };
+        *self.git_conflict_resolved.borrow_mut() = Some(on_resolved);
+        {
+            let git_conflict_resolved = self.git_conflict_resolved.clone();
+            dialog.connect_destroy(move |_| {
+                git_conflict_resolved.borrow_mut().take();
+            });
+        }
+
+        let resolve: Rc<dyn Fn(Resolution)> = {
+            let git = git.clone();
+            let list_box = list_box.clone();
+            let conflicts = conflicts.clone();
+            let resolve_box = resolve_box.clone();
+            let sender = sender.clone();
+            Rc::new(move |resolution| {
+                let Some(row) = list_box.selected_row() else {
+                    return;
+                };
+                let Some(conflict) = conflicts.get(row.index() as usize) else {
+                    return;
+                };
+                // Un fichero cada vez, hasta que llegue el resultado
+                resolve_box.set_sensitive(false);
+                let path = conflict.path.clone();
+                let file = path.clone();
+                Self::spawn_git(
+                    git.clone(),
+                    &sender,
+                    move |git| git.resolve(&file, &resolution),
+                    move |result| AppMsg::GitConflictResolved {
+                        path,
+                        result: result.map_err(|e| e.to_string()),
+                    },
+                );
+            })
+        };
+
+        {
+            let resolve = resolve.clone();
+            ours_btn.connect_clicked(move |_| resolve(Resolution::Ours));
+        }
+        {
+            let resolve = resolve.clone();
+            theirs_btn.connect_clicked(move |_| resolve(Resolution::Theirs));
+        }
+        {
+            let merged_view = merged_view.clone();
+            merged_btn.connect_clicked(move |_| {
+                let buffer = merged_view.buffer();
+                let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+                resolve(Resolution::Merged(text.to_string()));
+            });
+        }
+
+        let sender_clone = sender.clone();
+        let dialog_weak = dialog.downgrade();
+        finish_btn.connect_clicked(move |_| {
+            sender_clone.input(AppMsg::GitFinishMerge);
+            if let Some(d) = dialog_weak.upgrade() {
+                d.close();
+            }
+        });
+
+        let sender_clone = sender.clone();
+        let dialog_weak = dialog.downgrade();
+        abort_btn.connect_clicked(move |_| {
+            sender_clone.input(AppMsg::GitCommand(GitCommand::Abort));
+            if let Some(d) = dialog_weak.upgrade() {
+                d.close();
+            }
+        });
+
+        list_box.select_row(list_box.row_at_index(0).as_ref());
+
+        dialog.set_child(Some(&main_box));
+        dialog.present();
+    }
+
     /// Inserta formato markdown en el texto del editor
     fn insert_markdown_format(&mut self, format: MarkdownFormat) {
         // Solo funciona en modo INSERT
@@ -4,15 +4,16 @@ use relm4::gtk;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use webkit6::prelude::WebViewExt;
 
 use crate::core::{
-    Base, BaseQueryEngine, BaseView, CellFormat, CellGrid, CellRef, CellValue, ColumnConfig,
-    Filter, FilterGroup, FilterOperator, GroupedRecord, HtmlRenderer, NoteMetadata,
-    NoteWithProperties, NotesDatabase, PreviewTheme, PropertyValue, SortConfig, SortDirection,
-    SourceType, SpecialCellContent, SpecialRow, ViewType,
+    Base, BaseQueryEngine, BaseView, BaseWriter, BulkOperation, BulkTransaction, CellFormat,
+    CellGrid, CellRef, CellValue, ChartConfig, ChartKind, ColumnConfig, Filter, FilterGroup,
+    FilterOperator, GroupedRecord, HtmlRenderer, NoteMetadata, NoteWithProperties, NotesDatabase,
+    PreviewColors, PreviewTheme, PropertyValue, SortConfig, SortDirection, SourceType,
+    SpecialCellContent, SpecialRow, ViewType, render_chart_svg,
 };
 use crate::graph_view::GraphView;
 use crate::i18n::{I18n, Language};
@@ -47,6 +48,20 @@ impl Default for GtkThemeColors {
 }
 
 impl GtkThemeColors {
+    /// Convertir a colores de preview (para HtmlRenderer y gráficos)
+    pub fn to_preview_colors(&self) -> PreviewColors {
+        PreviewColors {
+            bg_primary: self.bg_primary.clone(),
+            bg_secondary: self.bg_secondary.clone(),
+            bg_tertiary: self.bg_tertiary.clone(),
+            fg_primary: self.fg_primary.clone(),
+            fg_secondary: self.fg_secondary.clone(),
+            fg_muted: self.fg_muted.clone(),
+            accent: self.accent.clone(),
+            border: self.border.clone(),
+        }
+    }
+
     /// Extraer colores del tema GTK actual usando lookup_color
     pub fn from_widget(widget: &impl IsA<gtk::Widget>) -> Self {
         let style_context = widget.style_context();
@@ -297,6 +312,13 @@ pub struct BaseTableWidget {
     #[allow(clippy::type_complexity)]
     on_cell_edit: Rc<RefCell<Option<Box<dyn Fn(i64, i64, &str, &str)>>>>,
 
+    /// Historial de operaciones masivas aplicadas (para deshacer)
+    bulk_history: Rc<RefCell<Vec<BulkTransaction>>>,
+
+    /// Callback tras aplicar o deshacer una operación masiva (mensaje para el usuario)
+    #[allow(clippy::type_complexity)]
+    on_bulk_applied: Rc<RefCell<Option<Box<dyn Fn(&str)>>>>,
+
     /// Preferencia de tema oscuro (sincronizada con la app)
     is_dark_theme: Rc<RefCell<bool>>,
 
@@ -509,6 +531,44 @@ impl BaseTableWidget {
         let notes_db: Rc<RefCell<Option<NotesDatabase>>> = Rc::new(RefCell::new(None));
         let available_properties = Rc::new(RefCell::new(Vec::new()));
         let notes = Rc::new(RefCell::new(Vec::new()));
+        let notes_root: Rc<RefCell<Option<PathBuf>>> = Rc::new(RefCell::new(None));
+        let bulk_history: Rc<RefCell<Vec<BulkTransaction>>> = Rc::new(RefCell::new(Vec::new()));
+        #[allow(clippy::type_complexity)]
+        let on_bulk_applied: Rc<RefCell<Option<Box<dyn Fn(&str)>>>> = Rc::new(RefCell::new(None));
+
+        // Handler para operaciones masivas sobre filas seleccionadas
+        if let Some(content_manager) = table_webview.user_content_manager() {
+            content_manager.register_script_message_handler("bulkEdit", None);
+
+            let notes_db_clone = notes_db.clone();
+            let notes_root_clone = notes_root.clone();
+            let bulk_history_clone = bulk_history.clone();
+            let on_bulk_applied_clone = on_bulk_applied.clone();
+            let i18n_clone = i18n.clone();
+            content_manager.connect_script_message_received(Some("bulkEdit"), move |_, result| {
+                // Formato esperado: JSON con {action, noteIds, key, value}
+                let message_str = result.to_str();
+                let clean_msg = message_str.trim_matches('"');
+
+                let Ok(json) = serde_json::from_str::<serde_json::Value>(clean_msg) else {
+                    eprintln!("⚠️ Error parsing bulkEdit JSON: {}", clean_msg);
+                    return;
+                };
+
+                let message = Self::run_bulk_action(
+                    &json,
+                    &notes_db_clone,
+                    &notes_root_clone,
+                    &bulk_history_clone,
+                    &i18n_clone.borrow(),
+                );
+                if let Some(message) = message {
+                    if let Some(ref callback) = *on_bulk_applied_clone.borrow() {
+                        callback(&message);
+                    }
+                }
+            });
+        }
 
         // Conectar botón de columnas UNA SOLA VEZ
         {
@@ -562,7 +622,7 @@ impl BaseTableWidget {
             current_sort: Rc::new(RefCell::new(None)),
             available_properties,
             db_path: Rc::new(RefCell::new(None)),
-            notes_root: Rc::new(RefCell::new(None)),
+            notes_root,
             base_id,
             notes_db,
             on_note_selected,
@@ -571,6 +631,8 @@ impl BaseTableWidget {
             on_source_type_changed: Rc::new(RefCell::new(None)),
             on_view_clicked,
             on_cell_edit,
+            bulk_history,
+            on_bulk_applied,
             is_dark_theme: Rc::new(RefCell::new(Self::detect_system_theme())),
             theme_colors: Rc::new(RefCell::new(GtkThemeColors::default())),
             is_loading: Rc::new(RefCell::new(false)),
@@ -1151,6 +1213,102 @@ impl BaseTableWidget {
         *self.on_cell_edit.borrow_mut() = Some(Box::new(callback));
     }
 
+    /// Configurar callback tras aplicar o deshacer una operación masiva
+    pub fn on_bulk_applied<F: Fn(&str) + 'static>(&self, callback: F) {
+        *self.on_bulk_applied.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Ejecutar una acción masiva recibida desde la tabla
+    ///
+    /// Devuelve el mensaje a mostrar al usuario, o None si la acción no es válida.
+    fn run_bulk_action(
+        json: &serde_json::Value,
+        notes_db: &Rc<RefCell<Option<NotesDatabase>>>,
+        notes_root: &Rc<RefCell<Option<PathBuf>>>,
+        history: &Rc<RefCell<Vec<BulkTransaction>>>,
+        i18n: &I18n,
+    ) -> Option<String> {
+        const MAX_BULK_HISTORY: usize = 20;
+
+        let db_ref = notes_db.borrow();
+        let db = db_ref.as_ref()?;
+        let mut writer = BaseWriter::new(db);
+        if let Some(root) = notes_root.borrow().as_ref() {
+            writer = writer.with_notes_root(root);
+        }
+
+        let text = |field: &str| {
+            json.get(field)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .trim()
+                .to_string()
+        };
+        let action = text("action");
+        let key = text("key");
+        let value = text("value");
+
+        if action == "undo" {
+            let Some(transaction) = history.borrow_mut().pop() else {
+                return Some(i18n.t("base_bulk_nothing_to_undo"));
+            };
+            return Some(match writer.undo_bulk(&transaction) {
+                Ok(count) => i18n.t("base_bulk_undone").replace("{}", &count.to_string()),
+                Err(e) => {
+                    eprintln!("Error al deshacer operación masiva: {}", e);
+                    format!("{}: {}", i18n.t("base_bulk_error"), e)
+                }
+            });
+        }
+
+        let operation = match action.as_str() {
+            "set" if !key.is_empty() => BulkOperation::SetProperty { key, value },
+            "clear" if !key.is_empty() => BulkOperation::ClearProperty(key),
+            "addTag" if !value.is_empty() => BulkOperation::AddTag(value),
+            "removeTag" if !value.is_empty() => BulkOperation::RemoveTag(value),
+            "move" => BulkOperation::MoveToFolder(Some(value).filter(|v| !v.is_empty())),
+            _ => return None,
+        };
+
+        // noteIds pueden venir como string o número
+        let note_ids: Vec<i64> = json
+            .get("noteIds")
+            .and_then(|v| v.as_array())
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|v| {
+                        v.as_i64()
+                            .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if note_ids.is_empty() {
+            return None;
+        }
+
+        match writer.apply_bulk(&note_ids, &operation) {
+            Ok(transaction) => {
+                let count = transaction.len();
+                if !transaction.is_empty() {
+                    let mut history = history.borrow_mut();
+                    history.push(transaction);
+                    if history.len() > MAX_BULK_HISTORY {
+                        history.remove(0);
+                    }
+                }
+                Some(
+                    i18n.t("base_bulk_applied")
+                        .replace("{}", &count.to_string()),
+                )
+            }
+            Err(e) => {
+                eprintln!("Error en operación masiva {:?}: {}", operation, e);
+                Some(format!("{}: {}", i18n.t("base_bulk_error"), e))
+            }
+        }
+    }
+
     /// Cargar una vista específica
     fn load_view(
         &mut self,
@@ -1510,13 +1668,21 @@ impl BaseTableWidget {
                                     },
                                 ]
                             };
-                            let html = Self::render_table_html_static(
-                                &filtered,
-                                &columns,
-                                Language::from_env(),
-                                false,
-                                &[],
-                            );
+                            let chart_config = Self::active_chart_config(base.borrow().as_ref());
+                            let html = match chart_config {
+                                Some(config) => Self::render_chart_html_with_colors(
+                                    &filtered,
+                                    &config,
+                                    &GtkThemeColors::default(),
+                                ),
+                                None => Self::render_table_html_static(
+                                    &filtered,
+                                    &columns,
+                                    Language::from_env(),
+                                    false,
+                                    &[],
+                                ),
+                            };
                             table_webview.load_html(&html, None);
 
                             // Actualizar status
@@ -3360,13 +3526,21 @@ impl BaseTableWidget {
                                     },
                                 ]
                             };
-                            let html = Self::render_table_html_static(
-                                &filtered,
-                                &columns,
-                                Language::from_env(),
-                                false,
-                                &[],
-                            );
+                            let chart_config = Self::active_chart_config(base.borrow().as_ref());
+                            let html = match chart_config {
+                                Some(config) => Self::render_chart_html_with_colors(
+                                    &filtered,
+                                    &config,
+                                    &GtkThemeColors::default(),
+                                ),
+                                None => Self::render_table_html_static(
+                                    &filtered,
+                                    &columns,
+                                    Language::from_env(),
+                                    false,
+                                    &[],
+                                ),
+                            };
                             table_webview.load_html(&html, None);
 
                             // Actualizar status bar
@@ -3518,6 +3692,15 @@ impl BaseTableWidget {
 
     /// Actualización interna del WebView (usada por update_data y force_update_webview)
     fn update_webview_internal(&self, notes: &[NoteWithProperties]) {
+        // Las vistas Chart se dibujan como SVG en lugar de tabla
+        let chart_config = Self::active_chart_config(self.base.borrow().as_ref());
+        if let Some(config) = chart_config {
+            let colors = self.theme_colors.borrow().clone();
+            let html = Self::render_chart_html_with_colors(notes, &config, &colors);
+            self.table_webview.load_html(&html, None);
+            return;
+        }
+
         // Obtener las columnas configuradas de la vista actual
         let columns = if let Some(base) = self.base.borrow().as_ref() {
             if let Some(view) = base.views.get(base.active_view) {
@@ -3563,6 +3746,60 @@ impl BaseTableWidget {
         self.table_webview.load_html(&html, None);
     }
 
+    /// Configuración del gráfico si la vista activa es de tipo Chart
+    fn active_chart_config(base: Option<&Base>) -> Option<ChartConfig> {
+        base.and_then(|b| b.active_view())
+            .filter(|v| v.view_type == ViewType::Chart)
+            .map(|v| {
+                v.chart.clone().unwrap_or_else(|| {
+                    let x = v.group_by.clone().unwrap_or_else(|| "folder".to_string());
+                    ChartConfig::new(ChartKind::Bar, x)
+                })
+            })
+    }
+
+    /// Generar el HTML para una vista Chart (SVG autocontenido)
+    fn render_chart_html_with_colors(
+        notes: &[NoteWithProperties],
+        config: &ChartConfig,
+        colors: &GtkThemeColors,
+    ) -> String {
+        let colors = colors.to_preview_colors();
+        let bg_primary = colors.bg_primary.clone();
+        let fg_primary = colors.fg_primary.clone();
+        let svg = render_chart_svg(config, notes, colors);
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+html, body {{
+    margin: 0;
+    min-height: 100vh;
+    background-color: {bg_primary};
+    color: {fg_primary};
+    font-family: 'Inter', -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+}}
+
+.chart-container {{
+    max-width: 960px;
+    margin: 0 auto;
+    padding: 16px;
+}}
+    </style>
+</head>
+<body>
+    <div class="chart-container">{svg}</div>
+</body>
+</html>"#,
+            bg_primary = bg_primary,
+            fg_primary = fg_primary,
+            svg = svg,
+        )
+    }
+
     /// Generar el HTML para la tabla
     fn render_table_html(&self, notes: &[NoteWithProperties], columns: &[ColumnConfig]) -> String {
         // Determinar si es editable:
@@ -3630,6 +3867,26 @@ impl BaseTableWidget {
         } else {
             ("Search in table...", "items", "No notes found")
         };
+        // (seleccionadas, propiedad, valor, asignar, limpiar, mover)
+        let bulk_labels = if language == Language::Spanish {
+            (
+                "seleccionadas",
+                "Propiedad",
+                "Valor / tag / carpeta",
+                "Asignar",
+                "Limpiar",
+                "Mover",
+            )
+        } else {
+            (
+                "selected",
+                "Property",
+                "Value / tag / folder",
+                "Set",
+                "Clear",
+                "Move",
+            )
+        };
 
         // CSS con colores dinámicos del tema GTK
         let css = format!(
@@ -3798,6 +4055,62 @@ tr.search-highlight td {
     background-color: rgba(137, 180, 250, 0.15);
 }
 
+/* Selección múltiple (Ctrl/Shift + clic) */
+tr.row-selected td {
+    background-color: rgba(137, 180, 250, 0.25);
+}
+
+.bulk-bar {
+    display: none;
+    position: fixed;
+    bottom: 16px;
+    left: 50%;
+    transform: translateX(-50%);
+    z-index: 200;
+    gap: 6px;
+    align-items: center;
+    padding: 8px 12px;
+    border: 1px solid var(--border);
+    border-radius: 8px;
+    background: var(--bg-secondary);
+    box-shadow: 0 4px 16px rgba(0, 0, 0, 0.3);
+}
+
+.bulk-bar.visible {
+    display: flex;
+}
+
+.bulk-bar input {
+    width: 140px;
+    padding: 4px 8px;
+    font-size: 13px;
+    border: 1px solid var(--border);
+    border-radius: 4px;
+    background: var(--bg-primary);
+    color: var(--fg-primary);
+    outline: none;
+}
+
+.bulk-bar button {
+    padding: 4px 10px;
+    font-size: 13px;
+    border: 1px solid var(--border);
+    border-radius: 4px;
+    background: var(--bg-tertiary);
+    color: var(--fg-primary);
+    cursor: pointer;
+}
+
+.bulk-bar button:hover {
+    border-color: var(--accent);
+}
+
+.bulk-count {
+    font-size: 13px;
+    color: var(--fg-muted);
+    margin-right: 4px;
+}
+
 /* Link de nota clickeable */
 .note-link-cell {
     color: var(--fg-secondary);
@@ -4108,8 +4421,8 @@ function filterTable(query) {{
 
 // Listener en document para capturar todos los clics
 document.addEventListener('click', function(event) {{
-    // Ignorar clics en el campo de búsqueda
-    if (event.target.closest('.search-container')) {{
+    // Ignorar clics en el campo de búsqueda y en la barra de acciones masivas
+    if (event.target.closest('.search-container') || event.target.closest('.bulk-bar')) {{
         return;
     }}
     
@@ -4132,6 +4445,12 @@ document.addEventListener('click', function(event) {{
     
     // Verificar si el clic fue en una fila de la tabla
     var row = event.target.closest('tr[data-path]');
+    if (row && (event.ctrlKey || event.metaKey || event.shiftKey)) {{
+        // Ctrl/Shift + clic = selección múltiple
+        event.preventDefault();
+        toggleRowSelection(row, event.shiftKey);
+        return;
+    }}
     if (row) {{
         // Clic en fila - enviar el path de la nota o el nombre si no hay path
         var path = row.dataset.path;
@@ -4171,6 +4490,71 @@ document.addEventListener('keydown', function(event) {{
     }}
 }});
 
+// === Selección múltiple y operaciones masivas ===
+var lastSelectedRow = null;
+
+function selectedRows() {{
+    return Array.prototype.slice.call(document.querySelectorAll('tr.row-selected'));
+}}
+
+function updateBulkBar() {{
+    var bar = document.getElementById('bulk-bar');
+    if (!bar) return;
+    var count = selectedRows().length;
+    bar.classList.toggle('visible', count > 0);
+    var countEl = document.getElementById('bulk-count');
+    if (countEl) countEl.textContent = count;
+}}
+
+function toggleRowSelection(row, range) {{
+    var rows = Array.prototype.slice.call(document.querySelectorAll('tbody tr[data-path]'));
+    if (range && lastSelectedRow && rows.indexOf(lastSelectedRow) >= 0) {{
+        var from = rows.indexOf(lastSelectedRow);
+        var to = rows.indexOf(row);
+        var step = from <= to ? 1 : -1;
+        for (var i = from; i !== to + step; i += step) {{
+            rows[i].classList.add('row-selected');
+        }}
+    }} else {{
+        row.classList.toggle('row-selected');
+    }}
+    lastSelectedRow = row;
+    updateBulkBar();
+}}
+
+function clearSelection() {{
+    selectedRows().forEach(function(row) {{ row.classList.remove('row-selected'); }});
+    lastSelectedRow = null;
+    updateBulkBar();
+}}
+
+function runBulk(action) {{
+    var noteIds = selectedRows().map(function(row) {{ return row.dataset.noteId; }});
+    if (action !== 'undo' && noteIds.length === 0) return;
+    var message = JSON.stringify({{
+        action: action,
+        noteIds: noteIds,
+        key: (document.getElementById('bulk-key') || {{}}).value || '',
+        value: (document.getElementById('bulk-value') || {{}}).value || ''
+    }});
+    if (window.webkit && window.webkit.messageHandlers && window.webkit.messageHandlers.bulkEdit) {{
+        window.webkit.messageHandlers.bulkEdit.postMessage(message);
+    }}
+}}
+
+// Ctrl+Z deshace la última operación masiva; Escape limpia la selección
+document.addEventListener('keydown', function(event) {{
+    var target = event.target;
+    var editing = target && (target.isContentEditable || target.tagName === 'INPUT');
+    if (editing) return;
+    if ((event.ctrlKey || event.metaKey) && event.key === 'z') {{
+        event.preventDefault();
+        runBulk('undo');
+    }} else if (event.key === 'Escape' && selectedRows().length > 0) {{
+        clearSelection();
+    }}
+}});
+
 // === Manejo de celdas editables ===
 function handleCellEdit(cell) {{
     var newValue = cell.textContent.trim();
@@ -4568,6 +4952,29 @@ document.addEventListener('keydown', function(event) {{
 </div>
 "#, search_placeholder, notes_count, items_label));
 
+            // Barra de acciones masivas (visible con filas seleccionadas)
+            html.push_str(&format!(
+                r#"<div id="bulk-bar" class="bulk-bar">
+    <span class="bulk-count"><span id="bulk-count">0</span> {}</span>
+    <input type="text" id="bulk-key" placeholder="{}" autocomplete="off">
+    <input type="text" id="bulk-value" placeholder="{}" autocomplete="off">
+    <button onclick="runBulk('set')">{}</button>
+    <button onclick="runBulk('clear')">{}</button>
+    <button onclick="runBulk('addTag')">+ Tag</button>
+    <button onclick="runBulk('removeTag')">− Tag</button>
+    <button onclick="runBulk('move')">{}</button>
+    <button onclick="runBulk('undo')" title="Ctrl+Z">↶</button>
+    <button onclick="clearSelection()">✕</button>
+</div>
+"#,
+                bulk_labels.0,
+                bulk_labels.1,
+                bulk_labels.2,
+                bulk_labels.3,
+                bulk_labels.4,
+                bulk_labels.5
+            ));
+
             html.push_str("<table>\n<thead>\n<tr>\n");
 
             // Columna # para números de fila
@@ -5960,13 +6367,21 @@ fn apply_sort_and_refresh(
             Vec::new(),
         )
     };
-    let html = BaseTableWidget::render_table_html_static(
-        &filtered,
-        &columns,
-        Language::from_env(),
-        false,
-        &special_rows,
-    );
+    let chart_config = BaseTableWidget::active_chart_config(base.borrow().as_ref());
+    let html = match chart_config {
+        Some(config) => BaseTableWidget::render_chart_html_with_colors(
+            &filtered,
+            &config,
+            &GtkThemeColors::default(),
+        ),
+        None => BaseTableWidget::render_table_html_static(
+            &filtered,
+            &columns,
+            Language::from_env(),
+            false,
+            &special_rows,
+        ),
+    };
     table_webview.load_html(&html, None);
 
     // Actualizar status
@@ -0,0 +1,642 @@
+//! Contenedores ZIP mínimos para los formatos de documento (EPUB, DOCX) y
+//! para leer exportaciones de otras aplicaciones (Notion, Google Takeout)
+//!
+//! El escritor solo crea entradas sin comprimir (método "stored"): es lo que
+//! exige el `mimetype` de un EPUB y basta para el resto, porque las imágenes ya
+//! van comprimidas y el XML pesa poco. El lector admite "stored" y "deflate",
+//! sin ZIP64 ni cifrado.
+
+use std::path::{Component, Path, PathBuf};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Not a ZIP archive")]
+    NotZip,
+    #[error("Corrupt ZIP entry: {0}")]
+    Corrupt(String),
+    #[error("Unsupported compression method {method} in {name}")]
+    UnsupportedMethod { name: String, method: u16 },
+    #[error("Invalid deflate stream")]
+    Inflate,
+    #[error("Uncompressed data exceeds {0} bytes")]
+    TooLarge(usize),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Tabla del CRC-32 (polinomio 0xEDB88320), calculada en compilación
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Entrada ya escrita, para el directorio central
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Escritor de un ZIP en memoria
+pub struct ZipWriter {
+    buffer: Vec<u8>,
+    entries: Vec<Entry>,
+    dos_time: u16,
+    dos_date: u16,
+}
+
+impl Default for ZipWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        use chrono::{Datelike, Timelike};
+        let now = chrono::Local::now();
+        Self {
+            buffer: Vec::new(),
+            entries: Vec::new(),
+            dos_time: ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16,
+            dos_date: (((now.year().max(1980) - 1980) as u32) << 9 | (now.month() << 5) | now.day())
+                as u16,
+        }
+    }
+
+    /// Añadir un fichero (las rutas usan `/`)
+    pub fn add(&mut self, name: &str, data: &[u8]) {
+        let crc = crc32(data);
+        let offset = self.buffer.len() as u32;
+        let size = data.len() as u32;
+
+        self.put_u32(0x0403_4b50);
+        self.put_u16(20); // versión necesaria
+        self.put_u16(0x0800); // nombres en UTF-8
+        self.put_u16(0); // sin comprimir
+        self.put_u16(self.dos_time);
+        self.put_u16(self.dos_date);
+        self.put_u32(crc);
+        self.put_u32(size);
+        self.put_u32(size);
+        self.put_u16(name.len() as u16);
+        self.put_u16(0);
+        self.buffer.extend_from_slice(name.as_bytes());
+        self.buffer.extend_from_slice(data);
+
+        self.entries.push(Entry {
+            name: name.to_string(),
+            crc,
+            size,
+            offset,
+        });
+    }
+
+    /// Cerrar el ZIP con el directorio central y devolver sus bytes
+    pub fn finish(mut self) -> Vec<u8> {
+        let start = self.buffer.len() as u32;
+        let entries = std::mem::take(&mut self.entries);
+        for entry in &entries {
+            self.put_u32(0x0201_4b50);
+            self.put_u16(20); // versión que lo creó
+            self.put_u16(20);
+            self.put_u16(0x0800);
+            self.put_u16(0);
+            self.put_u16(self.dos_time);
+            self.put_u16(self.dos_date);
+            self.put_u32(entry.crc);
+            self.put_u32(entry.size);
+            self.put_u32(entry.size);
+            self.put_u16(entry.name.len() as u16);
+            self.put_u16(0); // extra
+            self.put_u16(0); // comentario
+            self.put_u16(0); // disco
+            self.put_u16(0); // atributos internos
+            self.put_u32(0); // atributos externos
+            self.put_u32(entry.offset);
+            self.buffer.extend_from_slice(entry.name.as_bytes());
+        }
+        let size = self.buffer.len() as u32 - start;
+
+        self.put_u32(0x0605_4b50);
+        self.put_u16(0);
+        self.put_u16(0);
+        self.put_u16(entries.len() as u16);
+        self.put_u16(entries.len() as u16);
+        self.put_u32(size);
+        self.put_u32(start);
+        self.put_u16(0);
+        self.buffer
+    }
+
+    fn put_u16(&mut self, value: u16) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_u32(&mut self, value: u32) {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Entrada del directorio central de un ZIP leído
+#[derive(Debug, Clone)]
+pub struct ZipEntry {
+    pub name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: usize,
+    size: usize,
+    header_offset: usize,
+}
+
+impl ZipEntry {
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+/// Lector de un ZIP en memoria
+pub struct ZipReader {
+    data: Vec<u8>,
+    entries: Vec<ZipEntry>,
+}
+
+impl ZipReader {
+    pub fn open(path: &Path) -> Result<Self, ArchiveError> {
+        Self::new(std::fs::read(path)?)
+    }
+
+    pub fn new(data: Vec<u8>) -> Result<Self, ArchiveError> {
+        // Fin del directorio central: 22 bytes más un comentario de hasta 64 KiB
+        let search_from = data.len().saturating_sub(22 + 0xFFFF);
+        let end = (search_from..data.len().saturating_sub(21))
+            .rev()
+            .find(|&i| data[i..i + 4] == [0x50, 0x4b, 0x05, 0x06])
+            .ok_or(ArchiveError::NotZip)?;
+        let count = read_u16(&data, end + 10)? as usize;
+        let mut offset = read_u32(&data, end + 16)? as usize;
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            if read_u32(&data, offset)? != 0x0201_4b50 {
+                return Err(ArchiveError::Corrupt(format!(
+                    "directorio central en {}",
+                    offset
+                )));
+            }
+            let name_len = read_u16(&data, offset + 28)? as usize;
+            let extra_len = read_u16(&data, offset + 30)? as usize;
+            let comment_len = read_u16(&data, offset + 32)? as usize;
+            let name = data
+                .get(offset + 46..offset + 46 + name_len)
+                .ok_or_else(|| ArchiveError::Corrupt(format!("nombre en {}", offset)))?;
+            entries.push(ZipEntry {
+                name: String::from_utf8_lossy(name).replace('\\', "/"),
+                method: read_u16(&data, offset + 10)?,
+                crc: read_u32(&data, offset + 16)?,
+                compressed_size: read_u32(&data, offset + 20)? as usize,
+                size: read_u32(&data, offset + 24)? as usize,
+                header_offset: read_u32(&data, offset + 42)? as usize,
+            });
+            offset += 46 + name_len + extra_len + comment_len;
+        }
+        Ok(Self { data, entries })
+    }
+
+    pub fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    /// Contenido descomprimido de una entrada (con el CRC comprobado)
+    pub fn read(&self, entry: &ZipEntry) -> Result<Vec<u8>, ArchiveError> {
+        let corrupt = || ArchiveError::Corrupt(entry.name.clone());
+        let offset = entry.header_offset;
+        if read_u32(&self.data, offset)? != 0x0403_4b50 {
+            return Err(corrupt());
+        }
+        let name_len = read_u16(&self.data, offset + 26)? as usize;
+        let extra_len = read_u16(&self.data, offset + 28)? as usize;
+        let start = offset + 30 + name_len + extra_len;
+        let raw = self
+            .data
+            .get(start..start + entry.compressed_size)
+            .ok_or_else(corrupt)?;
+
+        let data = match entry.method {
+            0 => raw.to_vec(),
+            8 => inflate(raw, entry.size)?,
+            method => {
+                return Err(ArchiveError::UnsupportedMethod {
+                    name: entry.name.clone(),
+                    method,
+                });
+            }
+        };
+        if data.len() != entry.size || crc32(&data) != entry.crc {
+            return Err(corrupt());
+        }
+        Ok(data)
+    }
+
+    /// Extraer todo en `dir`; las rutas que saldrían de la carpeta se ignoran
+    ///
+    /// `budget` son los bytes que aún se pueden escribir y se descuenta con cada
+    /// entrada, para que varios ZIP (p. ej. anidados) compartan el mismo límite.
+    pub fn extract_to(&self, dir: &Path, budget: &mut usize) -> Result<Vec<PathBuf>, ArchiveError> {
+        let mut written = Vec::new();
+        for entry in &self.entries {
+            if entry.is_dir() {
+                continue;
+            }
+            let Some(relative) = safe_path(&entry.name) else {
+                continue;
+            };
+            // `read` nunca devuelve más de `entry.size` bytes
+            *budget = budget
+                .checked_sub(entry.size)
+                .ok_or(ArchiveError::TooLarge(MAX_EXTRACTED_SIZE))?;
+            let path = dir.join(relative);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, self.read(entry)?)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+/// Límite de bytes descomprimidos al extraer una exportación completa
+pub const MAX_EXTRACTED_SIZE: usize = 4 << 30;
+
+/// Ruta relativa de una entrada sin `..` ni raíz
+fn safe_path(name: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, ArchiveError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(ArchiveError::NotZip)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, ArchiveError> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or(ArchiveError::NotZip)
+}
+
+// ==================== DEFLATE (RFC 1951) ====================
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Orden de las longitudes del código de longitudes en un bloque dinámico
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Lectura de bits en el orden de DEFLATE (el menos significativo primero)
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, n: u32) -> Result<u32, ArchiveError> {
+        while self.count < n {
+            let byte = *self.data.get(self.position).ok_or(ArchiveError::Inflate)?;
+            self.position += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u64 << n) - 1) as u32;
+        self.buffer >>= n;
+        self.count -= n;
+        Ok(value)
+    }
+
+    /// Descartar los bits hasta el siguiente byte
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// Código Huffman canónico: cuántos códigos hay de cada longitud y los
+/// símbolos ordenados por código
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; 16];
+        for length in 1..16 {
+            offsets[length] = offsets[length - 1] + counts[length - 1];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, ArchiveError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = i32::from(self.counts[length]);
+            if code - first < count {
+                return self
+                    .symbols
+                    .get((index + code - first) as usize)
+                    .copied()
+                    .ok_or(ArchiveError::Inflate);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(ArchiveError::Inflate)
+    }
+}
+
+/// Descomprimir un flujo DEFLATE sin cabecera zlib
+///
+/// Falla en cuanto la salida pasaría de `limit` bytes, sin llegar a reservarlos.
+pub fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, ArchiveError> {
+    let mut reader = BitReader {
+        data,
+        position: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut out = Vec::with_capacity(limit.min(data.len().saturating_mul(3)));
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let start = reader.position;
+                let header = data.get(start..start + 4).ok_or(ArchiveError::Inflate)?;
+                let length = u16::from_le_bytes([header[0], header[1]]);
+                if length != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err(ArchiveError::Inflate);
+                }
+                let block = data
+                    .get(start + 4..start + 4 + length as usize)
+                    .ok_or(ArchiveError::Inflate)?;
+                if out.len() + block.len() > limit {
+                    return Err(ArchiveError::TooLarge(limit));
+                }
+                out.extend_from_slice(block);
+                reader.position = start + 4 + length as usize;
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5u8; 30]);
+                inflate_block(&mut reader, &mut out, limit, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_tables(&mut reader)?;
+                inflate_block(&mut reader, &mut out, limit, &literals, &distances)?;
+            }
+            _ => return Err(ArchiveError::Inflate),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+/// Tablas de un bloque con códigos dinámicos
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), ArchiveError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_count = reader.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(ArchiveError::Inflate);
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_count] {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let symbol = code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => (
+                *lengths.last().ok_or(ArchiveError::Inflate)?,
+                3 + reader.bits(2)?,
+            ),
+            17 => (0, 3 + reader.bits(3)?),
+            18 => (0, 11 + reader.bits(7)?),
+            _ => return Err(ArchiveError::Inflate),
+        };
+        for _ in 0..repeat {
+            lengths.push(value);
+        }
+    }
+    if lengths.len() > literal_count + distance_count || lengths[256] == 0 {
+        return Err(ArchiveError::Inflate);
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), ArchiveError> {
+    loop {
+        let symbol = literals.decode(reader)?;
+        if symbol != 256 && out.len() >= limit {
+            return Err(ArchiveError::TooLarge(limit));
+        }
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = (symbol - 257) as usize;
+                if index >= LENGTH_BASE.len() {
+                    return Err(ArchiveError::Inflate);
+                }
+                let length = LENGTH_BASE[index] as usize
+                    + reader.bits(u32::from(LENGTH_EXTRA[index]))? as usize;
+                let index = distances.decode(reader)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err(ArchiveError::Inflate);
+                }
+                let distance = DISTANCE_BASE[index] as usize
+                    + reader.bits(u32::from(DISTANCE_EXTRA[index]))? as usize;
+                if distance > out.len() {
+                    return Err(ArchiveError::Inflate);
+                }
+                if out.len() + length > limit {
+                    return Err(ArchiveError::TooLarge(limit));
+                }
+                // La copia puede solaparse consigo misma (`distance < length`)
+                let start = out.len() - distance;
+                for i in 0..length {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zip_writer() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let mut zip = ZipWriter::new();
+        zip.add("mimetype", b"application/epub+zip");
+        zip.add("a/b.txt", b"hola");
+        let bytes = zip.finish();
+
+        // La primera entrada empieza en 0 y su nombre/datos van en claro
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        assert_eq!(&bytes[30..38], b"mimetype");
+        assert_eq!(&bytes[38..58], b"application/epub+zip");
+        // Fin del directorio central: dos entradas
+        let end = bytes.len() - 22;
+        assert_eq!(&bytes[end..end + 4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([bytes[end + 10], bytes[end + 11]]), 2);
+    }
+
+    #[test]
+    fn test_zip_reader_and_inflate() {
+        let mut zip = ZipWriter::new();
+        zip.add("Export/Página.md", "# Página\n".as_bytes());
+        zip.add("../fuera.txt", b"no");
+        let reader = ZipReader::new(zip.finish()).unwrap();
+        assert_eq!(reader.entries().len(), 2);
+        assert_eq!(reader.entries()[0].name, "Export/Página.md");
+        assert_eq!(
+            reader.read(&reader.entries()[0]).unwrap(),
+            "# Página\n".as_bytes()
+        );
+
+        let dir = std::env::temp_dir().join("notnative_zip_reader_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut budget = MAX_EXTRACTED_SIZE;
+        let written = reader.extract_to(&dir, &mut budget).unwrap();
+        assert_eq!(written, [dir.join("Export/Página.md")]);
+        assert_eq!(budget, MAX_EXTRACTED_SIZE - "# Página\n".len());
+        // Sin presupuesto suficiente no se escribe nada más
+        let mut budget = 3;
+        assert!(matches!(
+            reader.extract_to(&dir, &mut budget),
+            Err(ArchiveError::TooLarge(_))
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(matches!(
+            ZipReader::new(b"no es un zip".to_vec()),
+            Err(ArchiveError::NotZip)
+        ));
+
+        // Bloque sin comprimir, con códigos fijos y con códigos dinámicos
+        assert_eq!(
+            inflate(&[0x01, 0x02, 0x00, 0xfd, 0xff, b'o', b'k'], 2).unwrap(),
+            b"ok"
+        );
+        let fixed = [0xcb, 0xc8, 0xcf, 0x49, 0x54, 0xc8, 0x80, 0x11, 0x00];
+        assert_eq!(inflate(&fixed, 14).unwrap(), b"hola hola hola");
+        // La salida se corta en cuanto supera el tamaño esperado
+        assert!(matches!(
+            inflate(&fixed, 13),
+            Err(ArchiveError::TooLarge(13))
+        ));
+        assert!(matches!(
+            inflate(&[0x01, 0x02, 0x00, 0xfd, 0xff, b'o', b'k'], 1),
+            Err(ArchiveError::TooLarge(1))
+        ));
+        let dynamic = [
+            0x0d, 0xca, 0x31, 0x0a, 0x80, 0x30, 0x10, 0x04, 0xc0, 0xaf, 0xec, 0x0b, 0xfc, 0x80,
+            0xa5, 0xbd, 0x8d, 0x60, 0xbf, 0x49, 0x4e, 0x10, 0xf4, 0x56, 0x72, 0x27, 0xf8, 0x7c,
+            0xd3, 0x4d, 0x31, 0xab, 0xf2, 0x94, 0xc3, 0xbe, 0x47, 0x3d, 0x89, 0xc2, 0xb0, 0x40,
+            0x33, 0x34, 0xa6, 0x02, 0x55, 0xb7, 0xb0, 0x6c, 0xfb, 0x80, 0xa3, 0xb2, 0x58, 0xb5,
+            0xce, 0x98, 0x07, 0x1b, 0x71, 0x9c, 0x17, 0x31, 0xf6, 0xeb, 0x84, 0x2b, 0x39, 0xfd,
+        ];
+        assert_eq!(
+            inflate(&dynamic, 76).unwrap(),
+            b"Notion exporta bases de datos como CSV con cabeceras; cada fila es una nota."
+        );
+        assert!(inflate(&fixed[..4], 14).is_err());
+    }
+}
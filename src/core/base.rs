@@ -632,6 +632,89 @@ pub enum ViewType {
     List,
     Board, // Kanban-style
     Gallery,
+    Chart, // Gráfico SVG (ver ChartConfig)
+}
+
+/// Tipo de gráfico para ViewType::Chart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartKind {
+    #[default]
+    Bar,
+    Line,
+    Pie,
+    Scatter,
+}
+
+/// Agregación aplicada a los valores Y de cada grupo del eje X
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartAggregation {
+    /// Número de notas en el grupo (no necesita propiedad Y)
+    #[default]
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl ChartAggregation {
+    /// Aplicar la agregación a una lista de valores
+    pub fn apply(&self, values: &[f64]) -> f64 {
+        match self {
+            ChartAggregation::Count => values.len() as f64,
+            ChartAggregation::Sum => values.iter().sum(),
+            ChartAggregation::Avg => {
+                if values.is_empty() {
+                    0.0
+                } else {
+                    values.iter().sum::<f64>() / values.len() as f64
+                }
+            }
+            ChartAggregation::Min => values.iter().copied().fold(f64::NAN, f64::min),
+            ChartAggregation::Max => values.iter().copied().fold(f64::NAN, f64::max),
+        }
+    }
+}
+
+/// Configuración de un gráfico (vista Chart o bloque ```chart en notas)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChartConfig {
+    /// Tipo de gráfico
+    #[serde(default, alias = "type")]
+    pub kind: ChartKind,
+
+    /// Propiedad del eje X (categoría, fecha o número en scatter)
+    pub x: String,
+
+    /// Propiedad del eje Y (numérica). None = contar notas
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+
+    /// Agregación de los valores Y por cada valor de X
+    #[serde(default, alias = "aggregation")]
+    pub aggregate: ChartAggregation,
+
+    /// Título opcional del gráfico
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl ChartConfig {
+    pub fn new(kind: ChartKind, x: impl Into<String>) -> Self {
+        Self {
+            kind,
+            x: x.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_y(mut self, y: impl Into<String>, aggregate: ChartAggregation) -> Self {
+        self.y = Some(y.into());
+        self.aggregate = aggregate;
+        self
+    }
 }
 
 /// Tipo de fuente de datos de la Base
@@ -680,6 +763,10 @@ pub struct BaseView {
     /// Filas especiales con fórmulas (totales, promedios, etc.)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub special_rows: Vec<SpecialRow>,
+
+    /// Configuración del gráfico (solo para ViewType::Chart)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart: Option<ChartConfig>,
 }
 
 impl BaseView {
@@ -697,6 +784,7 @@ impl BaseView {
             group_by: None,
             editable: false,
             special_rows: Vec::new(),
+            chart: None,
         }
     }
 
@@ -719,6 +807,13 @@ impl BaseView {
         view
     }
 
+    pub fn chart(name: impl Into<String>, config: ChartConfig) -> Self {
+        let mut view = Self::new(name);
+        view.view_type = ViewType::Chart;
+        view.chart = Some(config);
+        view
+    }
+
     /// Crear una vista para registros agrupados (sin columnas por defecto)
     pub fn grouped_records(name: impl Into<String>) -> Self {
         Self {
@@ -730,6 +825,7 @@ impl BaseView {
             group_by: None,
             editable: false,
             special_rows: Vec::new(),
+            chart: None,
         }
     }
 
@@ -748,6 +844,7 @@ impl BaseView {
             group_by: None,
             editable: true,
             special_rows: Vec::new(),
+            chart: None,
         }
     }
 }
@@ -1037,6 +1134,25 @@ mod tests {
         assert_eq!(parsed.source_folder, Some("projects".to_string()));
     }
 
+    #[test]
+    fn test_chart_view_serialization() {
+        let view = BaseView::chart(
+            "Horas",
+            ChartConfig::new(ChartKind::Line, "date").with_y("hours", ChartAggregation::Sum),
+        );
+        let base = Base::with_view("Tracking", view);
+
+        let yaml = base.serialize().unwrap();
+        let parsed = Base::parse(&yaml).unwrap();
+        let view = parsed.active_view().unwrap();
+
+        assert_eq!(view.view_type, ViewType::Chart);
+        let chart = view.chart.as_ref().unwrap();
+        assert_eq!(chart.kind, ChartKind::Line);
+        assert_eq!(chart.y.as_deref(), Some("hours"));
+        assert_eq!(chart.aggregate, ChartAggregation::Sum);
+    }
+
     #[test]
     fn test_base_view_columns() {
         let view = BaseView::table("Tasks");
@@ -3,6 +3,7 @@ use std::path::Path;
 
 use super::base::{Base, BaseView, FilterGroup, SortConfig, SortDirection};
 use super::database::{NoteMetadata, NotesDatabase, Result as DbResult};
+use super::inline_query::InlineQuery;
 use super::property::PropertyValue;
 
 /// Resultado de una nota con sus propiedades extraídas
@@ -53,6 +54,40 @@ impl<'a> BaseQueryEngine<'a> {
         self.query_view(view, base.source_folder.as_deref())
     }
 
+    /// Cargar una Base guardada por nombre y ejecutar una de sus vistas
+    /// (la vista activa si `view_name` es None)
+    pub fn query_saved_base(
+        &self,
+        name: &str,
+        view_name: Option<&str>,
+    ) -> DbResult<(BaseView, Vec<NoteWithProperties>)> {
+        let (_, _, _, _, config_yaml, _) = self.db.get_base_by_name(name)?.ok_or_else(|| {
+            super::database::DatabaseError::NoteNotFound(format!("Base '{}'", name))
+        })?;
+
+        let base = Base::parse(&config_yaml)
+            .map_err(|e| super::database::DatabaseError::NoteNotFound(e.to_string()))?;
+
+        let view = match view_name {
+            Some(view_name) => base
+                .views
+                .iter()
+                .find(|v| v.name.eq_ignore_ascii_case(view_name)),
+            None => base.active_view(),
+        }
+        .cloned()
+        .ok_or_else(|| {
+            super::database::DatabaseError::NoteNotFound(format!(
+                "View '{}' in base '{}'",
+                view_name.unwrap_or_default(),
+                name
+            ))
+        })?;
+
+        let results = self.query_view(&view, base.source_folder.as_deref())?;
+        Ok((view, results))
+    }
+
     /// Ejecutar una query para una vista específica
     pub fn query_view(
         &self,
@@ -86,6 +121,19 @@ impl<'a> BaseQueryEngine<'a> {
         Ok(results)
     }
 
+    /// Ejecutar una query inline (bloques ```query): WHERE/SORT de la vista
+    /// equivalente, fuentes del FROM y LIMIT
+    pub fn query_inline(&self, query: &InlineQuery) -> DbResult<Vec<NoteWithProperties>> {
+        let mut results = self.query_view(&query.to_view(), None)?;
+        results.retain(|note| query.sources.evaluate(&note.properties));
+
+        if let Some(limit) = query.limit {
+            results.truncate(limit);
+        }
+
+        Ok(results)
+    }
+
     /// Cargar propiedades de una nota desde la BD (propiedades inline indexadas)
     fn load_note_properties(&self, note: &NoteMetadata) -> DbResult<NoteWithProperties> {
         let mut properties = HashMap::new();
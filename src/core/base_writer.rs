@@ -4,11 +4,13 @@
 //! desde la interfaz de Base de datos. Los cambios en la tabla se propagan
 //! a los archivos .md correspondientes.
 
+use regex::Regex;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 use super::database::NotesDatabase;
+use super::frontmatter::{Frontmatter, FrontmatterError, update_tags};
 use super::inline_property::InlinePropertyParser;
 
 #[derive(Debug, Error)]
@@ -27,18 +29,320 @@ pub enum BaseWriterError {
 
     #[error("Database error: {0}")]
     Database(String),
+
+    #[error("Frontmatter error: {0}")]
+    Frontmatter(#[from] FrontmatterError),
+
+    #[error("Notes root not configured")]
+    NotesRootMissing,
+
+    #[error("Destination already exists: {0}")]
+    DestinationExists(String),
+
+    #[error("Notes changed since the bulk edit: {}", .0.join(", "))]
+    ModifiedSince(Vec<String>),
 }
 
 pub type Result<T> = std::result::Result<T, BaseWriterError>;
 
+/// Operación masiva sobre varias filas seleccionadas de una Base
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkOperation {
+    /// Asignar `key::value` (reemplaza el valor o añade la propiedad al final)
+    SetProperty { key: String, value: String },
+    /// Eliminar la propiedad de la nota
+    ClearProperty(String),
+    /// Añadir un tag al frontmatter
+    AddTag(String),
+    /// Quitar un tag (frontmatter e inline)
+    RemoveTag(String),
+    /// Mover la nota a otra carpeta (None = raíz)
+    MoveToFolder(Option<String>),
+}
+
+/// Estado de una nota antes y después de aplicarle una operación masiva
+#[derive(Debug, Clone)]
+struct NoteSnapshot {
+    note_id: i64,
+    name: String,
+    folder: Option<String>,
+    path: String,
+    content: String,
+    new_folder: Option<String>,
+    new_path: String,
+    new_content: String,
+}
+
+/// Operación masiva ya aplicada
+///
+/// Guarda el contenido previo de cada nota afectada para que un único
+/// [`BaseWriter::undo_bulk`] las restaure todas.
+#[derive(Debug, Clone)]
+pub struct BulkTransaction {
+    pub operation: BulkOperation,
+    snapshots: Vec<NoteSnapshot>,
+}
+
+impl BulkTransaction {
+    /// Número de notas modificadas
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// IDs de las notas modificadas
+    pub fn note_ids(&self) -> Vec<i64> {
+        self.snapshots.iter().map(|s| s.note_id).collect()
+    }
+}
+
 /// Escritor bidireccional para modificar propiedades inline en notas
 pub struct BaseWriter<'a> {
     db: &'a NotesDatabase,
+    notes_root: Option<PathBuf>,
 }
 
 impl<'a> BaseWriter<'a> {
     pub fn new(db: &'a NotesDatabase) -> Self {
-        Self { db }
+        Self {
+            db,
+            notes_root: None,
+        }
+    }
+
+    /// Directorio raíz de las notas (necesario para mover notas de carpeta)
+    pub fn with_notes_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.notes_root = Some(root.into());
+        self
+    }
+
+    /// Aplicar una operación masiva a varias notas como una sola transacción
+    ///
+    /// Si alguna nota falla, las ya modificadas se restauran antes de devolver
+    /// el error. Las notas que no cambian no se registran en la transacción.
+    pub fn apply_bulk(
+        &self,
+        note_ids: &[i64],
+        operation: &BulkOperation,
+    ) -> Result<BulkTransaction> {
+        let mut snapshots: Vec<NoteSnapshot> = Vec::new();
+
+        for &note_id in note_ids {
+            if snapshots.iter().any(|s| s.note_id == note_id) {
+                continue;
+            }
+            match self.apply_to_note(note_id, operation) {
+                Ok(Some(snapshot)) => snapshots.push(snapshot),
+                Ok(None) => {}
+                Err(e) => {
+                    for snapshot in snapshots.iter().rev() {
+                        if let Err(restore_err) = self.restore_snapshot(snapshot) {
+                            eprintln!("⚠️ Error restaurando {}: {}", snapshot.path, restore_err);
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(BulkTransaction {
+            operation: operation.clone(),
+            snapshots,
+        })
+    }
+
+    /// Deshacer una operación masiva restaurando el contenido y la ubicación
+    /// previos de cada nota. Devuelve el número de notas restauradas.
+    ///
+    /// Antes de escribir nada se comprueba que todas las notas siguen como las
+    /// dejó la operación: si alguna se editó, movió o borró después, no se
+    /// deshace ninguna y el error lista las notas en conflicto. Si una
+    /// restauración falla, las ya restauradas vuelven a su estado posterior.
+    pub fn undo_bulk(&self, transaction: &BulkTransaction) -> Result<usize> {
+        let mut modified = Vec::new();
+        for snapshot in &transaction.snapshots {
+            if snapshot.new_path != snapshot.path && Path::new(&snapshot.path).exists() {
+                return Err(BaseWriterError::DestinationExists(snapshot.path.clone()));
+            }
+            match fs::read_to_string(&snapshot.new_path) {
+                Ok(current) if current == snapshot.new_content => {}
+                Ok(_) => modified.push(snapshot.new_path.clone()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    modified.push(snapshot.new_path.clone())
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if !modified.is_empty() {
+            return Err(BaseWriterError::ModifiedSince(modified));
+        }
+
+        let mut restored: Vec<&NoteSnapshot> = Vec::new();
+        for snapshot in transaction.snapshots.iter().rev() {
+            if let Err(e) = self.restore_snapshot(snapshot) {
+                for done in restored.iter().rev() {
+                    if let Err(redo_err) = self.write_note(done) {
+                        eprintln!("⚠️ Error reaplicando {}: {}", done.new_path, redo_err);
+                    }
+                }
+                return Err(e);
+            }
+            restored.push(snapshot);
+        }
+        Ok(transaction.len())
+    }
+
+    /// Aplicar la operación a una nota, devolviendo su estado previo si cambió
+    fn apply_to_note(
+        &self,
+        note_id: i64,
+        operation: &BulkOperation,
+    ) -> Result<Option<NoteSnapshot>> {
+        let note_path = self
+            .db
+            .get_note_path_by_id(note_id)
+            .map_err(|e| BaseWriterError::Database(e.to_string()))?
+            .ok_or_else(|| BaseWriterError::NoteNotFound(format!("ID: {}", note_id)))?;
+        let metadata = self
+            .db
+            .get_note_by_path(&note_path)
+            .map_err(|e| BaseWriterError::Database(e.to_string()))?
+            .ok_or_else(|| BaseWriterError::NoteNotFound(note_path.clone()))?;
+
+        let content = fs::read_to_string(&note_path)?;
+        let mut new_content = content.clone();
+        let mut new_path = note_path.clone();
+        let mut new_folder = metadata.folder.clone();
+
+        match operation {
+            BulkOperation::SetProperty { key, value } => {
+                new_content = set_property_in_content(&content, key, value);
+            }
+            BulkOperation::ClearProperty(key) => {
+                new_content = clear_property_in_content(&content, key);
+            }
+            BulkOperation::AddTag(tag) => {
+                new_content = add_tag_to_content(&content, tag)?;
+            }
+            BulkOperation::RemoveTag(tag) => {
+                new_content = remove_tag_from_content(&content, tag)?;
+            }
+            BulkOperation::MoveToFolder(folder) => {
+                let root = self
+                    .notes_root
+                    .as_ref()
+                    .ok_or(BaseWriterError::NotesRootMissing)?;
+                let file_name = Path::new(&note_path)
+                    .file_name()
+                    .ok_or_else(|| BaseWriterError::NoteNotFound(note_path.clone()))?;
+                let target = match folder.as_deref().filter(|f| !f.is_empty()) {
+                    Some(f) => root.join(f).join(file_name),
+                    None => root.join(file_name),
+                };
+                new_path = target.to_string_lossy().to_string();
+                // Otra nota ocupa el destino: fallar antes de tocar nada
+                if new_path != note_path && target.exists() {
+                    return Err(BaseWriterError::DestinationExists(new_path));
+                }
+                new_folder = folder.clone().filter(|f| !f.is_empty());
+            }
+        }
+
+        if new_content == content && new_path == note_path {
+            return Ok(None);
+        }
+
+        let snapshot = NoteSnapshot {
+            note_id,
+            name: metadata.name,
+            folder: metadata.folder,
+            path: note_path,
+            content,
+            new_folder,
+            new_path,
+            new_content,
+        };
+
+        if let Err(e) = self.write_note(&snapshot) {
+            // Dejar la nota como estaba antes de propagar el error
+            let _ = self.restore_snapshot(&snapshot);
+            return Err(e);
+        }
+
+        Ok(Some(snapshot))
+    }
+
+    /// Escribir el nuevo contenido (moviendo el archivo si cambia de carpeta)
+    /// y re-indexar la nota
+    fn write_note(&self, snapshot: &NoteSnapshot) -> Result<()> {
+        let new_folder = snapshot.new_folder.as_deref();
+        if snapshot.new_path != snapshot.path {
+            let target = Path::new(&snapshot.new_path);
+            if target.exists() {
+                return Err(BaseWriterError::DestinationExists(
+                    snapshot.new_path.clone(),
+                ));
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&snapshot.path, target)?;
+            self.db
+                .move_note_to_folder(snapshot.note_id, new_folder, &snapshot.new_path)
+                .map_err(|e| BaseWriterError::Database(e.to_string()))?;
+        }
+
+        if snapshot.new_content != snapshot.content {
+            fs::write(&snapshot.new_path, &snapshot.new_content)?;
+        }
+
+        self.db
+            .index_note(
+                &snapshot.name,
+                &snapshot.new_path,
+                &snapshot.new_content,
+                new_folder,
+            )
+            .map_err(|e| BaseWriterError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Restaurar una nota a su estado previo y re-sincronizar sus propiedades
+    fn restore_snapshot(&self, snapshot: &NoteSnapshot) -> Result<()> {
+        if snapshot.new_path != snapshot.path {
+            let (moved, original) = (Path::new(&snapshot.new_path), Path::new(&snapshot.path));
+            // Si la nota no llegó a moverse (o algo ocupa ya su ruta original)
+            // no se renombra nada encima de otro archivo
+            if moved.exists() && original.exists() {
+                return Err(BaseWriterError::DestinationExists(snapshot.path.clone()));
+            }
+            if moved.exists() {
+                if let Some(parent) = original.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&snapshot.new_path, &snapshot.path)?;
+            }
+            self.db
+                .move_note_to_folder(snapshot.note_id, snapshot.folder.as_deref(), &snapshot.path)
+                .map_err(|e| BaseWriterError::Database(e.to_string()))?;
+        }
+
+        fs::write(&snapshot.path, &snapshot.content)?;
+
+        self.db
+            .index_note(
+                &snapshot.name,
+                &snapshot.path,
+                &snapshot.content,
+                snapshot.folder.as_deref(),
+            )
+            .map_err(|e| BaseWriterError::Database(e.to_string()))?;
+
+        Ok(())
     }
 
     /// Actualizar el valor de una propiedad existente en un grupo
@@ -340,6 +644,161 @@ impl<'a> BaseWriter<'a> {
     }
 }
 
+/// Asignar `key::value` en todas las apariciones de la propiedad, o añadirla
+/// al final de la nota si no existe
+fn set_property_in_content(content: &str, key: &str, value: &str) -> String {
+    let escaped_value = value.replace(',', "\\,");
+    let result = rewrite_property_groups(content, key, Some(&escaped_value));
+
+    if result != content
+        || InlinePropertyParser::parse(content)
+            .iter()
+            .any(|p| p.key == key)
+    {
+        return result;
+    }
+
+    let mut result = content.trim_end_matches('\n').to_string();
+    if !result.is_empty() {
+        result.push_str("\n\n");
+    }
+    result.push_str(&format!("[{}::{}]\n", key, escaped_value));
+    result
+}
+
+/// Eliminar una propiedad de todos los grupos de la nota
+fn clear_property_in_content(content: &str, key: &str) -> String {
+    rewrite_property_groups(content, key, None)
+}
+
+/// Reescribir los grupos `[...]` que contienen `key`
+///
+/// Con `Some(value)` se reemplaza el valor; con `None` se elimina el par y,
+/// si el grupo queda vacío, el grupo completo.
+fn rewrite_property_groups(content: &str, key: &str, value: Option<&str>) -> String {
+    let mut groups: Vec<(usize, usize)> = InlinePropertyParser::parse(content)
+        .iter()
+        .filter(|p| p.key == key)
+        .map(|p| (p.char_start, p.char_end))
+        .collect();
+    groups.sort_unstable();
+    groups.dedup();
+
+    let mut result = content.to_string();
+    // De fin a inicio para no invalidar las posiciones
+    for (start, end) in groups.into_iter().rev() {
+        let Some(group_text) = result.get(start..end) else {
+            continue;
+        };
+        match rewrite_group(group_text, key, value) {
+            Some(new_group) => result.replace_range(start..end, &new_group),
+            None => {
+                let mut start = start;
+                let mut end = end;
+                if result[..start].ends_with(' ') {
+                    start -= 1;
+                }
+                let line_start = start == 0 || result[..start].ends_with('\n');
+                if line_start && result[end..].starts_with('\n') {
+                    end += 1;
+                }
+                result.replace_range(start..end, "");
+            }
+        }
+    }
+    result
+}
+
+/// Reescribir un grupo `[a::1, b::2]`; `None` si queda vacío
+fn rewrite_group(group_text: &str, key: &str, value: Option<&str>) -> Option<String> {
+    let inner = &group_text[1..group_text.len() - 1];
+
+    // Separar por comas no escapadas
+    let mut parts: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek() == Some(&',') {
+            current.push(c);
+            current.push(chars.next().unwrap_or(','));
+        } else if c == ',' {
+            parts.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    parts.push(current);
+
+    let mut rewritten: Vec<String> = Vec::new();
+    for part in parts {
+        let Some(sep_pos) = part.find("::") else {
+            rewritten.push(part);
+            continue;
+        };
+        if part[..sep_pos].trim() != key {
+            rewritten.push(part);
+            continue;
+        }
+        if let Some(value) = value {
+            let after = &part[sep_pos..];
+            let sep_len = after.chars().take_while(|&c| c == ':').count();
+            rewritten.push(format!("{}{}", &part[..sep_pos + sep_len], value));
+        }
+    }
+
+    if rewritten.iter().all(|p| p.trim().is_empty()) {
+        return None;
+    }
+    if let Some(first) = rewritten.first_mut() {
+        *first = first.trim_start().to_string();
+    }
+    Some(format!("[{}]", rewritten.join(",")))
+}
+
+/// Añadir un tag al frontmatter de la nota
+pub fn add_tag_to_content(content: &str, tag: &str) -> Result<String> {
+    let tag = tag.trim().trim_start_matches('#');
+    let (frontmatter, _) = Frontmatter::parse_or_empty(content);
+    if tag.is_empty() || frontmatter.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+        return Ok(content.to_string());
+    }
+
+    let mut tags = frontmatter.tags;
+    tags.push(tag.to_string());
+    Ok(update_tags(content, tags)?)
+}
+
+/// Quitar un tag del frontmatter y sus apariciones inline `#tag`
+pub fn remove_tag_from_content(content: &str, tag: &str) -> Result<String> {
+    let tag = tag.trim().trim_start_matches('#');
+    if tag.is_empty() {
+        return Ok(content.to_string());
+    }
+
+    let (frontmatter, _) = Frontmatter::parse_or_empty(content);
+    let mut result = if frontmatter.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+        let tags = frontmatter
+            .tags
+            .into_iter()
+            .filter(|t| !t.eq_ignore_ascii_case(tag))
+            .collect();
+        update_tags(content, tags)?
+    } else {
+        content.to_string()
+    };
+
+    let inline_re = Regex::new(&format!(
+        r"(?mi)(^|[ \t])#{}([^\p{{L}}\p{{N}}_/-]|$)",
+        regex::escape(tag)
+    ))
+    .expect("regex de tag válida");
+    if inline_re.is_match(&result) {
+        result = inline_re.replace_all(&result, "$1$2").to_string();
+    }
+
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -366,4 +825,169 @@ mod tests {
         let result2 = append_property_to_group_pure(group2, "rating", "5");
         assert_eq!(result2, "[juego::Minecraft, horas::100, rating::5]");
     }
+
+    #[test]
+    fn test_bulk_content_helpers() {
+        let content = "Libro [autor::Cervantes, año::1605]\n[estado::leído]\n";
+
+        assert_eq!(
+            set_property_in_content(content, "estado", "pendiente"),
+            "Libro [autor::Cervantes, año::1605]\n[estado::pendiente]\n"
+        );
+        assert_eq!(
+            set_property_in_content("Nota", "rating", "5"),
+            "Nota\n\n[rating::5]\n"
+        );
+        assert_eq!(
+            clear_property_in_content(content, "autor"),
+            "Libro [año::1605]\n[estado::leído]\n"
+        );
+        assert_eq!(
+            clear_property_in_content(content, "estado"),
+            "Libro [autor::Cervantes, año::1605]\n"
+        );
+
+        let tagged = add_tag_to_content("Texto", "proyecto").unwrap();
+        assert!(tagged.starts_with("---\ntags:"));
+        assert!(tagged.contains("proyecto"));
+
+        let untagged = remove_tag_from_content("Texto #urgente y #otro", "urgente").unwrap();
+        assert_eq!(untagged, "Texto  y #otro");
+    }
+
+    #[test]
+    fn test_bulk_apply_and_undo() {
+        let dir = std::env::temp_dir().join("test_base_writer_bulk");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let db = NotesDatabase::new(&dir.join("notes.db")).unwrap();
+
+        let mut ids = Vec::new();
+        for (name, content) in [("A", "[estado::abierto]\n"), ("B", "Sin propiedades\n")] {
+            let path = dir.join(format!("{}.md", name));
+            fs::write(&path, content).unwrap();
+            ids.push(
+                db.index_note(name, &path.to_string_lossy(), content, None)
+                    .unwrap(),
+            );
+        }
+
+        let writer = BaseWriter::new(&db).with_notes_root(&dir);
+        let set = BulkOperation::SetProperty {
+            key: "estado".to_string(),
+            value: "cerrado".to_string(),
+        };
+        let tx = writer.apply_bulk(&ids, &set).unwrap();
+        assert_eq!(tx.len(), 2);
+        assert_eq!(db.get_notes_with_property("estado").unwrap().len(), 2);
+        assert!(
+            fs::read_to_string(dir.join("B.md"))
+                .unwrap()
+                .contains("[estado::cerrado]")
+        );
+
+        // Un único undo restaura todas las notas y sus propiedades inline
+        assert_eq!(writer.undo_bulk(&tx).unwrap(), 2);
+        assert_eq!(
+            fs::read_to_string(dir.join("A.md")).unwrap(),
+            "[estado::abierto]\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("B.md")).unwrap(),
+            "Sin propiedades\n"
+        );
+        assert_eq!(db.get_notes_with_property("estado").unwrap().len(), 1);
+
+        // Mover a carpeta y deshacer
+        let tx = writer
+            .apply_bulk(
+                &ids,
+                &BulkOperation::MoveToFolder(Some("Archivo".to_string())),
+            )
+            .unwrap();
+        assert!(dir.join("Archivo").join("A.md").exists());
+        assert_eq!(db.list_notes(Some("Archivo")).unwrap().len(), 2);
+        writer.undo_bulk(&tx).unwrap();
+        assert!(dir.join("A.md").exists());
+        assert!(!dir.join("Archivo").join("A.md").exists());
+
+        // Si el destino ya existe no se toca ninguna de las dos notas
+        fs::create_dir_all(dir.join("Archivo")).unwrap();
+        fs::write(dir.join("Archivo").join("A.md"), "Otra nota\n").unwrap();
+        let moved = writer.apply_bulk(
+            &ids,
+            &BulkOperation::MoveToFolder(Some("Archivo".to_string())),
+        );
+        assert!(matches!(moved, Err(BaseWriterError::DestinationExists(_))));
+        assert_eq!(
+            fs::read_to_string(dir.join("Archivo").join("A.md")).unwrap(),
+            "Otra nota\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("A.md")).unwrap(),
+            "[estado::abierto]\n"
+        );
+        assert!(dir.join("B.md").exists());
+        assert!(!dir.join("Archivo").join("B.md").exists());
+        fs::remove_file(dir.join("Archivo").join("A.md")).unwrap();
+
+        // Deshacer no pisa una nota creada después en la ruta original
+        let tx = writer
+            .apply_bulk(
+                &ids[..1],
+                &BulkOperation::MoveToFolder(Some("Archivo".to_string())),
+            )
+            .unwrap();
+        fs::write(dir.join("A.md"), "Nota nueva\n").unwrap();
+        assert!(matches!(
+            writer.undo_bulk(&tx),
+            Err(BaseWriterError::DestinationExists(_))
+        ));
+        assert_eq!(
+            fs::read_to_string(dir.join("A.md")).unwrap(),
+            "Nota nueva\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("Archivo").join("A.md")).unwrap(),
+            "[estado::abierto]\n"
+        );
+        fs::remove_file(dir.join("A.md")).unwrap();
+        fs::rename(dir.join("Archivo").join("A.md"), dir.join("A.md")).unwrap();
+        db.move_note_to_folder(ids[0], None, &dir.join("A.md").to_string_lossy())
+            .unwrap();
+
+        // Deshacer no pisa ediciones posteriores ni resucita notas borradas,
+        // y si alguna nota cambió no se restaura ninguna
+        let tx = writer.apply_bulk(&ids, &set).unwrap();
+        fs::write(dir.join("A.md"), "[estado::cerrado]\nEditado después\n").unwrap();
+        match writer.undo_bulk(&tx) {
+            Err(BaseWriterError::ModifiedSince(notes)) => {
+                assert_eq!(notes, vec![dir.join("A.md").to_string_lossy().to_string()])
+            }
+            other => panic!("undo debería rechazarse: {:?}", other),
+        }
+        assert_eq!(
+            fs::read_to_string(dir.join("A.md")).unwrap(),
+            "[estado::cerrado]\nEditado después\n"
+        );
+        assert!(
+            fs::read_to_string(dir.join("B.md"))
+                .unwrap()
+                .contains("[estado::cerrado]")
+        );
+
+        fs::remove_file(dir.join("B.md")).unwrap();
+        fs::write(dir.join("A.md"), "[estado::cerrado]\n").unwrap();
+        assert!(matches!(
+            writer.undo_bulk(&tx),
+            Err(BaseWriterError::ModifiedSince(_))
+        ));
+        assert!(!dir.join("B.md").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join("A.md")).unwrap(),
+            "[estado::cerrado]\n"
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
 }
@@ -0,0 +1,1962 @@
+//! Compilación de notas en libros EPUB 3 y DOCX
+//!
+//! Las notas se concatenan en el orden dado (o en el de `NotesConfig`) y cada
+//! encabezado `#` abre un capítulo; una nota que no empieza por uno lo recibe con
+//! su título. Las imágenes locales se incrustan, las notas al pie `[^1]` pasan a
+//! notas del formato (`epub:type="footnote"` en EPUB, notas de Word en DOCX) y se
+//! genera un índice con capítulos y secciones. Tags, propiedades inline y
+//! recordatorios se quitan o se dejan como texto según `BookOptions`.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use pulldown_cmark::{
+    Alignment, CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd,
+};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::archive::ZipWriter;
+use super::diagram::{DiagramKind, render_diagram};
+use super::export::{ExportNote, TAG_STRIP_RE, mime_type};
+use super::frontmatter::Frontmatter;
+use super::html_renderer::{PreviewColors, escape_html, url_decode};
+use super::inline_property::InlinePropertyParser;
+use super::math::{find_math, tex_to_mathml};
+use super::note_file::NotesDirectory;
+use super::notes_config::NotesConfig;
+use super::wikilink::{WIKI_LINK_RE, WikiLink, extract, find_links};
+
+/// Regex para recordatorios `!!RECORDAR(fecha, texto)` / `!!REMIND(date, text)`
+static REMINDER_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"!!(?:RECORDAR|REMIND)\(([^,]+),\s*([^)]+)\)").unwrap());
+
+/// Regex para el formato interno de recordatorios `[REMINDER:params|texto]`
+static REMINDER_WIDGET_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[REMINDER:[^|\]]*\|([^\]]*)\]").unwrap());
+
+/// Regex para líneas de recordatorio con emoji (`📅 mañana 9:00 - texto`)
+static REMINDER_EMOJI_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:📅|⏰|🔔)\s*\S").unwrap());
+
+/// Regex para tags inline (`#tag` tras espacio, `(` o `[`)
+static INLINE_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[\s(\[])#[\p{L}\p{N}_-]+").unwrap());
+
+/// Marcadores de las fórmulas protegidas del parser markdown
+const MATH_OPEN: char = '\u{E000}';
+const MATH_CLOSE: char = '\u{E001}';
+
+/// Regex para una fórmula protegida
+static MATH_SLOT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new("\u{E000}([0-9]+)\u{E001}").unwrap());
+
+/// Ancho máximo de una imagen en DOCX (EMU, ~15 cm)
+const DOCX_MAX_IMAGE_WIDTH: u64 = 5_400_000;
+
+/// EMU por píxel a 96 ppp
+const EMU_PER_PIXEL: u64 = 9525;
+
+#[derive(Debug, Error)]
+pub enum BookError {
+    #[error("Unknown book format: {0} (expected epub or docx)")]
+    UnknownFormat(String),
+    #[error("No notes to compile")]
+    Empty,
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Formato del libro
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookFormat {
+    Epub,
+    Docx,
+}
+
+impl BookFormat {
+    pub fn parse(s: &str) -> Result<Self, BookError> {
+        match s.trim().to_lowercase().as_str() {
+            "epub" | "epub3" => Ok(Self::Epub),
+            "docx" | "word" => Ok(Self::Docx),
+            other => Err(BookError::UnknownFormat(other.to_string())),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Epub => "epub",
+            Self::Docx => "docx",
+        }
+    }
+}
+
+/// Qué hacer con el marcado propio de NotNative (tags, propiedades, recordatorios)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarkupMode {
+    /// Quitarlo del texto
+    #[default]
+    Strip,
+    /// Dejarlo como texto legible
+    Render,
+}
+
+impl MarkupMode {
+    pub fn from_keep(keep: bool) -> Self {
+        if keep { Self::Render } else { Self::Strip }
+    }
+}
+
+/// Opciones del libro
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookOptions {
+    pub title: String,
+    pub author: Option<String>,
+    /// Código de idioma (`es`, `en`...)
+    pub language: String,
+    pub tags: MarkupMode,
+    pub properties: MarkupMode,
+    pub reminders: MarkupMode,
+    /// Índice al principio del libro
+    pub toc: bool,
+}
+
+impl Default for BookOptions {
+    fn default() -> Self {
+        Self {
+            title: "Sin título".to_string(),
+            author: None,
+            language: "es".to_string(),
+            tags: MarkupMode::default(),
+            properties: MarkupMode::default(),
+            reminders: MarkupMode::default(),
+            toc: true,
+        }
+    }
+}
+
+impl BookOptions {
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        let title = title.into();
+        if !title.trim().is_empty() {
+            self.title = title;
+        }
+        self
+    }
+
+    pub fn with_author(mut self, author: Option<String>) -> Self {
+        self.author = author.filter(|a| !a.trim().is_empty());
+        self
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        let language = language.into();
+        if !language.trim().is_empty() {
+            self.language = language.trim().to_string();
+        }
+        self
+    }
+
+    pub fn with_tags(mut self, mode: MarkupMode) -> Self {
+        self.tags = mode;
+        self
+    }
+
+    pub fn with_properties(mut self, mode: MarkupMode) -> Self {
+        self.properties = mode;
+        self
+    }
+
+    pub fn with_reminders(mut self, mode: MarkupMode) -> Self {
+        self.reminders = mode;
+        self
+    }
+
+    pub fn with_toc(mut self, toc: bool) -> Self {
+        self.toc = toc;
+        self
+    }
+
+    fn toc_title(&self) -> &'static str {
+        if self.language.starts_with("es") {
+            "Índice"
+        } else {
+            "Contents"
+        }
+    }
+}
+
+/// Ordenar las notas como en la barra lateral: primero las que tienen posición en
+/// `NotesConfig` y después el resto por nombre
+pub fn order_notes(notes: &mut [ExportNote], config: &NotesConfig) {
+    let position = |note: &ExportNote| {
+        config
+            .get_position(&note.name)
+            .or_else(|| config.get_position(note.title()))
+    };
+    notes.sort_by(|a, b| match (position(a), position(b)) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+    });
+}
+
+// ============================================================================
+// Manuscrito: notas preparadas y divididas en capítulos
+// ============================================================================
+
+/// Capítulo: desde un encabezado `#` hasta el siguiente
+struct Chapter<'a> {
+    title: String,
+    /// Nota de origen (las etiquetas de las notas al pie son por nota)
+    note: usize,
+    events: Vec<Event<'a>>,
+}
+
+impl Chapter<'_> {
+    /// Encabezados de nivel 1 y 2, en orden, para el índice
+    fn outline(&self, math: &[ProtectedMath]) -> Vec<(u8, String)> {
+        let mut outline = Vec::new();
+        let mut current: Option<(u8, String)> = None;
+        for event in &self.events {
+            match event {
+                Event::Start(Tag::Heading { level, .. })
+                    if matches!(level, HeadingLevel::H1 | HeadingLevel::H2) =>
+                {
+                    current = Some((*level as u8, String::new()));
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((_, title)) = current.as_mut() {
+                        title.push_str(&math_as_tex(text, math));
+                    }
+                }
+                Event::End(TagEnd::Heading(_)) => {
+                    if let Some(entry) = current.take() {
+                        outline.push(entry);
+                    }
+                }
+                _ => {}
+            }
+        }
+        outline
+    }
+}
+
+/// Fórmula protegida: (TeX, en bloque)
+type ProtectedMath = (String, bool);
+
+type FootnoteKey = (usize, String);
+
+struct Manuscript<'a> {
+    chapters: Vec<Chapter<'a>>,
+    footnotes: HashMap<FootnoteKey, Vec<Event<'a>>>,
+    math: &'a [ProtectedMath],
+}
+
+impl<'a> Manuscript<'a> {
+    fn parse(sources: &'a [String], math: &'a [ProtectedMath]) -> Self {
+        let mut options = Options::empty();
+        options.insert(Options::ENABLE_TABLES);
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+        options.insert(Options::ENABLE_TASKLISTS);
+        options.insert(Options::ENABLE_FOOTNOTES);
+
+        let mut chapters: Vec<Chapter<'a>> = Vec::new();
+        let mut footnotes = HashMap::new();
+        for (note, source) in sources.iter().enumerate() {
+            let mut footnote: Option<(String, Vec<Event<'a>>)> = None;
+            let mut in_title = false;
+            // Anidamiento de bloques: un `# ` dentro de una cita o una lista no abre capítulo
+            let mut depth = 0usize;
+            for event in Parser::new_ext(source, options) {
+                if let Some((_, events)) = footnote.as_mut() {
+                    if matches!(event, Event::End(TagEnd::FootnoteDefinition)) {
+                        let (label, events) = footnote.take().unwrap_or_default();
+                        footnotes.insert((note, label), events);
+                    } else {
+                        push_merged(events, event);
+                    }
+                    continue;
+                }
+                let top_level = depth == 0;
+                match &event {
+                    // Su cierre lo consume la rama de la nota al pie
+                    Event::Start(Tag::FootnoteDefinition(_)) => {}
+                    Event::Start(_) => depth += 1,
+                    Event::End(_) => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                match event {
+                    Event::Start(Tag::FootnoteDefinition(label)) => {
+                        footnote = Some((label.to_string(), Vec::new()));
+                    }
+                    Event::Start(Tag::Heading {
+                        level: HeadingLevel::H1,
+                        ..
+                    }) if top_level => {
+                        in_title = true;
+                        chapters.push(Chapter {
+                            title: String::new(),
+                            note,
+                            events: vec![event],
+                        });
+                    }
+                    event => {
+                        if chapters.is_empty() || chapters.last().is_some_and(|c| c.note != note) {
+                            chapters.push(Chapter {
+                                title: String::new(),
+                                note,
+                                events: Vec::new(),
+                            });
+                        }
+                        let chapter = chapters.last_mut().unwrap();
+                        match &event {
+                            Event::End(TagEnd::Heading(HeadingLevel::H1)) if depth == 0 => {
+                                in_title = false
+                            }
+                            Event::Text(text) | Event::Code(text) if in_title => {
+                                chapter.title.push_str(&math_as_tex(text, math));
+                            }
+                            _ => {}
+                        }
+                        push_merged(&mut chapter.events, event);
+                    }
+                }
+            }
+        }
+
+        Self {
+            chapters,
+            footnotes,
+            math,
+        }
+    }
+}
+
+/// Añadir un evento uniendo textos consecutivos (el parser los parte en los
+/// caracteres especiales y las fórmulas protegidas deben quedar enteras)
+fn push_merged<'a>(events: &mut Vec<Event<'a>>, event: Event<'a>) {
+    if let (Some(Event::Text(previous)), Event::Text(text)) = (events.last_mut(), &event) {
+        *previous = CowStr::from(format!("{}{}", previous, text));
+        return;
+    }
+    events.push(event);
+}
+
+/// Texto con las fórmulas protegidas devueltas a su código TeX
+fn math_as_tex(text: &str, math: &[ProtectedMath]) -> String {
+    MATH_SLOT_RE
+        .replace_all(text, |caps: &regex::Captures| {
+            caps[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| math.get(i))
+                .map(|(tex, _)| tex.clone())
+                .unwrap_or_default()
+        })
+        .to_string()
+}
+
+/// Partes de un texto: tramos normales y fórmulas (índice en la lista de fórmulas)
+fn split_math(text: &str) -> Vec<Result<&str, usize>> {
+    let mut parts = Vec::new();
+    let mut last = 0;
+    for caps in MATH_SLOT_RE.captures_iter(text) {
+        let whole = caps.get(0).unwrap();
+        if whole.start() > last {
+            parts.push(Ok(&text[last..whole.start()]));
+        }
+        if let Ok(index) = caps[1].parse() {
+            parts.push(Err(index));
+        }
+        last = whole.end();
+    }
+    if last < text.len() {
+        parts.push(Ok(&text[last..]));
+    }
+    parts
+}
+
+/// Aplicar `f` a los tramos de una línea que no son código en línea
+pub(crate) fn map_prose(line: &str, f: &mut dyn FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('`') {
+        out.push_str(&f(&rest[..start]));
+        let ticks = rest[start..].chars().take_while(|&c| c == '`').count();
+        let fence = &rest[start..start + ticks];
+        match rest[start + ticks..].find(fence) {
+            Some(end) => {
+                let close = start + ticks + end + ticks;
+                out.push_str(&rest[start..close]);
+                rest = &rest[close..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                return out;
+            }
+        }
+    }
+    out.push_str(&f(rest));
+    out
+}
+
+// ============================================================================
+// Imágenes
+// ============================================================================
+
+struct MediaFile {
+    /// Nombre dentro del paquete (`img-1.png`)
+    name: String,
+    mime: &'static str,
+    data: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Media {
+    files: Vec<MediaFile>,
+    by_path: HashMap<PathBuf, usize>,
+}
+
+impl Media {
+    /// Registrar una imagen local (una sola vez por ruta)
+    fn add(&mut self, path: &Path) -> Option<usize> {
+        if let Some(&index) = self.by_path.get(path) {
+            return Some(index);
+        }
+        let data = std::fs::read(path).ok()?;
+        let mime = mime_type(path);
+        if !mime.starts_with("image/") {
+            return None;
+        }
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let index = self.files.len();
+        self.files.push(MediaFile {
+            name: format!("img-{}.{}", index + 1, extension),
+            mime,
+            data,
+        });
+        self.by_path.insert(path.to_path_buf(), index);
+        Some(index)
+    }
+}
+
+/// Tamaño en píxeles de una imagen PNG, GIF, JPEG o BMP
+fn image_size(data: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| {
+        Some(u32::from(u16::from_be_bytes([
+            *data.get(i)?,
+            *data.get(i + 1)?,
+        ])))
+    };
+    let le16 = |i: usize| {
+        Some(u32::from(u16::from_le_bytes([
+            *data.get(i)?,
+            *data.get(i + 1)?,
+        ])))
+    };
+    let be32 = |i: usize| Some(u32::from_be_bytes(data.get(i..i + 4)?.try_into().ok()?));
+    let le32 = |i: usize| Some(i32::from_le_bytes(data.get(i..i + 4)?.try_into().ok()?));
+
+    if data.starts_with(b"\x89PNG") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    if data.starts_with(b"GIF") {
+        return Some((le16(6)?, le16(8)?));
+    }
+    if data.starts_with(b"BM") {
+        return Some((le32(18)?.unsigned_abs(), le32(22)?.unsigned_abs()));
+    }
+    if data.starts_with(&[0xFF, 0xD8]) {
+        let mut i = 2;
+        while i + 9 < data.len() {
+            if data[i] != 0xFF {
+                return None;
+            }
+            let marker = data[i + 1];
+            if marker == 0xFF {
+                i += 1;
+                continue;
+            }
+            if matches!(marker, 0xD8 | 0x01 | 0xD0..=0xD7) {
+                i += 2;
+                continue;
+            }
+            if matches!(marker, 0xC0..=0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF) {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    }
+    None
+}
+
+/// Escapar texto para XML
+fn xml_escape(text: &str) -> String {
+    escape_html(text)
+}
+
+// ============================================================================
+// Compilador
+// ============================================================================
+
+/// Compilador de notas en EPUB 3 o DOCX
+pub struct BookCompiler {
+    notes_root: PathBuf,
+    options: BookOptions,
+}
+
+impl BookCompiler {
+    pub fn new(notes_root: impl Into<PathBuf>) -> Self {
+        Self {
+            notes_root: notes_root.into(),
+            options: BookOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: BookOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &BookOptions {
+        &self.options
+    }
+
+    /// Compilar `notes` (en ese orden) y escribir el libro en `output`
+    pub fn compile(
+        &self,
+        notes: &[ExportNote],
+        format: BookFormat,
+        output: &Path,
+    ) -> Result<PathBuf, BookError> {
+        let bytes = match format {
+            BookFormat::Epub => self.epub(notes)?,
+            BookFormat::Docx => self.docx(notes)?,
+        };
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(output, bytes)?;
+        Ok(output.to_path_buf())
+    }
+
+    /// Markdown de las notas listo para el parser: sin frontmatter, con las
+    /// transclusiones resueltas, el marcado propio tratado y las fórmulas protegidas
+    fn sources(
+        &self,
+        notes: &[ExportNote],
+    ) -> Result<(Vec<String>, Vec<ProtectedMath>), BookError> {
+        if notes.is_empty() {
+            return Err(BookError::Empty);
+        }
+        let mut math = Vec::new();
+        let sources = notes
+            .iter()
+            .map(|note| {
+                let mut markdown = self.prepare(note);
+                let spans = find_math(&markdown);
+                let base = math.len();
+                for (i, span) in spans.iter().enumerate().rev() {
+                    markdown.replace_range(
+                        span.range.clone(),
+                        &format!("{}{}{}", MATH_OPEN, base + i, MATH_CLOSE),
+                    );
+                }
+                math.extend(spans.into_iter().map(|span| (span.tex, span.display)));
+                markdown
+            })
+            .collect();
+        Ok((sources, math))
+    }
+
+    fn prepare(&self, note: &ExportNote) -> String {
+        let (_, body) = Frontmatter::parse_or_empty(&note.markdown);
+        let body = self.expand_embeds(&body);
+
+        let mut out = String::with_capacity(body.len());
+        let mut in_fence = false;
+        for line in body.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            } else if !in_fence {
+                let prepared = self.prepare_line(line);
+                // Una línea que solo tenía marcado quitado desaparece
+                if prepared.trim().is_empty() && !line.trim().is_empty() {
+                    continue;
+                }
+                out.push_str(&prepared);
+                out.push('\n');
+                continue;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+
+        let starts_with_chapter = out
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .is_some_and(|line| line.starts_with("# "));
+        if starts_with_chapter {
+            out
+        } else {
+            format!("# {}\n\n{}", note.title(), out)
+        }
+    }
+
+    /// `![[Nota]]` y `![[Nota#Encabezado]]` sustituidos por su contenido (un nivel)
+    fn expand_embeds(&self, text: &str) -> String {
+        let embeds: Vec<_> = find_links(text)
+            .into_iter()
+            .filter(|(_, link)| link.embed)
+            .collect();
+        if embeds.is_empty() {
+            return text.to_string();
+        }
+        let Ok(notes_dir) = NotesDirectory::new(&self.notes_root) else {
+            return text.to_string();
+        };
+        let mut out = text.to_string();
+        for (range, link) in embeds.into_iter().rev() {
+            let content = notes_dir
+                .find_note(&link.note)
+                .ok()
+                .flatten()
+                .and_then(|note| note.read().ok())
+                .map(|source| Frontmatter::parse_or_empty(&source).1)
+                .and_then(|source| extract(&source, link.target.as_ref()));
+            if let Some(content) = content {
+                out.replace_range(range, content.trim_end());
+            }
+        }
+        out
+    }
+
+    /// Recordatorios, propiedades, enlaces y tags de una línea (fuera de código)
+    fn prepare_line(&self, line: &str) -> String {
+        if self.options.reminders == MarkupMode::Strip && REMINDER_EMOJI_RE.is_match(line) {
+            return String::new();
+        }
+        map_prose(line, &mut |text| {
+            let text = self.reminders(text);
+            let text = self.properties(&text);
+            let text = WIKI_LINK_RE.replace_all(&text, |caps: &regex::Captures| {
+                WikiLink::parse(&caps[2]).label()
+            });
+            match self.options.tags {
+                MarkupMode::Render => text.to_string(),
+                MarkupMode::Strip => INLINE_TAG_RE
+                    .replace_all(&text, |caps: &regex::Captures| {
+                        // El espacio de delante se va con el tag; `(` y `[` se quedan
+                        let prefix = &caps[1];
+                        if prefix.trim().is_empty() {
+                            String::new()
+                        } else {
+                            prefix.to_string()
+                        }
+                    })
+                    .to_string(),
+            }
+        })
+    }
+
+    fn reminders(&self, text: &str) -> String {
+        let strip = self.options.reminders == MarkupMode::Strip;
+        let text = REMINDER_RE.replace_all(text, |caps: &regex::Captures| {
+            if strip {
+                String::new()
+            } else {
+                format!("🔔 {} ({})", caps[2].trim(), caps[1].trim())
+            }
+        });
+        REMINDER_WIDGET_RE
+            .replace_all(&text, |caps: &regex::Captures| {
+                if strip {
+                    String::new()
+                } else {
+                    format!("🔔 {}", caps[1].trim())
+                }
+            })
+            .to_string()
+    }
+
+    /// `[campo::valor]` como `campo: valor` o quitado. Las ocultas (`:::`) se quitan siempre.
+    fn properties(&self, text: &str) -> String {
+        let properties = InlinePropertyParser::parse(text);
+        if properties.is_empty() {
+            return text.to_string();
+        }
+        let mut spans: Vec<(usize, usize, Vec<String>)> = Vec::new();
+        for property in properties {
+            if spans
+                .last()
+                .is_none_or(|(start, ..)| *start != property.char_start)
+            {
+                spans.push((property.char_start, property.char_end, Vec::new()));
+            }
+            if !property.hidden {
+                let visible = &mut spans.last_mut().unwrap().2;
+                visible.push(format!("{}: {}", property.key, property.raw_value));
+            }
+        }
+
+        let mut out = text.to_string();
+        for (start, end, visible) in spans.into_iter().rev() {
+            if self.options.properties == MarkupMode::Render && !visible.is_empty() {
+                out.replace_range(start..end, &visible.join(", "));
+            } else {
+                let start = if out[..start].ends_with(' ') {
+                    start - 1
+                } else {
+                    start
+                };
+                out.replace_range(start..end, "");
+            }
+        }
+        out
+    }
+
+    /// Ruta local de una imagen del markdown (relativa al directorio de notas)
+    fn image_path(&self, destination: &str) -> Option<PathBuf> {
+        if destination.starts_with("http://")
+            || destination.starts_with("https://")
+            || destination.starts_with("data:")
+        {
+            return None;
+        }
+        let destination = url_decode(destination.trim_start_matches("file://"));
+        let path = if destination.starts_with('/') {
+            PathBuf::from(destination)
+        } else {
+            self.notes_root.join(destination)
+        };
+        path.is_file().then_some(path)
+    }
+
+    /// Identificador estable del libro (UUID a partir del título y el contenido)
+    fn identifier(&self, sources: &[String]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.options.title.as_bytes());
+        for source in sources {
+            hasher.update(source.as_bytes());
+        }
+        let hex = format!("{:x}", hasher.finalize());
+        format!(
+            "urn:uuid:{}-{}-4{}-a{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[13..16],
+            &hex[17..20],
+            &hex[20..32]
+        )
+    }
+}
+
+// ============================================================================
+// EPUB 3
+// ============================================================================
+
+const EPUB_CSS: &str = r#"body {
+    font-family: serif;
+    line-height: 1.5;
+    margin: 0 5%;
+}
+h1 {
+    margin: 2em 0 1em;
+    text-align: center;
+}
+h2, h3, h4 {
+    margin: 1.5em 0 0.5em;
+}
+pre {
+    font-size: 0.85em;
+    white-space: pre-wrap;
+    background: #f4f4f6;
+    padding: 0.5em;
+}
+code {
+    font-family: monospace;
+}
+blockquote {
+    margin: 1em 2em;
+    font-style: italic;
+}
+table {
+    border-collapse: collapse;
+}
+th, td {
+    border: 1px solid #c9cbd3;
+    padding: 0.2em 0.5em;
+}
+img, svg {
+    max-width: 100%;
+}
+.math-display {
+    text-align: center;
+}
+a.noteref {
+    vertical-align: super;
+    font-size: 0.75em;
+    text-decoration: none;
+}
+aside.footnote {
+    font-size: 0.9em;
+    border-top: 1px solid #c9cbd3;
+    margin-top: 2em;
+}
+nav ol {
+    list-style: none;
+}
+"#;
+
+/// Estado compartido por los capítulos de un EPUB
+struct EpubState {
+    media: Media,
+    footnote_numbers: HashMap<FootnoteKey, usize>,
+    pending: VecDeque<(usize, FootnoteKey)>,
+}
+
+/// Recursos que usa un capítulo (propiedades del manifiesto)
+#[derive(Default)]
+struct EpubFeatures {
+    mathml: bool,
+    svg: bool,
+}
+
+impl BookCompiler {
+    /// Bytes del EPUB 3 con las notas compiladas
+    pub fn epub(&self, notes: &[ExportNote]) -> Result<Vec<u8>, BookError> {
+        let (sources, math) = self.sources(notes)?;
+        let manuscript = Manuscript::parse(&sources, &math);
+        let language = xml_escape(&self.options.language);
+        let title = xml_escape(&self.options.title);
+
+        let mut state = EpubState {
+            media: Media::default(),
+            footnote_numbers: HashMap::new(),
+            pending: VecDeque::new(),
+        };
+        let mut chapters = Vec::new();
+        let mut toc = String::new();
+        for (index, chapter) in manuscript.chapters.iter().enumerate() {
+            let file = format!("chapter-{:03}.xhtml", index + 1);
+            let mut features = EpubFeatures::default();
+
+            let events = self.xhtml_events(
+                &chapter.events,
+                chapter.note,
+                &manuscript,
+                &mut state,
+                &mut features,
+            );
+            let mut body = String::new();
+            pulldown_cmark::html::push_html(&mut body, events.into_iter());
+            while let Some((number, key)) = state.pending.pop_front() {
+                let events = manuscript.footnotes.get(&key).cloned().unwrap_or_default();
+                let events =
+                    self.xhtml_events(&events, key.0, &manuscript, &mut state, &mut features);
+                let mut html = String::new();
+                pulldown_cmark::html::push_html(&mut html, events.into_iter());
+                body.push_str(&format!(
+                    "<aside epub:type=\"footnote\" class=\"footnote\" id=\"fn{n}\">\n<p><a href=\"#fnref{n}\">{n}.</a></p>\n{html}</aside>\n",
+                    n = number
+                ));
+            }
+
+            let chapter_title = xml_escape(&chapter.title);
+            chapters.push((
+                file.clone(),
+                format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" xml:lang=\"{language}\" lang=\"{language}\">\n<head>\n<meta charset=\"UTF-8\"/>\n<title>{chapter_title}</title>\n<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/>\n</head>\n<body>\n<section epub:type=\"chapter\">\n{body}</section>\n</body>\n</html>\n"
+                ),
+                features,
+            ));
+
+            // Índice: capítulo y sus secciones `##`
+            let outline = chapter.outline(manuscript.math);
+            let sections: Vec<String> = outline
+                .iter()
+                .filter(|(level, _)| *level == 2)
+                .enumerate()
+                .map(|(i, (_, text))| {
+                    format!(
+                        "<li><a href=\"{}#s{}\">{}</a></li>",
+                        file,
+                        i + 1,
+                        xml_escape(text)
+                    )
+                })
+                .collect();
+            let label = if chapter.title.is_empty() {
+                format!("{}", index + 1)
+            } else {
+                chapter_title.clone()
+            };
+            toc.push_str(&format!("<li><a href=\"{}\">{}</a>", file, label));
+            if !sections.is_empty() {
+                toc.push_str(&format!("\n<ol>\n{}\n</ol>\n", sections.join("\n")));
+            }
+            toc.push_str("</li>\n");
+        }
+
+        let toc_title = self.options.toc_title();
+        let first = chapters
+            .first()
+            .map(|(file, ..)| file.clone())
+            .unwrap_or_default();
+        let nav = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" xml:lang=\"{language}\" lang=\"{language}\">\n<head>\n<meta charset=\"UTF-8\"/>\n<title>{toc_title}</title>\n<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/>\n</head>\n<body>\n<nav epub:type=\"toc\" id=\"toc\">\n<h1>{toc_title}</h1>\n<ol>\n{toc}</ol>\n</nav>\n<nav epub:type=\"landmarks\" hidden=\"hidden\">\n<ol>\n<li><a epub:type=\"toc\" href=\"nav.xhtml\">{toc_title}</a></li>\n<li><a epub:type=\"bodymatter\" href=\"{first}\">{title}</a></li>\n</ol>\n</nav>\n</body>\n</html>\n"
+        );
+
+        let mut manifest = String::from(
+            "<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n<item id=\"css\" href=\"style.css\" media-type=\"text/css\"/>\n",
+        );
+        let mut spine = String::new();
+        spine.push_str(if self.options.toc {
+            "<itemref idref=\"nav\"/>\n"
+        } else {
+            "<itemref idref=\"nav\" linear=\"no\"/>\n"
+        });
+        for (index, (file, _, features)) in chapters.iter().enumerate() {
+            let properties: Vec<&str> = [(features.mathml, "mathml"), (features.svg, "svg")]
+                .into_iter()
+                .filter_map(|(used, name)| used.then_some(name))
+                .collect();
+            let properties = if properties.is_empty() {
+                String::new()
+            } else {
+                format!(" properties=\"{}\"", properties.join(" "))
+            };
+            manifest.push_str(&format!(
+                "<item id=\"ch{:03}\" href=\"{}\" media-type=\"application/xhtml+xml\"{}/>\n",
+                index + 1,
+                file,
+                properties
+            ));
+            spine.push_str(&format!("<itemref idref=\"ch{:03}\"/>\n", index + 1));
+        }
+        for (index, file) in state.media.files.iter().enumerate() {
+            manifest.push_str(&format!(
+                "<item id=\"img{}\" href=\"images/{}\" media-type=\"{}\"/>\n",
+                index + 1,
+                file.name,
+                file.mime
+            ));
+        }
+
+        let author = self
+            .options
+            .author
+            .as_ref()
+            .map(|author| format!("<dc:creator>{}</dc:creator>\n", xml_escape(author)))
+            .unwrap_or_default();
+        let opf = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\" xml:lang=\"{language}\">\n<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n<dc:identifier id=\"book-id\">{}</dc:identifier>\n<dc:title>{title}</dc:title>\n<dc:language>{language}</dc:language>\n{author}<meta property=\"dcterms:modified\">{}</meta>\n</metadata>\n<manifest>\n{manifest}</manifest>\n<spine>\n{spine}</spine>\n</package>\n",
+            self.identifier(&sources),
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+        );
+
+        let mut zip = ZipWriter::new();
+        zip.add("mimetype", b"application/epub+zip");
+        zip.add(
+            "META-INF/container.xml",
+            b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n<rootfiles>\n<rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>\n</rootfiles>\n</container>\n",
+        );
+        zip.add("OEBPS/content.opf", opf.as_bytes());
+        zip.add("OEBPS/nav.xhtml", nav.as_bytes());
+        zip.add("OEBPS/style.css", EPUB_CSS.as_bytes());
+        for (file, xhtml, _) in &chapters {
+            zip.add(&format!("OEBPS/{}", file), xhtml.as_bytes());
+        }
+        for file in &state.media.files {
+            zip.add(&format!("OEBPS/images/{}", file.name), &file.data);
+        }
+        Ok(zip.finish())
+    }
+
+    /// Eventos de un capítulo (o nota al pie) listos para `push_html` en XHTML
+    fn xhtml_events<'a>(
+        &self,
+        events: &[Event<'a>],
+        note: usize,
+        manuscript: &Manuscript<'a>,
+        state: &mut EpubState,
+        features: &mut EpubFeatures,
+    ) -> Vec<Event<'a>> {
+        let mut out = Vec::with_capacity(events.len());
+        let mut sections = 0;
+        let mut dropped_links = Vec::new();
+        let mut dropped_image = false;
+        let mut diagram: Option<(DiagramKind, String)> = None;
+
+        for event in events {
+            if let Some((_, source)) = diagram.as_mut() {
+                match event {
+                    Event::Text(text) => source.push_str(text),
+                    Event::End(TagEnd::CodeBlock) => {
+                        let (kind, source) = diagram.take().unwrap();
+                        match render_diagram(kind, &source, &PreviewColors::print()) {
+                            Ok(svg) => {
+                                features.svg = true;
+                                out.push(Event::Html(
+                                    format!("<div class=\"diagram\">{}</div>\n", svg).into(),
+                                ));
+                            }
+                            Err(_) => {
+                                out.push(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(
+                                    kind.name().to_lowercase().into(),
+                                ))));
+                                out.push(Event::Text(source.into()));
+                                out.push(Event::End(TagEnd::CodeBlock));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match event {
+                Event::Start(Tag::Heading {
+                    level,
+                    classes,
+                    attrs,
+                    ..
+                }) => {
+                    let id = match level {
+                        HeadingLevel::H1 => None,
+                        HeadingLevel::H2 => {
+                            sections += 1;
+                            Some(CowStr::from(format!("s{}", sections)))
+                        }
+                        _ => None,
+                    };
+                    out.push(Event::Start(Tag::Heading {
+                        level: *level,
+                        id,
+                        classes: classes.clone(),
+                        attrs: attrs.clone(),
+                    }));
+                }
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                    if DiagramKind::from_info(info).is_some() =>
+                {
+                    diagram = DiagramKind::from_info(info).map(|kind| (kind, String::new()));
+                }
+                Event::Text(text) if text.contains(MATH_OPEN) => {
+                    for part in split_math(text) {
+                        match part {
+                            Ok(text) => out.push(Event::Text(text.to_string().into())),
+                            Err(index) => {
+                                let Some((tex, display)) = manuscript.math.get(index) else {
+                                    continue;
+                                };
+                                features.mathml = true;
+                                out.push(Event::Html(tex_to_mathml(tex, *display).into()));
+                            }
+                        }
+                    }
+                }
+                Event::Html(html) | Event::InlineHtml(html) => {
+                    let text = TAG_STRIP_RE.replace_all(html, "");
+                    if !text.trim().is_empty() {
+                        out.push(Event::Text(text.to_string().into()));
+                    }
+                }
+                Event::TaskListMarker(checked) => {
+                    out.push(Event::Text(if *checked { "☑ " } else { "☐ " }.into()));
+                }
+                Event::FootnoteReference(label) => {
+                    let key = (note, label.to_string());
+                    match state.footnote_numbers.get(&key).copied() {
+                        Some(number) => out.push(Event::Html(noteref(number).into())),
+                        None if manuscript.footnotes.contains_key(&key) => {
+                            let number = state.footnote_numbers.len() + 1;
+                            state.footnote_numbers.insert(key.clone(), number);
+                            state.pending.push_back((number, key));
+                            out.push(Event::Html(noteref(number).into()));
+                        }
+                        None => out.push(Event::Text(format!("[^{}]", label).into())),
+                    }
+                }
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    let external = ["http://", "https://", "mailto:"]
+                        .iter()
+                        .any(|scheme| dest_url.starts_with(scheme));
+                    dropped_links.push(!external);
+                    if external {
+                        out.push(event.clone());
+                    }
+                }
+                Event::End(TagEnd::Link) => {
+                    if !dropped_links.pop().unwrap_or(false) {
+                        out.push(event.clone());
+                    }
+                }
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }) => {
+                    match self
+                        .image_path(dest_url)
+                        .and_then(|path| state.media.add(&path))
+                    {
+                        Some(index) => out.push(Event::Start(Tag::Image {
+                            link_type: *link_type,
+                            dest_url: format!("images/{}", state.media.files[index].name).into(),
+                            title: title.clone(),
+                            id: id.clone(),
+                        })),
+                        // Sin imagen local (remota o inexistente) queda el texto alternativo
+                        None => dropped_image = true,
+                    }
+                }
+                Event::End(TagEnd::Image) if dropped_image => dropped_image = false,
+                event => out.push(event.clone()),
+            }
+        }
+        out
+    }
+}
+
+fn noteref(number: usize) -> String {
+    format!(
+        "<a epub:type=\"noteref\" class=\"noteref\" href=\"#fn{n}\" id=\"fnref{n}\">{n}</a>",
+        n = number
+    )
+}
+
+// ============================================================================
+// DOCX
+// ============================================================================
+
+const W_NS: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const R_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const REL_IMAGE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/image";
+const REL_HYPERLINK: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink";
+
+/// Relaciones fijas de `document.xml`; las imágenes y enlaces van a continuación
+const DOCX_FIXED_RELS: [(&str, &str); 4] = [
+    ("styles", "styles.xml"),
+    ("numbering", "numbering.xml"),
+    ("footnotes", "footnotes.xml"),
+    ("settings", "settings.xml"),
+];
+
+/// Estado del paquete DOCX compartido por el documento y las notas al pie
+struct DocxPackage<'m, 'a> {
+    manuscript: &'m Manuscript<'a>,
+    media: Media,
+    image_rels: HashMap<usize, String>,
+    /// Relaciones añadidas: (id, tipo, destino, externa)
+    rels: Vec<(String, &'static str, String, bool)>,
+    /// Listas numeradas: (nivel, inicio); cada una es un `w:num` propio para reiniciar
+    ordered_lists: Vec<(usize, u64)>,
+    footnote_ids: HashMap<FootnoteKey, usize>,
+    pending: VecDeque<(usize, FootnoteKey)>,
+    drawings: usize,
+}
+
+impl DocxPackage<'_, '_> {
+    fn add_rel(&mut self, kind: &'static str, target: String, external: bool) -> String {
+        let id = format!("rId{}", DOCX_FIXED_RELS.len() + self.rels.len() + 1);
+        self.rels.push((id.clone(), kind, target, external));
+        id
+    }
+
+    fn image_rel(&mut self, index: usize) -> String {
+        if let Some(id) = self.image_rels.get(&index) {
+            return id.clone();
+        }
+        let target = format!("media/{}", self.media.files[index].name);
+        let id = self.add_rel(REL_IMAGE, target, false);
+        self.image_rels.insert(index, id.clone());
+        id
+    }
+
+    /// `w:numId` de una lista nueva: 1 es la de viñetas, las numeradas van desde 2
+    fn list_num(&mut self, level: usize, start: Option<u64>) -> usize {
+        match start {
+            None => 1,
+            Some(start) => {
+                self.ordered_lists.push((level, start));
+                self.ordered_lists.len() + 1
+            }
+        }
+    }
+
+    fn footnote_id(&mut self, key: FootnoteKey) -> Option<usize> {
+        if let Some(&id) = self.footnote_ids.get(&key) {
+            return Some(id);
+        }
+        if !self.manuscript.footnotes.contains_key(&key) {
+            return None;
+        }
+        let id = self.footnote_ids.len() + 1;
+        self.footnote_ids.insert(key.clone(), id);
+        self.pending.push_back((id, key));
+        Some(id)
+    }
+}
+
+/// Escritor de eventos markdown a WordprocessingML
+struct DocxWriter<'p, 'm, 'a> {
+    compiler: &'p BookCompiler,
+    package: &'p mut DocxPackage<'m, 'a>,
+    note: usize,
+    out: String,
+    paragraph_open: bool,
+    bold: usize,
+    italic: usize,
+    strike: usize,
+    hyperlinks: Vec<bool>,
+    /// `w:numId` de cada lista abierta
+    lists: Vec<usize>,
+    item_start: bool,
+    quote: usize,
+    heading: Option<u8>,
+    alignments: Vec<Alignment>,
+    cell: Option<usize>,
+    cell_has_paragraph: bool,
+    row_cells: usize,
+    table_head: bool,
+    code_block: Option<String>,
+    image: Option<(Option<usize>, String)>,
+    /// Contenido de una nota al pie (estilo propio y marca en el primer párrafo)
+    footnote: bool,
+    footnote_mark: bool,
+}
+
+impl<'p, 'm, 'a> DocxWriter<'p, 'm, 'a> {
+    fn new(compiler: &'p BookCompiler, package: &'p mut DocxPackage<'m, 'a>, note: usize) -> Self {
+        Self {
+            compiler,
+            package,
+            note,
+            out: String::new(),
+            paragraph_open: false,
+            bold: 0,
+            italic: 0,
+            strike: 0,
+            hyperlinks: Vec::new(),
+            lists: Vec::new(),
+            item_start: false,
+            quote: 0,
+            heading: None,
+            alignments: Vec::new(),
+            cell: None,
+            cell_has_paragraph: false,
+            row_cells: 0,
+            table_head: false,
+            code_block: None,
+            image: None,
+            footnote: false,
+            footnote_mark: false,
+        }
+    }
+
+    fn open_paragraph(&mut self) {
+        if self.paragraph_open {
+            return;
+        }
+        let mut properties = String::new();
+        if let Some(level) = self.heading {
+            properties.push_str(&format!("<w:pStyle w:val=\"Heading{}\"/>", level));
+        } else if self.footnote {
+            properties.push_str("<w:pStyle w:val=\"FootnoteText\"/>");
+        } else if let Some(&num) = self.lists.last() {
+            properties.push_str("<w:pStyle w:val=\"ListParagraph\"/>");
+            let level = self.lists.len() - 1;
+            if self.item_start {
+                properties.push_str(&format!(
+                    "<w:numPr><w:ilvl w:val=\"{}\"/><w:numId w:val=\"{}\"/></w:numPr>",
+                    level, num
+                ));
+            } else {
+                properties.push_str(&format!("<w:ind w:left=\"{}\"/>", 720 * (level + 1)));
+            }
+        } else if self.quote > 0 {
+            properties.push_str("<w:pStyle w:val=\"Quote\"/>");
+        }
+        self.item_start = false;
+        if let Some(cell) = self.cell {
+            self.cell_has_paragraph = true;
+            let jc = match self.alignments.get(cell) {
+                Some(Alignment::Center) => Some("center"),
+                Some(Alignment::Right) => Some("right"),
+                _ => None,
+            };
+            if let Some(jc) = jc {
+                properties.push_str(&format!("<w:jc w:val=\"{}\"/>", jc));
+            }
+        }
+
+        self.out.push_str("<w:p>");
+        if !properties.is_empty() {
+            self.out.push_str(&format!("<w:pPr>{}</w:pPr>", properties));
+        }
+        self.paragraph_open = true;
+        if self.footnote_mark {
+            self.footnote_mark = false;
+            self.out.push_str(
+                "<w:r><w:rPr><w:rStyle w:val=\"FootnoteReference\"/></w:rPr><w:footnoteRef/></w:r><w:r><w:t xml:space=\"preserve\"> </w:t></w:r>",
+            );
+        }
+    }
+
+    fn close_paragraph(&mut self) {
+        if self.paragraph_open {
+            self.out.push_str("</w:p>");
+            self.paragraph_open = false;
+        }
+    }
+
+    fn run(&mut self, text: &str, code: bool) {
+        if text.is_empty() {
+            return;
+        }
+        self.open_paragraph();
+        let mut properties = String::new();
+        if self.hyperlinks.last() == Some(&true) {
+            properties.push_str("<w:rStyle w:val=\"Hyperlink\"/>");
+        } else if code {
+            properties.push_str("<w:rStyle w:val=\"InlineCode\"/>");
+        }
+        if self.bold > 0 || self.table_head {
+            properties.push_str("<w:b/>");
+        }
+        if self.italic > 0 {
+            properties.push_str("<w:i/>");
+        }
+        if self.strike > 0 {
+            properties.push_str("<w:strike/>");
+        }
+        self.out.push_str("<w:r>");
+        if !properties.is_empty() {
+            self.out.push_str(&format!("<w:rPr>{}</w:rPr>", properties));
+        }
+        self.out.push_str(&format!(
+            "<w:t xml:space=\"preserve\">{}</w:t></w:r>",
+            xml_escape(text)
+        ));
+    }
+
+    /// Texto con fórmulas: las fórmulas van en cursiva con su código TeX
+    fn text(&mut self, text: &str) {
+        for part in split_math(text) {
+            match part {
+                Ok(text) => self.run(text, false),
+                Err(index) => {
+                    let tex = self
+                        .package
+                        .manuscript
+                        .math
+                        .get(index)
+                        .map(|(tex, _)| tex.clone())
+                        .unwrap_or_default();
+                    self.italic += 1;
+                    self.run(&tex, false);
+                    self.italic -= 1;
+                }
+            }
+        }
+    }
+
+    fn image(&mut self, media: Option<usize>, alt: &str) {
+        let file = media.map(|index| &self.package.media.files[index]);
+        let size = file
+            .filter(|file| {
+                matches!(
+                    file.mime,
+                    "image/png" | "image/jpeg" | "image/gif" | "image/bmp"
+                )
+            })
+            .and_then(|file| image_size(&file.data))
+            .filter(|(width, height)| *width > 0 && *height > 0);
+        let (Some(index), Some((width, height)), false) = (media, size, self.footnote) else {
+            self.run(alt, false);
+            return;
+        };
+
+        let mut cx = u64::from(width) * EMU_PER_PIXEL;
+        let mut cy = u64::from(height) * EMU_PER_PIXEL;
+        if cx > DOCX_MAX_IMAGE_WIDTH {
+            cy = cy * DOCX_MAX_IMAGE_WIDTH / cx;
+            cx = DOCX_MAX_IMAGE_WIDTH;
+        }
+        let rel = self.package.image_rel(index);
+        self.package.drawings += 1;
+        let id = self.package.drawings;
+        let alt = xml_escape(alt);
+        self.open_paragraph();
+        self.out.push_str(&format!(
+            "<w:r><w:drawing><wp:inline distT=\"0\" distB=\"0\" distL=\"0\" distR=\"0\"><wp:extent cx=\"{cx}\" cy=\"{cy}\"/><wp:docPr id=\"{id}\" name=\"Imagen {id}\" descr=\"{alt}\"/><a:graphic><a:graphicData uri=\"http://schemas.openxmlformats.org/drawingml/2006/picture\"><pic:pic><pic:nvPicPr><pic:cNvPr id=\"{id}\" name=\"Imagen {id}\"/><pic:cNvPicPr/></pic:nvPicPr><pic:blipFill><a:blip r:embed=\"{rel}\"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr><a:xfrm><a:off x=\"0\" y=\"0\"/><a:ext cx=\"{cx}\" cy=\"{cy}\"/></a:xfrm><a:prstGeom prst=\"rect\"><a:avLst/></a:prstGeom></pic:spPr></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"
+        ));
+    }
+
+    fn write(&mut self, events: &[Event<'a>]) {
+        for event in events {
+            if let Some(code) = self.code_block.as_mut() {
+                match event {
+                    Event::Text(text) => code.push_str(text),
+                    Event::End(TagEnd::CodeBlock) => {
+                        let code = self.code_block.take().unwrap_or_default();
+                        for line in code.trim_end_matches('\n').split('\n') {
+                            self.out
+                                .push_str("<w:p><w:pPr><w:pStyle w:val=\"SourceCode\"/></w:pPr>");
+                            if !line.is_empty() {
+                                self.out.push_str(&format!(
+                                    "<w:r><w:t xml:space=\"preserve\">{}</w:t></w:r>",
+                                    xml_escape(line)
+                                ));
+                            }
+                            self.out.push_str("</w:p>");
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            if let Some((_, alt)) = self.image.as_mut() {
+                match event {
+                    Event::Text(text) | Event::Code(text) => alt.push_str(text),
+                    Event::End(TagEnd::Image) => {
+                        let (media, alt) = self.image.take().unwrap_or_default();
+                        self.image(media, &alt);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match event {
+                Event::Start(Tag::Paragraph) => self.open_paragraph(),
+                Event::End(TagEnd::Paragraph) => self.close_paragraph(),
+                Event::Start(Tag::Heading { level, .. }) => {
+                    self.close_paragraph();
+                    self.heading = Some(*level as u8);
+                    self.open_paragraph();
+                }
+                Event::End(TagEnd::Heading(_)) => {
+                    self.close_paragraph();
+                    self.heading = None;
+                }
+                Event::Start(Tag::BlockQuote) => {
+                    self.close_paragraph();
+                    self.quote += 1;
+                }
+                Event::End(TagEnd::BlockQuote) => {
+                    self.close_paragraph();
+                    self.quote = self.quote.saturating_sub(1);
+                }
+                Event::Start(Tag::CodeBlock(_)) => {
+                    self.close_paragraph();
+                    self.code_block = Some(String::new());
+                }
+                Event::Start(Tag::List(start)) => {
+                    self.close_paragraph();
+                    let num = self.package.list_num(self.lists.len(), *start);
+                    self.lists.push(num);
+                }
+                Event::End(TagEnd::List(_)) => {
+                    self.close_paragraph();
+                    self.lists.pop();
+                }
+                Event::Start(Tag::Item) => {
+                    self.close_paragraph();
+                    self.item_start = true;
+                }
+                Event::End(TagEnd::Item) => self.close_paragraph(),
+                Event::Start(Tag::Table(alignments)) => {
+                    self.close_paragraph();
+                    self.alignments = alignments.clone();
+                    let width = 9000 / alignments.len().max(1);
+                    let grid: String = alignments
+                        .iter()
+                        .map(|_| format!("<w:gridCol w:w=\"{}\"/>", width))
+                        .collect();
+                    self.out.push_str(&format!(
+                        "<w:tbl><w:tblPr><w:tblStyle w:val=\"TableGrid\"/><w:tblW w:w=\"0\" w:type=\"auto\"/></w:tblPr><w:tblGrid>{}</w:tblGrid>",
+                        grid
+                    ));
+                }
+                Event::End(TagEnd::Table) => {
+                    // Word necesita un párrafo entre una tabla y lo siguiente
+                    self.out.push_str("</w:tbl><w:p/>");
+                }
+                Event::Start(Tag::TableHead) => {
+                    self.table_head = true;
+                    self.row_cells = 0;
+                    self.out.push_str("<w:tr><w:trPr><w:tblHeader/></w:trPr>");
+                }
+                Event::End(TagEnd::TableHead) => {
+                    self.table_head = false;
+                    self.out.push_str("</w:tr>");
+                }
+                Event::Start(Tag::TableRow) => {
+                    self.row_cells = 0;
+                    self.out.push_str("<w:tr>");
+                }
+                Event::End(TagEnd::TableRow) => self.out.push_str("</w:tr>"),
+                Event::Start(Tag::TableCell) => {
+                    self.out.push_str("<w:tc>");
+                    self.cell = Some(self.row_cells);
+                    self.row_cells += 1;
+                    self.cell_has_paragraph = false;
+                }
+                Event::End(TagEnd::TableCell) => {
+                    self.close_paragraph();
+                    if !self.cell_has_paragraph {
+                        self.out.push_str("<w:p/>");
+                    }
+                    self.out.push_str("</w:tc>");
+                    self.cell = None;
+                }
+                Event::Start(Tag::Emphasis) => self.italic += 1,
+                Event::End(TagEnd::Emphasis) => self.italic = self.italic.saturating_sub(1),
+                Event::Start(Tag::Strong) => self.bold += 1,
+                Event::End(TagEnd::Strong) => self.bold = self.bold.saturating_sub(1),
+                Event::Start(Tag::Strikethrough) => self.strike += 1,
+                Event::End(TagEnd::Strikethrough) => self.strike = self.strike.saturating_sub(1),
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    // Las notas al pie no tienen relaciones propias: el enlace queda como texto
+                    let external = !self.footnote
+                        && ["http://", "https://", "mailto:"]
+                            .iter()
+                            .any(|scheme| dest_url.starts_with(scheme));
+                    if external {
+                        let rel = self
+                            .package
+                            .add_rel(REL_HYPERLINK, dest_url.to_string(), true);
+                        self.open_paragraph();
+                        self.out
+                            .push_str(&format!("<w:hyperlink r:id=\"{}\">", rel));
+                    }
+                    self.hyperlinks.push(external);
+                }
+                Event::End(TagEnd::Link) => {
+                    let external = self.hyperlinks.pop().unwrap_or(false);
+                    if external {
+                        self.out.push_str("</w:hyperlink>");
+                    }
+                }
+                Event::Start(Tag::Image { dest_url, .. }) => {
+                    let media = self
+                        .compiler
+                        .image_path(dest_url)
+                        .and_then(|path| self.package.media.add(&path));
+                    self.image = Some((media, String::new()));
+                }
+                Event::End(TagEnd::HtmlBlock) => self.close_paragraph(),
+                Event::Text(text) => self.text(text),
+                Event::Code(text) => self.run(text, true),
+                Event::Html(html) | Event::InlineHtml(html) => {
+                    let text = TAG_STRIP_RE.replace_all(html, "");
+                    let text = text.trim_end_matches('\n');
+                    if !text.trim().is_empty() {
+                        self.run(text, false);
+                    }
+                }
+                Event::SoftBreak => self.run(" ", false),
+                Event::HardBreak => {
+                    self.open_paragraph();
+                    self.out.push_str("<w:r><w:br/></w:r>");
+                }
+                Event::Rule => {
+                    self.close_paragraph();
+                    self.out.push_str(
+                        "<w:p><w:pPr><w:pBdr><w:bottom w:val=\"single\" w:sz=\"6\" w:space=\"1\" w:color=\"auto\"/></w:pBdr></w:pPr></w:p>",
+                    );
+                }
+                Event::TaskListMarker(checked) => {
+                    self.run(if *checked { "☑ " } else { "☐ " }, false)
+                }
+                Event::FootnoteReference(label) => {
+                    let id = if self.footnote {
+                        None
+                    } else {
+                        self.package.footnote_id((self.note, label.to_string()))
+                    };
+                    match id {
+                        Some(id) => {
+                            self.open_paragraph();
+                            self.out.push_str(&format!(
+                                "<w:r><w:rPr><w:rStyle w:val=\"FootnoteReference\"/></w:rPr><w:footnoteReference w:id=\"{}\"/></w:r>",
+                                id
+                            ));
+                        }
+                        None => self.run(&format!("[^{}]", label), false),
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.close_paragraph();
+    }
+}
+
+impl BookCompiler {
+    /// Bytes del DOCX con las notas compiladas
+    pub fn docx(&self, notes: &[ExportNote]) -> Result<Vec<u8>, BookError> {
+        let (sources, math) = self.sources(notes)?;
+        let manuscript = Manuscript::parse(&sources, &math);
+        let mut package = DocxPackage {
+            manuscript: &manuscript,
+            media: Media::default(),
+            image_rels: HashMap::new(),
+            rels: Vec::new(),
+            ordered_lists: Vec::new(),
+            footnote_ids: HashMap::new(),
+            pending: VecDeque::new(),
+            drawings: 0,
+        };
+
+        // Portada e índice
+        let mut body = format!(
+            "<w:p><w:pPr><w:pStyle w:val=\"Title\"/></w:pPr><w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+            xml_escape(&self.options.title)
+        );
+        if let Some(author) = &self.options.author {
+            body.push_str(&format!(
+                "<w:p><w:pPr><w:pStyle w:val=\"Subtitle\"/></w:pPr><w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+                xml_escape(author)
+            ));
+        }
+        if self.options.toc {
+            let entries: Vec<(u8, String)> = manuscript
+                .chapters
+                .iter()
+                .flat_map(|chapter| chapter.outline(manuscript.math))
+                .collect();
+            body.push_str(&self.docx_toc(&entries));
+        }
+
+        for chapter in &manuscript.chapters {
+            let mut writer = DocxWriter::new(self, &mut package, chapter.note);
+            writer.write(&chapter.events);
+            body.push_str(&writer.out);
+        }
+
+        // Notas al pie (las que se citan dentro de otras se añaden a la cola)
+        let mut footnotes = String::from(
+            "<w:footnote w:type=\"separator\" w:id=\"-1\"><w:p><w:pPr><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:r><w:separator/></w:r></w:p></w:footnote><w:footnote w:type=\"continuationSeparator\" w:id=\"0\"><w:p><w:pPr><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:r><w:continuationSeparator/></w:r></w:p></w:footnote>",
+        );
+        while let Some((id, key)) = package.pending.pop_front() {
+            let events = manuscript.footnotes.get(&key).cloned().unwrap_or_default();
+            let mut writer = DocxWriter::new(self, &mut package, key.0);
+            writer.footnote = true;
+            writer.footnote_mark = true;
+            writer.write(&events);
+            if writer.footnote_mark {
+                writer.open_paragraph();
+                writer.close_paragraph();
+            }
+            footnotes.push_str(&format!(
+                "<w:footnote w:id=\"{}\">{}</w:footnote>",
+                id, writer.out
+            ));
+        }
+
+        let document = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:document xmlns:w=\"{W_NS}\" xmlns:r=\"{R_NS}\" xmlns:wp=\"http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing\" xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\"><w:body>{body}<w:sectPr><w:pgSz w:w=\"11906\" w:h=\"16838\"/><w:pgMar w:top=\"1440\" w:right=\"1440\" w:bottom=\"1440\" w:left=\"1440\" w:header=\"708\" w:footer=\"708\" w:gutter=\"0\"/></w:sectPr></w:body></w:document>"
+        );
+        let footnotes = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:footnotes xmlns:w=\"{W_NS}\" xmlns:r=\"{R_NS}\" xmlns:wp=\"http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing\" xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\">{footnotes}</w:footnotes>"
+        );
+
+        let mut rels = String::new();
+        for (index, (kind, target)) in DOCX_FIXED_RELS.iter().enumerate() {
+            rels.push_str(&format!(
+                "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/{}\" Target=\"{}\"/>",
+                index + 1,
+                kind,
+                target
+            ));
+        }
+        for (id, kind, target, external) in &package.rels {
+            rels.push_str(&format!(
+                "<Relationship Id=\"{}\" Type=\"{}\" Target=\"{}\"{}/>",
+                id,
+                kind,
+                xml_escape(target),
+                if *external {
+                    " TargetMode=\"External\""
+                } else {
+                    ""
+                }
+            ));
+        }
+        let rels = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">{}</Relationships>",
+            rels
+        );
+
+        let settings = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:settings xmlns:w=\"{W_NS}\"><w:defaultTabStop w:val=\"708\"/>{}<w:footnotePr><w:footnote w:id=\"-1\"/><w:footnote w:id=\"0\"/></w:footnotePr></w:settings>",
+            if self.options.toc {
+                "<w:updateFields w:val=\"true\"/>"
+            } else {
+                ""
+            }
+        );
+
+        let author = self
+            .options
+            .author
+            .as_ref()
+            .map(|author| format!("<dc:creator>{}</dc:creator>", xml_escape(author)))
+            .unwrap_or_default();
+        let core = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<cp:coreProperties xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:dcterms=\"http://purl.org/dc/terms/\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\"><dc:title>{}</dc:title>{}<dc:language>{}</dc:language><dcterms:created xsi:type=\"dcterms:W3CDTF\">{}</dcterms:created></cp:coreProperties>",
+            xml_escape(&self.options.title),
+            author,
+            xml_escape(&self.options.language),
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+        );
+
+        let mut zip = ZipWriter::new();
+        zip.add("[Content_Types].xml", DOCX_CONTENT_TYPES.as_bytes());
+        zip.add("_rels/.rels", DOCX_PACKAGE_RELS.as_bytes());
+        zip.add("docProps/core.xml", core.as_bytes());
+        zip.add("word/document.xml", document.as_bytes());
+        zip.add("word/_rels/document.xml.rels", rels.as_bytes());
+        zip.add("word/styles.xml", self.docx_styles().as_bytes());
+        zip.add(
+            "word/numbering.xml",
+            docx_numbering(&package.ordered_lists).as_bytes(),
+        );
+        zip.add("word/footnotes.xml", footnotes.as_bytes());
+        zip.add("word/settings.xml", settings.as_bytes());
+        for index in package.image_rels.keys() {
+            let file = &package.media.files[*index];
+            zip.add(&format!("word/media/{}", file.name), &file.data);
+        }
+        Ok(zip.finish())
+    }
+
+    /// Campo `TOC` con las entradas ya escritas (Word lo actualiza al abrir)
+    fn docx_toc(&self, entries: &[(u8, String)]) -> String {
+        let mut xml = format!(
+            "<w:p><w:pPr><w:pStyle w:val=\"TOCHeading\"/></w:pPr><w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>",
+            self.options.toc_title()
+        );
+        let begin = "<w:r><w:fldChar w:fldCharType=\"begin\" w:dirty=\"true\"/></w:r><w:r><w:instrText xml:space=\"preserve\"> TOC \\o \"1-2\" \\h \\z \\u </w:instrText></w:r><w:r><w:fldChar w:fldCharType=\"separate\"/></w:r>";
+        let end = "<w:r><w:fldChar w:fldCharType=\"end\"/></w:r>";
+        if entries.is_empty() {
+            xml.push_str(&format!("<w:p>{}{}</w:p>", begin, end));
+            return xml;
+        }
+        for (i, (level, title)) in entries.iter().enumerate() {
+            xml.push_str(&format!(
+                "<w:p><w:pPr><w:pStyle w:val=\"TOC{}\"/></w:pPr>{}<w:r><w:t xml:space=\"preserve\">{}</w:t></w:r>{}</w:p>",
+                level,
+                if i == 0 { begin } else { "" },
+                xml_escape(title),
+                if i + 1 == entries.len() { end } else { "" }
+            ));
+        }
+        xml
+    }
+
+    fn docx_styles(&self) -> String {
+        let language = xml_escape(&self.options.language);
+        let headings: String = (1..=6)
+            .map(|level| {
+                let size = [36, 30, 26, 24, 24, 24][level - 1];
+                format!(
+                    "<w:style w:type=\"paragraph\" w:styleId=\"Heading{level}\"><w:name w:val=\"heading {level}\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:keepNext/>{}<w:spacing w:before=\"{}\" w:after=\"120\"/><w:outlineLvl w:val=\"{}\"/></w:pPr><w:rPr><w:b/><w:sz w:val=\"{size}\"/><w:szCs w:val=\"{size}\"/></w:rPr></w:style>",
+                    if level == 1 { "<w:pageBreakBefore/>" } else { "" },
+                    if level == 1 { 0 } else { 360 },
+                    level - 1,
+                )
+            })
+            .collect();
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:styles xmlns:w=\"{W_NS}\">\
+<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii=\"Georgia\" w:hAnsi=\"Georgia\" w:eastAsia=\"Georgia\" w:cs=\"Georgia\"/><w:sz w:val=\"24\"/><w:szCs w:val=\"24\"/><w:lang w:val=\"{language}\"/></w:rPr></w:rPrDefault><w:pPrDefault><w:pPr><w:spacing w:after=\"160\" w:line=\"300\" w:lineRule=\"auto\"/></w:pPr></w:pPrDefault></w:docDefaults>\
+<w:style w:type=\"paragraph\" w:default=\"1\" w:styleId=\"Normal\"><w:name w:val=\"Normal\"/><w:qFormat/></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"Title\"><w:name w:val=\"Title\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:spacing w:before=\"2400\" w:after=\"240\"/><w:jc w:val=\"center\"/></w:pPr><w:rPr><w:b/><w:sz w:val=\"56\"/><w:szCs w:val=\"56\"/></w:rPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"Subtitle\"><w:name w:val=\"Subtitle\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:qFormat/><w:pPr><w:jc w:val=\"center\"/></w:pPr><w:rPr><w:i/><w:sz w:val=\"32\"/><w:szCs w:val=\"32\"/></w:rPr></w:style>\
+{headings}\
+<w:style w:type=\"paragraph\" w:styleId=\"Quote\"><w:name w:val=\"Quote\"/><w:basedOn w:val=\"Normal\"/><w:qFormat/><w:pPr><w:ind w:left=\"567\" w:right=\"567\"/></w:pPr><w:rPr><w:i/><w:color w:val=\"44464F\"/></w:rPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"SourceCode\"><w:name w:val=\"Source Code\"/><w:basedOn w:val=\"Normal\"/><w:pPr><w:shd w:val=\"clear\" w:color=\"auto\" w:fill=\"F4F4F6\"/><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:rPr><w:rFonts w:ascii=\"Courier New\" w:hAnsi=\"Courier New\" w:cs=\"Courier New\"/><w:sz w:val=\"20\"/><w:szCs w:val=\"20\"/></w:rPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"ListParagraph\"><w:name w:val=\"List Paragraph\"/><w:basedOn w:val=\"Normal\"/><w:qFormat/><w:pPr><w:spacing w:after=\"60\"/><w:ind w:left=\"720\"/></w:pPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"FootnoteText\"><w:name w:val=\"footnote text\"/><w:basedOn w:val=\"Normal\"/><w:pPr><w:spacing w:after=\"0\" w:line=\"240\" w:lineRule=\"auto\"/></w:pPr><w:rPr><w:sz w:val=\"20\"/><w:szCs w:val=\"20\"/></w:rPr></w:style>\
+<w:style w:type=\"character\" w:styleId=\"FootnoteReference\"><w:name w:val=\"footnote reference\"/><w:rPr><w:vertAlign w:val=\"superscript\"/></w:rPr></w:style>\
+<w:style w:type=\"character\" w:styleId=\"Hyperlink\"><w:name w:val=\"Hyperlink\"/><w:rPr><w:color w:val=\"1E66F5\"/><w:u w:val=\"single\"/></w:rPr></w:style>\
+<w:style w:type=\"character\" w:styleId=\"InlineCode\"><w:name w:val=\"Inline Code\"/><w:rPr><w:rFonts w:ascii=\"Courier New\" w:hAnsi=\"Courier New\" w:cs=\"Courier New\"/><w:sz w:val=\"20\"/><w:szCs w:val=\"20\"/></w:rPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"TOCHeading\"><w:name w:val=\"TOC Heading\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:pPr><w:keepNext/><w:pageBreakBefore/><w:spacing w:after=\"240\"/></w:pPr><w:rPr><w:b/><w:sz w:val=\"32\"/><w:szCs w:val=\"32\"/></w:rPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"TOC1\"><w:name w:val=\"toc 1\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:pPr><w:spacing w:after=\"100\"/></w:pPr></w:style>\
+<w:style w:type=\"paragraph\" w:styleId=\"TOC2\"><w:name w:val=\"toc 2\"/><w:basedOn w:val=\"Normal\"/><w:next w:val=\"Normal\"/><w:pPr><w:spacing w:after=\"100\"/><w:ind w:left=\"440\"/></w:pPr></w:style>\
+<w:style w:type=\"table\" w:styleId=\"TableGrid\"><w:name w:val=\"Table Grid\"/><w:tblPr><w:tblBorders><w:top w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:left w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:bottom w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:right w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:insideH w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/><w:insideV w:val=\"single\" w:sz=\"4\" w:space=\"0\" w:color=\"auto\"/></w:tblBorders><w:tblCellMar><w:left w:w=\"108\" w:type=\"dxa\"/><w:right w:w=\"108\" w:type=\"dxa\"/></w:tblCellMar></w:tblPr></w:style>\
+</w:styles>"
+        )
+    }
+}
+
+/// Numeración: viñetas (`w:numId` 1) y una lista numerada propia por cada lista
+fn docx_numbering(ordered_lists: &[(usize, u64)]) -> String {
+    let levels = |ordered: bool| -> String {
+        (0..9)
+            .map(|level| {
+                let (format, text) = if ordered {
+                    ("decimal", format!("%{}.", level + 1))
+                } else {
+                    ("bullet", ["•", "◦", "▪"][level % 3].to_string())
+                };
+                format!(
+                    "<w:lvl w:ilvl=\"{level}\"><w:start w:val=\"1\"/><w:numFmt w:val=\"{format}\"/><w:lvlText w:val=\"{text}\"/><w:lvlJc w:val=\"left\"/><w:pPr><w:ind w:left=\"{}\" w:hanging=\"360\"/></w:pPr></w:lvl>",
+                    720 * (level + 1)
+                )
+            })
+            .collect()
+    };
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:numbering xmlns:w=\"{W_NS}\"><w:abstractNum w:abstractNumId=\"0\"><w:multiLevelType w:val=\"hybridMultilevel\"/>{}</w:abstractNum><w:abstractNum w:abstractNumId=\"1\"><w:multiLevelType w:val=\"hybridMultilevel\"/>{}</w:abstractNum><w:num w:numId=\"1\"><w:abstractNumId w:val=\"0\"/></w:num>",
+        levels(false),
+        levels(true)
+    );
+    for (index, (level, start)) in ordered_lists.iter().enumerate() {
+        xml.push_str(&format!(
+            "<w:num w:numId=\"{}\"><w:abstractNumId w:val=\"1\"/><w:lvlOverride w:ilvl=\"{}\"><w:startOverride w:val=\"{}\"/></w:lvlOverride></w:num>",
+            index + 2,
+            level,
+            start
+        ));
+    }
+    xml.push_str("</w:numbering>");
+    xml
+}
+
+const DOCX_CONTENT_TYPES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\"><Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/><Default Extension=\"xml\" ContentType=\"application/xml\"/><Default Extension=\"png\" ContentType=\"image/png\"/><Default Extension=\"jpg\" ContentType=\"image/jpeg\"/><Default Extension=\"jpeg\" ContentType=\"image/jpeg\"/><Default Extension=\"gif\" ContentType=\"image/gif\"/><Default Extension=\"bmp\" ContentType=\"image/bmp\"/><Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/><Override PartName=\"/word/styles.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml\"/><Override PartName=\"/word/numbering.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml\"/><Override PartName=\"/word/footnotes.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml\"/><Override PartName=\"/word/settings.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.settings+xml\"/><Override PartName=\"/docProps/core.xml\" ContentType=\"application/vnd.openxmlformats-package.core-properties+xml\"/></Types>";
+
+const DOCX_PACKAGE_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\"><Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/><Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties\" Target=\"docProps/core.xml\"/></Relationships>";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contains(bytes: &[u8], needle: &str) -> bool {
+        bytes
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    }
+
+    #[test]
+    fn test_prepare_and_order() {
+        let note = ExportNote::new(
+            "Libro/02 Nudo",
+            "---\ntags: [borrador]\n---\nTexto con #borrador y [[Otra nota|otra]].\n\n[estado::revisar] !!RECORDAR(2026-05-01, revisar)\n\n```\n#no-es-tag\n```\n",
+        );
+        let compiler = BookCompiler::new("/tmp");
+        let prepared = compiler.prepare(&note);
+        assert!(prepared.starts_with("# 02 Nudo\n"));
+        assert!(prepared.contains("Texto con y otra."));
+        assert!(!prepared.contains("RECORDAR") && !prepared.contains("estado"));
+        assert!(prepared.contains("#no-es-tag"));
+
+        let compiler = compiler.with_options(
+            BookOptions::default()
+                .with_tags(MarkupMode::Render)
+                .with_properties(MarkupMode::Render)
+                .with_reminders(MarkupMode::Render),
+        );
+        let prepared = compiler.prepare(&note);
+        assert!(prepared.contains("Texto con #borrador y otra."));
+        assert!(prepared.contains("estado: revisar"));
+        assert!(prepared.contains("🔔 revisar (2026-05-01)"));
+
+        let mut config = NotesConfig::new();
+        config.set_position("Libro/02 Nudo".to_string(), 0);
+        config.set_position("Libro/01 Inicio".to_string(), 1);
+        let mut notes = vec![
+            ExportNote::new("Libro/Apéndice", ""),
+            ExportNote::new("Libro/01 Inicio", ""),
+            ExportNote::new("Libro/02 Nudo", ""),
+        ];
+        order_notes(&mut notes, &config);
+        let names: Vec<&str> = notes.iter().map(|note| note.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Libro/02 Nudo", "Libro/01 Inicio", "Libro/Apéndice"]
+        );
+    }
+
+    #[test]
+    fn test_chapters_split_on_top_level_h1() {
+        let sources = vec![
+            "# Uno\n\n> # Cita\n\n- # En lista\n\ntexto\n\n# Dos\n".to_string(),
+            "Sin título\n".to_string(),
+        ];
+        let manuscript = Manuscript::parse(&sources, &[]);
+        let titles: Vec<&str> = manuscript
+            .chapters
+            .iter()
+            .map(|chapter| chapter.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Uno", "Dos", ""]);
+        assert_eq!(manuscript.chapters[2].note, 1);
+    }
+
+    #[test]
+    fn test_compile_epub_and_docx() {
+        let root = std::env::temp_dir().join("notnative_book_test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("img")).unwrap();
+        // PNG de 2x1 (solo cabecera: basta para medir e incrustar)
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        png.extend_from_slice(&[0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 0, 2, 0, 0, 0, 1]);
+        std::fs::write(root.join("img/mapa.png"), &png).unwrap();
+
+        let notes = vec![
+            ExportNote::new(
+                "Novela/Inicio",
+                "# Inicio\n\nEra una noche[^1] oscura. #pendiente\n\n## Llegada\n\n![Mapa](img/mapa.png)\n\n[^1]: Y fría.\n",
+            ),
+            ExportNote::new(
+                "Novela/Final",
+                "Todo termina[^1].\n\n1. uno\n2. dos\n\n| a | b |\n|:-:|--:|\n| 1 | 2 |\n\n[^1]: Otra nota con la misma etiqueta.\n",
+            ),
+        ];
+        let compiler = BookCompiler::new(&root).with_options(
+            BookOptions::default()
+                .with_title("La noche")
+                .with_author(Some("Ana".to_string())),
+        );
+
+        let epub = compiler.epub(&notes).unwrap();
+        assert_eq!(&epub[30..38], b"mimetype");
+        assert!(contains(&epub, "OEBPS/chapter-001.xhtml"));
+        assert!(contains(&epub, "OEBPS/chapter-002.xhtml"));
+        assert!(contains(&epub, "OEBPS/images/img-1.png"));
+        assert!(contains(&epub, "href=\"chapter-001.xhtml#s1\""));
+        assert!(contains(&epub, "epub:type=\"footnote\""));
+        assert!(contains(&epub, "Otra nota con la misma etiqueta."));
+        assert!(!contains(&epub, "#pendiente"));
+
+        let path = compiler
+            .compile(&notes, BookFormat::Docx, &root.join("la-noche.docx"))
+            .unwrap();
+        let docx = std::fs::read(path).unwrap();
+        assert!(contains(&docx, "word/document.xml"));
+        assert!(contains(&docx, "<w:pStyle w:val=\"Heading1\"/>"));
+        assert!(contains(&docx, "TOC \\o \"1-2\""));
+        assert!(contains(&docx, "<w:footnoteReference w:id=\"2\"/>"));
+        assert!(contains(&docx, "<wp:extent cx=\"19050\" cy=\"9525\"/>"));
+        assert!(contains(&docx, "<w:jc w:val=\"right\"/>"));
+        assert!(contains(&docx, "word/media/img-1.png"));
+        assert!(!contains(&docx, "#pendiente"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
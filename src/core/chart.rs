@@ -0,0 +1,715 @@
+//! Renderizado de gráficos SVG a partir de resultados de Bases
+//!
+//! Genera SVG autocontenido (sin JavaScript ni recursos externos) para:
+//! - La vista `ViewType::Chart` del WebView de Bases
+//! - Bloques ```chart embebidos en notas (ver `HtmlRenderer`)
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::base::{CellFormat, ChartAggregation, ChartConfig, ChartKind};
+use super::base_query::NoteWithProperties;
+use super::html_renderer::PreviewColors;
+use super::property::PropertyValue;
+
+/// Dimensiones del lienzo SVG
+const WIDTH: f64 = 720.0;
+const HEIGHT: f64 = 400.0;
+const MARGIN_LEFT: f64 = 64.0;
+const MARGIN_RIGHT: f64 = 24.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 72.0;
+/// Máximo de marcas en un eje
+const MAX_TICKS: usize = 100;
+
+/// Colores de las series (el primero se sustituye por el acento del tema)
+const PALETTE: [&str; 8] = [
+    "#89b4fa", "#a6e3a1", "#f9e2af", "#fab387", "#f38ba8", "#cba6f7", "#94e2d5", "#89dceb",
+];
+
+/// Un punto del gráfico: etiqueta del eje X y valores numéricos
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartPoint {
+    /// Etiqueta (valor de X en categorías, título de la nota en scatter)
+    pub label: String,
+    /// Valor numérico de X (solo relevante en scatter)
+    pub x: f64,
+    /// Valor agregado de Y
+    pub y: f64,
+}
+
+/// Renderer de gráficos SVG
+pub struct ChartRenderer<'a> {
+    config: &'a ChartConfig,
+    colors: PreviewColors,
+}
+
+impl<'a> ChartRenderer<'a> {
+    pub fn new(config: &'a ChartConfig, colors: PreviewColors) -> Self {
+        Self { config, colors }
+    }
+
+    /// Calcular los puntos del gráfico a partir de las notas de la Base
+    pub fn build_points(&self, notes: &[NoteWithProperties]) -> Vec<ChartPoint> {
+        if self.config.kind == ChartKind::Scatter {
+            return self.build_scatter_points(notes);
+        }
+
+        // Agrupar por valor de X, guardando la clave de orden del primer valor visto
+        let mut groups: HashMap<String, (String, Vec<f64>, usize)> = HashMap::new();
+
+        for note in notes {
+            let Some(x_value) = note.get(&self.config.x).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+
+            let entry = groups
+                .entry(x_value.to_display_string())
+                .or_insert_with(|| (x_value.sort_key(), Vec::new(), 0));
+            entry.2 += 1;
+
+            if let Some(n) = self
+                .config
+                .y
+                .as_deref()
+                .and_then(|y| note.get(y))
+                .and_then(numeric_value)
+            {
+                entry.1.push(n);
+            }
+        }
+
+        let mut points: Vec<(String, ChartPoint)> = groups
+            .into_iter()
+            .map(|(label, (sort_key, values, count))| {
+                let y = match (self.config.aggregate, &self.config.y) {
+                    (ChartAggregation::Count, None) => count as f64,
+                    (aggregation, _) => aggregation.apply(&values),
+                };
+                (
+                    sort_key,
+                    ChartPoint {
+                        label,
+                        x: 0.0,
+                        y: if y.is_nan() { 0.0 } else { y },
+                    },
+                )
+            })
+            .collect();
+
+        points.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.label.cmp(&b.1.label)));
+
+        points
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, mut point))| {
+                point.x = i as f64;
+                point
+            })
+            .collect()
+    }
+
+    /// Scatter: un punto por nota con X e Y numéricos
+    fn build_scatter_points(&self, notes: &[NoteWithProperties]) -> Vec<ChartPoint> {
+        let Some(y_prop) = self.config.y.as_deref() else {
+            return Vec::new();
+        };
+
+        notes
+            .iter()
+            .filter_map(|note| {
+                let x = note.get(&self.config.x).and_then(numeric_value)?;
+                let y = note.get(y_prop).and_then(numeric_value)?;
+                Some(ChartPoint {
+                    label: note.metadata.name.clone(),
+                    x,
+                    y,
+                })
+            })
+            .collect()
+    }
+
+    /// Renderizar el gráfico completo como SVG
+    pub fn render(&self, notes: &[NoteWithProperties]) -> String {
+        let points = self.build_points(notes);
+        self.render_points(&points)
+    }
+
+    /// Renderizar puntos ya calculados como SVG
+    pub fn render_points(&self, points: &[ChartPoint]) -> String {
+        let mut svg = String::new();
+        let _ = write!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" class="nn-chart" viewBox="0 0 {w} {h}" width="100%" preserveAspectRatio="xMidYMid meet" font-family="Inter, sans-serif" font-size="12">"#,
+            w = WIDTH,
+            h = HEIGHT
+        );
+
+        if let Some(title) = &self.config.title {
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="22" text-anchor="middle" font-size="15" font-weight="600" fill="{}">{}</text>"#,
+                WIDTH / 2.0,
+                self.colors.fg_primary,
+                escape_xml(title)
+            );
+        }
+
+        if points.is_empty() {
+            let _ = write!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="middle" fill="{}">No data</text>"#,
+                WIDTH / 2.0,
+                HEIGHT / 2.0,
+                self.colors.fg_muted
+            );
+        } else {
+            match self.config.kind {
+                ChartKind::Bar => self.render_bars(&mut svg, points),
+                ChartKind::Line => self.render_line(&mut svg, points),
+                ChartKind::Pie => self.render_pie(&mut svg, points),
+                ChartKind::Scatter => self.render_scatter(&mut svg, points),
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    fn render_bars(&self, svg: &mut String, points: &[ChartPoint]) {
+        let (min, max) = value_range(points.iter().map(|p| p.y), true);
+        let ticks = nice_ticks(min, max, 5);
+        let (lo, hi) = tick_bounds(&ticks);
+        self.render_y_axis(svg, &ticks, lo, hi);
+
+        let band = plot_width() / points.len() as f64;
+        let bar_width = (band * 0.7).max(1.0);
+        let zero_y = scale(0.0, lo, hi, plot_bottom(), MARGIN_TOP);
+
+        for (i, point) in points.iter().enumerate() {
+            let x = MARGIN_LEFT + band * i as f64 + (band - bar_width) / 2.0;
+            let y = scale(point.y, lo, hi, plot_bottom(), MARGIN_TOP);
+            let (top, height) = if y < zero_y {
+                (y, zero_y - y)
+            } else {
+                (zero_y, y - zero_y)
+            };
+            let _ = write!(
+                svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" rx="3" fill="{}"><title>{}: {}</title></rect>"#,
+                x,
+                top,
+                bar_width,
+                height,
+                self.series_color(0),
+                escape_xml(&point.label),
+                format_value(point.y)
+            );
+        }
+
+        self.render_category_labels(svg, points);
+    }
+
+    fn render_line(&self, svg: &mut String, points: &[ChartPoint]) {
+        let (min, max) = value_range(points.iter().map(|p| p.y), false);
+        let ticks = nice_ticks(min, max, 5);
+        let (lo, hi) = tick_bounds(&ticks);
+        self.render_y_axis(svg, &ticks, lo, hi);
+
+        let band = plot_width() / points.len() as f64;
+        let coords: Vec<(f64, f64)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                (
+                    MARGIN_LEFT + band * (i as f64 + 0.5),
+                    scale(p.y, lo, hi, plot_bottom(), MARGIN_TOP),
+                )
+            })
+            .collect();
+
+        let path: Vec<String> = coords
+            .iter()
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect();
+        let _ = write!(
+            svg,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2.5" stroke-linejoin="round"/>"#,
+            path.join(" "),
+            self.series_color(0)
+        );
+
+        for ((x, y), point) in coords.iter().zip(points) {
+            let _ = write!(
+                svg,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="4" fill="{}"><title>{}: {}</title></circle>"#,
+                x,
+                y,
+                self.series_color(0),
+                escape_xml(&point.label),
+                format_value(point.y)
+            );
+        }
+
+        self.render_category_labels(svg, points);
+    }
+
+    fn render_pie(&self, svg: &mut String, points: &[ChartPoint]) {
+        let slices: Vec<&ChartPoint> = points.iter().filter(|p| p.y > 0.0).collect();
+        let total: f64 = slices.iter().map(|p| p.y).sum();
+        if total <= 0.0 {
+            return;
+        }
+
+        let radius = (HEIGHT - MARGIN_TOP - 24.0) / 2.0;
+        let cx = MARGIN_LEFT + radius + 16.0;
+        let cy = MARGIN_TOP + radius;
+        let mut angle = -std::f64::consts::FRAC_PI_2;
+
+        for (i, point) in slices.iter().enumerate() {
+            let fraction = point.y / total;
+            let color = self.series_color(i);
+            let tooltip = format!(
+                "{}: {} ({:.1}%)",
+                escape_xml(&point.label),
+                format_value(point.y),
+                fraction * 100.0
+            );
+
+            if slices.len() == 1 {
+                let _ = write!(
+                    svg,
+                    r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}"><title>{}</title></circle>"#,
+                    cx, cy, radius, color, tooltip
+                );
+            } else {
+                let end = angle + fraction * std::f64::consts::TAU;
+                let large_arc = if fraction > 0.5 { 1 } else { 0 };
+                let _ = write!(
+                    svg,
+                    r#"<path d="M {:.1} {:.1} L {:.1} {:.1} A {:.1} {:.1} 0 {} 1 {:.1} {:.1} Z" fill="{}" stroke="{}" stroke-width="1"><title>{}</title></path>"#,
+                    cx,
+                    cy,
+                    cx + radius * angle.cos(),
+                    cy + radius * angle.sin(),
+                    radius,
+                    radius,
+                    large_arc,
+                    cx + radius * end.cos(),
+                    cy + radius * end.sin(),
+                    color,
+                    self.colors.bg_primary,
+                    tooltip
+                );
+                angle = end;
+            }
+
+            // Leyenda a la derecha
+            let legend_x = cx + radius + 40.0;
+            let legend_y = MARGIN_TOP + 8.0 + i as f64 * 22.0;
+            if legend_y < HEIGHT - 8.0 {
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{:.1}" y="{:.1}" width="12" height="12" rx="2" fill="{}"/><text x="{:.1}" y="{:.1}" fill="{}">{} — {:.1}%</text>"#,
+                    legend_x,
+                    legend_y - 10.0,
+                    color,
+                    legend_x + 18.0,
+                    legend_y,
+                    self.colors.fg_primary,
+                    escape_xml(&truncate_label(&point.label, 32)),
+                    fraction * 100.0
+                );
+            }
+        }
+    }
+
+    fn render_scatter(&self, svg: &mut String, points: &[ChartPoint]) {
+        let (y_min, y_max) = value_range(points.iter().map(|p| p.y), false);
+        let y_ticks = nice_ticks(y_min, y_max, 5);
+        let (y_lo, y_hi) = tick_bounds(&y_ticks);
+        self.render_y_axis(svg, &y_ticks, y_lo, y_hi);
+
+        let (x_min, x_max) = value_range(points.iter().map(|p| p.x), false);
+        let x_ticks = nice_ticks(x_min, x_max, 6);
+        let (x_lo, x_hi) = tick_bounds(&x_ticks);
+        let x_end = MARGIN_LEFT + plot_width();
+
+        for tick in &x_ticks {
+            let x = scale(*tick, x_lo, x_hi, MARGIN_LEFT, x_end);
+            let _ = write!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="{}">{}</text>"#,
+                x,
+                plot_bottom() + 18.0,
+                self.colors.fg_secondary,
+                format_value(*tick)
+            );
+        }
+
+        for point in points {
+            let _ = write!(
+                svg,
+                r#"<circle cx="{:.1}" cy="{:.1}" r="5" fill="{}" fill-opacity="0.8"><title>{}: ({}, {})</title></circle>"#,
+                scale(point.x, x_lo, x_hi, MARGIN_LEFT, x_end),
+                scale(point.y, y_lo, y_hi, plot_bottom(), MARGIN_TOP),
+                self.series_color(0),
+                escape_xml(&point.label),
+                format_value(point.x),
+                format_value(point.y)
+            );
+        }
+
+        self.render_axis_title(svg, &self.config.x, WIDTH / 2.0, HEIGHT - 16.0);
+    }
+
+    /// Eje Y con líneas de cuadrícula horizontales
+    fn render_y_axis(&self, svg: &mut String, ticks: &[f64], lo: f64, hi: f64) {
+        let x_end = MARGIN_LEFT + plot_width();
+        for tick in ticks {
+            let y = scale(*tick, lo, hi, plot_bottom(), MARGIN_TOP);
+            let _ = write!(
+                svg,
+                r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="1"/><text x="{:.1}" y="{:.1}" text-anchor="end" fill="{}">{}</text>"#,
+                MARGIN_LEFT,
+                y,
+                x_end,
+                y,
+                self.colors.border,
+                MARGIN_LEFT - 8.0,
+                y + 4.0,
+                self.colors.fg_secondary,
+                format_value(*tick)
+            );
+        }
+
+        let y_title = match (&self.config.y, self.config.aggregate) {
+            (Some(y), ChartAggregation::Count) => format!("count({})", y),
+            (Some(y), aggregation) => format!("{:?}({})", aggregation, y).to_lowercase(),
+            (None, _) => "count".to_string(),
+        };
+        let _ = write!(
+            svg,
+            r#"<text x="14" y="{:.1}" text-anchor="middle" transform="rotate(-90 14 {:.1})" fill="{}">{}</text>"#,
+            (MARGIN_TOP + plot_bottom()) / 2.0,
+            (MARGIN_TOP + plot_bottom()) / 2.0,
+            self.colors.fg_muted,
+            escape_xml(&y_title)
+        );
+    }
+
+    /// Etiquetas de categoría bajo el eje X (rotadas si hay muchas)
+    fn render_category_labels(&self, svg: &mut String, points: &[ChartPoint]) {
+        let band = plot_width() / points.len() as f64;
+        let rotate = points.len() > 8;
+        // Con muchas categorías, mostrar solo una de cada N etiquetas
+        let step = (points.len() / 24).max(1);
+
+        for (i, point) in points.iter().enumerate().step_by(step) {
+            let x = MARGIN_LEFT + band * (i as f64 + 0.5);
+            let y = plot_bottom() + 16.0;
+            let label = escape_xml(&truncate_label(&point.label, if rotate { 14 } else { 18 }));
+            if rotate {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{:.1}" y="{:.1}" text-anchor="end" transform="rotate(-40 {:.1} {:.1})" fill="{}">{}</text>"#,
+                    x, y, x, y, self.colors.fg_secondary, label
+                );
+            } else {
+                let _ = write!(
+                    svg,
+                    r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="{}">{}</text>"#,
+                    x, y, self.colors.fg_secondary, label
+                );
+            }
+        }
+
+        let _ = write!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="1.5"/>"#,
+            MARGIN_LEFT,
+            plot_bottom(),
+            MARGIN_LEFT + plot_width(),
+            plot_bottom(),
+            self.colors.fg_muted
+        );
+    }
+
+    fn render_axis_title(&self, svg: &mut String, title: &str, x: f64, y: f64) {
+        let _ = write!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" fill="{}">{}</text>"#,
+            x,
+            y,
+            self.colors.fg_muted,
+            escape_xml(title)
+        );
+    }
+
+    fn series_color(&self, index: usize) -> &str {
+        if index == 0 {
+            &self.colors.accent
+        } else {
+            PALETTE[index % PALETTE.len()]
+        }
+    }
+}
+
+/// Renderizar un gráfico SVG para un conjunto de notas
+pub fn render_chart_svg(
+    config: &ChartConfig,
+    notes: &[NoteWithProperties],
+    colors: PreviewColors,
+) -> String {
+    ChartRenderer::new(config, colors).render(notes)
+}
+
+/// Extraer un valor numérico de una propiedad (números, texto numérico o checkbox)
+fn numeric_value(value: &PropertyValue) -> Option<f64> {
+    match value {
+        PropertyValue::Number(n) => Some(*n),
+        PropertyValue::Text(s) => s.trim().replace(',', ".").parse().ok(),
+        PropertyValue::Checkbox(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+fn plot_width() -> f64 {
+    WIDTH - MARGIN_LEFT - MARGIN_RIGHT
+}
+
+fn plot_bottom() -> f64 {
+    HEIGHT - MARGIN_BOTTOM
+}
+
+/// Interpolación lineal de `value` desde [lo, hi] a [out_lo, out_hi]
+fn scale(value: f64, lo: f64, hi: f64, out_lo: f64, out_hi: f64) -> f64 {
+    if (hi - lo).abs() < f64::EPSILON {
+        return (out_lo + out_hi) / 2.0;
+    }
+    out_lo + (value - lo) / (hi - lo) * (out_hi - out_lo)
+}
+
+/// Rango de valores, incluyendo opcionalmente el 0 (barras)
+fn value_range(values: impl Iterator<Item = f64>, include_zero: bool) -> (f64, f64) {
+    let (mut min, mut max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+    if !min.is_finite() || !max.is_finite() {
+        return (0.0, 1.0);
+    }
+    if include_zero {
+        min = min.min(0.0);
+        max = max.max(0.0);
+    }
+    if (max - min).abs() < f64::EPSILON {
+        max = min + 1.0;
+    }
+    (min, max)
+}
+
+fn tick_bounds(ticks: &[f64]) -> (f64, f64) {
+    (
+        ticks.first().copied().unwrap_or(0.0),
+        ticks.last().copied().unwrap_or(1.0),
+    )
+}
+
+/// Generar marcas "bonitas" (1, 2, 5 × 10^n) que cubren [min, max]
+fn nice_ticks(min: f64, max: f64, count: usize) -> Vec<f64> {
+    let range = nice_number(max - min, false);
+    let step = nice_number(range / (count.max(2) - 1) as f64, true);
+    let start = (min / step).floor() * step;
+    let end = (max / step).ceil() * step;
+    // Con valores enormes el paso puede ser menor que la precisión de `start`
+    if !step.is_finite() || !start.is_finite() || start + step == start {
+        return vec![min, max];
+    }
+
+    let count = (((end - start) / step).round() as usize).min(MAX_TICKS);
+    (0..=count)
+        .map(|i| {
+            // Evitar -0 y errores de coma flotante
+            let rounded = ((start + i as f64 * step) / step).round() * step;
+            if rounded == 0.0 { 0.0 } else { rounded }
+        })
+        .collect()
+}
+
+fn nice_number(value: f64, round: bool) -> f64 {
+    if value <= 0.0 {
+        return 1.0;
+    }
+    let exponent = value.log10().floor();
+    let fraction = value / 10f64.powf(exponent);
+    let nice = if round {
+        if fraction < 1.5 {
+            1.0
+        } else if fraction < 3.0 {
+            2.0
+        } else if fraction < 7.0 {
+            5.0
+        } else {
+            10.0
+        }
+    } else if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * 10f64.powf(exponent)
+}
+
+fn format_value(value: f64) -> String {
+    CellFormat::new().format_number(value)
+}
+
+fn truncate_label(label: &str, max_chars: usize) -> String {
+    if label.chars().count() > max_chars {
+        let truncated: String = label.chars().take(max_chars - 1).collect();
+        format!("{}…", truncated)
+    } else {
+        label.to_string()
+    }
+}
+
+pub(crate) fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::database::NoteMetadata;
+
+    fn note(name: &str, props: Vec<(&str, PropertyValue)>) -> NoteWithProperties {
+        NoteWithProperties {
+            metadata: NoteMetadata {
+                id: 1,
+                name: name.to_string(),
+                path: format!("/test/{}.md", name),
+                folder: None,
+                order_index: 0,
+                icon: None,
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            },
+            properties: props.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+            content: None,
+        }
+    }
+
+    fn sample_notes() -> Vec<NoteWithProperties> {
+        vec![
+            note(
+                "a",
+                vec![
+                    ("status", PropertyValue::Text("done".into())),
+                    ("hours", PropertyValue::Number(2.0)),
+                ],
+            ),
+            note(
+                "b",
+                vec![
+                    ("status", PropertyValue::Text("todo".into())),
+                    ("hours", PropertyValue::Number(3.0)),
+                ],
+            ),
+            note(
+                "c",
+                vec![
+                    ("status", PropertyValue::Text("done".into())),
+                    ("hours", PropertyValue::Text("4.5".into())),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_build_points_aggregation() {
+        let notes = sample_notes();
+
+        let count = ChartConfig::new(ChartKind::Bar, "status");
+        let points = ChartRenderer::new(&count, PreviewColors::default()).build_points(&notes);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].label, "done");
+        assert_eq!(points[0].y, 2.0);
+        assert_eq!(points[1].y, 1.0);
+
+        let sum = ChartConfig::new(ChartKind::Bar, "status").with_y("hours", ChartAggregation::Sum);
+        let points = ChartRenderer::new(&sum, PreviewColors::default()).build_points(&notes);
+        assert_eq!(points[0].y, 6.5);
+        assert_eq!(points[1].y, 3.0);
+
+        let avg =
+            ChartConfig::new(ChartKind::Line, "status").with_y("hours", ChartAggregation::Avg);
+        let points = ChartRenderer::new(&avg, PreviewColors::default()).build_points(&notes);
+        assert_eq!(points[0].y, 3.25);
+    }
+
+    #[test]
+    fn test_scatter_points_require_numeric_values() {
+        let notes = sample_notes();
+        let config =
+            ChartConfig::new(ChartKind::Scatter, "hours").with_y("hours", ChartAggregation::Count);
+        let points = ChartRenderer::new(&config, PreviewColors::default()).build_points(&notes);
+
+        assert_eq!(points.len(), 3);
+        assert!(points.iter().all(|p| p.x == p.y));
+    }
+
+    #[test]
+    fn test_render_svg_is_self_contained() {
+        let notes = sample_notes();
+        for kind in [
+            ChartKind::Bar,
+            ChartKind::Line,
+            ChartKind::Pie,
+            ChartKind::Scatter,
+        ] {
+            let mut config =
+                ChartConfig::new(kind, "status").with_y("hours", ChartAggregation::Sum);
+            if kind == ChartKind::Scatter {
+                config.x = "hours".to_string();
+            }
+            config.title = Some("Horas <semana>".to_string());
+            let svg = render_chart_svg(&config, &notes, PreviewColors::default());
+
+            assert!(svg.starts_with("<svg"));
+            assert!(svg.ends_with("</svg>"));
+            assert!(svg.contains("Horas &lt;semana&gt;"));
+            assert!(!svg.contains("<script"));
+            assert!(!svg.contains("No data"));
+        }
+
+        let empty = render_chart_svg(
+            &ChartConfig::new(ChartKind::Bar, "missing"),
+            &notes,
+            PreviewColors::default(),
+        );
+        assert!(empty.contains("No data"));
+    }
+
+    #[test]
+    fn test_nice_ticks() {
+        assert_eq!(nice_ticks(0.0, 6.5, 5), vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+        let ticks = nice_ticks(-3.0, 12.0, 5);
+        assert!(ticks.first().unwrap() <= &-3.0);
+        assert!(ticks.last().unwrap() >= &12.0);
+
+        // Valores enormes: el paso no llega a mover la marca y no se itera sin fin
+        assert_eq!(nice_ticks(1e18, 1e18, 5), vec![1e18, 1e18]);
+        let ticks = nice_ticks(1e18, 1.0000001e18, 5);
+        assert!(ticks.len() >= 2 && ticks.len() <= MAX_TICKS + 1);
+        let (min, max) = value_range([1e18, 3e18].into_iter(), true);
+        let ticks = nice_ticks(min, max, 5);
+        assert!(*ticks.last().unwrap() >= 3e18);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::core::EditorMode;
+use crate::core::ex_command::{CommandHistory, ExCommand, ExCommandKind};
+use crate::core::registers::{MACRO_ESCAPE, Registers};
 
 /// Acciones que el editor puede realizar en respuesta a comandos
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,6 +30,11 @@ pub enum EditorAction {
     InsertImage,
     /// Insertar tabla
     InsertTable,
+    /// Tab / Shift-Tab en modo Insert: celda siguiente o anterior dentro de una
+    /// tabla (realineándola); fuera de una tabla Tab inserta un tabulador
+    Tab {
+        backwards: bool,
+    },
 
     /// Undo/Redo
     Undo,
@@ -47,6 +54,11 @@ pub enum EditorAction {
     /// Búsqueda
     Search(String),
 
+    /// Comando ex que ejecuta la aplicación (`:%s`, `:g`, `:e`, `:mcp`...)
+    Ex(ExCommand),
+    /// Error al interpretar un comando ex (se muestra al usuario)
+    CommandError(String),
+
     /// Sidebar
     OpenSidebar,
     OpenBasesSidebar,
@@ -55,28 +67,352 @@ pub enum EditorAction {
     /// Crear nueva nota
     CreateNote,
 
+    /// Movimiento vim con contador (3w, f{c}, %, 5G...)
+    Motion {
+        motion: Motion,
+        count: usize,
+    },
+
+    /// Operador aplicado a un movimiento, objeto de texto o líneas (3dw, ciw, yap, >>)
+    Operate {
+        operator: Operator,
+        target: OperatorTarget,
+        count: usize,
+    },
+
+    /// Repetir un cambio (`.`) re-insertando el texto escrito tras el operador `c`
+    RepeatChange {
+        action: Box<EditorAction>,
+        inserted: String,
+    },
+
+    /// Pegar el registro sin nombre (`p` después del cursor, `P` antes)
+    Put {
+        before: bool,
+        count: usize,
+    },
+
+    /// Acción con registro explícito (`"ayy`, `"bp`, `"_dd`)
+    UseRegister {
+        register: char,
+        action: Box<EditorAction>,
+    },
+
+    /// Insertar texto en el cursor sin cambiar de modo (al reproducir macros)
+    InsertText(String),
+
+    /// Varias acciones seguidas (reproducción de macros con `@`)
+    Sequence(Vec<EditorAction>),
+
+    /// Empezar a grabar una macro (`q{reg}`)
+    StartMacro(char),
+    /// Terminar la grabación (`q`) y guardarla en el registro
+    StopMacro(char),
+
+    /// Plegar o desplegar secciones por encabezado (`za`, `zo`, `zc`, `zM`, `zR`)
+    Fold(FoldCommand),
+
+    /// Mover o promover/degradar la sección actual con sus subsecciones
+    EditSection {
+        edit: SectionEdit,
+        count: usize,
+    },
+
     /// Sin acción
     None,
 }
 
+/// Comandos de plegado (`z`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldCommand {
+    /// `za`
+    Toggle,
+    /// `zo`
+    Open,
+    /// `zc`
+    Close,
+    /// `zR`
+    OpenAll,
+    /// `zM`
+    CloseAll,
+}
+
+/// Ediciones sobre una sección completa
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionEdit {
+    /// `gK`: intercambiar con la sección hermana anterior
+    MoveUp,
+    /// `gJ`: intercambiar con la sección hermana siguiente
+    MoveDown,
+    /// `g<`: subir un nivel (`##` → `#`)
+    Promote,
+    /// `g>`: bajar un nivel (`#` → `##`)
+    Demote,
+}
+
+/// Operadores vim que esperan un movimiento u objeto de texto
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `d`
+    Delete,
+    /// `c`
+    Change,
+    /// `y`
+    Yank,
+    /// `>`
+    Indent,
+    /// `<`
+    Outdent,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'd' => Some(Self::Delete),
+            'c' => Some(Self::Change),
+            'y' => Some(Self::Yank),
+            '>' => Some(Self::Indent),
+            '<' => Some(Self::Outdent),
+            _ => None,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Self::Delete => 'd',
+            Self::Change => 'c',
+            Self::Yank => 'y',
+            Self::Indent => '>',
+            Self::Outdent => '<',
+        }
+    }
+
+    /// Si el operador modifica el texto (y por tanto se puede repetir con `.`)
+    pub fn is_change(self) -> bool {
+        self != Self::Yank
+    }
+}
+
+/// Movimientos vim
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    /// `w` / `W`
+    WordForward,
+    BigWordForward,
+    /// `b` / `B`
+    WordBackward,
+    BigWordBackward,
+    /// `e` / `E`
+    WordEnd,
+    BigWordEnd,
+    /// `0`
+    LineStart,
+    /// `^`
+    FirstNonBlank,
+    /// `$`
+    LineEnd,
+    /// `gg`
+    DocStart,
+    /// `G`
+    DocEnd,
+    /// `{N}G` / `{N}gg` (1-indexed)
+    GotoLine(usize),
+    /// `}` / `{`
+    ParagraphForward,
+    ParagraphBackward,
+    /// `f{c}` / `F{c}`
+    FindChar(char),
+    FindCharBackward(char),
+    /// `t{c}` / `T{c}`
+    TillChar(char),
+    TillCharBackward(char),
+    /// `%`
+    MatchingPair,
+    /// `]]` / `[[`: siguiente / anterior encabezado
+    SectionForward,
+    SectionBackward,
+}
+
+impl Motion {
+    /// Movimientos por líneas: un operador afecta a líneas completas
+    pub fn is_linewise(self) -> bool {
+        matches!(
+            self,
+            Self::Up | Self::Down | Self::DocStart | Self::DocEnd | Self::GotoLine(_)
+        )
+    }
+
+    /// Movimientos inclusivos: el operador incluye el carácter de destino
+    pub fn is_inclusive(self) -> bool {
+        matches!(
+            self,
+            Self::WordEnd
+                | Self::BigWordEnd
+                | Self::FindChar(_)
+                | Self::TillChar(_)
+                | Self::MatchingPair
+        )
+    }
+
+    /// Dirección opuesta de una búsqueda f/F/t/T (para `,`)
+    fn reversed(self) -> Self {
+        match self {
+            Self::FindChar(c) => Self::FindCharBackward(c),
+            Self::FindCharBackward(c) => Self::FindChar(c),
+            Self::TillChar(c) => Self::TillCharBackward(c),
+            Self::TillCharBackward(c) => Self::TillChar(c),
+            other => other,
+        }
+    }
+}
+
+/// Tipos de objeto de texto (`iw`, `a(`, `il`...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextObjectKind {
+    /// `w`
+    Word,
+    /// `W`
+    BigWord,
+    /// `p`
+    Paragraph,
+    /// `"`, `'` o `` ` ``
+    Quote(char),
+    /// `(`/`b`, `[`, `{`/`B`, `<`
+    Pair(char, char),
+    /// `l`: enlace markdown `[texto](url)` o wikilink `[[Nota]]`
+    Link,
+    /// `:`: propiedad inline `[clave::valor]`
+    InlineProperty,
+    /// `c`: bloque de código con vallas (o código inline)
+    CodeBlock,
+}
+
+impl TextObjectKind {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'w' => Some(Self::Word),
+            'W' => Some(Self::BigWord),
+            'p' => Some(Self::Paragraph),
+            '"' | '\'' | '`' => Some(Self::Quote(c)),
+            '(' | ')' | 'b' => Some(Self::Pair('(', ')')),
+            '[' | ']' => Some(Self::Pair('[', ']')),
+            '{' | '}' | 'B' => Some(Self::Pair('{', '}')),
+            '<' | '>' => Some(Self::Pair('<', '>')),
+            'l' => Some(Self::Link),
+            ':' => Some(Self::InlineProperty),
+            'c' => Some(Self::CodeBlock),
+            _ => None,
+        }
+    }
+}
+
+/// Objeto de texto: `i` (interior) o `a` (incluyendo delimitadores/espacios)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextObject {
+    pub kind: TextObjectKind,
+    pub inner: bool,
+}
+
+/// Destino de un operador
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatorTarget {
+    Motion(Motion),
+    TextObject(TextObject),
+    /// Operador duplicado (`dd`, `cc`, `yy`, `>>`): líneas completas
+    Line,
+}
+
+/// Comando normal a medio escribir: contadores, operador y prefijos
+#[derive(Debug, Default, Clone)]
+struct PendingCommand {
+    /// Contador antes del operador (`3dw`)
+    count: Option<usize>,
+    operator: Option<Operator>,
+    /// Contador después del operador (`d3w`)
+    operator_count: Option<usize>,
+    /// Prefijo que espera otra tecla: `g`, `f`, `F`, `t`, `T`, `i`, `a`, `"`, `q`, `@`
+    prefix: Option<char>,
+    /// Registro elegido con `"{reg}`
+    register: Option<char>,
+}
+
+/// Límite de contadores para evitar desbordes con `99999999dd`
+const MAX_COUNT: usize = 100_000;
+
+/// Profundidad máxima de macros que se llaman entre sí (`@a` dentro de `@b`)
+const MAX_MACRO_DEPTH: usize = 16;
+
 /// Parser de comandos estilo vim
 #[derive(Debug)]
 pub struct CommandParser {
-    /// Buffer acumulativo para comandos multi-tecla (ej: "dd", "2w")
-    pending: String,
+    /// Estado acumulado para comandos multi-tecla (ej: "dd", "3dw", "ciw")
+    pending: PendingCommand,
+    /// Último cambio realizado (para `.`)
+    last_change: Option<EditorAction>,
+    /// Texto escrito en modo Insert tras el último `c` (para `.`)
+    last_inserted: Option<String>,
+    /// Última búsqueda f/F/t/T (para `;` y `,`)
+    last_find: Option<Motion>,
+    /// Si el próximo texto escrito en Insert viene de un `c` (para `.`)
+    change_awaits_text: bool,
+    /// Registros de copia/borrado y macros
+    registers: Registers,
+    /// Macro en grabación: registro y teclas acumuladas
+    recording: Option<(char, String)>,
+    /// Nivel de anidamiento de la macro que se está reproduciendo
+    replay_depth: usize,
+    /// Comandos ex ejecutados (↑/↓ en modo Comando)
+    history: CommandHistory,
 }
 
 impl CommandParser {
     pub fn new() -> Self {
         Self {
-            pending: String::new(),
+            pending: PendingCommand::default(),
+            last_change: None,
+            last_inserted: None,
+            last_find: None,
+            change_awaits_text: false,
+            registers: Registers::new(),
+            recording: None,
+            replay_depth: 0,
+            history: CommandHistory::new(),
         }
     }
 
+    /// Usar registros guardados (los que se persisten en `NotesConfig`)
+    pub fn with_registers(mut self, registers: Registers) -> Self {
+        self.registers = registers;
+        self
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// Registro de la macro que se está grabando
+    pub fn recording_macro(&self) -> Option<char> {
+        self.recording.as_ref().map(|(name, _)| *name)
+    }
+
     /// Procesa una tecla en modo Normal y devuelve una acción
     pub fn parse_normal_mode(&mut self, key: &str, modifiers: KeyModifiers) -> EditorAction {
+        // Las teclas modificadoras solas no interrumpen un comando pendiente
+        if is_modifier_key(key) {
+            return EditorAction::None;
+        }
+
         // Comandos con modificadores (Ctrl, Alt)
         if modifiers.ctrl {
+            self.clear_pending();
             return match key {
                 "s" => EditorAction::Save,
                 "z" => EditorAction::Undo,
@@ -88,61 +424,472 @@ impl CommandParser {
             };
         }
 
-        // Comandos de una sola tecla
-        match key {
-            "i" => EditorAction::ChangeMode(EditorMode::Insert),
-            "a" => EditorAction::ChangeMode(EditorMode::ChatAI),
-            ":" => EditorAction::ChangeMode(EditorMode::Command),
-            "v" => EditorAction::ChangeMode(EditorMode::Visual),
-            "t" => EditorAction::OpenSidebar,
-            "b" => EditorAction::OpenBasesSidebar,
-            "n" => EditorAction::CreateNote,
+        // ESC en modo Normal: cancelar comando pendiente y cerrar sidebar si está abierto
+        if key == "Escape" {
+            self.clear_pending();
+            self.record_key(MACRO_ESCAPE);
+            return EditorAction::CloseSidebar;
+        }
+
+        let ch = match key {
+            "Left" => 'h',
+            "Down" => 'j',
+            "Up" => 'k',
+            "Right" => 'l',
+            _ => match key_to_char(key) {
+                Some(c) => c,
+                None => {
+                    self.clear_pending();
+                    return EditorAction::None;
+                }
+            },
+        };
+
+        let action = self.parse_normal_char(ch);
+        if !matches!(
+            action,
+            EditorAction::StartMacro(_) | EditorAction::StopMacro(_)
+        ) {
+            self.record_key(ch);
+        }
+        action
+    }
+
+    /// Añadir una tecla a la macro en grabación
+    fn record_key(&mut self, ch: char) {
+        if let Some((_, keys)) = &mut self.recording {
+            keys.push(ch);
+        }
+    }
+
+    /// Gramática completa, envolviendo la acción si se eligió un registro con `"`
+    fn parse_normal_char(&mut self, ch: char) -> EditorAction {
+        let register = self.pending.register;
+        let action = self.parse_command_char(ch);
+        match (register, action) {
+            (
+                Some(register),
+                action @ (EditorAction::Operate { .. } | EditorAction::Put { .. }),
+            ) => EditorAction::UseRegister {
+                register,
+                action: Box::new(action),
+            },
+            (_, action) => action,
+        }
+    }
+
+    /// Gramática operador + contador + movimiento/objeto de texto
+    fn parse_command_char(&mut self, ch: char) -> EditorAction {
+        // 1. Prefijos que esperan un carácter más
+        if let Some(prefix) = self.pending.prefix.take() {
+            return match prefix {
+                '"' if Registers::is_valid_name(ch) => {
+                    self.pending.register = Some(ch);
+                    EditorAction::None
+                }
+                'q' if Registers::is_macro_name(ch) => {
+                    self.clear_pending();
+                    self.recording = Some((ch, String::new()));
+                    EditorAction::StartMacro(ch)
+                }
+                '@' => {
+                    let count = self.explicit_count().unwrap_or(1);
+                    self.clear_pending();
+                    self.replay_macro(ch, count)
+                }
+                'f' => self.finish_motion(Motion::FindChar(ch)),
+                'F' => self.finish_motion(Motion::FindCharBackward(ch)),
+                't' => self.finish_motion(Motion::TillChar(ch)),
+                'T' => self.finish_motion(Motion::TillCharBackward(ch)),
+                'i' | 'a' => match TextObjectKind::from_char(ch) {
+                    Some(kind) => self.finish_target(OperatorTarget::TextObject(TextObject {
+                        kind,
+                        inner: prefix == 'i',
+                    })),
+                    None => self.cancel(),
+                },
+                'g' => match ch {
+                    'g' => self.finish_motion(Motion::DocStart),
+                    'b' if self.pending.operator.is_none() => {
+                        self.clear_pending();
+                        EditorAction::OpenBasesSidebar
+                    }
+                    'K' | 'J' | '<' | '>' if self.pending.operator.is_none() => {
+                        let edit = match ch {
+                            'K' => SectionEdit::MoveUp,
+                            'J' => SectionEdit::MoveDown,
+                            '<' => SectionEdit::Promote,
+                            _ => SectionEdit::Demote,
+                        };
+                        let count = self.explicit_count().unwrap_or(1);
+                        self.clear_pending();
+                        let action = EditorAction::EditSection { edit, count };
+                        self.remember_change(action.clone());
+                        action
+                    }
+                    _ => self.cancel(),
+                },
+                ']' if ch == ']' => self.finish_motion(Motion::SectionForward),
+                '[' if ch == '[' => self.finish_motion(Motion::SectionBackward),
+                'z' => {
+                    let command = match ch {
+                        'a' => FoldCommand::Toggle,
+                        'o' => FoldCommand::Open,
+                        'c' => FoldCommand::Close,
+                        'R' => FoldCommand::OpenAll,
+                        'M' => FoldCommand::CloseAll,
+                        _ => return self.cancel(),
+                    };
+                    self.clear_pending();
+                    EditorAction::Fold(command)
+                }
+                _ => self.cancel(),
+            };
+        }
 
-            // Movimientos básicos (vim-style)
-            "h" | "Left" => EditorAction::MoveCursorLeft,
-            "j" | "Down" => EditorAction::MoveCursorDown,
-            "k" | "Up" => EditorAction::MoveCursorUp,
-            "l" | "Right" => EditorAction::MoveCursorRight,
+        // 2. Contadores (el 0 inicial es un movimiento)
+        if let Some(digit) = ch.to_digit(10) {
+            let slot = if self.pending.operator.is_some() {
+                &mut self.pending.operator_count
+            } else {
+                &mut self.pending.count
+            };
+            if digit != 0 || slot.is_some() {
+                let value = slot.unwrap_or(0) * 10 + digit as usize;
+                *slot = Some(value.min(MAX_COUNT));
+                return EditorAction::None;
+            }
+        }
 
-            // Movimientos de línea
-            "0" => EditorAction::MoveCursorLineStart,
-            "$" => EditorAction::MoveCursorLineEnd,
-            "g" if self.pending == "g" => {
-                self.pending.clear();
-                EditorAction::MoveCursorDocStart
+        // 3. Con operador pendiente: esperar movimiento u objeto de texto
+        if let Some(operator) = self.pending.operator {
+            if ch == operator.to_char() {
+                return self.finish_target(OperatorTarget::Line);
             }
-            "g" => {
-                self.pending.push_str("g");
-                EditorAction::None
+            if matches!(ch, 'i' | 'a' | 'f' | 'F' | 't' | 'T' | 'g' | '[' | ']') {
+                self.pending.prefix = Some(ch);
+                return EditorAction::None;
             }
-            "G" => EditorAction::MoveCursorDocEnd,
+            return match Self::simple_motion(ch) {
+                Some(motion) => self.finish_target(OperatorTarget::Motion(motion)),
+                None => self.cancel(),
+            };
+        }
 
-            // Edición
-            "x" => EditorAction::DeleteCharAfter,
-            "d" if self.pending == "d" => {
-                self.pending.clear();
-                EditorAction::DeleteLine
+        // 4. Sin operador
+        if let Some(operator) = Operator::from_char(ch) {
+            self.pending.operator = Some(operator);
+            return EditorAction::None;
+        }
+        if matches!(ch, 'f' | 'F' | 'g' | '"' | '@' | '[' | ']' | 'z') {
+            self.pending.prefix = Some(ch);
+            return EditorAction::None;
+        }
+        if let Some(motion) = Self::simple_motion(ch) {
+            return self.finish_motion(motion);
+        }
+
+        match ch {
+            'D' => self.finish_with(Operator::Delete, OperatorTarget::Motion(Motion::LineEnd)),
+            'C' => self.finish_with(Operator::Change, OperatorTarget::Motion(Motion::LineEnd)),
+            'Y' => self.finish_with(Operator::Yank, OperatorTarget::Line),
+            'x' if self.pending.count.is_some() || self.pending.register.is_some() => {
+                self.finish_with(Operator::Delete, OperatorTarget::Motion(Motion::Right))
+            }
+            'x' => {
+                self.clear_pending();
+                self.remember_change(EditorAction::DeleteCharAfter);
+                EditorAction::DeleteCharAfter
+            }
+            'p' | 'P' => {
+                let count = self.explicit_count().unwrap_or(1);
+                self.clear_pending();
+                EditorAction::Put {
+                    before: ch == 'P',
+                    count,
+                }
             }
-            "d" => {
-                self.pending.push_str("d");
-                EditorAction::None
+            'q' => match self.recording.take() {
+                Some((name, keys)) => {
+                    self.clear_pending();
+                    self.registers.set_macro(name, &keys);
+                    EditorAction::StopMacro(name)
+                }
+                // Una macro no puede empezar otra grabación al reproducirse
+                None if self.replay_depth > 0 => self.cancel(),
+                None => {
+                    self.pending.prefix = Some('q');
+                    EditorAction::None
+                }
+            },
+            '.' => self.repeat_last_change(),
+            ';' | ',' => match self.last_find {
+                Some(motion) => {
+                    let motion = if ch == ',' { motion.reversed() } else { motion };
+                    // Repetir no sobrescribe la búsqueda original
+                    let last_find = self.last_find;
+                    let action = self.finish_motion(motion);
+                    self.last_find = last_find;
+                    action
+                }
+                None => self.cancel(),
+            },
+            _ => {
+                let action = match ch {
+                    'i' => {
+                        self.change_awaits_text = false;
+                        EditorAction::ChangeMode(EditorMode::Insert)
+                    }
+                    'a' => EditorAction::ChangeMode(EditorMode::ChatAI),
+                    ':' => EditorAction::ChangeMode(EditorMode::Command),
+                    'v' => EditorAction::ChangeMode(EditorMode::Visual),
+                    't' => EditorAction::OpenSidebar,
+                    'n' => EditorAction::CreateNote,
+                    'u' => EditorAction::Undo,
+                    _ => EditorAction::None,
+                };
+                self.clear_pending();
+                action
             }
+        }
+    }
 
-            "u" => EditorAction::Undo,
+    /// Movimientos de una sola tecla
+    fn simple_motion(ch: char) -> Option<Motion> {
+        Some(match ch {
+            'h' => Motion::Left,
+            'l' | ' ' => Motion::Right,
+            'j' => Motion::Down,
+            'k' => Motion::Up,
+            'w' => Motion::WordForward,
+            'W' => Motion::BigWordForward,
+            'b' => Motion::WordBackward,
+            'B' => Motion::BigWordBackward,
+            'e' => Motion::WordEnd,
+            'E' => Motion::BigWordEnd,
+            '0' => Motion::LineStart,
+            '^' => Motion::FirstNonBlank,
+            '$' => Motion::LineEnd,
+            'G' => Motion::DocEnd,
+            '%' => Motion::MatchingPair,
+            '}' => Motion::ParagraphForward,
+            '{' => Motion::ParagraphBackward,
+            _ => return None,
+        })
+    }
+
+    /// Completar un movimiento: con operador pendiente se convierte en su destino
+    fn finish_motion(&mut self, motion: Motion) -> EditorAction {
+        if matches!(
+            motion,
+            Motion::FindChar(_)
+                | Motion::FindCharBackward(_)
+                | Motion::TillChar(_)
+                | Motion::TillCharBackward(_)
+        ) {
+            self.last_find = Some(motion);
+        }
+
+        // {N}G y {N}gg saltan a una línea concreta
+        let explicit = self.explicit_count();
+        let motion = match (motion, explicit) {
+            (Motion::DocStart | Motion::DocEnd, Some(line)) => Motion::GotoLine(line),
+            (motion, _) => motion,
+        };
+
+        if self.pending.operator.is_some() {
+            return self.finish_target(OperatorTarget::Motion(motion));
+        }
+
+        self.clear_pending();
+        match (motion, explicit) {
+            // Sin contador se mantienen las acciones clásicas (movimiento visual)
+            (Motion::Left, None) => EditorAction::MoveCursorLeft,
+            (Motion::Right, None) => EditorAction::MoveCursorRight,
+            (Motion::Up, None) => EditorAction::MoveCursorUp,
+            (Motion::Down, None) => EditorAction::MoveCursorDown,
+            (Motion::LineStart, _) => EditorAction::MoveCursorLineStart,
+            (Motion::LineEnd, None) => EditorAction::MoveCursorLineEnd,
+            (Motion::DocStart, _) => EditorAction::MoveCursorDocStart,
+            (Motion::DocEnd, _) => EditorAction::MoveCursorDocEnd,
+            (motion, count) => EditorAction::Motion {
+                motion,
+                count: count.unwrap_or(1),
+            },
+        }
+    }
 
-            // ESC en modo Normal: cerrar sidebar si está abierto
-            "Escape" => {
-                self.pending.clear();
-                EditorAction::CloseSidebar
+    /// Completar el operador pendiente con su destino
+    fn finish_target(&mut self, target: OperatorTarget) -> EditorAction {
+        let Some(operator) = self.pending.operator else {
+            return self.cancel();
+        };
+        let explicit = self.explicit_count();
+        let register = self.pending.register;
+        self.clear_pending();
+
+        let action = if operator == Operator::Delete
+            && target == OperatorTarget::Line
+            && explicit.is_none()
+            && register.is_none()
+        {
+            EditorAction::DeleteLine
+        } else {
+            EditorAction::Operate {
+                operator,
+                target,
+                count: explicit.unwrap_or(1),
             }
+        };
 
-            _ => {
-                self.pending.clear();
-                EditorAction::None
+        if operator.is_change() {
+            self.remember_change(action.clone());
+            self.change_awaits_text = operator == Operator::Change;
+        }
+        action
+    }
+
+    /// Atajos que equivalen a operador + destino (`D`, `C`, `Y`, `3x`)
+    fn finish_with(&mut self, operator: Operator, target: OperatorTarget) -> EditorAction {
+        self.pending.operator = Some(operator);
+        self.finish_target(target)
+    }
+
+    /// Producto de los contadores escritos (None si no se escribió ninguno)
+    fn explicit_count(&self) -> Option<usize> {
+        match (self.pending.count, self.pending.operator_count) {
+            (None, None) => None,
+            (a, b) => Some((a.unwrap_or(1) * b.unwrap_or(1)).min(MAX_COUNT)),
+        }
+    }
+
+    fn remember_change(&mut self, action: EditorAction) {
+        self.last_change = Some(action);
+        self.last_inserted = None;
+    }
+
+    /// `.`: repetir el último cambio (un contador nuevo reemplaza al original)
+    fn repeat_last_change(&mut self) -> EditorAction {
+        let count = self.explicit_count();
+        self.clear_pending();
+
+        let Some(mut action) = self.last_change.clone() else {
+            return EditorAction::None;
+        };
+        if let (
+            EditorAction::Operate {
+                count: old_count, ..
             }
+            | EditorAction::EditSection {
+                count: old_count, ..
+            },
+            Some(count),
+        ) = (&mut action, count)
+        {
+            *old_count = count;
+        }
+
+        match &self.last_inserted {
+            Some(inserted) => EditorAction::RepeatChange {
+                action: Box::new(action),
+                inserted: inserted.clone(),
+            },
+            None => action,
         }
     }
 
+    /// Registrar el texto escrito en modo Insert al volver a Normal
+    ///
+    /// Tras un `c` se guarda para que `.` lo repita; si se está grabando una
+    /// macro, el texto y el Escape final pasan a formar parte de ella.
+    pub fn record_inserted_text(&mut self, text: &str) {
+        if std::mem::take(&mut self.change_awaits_text) {
+            self.last_inserted = Some(text.to_string());
+        }
+        if let Some((_, keys)) = &mut self.recording {
+            keys.push_str(text);
+            keys.push(MACRO_ESCAPE);
+        }
+    }
+
+    /// `@{reg}` / `@@`: convertir las teclas de una macro en acciones
+    fn replay_macro(&mut self, name: char, count: usize) -> EditorAction {
+        let name = match name {
+            '@' => match self.registers.last_macro() {
+                Some(last) => last,
+                None => return EditorAction::None,
+            },
+            name => name,
+        };
+        if self.replay_depth >= MAX_MACRO_DEPTH {
+            return EditorAction::None;
+        }
+        let Some(keys) = self.registers.take_macro(name) else {
+            return EditorAction::None;
+        };
+
+        self.replay_depth += 1;
+        let mut actions = Vec::new();
+        for _ in 0..count {
+            self.replay_keys(&keys, &mut actions);
+        }
+        self.replay_depth -= 1;
+        self.clear_pending();
+
+        if actions.is_empty() {
+            EditorAction::None
+        } else {
+            EditorAction::Sequence(actions)
+        }
+    }
+
+    fn replay_keys(&mut self, keys: &str, actions: &mut Vec<EditorAction>) {
+        let mut chars = keys.chars();
+        while let Some(ch) = chars.next() {
+            if ch == MACRO_ESCAPE {
+                self.clear_pending();
+                continue;
+            }
+            let action = self.parse_normal_char(ch);
+
+            // Lo escrito en Insert se guardó tras la acción que entró en Insert
+            if Self::enters_insert(&action) {
+                let text: String = chars.by_ref().take_while(|&c| c != MACRO_ESCAPE).collect();
+                if let EditorAction::ChangeMode(_) = action {
+                    actions.push(EditorAction::InsertText(text));
+                } else {
+                    self.change_awaits_text = false;
+                    self.last_inserted = Some(text.clone());
+                    actions.push(EditorAction::RepeatChange {
+                        action: Box::new(action),
+                        inserted: text,
+                    });
+                }
+                continue;
+            }
+
+            match action {
+                EditorAction::None => {}
+                EditorAction::Sequence(nested) => actions.extend(nested),
+                action => actions.push(action),
+            }
+        }
+    }
+
+    fn enters_insert(action: &EditorAction) -> bool {
+        match action {
+            EditorAction::ChangeMode(mode) => *mode == EditorMode::Insert,
+            EditorAction::Operate { operator, .. } => *operator == Operator::Change,
+            EditorAction::UseRegister { action, .. } => Self::enters_insert(action),
+            _ => false,
+        }
+    }
+
+    fn cancel(&mut self) -> EditorAction {
+        self.clear_pending();
+        EditorAction::None
+    }
+
     /// Procesa entrada en modo Insert
     pub fn parse_insert_mode(&mut self, key: &str, modifiers: KeyModifiers) -> EditorAction {
         if key == "Escape" {
@@ -177,74 +924,129 @@ impl CommandParser {
             "Right" => EditorAction::MoveCursorRight,
             "Up" => EditorAction::MoveCursorUp,
             "Down" => EditorAction::MoveCursorDown,
-            "space" => EditorAction::InsertChar(' '),
-            "Tab" => EditorAction::InsertChar('\t'),
-
-            // Caracteres especiales comunes que GTK reporta con nombres específicos
-            "period" => EditorAction::InsertChar('.'),
-            "comma" => EditorAction::InsertChar(','),
-            "semicolon" => EditorAction::InsertChar(';'),
-            "colon" => EditorAction::InsertChar(':'),
-            "exclam" => EditorAction::InsertChar('!'),
-            "question" => EditorAction::InsertChar('?'),
-            "slash" => EditorAction::InsertChar('/'),
-            "backslash" => EditorAction::InsertChar('\\'),
-            "minus" => EditorAction::InsertChar('-'),
-            "underscore" => EditorAction::InsertChar('_'),
-            "equal" => EditorAction::InsertChar('='),
-            "plus" => EditorAction::InsertChar('+'),
-            "asterisk" => EditorAction::InsertChar('*'),
-            "ampersand" => EditorAction::InsertChar('&'),
-            "percent" => EditorAction::InsertChar('%'),
-            "numbersign" => EditorAction::InsertChar('#'),
-            "at" => EditorAction::InsertChar('@'),
-            "dollar" => EditorAction::InsertChar('$'),
-            "parenleft" => EditorAction::InsertChar('('),
-            "parenright" => EditorAction::InsertChar(')'),
-            "bracketleft" => EditorAction::InsertChar('['),
-            "bracketright" => EditorAction::InsertChar(']'),
-            "braceleft" => EditorAction::InsertChar('{'),
-            "braceright" => EditorAction::InsertChar('}'),
-            "less" => EditorAction::InsertChar('<'),
-            "greater" => EditorAction::InsertChar('>'),
-            "quotedbl" => EditorAction::InsertChar('"'),
-            "apostrophe" => EditorAction::InsertChar('\''),
-            "grave" => EditorAction::InsertChar('`'),
-            "asciitilde" => EditorAction::InsertChar('~'),
-            "bar" => EditorAction::InsertChar('|'),
-            "asciicircum" => EditorAction::InsertChar('^'),
+            "Tab" => EditorAction::Tab { backwards: false },
+            "ISO_Left_Tab" => EditorAction::Tab { backwards: true },
 
-            _ => {
-                // Si es un carácter imprimible de longitud 1, insertarlo
-                if key.chars().count() == 1 {
-                    if let Some(ch) = key.chars().next() {
-                        return EditorAction::InsertChar(ch);
-                    }
-                }
-                EditorAction::None
-            }
+            _ => key_to_char(key)
+                .map(EditorAction::InsertChar)
+                .unwrap_or(EditorAction::None),
         }
     }
 
-    /// Procesa entrada en modo Command (para comandos ex-style como :w, :q)
+    /// Procesa entrada en modo Command (comandos ex como `:w`, `:%s/a/b/g`, `:e Nota`)
     pub fn parse_command_mode(&mut self, command: &str) -> EditorAction {
-        let trimmed = command.trim();
-        match trimmed {
-            "w" | "write" => EditorAction::Save,
-            "q" | "quit" => EditorAction::Quit,
-            "wq" | "x" => EditorAction::SaveAndQuit,
-            "q!" => EditorAction::ForceQuit,
-            _ if trimmed.starts_with('/') => EditorAction::Search(trimmed[1..].to_string()),
-            _ => EditorAction::None,
+        self.history.push(command);
+        match ExCommand::parse(command) {
+            Ok(ExCommand {
+                range: None,
+                kind: ExCommandKind::Write,
+            }) => EditorAction::Save,
+            Ok(ExCommand {
+                range: None,
+                kind: ExCommandKind::Quit,
+            }) => EditorAction::Quit,
+            Ok(ExCommand {
+                range: None,
+                kind: ExCommandKind::WriteQuit,
+            }) => EditorAction::SaveAndQuit,
+            Ok(ExCommand {
+                range: None,
+                kind: ExCommandKind::ForceQuit,
+            }) => EditorAction::ForceQuit,
+            Ok(ExCommand {
+                kind: ExCommandKind::Search(text),
+                ..
+            }) => EditorAction::Search(text),
+            Ok(command) => EditorAction::Ex(command),
+            Err(e) => EditorAction::CommandError(e.to_string()),
         }
     }
 
+    /// Historial de comandos ex (para navegar con ↑/↓)
+    pub fn command_history(&mut self) -> &mut CommandHistory {
+        &mut self.history
+    }
+
     /// Limpia el buffer de comandos pendientes
     pub fn clear_pending(&mut self) {
-        self.pending.clear();
+        self.pending = PendingCommand::default();
+    }
+
+    /// Si hay un comando a medio escribir (ej: tras pulsar `d` o `3`)
+    pub fn has_pending(&self) -> bool {
+        let p = &self.pending;
+        p.count.is_some() || p.operator.is_some() || p.prefix.is_some() || p.register.is_some()
     }
 }
 
+/// Teclas modificadoras que GTK reporta por separado
+fn is_modifier_key(key: &str) -> bool {
+    matches!(
+        key,
+        "Shift_L"
+            | "Shift_R"
+            | "Control_L"
+            | "Control_R"
+            | "Alt_L"
+            | "Alt_R"
+            | "Super_L"
+            | "Super_R"
+            | "Meta_L"
+            | "Meta_R"
+            | "ISO_Level3_Shift"
+            | "Caps_Lock"
+    )
+}
+
+/// Convertir el nombre de tecla de GTK en el carácter que produce
+pub fn key_to_char(key: &str) -> Option<char> {
+    let ch = match key {
+        // Caracteres especiales comunes que GTK reporta con nombres específicos
+        "space" => ' ',
+        "period" => '.',
+        "comma" => ',',
+        "semicolon" => ';',
+        "colon" => ':',
+        "exclam" => '!',
+        "question" => '?',
+        "slash" => '/',
+        "backslash" => '\\',
+        "minus" => '-',
+        "underscore" => '_',
+        "equal" => '=',
+        "plus" => '+',
+        "asterisk" => '*',
+        "ampersand" => '&',
+        "percent" => '%',
+        "numbersign" => '#',
+        "at" => '@',
+        "dollar" => '$',
+        "parenleft" => '(',
+        "parenright" => ')',
+        "bracketleft" => '[',
+        "bracketright" => ']',
+        "braceleft" => '{',
+        "braceright" => '}',
+        "less" => '<',
+        "greater" => '>',
+        "quotedbl" => '"',
+        "apostrophe" => '\'',
+        "grave" => '`',
+        "asciitilde" => '~',
+        "bar" => '|',
+        "asciicircum" => '^',
+        _ => {
+            // Si es un carácter imprimible de longitud 1, usarlo tal cual
+            let mut chars = key.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            };
+        }
+    };
+    Some(ch)
+}
+
 impl Default for CommandParser {
     fn default() -> Self {
         Self::new()
@@ -313,6 +1115,10 @@ mod tests {
             parser.parse_insert_mode("Escape", mods),
             EditorAction::ChangeMode(EditorMode::Normal)
         );
+        assert_eq!(
+            parser.parse_insert_mode("ISO_Left_Tab", mods),
+            EditorAction::Tab { backwards: true }
+        );
     }
 
     #[test]
@@ -326,5 +1132,357 @@ mod tests {
             parser.parse_command_mode("/search"),
             EditorAction::Search("search".to_string())
         );
+        assert!(matches!(
+            parser.parse_command_mode("%s/a/b/g"),
+            EditorAction::Ex(ExCommand {
+                kind: ExCommandKind::Substitute { global: true, .. },
+                ..
+            })
+        ));
+        assert!(matches!(
+            parser.parse_command_mode("frobnicate"),
+            EditorAction::CommandError(_)
+        ));
+        assert_eq!(
+            parser.command_history().older(""),
+            Some("frobnicate".to_string())
+        );
+    }
+
+    /// Enviar una secuencia de teclas y devolver la última acción
+    fn feed(parser: &mut CommandParser, keys: &[&str]) -> EditorAction {
+        let mods = KeyModifiers::default();
+        keys.iter()
+            .map(|k| parser.parse_normal_mode(k, mods))
+            .last()
+            .unwrap_or(EditorAction::None)
+    }
+
+    #[test]
+    fn test_operator_grammar() {
+        let mut parser = CommandParser::new();
+
+        assert_eq!(
+            feed(&mut parser, &["3", "d", "w"]),
+            EditorAction::Operate {
+                operator: Operator::Delete,
+                target: OperatorTarget::Motion(Motion::WordForward),
+                count: 3,
+            }
+        );
+        // Los contadores antes y después del operador se multiplican
+        assert_eq!(
+            feed(&mut parser, &["2", "y", "3", "j"]),
+            EditorAction::Operate {
+                operator: Operator::Yank,
+                target: OperatorTarget::Motion(Motion::Down),
+                count: 6,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["c", "i", "w"]),
+            EditorAction::Operate {
+                operator: Operator::Change,
+                target: OperatorTarget::TextObject(TextObject {
+                    kind: TextObjectKind::Word,
+                    inner: true,
+                }),
+                count: 1,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["y", "a", "p"]),
+            EditorAction::Operate {
+                operator: Operator::Yank,
+                target: OperatorTarget::TextObject(TextObject {
+                    kind: TextObjectKind::Paragraph,
+                    inner: false,
+                }),
+                count: 1,
+            }
+        );
+        // GTK reporta "$" como "dollar" (y Shift por separado)
+        assert_eq!(
+            feed(&mut parser, &["d", "Shift_L", "dollar"]),
+            EditorAction::Operate {
+                operator: Operator::Delete,
+                target: OperatorTarget::Motion(Motion::LineEnd),
+                count: 1,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["greater", "greater"]),
+            EditorAction::Operate {
+                operator: Operator::Indent,
+                target: OperatorTarget::Line,
+                count: 1,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["d", "t", "comma"]),
+            EditorAction::Operate {
+                operator: Operator::Delete,
+                target: OperatorTarget::Motion(Motion::TillChar(',')),
+                count: 1,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["c", "i", "colon"]),
+            EditorAction::Operate {
+                operator: Operator::Change,
+                target: OperatorTarget::TextObject(TextObject {
+                    kind: TextObjectKind::InlineProperty,
+                    inner: true,
+                }),
+                count: 1,
+            }
+        );
+        // Un objeto de texto desconocido cancela el comando
+        assert_eq!(feed(&mut parser, &["d", "i", "z"]), EditorAction::None);
+        assert!(!parser.has_pending());
+    }
+
+    #[test]
+    fn test_motions() {
+        let mut parser = CommandParser::new();
+
+        assert_eq!(
+            feed(&mut parser, &["w"]),
+            EditorAction::Motion {
+                motion: Motion::WordForward,
+                count: 1,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["3", "b"]),
+            EditorAction::Motion {
+                motion: Motion::WordBackward,
+                count: 3,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["f", "x"]),
+            EditorAction::Motion {
+                motion: Motion::FindChar('x'),
+                count: 1,
+            }
+        );
+        // `,` repite la búsqueda en sentido contrario
+        assert_eq!(
+            feed(&mut parser, &["comma"]),
+            EditorAction::Motion {
+                motion: Motion::FindCharBackward('x'),
+                count: 1,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["percent"]),
+            EditorAction::Motion {
+                motion: Motion::MatchingPair,
+                count: 1,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["1", "2", "G"]),
+            EditorAction::Motion {
+                motion: Motion::GotoLine(12),
+                count: 12,
+            }
+        );
+        // Sin contador se conservan las acciones clásicas
+        assert_eq!(feed(&mut parser, &["j"]), EditorAction::MoveCursorDown);
+        assert_eq!(
+            feed(&mut parser, &["g", "g"]),
+            EditorAction::MoveCursorDocStart
+        );
+        assert_eq!(
+            feed(&mut parser, &["g", "b"]),
+            EditorAction::OpenBasesSidebar
+        );
+    }
+
+    #[test]
+    fn test_repeat_last_change() {
+        let mut parser = CommandParser::new();
+        let delete_word = feed(&mut parser, &["d", "w"]);
+
+        // Los movimientos y las copias no reemplazan el último cambio
+        feed(&mut parser, &["j"]);
+        feed(&mut parser, &["y", "y"]);
+        assert_eq!(feed(&mut parser, &["period"]), delete_word);
+        assert_eq!(
+            feed(&mut parser, &["3", "period"]),
+            EditorAction::Operate {
+                operator: Operator::Delete,
+                target: OperatorTarget::Motion(Motion::WordForward),
+                count: 3,
+            }
+        );
+
+        // Tras `ciw` + texto, `.` re-inserta lo escrito
+        let change = feed(&mut parser, &["c", "i", "w"]);
+        parser.record_inserted_text("nuevo");
+        assert_eq!(
+            feed(&mut parser, &["period"]),
+            EditorAction::RepeatChange {
+                action: Box::new(change),
+                inserted: "nuevo".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_registers_and_put() {
+        let mut parser = CommandParser::new();
+
+        assert_eq!(
+            feed(&mut parser, &["quotedbl", "a", "y", "y"]),
+            EditorAction::UseRegister {
+                register: 'a',
+                action: Box::new(EditorAction::Operate {
+                    operator: Operator::Yank,
+                    target: OperatorTarget::Line,
+                    count: 1,
+                }),
+            }
+        );
+        // Con registro, `dd` deja de ser la acción clásica DeleteLine
+        assert_eq!(
+            feed(&mut parser, &["quotedbl", "underscore", "d", "d"]),
+            EditorAction::UseRegister {
+                register: '_',
+                action: Box::new(EditorAction::Operate {
+                    operator: Operator::Delete,
+                    target: OperatorTarget::Line,
+                    count: 1,
+                }),
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["2", "p"]),
+            EditorAction::Put {
+                before: false,
+                count: 2,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["quotedbl", "b", "P"]),
+            EditorAction::UseRegister {
+                register: 'b',
+                action: Box::new(EditorAction::Put {
+                    before: true,
+                    count: 1,
+                }),
+            }
+        );
+        // Un registro inválido cancela el comando
+        assert_eq!(
+            feed(&mut parser, &["quotedbl", "exclam"]),
+            EditorAction::None
+        );
+        assert!(!parser.has_pending());
+    }
+
+    #[test]
+    fn test_macro_record_and_replay() {
+        let mut parser = CommandParser::new();
+
+        assert_eq!(
+            feed(&mut parser, &["q", "a"]),
+            EditorAction::StartMacro('a')
+        );
+        assert_eq!(parser.recording_macro(), Some('a'));
+        let delete_word = feed(&mut parser, &["d", "w"]);
+        feed(&mut parser, &["j"]);
+        let change = feed(&mut parser, &["c", "i", "w"]);
+        parser.record_inserted_text("hola");
+        assert_eq!(feed(&mut parser, &["q"]), EditorAction::StopMacro('a'));
+        assert_eq!(parser.recording_macro(), None);
+        assert_eq!(
+            parser.registers().get('a').unwrap().text,
+            "dwjciwhola\u{1b}"
+        );
+
+        let once = vec![
+            delete_word,
+            EditorAction::MoveCursorDown,
+            EditorAction::RepeatChange {
+                action: Box::new(change),
+                inserted: "hola".to_string(),
+            },
+        ];
+        assert_eq!(
+            feed(&mut parser, &["at", "a"]),
+            EditorAction::Sequence(once.clone())
+        );
+        // `2@@` repite la última macro dos veces
+        assert_eq!(
+            feed(&mut parser, &["2", "at", "at"]),
+            EditorAction::Sequence([once.clone(), once].concat())
+        );
+
+        // Texto escrito tras `i` se reproduce como inserción directa
+        feed(&mut parser, &["q", "b", "i"]);
+        parser.record_inserted_text("- ");
+        feed(&mut parser, &["j", "q"]);
+        assert_eq!(
+            feed(&mut parser, &["at", "b"]),
+            EditorAction::Sequence(vec![
+                EditorAction::InsertText("- ".to_string()),
+                EditorAction::MoveCursorDown,
+            ])
+        );
+
+        // Una macro que se llama a sí misma no se repite indefinidamente
+        parser.registers_mut().set_macro('c', "x@c");
+        assert!(matches!(
+            feed(&mut parser, &["at", "c"]),
+            EditorAction::Sequence(actions) if actions.len() == MAX_MACRO_DEPTH
+        ));
+        assert_eq!(feed(&mut parser, &["at", "z"]), EditorAction::None);
+    }
+
+    #[test]
+    fn test_folds_and_sections() {
+        let mut parser = CommandParser::new();
+
+        assert_eq!(
+            feed(&mut parser, &["z", "a"]),
+            EditorAction::Fold(FoldCommand::Toggle)
+        );
+        assert_eq!(
+            feed(&mut parser, &["z", "Shift_L", "M"]),
+            EditorAction::Fold(FoldCommand::CloseAll)
+        );
+        assert_eq!(
+            feed(&mut parser, &["2", "bracketright", "bracketright"]),
+            EditorAction::Motion {
+                motion: Motion::SectionForward,
+                count: 2,
+            }
+        );
+        assert_eq!(
+            feed(&mut parser, &["d", "bracketleft", "bracketleft"]),
+            EditorAction::Operate {
+                operator: Operator::Delete,
+                target: OperatorTarget::Motion(Motion::SectionBackward),
+                count: 1,
+            }
+        );
+
+        // Mover una sección es un cambio repetible con `.`
+        let move_up = EditorAction::EditSection {
+            edit: SectionEdit::MoveUp,
+            count: 1,
+        };
+        assert_eq!(feed(&mut parser, &["g", "Shift_L", "K"]), move_up);
+        assert_eq!(feed(&mut parser, &["period"]), move_up);
+        assert_eq!(
+            feed(&mut parser, &["g", "greater"]),
+            EditorAction::EditSection {
+                edit: SectionEdit::Demote,
+                count: 1,
+            }
+        );
     }
 }
@@ -1,8 +1,12 @@
 use chrono::{DateTime, Utc};
 use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, params};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use super::note_buffer::UndoHistory;
+
 #[derive(Debug, Error)]
 pub enum DatabaseError {
     #[error("Database error: {0}")]
@@ -16,6 +20,12 @@ pub enum DatabaseError {
 
     #[error("Tag not found: {0}")]
     TagNotFound(String),
+
+    #[error("Saved search not found: {0}")]
+    SavedSearchNotFound(String),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
 }
 
 pub type Result<T> = std::result::Result<T, DatabaseError>;
@@ -85,13 +95,83 @@ pub struct GlobalEmbedding {
 }
 
 /// Query de búsqueda con filtros opcionales
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SearchQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub folder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub date_from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub date_to: Option<DateTime<Utc>>,
+    /// Filtros de propiedades inline: clave -> valor (vacío = la propiedad existe)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
+}
+
+impl SearchQuery {
+    /// Parsear el texto de la barra de búsqueda:
+    /// `#tag`, `folder:Carpeta`, `clave::valor` y el resto como texto libre
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut words = Vec::new();
+
+        for word in input.split_whitespace() {
+            if let Some(tag) = word.strip_prefix('#').filter(|t| !t.is_empty()) {
+                query.tags.push(tag.to_lowercase());
+            } else if let Some(folder) = word.strip_prefix("folder:").filter(|f| !f.is_empty()) {
+                query.folder = Some(folder.trim_matches('"').to_string());
+            } else if let Some((key, value)) = word.split_once("::").filter(|(k, _)| !k.is_empty())
+            {
+                query.properties.insert(key.to_string(), value.to_string());
+            } else {
+                words.push(word);
+            }
+        }
+
+        if !words.is_empty() {
+            query.text = Some(words.join(" "));
+        }
+
+        query
+    }
+
+    /// ¿La query no tiene ningún filtro?
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Texto de la barra de búsqueda que vuelve a producir la query con `parse`
+impl std::fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts: Vec<String> = self.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        if let Some(folder) = &self.folder {
+            parts.push(format!("folder:{}", folder));
+        }
+        parts.extend(
+            self.properties
+                .iter()
+                .map(|(key, value)| format!("{}::{}", key, value)),
+        );
+        parts.extend(self.text.clone());
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// Búsqueda guardada que se muestra como carpeta virtual en el sidebar
+#[derive(Debug, Clone)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    pub query: SearchQuery,
+    pub icon: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Fila de propiedad inline de la base de datos
@@ -192,7 +272,7 @@ impl std::fmt::Debug for NotesDatabase {
 
 impl NotesDatabase {
     /// Versión actual del esquema
-    const SCHEMA_VERSION: i32 = 10;
+    const SCHEMA_VERSION: i32 = 12;
 
     /// Crear o abrir base de datos en la ruta especificada
     pub fn new(path: &Path) -> Result<Self> {
@@ -222,12 +302,16 @@ impl NotesDatabase {
 
     /// Clona la conexión abriendo una nueva conexión a la misma base de datos
     pub fn clone_connection(&self) -> Self {
-        let conn =
-            Connection::open(&self.path).expect("No se pudo clonar la conexión a la base de datos");
-        Self {
-            conn,
+        self.try_clone_connection()
+            .expect("No se pudo clonar la conexión a la base de datos")
+    }
+
+    /// Como `clone_connection`, pero devuelve el error si no se puede abrir
+    pub fn try_clone_connection(&self) -> Result<Self> {
+        Ok(Self {
+            conn: Connection::open(&self.path)?,
             path: self.path.clone(),
-        }
+        })
     }
 
     /// Obtiene el path de la base de datos
@@ -462,6 +546,16 @@ impl NotesDatabase {
                 self.migrate_to_v10()?;
             }
 
+            // Migración v10 -> v11: Búsquedas guardadas (carpetas virtuales)
+            if current_version < 11 {
+                self.migrate_to_v11()?;
+            }
+
+            // Migración v11 -> v12: Historial de undo persistente por nota
+            if current_version < 12 {
+                self.migrate_to_v12()?;
+            }
+
             println!(
                 "✅ Migraciones completadas - BD actualizada a v{}",
                 Self::SCHEMA_VERSION
@@ -934,6 +1028,56 @@ impl NotesDatabase {
         Ok(())
     }
 
+    /// Migración a versión 11: Búsquedas guardadas como carpetas virtuales
+    fn migrate_to_v11(&mut self) -> Result<()> {
+        println!("Aplicando migración v11: Búsquedas guardadas");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS saved_searches (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                query_json TEXT NOT NULL,
+                icon TEXT,
+                order_index INTEGER DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            "#,
+        )?;
+
+        println!("  🔍 Tabla 'saved_searches' creada");
+
+        // Actualizar versión
+        self.conn
+            .execute("REPLACE INTO schema_version (version) VALUES (11)", [])?;
+
+        Ok(())
+    }
+
+    /// Migración a versión 12: Historial de undo persistente por nota
+    fn migrate_to_v12(&mut self) -> Result<()> {
+        println!("Aplicando migración v12: Historial de undo por nota");
+
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS note_undo_history (
+                note_path TEXT PRIMARY KEY,
+                history_json TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+            "#,
+        )?;
+
+        println!("  ↶ Tabla 'note_undo_history' creada");
+
+        // Actualizar versión
+        self.conn
+            .execute("REPLACE INTO schema_version (version) VALUES (12)", [])?;
+
+        Ok(())
+    }
+
     /// Indexar una nota en la base de datos
     pub fn index_note(
         &self,
@@ -1925,27 +2069,128 @@ impl NotesDatabase {
     }
 
     /// Buscar notas usando FTS5 y filtros opcionales
+    /// (texto, tags, carpeta, rango de fechas de modificación y propiedades inline)
     pub fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
-        if query.text.is_none() && query.tags.is_empty() {
-            // Sin filtros, devolver todas las notas
-            let notes = self
-                .list_notes(query.folder.as_deref())?
-                .into_iter()
-                .map(|note| SearchResult {
-                    note_id: note.id,
-                    note_name: note.name,
-                    note_path: note.path,
-                    snippet: String::new(),
-                    relevance: 0.0,
-                    matched_tags: vec![],
-                    similarity: None,
-                })
-                .collect();
-            return Ok(notes);
+        let notes = self.list_notes(query.folder.as_deref())?;
+
+        // Texto libre: ids que coinciden -> snippet
+        let text_matches = match query.text.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => Some(self.match_text(text)?),
+            _ => None,
+        };
+
+        // Tags: la nota debe tener todos
+        let mut tag_matches: Option<HashSet<i64>> = None;
+        for tag in &query.tags {
+            let ids = self.note_ids_with_tag(tag)?;
+            tag_matches = Some(match tag_matches {
+                Some(current) => current.intersection(&ids).copied().collect(),
+                None => ids,
+            });
+        }
+
+        let mut results = Vec::new();
+        for note in notes {
+            if tag_matches
+                .as_ref()
+                .is_some_and(|ids| !ids.contains(&note.id))
+            {
+                continue;
+            }
+            if query.date_from.is_some_and(|from| note.updated_at < from)
+                || query.date_to.is_some_and(|to| note.updated_at > to)
+            {
+                continue;
+            }
+
+            let snippet = match &text_matches {
+                Some(matches) => match matches.get(&note.id) {
+                    Some(snippet) => snippet.clone(),
+                    None => continue,
+                },
+                None => String::new(),
+            };
+
+            if !query.properties.is_empty() && !self.note_matches_properties(note.id, query)? {
+                continue;
+            }
+
+            results.push(SearchResult {
+                note_id: note.id,
+                note_name: note.name,
+                note_path: note.path,
+                snippet,
+                relevance: 1.0,
+                matched_tags: query.tags.clone(),
+                similarity: None,
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Ids de notas cuyo nombre o contenido coincide con el texto (sin límite), con snippet
+    fn match_text(&self, text: &str) -> Result<HashMap<i64, String>> {
+        let fts_query = Self::build_fts_query(text);
+
+        let mut matches = HashMap::new();
+        if !fts_query.trim().is_empty() {
+            let mut stmt = self.conn.prepare(
+                "SELECT rowid, snippet(notes_fts, -1, '<mark>', '</mark>', '...', 16)
+                 FROM notes_fts WHERE notes_fts MATCH ?1",
+            )?;
+            for row in stmt.query_map([&fts_query], |row| Ok((row.get(0)?, row.get(1)?)))? {
+                let (id, snippet) = row?;
+                matches.insert(id, snippet);
+            }
+        }
+
+        // Fallback a LIKE si FTS5 no encontró nada
+        if matches.is_empty() {
+            let like_pattern = format!("%{}%", text.to_lowercase());
+            let mut stmt = self.conn.prepare(
+                "SELECT rowid, substr(content, 1, 100) FROM notes_fts
+                 WHERE LOWER(name) LIKE ?1 OR LOWER(content) LIKE ?1",
+            )?;
+            for row in stmt.query_map([&like_pattern], |row| Ok((row.get(0)?, row.get(1)?)))? {
+                let (id, snippet) = row?;
+                matches.insert(id, snippet);
+            }
         }
 
-        // TODO: Implementar búsqueda FTS5 completa con snippets
-        Ok(vec![])
+        Ok(matches)
+    }
+
+    /// Ids de notas que tienen un tag (sin # y sin distinguir mayúsculas)
+    fn note_ids_with_tag(&self, tag: &str) -> Result<HashSet<i64>> {
+        let tag = tag.trim_start_matches('#').to_lowercase();
+        let mut stmt = self.conn.prepare(
+            "SELECT note_tags.note_id FROM note_tags
+             JOIN tags ON note_tags.tag_id = tags.id
+             WHERE LOWER(tags.name) = ?1",
+        )?;
+
+        let ids = stmt
+            .query_map([&tag], |row| row.get(0))?
+            .collect::<SqliteResult<HashSet<i64>>>()?;
+
+        Ok(ids)
+    }
+
+    /// ¿Cumple la nota todos los filtros de propiedades inline de la query?
+    fn note_matches_properties(&self, note_id: i64, query: &SearchQuery) -> Result<bool> {
+        let props = self.get_inline_properties(note_id)?;
+
+        Ok(query.properties.iter().all(|(key, value)| {
+            props.iter().any(|prop| {
+                prop.key.eq_ignore_ascii_case(key)
+                    && (value.is_empty()
+                        || prop
+                            .to_property_value()
+                            .to_display_string()
+                            .eq_ignore_ascii_case(value))
+            })
+        }))
     }
 
     /// Construye una query FTS5 inteligente desde el texto del usuario
@@ -2260,6 +2505,12 @@ impl NotesDatabase {
         new_folder: Option<&str>,
         new_path: &str,
     ) -> Result<()> {
+        // El historial de undo sigue a la nota
+        self.conn.execute(
+            "UPDATE OR REPLACE note_undo_history SET note_path = ?1
+             WHERE note_path = (SELECT path FROM notes WHERE id = ?2)",
+            params![new_path, note_id],
+        )?;
         self.conn.execute(
             "UPDATE notes SET folder = ?1, path = ?2 WHERE id = ?3",
             params![new_folder, new_path, note_id],
@@ -2893,6 +3144,9 @@ impl NotesDatabase {
                 params![new_path, old_path],
             )?;
 
+            // 4. Conservar el historial de undo
+            self.rename_undo_history(&old_path, new_path)?;
+
             println!(
                 "📝 Nota renombrada: '{}' -> '{}' (incluidos embeddings)",
                 old_name, new_name
@@ -3334,6 +3588,172 @@ impl NotesDatabase {
         Ok(())
     }
 
+    // ==================== FUNCIONES DE BÚSQUEDAS GUARDADAS ====================
+
+    /// Guardar una búsqueda con nombre (carpeta virtual)
+    pub fn create_saved_search(&self, name: &str, query: &SearchQuery) -> Result<i64> {
+        let now = Utc::now().timestamp();
+        let query_json = serde_json::to_string(query)?;
+
+        self.conn.execute(
+            r#"
+            INSERT INTO saved_searches (name, query_json, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![name, query_json, now, now],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Actualizar la query de una búsqueda guardada
+    pub fn update_saved_search(&self, name: &str, query: &SearchQuery) -> Result<()> {
+        let now = Utc::now().timestamp();
+        let query_json = serde_json::to_string(query)?;
+
+        let updated = self.conn.execute(
+            "UPDATE saved_searches SET query_json = ?1, updated_at = ?2 WHERE name = ?3",
+            params![query_json, now, name],
+        )?;
+
+        if updated == 0 {
+            return Err(DatabaseError::SavedSearchNotFound(name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Listar las búsquedas guardadas
+    pub fn list_saved_searches(&self) -> Result<Vec<SavedSearch>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, query_json, icon, created_at, updated_at
+             FROM saved_searches ORDER BY order_index, name",
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, i64>(4)?,
+                    row.get::<_, i64>(5)?,
+                ))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(id, name, query_json, icon, created_at, updated_at)| {
+                Ok(SavedSearch {
+                    id,
+                    name,
+                    query: serde_json::from_str(&query_json)?,
+                    icon,
+                    created_at: DateTime::from_timestamp(created_at, 0).unwrap_or_default(),
+                    updated_at: DateTime::from_timestamp(updated_at, 0).unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Obtener una búsqueda guardada por nombre
+    pub fn get_saved_search(&self, name: &str) -> Result<Option<SavedSearch>> {
+        Ok(self
+            .list_saved_searches()?
+            .into_iter()
+            .find(|search| search.name == name))
+    }
+
+    /// Eliminar una búsqueda guardada
+    pub fn delete_saved_search(&self, name: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM saved_searches WHERE name = ?1", params![name])?;
+        Ok(())
+    }
+
+    /// Renombrar una búsqueda guardada
+    pub fn rename_saved_search(&self, name: &str, new_name: &str) -> Result<()> {
+        let updated = self.conn.execute(
+            "UPDATE saved_searches SET name = ?1 WHERE name = ?2",
+            params![new_name, name],
+        )?;
+
+        if updated == 0 {
+            return Err(DatabaseError::SavedSearchNotFound(name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Recalcular el contenido de una carpeta virtual ejecutando su búsqueda
+    pub fn run_saved_search(&self, name: &str) -> Result<Vec<SearchResult>> {
+        let saved = self
+            .get_saved_search(name)?
+            .ok_or_else(|| DatabaseError::SavedSearchNotFound(name.to_string()))?;
+
+        self.search(&saved.query)
+    }
+
+    // ==================== FUNCIONES DE HISTORIAL DE UNDO ====================
+
+    /// Guardar el historial de undo de una nota (un historial vacío borra el guardado)
+    pub fn save_undo_history(&self, note_path: &str, history: &UndoHistory) -> Result<()> {
+        if history.is_empty() {
+            return self.delete_undo_history(note_path);
+        }
+
+        let now = Utc::now().timestamp();
+        let history_json = serde_json::to_string(history)?;
+
+        self.conn.execute(
+            r#"
+            INSERT INTO note_undo_history (note_path, history_json, updated_at)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(note_path) DO UPDATE SET
+                history_json = excluded.history_json,
+                updated_at = excluded.updated_at
+            "#,
+            params![note_path, history_json, now],
+        )?;
+
+        Ok(())
+    }
+
+    /// Cargar el historial de undo guardado de una nota
+    pub fn load_undo_history(&self, note_path: &str) -> Result<Option<UndoHistory>> {
+        let history_json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT history_json FROM note_undo_history WHERE note_path = ?1",
+                params![note_path],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(history_json
+            .map(|json| serde_json::from_str(&json))
+            .transpose()?)
+    }
+
+    /// Eliminar el historial de undo de una nota
+    pub fn delete_undo_history(&self, note_path: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM note_undo_history WHERE note_path = ?1",
+            params![note_path],
+        )?;
+        Ok(())
+    }
+
+    /// Mover el historial de undo cuando una nota cambia de ruta
+    pub fn rename_undo_history(&self, old_path: &str, new_path: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE OR REPLACE note_undo_history SET note_path = ?1 WHERE note_path = ?2",
+            params![new_path, old_path],
+        )?;
+        Ok(())
+    }
+
     /// Buscar notas que tengan una propiedad con un valor específico
     pub fn find_notes_by_property(&self, key: &str, value: &str) -> Result<Vec<i64>> {
         let mut stmt = self.conn.prepare(
@@ -3459,6 +3879,7 @@ impl NotesDatabase {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::note_buffer::NoteBuffer;
 
     #[test]
     fn test_create_database() {
@@ -3497,6 +3918,122 @@ mod tests {
         std::fs::remove_file(db_path).ok();
     }
 
+    #[test]
+    fn test_search_filters() {
+        let db_path = std::env::temp_dir().join("test_notes_search.db");
+        let db = NotesDatabase::new(&db_path).unwrap();
+
+        db.index_note(
+            "Draft",
+            "/path/to/Draft.md",
+            "#inbox rust notes [status::draft]",
+            Some("Work"),
+        )
+        .unwrap();
+        db.index_note(
+            "Final",
+            "/path/to/Final.md",
+            "#inbox gtk notes [status::final]",
+            None,
+        )
+        .unwrap();
+
+        let names = |query: &SearchQuery| -> Vec<String> {
+            let mut names: Vec<String> = db
+                .search(query)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.note_name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(&SearchQuery::parse("#inbox")), vec!["Draft", "Final"]);
+        assert_eq!(names(&SearchQuery::parse("#inbox rust")), vec!["Draft"]);
+        assert_eq!(names(&SearchQuery::parse("status::final")), vec!["Final"]);
+        assert_eq!(
+            names(&SearchQuery::parse("folder:Work notes")),
+            vec!["Draft"]
+        );
+        assert!(names(&SearchQuery::parse("#missing")).is_empty());
+
+        // El texto mostrado al editar una búsqueda guardada vuelve a dar la misma query
+        let query = SearchQuery::parse("rust #inbox status::final folder:Work");
+        assert_eq!(SearchQuery::parse(&query.to_string()), query);
+
+        std::fs::remove_file(db_path).ok();
+    }
+
+    #[test]
+    fn test_saved_searches() {
+        let db_path = std::env::temp_dir().join("test_notes_saved_searches.db");
+        let db = NotesDatabase::new(&db_path).unwrap();
+
+        db.index_note("Todo", "/path/to/Todo.md", "#inbox", None)
+            .unwrap();
+        db.create_saved_search("Inbox", &SearchQuery::parse("#inbox"))
+            .unwrap();
+
+        let saved = db.list_saved_searches().unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].query.tags, vec!["inbox"]);
+        assert_eq!(db.run_saved_search("Inbox").unwrap().len(), 1);
+
+        // El contenido se recalcula al cambiar las notas
+        db.index_note("Other", "/path/to/Other.md", "#inbox", None)
+            .unwrap();
+        assert_eq!(db.run_saved_search("Inbox").unwrap().len(), 2);
+
+        db.rename_saved_search("Inbox", "Entrada").unwrap();
+        assert_eq!(db.run_saved_search("Entrada").unwrap().len(), 2);
+        assert!(matches!(
+            db.rename_saved_search("Inbox", "Otra"),
+            Err(DatabaseError::SavedSearchNotFound(_))
+        ));
+        db.rename_saved_search("Entrada", "Inbox").unwrap();
+
+        db.delete_saved_search("Inbox").unwrap();
+        assert!(matches!(
+            db.run_saved_search("Inbox"),
+            Err(DatabaseError::SavedSearchNotFound(_))
+        ));
+
+        std::fs::remove_file(db_path).ok();
+    }
+
+    #[test]
+    fn test_undo_history() {
+        let db_path = std::env::temp_dir().join("test_notes_undo_history.db");
+        let db = NotesDatabase::new(&db_path).unwrap();
+
+        let mut buffer = NoteBuffer::from_text("Hola");
+        buffer.insert(4, " mundo");
+        db.save_undo_history("/notas/Hola.md", &buffer.history())
+            .unwrap();
+
+        let history = db.load_undo_history("/notas/Hola.md").unwrap().unwrap();
+        let mut reopened = NoteBuffer::from_text("Hola mundo");
+        assert!(reopened.restore_history(history));
+        assert!(reopened.undo());
+        assert_eq!(reopened.to_string(), "Hola");
+
+        // Al renombrar la nota, el historial la acompaña
+        db.index_note("Hola", "/notas/Hola.md", "Hola mundo", None)
+            .unwrap();
+        db.rename_note("Hola", "Saludo", "/notas/Saludo.md", None)
+            .unwrap();
+        assert!(db.load_undo_history("/notas/Hola.md").unwrap().is_none());
+        assert!(db.load_undo_history("/notas/Saludo.md").unwrap().is_some());
+
+        // Guardar un historial vacío lo elimina
+        db.save_undo_history("/notas/Saludo.md", &NoteBuffer::new().history())
+            .unwrap();
+        assert!(db.load_undo_history("/notas/Saludo.md").unwrap().is_none());
+
+        std::fs::remove_file(db_path).ok();
+    }
+
     #[test]
     fn test_tags() {
         let temp_dir = std::env::temp_dir();
@@ -0,0 +1,2259 @@
+//! Diagramas ```mermaid y ```dot (Graphviz) renderizados a SVG
+//!
+//! Igual que los gráficos de `chart`, el SVG se genera en Rust: no hace falta
+//! JavaScript, el binario de Graphviz ni conexión, y el HTML exportado lo lleva
+//! incrustado. Se soporta:
+//! - Graphviz: `graph`/`digraph` con nodos, aristas, subgrafos, `rankdir` y los
+//!   atributos `label`, `shape`, `style`, `dir` y `arrowhead`
+//! - Mermaid: `flowchart`/`graph`, `sequenceDiagram` y `pie`
+//!
+//! Es un subconjunto: cualquier otro tipo (`gantt`, `classDiagram`, `neato`...) no se
+//! dibuja y el preview muestra en su lugar un recuadro de error con los tipos soportados.
+//!
+//! Los grafos se colocan por capas (ciclos invertidos, rango por camino más largo,
+//! orden por baricentros). Como el layout es caro en notas grandes, el SVG se
+//! guarda en caché por el hash del contenido (ver `render_diagram_cached`).
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+use std::sync::{LazyLock, Mutex};
+
+use thiserror::Error;
+
+use super::base::{ChartConfig, ChartKind};
+use super::chart::{ChartPoint, ChartRenderer, escape_xml};
+use super::html_renderer::PreviewColors;
+
+/// Métricas aproximadas del texto (font-size 14)
+const FONT_SIZE: f64 = 14.0;
+const CHAR_WIDTH: f64 = 7.8;
+const LINE_HEIGHT: f64 = 18.0;
+
+/// Separaciones del layout
+const MARGIN: f64 = 16.0;
+const RANK_GAP: f64 = 56.0;
+const NODE_GAP: f64 = 28.0;
+const DUMMY_SIZE: f64 = 8.0;
+
+/// SVG guardados como máximo en la caché (se vacía al llenarse)
+const CACHE_CAPACITY: usize = 128;
+
+/// SVG (o error) de cada diagrama, por hash de tipo + contenido + colores
+static SVG_CACHE: LazyLock<Mutex<HashMap<u64, Result<String, DiagramError>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum DiagramError {
+    #[error("Line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error(
+        "Unsupported diagram type: {0} (supported: mermaid flowchart, sequenceDiagram and pie; dot graph and digraph)"
+    )]
+    Unsupported(String),
+    #[error("Empty diagram")]
+    Empty,
+}
+
+/// Lenguaje del bloque de código
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagramKind {
+    Mermaid,
+    Graphviz,
+}
+
+impl DiagramKind {
+    /// Tipo de diagrama según el info string del bloque (```mermaid, ```dot, ```graphviz)
+    pub fn from_info(info: &str) -> Option<Self> {
+        match info.split_whitespace().next()? {
+            "mermaid" => Some(Self::Mermaid),
+            "dot" | "graphviz" => Some(Self::Graphviz),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Mermaid => "Mermaid",
+            Self::Graphviz => "Graphviz",
+        }
+    }
+}
+
+// ============================================================================
+// MODELO
+// ============================================================================
+
+/// Dirección en la que avanzan los rangos del grafo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    #[default]
+    TopDown,
+    BottomUp,
+    LeftRight,
+    RightLeft,
+}
+
+impl Direction {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "TB" | "TD" => Some(Self::TopDown),
+            "BT" => Some(Self::BottomUp),
+            "LR" => Some(Self::LeftRight),
+            "RL" => Some(Self::RightLeft),
+            _ => None,
+        }
+    }
+
+    fn is_horizontal(self) -> bool {
+        matches!(self, Self::LeftRight | Self::RightLeft)
+    }
+
+    fn is_reversed(self) -> bool {
+        matches!(self, Self::BottomUp | Self::RightLeft)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeShape {
+    #[default]
+    Rect,
+    Rounded,
+    Ellipse,
+    Circle,
+    Diamond,
+    /// Solo el texto (`plaintext` en Graphviz)
+    Plain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Thick,
+    /// Cuenta para el layout pero no se dibuja
+    Invisible,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    pub shape: NodeShape,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub label: Option<String>,
+    pub arrow_start: bool,
+    pub arrow_end: bool,
+    pub style: EdgeStyle,
+}
+
+/// Grafo de nodos y aristas (flowchart de Mermaid o grafo de Graphviz)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Graph {
+    pub direction: Direction,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl Graph {
+    /// Índice del nodo `id`, creándolo (con su id como texto) si no existe
+    fn node(&mut self, id: &str, shape: NodeShape) -> usize {
+        match self.nodes.iter().position(|n| n.id == id) {
+            Some(index) => index,
+            None => {
+                self.nodes.push(GraphNode {
+                    id: id.to_string(),
+                    label: id.to_string(),
+                    shape,
+                });
+                self.nodes.len() - 1
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub id: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SequenceStep {
+    Message {
+        from: usize,
+        to: usize,
+        text: String,
+        dashed: bool,
+        arrow: bool,
+    },
+    /// Nota sobre los participantes `from..=to` (o a un lado de uno solo)
+    Note {
+        from: usize,
+        to: usize,
+        side: NoteSide,
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteSide {
+    Left,
+    Right,
+    Over,
+}
+
+/// Diagrama de secuencia de Mermaid
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sequence {
+    pub participants: Vec<Participant>,
+    pub steps: Vec<SequenceStep>,
+}
+
+impl Sequence {
+    fn participant(&mut self, id: &str) -> usize {
+        match self.participants.iter().position(|p| p.id == id) {
+            Some(index) => index,
+            None => {
+                self.participants.push(Participant {
+                    id: id.to_string(),
+                    label: id.to_string(),
+                });
+                self.participants.len() - 1
+            }
+        }
+    }
+}
+
+/// Diagrama ya parseado
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagram {
+    Graph(Graph),
+    Sequence(Sequence),
+    Pie {
+        title: Option<String>,
+        slices: Vec<(String, f64)>,
+    },
+}
+
+// ============================================================================
+// RENDER Y CACHÉ
+// ============================================================================
+
+/// Renderizar un diagrama a SVG
+pub fn render_diagram(
+    kind: DiagramKind,
+    source: &str,
+    colors: &PreviewColors,
+) -> Result<String, DiagramError> {
+    let id = format!("{:x}", content_hash(kind, source, colors));
+    let diagram = match kind {
+        DiagramKind::Mermaid => parse_mermaid(source)?,
+        DiagramKind::Graphviz => Diagram::Graph(parse_dot(source)?),
+    };
+    Ok(match diagram {
+        Diagram::Graph(graph) => render_graph(&graph, colors, &id),
+        Diagram::Sequence(sequence) => render_sequence(&sequence, colors, &id),
+        Diagram::Pie { title, slices } => {
+            let config = ChartConfig {
+                kind: ChartKind::Pie,
+                title,
+                ..Default::default()
+            };
+            let points: Vec<ChartPoint> = slices
+                .into_iter()
+                .map(|(label, y)| ChartPoint { label, x: 0.0, y })
+                .collect();
+            ChartRenderer::new(&config, colors.clone()).render_points(&points)
+        }
+    })
+}
+
+/// Como `render_diagram`, pero reutilizando el SVG si el bloque no ha cambiado:
+/// el preview se regenera en cada pulsación y el layout de un grafo grande no es gratis
+pub fn render_diagram_cached(
+    kind: DiagramKind,
+    source: &str,
+    colors: &PreviewColors,
+) -> Result<String, DiagramError> {
+    let key = content_hash(kind, source, colors);
+    if let Some(cached) = SVG_CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.get(&key).cloned())
+    {
+        return cached;
+    }
+
+    let result = render_diagram(kind, source, colors);
+    if let Ok(mut cache) = SVG_CACHE.lock() {
+        if cache.len() >= CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(key, result.clone());
+    }
+    result
+}
+
+fn content_hash(kind: DiagramKind, source: &str, colors: &PreviewColors) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    kind.hash(&mut hasher);
+    source.trim().hash(&mut hasher);
+    for color in [
+        &colors.bg_primary,
+        &colors.bg_secondary,
+        &colors.fg_primary,
+        &colors.fg_secondary,
+        &colors.fg_muted,
+        &colors.accent,
+        &colors.border,
+    ] {
+        color.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+// ============================================================================
+// MERMAID
+// ============================================================================
+
+/// Parsear un bloque Mermaid (`flowchart`/`graph`, `sequenceDiagram` o `pie`)
+pub fn parse_mermaid(source: &str) -> Result<Diagram, DiagramError> {
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with("%%"))
+        .peekable();
+
+    // Frontmatter opcional (--- title: ... ---)
+    if lines.peek().is_some_and(|(_, line)| *line == "---") {
+        lines.next();
+        for (_, line) in lines.by_ref() {
+            if line == "---" {
+                break;
+            }
+        }
+    }
+
+    let (_, header) = lines.next().ok_or(DiagramError::Empty)?;
+    let mut words = header.split_whitespace();
+    match words.next().unwrap_or_default() {
+        "graph" | "flowchart" => {
+            let direction = words.next().and_then(Direction::parse).unwrap_or_default();
+            parse_flowchart(direction, lines).map(Diagram::Graph)
+        }
+        "sequenceDiagram" => parse_sequence(lines).map(Diagram::Sequence),
+        "pie" => parse_pie(header, lines),
+        other => Err(DiagramError::Unsupported(format!("mermaid {}", other))),
+    }
+}
+
+fn parse_flowchart<'a>(
+    direction: Direction,
+    lines: impl Iterator<Item = (usize, &'a str)>,
+) -> Result<Graph, DiagramError> {
+    let mut graph = Graph {
+        direction,
+        ..Default::default()
+    };
+
+    for (line, text) in lines {
+        for statement in split_statements(text) {
+            let keyword = statement.split_whitespace().next().unwrap_or_default();
+            // Agrupaciones y estilos: los nodos de un subgrafo se dibujan igual
+            if matches!(
+                keyword,
+                "subgraph"
+                    | "end"
+                    | "classDef"
+                    | "class"
+                    | "style"
+                    | "linkStyle"
+                    | "click"
+                    | "direction"
+            ) {
+                continue;
+            }
+            parse_flow_statement(&mut graph, statement)
+                .map_err(|message| DiagramError::Syntax { line, message })?;
+        }
+    }
+
+    if graph.nodes.is_empty() {
+        return Err(DiagramError::Empty);
+    }
+    Ok(graph)
+}
+
+/// Separar una línea por `;` fuera de textos entre corchetes o comillas
+fn split_statements(line: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut depth = 0i32;
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' | '{' if !quoted => depth += 1,
+            ']' | ')' | '}' if !quoted => depth -= 1,
+            ';' if !quoted && depth <= 0 => {
+                statements.push(line[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.push(line[start..].trim());
+    statements.retain(|s| !s.is_empty());
+    statements
+}
+
+/// Cursor sobre una sentencia de flowchart
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        if self.rest().starts_with(prefix) {
+            self.pos += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Una arista de flowchart ya leída
+struct FlowLink {
+    label: Option<String>,
+    arrow_start: bool,
+    arrow_end: bool,
+    style: EdgeStyle,
+}
+
+/// `A[Texto] -->|etiqueta| B & C -.-> D`: nodos encadenados por enlaces
+fn parse_flow_statement(graph: &mut Graph, statement: &str) -> Result<(), String> {
+    let mut cursor = Cursor {
+        text: statement,
+        pos: 0,
+    };
+    let mut previous = parse_node_group(graph, &mut cursor)?;
+    loop {
+        cursor.skip_whitespace();
+        if cursor.rest().is_empty() {
+            return Ok(());
+        }
+        let link = parse_flow_link(&mut cursor)
+            .ok_or_else(|| format!("Expected a link before '{}'", cursor.rest()))?;
+        let next = parse_node_group(graph, &mut cursor)?;
+        for &from in &previous {
+            for &to in &next {
+                graph.edges.push(GraphEdge {
+                    from,
+                    to,
+                    label: link.label.clone(),
+                    arrow_start: link.arrow_start,
+                    arrow_end: link.arrow_end,
+                    style: link.style,
+                });
+            }
+        }
+        previous = next;
+    }
+}
+
+/// `A & B[Texto]`
+fn parse_node_group(graph: &mut Graph, cursor: &mut Cursor) -> Result<Vec<usize>, String> {
+    let mut nodes = vec![parse_flow_node(graph, cursor)?];
+    loop {
+        cursor.skip_whitespace();
+        if !cursor.eat("&") {
+            return Ok(nodes);
+        }
+        nodes.push(parse_flow_node(graph, cursor)?);
+    }
+}
+
+/// Delimitadores de forma de Mermaid: (apertura, cierre, forma)
+const FLOW_SHAPES: [(&str, &str, NodeShape); 11] = [
+    ("(((", ")))", NodeShape::Circle),
+    ("((", "))", NodeShape::Circle),
+    ("([", "])", NodeShape::Rounded),
+    ("[[", "]]", NodeShape::Rect),
+    ("[(", ")]", NodeShape::Rect),
+    ("{{", "}}", NodeShape::Rounded),
+    ("[/", "]", NodeShape::Rect),
+    ("[\\", "]", NodeShape::Rect),
+    ("[", "]", NodeShape::Rect),
+    ("(", ")", NodeShape::Rounded),
+    ("{", "}", NodeShape::Diamond),
+];
+
+fn parse_flow_node(graph: &mut Graph, cursor: &mut Cursor) -> Result<usize, String> {
+    cursor.skip_whitespace();
+    let rest = cursor.rest();
+    let id_len = rest
+        .char_indices()
+        .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+        .map_or(rest.len(), |(i, _)| i);
+    if id_len == 0 {
+        return Err(format!("Expected a node at '{}'", rest));
+    }
+    let id = &rest[..id_len];
+    cursor.pos += id_len;
+
+    let mut shape_label = None;
+    for (open, close, shape) in FLOW_SHAPES {
+        if !cursor.eat(open) {
+            continue;
+        }
+        let rest = cursor.rest();
+        // Un texto entre comillas puede contener los delimitadores
+        let search_from = rest
+            .strip_prefix('"')
+            .and_then(|quoted| quoted.find('"'))
+            .map_or(0, |i| i + 2);
+        let end = rest[search_from..]
+            .find(close)
+            .map(|i| search_from + i)
+            .ok_or_else(|| format!("Missing '{}' for node '{}'", close, id))?;
+        shape_label = Some((clean_label(&rest[..end]), shape));
+        cursor.pos += end + close.len();
+        break;
+    }
+    // Clase CSS (A:::importante): no cambia el dibujo
+    if cursor.eat(":::") {
+        let rest = cursor.rest();
+        cursor.pos += rest
+            .find(|c: char| c.is_whitespace() || c == '&')
+            .unwrap_or(rest.len());
+    }
+
+    let index = graph.node(id, NodeShape::Rect);
+    if let Some((label, shape)) = shape_label {
+        graph.nodes[index].label = label;
+        graph.nodes[index].shape = shape;
+    }
+    Ok(index)
+}
+
+/// Texto de un nodo o arista: sin comillas, con `<br>` como salto de línea
+fn clean_label(raw: &str) -> String {
+    let label = raw.trim();
+    let label = label
+        .strip_prefix('"')
+        .and_then(|l| l.strip_suffix('"'))
+        .unwrap_or(label);
+    let label = label.trim_matches(['/', '\\', '`']);
+    label
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .replace("<br>", "\n")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// Enlace de flowchart: `-->`, `---`, `-.->`, `==>`, `<-->`, `--o`, `~~~`,
+/// con texto `-->|texto|`, `-- texto -->`, `-. texto .->` o `== texto ==>`
+fn parse_flow_link(cursor: &mut Cursor) -> Option<FlowLink> {
+    cursor.skip_whitespace();
+    let rest = cursor.rest();
+    let arrow_start = rest.starts_with('<');
+    let body_start = usize::from(arrow_start);
+    let body_len = rest[body_start..]
+        .find(|c: char| !matches!(c, '-' | '=' | '.' | '~'))
+        .unwrap_or(rest.len() - body_start);
+    let mut body = &rest[body_start..body_start + body_len];
+    if body.len() < 2 {
+        return None;
+    }
+    let mut consumed = body_start + body_len;
+    let mut label = None;
+
+    let after = &rest[consumed..];
+    let head = after.chars().next();
+    let is_head = |c: Option<char>, tail: &str| match c {
+        Some('>') => true,
+        Some('o' | 'x') => tail[1..].starts_with(|c: char| c.is_whitespace() || c == '|'),
+        _ => false,
+    };
+    let mut arrow_end = is_head(head, after);
+
+    if !arrow_end && matches!(body, "--" | "==" | "-.") && head.is_some_and(char::is_whitespace) {
+        // Texto dentro del enlace: "-- texto -->"
+        let close = after
+            .match_indices(['-', '=', '.'])
+            .map(|(i, _)| i)
+            .find(|&i| {
+                let tail = &after[i..];
+                let run = tail
+                    .find(|c: char| !matches!(c, '-' | '=' | '.'))
+                    .unwrap_or(tail.len());
+                run >= 2 || tail[run..].starts_with('>')
+            })?;
+        label = Some(clean_label(&after[..close]));
+        let tail = &after[close..];
+        let run = tail
+            .find(|c: char| !matches!(c, '-' | '=' | '.'))
+            .unwrap_or(tail.len());
+        body = &tail[..run];
+        consumed += close + run;
+        arrow_end = is_head(tail[run..].chars().next(), &tail[run..]);
+    }
+    if arrow_end {
+        consumed += 1;
+    }
+    cursor.pos += consumed;
+
+    // Texto tras el enlace: -->|texto|
+    cursor.skip_whitespace();
+    if cursor.rest().starts_with('|') {
+        let rest = &cursor.rest()[1..];
+        if let Some(end) = rest.find('|') {
+            label = Some(clean_label(&rest[..end]));
+            cursor.pos += end + 2;
+        }
+    }
+
+    let style = if body.contains('~') {
+        EdgeStyle::Invisible
+    } else if body.contains('.') {
+        EdgeStyle::Dashed
+    } else if body.contains('=') {
+        EdgeStyle::Thick
+    } else {
+        EdgeStyle::Solid
+    };
+    Some(FlowLink {
+        label: label.filter(|l| !l.is_empty()),
+        arrow_start,
+        arrow_end,
+        style,
+    })
+}
+
+/// Flechas de mensaje de Mermaid: (operador, discontinua, con punta)
+const SEQUENCE_ARROWS: [(&str, bool, bool); 10] = [
+    ("-->>", true, true),
+    ("->>", false, true),
+    ("--x", true, true),
+    ("-x", false, true),
+    ("--)", true, true),
+    ("-)", false, true),
+    ("<<-->>", true, true),
+    ("<<->>", false, true),
+    ("-->", true, false),
+    ("->", false, false),
+];
+
+fn parse_sequence<'a>(
+    lines: impl Iterator<Item = (usize, &'a str)>,
+) -> Result<Sequence, DiagramError> {
+    let mut sequence = Sequence::default();
+    let mut autonumber = None;
+
+    for (line, text) in lines {
+        let keyword = text.split_whitespace().next().unwrap_or_default();
+        match keyword {
+            "participant" | "actor" => {
+                let rest = text[keyword.len()..].trim();
+                let (id, label) = match rest.split_once(" as ") {
+                    Some((id, label)) => (id.trim(), label.trim()),
+                    None => (rest, rest),
+                };
+                let index = sequence.participant(id);
+                sequence.participants[index].label = clean_label(label);
+                continue;
+            }
+            "autonumber" => {
+                autonumber = Some(1);
+                continue;
+            }
+            "Note" | "note" => {
+                let step = parse_note(&mut sequence, &text[keyword.len()..]).ok_or_else(|| {
+                    DiagramError::Syntax {
+                        line,
+                        message: format!("Invalid note: {}", text),
+                    }
+                })?;
+                sequence.steps.push(step);
+                continue;
+            }
+            // Bloques y activaciones: solo afectan a la decoración
+            "loop" | "alt" | "else" | "opt" | "par" | "and" | "critical" | "break" | "rect"
+            | "end" | "activate" | "deactivate" | "box" | "title" | "create" | "destroy" => {
+                continue;
+            }
+            _ => {}
+        }
+
+        let (message, text_part) = match text.split_once(':') {
+            Some((message, text)) => (message, text.trim()),
+            None => (text, ""),
+        };
+        let Some((position, op, dashed, arrow)) = SEQUENCE_ARROWS
+            .iter()
+            .filter_map(|&(op, dashed, arrow)| {
+                message
+                    .find(op)
+                    .map(|position| (position, op, dashed, arrow))
+            })
+            .min_by_key(|&(position, op, ..)| (position, std::cmp::Reverse(op.len())))
+        else {
+            return Err(DiagramError::Syntax {
+                line,
+                message: format!("Expected a message like 'A->>B: text', got '{}'", text),
+            });
+        };
+
+        let from = message[..position].trim();
+        let to = message[position + op.len()..]
+            .trim()
+            .trim_start_matches(['+', '-']);
+        if from.is_empty() || to.is_empty() {
+            return Err(DiagramError::Syntax {
+                line,
+                message: format!("Missing participant in '{}'", text),
+            });
+        }
+        let from = sequence.participant(from);
+        let to = sequence.participant(to.trim());
+        let mut text = clean_label(text_part);
+        if let Some(number) = autonumber.as_mut() {
+            text = format!("{}. {}", number, text);
+            *number += 1;
+        }
+        sequence.steps.push(SequenceStep::Message {
+            from,
+            to,
+            text,
+            dashed,
+            arrow,
+        });
+    }
+
+    if sequence.participants.is_empty() {
+        return Err(DiagramError::Empty);
+    }
+    Ok(sequence)
+}
+
+/// `right of A: texto`, `left of A: texto`, `over A,B: texto`
+fn parse_note(sequence: &mut Sequence, rest: &str) -> Option<SequenceStep> {
+    let (position, text) = rest.split_once(':')?;
+    let position = position.trim();
+    let (side, targets) = if let Some(targets) = position.strip_prefix("right of") {
+        (NoteSide::Right, targets)
+    } else if let Some(targets) = position.strip_prefix("left of") {
+        (NoteSide::Left, targets)
+    } else {
+        (NoteSide::Over, position.strip_prefix("over")?)
+    };
+    let mut targets = targets.split(',').map(str::trim).filter(|t| !t.is_empty());
+    let first = sequence.participant(targets.next()?);
+    let last = targets.next().map_or(first, |id| sequence.participant(id));
+    Some(SequenceStep::Note {
+        from: first.min(last),
+        to: first.max(last),
+        side,
+        text: clean_label(text),
+    })
+}
+
+fn parse_pie<'a>(
+    header: &str,
+    lines: impl Iterator<Item = (usize, &'a str)>,
+) -> Result<Diagram, DiagramError> {
+    let mut title = header
+        .split_once("title")
+        .map(|(_, title)| title.trim().to_string());
+    let mut slices = Vec::new();
+    for (line, text) in lines {
+        if let Some(rest) = text.strip_prefix("title") {
+            title = Some(rest.trim().to_string());
+            continue;
+        }
+        if text == "showData" {
+            continue;
+        }
+        let (label, value) = text
+            .rsplit_once(':')
+            .and_then(|(label, value)| Some((label, value.trim().parse::<f64>().ok()?)))
+            .filter(|(_, value)| value.is_finite() && *value >= 0.0)
+            .ok_or_else(|| DiagramError::Syntax {
+                line,
+                message: format!("Expected '\"Label\" : value', got '{}'", text),
+            })?;
+        slices.push((clean_label(label), value));
+    }
+    if slices.is_empty() {
+        return Err(DiagramError::Empty);
+    }
+    Ok(Diagram::Pie {
+        title: title.filter(|t| !t.is_empty()),
+        slices,
+    })
+}
+
+// ============================================================================
+// GRAPHVIZ (DOT)
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum DotToken {
+    /// Identificador, número, "cadena" o <html>
+    Id(String),
+    /// { } [ ] = ; , : -> --
+    Punct(&'static str),
+}
+
+fn tokenize_dot(source: &str) -> Result<Vec<(usize, DotToken)>, DiagramError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    let mut line_start = true;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                i += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            // Líneas de preprocesador (#) y comentarios // y /* */
+            '#' if line_start => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i += 2;
+                continue;
+            }
+            _ => {}
+        }
+        line_start = false;
+
+        let token = match c {
+            '{' | '}' | '[' | ']' | '=' | ';' | ',' | ':' => {
+                i += 1;
+                DotToken::Punct(match c {
+                    '{' => "{",
+                    '}' => "}",
+                    '[' => "[",
+                    ']' => "]",
+                    '=' => "=",
+                    ';' => ";",
+                    ',' => ",",
+                    _ => ":",
+                })
+            }
+            '-' if matches!(chars.get(i + 1), Some('>' | '-')) => {
+                i += 2;
+                DotToken::Punct(if chars[i - 1] == '>' { "->" } else { "--" })
+            }
+            '"' => {
+                let start_line = line;
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(DiagramError::Syntax {
+                                line: start_line,
+                                message: "Unterminated string".to_string(),
+                            });
+                        }
+                        Some('"') => break,
+                        Some('\\') => {
+                            match chars.get(i + 1) {
+                                Some('n' | 'l' | 'r') => text.push('\n'),
+                                Some('\n') => line += 1,
+                                Some(&other) => text.push(other),
+                                None => {}
+                            }
+                            i += 1;
+                        }
+                        Some(&other) => {
+                            if other == '\n' {
+                                line += 1;
+                            }
+                            text.push(other);
+                        }
+                    }
+                    i += 1;
+                }
+                i += 1;
+                DotToken::Id(text.trim_end_matches('\n').to_string())
+            }
+            '<' => {
+                // Etiqueta HTML: se queda el texto, con <br/> como salto de línea
+                let mut depth = 0;
+                let mut html = String::new();
+                while let Some(&c) = chars.get(i) {
+                    i += 1;
+                    match c {
+                        '<' => depth += 1,
+                        '>' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        '\n' => line += 1,
+                        _ => {}
+                    }
+                    html.push(c);
+                }
+                DotToken::Id(html_text(&html[1..]))
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = String::new();
+                while let Some(&c) = chars.get(i) {
+                    let minus = c == '-' && id.is_empty();
+                    if c.is_alphanumeric() || c == '_' || c == '.' || minus {
+                        id.push(c);
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                DotToken::Id(id)
+            }
+            other => {
+                return Err(DiagramError::Syntax {
+                    line,
+                    message: format!("Unexpected character '{}'", other),
+                });
+            }
+        };
+        tokens.push((line, token));
+    }
+    Ok(tokens)
+}
+
+/// Texto de una etiqueta HTML de Graphviz
+fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    let mut tag = String::new();
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                if tag.trim_start().to_ascii_lowercase().starts_with("br") {
+                    text.push('\n');
+                }
+            }
+            c if in_tag => tag.push(c),
+            c => text.push(c),
+        }
+    }
+    text.trim().to_string()
+}
+
+/// Parsear un grafo DOT (`graph { a -- b }` o `digraph { a -> b }`)
+pub fn parse_dot(source: &str) -> Result<Graph, DiagramError> {
+    let tokens = tokenize_dot(source)?;
+    if tokens.is_empty() {
+        return Err(DiagramError::Empty);
+    }
+    let mut parser = DotParser {
+        tokens,
+        pos: 0,
+        graph: Graph::default(),
+        directed: false,
+        node_defaults: Vec::new(),
+        edge_defaults: Vec::new(),
+    };
+    parser.parse()?;
+    if parser.graph.nodes.is_empty() {
+        return Err(DiagramError::Empty);
+    }
+    Ok(parser.graph)
+}
+
+type DotAttrs = Vec<(String, String)>;
+
+struct DotParser {
+    tokens: Vec<(usize, DotToken)>,
+    pos: usize,
+    graph: Graph,
+    directed: bool,
+    node_defaults: DotAttrs,
+    edge_defaults: DotAttrs,
+}
+
+impl DotParser {
+    fn peek(&self) -> Option<&DotToken> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn peek_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(DotToken::Punct(p)) if *p == punct)
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(DotToken::Id(id)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn error(&self, message: impl Into<String>) -> DiagramError {
+        let line = self
+            .tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |(line, _)| *line);
+        DiagramError::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), DiagramError> {
+        if self.peek_punct(punct) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", punct)))
+        }
+    }
+
+    fn id(&mut self) -> Result<String, DiagramError> {
+        match self.peek() {
+            Some(DotToken::Id(id)) => {
+                let id = id.clone();
+                self.pos += 1;
+                Ok(id)
+            }
+            _ => Err(self.error("Expected an identifier")),
+        }
+    }
+
+    fn parse(&mut self) -> Result<(), DiagramError> {
+        if self.peek_keyword("strict") {
+            self.pos += 1;
+        }
+        if self.peek_keyword("digraph") {
+            self.directed = true;
+        } else if !self.peek_keyword("graph") {
+            return Err(match self.peek() {
+                Some(DotToken::Id(id)) => DiagramError::Unsupported(format!("dot {}", id)),
+                _ => self.error("Expected 'graph' or 'digraph'"),
+            });
+        }
+        self.pos += 1;
+        if matches!(self.peek(), Some(DotToken::Id(_))) {
+            self.pos += 1;
+        }
+        self.expect("{")?;
+        self.statements()?;
+        self.expect("}")
+    }
+
+    fn statements(&mut self) -> Result<(), DiagramError> {
+        while self.peek().is_some() && !self.peek_punct("}") {
+            self.statement()?;
+            while self.peek_punct(";") || self.peek_punct(",") {
+                self.pos += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self) -> Result<(), DiagramError> {
+        for (keyword, is_node) in [("node", true), ("edge", false), ("graph", false)] {
+            let next_is_attrs = matches!(
+                self.tokens.get(self.pos + 1),
+                Some((_, DotToken::Punct("[")))
+            );
+            if self.peek_keyword(keyword) && next_is_attrs {
+                self.pos += 1;
+                let attrs = self.attributes()?;
+                match keyword {
+                    "graph" => self.apply_graph_attrs(&attrs),
+                    _ if is_node => self.node_defaults.extend(attrs),
+                    _ => self.edge_defaults.extend(attrs),
+                }
+                return Ok(());
+            }
+        }
+
+        // Atributo de grafo suelto: rankdir=LR
+        if matches!(
+            self.tokens.get(self.pos + 1),
+            Some((_, DotToken::Punct("=")))
+        ) && matches!(self.peek(), Some(DotToken::Id(_)))
+        {
+            let key = self.id()?;
+            self.pos += 1;
+            let value = self.id()?;
+            self.apply_graph_attrs(&[(key, value)]);
+            return Ok(());
+        }
+
+        // Nodo o cadena de aristas (los extremos pueden ser subgrafos)
+        let mut groups = vec![self.operand()?];
+        while self.peek_punct("->") || self.peek_punct("--") {
+            self.pos += 1;
+            groups.push(self.operand()?);
+        }
+        let attrs = if self.peek_punct("[") {
+            self.attributes()?
+        } else {
+            Vec::new()
+        };
+
+        if groups.len() == 1 {
+            if let [node] = groups[0].as_slice() {
+                self.apply_node_attrs(*node, &attrs);
+            }
+            return Ok(());
+        }
+
+        let mut edge_attrs = self.edge_defaults.clone();
+        edge_attrs.extend(attrs);
+        for pair in groups.windows(2) {
+            for &from in &pair[0] {
+                for &to in &pair[1] {
+                    let edge = self.edge(from, to, &edge_attrs);
+                    self.graph.edges.push(edge);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Extremo de una arista: un nodo (con puerto opcional) o un subgrafo
+    fn operand(&mut self) -> Result<Vec<usize>, DiagramError> {
+        if self.peek_keyword("subgraph") || self.peek_punct("{") {
+            return self.subgraph();
+        }
+        let id = self.id()?;
+        // Puerto (a:n, a:puerto:sw): no afecta al dibujo
+        while self.peek_punct(":") {
+            self.pos += 1;
+            self.id()?;
+        }
+        let existing = self.graph.nodes.iter().any(|n| n.id == id);
+        let index = self.graph.node(&id, NodeShape::Ellipse);
+        if !existing {
+            let defaults = self.node_defaults.clone();
+            self.apply_node_attrs(index, &defaults);
+        }
+        Ok(vec![index])
+    }
+
+    /// `subgraph nombre { ... }`: los atributos por defecto son locales
+    fn subgraph(&mut self) -> Result<Vec<usize>, DiagramError> {
+        if self.peek_keyword("subgraph") {
+            self.pos += 1;
+            if matches!(self.peek(), Some(DotToken::Id(_))) {
+                self.pos += 1;
+            }
+        }
+        self.expect("{")?;
+        let first_node = self.graph.nodes.len();
+        let saved = (self.node_defaults.clone(), self.edge_defaults.clone());
+
+        let start = self.pos;
+        self.statements()?;
+        self.expect("}")?;
+        (self.node_defaults, self.edge_defaults) = saved;
+
+        // Nodos del subgrafo: los creados y los ya existentes que se nombran dentro
+        let mut nodes: Vec<usize> = (first_node..self.graph.nodes.len()).collect();
+        for (_, token) in &self.tokens[start..self.pos] {
+            if let DotToken::Id(id) = token
+                && let Some(index) = self.graph.nodes[..first_node]
+                    .iter()
+                    .position(|n| &n.id == id)
+                && !nodes.contains(&index)
+            {
+                nodes.push(index);
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn attributes(&mut self) -> Result<DotAttrs, DiagramError> {
+        let mut attrs = Vec::new();
+        while self.peek_punct("[") {
+            self.pos += 1;
+            while !self.peek_punct("]") {
+                if self.peek().is_none() {
+                    return Err(self.error("Expected ']'"));
+                }
+                let key = self.id()?;
+                let value = if self.peek_punct("=") {
+                    self.pos += 1;
+                    self.id()?
+                } else {
+                    "true".to_string()
+                };
+                attrs.push((key.to_ascii_lowercase(), value));
+                while self.peek_punct(",") || self.peek_punct(";") {
+                    self.pos += 1;
+                }
+            }
+            self.pos += 1;
+        }
+        Ok(attrs)
+    }
+
+    fn apply_graph_attrs(&mut self, attrs: &[(String, String)]) {
+        for (key, value) in attrs {
+            if key.eq_ignore_ascii_case("rankdir")
+                && let Some(direction) = Direction::parse(value)
+            {
+                self.graph.direction = direction;
+            }
+        }
+    }
+
+    fn apply_node_attrs(&mut self, index: usize, attrs: &[(String, String)]) {
+        let node = &mut self.graph.nodes[index];
+        for (key, value) in attrs {
+            match key.as_str() {
+                "label" if value != "\\N" => node.label = value.replace('|', " | "),
+                "shape" => {
+                    node.shape = match value.to_ascii_lowercase().as_str() {
+                        "box" | "rect" | "rectangle" | "square" | "record" | "folder" | "note"
+                        | "tab" | "component" | "cylinder" => NodeShape::Rect,
+                        "mrecord" => NodeShape::Rounded,
+                        "circle" | "doublecircle" | "point" => NodeShape::Circle,
+                        "diamond" | "mdiamond" => NodeShape::Diamond,
+                        "plaintext" | "plain" | "none" => NodeShape::Plain,
+                        _ => NodeShape::Ellipse,
+                    }
+                }
+                "style" if value.contains("rounded") && node.shape == NodeShape::Rect => {
+                    node.shape = NodeShape::Rounded
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn edge(&self, from: usize, to: usize, attrs: &[(String, String)]) -> GraphEdge {
+        let mut edge = GraphEdge {
+            from,
+            to,
+            label: None,
+            arrow_start: false,
+            arrow_end: self.directed,
+            style: EdgeStyle::Solid,
+        };
+        for (key, value) in attrs {
+            match key.as_str() {
+                "label" | "xlabel" => edge.label = Some(value.clone()).filter(|l| !l.is_empty()),
+                "style" => {
+                    edge.style = match value.as_str() {
+                        "dashed" | "dotted" => EdgeStyle::Dashed,
+                        "bold" => EdgeStyle::Thick,
+                        "invis" => EdgeStyle::Invisible,
+                        _ => EdgeStyle::Solid,
+                    }
+                }
+                "dir" => {
+                    (edge.arrow_start, edge.arrow_end) = match value.as_str() {
+                        "both" => (true, true),
+                        "back" => (true, false),
+                        "none" => (false, false),
+                        _ => (false, true),
+                    }
+                }
+                "arrowhead" if value == "none" => edge.arrow_end = false,
+                _ => {}
+            }
+        }
+        edge
+    }
+}
+
+// ============================================================================
+// LAYOUT POR CAPAS
+// ============================================================================
+
+/// Posición de un nodo (centro) y su tamaño
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Placed {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+struct GraphLayout {
+    nodes: Vec<Placed>,
+    /// Puntos de cada arista (en el orden de `Graph::edges`), ya recortados al borde
+    edges: Vec<Vec<(f64, f64)>>,
+    width: f64,
+    height: f64,
+}
+
+fn text_width(text: &str) -> f64 {
+    text.lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0) as f64
+        * CHAR_WIDTH
+}
+
+fn text_height(text: &str) -> f64 {
+    text.lines().count().max(1) as f64 * LINE_HEIGHT
+}
+
+fn node_size(node: &GraphNode) -> (f64, f64) {
+    let width = text_width(&node.label) + 24.0;
+    let height = text_height(&node.label) + 16.0;
+    match node.shape {
+        NodeShape::Rect | NodeShape::Rounded | NodeShape::Plain => {
+            (width.max(48.0), height.max(36.0))
+        }
+        NodeShape::Ellipse => ((width * 1.25).max(56.0), (height * 1.3).max(40.0)),
+        NodeShape::Circle => {
+            let diameter = width.max(height).max(40.0);
+            (diameter, diameter)
+        }
+        NodeShape::Diamond => ((width * 1.6).max(60.0), (height * 1.8).max(50.0)),
+    }
+}
+
+fn layout_graph(graph: &Graph) -> GraphLayout {
+    let node_count = graph.nodes.len();
+    let horizontal = graph.direction.is_horizontal();
+    let sizes: Vec<(f64, f64)> = graph.nodes.iter().map(node_size).collect();
+
+    // 1. Quitar ciclos: las aristas de retroceso de un DFS se invierten
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+    for (index, edge) in graph.edges.iter().enumerate() {
+        if edge.from != edge.to {
+            adjacency[edge.from].push(index);
+        }
+    }
+    let mut reversed = vec![false; graph.edges.len()];
+    let mut state = vec![0u8; node_count]; // 0 = sin visitar, 1 = en la pila, 2 = hecho
+    for root in 0..node_count {
+        if state[root] != 0 {
+            continue;
+        }
+        let mut stack = vec![(root, 0)];
+        state[root] = 1;
+        while let Some((node, next)) = stack.pop() {
+            if let Some(&edge) = adjacency[node].get(next) {
+                stack.push((node, next + 1));
+                let target = graph.edges[edge].to;
+                match state[target] {
+                    0 => {
+                        state[target] = 1;
+                        stack.push((target, 0));
+                    }
+                    1 => reversed[edge] = true,
+                    _ => {}
+                }
+            } else {
+                state[node] = 2;
+            }
+        }
+    }
+    let oriented: Vec<Option<(usize, usize)>> = graph
+        .edges
+        .iter()
+        .zip(&reversed)
+        .map(|(edge, &rev)| {
+            (edge.from != edge.to).then_some(if rev {
+                (edge.to, edge.from)
+            } else {
+                (edge.from, edge.to)
+            })
+        })
+        .collect();
+
+    // 2. Rangos por camino más largo (orden topológico de Kahn)
+    let mut indegree = vec![0usize; node_count];
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); node_count];
+    for &(from, to) in oriented.iter().flatten() {
+        indegree[to] += 1;
+        successors[from].push(to);
+    }
+    let mut rank = vec![0usize; node_count];
+    let mut queue: Vec<usize> = (0..node_count).filter(|&n| indegree[n] == 0).collect();
+    let mut head = 0;
+    while head < queue.len() {
+        let node = queue[head];
+        head += 1;
+        for &next in &successors[node] {
+            rank[next] = rank[next].max(rank[node] + 1);
+            indegree[next] -= 1;
+            if indegree[next] == 0 {
+                queue.push(next);
+            }
+        }
+    }
+
+    // 3. Nodos ficticios en las aristas que saltan varias capas
+    let mut vertex_rank = rank.clone();
+    let mut vertex_size: Vec<(f64, f64)> = sizes.clone();
+    let mut paths: Vec<Vec<usize>> = Vec::with_capacity(graph.edges.len());
+    for link in &oriented {
+        let Some((from, to)) = *link else {
+            paths.push(Vec::new());
+            continue;
+        };
+        let mut path = vec![from];
+        for r in rank[from] + 1..rank[to] {
+            vertex_rank.push(r);
+            vertex_size.push((DUMMY_SIZE, DUMMY_SIZE));
+            path.push(vertex_rank.len() - 1);
+        }
+        path.push(to);
+        paths.push(path);
+    }
+    let vertex_count = vertex_rank.len();
+    let rank_count = vertex_rank.iter().max().map_or(0, |r| r + 1);
+    let mut layers: Vec<Vec<usize>> = vec![Vec::new(); rank_count];
+    for (vertex, &r) in vertex_rank.iter().enumerate() {
+        layers[r].push(vertex);
+    }
+    let mut upper: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    let mut lower: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+    for path in &paths {
+        for pair in path.windows(2) {
+            lower[pair[0]].push(pair[1]);
+            upper[pair[1]].push(pair[0]);
+        }
+    }
+
+    // 4. Orden dentro de cada capa por baricentros (barridos alternos)
+    let mut order = vec![0.0f64; vertex_count];
+    let renumber = |layers: &Vec<Vec<usize>>, order: &mut Vec<f64>| {
+        for layer in layers {
+            for (i, &v) in layer.iter().enumerate() {
+                order[v] = i as f64;
+            }
+        }
+    };
+    renumber(&layers, &mut order);
+    for sweep in 0..8 {
+        let downward = sweep % 2 == 0;
+        let range: Vec<usize> = if downward {
+            (1..rank_count).collect()
+        } else {
+            (0..rank_count.saturating_sub(1)).rev().collect()
+        };
+        for r in range {
+            let neighbors = if downward { &upper } else { &lower };
+            let mut keyed: Vec<(f64, usize)> = layers[r]
+                .iter()
+                .map(|&v| {
+                    let adjacent = &neighbors[v];
+                    let key = if adjacent.is_empty() {
+                        order[v]
+                    } else {
+                        adjacent.iter().map(|&u| order[u]).sum::<f64>() / adjacent.len() as f64
+                    };
+                    (key, v)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[r] = keyed.into_iter().map(|(_, v)| v).collect();
+            for (i, &v) in layers[r].iter().enumerate() {
+                order[v] = i as f64;
+            }
+        }
+    }
+
+    // 5. Coordenadas: eje de rangos y eje transversal
+    let main_extent = |v: usize| {
+        let (w, h) = vertex_size[v];
+        if horizontal { w } else { h }
+    };
+    let cross_extent = |v: usize| {
+        let (w, h) = vertex_size[v];
+        if horizontal { h } else { w }
+    };
+    let mut rank_position = vec![0.0; rank_count];
+    let mut cursor = MARGIN;
+    for r in 0..rank_count {
+        let thickness = layers[r]
+            .iter()
+            .map(|&v| main_extent(v))
+            .fold(0.0, f64::max);
+        rank_position[r] = cursor + thickness / 2.0;
+        cursor += thickness + RANK_GAP;
+    }
+    let main_total = cursor - RANK_GAP + MARGIN;
+
+    let mut cross = vec![0.0f64; vertex_count];
+    for layer in &layers {
+        let mut next = 0.0;
+        for &v in layer {
+            cross[v] = next + cross_extent(v) / 2.0;
+            next += cross_extent(v) + NODE_GAP;
+        }
+    }
+    // Acercar cada vértice a la media de sus vecinos sin solaparse
+    for iteration in 0..6 {
+        let downward = iteration % 2 == 0;
+        let range: Vec<usize> = if downward {
+            (0..rank_count).collect()
+        } else {
+            (0..rank_count).rev().collect()
+        };
+        for r in range {
+            let desired: Vec<f64> = layers[r]
+                .iter()
+                .map(|&v| {
+                    let adjacent: Vec<usize> = upper[v].iter().chain(&lower[v]).copied().collect();
+                    if adjacent.is_empty() {
+                        cross[v]
+                    } else {
+                        adjacent.iter().map(|&u| cross[u]).sum::<f64>() / adjacent.len() as f64
+                    }
+                })
+                .collect();
+            let mut min_left = f64::NEG_INFINITY;
+            for (i, &v) in layers[r].iter().enumerate() {
+                let half = cross_extent(v) / 2.0;
+                cross[v] = desired[i].max(min_left + half);
+                min_left = cross[v] + half + NODE_GAP;
+            }
+        }
+    }
+    let cross_min = (0..vertex_count)
+        .map(|v| cross[v] - cross_extent(v) / 2.0)
+        .fold(f64::INFINITY, f64::min);
+    let cross_max = (0..vertex_count)
+        .map(|v| cross[v] + cross_extent(v) / 2.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let cross_total = cross_max - cross_min + 2.0 * MARGIN;
+
+    let point = |v: usize| {
+        let mut main = rank_position[vertex_rank[v]];
+        if graph.direction.is_reversed() {
+            main = main_total - main;
+        }
+        let across = cross[v] - cross_min + MARGIN;
+        if horizontal {
+            (main, across)
+        } else {
+            (across, main)
+        }
+    };
+
+    let nodes: Vec<Placed> = (0..node_count)
+        .map(|v| {
+            let (x, y) = point(v);
+            Placed {
+                x,
+                y,
+                width: sizes[v].0,
+                height: sizes[v].1,
+            }
+        })
+        .collect();
+
+    // 6. Trazado de las aristas, recortado al borde de los nodos
+    let edges = graph
+        .edges
+        .iter()
+        .zip(&paths)
+        .zip(&reversed)
+        .map(|((edge, path), &rev)| {
+            if edge.from == edge.to {
+                let node = nodes[edge.from];
+                let (x, y) = (node.x + node.width / 2.0, node.y);
+                return vec![
+                    (x, y - 8.0),
+                    (x + 28.0, y - 18.0),
+                    (x + 28.0, y + 18.0),
+                    (x, y + 8.0),
+                ];
+            }
+            let mut points: Vec<(f64, f64)> = path.iter().map(|&v| point(v)).collect();
+            if rev {
+                points.reverse();
+            }
+            let last = points.len() - 1;
+            points[0] = clip(&nodes[edge.from], graph.nodes[edge.from].shape, points[1]);
+            points[last] = clip(
+                &nodes[edge.to],
+                graph.nodes[edge.to].shape,
+                points[last - 1],
+            );
+            points
+        })
+        .collect();
+
+    let (width, height) = if horizontal {
+        (main_total, cross_total)
+    } else {
+        (cross_total, main_total)
+    };
+    GraphLayout {
+        nodes,
+        edges,
+        width,
+        height,
+    }
+}
+
+/// Punto del borde del nodo en la dirección de `toward`
+fn clip(node: &Placed, shape: NodeShape, toward: (f64, f64)) -> (f64, f64) {
+    let (dx, dy) = (toward.0 - node.x, toward.1 - node.y);
+    if dx.abs() < f64::EPSILON && dy.abs() < f64::EPSILON {
+        return (node.x, node.y);
+    }
+    let (hw, hh) = (node.width / 2.0, node.height / 2.0);
+    let t = match shape {
+        NodeShape::Ellipse | NodeShape::Circle => {
+            1.0 / ((dx / hw).powi(2) + (dy / hh).powi(2)).sqrt()
+        }
+        NodeShape::Diamond => 1.0 / (dx.abs() / hw + dy.abs() / hh),
+        _ => {
+            let tx = if dx.abs() > f64::EPSILON {
+                hw / dx.abs()
+            } else {
+                f64::INFINITY
+            };
+            let ty = if dy.abs() > f64::EPSILON {
+                hh / dy.abs()
+            } else {
+                f64::INFINITY
+            };
+            tx.min(ty)
+        }
+    };
+    (node.x + dx * t, node.y + dy * t)
+}
+
+// ============================================================================
+// SVG
+// ============================================================================
+
+fn svg_open(svg: &mut String, width: f64, height: f64, colors: &PreviewColors, id: &str) {
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" class="nn-diagram" viewBox="0 0 {w:.0} {h:.0}" width="{w:.0}" style="max-width: 100%; height: auto" font-family="Inter, sans-serif" font-size="{FONT_SIZE}">"#,
+        w = width.ceil(),
+        h = height.ceil()
+    );
+    let _ = write!(
+        svg,
+        r#"<defs><marker id="nn-arrow-{id}" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="8" markerHeight="8" orient="auto-start-reverse"><path d="M0,0 L10,5 L0,10 z" fill="{}"/></marker></defs>"#,
+        colors.fg_secondary
+    );
+}
+
+/// Texto (varias líneas) centrado en (x, y)
+fn svg_text(svg: &mut String, text: &str, x: f64, y: f64, fill: &str) {
+    let lines: Vec<&str> = text.lines().collect();
+    let first = y - (lines.len().saturating_sub(1)) as f64 * LINE_HEIGHT / 2.0;
+    let _ = write!(
+        svg,
+        r#"<text x="{x:.1}" y="{first:.1}" text-anchor="middle" dominant-baseline="central" fill="{fill}">"#
+    );
+    for (i, line) in lines.iter().enumerate() {
+        let dy = if i == 0 { 0.0 } else { LINE_HEIGHT };
+        let _ = write!(
+            svg,
+            r#"<tspan x="{x:.1}" dy="{dy}">{}</tspan>"#,
+            escape_xml(line)
+        );
+    }
+    svg.push_str("</text>");
+}
+
+/// Etiqueta con fondo (texto de aristas y mensajes)
+fn svg_label(svg: &mut String, text: &str, x: f64, y: f64, colors: &PreviewColors) {
+    let (w, h) = (text_width(text) + 8.0, text_height(text) + 4.0);
+    let _ = write!(
+        svg,
+        r#"<rect x="{:.1}" y="{:.1}" width="{w:.1}" height="{h:.1}" rx="3" fill="{}" opacity="0.9"/>"#,
+        x - w / 2.0,
+        y - h / 2.0,
+        colors.bg_primary
+    );
+    svg_text(svg, text, x, y, &colors.fg_secondary);
+}
+
+fn render_graph(graph: &Graph, colors: &PreviewColors, id: &str) -> String {
+    let layout = layout_graph(graph);
+    let mut svg = String::new();
+    svg_open(&mut svg, layout.width, layout.height, colors, id);
+
+    for (edge, points) in graph.edges.iter().zip(&layout.edges) {
+        if edge.style == EdgeStyle::Invisible {
+            continue;
+        }
+        let mut d = String::new();
+        for (i, (x, y)) in points.iter().enumerate() {
+            let _ = write!(d, "{}{:.1},{:.1} ", if i == 0 { "M" } else { "L" }, x, y);
+        }
+        let (width, dash) = match edge.style {
+            EdgeStyle::Thick => (3.0, ""),
+            EdgeStyle::Dashed => (1.5, r#" stroke-dasharray="6 4""#),
+            _ => (1.5, ""),
+        };
+        let marker = format!("url(#nn-arrow-{})", id);
+        let _ = write!(
+            svg,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}"{}{}{}/>"#,
+            d.trim_end(),
+            colors.fg_secondary,
+            width,
+            dash,
+            if edge.arrow_end {
+                format!(r#" marker-end="{}""#, marker)
+            } else {
+                String::new()
+            },
+            if edge.arrow_start {
+                format!(r#" marker-start="{}""#, marker)
+            } else {
+                String::new()
+            }
+        );
+    }
+
+    for (node, placed) in graph.nodes.iter().zip(&layout.nodes) {
+        let Placed {
+            x,
+            y,
+            width,
+            height,
+        } = *placed;
+        let (fill, stroke) = (&colors.bg_secondary, &colors.accent);
+        let _ = match node.shape {
+            NodeShape::Rect | NodeShape::Rounded => write!(
+                svg,
+                r#"<rect x="{:.1}" y="{:.1}" width="{width:.1}" height="{height:.1}" rx="{}" fill="{fill}" stroke="{stroke}" stroke-width="1.5"/>"#,
+                x - width / 2.0,
+                y - height / 2.0,
+                if node.shape == NodeShape::Rounded {
+                    height / 2.0
+                } else {
+                    4.0
+                }
+            ),
+            NodeShape::Ellipse | NodeShape::Circle => write!(
+                svg,
+                r#"<ellipse cx="{x:.1}" cy="{y:.1}" rx="{:.1}" ry="{:.1}" fill="{fill}" stroke="{stroke}" stroke-width="1.5"/>"#,
+                width / 2.0,
+                height / 2.0
+            ),
+            NodeShape::Diamond => write!(
+                svg,
+                r#"<polygon points="{:.1},{y:.1} {x:.1},{:.1} {:.1},{y:.1} {x:.1},{:.1}" fill="{fill}" stroke="{stroke}" stroke-width="1.5"/>"#,
+                x - width / 2.0,
+                y - height / 2.0,
+                x + width / 2.0,
+                y + height / 2.0
+            ),
+            NodeShape::Plain => Ok(()),
+        };
+        svg_text(&mut svg, &node.label, x, y, &colors.fg_primary);
+    }
+
+    // Etiquetas al final para que queden por encima de nodos y aristas
+    for (edge, points) in graph.edges.iter().zip(&layout.edges) {
+        if let Some(label) = edge
+            .label
+            .as_deref()
+            .filter(|_| edge.style != EdgeStyle::Invisible)
+        {
+            let (x, y) = midpoint(points);
+            svg_label(&mut svg, label, x, y, colors);
+        }
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Punto a mitad de recorrido de una polilínea
+fn midpoint(points: &[(f64, f64)]) -> (f64, f64) {
+    let segment = |pair: &[(f64, f64)]| (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1);
+    let total: f64 = points.windows(2).map(segment).sum();
+    let mut remaining = total / 2.0;
+    for pair in points.windows(2) {
+        let length = segment(pair);
+        if length >= remaining && length > 0.0 {
+            let t = remaining / length;
+            return (
+                pair[0].0 + (pair[1].0 - pair[0].0) * t,
+                pair[0].1 + (pair[1].1 - pair[0].1) * t,
+            );
+        }
+        remaining -= length;
+    }
+    points.first().copied().unwrap_or_default()
+}
+
+fn render_sequence(sequence: &Sequence, colors: &PreviewColors, id: &str) -> String {
+    let count = sequence.participants.len();
+    let box_width: Vec<f64> = sequence
+        .participants
+        .iter()
+        .map(|p| (text_width(&p.label) + 24.0).max(80.0))
+        .collect();
+    let box_height = sequence
+        .participants
+        .iter()
+        .map(|p| text_height(&p.label) + 16.0)
+        .fold(36.0, f64::max);
+
+    // Separación entre columnas: cabe cada caja y el texto de cada mensaje
+    let mut gaps: Vec<f64> = (0..count.saturating_sub(1))
+        .map(|i| (box_width[i] + box_width[i + 1]) / 2.0 + 24.0)
+        .collect();
+    let mut right_extra: f64 = 0.0;
+    for step in &sequence.steps {
+        let (from, to, needed) = match step {
+            SequenceStep::Message { from, to, text, .. } if from == to => {
+                let needed = text_width(text) + 56.0;
+                match gaps.get_mut(*from) {
+                    Some(gap) => *gap = gap.max(needed),
+                    None => right_extra = right_extra.max(needed),
+                }
+                continue;
+            }
+            SequenceStep::Message { from, to, text, .. } => {
+                (*from.min(to), *from.max(to), text_width(text) + 32.0)
+            }
+            SequenceStep::Note { from, to, text, .. } => (*from, *to, text_width(text) + 24.0),
+        };
+        if from == to {
+            continue;
+        }
+        let current: f64 = gaps[from..to].iter().sum();
+        if current < needed {
+            gaps[to - 1] += needed - current;
+        }
+    }
+    let mut column = vec![MARGIN + box_width[0] / 2.0];
+    for gap in &gaps {
+        column.push(column.last().unwrap() + gap);
+    }
+
+    // Alto de cada paso
+    let step_heights: Vec<f64> = sequence
+        .steps
+        .iter()
+        .map(|step| match step {
+            SequenceStep::Message { from, to, text, .. } => {
+                text_height(text) + if from == to { 44.0 } else { 24.0 }
+            }
+            SequenceStep::Note { text, .. } => text_height(text) + 28.0,
+        })
+        .collect();
+    let body_top = MARGIN + box_height + 16.0;
+    let body_bottom = body_top + step_heights.iter().sum::<f64>() + 8.0;
+    let height = body_bottom + box_height + MARGIN;
+    let notes_extra = sequence
+        .steps
+        .iter()
+        .filter_map(|step| match step {
+            SequenceStep::Note {
+                side: NoteSide::Right,
+                from,
+                text,
+                ..
+            } if *from == count - 1 => Some(text_width(text) + 40.0),
+            _ => None,
+        })
+        .fold(0.0, f64::max);
+    let width = column[count - 1]
+        + (box_width[count - 1] / 2.0)
+            .max(right_extra)
+            .max(notes_extra)
+        + MARGIN;
+
+    let mut svg = String::new();
+    svg_open(&mut svg, width, height, colors, id);
+
+    // Líneas de vida y cajas arriba y abajo
+    for (i, participant) in sequence.participants.iter().enumerate() {
+        let x = column[i];
+        let _ = write!(
+            svg,
+            r#"<line x1="{x:.1}" y1="{:.1}" x2="{x:.1}" y2="{body_bottom:.1}" stroke="{}" stroke-width="1" stroke-dasharray="4 4"/>"#,
+            MARGIN + box_height,
+            colors.fg_muted
+        );
+        for top in [MARGIN, body_bottom] {
+            let _ = write!(
+                svg,
+                r#"<rect x="{:.1}" y="{top:.1}" width="{:.1}" height="{box_height:.1}" rx="4" fill="{}" stroke="{}" stroke-width="1.5"/>"#,
+                x - box_width[i] / 2.0,
+                box_width[i],
+                colors.bg_secondary,
+                colors.accent
+            );
+            svg_text(
+                &mut svg,
+                &participant.label,
+                x,
+                top + box_height / 2.0,
+                &colors.fg_primary,
+            );
+        }
+    }
+
+    let marker = format!(r#" marker-end="url(#nn-arrow-{})""#, id);
+    let mut y = body_top;
+    for (step, step_height) in sequence.steps.iter().zip(&step_heights) {
+        match step {
+            SequenceStep::Message {
+                from,
+                to,
+                text,
+                dashed,
+                arrow,
+            } => {
+                let text_bottom = y + text_height(text);
+                let dash = if *dashed {
+                    r#" stroke-dasharray="6 4""#
+                } else {
+                    ""
+                };
+                let head = if *arrow { marker.as_str() } else { "" };
+                let (x1, x2) = (column[*from], column[*to]);
+                if from == to {
+                    svg_text(
+                        &mut svg,
+                        text,
+                        x1 + 28.0 + text_width(text) / 2.0,
+                        y + 8.0,
+                        &colors.fg_primary,
+                    );
+                    let _ = write!(
+                        svg,
+                        r#"<path d="M{x1:.1},{:.1} h36 v20 h-34" fill="none" stroke="{}" stroke-width="1.5"{dash}{head}/>"#,
+                        text_bottom + 4.0,
+                        colors.fg_secondary
+                    );
+                } else {
+                    svg_text(
+                        &mut svg,
+                        text,
+                        (x1 + x2) / 2.0,
+                        y + text_height(text) / 2.0,
+                        &colors.fg_primary,
+                    );
+                    let line_y = text_bottom + 8.0;
+                    let end = if x2 > x1 { x2 - 2.0 } else { x2 + 2.0 };
+                    let _ = write!(
+                        svg,
+                        r#"<line x1="{x1:.1}" y1="{line_y:.1}" x2="{end:.1}" y2="{line_y:.1}" stroke="{}" stroke-width="1.5"{dash}{head}/>"#,
+                        colors.fg_secondary
+                    );
+                }
+            }
+            SequenceStep::Note {
+                from,
+                to,
+                side,
+                text,
+            } => {
+                let note_width = text_width(text) + 20.0;
+                let (left, right) = match side {
+                    NoteSide::Right => (column[*from] + 12.0, column[*from] + 12.0 + note_width),
+                    NoteSide::Left => (column[*from] - 12.0 - note_width, column[*from] - 12.0),
+                    NoteSide::Over => {
+                        let center = (column[*from] + column[*to]) / 2.0;
+                        let span = (column[*to] - column[*from] + 40.0).max(note_width);
+                        (center - span / 2.0, center + span / 2.0)
+                    }
+                };
+                let note_height = text_height(text) + 12.0;
+                let _ = write!(
+                    svg,
+                    r#"<rect x="{left:.1}" y="{:.1}" width="{:.1}" height="{note_height:.1}" rx="3" fill="{}" stroke="{}" stroke-width="1"/>"#,
+                    y + 4.0,
+                    right - left,
+                    colors.bg_primary,
+                    colors.border
+                );
+                svg_text(
+                    &mut svg,
+                    text,
+                    (left + right) / 2.0,
+                    y + 4.0 + note_height / 2.0,
+                    &colors.fg_primary,
+                );
+            }
+        }
+        y += step_height;
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flowchart() {
+        let source = "%% comentario\nflowchart LR\n  A[Inicio] -->|sí| B{¿Decidir?}\n  B -- no --> C((Fin)) & D\n  D -.-> A; A ==> E([Otro])\nclassDef x fill:#f00\n";
+        let Diagram::Graph(graph) = parse_mermaid(source).unwrap() else {
+            panic!("expected a graph");
+        };
+        assert_eq!(graph.direction, Direction::LeftRight);
+        let labels: Vec<&str> = graph.nodes.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(labels, ["Inicio", "¿Decidir?", "Fin", "D", "Otro"]);
+        assert_eq!(graph.nodes[1].shape, NodeShape::Diamond);
+        assert_eq!(graph.nodes[2].shape, NodeShape::Circle);
+        assert_eq!(graph.edges.len(), 5);
+        assert_eq!(graph.edges[0].label.as_deref(), Some("sí"));
+        assert_eq!(graph.edges[1].label.as_deref(), Some("no"));
+        assert_eq!((graph.edges[2].from, graph.edges[2].to), (1, 3));
+        assert_eq!(graph.edges[3].style, EdgeStyle::Dashed);
+        assert_eq!(graph.edges[4].style, EdgeStyle::Thick);
+        assert!(graph.edges.iter().all(|e| e.arrow_end));
+
+        let error = parse_mermaid("graph TD\nA --> B\nA -->\n").unwrap_err();
+        assert!(matches!(error, DiagramError::Syntax { line: 3, .. }));
+        assert_eq!(
+            parse_mermaid("gantt\ntitle x").unwrap_err(),
+            DiagramError::Unsupported("mermaid gantt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_sequence_and_pie() {
+        let source = "sequenceDiagram\nparticipant A as Alice\nA->>B: Hola\nB-->>A: Adiós\nNote over A,B: fin\nB->>B: pensar";
+        let Diagram::Sequence(sequence) = parse_mermaid(source).unwrap() else {
+            panic!("expected a sequence");
+        };
+        assert_eq!(sequence.participants[0].label, "Alice");
+        assert_eq!(sequence.participants[1].id, "B");
+        assert_eq!(sequence.steps.len(), 4);
+        assert!(matches!(
+            &sequence.steps[1],
+            SequenceStep::Message { from: 1, to: 0, dashed: true, arrow: true, text } if text == "Adiós"
+        ));
+        assert!(matches!(
+            &sequence.steps[2],
+            SequenceStep::Note {
+                from: 0,
+                to: 1,
+                side: NoteSide::Over,
+                ..
+            }
+        ));
+
+        let pie = parse_mermaid("pie title Mascotas\n\"Perros\" : 3\n\"Gatos\" : 2.5").unwrap();
+        assert_eq!(
+            pie,
+            Diagram::Pie {
+                title: Some("Mascotas".to_string()),
+                slices: vec![("Perros".to_string(), 3.0), ("Gatos".to_string(), 2.5)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_dot() {
+        let source = r#"
+            // Dependencias
+            digraph deps {
+                rankdir=LR;
+                node [shape=box];
+                a [label="Núcleo\nlib"];
+                a -> b -> c [label="usa", style=dashed];
+                subgraph cluster_x { d; e [shape=circle] }
+                c -> { d e };
+                b -> a [dir=none];
+            }
+        "#;
+        let graph = parse_dot(source).unwrap();
+        assert_eq!(graph.direction, Direction::LeftRight);
+        assert_eq!(graph.nodes[0].label, "Núcleo\nlib");
+        assert_eq!(graph.nodes[0].shape, NodeShape::Rect);
+        assert_eq!(graph.nodes[4].shape, NodeShape::Circle);
+        assert_eq!(graph.edges.len(), 5);
+        assert_eq!(graph.edges[1].label.as_deref(), Some("usa"));
+        assert_eq!(graph.edges[1].style, EdgeStyle::Dashed);
+        assert_eq!((graph.edges[3].from, graph.edges[3].to), (2, 4));
+        assert!(!graph.edges[4].arrow_end);
+
+        let undirected = parse_dot("graph { x -- y }").unwrap();
+        assert!(!undirected.edges[0].arrow_end);
+        assert_eq!(undirected.nodes[0].shape, NodeShape::Ellipse);
+
+        let error = parse_dot("digraph {\n a -> \n}").unwrap_err();
+        assert!(matches!(error, DiagramError::Syntax { line: 3, .. }));
+        assert!(parse_dot("flowchart { }").is_err());
+    }
+
+    #[test]
+    fn test_unsupported_and_malformed() {
+        let colors = PreviewColors::default();
+
+        // Los tipos fuera del subconjunto dan un error que dice cuáles hay
+        let error = render_diagram(
+            DiagramKind::Mermaid,
+            "classDiagram
+A <|-- B",
+            &colors,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error,
+            DiagramError::Unsupported("mermaid classDiagram".to_string())
+        );
+        assert!(error.to_string().contains("sequenceDiagram"));
+        assert_eq!(
+            parse_dot("neato { a }").unwrap_err(),
+            DiagramError::Unsupported("dot neato".to_string())
+        );
+
+        // Entradas mal formadas: error, nunca pánico
+        assert_eq!(
+            parse_mermaid("%% solo\n\n").unwrap_err(),
+            DiagramError::Empty
+        );
+        assert_eq!(parse_dot("  // nada").unwrap_err(), DiagramError::Empty);
+        assert!(matches!(
+            parse_mermaid("pie\n\"A\" : NaN"),
+            Err(DiagramError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse_mermaid("pie\n\"A\" : -3"),
+            Err(DiagramError::Syntax { line: 2, .. })
+        ));
+        for source in [
+            "digraph { a -> b [label=\"sin cerrar] }",
+            "digraph { a -> b",
+            "digraph { { { }",
+            "graph { a -- }",
+        ] {
+            assert!(
+                render_diagram(DiagramKind::Graphviz, source, &colors).is_err(),
+                "{source}"
+            );
+        }
+        for source in [
+            "graph TD\nA[sin cerrar --> B",
+            "flowchart LR\n-->",
+            "sequenceDiagram\n->>",
+        ] {
+            assert!(
+                render_diagram(DiagramKind::Mermaid, source, &colors).is_err(),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_layout_and_render() {
+        let graph = parse_dot("digraph { a -> b; b -> c; a -> c; c -> a }").unwrap();
+        let layout = layout_graph(&graph);
+        // Cada rango por debajo del anterior, aunque haya un ciclo
+        assert!(layout.nodes[0].y < layout.nodes[1].y);
+        assert!(layout.nodes[1].y < layout.nodes[2].y);
+        // a -> c salta una capa: pasa por un nodo ficticio
+        assert_eq!(layout.edges[2].len(), 3);
+        // Los extremos quedan en el borde de los nodos, no en el centro
+        let (start, a) = (layout.edges[0][0], layout.nodes[0]);
+        let on_ellipse = ((start.0 - a.x) / (a.width / 2.0)).powi(2)
+            + ((start.1 - a.y) / (a.height / 2.0)).powi(2);
+        assert!((on_ellipse - 1.0).abs() < 1e-6);
+        assert!(start.1 > a.y);
+
+        let colors = PreviewColors::default();
+        let svg = render_diagram(
+            DiagramKind::Graphviz,
+            "digraph { a -> b [label=\"x<y\"] }",
+            &colors,
+        )
+        .unwrap();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" class="nn-diagram""#));
+        assert!(svg.contains("marker-end"));
+        assert!(svg.contains("x&lt;y"));
+        assert!(svg.ends_with("</svg>"));
+
+        let sequence = render_diagram(
+            DiagramKind::Mermaid,
+            "sequenceDiagram\nA->>B: hola",
+            &colors,
+        )
+        .unwrap();
+        assert_eq!(sequence.matches("<rect").count(), 4);
+    }
+
+    #[test]
+    fn test_cache() {
+        let colors = PreviewColors::default();
+        let source = "graph TD\n  cache_a --> cache_b";
+        let key = content_hash(DiagramKind::Mermaid, source, &colors);
+        let first = render_diagram_cached(DiagramKind::Mermaid, source, &colors).unwrap();
+        assert!(SVG_CACHE.lock().unwrap().contains_key(&key));
+        assert_eq!(
+            render_diagram_cached(DiagramKind::Mermaid, source, &colors).unwrap(),
+            first
+        );
+
+        // Otro contenido u otros colores, otra entrada
+        let mut light = colors.clone();
+        light.bg_primary = "#ffffff".to_string();
+        assert_ne!(content_hash(DiagramKind::Mermaid, source, &light), key);
+        assert_ne!(content_hash(DiagramKind::Graphviz, source, &colors), key);
+
+        // Los errores también se cachean
+        let broken = "graph TD\n  A -->";
+        assert!(render_diagram_cached(DiagramKind::Mermaid, broken, &colors).is_err());
+        assert!(SVG_CACHE.lock().unwrap().contains_key(&content_hash(
+            DiagramKind::Mermaid,
+            broken,
+            &colors
+        )));
+    }
+}
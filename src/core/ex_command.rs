@@ -0,0 +1,1339 @@
+//! Lenguaje de comandos ex (modo Comando, tras pulsar `:`)
+//!
+//! ```text
+//! :%s/foo/bar/g        sustituir en todo el documento
+//! :'<,'>s/^/- /        sustituir en la selección visual
+//! :g/TODO/d            borrar las líneas que coinciden
+//! :10,20sort u         ordenar un rango quitando duplicados
+//! :e Nota              abrir una nota (Tab completa)
+//! :table row add       añadir una fila a la tabla bajo el cursor
+//! :template Reunión    insertar una plantilla en el cursor (Tab completa)
+//! :today  :weekly -1   abrir la nota periódica de hoy / de la semana pasada
+//! :next  :prev  :up    moverse entre notas periódicas y al periodo superior
+//! :copylink            copiar [[Nota#Encabezado]] o [[Nota#^bloque]] de la línea actual
+//! :export pdf ~/x.pdf  exportar la nota a HTML autocontenido o PDF
+//! :git sync  :history  commit, pull y push del vault / historial Git de la nota
+//! :mcp create_note {"name": "x", "content": ""}
+//! ```
+//!
+//! El parser produce un [`ExCommand`]; los comandos que editan texto (`s`, `g`,
+//! `d`, `sort`) se aplican con [`ExCommand::apply_to_text`] y el resto los
+//! ejecuta la aplicación.
+
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+use thiserror::Error;
+
+use super::export::ExportFormat;
+use super::periodic::{Period, parse_date};
+use super::table::{ColumnAlignment, TableEdit};
+use super::vault_git::GitCommand;
+
+/// Errores al interpretar o ejecutar un comando ex
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ExError {
+    #[error("Not an editor command: {0}")]
+    UnknownCommand(String),
+    #[error("Invalid range: {0}")]
+    InvalidRange(String),
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+    #[error("Pattern not found: {0}")]
+    PatternNotFound(String),
+    #[error("Argument required: {0}")]
+    MissingArgument(&'static str),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("Invalid JSON arguments: {0}")]
+    InvalidJson(String),
+    #[error("No visual selection")]
+    NoVisualSelection,
+}
+
+pub type Result<T> = std::result::Result<T, ExError>;
+
+/// Línea de referencia dentro de un rango
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Address {
+    /// Número de línea (1 = primera)
+    Line(usize),
+    /// `.` línea del cursor
+    Current,
+    /// `$` última línea
+    Last,
+    /// `'<` inicio de la selección visual
+    VisualStart,
+    /// `'>` fin de la selección visual
+    VisualEnd,
+}
+
+/// Dirección con desplazamiento opcional (`.+3`, `$-1`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineAddress {
+    pub address: Address,
+    pub offset: i64,
+}
+
+/// Rango de líneas de un comando
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineRange {
+    /// `%` todo el documento
+    All,
+    Single(LineAddress),
+    Span(LineAddress, LineAddress),
+}
+
+/// Estado del editor necesario para resolver direcciones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExContext {
+    /// Línea del cursor (empezando en 0)
+    pub cursor_line: usize,
+    /// Primera y última línea de la selección visual (empezando en 0)
+    pub visual: Option<(usize, usize)>,
+}
+
+/// Opciones de `:sort`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortOptions {
+    /// `:sort!` orden inverso
+    pub reverse: bool,
+    /// `i` ignorar mayúsculas
+    pub ignore_case: bool,
+    /// `n` ordenar por el primer número de la línea
+    pub numeric: bool,
+    /// `u` quitar líneas duplicadas
+    pub unique: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExCommandKind {
+    Write,
+    Quit,
+    WriteQuit,
+    ForceQuit,
+    /// `:/texto` buscar y resaltar en la nota
+    Search(String),
+    /// `:42` (solo un rango): ir a la línea
+    GotoLine,
+    /// `:s/patrón/reemplazo/flags`
+    Substitute {
+        pattern: String,
+        replacement: String,
+        global: bool,
+        ignore_case: bool,
+    },
+    /// `:g/patrón/cmd` (o `:g!`/`:v` para las líneas que no coinciden)
+    Global {
+        pattern: String,
+        invert: bool,
+        command: Box<ExCommand>,
+    },
+    /// `:d` borrar líneas
+    Delete,
+    Sort(SortOptions),
+    /// `:e Nota`
+    Edit(String),
+    /// `:move Carpeta` (vacío o `/` para la raíz)
+    Move(String),
+    /// `:tag add x` / `:tag remove x`
+    Tag {
+        add: bool,
+        tag: String,
+    },
+    /// `:noh` quitar el resaltado de búsqueda
+    NoHighlight,
+    /// `:base Nombre`
+    Base(String),
+    /// `:table row add`, `:table col delete`, `:table sort`... (tabla bajo el cursor)
+    Table(TableEdit),
+    /// `:template Nombre` insertar una plantilla en el cursor
+    Template(String),
+    /// `:today`, `:daily -1`, `:weekly 2025-03-07`... abrir una nota periódica
+    Periodic {
+        period: Period,
+        target: PeriodicTarget,
+    },
+    /// `:next`, `:prev 2`: periodo siguiente/anterior de la nota periódica abierta
+    PeriodicStep(i32),
+    /// `:up` nota del periodo superior (día → semana → mes → año)
+    PeriodicParent,
+    /// `:copylink` copiar un enlace al encabezado o bloque de la línea del cursor
+    CopyLink,
+    /// `:export html|pdf [ruta]` exportar la nota abierta
+    Export {
+        format: ExportFormat,
+        path: Option<String>,
+    },
+    /// `:git init|commit|log|pull|push|sync|remote|conflicts|abort`
+    Git(GitCommand),
+    /// `:mcp tool_name {json}`
+    Mcp {
+        tool: String,
+        args: Value,
+    },
+}
+
+/// Qué nota periódica abrir
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodicTarget {
+    /// Periodos respecto al actual (0 = hoy, esta semana...)
+    Offset(i32),
+    /// Periodo que contiene una fecha
+    Date(NaiveDate),
+}
+
+/// Comando ex ya interpretado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExCommand {
+    pub range: Option<LineRange>,
+    pub kind: ExCommandKind,
+}
+
+/// Resultado de aplicar un comando de edición al texto de la nota
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExEdit {
+    pub text: String,
+    /// Línea donde queda el cursor (empezando en 0)
+    pub cursor_line: usize,
+    /// Sustituciones, líneas borradas u ordenadas
+    pub changes: usize,
+}
+
+/// Nombres de comando que se completan con Tab
+pub const EX_COMMANDS: &[&str] = &[
+    "base",
+    "copylink",
+    "daily",
+    "delete",
+    "edit",
+    "export",
+    "git",
+    "global",
+    "history",
+    "mcp",
+    "monthly",
+    "move",
+    "next",
+    "noh",
+    "prev",
+    "quit",
+    "sort",
+    "substitute",
+    "table",
+    "tag",
+    "template",
+    "today",
+    "up",
+    "vglobal",
+    "weekly",
+    "wq",
+    "write",
+    "yearly",
+];
+
+/// Qué tipo de argumento se está escribiendo (para completar con Tab)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Command,
+    Note,
+    Folder,
+    Base,
+    Template,
+}
+
+impl ExCommand {
+    /// Interpretar una línea de comando (sin el `:` inicial)
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if let Some(search) = input.strip_prefix('/') {
+            return Ok(Self::new(None, ExCommandKind::Search(search.to_string())));
+        }
+
+        let (range, rest) = parse_range(input)?;
+        let rest = rest.trim_start();
+        if rest.is_empty() {
+            return match range {
+                Some(_) => Ok(Self::new(range, ExCommandKind::GotoLine)),
+                None => Err(ExError::UnknownCommand(String::new())),
+            };
+        }
+
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (name, args) = rest.split_at(name_len);
+        let bang = args.starts_with('!');
+        let args = if bang { &args[1..] } else { args };
+
+        let kind = match name {
+            "w" | "write" => ExCommandKind::Write,
+            "q" | "quit" if bang => ExCommandKind::ForceQuit,
+            "q" | "quit" => ExCommandKind::Quit,
+            "wq" | "x" => ExCommandKind::WriteQuit,
+            "s" | "substitute" => parse_substitute(args)?,
+            "g" | "global" => parse_global(args, bang)?,
+            "v" | "vglobal" => parse_global(args, true)?,
+            "d" | "delete" => ExCommandKind::Delete,
+            "sor" | "sort" => parse_sort(args, bang),
+            "e" | "edit" => ExCommandKind::Edit(required(args, "note")?),
+            "m" | "move" => ExCommandKind::Move(args.trim().to_string()),
+            "tag" => parse_tag(args)?,
+            "table" => parse_table(args)?,
+            "template" => ExCommandKind::Template(required(args, "template")?),
+            "today" => parse_periodic(Period::Daily, args)?,
+            "daily" | "weekly" | "monthly" | "yearly" => {
+                let period =
+                    Period::parse(name).map_err(|e| ExError::InvalidArgument(e.to_string()))?;
+                parse_periodic(period, args)?
+            }
+            "next" => ExCommandKind::PeriodicStep(parse_count(args)?),
+            "prev" | "previous" => ExCommandKind::PeriodicStep(-parse_count(args)?),
+            "up" => ExCommandKind::PeriodicParent,
+            "copylink" => ExCommandKind::CopyLink,
+            "export" => parse_export(args)?,
+            "git" => parse_git(args)?,
+            "history" => ExCommandKind::Git(GitCommand::Log),
+            "noh" | "nohlsearch" => ExCommandKind::NoHighlight,
+            "base" => ExCommandKind::Base(required(args, "base")?),
+            "mcp" => parse_mcp(args)?,
+            _ => return Err(ExError::UnknownCommand(rest.to_string())),
+        };
+
+        // Un rango en un comando que no lo usa (`:3w`) es un error, no se ignora
+        let command = Self::new(range, kind);
+        if command.range.is_some() && !command.edits_text() {
+            return Err(ExError::InvalidArgument(input.to_string()));
+        }
+        Ok(command)
+    }
+
+    fn new(range: Option<LineRange>, kind: ExCommandKind) -> Self {
+        Self { range, kind }
+    }
+
+    /// Si el comando modifica el texto de la nota abierta
+    pub fn edits_text(&self) -> bool {
+        matches!(
+            self.kind,
+            ExCommandKind::Substitute { .. }
+                | ExCommandKind::Global { .. }
+                | ExCommandKind::Delete
+                | ExCommandKind::Sort(_)
+        )
+    }
+
+    /// Aplicar un comando de edición al texto (None si el comando no edita texto)
+    pub fn apply_to_text(&self, text: &str, ctx: &ExContext) -> Result<Option<ExEdit>> {
+        if !self.edits_text() {
+            return Ok(None);
+        }
+
+        let trailing_newline = text.ends_with('\n');
+        let body = if trailing_newline {
+            &text[..text.len() - 1]
+        } else {
+            text
+        };
+        let mut lines: Vec<String> = body.split('\n').map(str::to_string).collect();
+
+        // Sin rango: `:g` y `:sort` usan todo el documento, el resto la línea actual
+        let default = match self.kind {
+            ExCommandKind::Global { .. } | ExCommandKind::Sort(_) => LineRange::All,
+            _ => LineRange::Single(LineAddress {
+                address: Address::Current,
+                offset: 0,
+            }),
+        };
+        let (first, last) = self.range.unwrap_or(default).resolve(ctx, lines.len())?;
+
+        let changes = self.apply_lines(&mut lines, first, last)?;
+        let cursor_line = first.min(lines.len().saturating_sub(1));
+
+        let mut text = lines.join("\n");
+        if trailing_newline && !text.is_empty() {
+            text.push('\n');
+        }
+        Ok(Some(ExEdit {
+            text,
+            cursor_line,
+            changes,
+        }))
+    }
+
+    fn apply_lines(&self, lines: &mut Vec<String>, first: usize, last: usize) -> Result<usize> {
+        match &self.kind {
+            ExCommandKind::Substitute {
+                pattern,
+                replacement,
+                global,
+                ignore_case,
+            } => {
+                let regex = build_regex(pattern, *ignore_case)?;
+                let replacement = vim_replacement(replacement);
+                let mut count = 0;
+                for line in &mut lines[first..=last] {
+                    let matches = regex.find_iter(line).count();
+                    if matches == 0 {
+                        continue;
+                    }
+                    let limit = if *global { 0 } else { 1 };
+                    *line = regex
+                        .replacen(line, limit, replacement.as_str())
+                        .into_owned();
+                    count += if *global { matches } else { 1 };
+                }
+                if count == 0 {
+                    return Err(ExError::PatternNotFound(pattern.clone()));
+                }
+                Ok(count)
+            }
+            ExCommandKind::Global {
+                pattern,
+                invert,
+                command,
+            } => {
+                let regex = build_regex(pattern, false)?;
+                let marked: Vec<usize> = (first..=last)
+                    .filter(|&i| regex.is_match(&lines[i]) != *invert)
+                    .collect();
+                if marked.is_empty() {
+                    return Err(ExError::PatternNotFound(pattern.clone()));
+                }
+                // De abajo arriba para que borrar no desplace las líneas pendientes
+                let mut count = 0;
+                for line in marked.into_iter().rev() {
+                    match command.apply_lines(lines, line, line) {
+                        Ok(n) => count += n,
+                        Err(ExError::PatternNotFound(_)) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(count)
+            }
+            ExCommandKind::Delete => {
+                lines.drain(first..=last);
+                if lines.is_empty() {
+                    lines.push(String::new());
+                }
+                Ok(last - first + 1)
+            }
+            ExCommandKind::Sort(options) => {
+                let mut sorted = lines[first..=last].to_vec();
+                sort_lines(&mut sorted, options);
+                let count = sorted.len();
+                lines.splice(first..=last, sorted);
+                Ok(count)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+impl LineRange {
+    /// Resolver el rango a líneas `(primera, última)` empezando en 0
+    pub fn resolve(&self, ctx: &ExContext, line_count: usize) -> Result<(usize, usize)> {
+        let last_line = line_count.saturating_sub(1);
+        let (first, last) = match self {
+            LineRange::All => return Ok((0, last_line)),
+            LineRange::Single(address) => {
+                let line = address.resolve(ctx, last_line)?;
+                (line, line)
+            }
+            LineRange::Span(from, to) => {
+                (from.resolve(ctx, last_line)?, to.resolve(ctx, last_line)?)
+            }
+        };
+        // Como vim, un rango al revés se invierte
+        Ok((first.min(last), first.max(last)))
+    }
+}
+
+impl LineAddress {
+    fn resolve(&self, ctx: &ExContext, last_line: usize) -> Result<usize> {
+        let base = match self.address {
+            Address::Line(n) => n.saturating_sub(1) as i64,
+            Address::Current => ctx.cursor_line as i64,
+            Address::Last => last_line as i64,
+            Address::VisualStart => ctx.visual.ok_or(ExError::NoVisualSelection)?.0 as i64,
+            Address::VisualEnd => ctx.visual.ok_or(ExError::NoVisualSelection)?.1 as i64,
+        };
+        match base.checked_add(self.offset) {
+            Some(line) if (0..=last_line as i64).contains(&line) => Ok(line as usize),
+            Some(line) => Err(ExError::InvalidRange(format!("{}", line.saturating_add(1)))),
+            None => Err(ExError::InvalidRange(format!("{:+}", self.offset))),
+        }
+    }
+}
+
+/// Separar el rango inicial del resto del comando
+fn parse_range(input: &str) -> Result<(Option<LineRange>, &str)> {
+    if let Some(rest) = input.strip_prefix('%') {
+        return Ok((Some(LineRange::All), rest));
+    }
+    let Some((from, rest)) = parse_address(input)? else {
+        return Ok((None, input));
+    };
+    if let Some(after_comma) = rest.strip_prefix(',') {
+        return match parse_address(after_comma)? {
+            Some((to, rest)) => Ok((Some(LineRange::Span(from, to)), rest)),
+            None => Err(ExError::InvalidRange(input.to_string())),
+        };
+    }
+    Ok((Some(LineRange::Single(from)), rest))
+}
+
+fn parse_address(input: &str) -> Result<Option<(LineAddress, &str)>> {
+    let digits = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (address, mut rest) = if digits > 0 {
+        let n = input[..digits]
+            .parse()
+            .map_err(|_| ExError::InvalidRange(input.to_string()))?;
+        (Address::Line(n), &input[digits..])
+    } else if let Some(rest) = input.strip_prefix('.') {
+        (Address::Current, rest)
+    } else if let Some(rest) = input.strip_prefix('$') {
+        (Address::Last, rest)
+    } else if let Some(rest) = input.strip_prefix("'<") {
+        (Address::VisualStart, rest)
+    } else if let Some(rest) = input.strip_prefix("'>") {
+        (Address::VisualEnd, rest)
+    } else if input.starts_with(['+', '-']) {
+        // `+3` equivale a `.+3`
+        (Address::Current, input)
+    } else {
+        return Ok(None);
+    };
+
+    let mut offset = 0i64;
+    while let Some(sign) = rest.chars().next().filter(|c| matches!(c, '+' | '-')) {
+        let after = &rest[1..];
+        let digits = after
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after.len());
+        let amount: i64 = if digits == 0 {
+            1
+        } else {
+            after[..digits]
+                .parse()
+                .map_err(|_| ExError::InvalidRange(input.to_string()))?
+        };
+        offset = if sign == '+' {
+            offset.checked_add(amount)
+        } else {
+            offset.checked_sub(amount)
+        }
+        .ok_or_else(|| ExError::InvalidRange(input.to_string()))?;
+        rest = &after[digits..];
+    }
+    Ok(Some((LineAddress { address, offset }, rest)))
+}
+
+/// Partir `/a/b/flags` por el delimitador (cualquier carácter no alfanumérico)
+fn split_delimited(args: &str, parts: usize) -> Result<(Vec<String>, &str)> {
+    let mut chars = args.char_indices();
+    let delimiter = match chars.next() {
+        Some((_, c)) if !c.is_alphanumeric() && !c.is_whitespace() && c != '\\' => c,
+        _ => return Err(ExError::MissingArgument("pattern")),
+    };
+
+    let mut fields = vec![String::new()];
+    let mut rest_start = args.len();
+    let mut escaped = false;
+    for (i, c) in chars {
+        if escaped {
+            // `\/` es el delimitador literal; el resto de escapes se conserva
+            if c != delimiter {
+                fields.last_mut().unwrap().push('\\');
+            }
+            fields.last_mut().unwrap().push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            if fields.len() == parts {
+                rest_start = i + c.len_utf8();
+                break;
+            }
+            fields.push(String::new());
+        } else {
+            fields.last_mut().unwrap().push(c);
+        }
+    }
+    if escaped {
+        fields.last_mut().unwrap().push('\\');
+    }
+    // El último delimitador es opcional (`:s/a/b`)
+    while fields.len() < parts {
+        fields.push(String::new());
+    }
+    let rest = if rest_start < args.len() {
+        &args[rest_start..]
+    } else {
+        ""
+    };
+    Ok((fields, rest))
+}
+
+fn parse_substitute(args: &str) -> Result<ExCommandKind> {
+    let (fields, flags) = split_delimited(args, 2)?;
+    let [pattern, replacement]: [String; 2] = fields
+        .try_into()
+        .map_err(|_| ExError::MissingArgument("pattern"))?;
+    if pattern.is_empty() {
+        return Err(ExError::MissingArgument("pattern"));
+    }
+    build_regex(&pattern, false)?;
+
+    let flags = flags.trim();
+    Ok(ExCommandKind::Substitute {
+        pattern,
+        replacement,
+        global: flags.contains('g'),
+        ignore_case: flags.contains('i'),
+    })
+}
+
+fn parse_global(args: &str, invert: bool) -> Result<ExCommandKind> {
+    let (fields, command) = split_delimited(args, 1)?;
+    let pattern = fields.into_iter().next().unwrap_or_default();
+    if pattern.is_empty() {
+        return Err(ExError::MissingArgument("pattern"));
+    }
+    build_regex(&pattern, false)?;
+
+    // Sin comando, `:g` borra (el `p` de vim no tiene sentido en el editor)
+    let parsed = if command.trim().is_empty() {
+        ExCommand::new(None, ExCommandKind::Delete)
+    } else {
+        ExCommand::parse(command)?
+    };
+    // Dentro de `:g` solo se admiten comandos que actúan sobre una línea
+    if !matches!(
+        parsed.kind,
+        ExCommandKind::Substitute { .. } | ExCommandKind::Delete
+    ) {
+        return Err(ExError::UnknownCommand(command.trim().to_string()));
+    }
+    Ok(ExCommandKind::Global {
+        pattern,
+        invert,
+        command: Box::new(parsed),
+    })
+}
+
+fn parse_sort(args: &str, reverse: bool) -> ExCommandKind {
+    let flags = args.trim();
+    ExCommandKind::Sort(SortOptions {
+        reverse,
+        ignore_case: flags.contains('i'),
+        numeric: flags.contains('n'),
+        unique: flags.contains('u'),
+    })
+}
+
+fn parse_tag(args: &str) -> Result<ExCommandKind> {
+    let mut words = args.split_whitespace();
+    let add = match words.next() {
+        Some("add" | "+") => true,
+        Some("remove" | "rm" | "-") => false,
+        Some(_) | None => return Err(ExError::MissingArgument("add|remove")),
+    };
+    let tag = words
+        .next()
+        .map(|t| t.trim_start_matches('#').to_string())
+        .filter(|t| !t.is_empty())
+        .ok_or(ExError::MissingArgument("tag"))?;
+    Ok(ExCommandKind::Tag { add, tag })
+}
+
+/// `:table [format]`, `:table row add|insert|delete|up|down`,
+/// `:table col add|insert|delete|left|right`, `:table sort [desc]`,
+/// `:table align left|center|right|none`
+fn parse_table(args: &str) -> Result<ExCommandKind> {
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let edit = match words.as_slice() {
+        [] | ["format" | "fmt"] => TableEdit::Format,
+        ["row", action] => match *action {
+            "add" | "below" => TableEdit::InsertRow { above: false },
+            "insert" | "above" => TableEdit::InsertRow { above: true },
+            "delete" | "del" => TableEdit::DeleteRow,
+            "up" => TableEdit::MoveRow { up: true },
+            "down" => TableEdit::MoveRow { up: false },
+            _ => return Err(ExError::InvalidArgument(args.trim().to_string())),
+        },
+        ["col" | "column", action] => match *action {
+            "add" | "after" => TableEdit::InsertColumn { before: false },
+            "insert" | "before" => TableEdit::InsertColumn { before: true },
+            "delete" | "del" => TableEdit::DeleteColumn,
+            "left" => TableEdit::MoveColumn { left: true },
+            "right" => TableEdit::MoveColumn { left: false },
+            _ => return Err(ExError::InvalidArgument(args.trim().to_string())),
+        },
+        ["sort"] | ["sort", "asc"] => TableEdit::Sort { descending: false },
+        ["sort", "desc"] => TableEdit::Sort { descending: true },
+        ["align", alignment] => TableEdit::Align(match *alignment {
+            "left" => ColumnAlignment::Left,
+            "center" => ColumnAlignment::Center,
+            "right" => ColumnAlignment::Right,
+            "none" => ColumnAlignment::None,
+            _ => return Err(ExError::InvalidArgument(args.trim().to_string())),
+        }),
+        ["row" | "col" | "column"] => return Err(ExError::MissingArgument("action")),
+        ["align"] => return Err(ExError::MissingArgument("left|center|right|none")),
+        _ => return Err(ExError::InvalidArgument(args.trim().to_string())),
+    };
+    Ok(ExCommandKind::Table(edit))
+}
+
+/// `html` o `pdf` y, opcionalmente, el fichero o carpeta de salida
+fn parse_export(args: &str) -> Result<ExCommandKind> {
+    let args = required(args, "format")?;
+    let (format, path) = match args.split_once(char::is_whitespace) {
+        Some((format, path)) => (format, Some(path.trim().to_string())),
+        None => (args.as_str(), None),
+    };
+    let format =
+        ExportFormat::parse(format).map_err(|e| ExError::InvalidArgument(e.to_string()))?;
+    Ok(ExCommandKind::Export { format, path })
+}
+
+/// Subcomando de `:git` y su argumento
+fn parse_git(args: &str) -> Result<ExCommandKind> {
+    let args = required(args, "subcommand")?;
+    let (name, rest) = match args.split_once(char::is_whitespace) {
+        Some((name, rest)) => (name, rest.trim()),
+        None => (args.as_str(), ""),
+    };
+    let command = match name {
+        "init" => GitCommand::Init,
+        "commit" => GitCommand::Commit(Some(rest.to_string()).filter(|m| !m.is_empty())),
+        "log" | "history" => GitCommand::Log,
+        "pull" => GitCommand::Pull,
+        "push" => GitCommand::Push,
+        "sync" => GitCommand::Sync,
+        "remote" => GitCommand::Remote(required(rest, "url")?),
+        "conflicts" => GitCommand::Conflicts,
+        "abort" => GitCommand::Abort,
+        other => return Err(ExError::InvalidArgument(other.to_string())),
+    };
+    Ok(ExCommandKind::Git(command))
+}
+
+fn parse_mcp(args: &str) -> Result<ExCommandKind> {
+    let args = args.trim();
+    let (tool, json) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    if tool.is_empty() {
+        return Err(ExError::MissingArgument("tool"));
+    }
+    let json = json.trim();
+    let args = if json.is_empty() {
+        Value::Object(Default::default())
+    } else {
+        serde_json::from_str(json).map_err(|e| ExError::InvalidJson(e.to_string()))?
+    };
+    Ok(ExCommandKind::Mcp {
+        tool: mcp_tool_name(tool),
+        args,
+    })
+}
+
+fn required(args: &str, what: &'static str) -> Result<String> {
+    let value = args.trim();
+    if value.is_empty() {
+        Err(ExError::MissingArgument(what))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// `:weekly`, `:weekly -1`, `:weekly +2`, `:weekly 2025-03-07`
+fn parse_periodic(period: Period, args: &str) -> Result<ExCommandKind> {
+    let args = args.trim();
+    let target = if args.is_empty() {
+        PeriodicTarget::Offset(0)
+    } else if let Ok(offset) = args.trim_start_matches('+').parse::<i32>() {
+        PeriodicTarget::Offset(offset)
+    } else {
+        let date = parse_date(args).map_err(|_| ExError::InvalidArgument(args.to_string()))?;
+        PeriodicTarget::Date(date)
+    };
+    Ok(ExCommandKind::Periodic { period, target })
+}
+
+/// Número de pasos de `:next`/`:prev` (1 si no se indica)
+fn parse_count(args: &str) -> Result<i32> {
+    let args = args.trim();
+    if args.is_empty() {
+        return Ok(1);
+    }
+    args.parse::<i32>()
+        .map_err(|_| ExError::InvalidArgument(args.to_string()))
+}
+
+/// Aceptar el nombre de la herramienta como en los esquemas (`create_note`) o como
+/// en el enum (`CreateNote`)
+pub fn mcp_tool_name(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn build_regex(pattern: &str, ignore_case: bool) -> Result<Regex> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|e| ExError::InvalidPattern(e.to_string()))
+}
+
+/// Convertir el reemplazo estilo vim (`&`, `\1`, `\n`) a la sintaxis de `regex`
+fn vim_replacement(replacement: &str) -> String {
+    let mut result = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => result.push_str("$$"),
+            '&' => result.push_str("${0}"),
+            '\\' => match chars.next() {
+                Some(d) if d.is_ascii_digit() => {
+                    result.push_str("${");
+                    result.push(d);
+                    result.push('}');
+                }
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('$') => result.push_str("$$"),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            },
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn sort_lines(lines: &mut Vec<String>, options: &SortOptions) {
+    if options.numeric {
+        // Como vim: las líneas sin número van primero, el resto por su valor
+        lines.sort_by_key(|line| first_number(line));
+    } else if options.ignore_case {
+        lines.sort_by_key(|line| line.to_lowercase());
+    } else {
+        lines.sort();
+    }
+    if options.unique {
+        lines.dedup_by(|a, b| {
+            if options.ignore_case {
+                a.eq_ignore_ascii_case(b)
+            } else {
+                a == b
+            }
+        });
+    }
+    if options.reverse {
+        lines.reverse();
+    }
+}
+
+fn first_number(line: &str) -> Option<i64> {
+    let start = line.find(|c: char| c.is_ascii_digit())?;
+    let negative = line[..start].ends_with('-');
+    let digits: String = line[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    let value: i64 = digits.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Qué se está completando y desde qué byte empieza el texto a completar
+pub fn completion_context(input: &str) -> Option<(CompletionKind, usize)> {
+    let (_, rest) = parse_range(input).ok()?;
+    let offset = input.len() - rest.len();
+    match rest.split_once(' ') {
+        None if rest.chars().all(|c| c.is_ascii_alphabetic()) => {
+            Some((CompletionKind::Command, offset))
+        }
+        None => None,
+        Some((name, args)) => {
+            let start = input.len() - args.len();
+            let start = start + (args.len() - args.trim_start().len());
+            let kind = match name {
+                "e" | "edit" => CompletionKind::Note,
+                "m" | "move" => CompletionKind::Folder,
+                "base" => CompletionKind::Base,
+                "template" => CompletionKind::Template,
+                _ => return None,
+            };
+            Some((kind, start))
+        }
+    }
+}
+
+/// Completar la línea de comando con los candidatos del tipo que corresponda
+///
+/// Devuelve las líneas completas posibles, primero las que empiezan por lo escrito
+/// y después las que lo contienen (sin distinguir mayúsculas).
+pub fn complete(input: &str, candidates: &[String]) -> Vec<String> {
+    let Some((kind, start)) = completion_context(input) else {
+        return Vec::new();
+    };
+    let prefix = input[start..].to_lowercase();
+    let head = &input[..start];
+
+    let candidates: Vec<&str> = if kind == CompletionKind::Command {
+        EX_COMMANDS.to_vec()
+    } else {
+        candidates.iter().map(String::as_str).collect()
+    };
+
+    let (mut starts, mut contains): (Vec<&str>, Vec<&str>) = candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().contains(&prefix))
+        .partition(|c| c.to_lowercase().starts_with(&prefix));
+    starts.sort_unstable();
+    contains.sort_unstable();
+
+    let separator = if kind == CompletionKind::Command {
+        " "
+    } else {
+        ""
+    };
+    starts
+        .into_iter()
+        .chain(contains)
+        .map(|c| format!("{head}{c}{separator}"))
+        .collect()
+}
+
+/// Historial de comandos navegable con ↑/↓
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    entries: Vec<String>,
+    /// Posición actual al navegar (None = línea nueva)
+    cursor: Option<usize>,
+    /// Lo que se estaba escribiendo antes de empezar a navegar
+    draft: String,
+}
+
+/// Máximo de comandos recordados
+const MAX_HISTORY: usize = 100;
+
+impl CommandHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Añadir un comando ejecutado (sin duplicar el último)
+    pub fn push(&mut self, command: &str) {
+        let command = command.trim();
+        self.cursor = None;
+        if command.is_empty() {
+            return;
+        }
+        self.entries.retain(|c| c != command);
+        self.entries.push(command.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+    }
+
+    /// ↑: comando anterior que empiece por lo escrito
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        if self.cursor.is_none() {
+            self.draft = current.to_string();
+        }
+        let end = self.cursor.unwrap_or(self.entries.len());
+        let index = self.entries[..end]
+            .iter()
+            .rposition(|c| c.starts_with(&self.draft))?;
+        self.cursor = Some(index);
+        Some(self.entries[index].clone())
+    }
+
+    /// ↓: comando siguiente, o lo que se estaba escribiendo al llegar al final
+    pub fn newer(&mut self) -> Option<String> {
+        let start = self.cursor? + 1;
+        match self.entries[start..]
+            .iter()
+            .position(|c| c.starts_with(&self.draft))
+        {
+            Some(offset) => {
+                self.cursor = Some(start + offset);
+                Some(self.entries[start + offset].clone())
+            }
+            None => {
+                self.cursor = None;
+                Some(self.draft.clone())
+            }
+        }
+    }
+
+    /// Dejar de navegar (al editar la línea o salir del modo Comando)
+    pub fn reset(&mut self) {
+        self.cursor = None;
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(command: &str, text: &str, ctx: ExContext) -> Result<ExEdit> {
+        ExCommand::parse(command)?
+            .apply_to_text(text, &ctx)
+            .map(|edit| edit.expect("comando de edición"))
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let cmd = ExCommand::parse("%s/a/b/g").unwrap();
+        assert_eq!(cmd.range, Some(LineRange::All));
+
+        let cmd = ExCommand::parse("'<,'>d").unwrap();
+        assert_eq!(
+            cmd.range,
+            Some(LineRange::Span(
+                LineAddress {
+                    address: Address::VisualStart,
+                    offset: 0
+                },
+                LineAddress {
+                    address: Address::VisualEnd,
+                    offset: 0
+                },
+            ))
+        );
+
+        let ctx = ExContext {
+            cursor_line: 4,
+            visual: None,
+        };
+        let range = ExCommand::parse(".,+2d").unwrap().range.unwrap();
+        assert_eq!(range.resolve(&ctx, 10), Ok((4, 6)));
+        let range = ExCommand::parse("3,$-1d").unwrap().range.unwrap();
+        assert_eq!(range.resolve(&ctx, 10), Ok((2, 8)));
+        let range = ExCommand::parse("'<d").unwrap().range.unwrap();
+        assert_eq!(range.resolve(&ctx, 10), Err(ExError::NoVisualSelection));
+        let range = ExCommand::parse("20d").unwrap().range.unwrap();
+        assert!(matches!(
+            range.resolve(&ctx, 10),
+            Err(ExError::InvalidRange(_))
+        ));
+
+        // Desplazamientos enormes dan error en vez de desbordar
+        assert!(matches!(
+            ExCommand::parse("+9223372036854775807+1d"),
+            Err(ExError::InvalidRange(_))
+        ));
+        let range = ExCommand::parse("$+9223372036854775807d")
+            .unwrap()
+            .range
+            .unwrap();
+        assert!(matches!(
+            range.resolve(&ctx, 10),
+            Err(ExError::InvalidRange(_))
+        ));
+
+        // Los comandos que no usan rango lo rechazan
+        assert!(matches!(
+            ExCommand::parse("3w"),
+            Err(ExError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            ExCommand::parse("%wq"),
+            Err(ExError::InvalidArgument(_))
+        ));
+
+        assert_eq!(
+            ExCommand::parse("42").unwrap().kind,
+            ExCommandKind::GotoLine
+        );
+        assert_eq!(
+            ExCommand::parse("q!").unwrap().kind,
+            ExCommandKind::ForceQuit
+        );
+        assert!(matches!(
+            ExCommand::parse("frobnicate"),
+            Err(ExError::UnknownCommand(_))
+        ));
+    }
+
+    #[test]
+    fn test_substitute() {
+        let ctx = ExContext::default();
+        let text = "foo bar foo\nfoo\nbaz\n";
+
+        let edit = run("s/foo/qux/", text, ctx).unwrap();
+        assert_eq!(edit.text, "qux bar foo\nfoo\nbaz\n");
+        assert_eq!(edit.changes, 1);
+
+        let edit = run("%s/foo/qux/g", text, ctx).unwrap();
+        assert_eq!(edit.text, "qux bar qux\nqux\nbaz\n");
+        assert_eq!(edit.changes, 3);
+
+        // Grupos, `&` y delimitador alternativo
+        let edit = run(r"%s#(\w+) (\w+)#\2 [&]#", "hola mundo\n", ctx).unwrap();
+        assert_eq!(edit.text, "mundo [hola mundo]\n");
+        let edit = run(r"s/\//-/g", "a/b/c", ctx).unwrap();
+        assert_eq!(edit.text, "a-b-c");
+        let edit = run("s/A/$/i", "a", ctx).unwrap();
+        assert_eq!(edit.text, "$");
+
+        let visual = ExContext {
+            cursor_line: 0,
+            visual: Some((1, 2)),
+        };
+        let edit = run("'<,'>s/^/- /", "t\nuno\ndos\n", visual).unwrap();
+        assert_eq!(edit.text, "t\n- uno\n- dos\n");
+
+        assert_eq!(
+            run("s/zzz/y/", text, ctx),
+            Err(ExError::PatternNotFound("zzz".into()))
+        );
+        assert!(matches!(
+            ExCommand::parse("s/(/x/"),
+            Err(ExError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_global_delete_and_sort() {
+        let ctx = ExContext::default();
+        let text = "- [ ] a\n- [x] b\n- [ ] c\n- [x] d\n";
+
+        let edit = run(r"g/\[x\]/d", text, ctx).unwrap();
+        assert_eq!(edit.text, "- [ ] a\n- [ ] c\n");
+        assert_eq!(edit.changes, 2);
+
+        let edit = run(r"v/\[x\]/s/\[ \]/[x]/", text, ctx).unwrap();
+        assert_eq!(edit.text, "- [x] a\n- [x] b\n- [x] c\n- [x] d\n");
+
+        let edit = run("2,3d", "1\n2\n3\n4", ctx).unwrap();
+        assert_eq!(edit.text, "1\n4");
+        assert_eq!(edit.cursor_line, 1);
+
+        let edit = run("sort", "c\nB\na\nc\n", ctx).unwrap();
+        assert_eq!(edit.text, "B\na\nc\nc\n");
+        let edit = run("sort iu", "c\nB\na\nC\n", ctx).unwrap();
+        assert_eq!(edit.text, "a\nB\nc\n");
+        let edit = run("sort! n", "x10\nx9\nx100\n", ctx).unwrap();
+        assert_eq!(edit.text, "x100\nx10\nx9\n");
+        let edit = run("2,$sort", "# Título\nz\ny\n", ctx).unwrap();
+        assert_eq!(edit.text, "# Título\ny\nz\n");
+
+        // Los comandos que no editan texto no cambian nada
+        let cmd = ExCommand::parse("noh").unwrap();
+        assert_eq!(cmd.apply_to_text(text, &ctx), Ok(None));
+    }
+
+    #[test]
+    fn test_app_commands() {
+        assert_eq!(
+            ExCommand::parse("e Proyectos/Plan").unwrap().kind,
+            ExCommandKind::Edit("Proyectos/Plan".into())
+        );
+        assert_eq!(
+            ExCommand::parse("e").unwrap_err(),
+            ExError::MissingArgument("note")
+        );
+        assert_eq!(
+            ExCommand::parse("move Archivo").unwrap().kind,
+            ExCommandKind::Move("Archivo".into())
+        );
+        assert_eq!(
+            ExCommand::parse("tag add #urgente").unwrap().kind,
+            ExCommandKind::Tag {
+                add: true,
+                tag: "urgente".into()
+            }
+        );
+        assert_eq!(
+            ExCommand::parse("base Tareas activas").unwrap().kind,
+            ExCommandKind::Base("Tareas activas".into())
+        );
+        assert_eq!(
+            ExCommand::parse("template Reunión semanal").unwrap().kind,
+            ExCommandKind::Template("Reunión semanal".into())
+        );
+        assert_eq!(
+            ExCommand::parse("template").unwrap_err(),
+            ExError::MissingArgument("template")
+        );
+        assert_eq!(
+            ExCommand::parse("today").unwrap().kind,
+            ExCommandKind::Periodic {
+                period: Period::Daily,
+                target: PeriodicTarget::Offset(0)
+            }
+        );
+        assert_eq!(
+            ExCommand::parse("weekly -1").unwrap().kind,
+            ExCommandKind::Periodic {
+                period: Period::Weekly,
+                target: PeriodicTarget::Offset(-1)
+            }
+        );
+        assert_eq!(
+            ExCommand::parse("monthly 2025-03-07").unwrap().kind,
+            ExCommandKind::Periodic {
+                period: Period::Monthly,
+                target: PeriodicTarget::Date(NaiveDate::from_ymd_opt(2025, 3, 7).unwrap())
+            }
+        );
+        assert!(matches!(
+            ExCommand::parse("daily ayer"),
+            Err(ExError::InvalidArgument(_))
+        ));
+        assert_eq!(
+            ExCommand::parse("prev 2").unwrap().kind,
+            ExCommandKind::PeriodicStep(-2)
+        );
+        assert_eq!(
+            ExCommand::parse("next").unwrap().kind,
+            ExCommandKind::PeriodicStep(1)
+        );
+        assert_eq!(
+            ExCommand::parse("up").unwrap().kind,
+            ExCommandKind::PeriodicParent
+        );
+        assert_eq!(
+            ExCommand::parse("copylink").unwrap().kind,
+            ExCommandKind::CopyLink
+        );
+        assert_eq!(
+            ExCommand::parse("export pdf ~/Informes/plan.pdf")
+                .unwrap()
+                .kind,
+            ExCommandKind::Export {
+                format: ExportFormat::Pdf,
+                path: Some("~/Informes/plan.pdf".into())
+            }
+        );
+        assert!(ExCommand::parse("export docx").is_err());
+        assert_eq!(
+            ExCommand::parse("git commit Repaso semanal").unwrap().kind,
+            ExCommandKind::Git(GitCommand::Commit(Some("Repaso semanal".into())))
+        );
+        assert_eq!(
+            ExCommand::parse("git remote ~/backup.git").unwrap().kind,
+            ExCommandKind::Git(GitCommand::Remote("~/backup.git".into()))
+        );
+        assert_eq!(
+            ExCommand::parse("history").unwrap().kind,
+            ExCommandKind::Git(GitCommand::Log)
+        );
+        assert!(ExCommand::parse("git remote").is_err());
+        assert!(ExCommand::parse("git rebase").is_err());
+        assert_eq!(
+            ExCommand::parse("/buscar esto").unwrap().kind,
+            ExCommandKind::Search("buscar esto".into())
+        );
+
+        let cmd = ExCommand::parse(r#"mcp create_note {"name": "x", "content": "y"}"#).unwrap();
+        assert_eq!(
+            cmd.kind,
+            ExCommandKind::Mcp {
+                tool: "CreateNote".into(),
+                args: serde_json::json!({"name": "x", "content": "y"}),
+            }
+        );
+        assert_eq!(
+            ExCommand::parse("mcp ListNotes").unwrap().kind,
+            ExCommandKind::Mcp {
+                tool: "ListNotes".into(),
+                args: serde_json::json!({}),
+            }
+        );
+        assert!(matches!(
+            ExCommand::parse("mcp read_note {name"),
+            Err(ExError::InvalidJson(_))
+        ));
+
+        assert_eq!(
+            ExCommand::parse("table").unwrap().kind,
+            ExCommandKind::Table(TableEdit::Format)
+        );
+        assert_eq!(
+            ExCommand::parse("table row insert").unwrap().kind,
+            ExCommandKind::Table(TableEdit::InsertRow { above: true })
+        );
+        assert_eq!(
+            ExCommand::parse("table col right").unwrap().kind,
+            ExCommandKind::Table(TableEdit::MoveColumn { left: false })
+        );
+        assert_eq!(
+            ExCommand::parse("table sort desc").unwrap().kind,
+            ExCommandKind::Table(TableEdit::Sort { descending: true })
+        );
+        assert_eq!(
+            ExCommand::parse("table align center").unwrap().kind,
+            ExCommandKind::Table(TableEdit::Align(ColumnAlignment::Center))
+        );
+        assert_eq!(
+            ExCommand::parse("table row").unwrap_err(),
+            ExError::MissingArgument("action")
+        );
+        assert!(matches!(
+            ExCommand::parse("table col sideways"),
+            Err(ExError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_completion() {
+        let notes = vec![
+            "Diario".to_string(),
+            "Proyectos/Plan".to_string(),
+            "Plantillas".to_string(),
+        ];
+        assert_eq!(
+            complete("e pla", &notes),
+            vec!["e Plantillas".to_string(), "e Proyectos/Plan".to_string()]
+        );
+        assert_eq!(complete("so", &notes), vec!["sort ".to_string()]);
+        assert_eq!(
+            completion_context("move Ar"),
+            Some((CompletionKind::Folder, 5))
+        );
+        assert_eq!(
+            completion_context("template Re"),
+            Some((CompletionKind::Template, 9))
+        );
+        assert_eq!(completion_context("%s/a/b"), None);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = CommandHistory::new();
+        history.push("w");
+        history.push("%s/a/b/g");
+        history.push("e Diario");
+        history.push("w");
+
+        assert_eq!(history.older(""), Some("w".into()));
+        assert_eq!(history.older(""), Some("e Diario".into()));
+        assert_eq!(history.newer(), Some("w".into()));
+        assert_eq!(history.newer(), Some(String::new()));
+        assert_eq!(history.newer(), None);
+
+        // Con texto escrito, solo los comandos que empiezan igual
+        history.reset();
+        assert_eq!(history.older("%"), Some("%s/a/b/g".into()));
+        assert_eq!(history.older("%"), None);
+        assert_eq!(history.entries().len(), 3);
+    }
+}
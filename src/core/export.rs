@@ -0,0 +1,940 @@
+//! Exportación de notas a HTML autocontenido y PDF
+//!
+//! El HTML sale del mismo `HtmlRenderer::render` que el preview, con el tema
+//! `PreviewTheme::Print`: CSS en línea, fórmulas y diagramas ya renderizados,
+//! imágenes locales incrustadas como data URI, enlaces internos reescritos a los
+//! `.html` exportados y sin JavaScript. El PDF se genera sin ventana a partir de ese
+//! HTML con el primer motor disponible (WeasyPrint, Chromium headless o wkhtmltopdf);
+//! si la nota tiene fórmulas se prefiere Chromium, el único que dibuja MathML.
+
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use thiserror::Error;
+
+use super::database::NotesDatabase;
+use super::html_renderer::{HtmlRenderer, PreviewTheme, escape_html, url_decode};
+use super::wikilink::heading_id;
+
+/// Regex para imágenes locales que el renderer ya ha convertido a file://
+pub(crate) static FILE_IMG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<img src="file://([^"]+)""#).unwrap());
+
+/// Regex para los enlaces internos clicables del preview
+static INTERNAL_ANCHOR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r##"<a href="#" class="internal-link" data-note="([^"]*)" onclick="[^"]*">"##)
+        .unwrap()
+});
+
+/// Regex para los enlaces de tags clicables del preview
+pub(crate) static TAG_ANCHOR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r##"<a href="#" class="tag-link" data-tag="([^"]*)" onclick="[^"]*">"##).unwrap()
+});
+
+/// Regex para los checkboxes interactivos de tareas
+static TODO_ONCLICK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#" onclick="handleTodoClick\([^"]*\)""#).unwrap());
+
+/// Regex para encabezados sin atributos (se les añade un id para los enlaces #Encabezado)
+static HEADING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<h([1-6])>(.*?)</h[1-6]>").unwrap());
+
+/// Regex para etiquetas HTML (texto plano de un encabezado)
+pub(crate) static TAG_STRIP_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]+>").unwrap());
+
+/// Motores de PDF, por orden de preferencia (WeasyPrint soporta todo `@page`)
+const PDF_ENGINES: [(&str, PdfEngineKind); 6] = [
+    ("weasyprint", PdfEngineKind::WeasyPrint),
+    ("chromium", PdfEngineKind::Chromium),
+    ("chromium-browser", PdfEngineKind::Chromium),
+    ("google-chrome", PdfEngineKind::Chromium),
+    ("google-chrome-stable", PdfEngineKind::Chromium),
+    ("wkhtmltopdf", PdfEngineKind::Wkhtmltopdf),
+];
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Unknown export format: {0} (expected html or pdf)")]
+    UnknownFormat(String),
+    #[error("Invalid page size: {0}")]
+    InvalidPageSize(String),
+    #[error("No PDF engine found: install weasyprint, chromium or wkhtmltopdf")]
+    NoPdfEngine,
+    #[error("PDF engine {engine} failed: {message}")]
+    PdfEngine { engine: String, message: String },
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Formato de salida
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Html,
+    Pdf,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Result<Self, ExportError> {
+        match s.trim().to_lowercase().as_str() {
+            "html" | "htm" => Ok(Self::Html),
+            "pdf" => Ok(Self::Pdf),
+            other => Err(ExportError::UnknownFormat(other.to_string())),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+// ============================================================================
+// PÁGINA
+// ============================================================================
+
+/// Tamaño de página del PDF
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PageSize {
+    #[default]
+    A4,
+    A3,
+    A5,
+    Letter,
+    Legal,
+    /// Ancho × alto en milímetros
+    Custom {
+        width_mm: f64,
+        height_mm: f64,
+    },
+}
+
+impl PageSize {
+    /// `A4`, `letter`, `210x297` o `210x297mm`
+    pub fn parse(s: &str) -> Result<Self, ExportError> {
+        let value = s.trim().to_lowercase();
+        match value.as_str() {
+            "a4" => return Ok(Self::A4),
+            "a3" => return Ok(Self::A3),
+            "a5" => return Ok(Self::A5),
+            "letter" | "carta" => return Ok(Self::Letter),
+            "legal" => return Ok(Self::Legal),
+            _ => {}
+        }
+        value
+            .trim_end_matches("mm")
+            .split_once(['x', '×'])
+            .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+            .filter(|&(w, h): &(f64, f64)| w > 0.0 && h > 0.0)
+            .map(|(width_mm, height_mm)| Self::Custom {
+                width_mm,
+                height_mm,
+            })
+            .ok_or_else(|| ExportError::InvalidPageSize(s.to_string()))
+    }
+
+    /// Ancho y alto en vertical, en milímetros
+    pub fn dimensions_mm(&self) -> (f64, f64) {
+        match *self {
+            Self::A4 => (210.0, 297.0),
+            Self::A3 => (297.0, 420.0),
+            Self::A5 => (148.0, 210.0),
+            Self::Letter => (215.9, 279.4),
+            Self::Legal => (215.9, 355.6),
+            Self::Custom {
+                width_mm,
+                height_mm,
+            } => (width_mm, height_mm),
+        }
+    }
+
+    /// Nombre que entiende wkhtmltopdf (None = tamaño personalizado)
+    fn wkhtmltopdf_name(&self) -> Option<&'static str> {
+        match self {
+            Self::A4 => Some("A4"),
+            Self::A3 => Some("A3"),
+            Self::A5 => Some("A5"),
+            Self::Letter => Some("Letter"),
+            Self::Legal => Some("Legal"),
+            Self::Custom { .. } => None,
+        }
+    }
+}
+
+/// Márgenes de página en milímetros
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Margins {
+    pub fn uniform(mm: f64) -> Self {
+        Self {
+            top: mm,
+            right: mm,
+            bottom: mm,
+            left: mm,
+        }
+    }
+}
+
+impl Default for Margins {
+    fn default() -> Self {
+        Self::uniform(20.0)
+    }
+}
+
+/// Configuración de página: tamaño, márgenes, cabecera y pie
+///
+/// Cabecera y pie admiten `{title}`, `{date}`, `{page}` y `{pages}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageSetup {
+    pub size: PageSize,
+    pub landscape: bool,
+    pub margins: Margins,
+    pub header: Option<String>,
+    pub footer: Option<String>,
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        Self {
+            size: PageSize::default(),
+            landscape: false,
+            margins: Margins::default(),
+            header: None,
+            footer: Some("{page} / {pages}".to_string()),
+        }
+    }
+}
+
+impl PageSetup {
+    pub fn with_size(mut self, size: PageSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_landscape(mut self, landscape: bool) -> Self {
+        self.landscape = landscape;
+        self
+    }
+
+    pub fn with_margins(mut self, margins: Margins) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    pub fn with_header(mut self, header: Option<String>) -> Self {
+        self.header = header.filter(|h| !h.trim().is_empty());
+        self
+    }
+
+    pub fn with_footer(mut self, footer: Option<String>) -> Self {
+        self.footer = footer.filter(|f| !f.trim().is_empty());
+        self
+    }
+
+    /// Ancho y alto reales (girados si la página es apaisada)
+    fn dimensions_mm(&self) -> (f64, f64) {
+        let (width, height) = self.size.dimensions_mm();
+        if self.landscape {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Regla `@page` con tamaño, márgenes y cabecera/pie como cajas de margen
+    fn css(&self, title: &str, date: &str) -> String {
+        let (width, height) = self.dimensions_mm();
+        let Margins {
+            top,
+            right,
+            bottom,
+            left,
+        } = self.margins;
+        let mut css = format!(
+            "@page {{\n    size: {width}mm {height}mm;\n    margin: {top}mm {right}mm {bottom}mm {left}mm;\n"
+        );
+        for (template, position) in [
+            (&self.header, "top-center"),
+            (&self.footer, "bottom-center"),
+        ] {
+            if let Some(template) = template {
+                css.push_str(&format!(
+                    "    @{position} {{\n        content: {};\n        font-size: 9pt;\n        color: #80838f;\n    }}\n",
+                    css_content(template, title, date)
+                ));
+            }
+        }
+        css.push_str("}\n");
+        css
+    }
+}
+
+/// Valor de `content` para una cabecera/pie: textos entre comillas y contadores
+fn css_content(template: &str, title: &str, date: &str) -> String {
+    let text = template.replace("{title}", title).replace("{date}", date);
+    let mut parts = Vec::new();
+    let mut rest = text.as_str();
+    while !rest.is_empty() {
+        let next = [("{page}", "counter(page)"), ("{pages}", "counter(pages)")]
+            .into_iter()
+            .filter_map(|(placeholder, counter)| {
+                rest.find(placeholder).map(|i| (i, placeholder, counter))
+            })
+            .min_by_key(|(i, ..)| *i);
+        let Some((index, placeholder, counter)) = next else {
+            parts.push(css_string(rest));
+            break;
+        };
+        if index > 0 {
+            parts.push(css_string(&rest[..index]));
+        }
+        parts.push(counter.to_string());
+        rest = &rest[index + placeholder.len()..];
+    }
+    if parts.is_empty() {
+        "\"\"".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn css_string(text: &str) -> String {
+    format!(
+        "\"{}\"",
+        text.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', " ")
+    )
+}
+
+// ============================================================================
+// HTML
+// ============================================================================
+
+/// Nota a exportar: nombre con carpeta (`Proyectos/Plan`) y su markdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportNote {
+    pub name: String,
+    pub markdown: String,
+}
+
+impl ExportNote {
+    pub fn new(name: impl Into<String>, markdown: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            markdown: markdown.into(),
+        }
+    }
+
+    /// Título visible (sin carpeta)
+    pub fn title(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+
+    /// Ruta del fichero exportado, relativa a la carpeta de salida
+    pub fn relative_path(&self, format: ExportFormat) -> PathBuf {
+        PathBuf::from(format!("{}.{}", self.name, format.extension()))
+    }
+}
+
+/// Exportador de notas a HTML autocontenido y PDF
+pub struct NoteExporter {
+    notes_root: PathBuf,
+    page: PageSetup,
+    notes_db: Option<NotesDatabase>,
+}
+
+impl NoteExporter {
+    pub fn new(notes_root: impl Into<PathBuf>) -> Self {
+        Self {
+            notes_root: notes_root.into(),
+            page: PageSetup::default(),
+            notes_db: None,
+        }
+    }
+
+    pub fn with_page(mut self, page: PageSetup) -> Self {
+        self.page = page;
+        self
+    }
+
+    /// BD para los bloques ```chart, ```base y ```query
+    pub fn with_database(mut self, db: NotesDatabase) -> Self {
+        self.notes_db = Some(db);
+        self
+    }
+
+    pub fn page(&self) -> &PageSetup {
+        &self.page
+    }
+
+    /// HTML autocontenido de una nota. Los enlaces a notas de `exported` (el resto del
+    /// lote) apuntan a su `.html`; los demás quedan como texto.
+    pub fn note_html(&self, note: &ExportNote, exported: &[String]) -> String {
+        let mut renderer =
+            HtmlRenderer::with_base_path(PreviewTheme::Print, self.notes_root.clone());
+        if let Some(db) = &self.notes_db {
+            renderer.set_database(db.clone_connection());
+        }
+        let html = renderer.render(&note.markdown);
+
+        let title = escape_html(note.title());
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let head = format!(
+            "<title>{}</title>\n    <style>\n{}    </style>\n</head>",
+            title,
+            self.page.css(note.title(), &date)
+        );
+        let html = html.replacen("</head>", &head, 1);
+
+        let html = inline_images(&html);
+        let html = rewrite_links(&html, &note.name, exported);
+        add_heading_ids(&html)
+    }
+
+    /// Exportar una nota a `output` (fichero) en el formato indicado
+    pub fn export_note(
+        &self,
+        note: &ExportNote,
+        format: ExportFormat,
+        output: &Path,
+    ) -> Result<PathBuf, ExportError> {
+        let html = self.note_html(note, &[]);
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        match format {
+            ExportFormat::Html => std::fs::write(output, html)?,
+            ExportFormat::Pdf => {
+                html_to_pdf(&html, output, &self.page, note.title())?;
+            }
+        }
+        Ok(output.to_path_buf())
+    }
+
+    /// Exportar varias notas (una carpeta) a `output_dir`, conservando las subcarpetas.
+    /// En HTML los enlaces entre ellas siguen funcionando y se añade un `index.html`.
+    pub fn export_notes(
+        &self,
+        notes: &[ExportNote],
+        format: ExportFormat,
+        output_dir: &Path,
+    ) -> Result<Vec<PathBuf>, ExportError> {
+        let names: Vec<String> = notes.iter().map(|note| note.name.clone()).collect();
+        let mut written = Vec::with_capacity(notes.len() + 1);
+        for note in notes {
+            let output = output_dir.join(note.relative_path(format));
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let html = self.note_html(note, &names);
+            match format {
+                ExportFormat::Html => std::fs::write(&output, html)?,
+                ExportFormat::Pdf => {
+                    html_to_pdf(&html, &output, &self.page, note.title())?;
+                }
+            }
+            written.push(output);
+        }
+
+        if format == ExportFormat::Html && notes.len() > 1 {
+            let index = output_dir.join("index.html");
+            std::fs::write(&index, self.index_html(notes))?;
+            written.push(index);
+        }
+        Ok(written)
+    }
+
+    /// Índice de un lote exportado, con el mismo estilo que las notas
+    fn index_html(&self, notes: &[ExportNote]) -> String {
+        let title = self
+            .notes_root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Notes".to_string());
+        let mut names: Vec<&str> = notes.iter().map(|note| note.name.as_str()).collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        let list: String = names
+            .iter()
+            .map(|name| format!("- [[{}]]\n", name))
+            .collect();
+        let index = ExportNote::new("index", format!("# {}\n\n{}", title, list));
+        let all: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        self.note_html(&index, &all).replacen(
+            "<title>index</title>",
+            &format!("<title>{}</title>", escape_html(&title)),
+            1,
+        )
+    }
+}
+
+/// Imágenes locales como data URI (las remotas se dejan como están)
+fn inline_images(html: &str) -> String {
+    FILE_IMG_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let raw = &caps[1];
+            let path = [PathBuf::from(raw), PathBuf::from(url_decode(raw))]
+                .into_iter()
+                .find(|path| path.is_file());
+            match path.and_then(|path| Some((mime_type(&path), std::fs::read(&path).ok()?))) {
+                Some((mime, bytes)) => {
+                    format!(
+                        r#"<img src="data:{};base64,{}""#,
+                        mime,
+                        base64_encode(&bytes)
+                    )
+                }
+                None => caps[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+pub(crate) fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "avif" => "image/avif",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Base64 estándar (RFC 4648) con relleno
+pub(crate) fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodificar base64 estándar; ignora espacios y saltos de línea y devuelve
+/// `None` si hay caracteres fuera del alfabeto
+pub(crate) fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in text.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            b' ' | b'\t' | b'\r' | b'\n' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+/// Enlaces internos a `.html` relativos (o a texto si la nota no se exporta),
+/// tags como texto y checkboxes de solo lectura
+pub(crate) fn rewrite_links(html: &str, current: &str, exported: &[String]) -> String {
+    let depth = current.matches('/').count();
+    let html = INTERNAL_ANCHOR_RE.replace_all(html, |caps: &regex::Captures| {
+        let destination = url_decode(&caps[1]);
+        let (note, anchor) = match destination.split_once('#') {
+            Some((note, anchor)) => (note, Some(anchor)),
+            None => (destination.as_str(), None),
+        };
+        let fragment = anchor.map(|anchor| match anchor.strip_prefix('^') {
+            Some(block) => format!("#block-{}", block),
+            None => format!("#{}", heading_id(anchor)),
+        });
+
+        let target = if note.is_empty() {
+            Some(String::new())
+        } else {
+            resolve_exported(note, exported)
+                .map(|name| format!("{}{}.html", "../".repeat(depth), href_encode(name)))
+        };
+        match target {
+            Some(target) => format!(
+                r#"<a href="{}{}" class="internal-link">"#,
+                target,
+                fragment.unwrap_or_default()
+            ),
+            None => r#"<a class="internal-link unresolved">"#.to_string(),
+        }
+    });
+    let html = TAG_ANCHOR_RE.replace_all(&html, r#"<a class="tag-link">"#);
+    TODO_ONCLICK_RE.replace_all(&html, " disabled").to_string()
+}
+
+/// Nota del lote a la que apunta un enlace: por nombre completo o, si no, por nombre base
+pub(crate) fn resolve_exported<'a>(note: &str, exported: &'a [String]) -> Option<&'a str> {
+    let base = |name: &str| name.rsplit('/').next().unwrap_or(name).to_lowercase();
+    exported
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(note))
+        .or_else(|| exported.iter().find(|name| base(name) == base(note)))
+        .map(String::as_str)
+}
+
+pub(crate) fn href_encode(name: &str) -> String {
+    name.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('#', "%23")
+        .replace('?', "%3F")
+        .replace('"', "%22")
+}
+
+/// `id` en los encabezados para que funcionen los enlaces `[[Nota#Encabezado]]`
+pub(crate) fn add_heading_ids(html: &str) -> String {
+    let mut seen = HashSet::new();
+    HEADING_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let text = TAG_STRIP_RE.replace_all(&caps[2], "");
+            let base = heading_id(&url_decode(&text.replace("&amp;", "&")));
+            let mut id = base.clone();
+            let mut n = 1;
+            while !seen.insert(id.clone()) {
+                id = format!("{}-{}", base, n);
+                n += 1;
+            }
+            format!(
+                r#"<h{level} id="{id}">{}</h{level}>"#,
+                &caps[2],
+                level = &caps[1]
+            )
+        })
+        .to_string()
+}
+
+// ============================================================================
+// PDF
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfEngineKind {
+    WeasyPrint,
+    Chromium,
+    Wkhtmltopdf,
+}
+
+/// Programa externo que convierte el HTML exportado a PDF sin abrir ventanas
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfEngine {
+    pub kind: PdfEngineKind,
+    pub program: PathBuf,
+}
+
+impl PdfEngine {
+    /// Motor instalado en el PATH más adecuado para `html`
+    pub fn detect(html: &str) -> Option<Self> {
+        Self::detect_in(&std::env::var_os("PATH")?, html)
+    }
+
+    /// Motor más adecuado para `html` entre los programas de `path` (formato `PATH`).
+    /// Las fórmulas son MathML, que WeasyPrint y wkhtmltopdf muestran como texto
+    /// plano: si el HTML las tiene, Chromium pasa por delante.
+    pub fn detect_in(path: &OsStr, html: &str) -> Option<Self> {
+        let installed = |(program, kind): &(&str, PdfEngineKind)| {
+            std::env::split_paths(path)
+                .map(|dir| dir.join(program))
+                .find(|candidate| candidate.is_file())
+                .map(|program| Self {
+                    kind: *kind,
+                    program,
+                })
+        };
+        let chromium = || {
+            PDF_ENGINES
+                .iter()
+                .filter(|(_, kind)| *kind == PdfEngineKind::Chromium)
+                .find_map(installed)
+        };
+        html.contains("<math")
+            .then(chromium)
+            .flatten()
+            .or_else(|| PDF_ENGINES.iter().find_map(installed))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            PdfEngineKind::WeasyPrint => "weasyprint",
+            PdfEngineKind::Chromium => "chromium",
+            PdfEngineKind::Wkhtmltopdf => "wkhtmltopdf",
+        }
+    }
+
+    /// Orden para convertir `html` en `pdf`. WeasyPrint y Chromium leen la página
+    /// de `@page`; a wkhtmltopdf hay que pasársela en argumentos.
+    fn command(&self, html: &Path, pdf: &Path, page: &PageSetup, title: &str) -> Command {
+        let mut command = Command::new(&self.program);
+        match self.kind {
+            PdfEngineKind::WeasyPrint => {
+                command.arg("--quiet").arg(html).arg(pdf);
+            }
+            PdfEngineKind::Chromium => {
+                command
+                    .args(["--headless", "--disable-gpu", "--no-pdf-header-footer"])
+                    .arg(format!("--print-to-pdf={}", pdf.display()))
+                    .arg(format!("file://{}", html.display()));
+            }
+            PdfEngineKind::Wkhtmltopdf => {
+                command.args([
+                    "--quiet",
+                    "--enable-local-file-access",
+                    "--print-media-type",
+                ]);
+                match page.size.wkhtmltopdf_name() {
+                    Some(name) => {
+                        command.args(["--page-size", name]);
+                    }
+                    None => {
+                        let (width, height) = page.size.dimensions_mm();
+                        command
+                            .args(["--page-width", &format!("{}mm", width)])
+                            .args(["--page-height", &format!("{}mm", height)]);
+                    }
+                }
+                if page.landscape {
+                    command.args(["--orientation", "Landscape"]);
+                }
+                let margins = page.margins;
+                for (flag, value) in [
+                    ("--margin-top", margins.top),
+                    ("--margin-right", margins.right),
+                    ("--margin-bottom", margins.bottom),
+                    ("--margin-left", margins.left),
+                ] {
+                    command.args([flag, &format!("{}mm", value)]);
+                }
+                let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+                let wk = |template: &str| {
+                    template
+                        .replace("{title}", title)
+                        .replace("{date}", &date)
+                        .replace("{pages}", "[topage]")
+                        .replace("{page}", "[page]")
+                };
+                if let Some(header) = &page.header {
+                    command
+                        .args(["--header-center", &wk(header)])
+                        .args(["--header-font-size", "9"]);
+                }
+                if let Some(footer) = &page.footer {
+                    command
+                        .args(["--footer-center", &wk(footer)])
+                        .args(["--footer-font-size", "9"]);
+                }
+                command.args(["--title", title]).arg(html).arg(pdf);
+            }
+        }
+        command
+    }
+}
+
+/// Convertir un HTML exportado en PDF con el primer motor disponible
+pub fn html_to_pdf(
+    html: &str,
+    output: &Path,
+    page: &PageSetup,
+    title: &str,
+) -> Result<PdfEngine, ExportError> {
+    let engine = PdfEngine::detect(html).ok_or(ExportError::NoPdfEngine)?;
+
+    let temp = std::env::temp_dir().join(format!(
+        "notnative-export-{}-{}.html",
+        std::process::id(),
+        chrono::Local::now()
+            .timestamp_nanos_opt()
+            .unwrap_or_default()
+    ));
+    std::fs::write(&temp, html)?;
+    let result = engine.command(&temp, output, page, title).output();
+    let _ = std::fs::remove_file(&temp);
+
+    let output_ok = output.is_file();
+    match result {
+        Ok(result) if result.status.success() && output_ok => Ok(engine),
+        Ok(result) => Err(ExportError::PdfEngine {
+            engine: engine.name().to_string(),
+            message: String::from_utf8_lossy(&result.stderr).trim().to_string(),
+        }),
+        Err(e) => Err(ExportError::PdfEngine {
+            engine: engine.name().to_string(),
+            message: e.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_setup() {
+        assert_eq!(PageSize::parse("letter").unwrap(), PageSize::Letter);
+        assert_eq!(
+            PageSize::parse("100x150mm").unwrap(),
+            PageSize::Custom {
+                width_mm: 100.0,
+                height_mm: 150.0
+            }
+        );
+        assert!(PageSize::parse("huge").is_err());
+        assert_eq!(ExportFormat::parse("PDF").unwrap(), ExportFormat::Pdf);
+        assert!(ExportFormat::parse("docx").is_err());
+
+        let page = PageSetup::default()
+            .with_size(PageSize::A5)
+            .with_landscape(true)
+            .with_margins(Margins::uniform(12.5))
+            .with_header(Some("{title} \"borrador\"".to_string()));
+        let css = page.css("Plan", "2026-01-02");
+        assert!(css.contains("size: 210mm 148mm;"));
+        assert!(css.contains("margin: 12.5mm 12.5mm 12.5mm 12.5mm;"));
+        assert!(css.contains(
+            r#"@top-center {
+        content: "Plan \"borrador\"";"#
+        ));
+        assert!(css.contains(r#"content: counter(page) " / " counter(pages);"#));
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(&[0xff, 0xfe, 0x00]), "//4A");
+
+        assert_eq!(base64_decode("Zm9v\nYmFy").unwrap(), b"foobar");
+        assert_eq!(base64_decode("Zm8=").unwrap(), b"fo");
+        assert_eq!(base64_decode("//4A").unwrap(), [0xff, 0xfe, 0x00]);
+        assert!(base64_decode("no válido").is_none());
+    }
+
+    #[test]
+    fn test_standalone_html() {
+        let root = std::env::temp_dir().join("notnative_export_test");
+        std::fs::create_dir_all(root.join("img")).unwrap();
+        std::fs::write(root.join("img/punto.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        let note = ExportNote::new(
+            "Proyectos/Plan",
+            "# Plan\n\n## Fase uno\n\n![punto](img/punto.png)\n\n[[Otra#Fase uno|otra]] [[#Fase uno]] [[Nada]] #tag\n\n- [ ] tarea\n\n$x^2$\n\n```dot\ndigraph { a -> b }\n```\n",
+        );
+        let exporter = NoteExporter::new(&root);
+        let html = exporter.note_html(&note, &["Proyectos/Plan".to_string(), "Otra".to_string()]);
+
+        assert!(html.contains("<title>Plan</title>"));
+        assert!(html.contains("@page {"));
+        assert!(html.contains(r#"<body class="print">"#));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("notifyRust"));
+        assert!(!html.contains("file://"));
+        assert!(html.contains(r#"<img src="data:image/png;base64,iVBORw==""#));
+        assert!(
+            html.contains(r##"<a href="../Otra.html#fase-uno" class="internal-link">otra</a>"##)
+        );
+        assert!(html.contains(r##"<a href="#fase-uno" class="internal-link">"##));
+        assert!(html.contains(r#"<a class="internal-link unresolved">Nada</a>"#));
+        assert!(html.contains(r#"<a class="tag-link">"#));
+        assert!(html.contains(r#"<h2 id="fase-uno">Fase uno</h2>"#));
+        assert!(html.contains(" disabled"));
+        // Fórmulas y diagramas ya renderizados
+        assert!(html.contains("<math"));
+        assert!(html.contains(r#"class="nn-diagram""#));
+
+        // Con fórmulas el PDF sale de Chromium aunque WeasyPrint esté antes;
+        // sin Chromium se usa el motor disponible
+        let bin = root.join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join("weasyprint"), "").unwrap();
+        let engine = |html: &str| PdfEngine::detect_in(bin.as_os_str(), html).unwrap().kind;
+        assert_eq!(engine(&html), PdfEngineKind::WeasyPrint);
+        std::fs::write(bin.join("chromium"), "").unwrap();
+        assert_eq!(engine(&html), PdfEngineKind::Chromium);
+        let plain = exporter.note_html(&ExportNote::new("Sin fórmulas", "Texto"), &[]);
+        assert_eq!(engine(&plain), PdfEngineKind::WeasyPrint);
+
+        let out = root.join("out");
+        let notes = vec![note, ExportNote::new("Otra", "Vuelta a [[Plan]]")];
+        let written = exporter
+            .export_notes(&notes, ExportFormat::Html, &out)
+            .unwrap();
+        assert_eq!(written.len(), 3);
+        assert!(out.join("Proyectos/Plan.html").is_file());
+        let index = std::fs::read_to_string(out.join("index.html")).unwrap();
+        assert!(index.contains(r#"href="Proyectos/Plan.html""#));
+        let back = std::fs::read_to_string(out.join("Otra.html")).unwrap();
+        assert!(back.contains(r#"href="Proyectos/Plan.html""#));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_wkhtmltopdf_command() {
+        let engine = PdfEngine {
+            kind: PdfEngineKind::Wkhtmltopdf,
+            program: PathBuf::from("/usr/bin/wkhtmltopdf"),
+        };
+        let page = PageSetup::default()
+            .with_size(PageSize::Custom {
+                width_mm: 100.0,
+                height_mm: 200.0,
+            })
+            .with_header(Some("{title}".to_string()));
+        let command = engine.command(
+            Path::new("/tmp/a.html"),
+            Path::new("/tmp/a.pdf"),
+            &page,
+            "Nota",
+        );
+        let args: Vec<String> = command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        let joined = args.join(" ");
+        assert!(joined.contains("--page-width 100mm --page-height 200mm"));
+        assert!(joined.contains("--margin-top 20mm"));
+        assert!(joined.contains("--header-center Nota"));
+        assert!(joined.contains("--footer-center [page] / [topage]"));
+        assert!(joined.ends_with("/tmp/a.html /tmp/a.pdf"));
+    }
+}
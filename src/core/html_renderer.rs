@@ -2,15 +2,35 @@
 //!
 //! Convierte el contenido Markdown a HTML completo con:
 //! - Checkboxes interactivos para TODOs
-//! - Links internos [[nota]] clickeables
+//! - Links internos [[nota]], [[nota#Encabezado]] y [[nota#^bloque]] clickeables
+//! - Transclusión ![[nota#Encabezado]] con enlace a la nota de origen
+//! - Fórmulas LaTeX $...$ y $$...$$ como MathML (sin JavaScript ni red)
 //! - Syntax highlighting en code blocks (highlight.js)
+//! - Bloques ```chart con gráficos SVG generados desde Bases
+//! - Bloques ```base que embeben la tabla de una vista de Base
+//! - Bloques ```query / ```dataview con queries TABLE/LIST/TASK (ver `InlineQuery`)
 //! - Soporte para tema claro/oscuro
 
-use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd, html};
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd, html};
 use regex::Regex;
-use std::path::PathBuf;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::LazyLock;
 
+use super::base::{BaseView, ChartConfig, ColumnConfig};
+use super::base_query::{BaseQueryEngine, NoteWithProperties};
+use super::chart::render_chart_svg;
+use super::database::NotesDatabase;
+use super::diagram::{DiagramKind, render_diagram_cached};
+use super::inline_query::{InlineQuery, QueryKind, extract_tasks};
+use super::markdown::MarkdownParser;
+use super::math::{find_math, tex_to_mathml};
+use super::note_file::NotesDirectory;
+use super::wikilink::{WIKI_LINK_RE, WikiLink, extract};
+
 // ============================================================================
 // REGEX ESTÁTICOS - Compilados una sola vez para mejor rendimiento
 // ============================================================================
@@ -26,6 +46,35 @@ static PROP_PAIR_RE: LazyLock<Regex> =
 static INTERNAL_LINK_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"\[\[([^\]]+)\]\]").unwrap());
 
+/// Regex para IDs de bloque `^a1b2c3` al final de una línea (o en una línea propia)
+static BLOCK_ID_MARK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)(^|[ \t])\^([A-Za-z0-9-]+)[ \t]*$").unwrap());
+
+/// Regex para los huecos donde va el HTML renderizado aparte (transclusiones, fórmulas)
+static FRAGMENT_SLOT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<(?:div|span) class="fragment-slot" data-fragment="(\d+)"></(?:div|span)>"#)
+        .unwrap()
+});
+
+/// Regex para la cabecera de un callout: `[!tipo]` seguido opcionalmente de `+` o `-`
+static CALLOUT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[!([A-Za-z][\w-]*)\]([+-])?").unwrap());
+
+/// Regex para el prefijo de cita (`> `) de las líneas de una fórmula dentro de una cita
+static QUOTE_PREFIX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^[ \t]*>[ \t>]*").unwrap());
+
+/// Regex para checkboxes interactivos dentro de una transclusión
+static EMBED_CHECKBOX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<input type="checkbox" class="todo-checkbox"[^>]*>"#).unwrap());
+
+/// Regex para atributos data-line (líneas de la nota transcluida, no de la actual)
+static DATA_LINE_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#" data-line="\d+""#).unwrap());
+
+/// Profundidad máxima de transclusiones anidadas
+const MAX_EMBED_DEPTH: usize = 4;
+
 /// Regex para tags #tag
 static TAG_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?m)(^|[\s\(\[,])#([a-zA-Z][a-zA-Z0-9_-]*)").unwrap());
@@ -68,8 +117,12 @@ static TAG_LINK_RE: LazyLock<Regex> =
 /// Regex para imágenes en HTML
 static IMG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"<img src="([^"]+)""#).unwrap());
 
+/// Regex para atributos key="value" o key=value en la línea de apertura de un bloque
+static BLOCK_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(\w+)=(?:"([^"]*)"|(\S+))"#).unwrap());
+
 /// Decodifica una cadena URL-encoded (percent-encoded)
-fn url_decode(s: &str) -> String {
+pub(crate) fn url_decode(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
     let mut chars = s.chars().peekable();
 
@@ -129,6 +182,8 @@ fn url_decode(s: &str) -> String {
 pub enum PreviewTheme {
     Light,
     Dark,
+    /// Exportación (HTML autocontenido, PDF): fondo blanco, sin JavaScript
+    Print,
 }
 
 impl Default for PreviewTheme {
@@ -166,11 +221,153 @@ impl Default for PreviewColors {
     }
 }
 
+impl PreviewColors {
+    /// Colores del tema de impresión (los mismos que `body.print` en el CSS)
+    pub fn print() -> Self {
+        Self {
+            bg_primary: "#ffffff".to_string(),
+            bg_secondary: "#f4f4f6".to_string(),
+            bg_tertiary: "#e2e3e8".to_string(),
+            fg_primary: "#1e1f24".to_string(),
+            fg_secondary: "#44464f".to_string(),
+            fg_muted: "#80838f".to_string(),
+            accent: "#1e66f5".to_string(),
+            border: "#c9cbd3".to_string(),
+        }
+    }
+}
+
+/// Primera línea de un callout (`[!tipo]± Título`) dentro de un blockquote
+struct CalloutHeader<'a> {
+    kind: String,
+    foldable: bool,
+    open: bool,
+    title: Vec<Event<'a>>,
+    /// Eventos que ocupa la cabecera (desde el inicio del párrafo hasta el salto de línea)
+    consumed: usize,
+}
+
+impl<'a> CalloutHeader<'a> {
+    /// `events` empieza justo después del `Start(BlockQuote)`
+    fn parse(events: &[Event<'a>]) -> Option<Self> {
+        if !matches!(events.first(), Some(Event::Start(Tag::Paragraph))) {
+            return None;
+        }
+
+        // pulldown-cmark puede partir `[!note]` en varios textos
+        let mut index = 1;
+        let mut lead = String::new();
+        while let Some(Event::Text(text)) = events.get(index) {
+            lead.push_str(text);
+            index += 1;
+        }
+        let caps = CALLOUT_RE.captures(&lead)?;
+        let kind = caps[1].to_lowercase();
+        let fold = caps.get(2).map_or("", |m| m.as_str());
+        let rest = lead[caps.get(0)?.end()..].trim_start().to_string();
+
+        // Título: el resto de la línea, con su formato inline
+        let mut title = Vec::new();
+        if !rest.is_empty() {
+            title.push(Event::Text(CowStr::from(rest)));
+        }
+        let mut depth = 0usize;
+        while let Some(event) = events.get(index) {
+            match event {
+                Event::SoftBreak | Event::HardBreak if depth == 0 => break,
+                Event::End(TagEnd::Paragraph) if depth == 0 => break,
+                Event::Start(_) => depth += 1,
+                Event::End(_) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            title.push(event.clone());
+            index += 1;
+        }
+
+        Some(Self {
+            kind,
+            foldable: !fold.is_empty(),
+            open: fold == "+",
+            title,
+            consumed: index,
+        })
+    }
+}
+
+/// Icono y nombre canónico (para el color en CSS) de un tipo de callout
+pub(crate) struct CalloutStyle {
+    pub(crate) name: &'static str,
+    icon: &'static str,
+}
+
+pub(crate) fn callout_style(kind: &str) -> CalloutStyle {
+    let (name, icon) = match kind {
+        "abstract" | "summary" | "tldr" => ("abstract", "📋"),
+        "info" => ("info", "ℹ️"),
+        "todo" => ("todo", "☑️"),
+        "tip" | "hint" | "important" => ("tip", "🔥"),
+        "success" | "check" | "done" => ("success", "✅"),
+        "question" | "help" | "faq" => ("question", "❓"),
+        "warning" | "caution" | "attention" => ("warning", "⚠️"),
+        "failure" | "fail" | "missing" => ("failure", "❌"),
+        "danger" | "error" => ("danger", "⚡"),
+        "bug" => ("bug", "🐞"),
+        "example" => ("example", "📑"),
+        "quote" | "cite" => ("quote", "❝"),
+        // note y tipos desconocidos
+        _ => ("note", "✏️"),
+    };
+    CalloutStyle { name, icon }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Contenido de un bloque ```chart: fuente de datos + configuración del gráfico
+#[derive(Debug, Deserialize)]
+struct ChartBlock {
+    /// Base guardada de la que tomar las notas
+    #[serde(default)]
+    base: Option<String>,
+
+    /// Vista de la Base (default: la activa)
+    #[serde(default)]
+    view: Option<String>,
+
+    /// Carpeta fuente cuando no se indica Base
+    #[serde(default)]
+    folder: Option<String>,
+
+    #[serde(flatten)]
+    config: ChartConfig,
+}
+
+/// Estado del outline que refleja el preview del editor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreviewOutline {
+    /// Líneas de los encabezados cuyas secciones están plegadas
+    pub folded: BTreeSet<usize>,
+    /// Encabezado al que desplazar la vista al cargar (`]]`, panel de outline)
+    pub scroll_to: Option<usize>,
+    /// Bloque (`^id`) al que desplazar la vista al cargar (`[[Nota#^id]]`)
+    pub scroll_to_block: Option<String>,
+}
+
 /// Renderer de Markdown a HTML
 pub struct HtmlRenderer {
     theme: PreviewTheme,
     base_path: Option<PathBuf>, // Directorio base para resolver rutas relativas de imágenes
     colors: Option<PreviewColors>, // Colores dinámicos del tema GTK
+    notes_db: Option<Rc<NotesDatabase>>, // BD de los bloques que consultan Bases
+    outline: Option<PreviewOutline>, // Pliegues del editor (None = encabezados sin anotar)
+    fragments: RefCell<Vec<String>>, // HTML renderizado aparte (transclusiones, fórmulas) del render en curso
+    embed_stack: Vec<String>,        // Destinos que se están transcluyendo (evita ciclos)
+    embeddable: Option<BTreeSet<PathBuf>>, // Notas que se pueden transcluir (None = todas)
 }
 
 impl Default for HtmlRenderer {
@@ -185,6 +382,11 @@ impl HtmlRenderer {
             theme,
             base_path: None,
             colors: None,
+            notes_db: None,
+            outline: None,
+            fragments: RefCell::new(Vec::new()),
+            embed_stack: Vec::new(),
+            embeddable: None,
         }
     }
 
@@ -194,6 +396,11 @@ impl HtmlRenderer {
             theme,
             base_path: Some(base_path),
             colors: None,
+            notes_db: None,
+            outline: None,
+            fragments: RefCell::new(Vec::new()),
+            embed_stack: Vec::new(),
+            embeddable: None,
         }
     }
 
@@ -203,6 +410,11 @@ impl HtmlRenderer {
             theme,
             base_path: Some(base_path),
             colors: Some(colors),
+            notes_db: None,
+            outline: None,
+            fragments: RefCell::new(Vec::new()),
+            embed_stack: Vec::new(),
+            embeddable: None,
         }
     }
 
@@ -211,6 +423,22 @@ impl HtmlRenderer {
         self.colors = Some(colors);
     }
 
+    /// Establece la BD usada por los bloques que consultan Bases
+    pub fn set_database(&mut self, db: NotesDatabase) {
+        self.notes_db = Some(Rc::new(db));
+    }
+
+    /// Limita las transclusiones `![[Nota]]` a estas notas; las demás se muestran
+    /// como ausentes (al publicar, las notas con `publish: false`)
+    pub fn set_embeddable(&mut self, notes: impl IntoIterator<Item = PathBuf>) {
+        self.embeddable = Some(notes.into_iter().collect());
+    }
+
+    /// Anota los encabezados con su línea (`data-line`) y aplica pliegues y salto
+    pub fn set_outline(&mut self, outline: PreviewOutline) {
+        self.outline = Some(outline);
+    }
+
     /// Renderiza Markdown a HTML completo (documento completo con estilos)
     pub fn render(&self, markdown: &str) -> String {
         let body_html = self.render_body(markdown);
@@ -231,20 +459,210 @@ impl HtmlRenderer {
 
         let parser = Parser::new_ext(&processed, options);
 
+        // Líneas de los encabezados, en el mismo orden en que los emite el parser
+        let heading_lines: Vec<usize> = match self.outline {
+            Some(_) => MarkdownParser::new(processed.clone())
+                .headings()
+                .into_iter()
+                .map(|heading| heading.line)
+                .collect(),
+            None => Vec::new(),
+        };
+
         // Procesar eventos para añadir atributos custom
-        let parser = self.process_events(parser, markdown);
+        let parser = self.process_events(parser, markdown, &heading_lines);
 
         // Generar HTML
         let mut html_output = String::new();
         html::push_html(&mut html_output, parser.into_iter());
 
         // Post-procesar para añadir data attributes y handlers
-        self.postprocess_html(&html_output)
+        let html_output = self.postprocess_html(&html_output);
+
+        // Colocar las transclusiones y fórmulas en sus huecos
+        let fragments = self.fragments.take();
+        FRAGMENT_SLOT_RE
+            .replace_all(&html_output, |caps: &regex::Captures| {
+                caps[1]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| fragments.get(i))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .to_string()
+    }
+
+    /// HTML de una transclusión `![[Nota#Encabezado]]`: el contenido renderizado
+    /// con un enlace a su origen. Las tareas se muestran sin interacción porque
+    /// sus líneas son las de la otra nota.
+    fn render_embed(&self, link: &WikiLink) -> String {
+        let destination = link.destination();
+        let source = format!(
+            "<a href=\"#\" class=\"internal-link\" data-note=\"{}\" onclick=\"notifyRust(&quot;open-note&quot;, &quot;{}&quot;); return false;\">↗ {}</a>",
+            destination,
+            destination,
+            link.label()
+        );
+
+        let nested =
+            self.embed_stack.contains(&destination) || self.embed_stack.len() >= MAX_EMBED_DEPTH;
+        let body = match self.base_path.as_ref().filter(|_| !nested) {
+            Some(base_path) => {
+                let markdown = NotesDirectory::new(base_path)
+                    .ok()
+                    .and_then(|notes_dir| notes_dir.find_note(&link.note).ok().flatten())
+                    .filter(|note| {
+                        self.embeddable
+                            .as_ref()
+                            .is_none_or(|allowed| allowed.contains(note.path()))
+                    })
+                    .and_then(|note| note.read().ok())
+                    .and_then(|text| extract(&text, link.target.as_ref()));
+                match markdown {
+                    Some(markdown) => {
+                        let mut embed_stack = self.embed_stack.clone();
+                        embed_stack.push(destination.clone());
+                        let child = HtmlRenderer {
+                            theme: self.theme,
+                            base_path: self.base_path.clone(),
+                            colors: None,
+                            notes_db: self.notes_db.clone(),
+                            outline: None,
+                            fragments: RefCell::new(Vec::new()),
+                            embed_stack,
+                            embeddable: self.embeddable.clone(),
+                        };
+                        let html = child.render_body(&markdown);
+                        let html =
+                            EMBED_CHECKBOX_RE.replace_all(&html, |caps: &regex::Captures| {
+                                if caps[0].contains(" checked") {
+                                    "☑"
+                                } else {
+                                    "☐"
+                                }
+                                .to_string()
+                            });
+                        DATA_LINE_ATTR_RE.replace_all(&html, "").to_string()
+                    }
+                    None => format!("<p class=\"transclusion-missing\">⚠ {}</p>", destination),
+                }
+            }
+            None => String::new(),
+        };
+
+        format!(
+            "<div class=\"transclusion\"><div class=\"transclusion-source\">{}</div><div class=\"transclusion-body\">{}</div></div>",
+            source, body
+        )
+    }
+
+    /// Guarda HTML ya renderizado y devuelve el hueco donde se colocará tras el render
+    fn fragment_slot(&self, tag: &str, html: String) -> String {
+        let mut fragments = self.fragments.borrow_mut();
+        fragments.push(html);
+        format!(
+            r#"<{tag} class="fragment-slot" data-fragment="{}"></{tag}>"#,
+            fragments.len() - 1
+        )
     }
 
-    /// Pre-procesa el markdown para convertir sintaxis custom
+    /// Convierte las fórmulas `$...$` y `$$...$$` a MathML y las aparta del parser
+    /// de markdown (que se comería `_`, `*` y `\\`). Se conservan los saltos de línea
+    /// para que las líneas de los encabezados sigan siendo las de la nota.
+    fn protect_math(&self, markdown: &str) -> String {
+        let spans = find_math(markdown);
+        if spans.is_empty() {
+            return markdown.to_string();
+        }
+
+        let mut result = String::with_capacity(markdown.len());
+        let mut last = 0;
+        for span in spans {
+            let line_start = markdown[..span.range.start]
+                .rfind('\n')
+                .map_or(0, |i| i + 1);
+            let prefix = &markdown[line_start..span.range.start];
+            let rest_of_line = markdown[span.range.end..].split('\n').next().unwrap_or("");
+
+            // Dentro de una cita, las líneas siguientes llevan su `> ` (se mantiene fuera)
+            let quoted = prefix.contains('>');
+            let line_breaks: String = markdown[span.range.clone()]
+                .split('\n')
+                .skip(1)
+                .map(|line| match QUOTE_PREFIX_RE.find(line).filter(|_| quoted) {
+                    Some(quote) => format!("\n{}", quote.as_str()),
+                    None => "\n".to_string(),
+                })
+                .collect();
+            let tex = if quoted {
+                QUOTE_PREFIX_RE.replace_all(&span.tex, "").to_string()
+            } else {
+                span.tex
+            };
+
+            let class = if span.display {
+                "math math-display"
+            } else {
+                "math"
+            };
+            let html = format!(
+                r#"<span class="{}">{}</span>"#,
+                class,
+                tex_to_mathml(&tex, span.display)
+            );
+            // Un bloque $$ en líneas propias va como bloque HTML (le sigue una línea en
+            // blanco); el resto, en línea dentro de su párrafo
+            let whole_lines = prefix.trim_start_matches([' ', '\t', '>']).is_empty()
+                && rest_of_line.trim().is_empty();
+            let tag = if span.display && whole_lines && !line_breaks.is_empty() {
+                "div"
+            } else {
+                "span"
+            };
+
+            result.push_str(&markdown[last..span.range.start]);
+            result.push_str(&self.fragment_slot(tag, html));
+            result.push_str(&line_breaks);
+            last = span.range.end;
+        }
+        result.push_str(&markdown[last..]);
+        result
+    }
+
+    /// Pre-procesa el markdown para convertir sintaxis custom.
+    /// El contenido de los bloques especiales (```query, ```base...) se deja intacto
+    /// para que #tags y [k::v] lleguen sin transformar a su renderizador.
     fn preprocess_markdown(&self, markdown: &str) -> String {
-        let mut result = markdown.to_string();
+        let mut result = String::with_capacity(markdown.len());
+        let mut segment = String::new();
+        let mut in_special_block = false;
+
+        for line in markdown.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if in_special_block {
+                result.push_str(line);
+                in_special_block = !trimmed.starts_with("```");
+            } else if trimmed
+                .strip_prefix("```")
+                .is_some_and(Self::is_special_block)
+            {
+                result.push_str(&self.preprocess_segment(&segment));
+                segment.clear();
+                result.push_str(line);
+                in_special_block = true;
+            } else {
+                segment.push_str(line);
+            }
+        }
+
+        result.push_str(&self.preprocess_segment(&segment));
+        result
+    }
+
+    /// Pre-procesa un fragmento de markdown fuera de bloques especiales
+    fn preprocess_segment(&self, markdown: &str) -> String {
+        let mut result = self.protect_math(markdown);
 
         // Procesar propiedades inline [campo::valor] y [campo:::valor]
         // También soporta grupos: [campo1::val1, campo2:::val2]
@@ -306,13 +724,56 @@ impl HtmlRenderer {
 
         result = processed_lines.join("\n");
 
+        // IDs de bloque: anclas invisibles para [[nota#^id]]. Un ID en una línea propia
+        // se quita sin más para no romper la tabla o cita que lo precede.
+        let scroll_to_block = self
+            .outline
+            .as_ref()
+            .and_then(|outline| outline.scroll_to_block.as_deref());
+        result = BLOCK_ID_MARK_RE
+            .replace_all(&result, |caps: &regex::Captures| {
+                if caps[1].is_empty() {
+                    return String::new();
+                }
+                let class = if scroll_to_block == Some(&caps[2]) {
+                    "block-id scroll-target"
+                } else {
+                    "block-id"
+                };
+                format!(
+                    r#"{}<span class="{}" id="block-{}"></span>"#,
+                    &caps[1], class, &caps[2]
+                )
+            })
+            .to_string();
+
+        // Transclusiones ![[nota#Encabezado]]: se renderizan aparte y se colocan
+        // al final en su hueco (las imágenes ![[foto.png]] se dejan como están)
+        result = WIKI_LINK_RE
+            .replace_all(&result, |caps: &regex::Captures| {
+                let mut link = WikiLink::parse(&caps[2]);
+                link.embed = !caps[1].is_empty();
+                let is_attachment = Path::new(&link.note)
+                    .extension()
+                    .is_some_and(|ext| !ext.eq_ignore_ascii_case("md"));
+                if !link.embed || link.note.is_empty() || is_attachment {
+                    return caps[0].to_string();
+                }
+                self.fragment_slot("div", self.render_embed(&link))
+            })
+            .to_string();
+
         // Convertir [[nota]] a links especiales (placeholder que post-procesaremos)
         // URL-encode el nombre para manejar espacios y caracteres especiales
         result = INTERNAL_LINK_RE
             .replace_all(&result, |caps: &regex::Captures| {
-                let note_name = &caps[1];
-                let encoded_name = note_name.replace(' ', "%20");
-                format!(r#"[{}](notnative://note/{})"#, note_name, encoded_name)
+                let link = WikiLink::parse(&caps[1]);
+                let encoded_name = link
+                    .destination()
+                    .replace('%', "%25")
+                    .replace(' ', "%20")
+                    .replace('#', "%23");
+                format!(r#"[{}](notnative://note/{})"#, link.label(), encoded_name)
             })
             .to_string();
 
@@ -417,20 +878,79 @@ impl HtmlRenderer {
             })
             .to_string();
 
+        // lines() descarta el salto final; restaurarlo para no pegar el bloque siguiente
+        if markdown.ends_with('\n') && !result.ends_with('\n') {
+            result.push('\n');
+        }
+
         result
     }
 
     /// Procesa eventos del parser para personalizar el output
     #[allow(unused_assignments)]
-    fn process_events<'a>(&self, parser: Parser<'a>, original_markdown: &'a str) -> Vec<Event<'a>> {
+    fn process_events<'a>(
+        &self,
+        parser: Parser<'a>,
+        original_markdown: &'a str,
+        heading_lines: &[usize],
+    ) -> Vec<Event<'a>> {
         let lines: Vec<&str> = original_markdown.lines().collect();
         let mut events: Vec<Event<'a>> = Vec::new();
+        let mut heading_lines = heading_lines.iter();
         let mut current_line = 0;
         let mut in_list_item = false;
         let mut list_item_line = 0;
+        // Bloque especial en curso (info string, contenido acumulado)
+        let mut special_block: Option<(String, String)> = None;
 
         for event in parser {
             match &event {
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                    if Self::is_special_block(info) =>
+                {
+                    special_block = Some((info.to_string(), String::new()));
+                    continue;
+                }
+                Event::Text(text) if special_block.is_some() => {
+                    if let Some((_, content)) = special_block.as_mut() {
+                        content.push_str(text);
+                    }
+                    continue;
+                }
+                Event::End(TagEnd::CodeBlock) if special_block.is_some() => {
+                    if let Some((info, content)) = special_block.take() {
+                        let html = self.render_special_block(&info, &content);
+                        events.push(Event::Html(CowStr::from(html)));
+                    }
+                    continue;
+                }
+                Event::Start(Tag::Heading {
+                    level,
+                    id,
+                    classes,
+                    attrs,
+                }) if self.outline.is_some() => {
+                    let (Some(outline), Some(&line)) = (&self.outline, heading_lines.next()) else {
+                        events.push(event);
+                        continue;
+                    };
+                    let mut classes = classes.clone();
+                    let mut attrs = attrs.clone();
+                    attrs.push(("data-line".into(), Some(line.to_string().into())));
+                    if outline.folded.contains(&line) {
+                        classes.push("folded".into());
+                    }
+                    if outline.scroll_to == Some(line) {
+                        classes.push("scroll-target".into());
+                    }
+                    events.push(Event::Start(Tag::Heading {
+                        level: *level,
+                        id: id.clone(),
+                        classes,
+                        attrs,
+                    }));
+                    continue;
+                }
                 Event::Start(Tag::Item) => {
                     in_list_item = true;
                     // Encontrar la línea actual basándonos en el contexto
@@ -458,7 +978,261 @@ impl HtmlRenderer {
             events.push(event);
         }
 
-        events
+        // Al imprimir, un <details> cerrado perdería el cuerpo del callout
+        Self::render_callouts(events, self.theme == PreviewTheme::Print)
+    }
+
+    /// Convierte los blockquotes que empiezan por `[!tipo]` en callouts al estilo Obsidian:
+    /// `> [!warning] Título` (fijo), `> [!tip]+ Título` (abierto) o `> [!note]- Título` (plegado)
+    fn render_callouts(events: Vec<Event<'_>>, force_open: bool) -> Vec<Event<'_>> {
+        let mut output = Vec::with_capacity(events.len());
+        // Cierre HTML de cada blockquote abierto (None = blockquote normal)
+        let mut quotes: Vec<Option<&'static str>> = Vec::new();
+        let mut i = 0;
+
+        while i < events.len() {
+            match &events[i] {
+                Event::Start(Tag::BlockQuote) => {
+                    let Some(header) = CalloutHeader::parse(&events[i + 1..]) else {
+                        quotes.push(None);
+                        output.push(events[i].clone());
+                        i += 1;
+                        continue;
+                    };
+
+                    let style = callout_style(&header.kind);
+                    let (container, title_tag) = if header.foldable {
+                        ("details", "summary")
+                    } else {
+                        ("div", "div")
+                    };
+                    output.push(Event::Html(CowStr::from(format!(
+                        r#"<{container} class="callout" data-callout="{}"{}><{title_tag} class="callout-title"><span class="callout-icon">{}</span><span class="callout-title-text">"#,
+                        style.name,
+                        if header.foldable && (header.open || force_open) {
+                            " open"
+                        } else {
+                            ""
+                        },
+                        style.icon,
+                    ))));
+                    if header.title.is_empty() {
+                        output.push(Event::Text(CowStr::from(capitalize(&header.kind))));
+                    } else {
+                        output.extend(header.title);
+                    }
+                    output.push(Event::Html(CowStr::from(format!(
+                        r#"</span></{title_tag}><div class="callout-content">"#
+                    ))));
+                    quotes.push(Some(if header.foldable {
+                        "</div></details>"
+                    } else {
+                        "</div></div>"
+                    }));
+
+                    // El resto del primer párrafo (si lo hay) es el cuerpo del callout
+                    i += 1 + header.consumed;
+                    if matches!(events.get(i), Some(Event::End(TagEnd::Paragraph))) {
+                        i += 1;
+                    } else {
+                        output.push(Event::Start(Tag::Paragraph));
+                        i += 1; // salto de línea tras el título
+                    }
+                    continue;
+                }
+                Event::End(TagEnd::BlockQuote) => match quotes.pop().flatten() {
+                    Some(close) => output.push(Event::Html(CowStr::from(close))),
+                    None => output.push(events[i].clone()),
+                },
+                event => output.push(event.clone()),
+            }
+            i += 1;
+        }
+
+        output
+    }
+
+    /// ¿Es un bloque de código que se renderiza como contenido especial?
+    fn is_special_block(info: &str) -> bool {
+        Self::is_live_block(info) || DiagramKind::from_info(info).is_some()
+    }
+
+    /// ¿Es un bloque especial cuyo contenido sale de otras notas?
+    fn is_live_block(info: &str) -> bool {
+        matches!(
+            info.split_whitespace().next(),
+            Some("chart" | "base" | "query" | "dataview")
+        )
+    }
+
+    /// ¿Contiene el markdown bloques que dependen de otras notas?
+    /// (se deben re-renderizar cuando cambian las notas fuente)
+    pub fn has_live_blocks(markdown: &str) -> bool {
+        markdown.lines().any(|line| {
+            let line = line.trim_start();
+            line.strip_prefix("```").is_some_and(Self::is_live_block)
+        })
+    }
+
+    /// Renderiza un bloque especial (```chart, ```base, ```query, ```mermaid, ...) como HTML
+    fn render_special_block(&self, info: &str, content: &str) -> String {
+        if let Some(kind) = DiagramKind::from_info(info) {
+            return self.render_diagram_block(kind, content);
+        }
+
+        let result = match info.split_whitespace().next() {
+            Some("chart") => self.render_chart_block(content),
+            Some("base") => self.render_base_block(info),
+            Some("query" | "dataview") => self.render_query_block(content),
+            _ => Err(format!("Unknown block type: {}", info)),
+        };
+
+        result
+            .unwrap_or_else(|e| format!(r#"<div class="embed-error">⚠️ {}</div>"#, escape_html(&e)))
+    }
+
+    /// Motor de queries sobre la BD configurada
+    fn query_engine(&self) -> Result<BaseQueryEngine<'_>, String> {
+        let db = self
+            .notes_db
+            .as_ref()
+            .ok_or_else(|| "This block needs access to the notes database".to_string())?;
+        let notes_root = self.base_path.as_deref().unwrap_or(Path::new("."));
+        Ok(BaseQueryEngine::new(db, notes_root))
+    }
+
+    /// Renderiza un bloque ```chart consultando la Base indicada
+    fn render_chart_block(&self, content: &str) -> Result<String, String> {
+        let block: ChartBlock =
+            serde_yaml::from_str(content).map_err(|e| format!("Invalid chart block: {}", e))?;
+        let engine = self.query_engine()?;
+
+        let notes = match &block.base {
+            Some(base_name) => engine
+                .query_saved_base(base_name, block.view.as_deref())
+                .map(|(_, notes)| notes),
+            None => engine.query_view(&BaseView::new("chart"), block.folder.as_deref()),
+        }
+        .map_err(|e| e.to_string())?;
+
+        let colors = self.svg_colors();
+        Ok(format!(
+            r#"<div class="chart-block">{}</div>"#,
+            render_chart_svg(&block.config, &notes, colors)
+        ))
+    }
+
+    /// Colores para los SVG generados (gráficos, diagramas)
+    fn svg_colors(&self) -> PreviewColors {
+        match self.theme {
+            PreviewTheme::Print => PreviewColors::print(),
+            _ => self.colors.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Renderiza un bloque ```mermaid o ```dot como SVG incrustado (cacheado por contenido)
+    fn render_diagram_block(&self, kind: DiagramKind, content: &str) -> String {
+        let colors = self.svg_colors();
+        match render_diagram_cached(kind, content, &colors) {
+            Ok(svg) => format!(r#"<div class="diagram-block">{}</div>"#, svg),
+            Err(e) => format!(
+                r#"<div class="embed-error">⚠️ {} diagram: {}</div>"#,
+                kind.name(),
+                escape_html(&e.to_string())
+            ),
+        }
+    }
+
+    /// Renderiza un bloque ```base name="Base" view="Vista" limit=N como tabla
+    fn render_base_block(&self, info: &str) -> Result<String, String> {
+        let attrs = parse_block_attributes(info);
+        let base_name = attrs
+            .iter()
+            .find(|(k, _)| k == "name" || k == "base")
+            .map(|(_, v)| v.as_str())
+            .ok_or_else(|| "Base block needs a name=\"...\" attribute".to_string())?;
+        let view_name = attrs
+            .iter()
+            .find(|(k, _)| k == "view")
+            .map(|(_, v)| v.as_str());
+        let limit = attrs
+            .iter()
+            .find(|(k, _)| k == "limit")
+            .and_then(|(_, v)| v.parse::<usize>().ok());
+
+        let (view, notes) = self
+            .query_engine()?
+            .query_saved_base(base_name, view_name)
+            .map_err(|e| e.to_string())?;
+
+        let total = notes.len();
+        let shown = &notes[..limit.unwrap_or(total).min(total)];
+
+        Ok(format!(
+            r#"<div class="base-embed" data-base="{name}"><div class="base-embed-header"><span class="base-embed-title">{name}</span><span class="base-embed-view">{view}</span><span class="base-embed-count">{count}</span></div>{table}</div>"#,
+            name = escape_html(base_name),
+            view = escape_html(&view.name),
+            count = total,
+            table = render_notes_table(shown, &view.columns),
+        ))
+    }
+
+    /// Renderiza un bloque ```query (TABLE/LIST/TASK ... FROM ... WHERE ... SORT ... LIMIT)
+    fn render_query_block(&self, content: &str) -> Result<String, String> {
+        let query = InlineQuery::parse(content).map_err(|e| format!("Query error: {}", e))?;
+        let notes = self
+            .query_engine()?
+            .query_inline(&query)
+            .map_err(|e| e.to_string())?;
+
+        let body = match query.kind {
+            QueryKind::Table => render_notes_table(&notes, &query.fields),
+            QueryKind::List => {
+                let mut html = String::from("<ul class=\"query-list\">");
+                for note in &notes {
+                    html.push_str("<li>");
+                    html.push_str(&note_link_html(&note.metadata.name));
+                    if let Some(field) = query.fields.first() {
+                        html.push_str(&format!(
+                            r#" <span class="query-field">{}</span>"#,
+                            escape_html(&note.get_display(&field.property))
+                        ));
+                    }
+                    html.push_str("</li>");
+                }
+                html.push_str("</ul>");
+                html
+            }
+            QueryKind::Task => {
+                let mut html = String::new();
+                for note in &notes {
+                    let tasks = extract_tasks(note.content.as_deref().unwrap_or(""));
+                    if tasks.is_empty() {
+                        continue;
+                    }
+                    html.push_str(&format!(
+                        r#"<div class="query-task-group">{}<ul class="query-tasks">"#,
+                        note_link_html(&note.metadata.name)
+                    ));
+                    for task in tasks {
+                        // Sin `disabled` para no confundirlos con los TODOs de la nota actual
+                        html.push_str(&format!(
+                            r#"<li><input type="checkbox" class="query-task-checkbox" onclick="return false;"{}> <span>{}</span></li>"#,
+                            if task.completed { " checked" } else { "" },
+                            escape_html(&task.text)
+                        ));
+                    }
+                    html.push_str("</ul></div>");
+                }
+                html
+            }
+        };
+
+        if notes.is_empty() || body.is_empty() {
+            return Ok(r#"<div class="query-block query-empty">No results</div>"#.to_string());
+        }
+
+        Ok(format!(r#"<div class="query-block">{}</div>"#, body))
     }
 
     /// Post-procesa el HTML para añadir interactividad
@@ -546,10 +1320,15 @@ impl HtmlRenderer {
     /// Envuelve el body HTML en un documento completo con estilos y scripts
     fn wrap_in_document(&self, body: &str) -> String {
         let css = self.get_css();
-        let js = self.get_javascript();
+        // La exportación no tiene a quién avisar: sin scripts
+        let script = match self.theme {
+            PreviewTheme::Print => String::new(),
+            _ => format!("<script>\n{}\n    </script>", self.get_javascript()),
+        };
         let theme_class = match self.theme {
             PreviewTheme::Light => "light",
             PreviewTheme::Dark => "dark",
+            PreviewTheme::Print => "print",
         };
 
         format!(
@@ -564,22 +1343,22 @@ impl HtmlRenderer {
 </head>
 <body class="{theme_class}">
     {body}
-    <script>
-{js}
-    </script>
+    {script}
 </body>
 </html>"#,
             css = css,
             body = body,
-            js = js,
+            script = script,
             theme_class = theme_class
         )
     }
 
     /// Retorna el CSS para el preview
     fn get_css(&self) -> String {
-        // Si tenemos colores dinámicos, usarlos
-        if let Some(ref colors) = self.colors {
+        // Si tenemos colores dinámicos, usarlos (la impresión usa siempre los suyos)
+        if let Some(ref colors) = self.colors
+            && self.theme != PreviewTheme::Print
+        {
             return self.get_dynamic_css(colors);
         }
 
@@ -663,6 +1442,40 @@ body.light {{
     --link: #209fb5;
     --link-internal: #8839ef;
 }}
+
+body.print {{
+    --bg-primary: #ffffff;
+    --bg-secondary: #f4f4f6;
+    --bg-tertiary: #e2e3e8;
+    --fg-primary: #1e1f24;
+    --fg-secondary: #44464f;
+    --fg-muted: #80838f;
+    --accent: #1e66f5;
+    --accent-hover: #1e66f5;
+    --green: #40a02b;
+    --red: #d20f39;
+    --yellow: #df8e1d;
+    --peach: #fe640b;
+    --code-bg: #f4f4f6;
+    --border: #c9cbd3;
+    --link: #1e66f5;
+    --link-internal: #8839ef;
+    padding: 0;
+    font-size: 11pt;
+    -webkit-print-color-adjust: exact;
+    print-color-adjust: exact;
+}}
+
+body.print h1, body.print h2, body.print h3,
+body.print h4, body.print h5, body.print h6 {{
+    break-after: avoid;
+}}
+
+body.print pre, body.print blockquote, body.print table, body.print img,
+body.print .callout, body.print .chart-block, body.print .diagram-block,
+body.print .math-display {{
+    break-inside: avoid;
+}}
 {common_css}"#,
             common_css = Self::get_common_css(),
         )
@@ -720,6 +1533,16 @@ h4 { font-size: 1.1em; }
 h5 { font-size: 1em; }
 h6 { font-size: 0.9em; color: var(--fg-secondary); }
 
+/* Secciones plegadas (za / zM) */
+.folded::after {
+    content: " …";
+    color: var(--fg-muted);
+}
+
+.fold-hidden {
+    display: none !important;
+}
+
 /* Paragraphs */
 p {
     margin-bottom: 1em;
@@ -748,6 +1571,40 @@ a.internal-link:hover {
     background-color: rgba(139, 92, 246, 0.2);
 }
 
+/* Transclusión ![[nota#Encabezado]] */
+.transclusion {
+    border-left: 3px solid var(--link-internal);
+    background-color: rgba(139, 92, 246, 0.05);
+    border-radius: 4px;
+    padding: 0.5em 1em;
+    margin: 1em 0;
+}
+
+.transclusion-source {
+    font-size: 0.85em;
+    margin-bottom: 0.5em;
+}
+
+.transclusion-body > :last-child {
+    margin-bottom: 0;
+}
+
+.transclusion-missing {
+    color: var(--fg-muted);
+}
+
+/* Fórmulas LaTeX ($...$ y $$...$$) renderizadas como MathML */
+math {
+    font-family: "Latin Modern Math", "STIX Two Math", "Cambria Math", math;
+}
+
+.math-display {
+    display: block;
+    overflow-x: auto;
+    overflow-y: hidden;
+    margin: 1em 0;
+}
+
 /* Tags (#tag) */
 a.tag-link {
     color: var(--yellow);
@@ -913,6 +1770,73 @@ blockquote p:last-child {
     margin-bottom: 0;
 }
 
+/* Callouts (> [!note] Título) */
+.callout {
+    --callout-color: var(--accent);
+    margin: 1em 0;
+    border-left: 4px solid var(--callout-color);
+    border-radius: 0 8px 8px 0;
+    background-color: color-mix(in srgb, var(--callout-color) 10%, var(--bg-primary));
+    overflow: hidden;
+}
+
+.callout[data-callout="abstract"],
+.callout[data-callout="tip"] { --callout-color: var(--link); }
+.callout[data-callout="success"] { --callout-color: var(--green); }
+.callout[data-callout="question"] { --callout-color: var(--yellow); }
+.callout[data-callout="warning"] { --callout-color: var(--peach); }
+.callout[data-callout="failure"],
+.callout[data-callout="danger"],
+.callout[data-callout="bug"] { --callout-color: var(--red); }
+.callout[data-callout="example"] { --callout-color: var(--link-internal); }
+.callout[data-callout="quote"] { --callout-color: var(--fg-muted); }
+
+.callout-title {
+    display: flex;
+    align-items: center;
+    gap: 0.5em;
+    padding: 0.5em 1em;
+    font-weight: 600;
+    color: var(--callout-color);
+    list-style: none;
+}
+
+summary.callout-title {
+    cursor: pointer;
+    user-select: none;
+}
+
+summary.callout-title::-webkit-details-marker {
+    display: none;
+}
+
+summary.callout-title::after {
+    content: "▾";
+    margin-left: auto;
+    transition: transform 0.15s ease;
+}
+
+details.callout:not([open]) > summary.callout-title::after {
+    transform: rotate(-90deg);
+}
+
+.callout-content {
+    padding: 0 1em 0.5em;
+    color: var(--fg-primary);
+}
+
+.callout-content:empty {
+    display: none;
+}
+
+.callout-content > :first-child {
+    margin-top: 0;
+}
+
+.callout-content > :last-child {
+    margin-bottom: 0;
+}
+
 /* Lists */
 ul, ol {
     margin: 1em 0;
@@ -1032,6 +1956,115 @@ img {
     border-radius: 8px;
 }
 
+/* Gráficos SVG (```chart) */
+.chart-block {
+    margin: 1em 0;
+    padding: 12px;
+    background-color: var(--bg-secondary);
+    border: 1px solid var(--border);
+    border-radius: 8px;
+}
+
+.chart-block svg {
+    display: block;
+}
+
+/* Diagramas SVG (```mermaid, ```dot) */
+.diagram-block {
+    margin: 1em 0;
+    padding: 12px;
+    overflow-x: auto;
+    text-align: center;
+    background-color: var(--bg-secondary);
+    border: 1px solid var(--border);
+    border-radius: 8px;
+}
+
+.diagram-block svg {
+    display: inline-block;
+}
+
+/* Vistas de Base embebidas (```base) */
+.base-embed {
+    margin: 1em 0;
+    border: 1px solid var(--border);
+    border-radius: 8px;
+    overflow: hidden;
+}
+
+.base-embed-header {
+    display: flex;
+    align-items: center;
+    gap: 8px;
+    padding: 8px 14px;
+    background-color: var(--bg-secondary);
+    border-bottom: 1px solid var(--border);
+    font-size: 0.9em;
+}
+
+.base-embed-title {
+    font-weight: 600;
+    color: var(--fg-primary);
+}
+
+.base-embed-view {
+    color: var(--accent);
+}
+
+.base-embed-count {
+    margin-left: auto;
+    color: var(--fg-muted);
+}
+
+.base-embed table {
+    margin: 0;
+    border-style: hidden;
+}
+
+.base-embed-empty {
+    text-align: center;
+    color: var(--fg-muted);
+}
+
+/* Resultados de queries inline (```query) */
+.query-block {
+    margin: 1em 0;
+}
+
+.query-block table {
+    margin: 0;
+}
+
+.query-field {
+    color: var(--fg-muted);
+}
+
+.query-task-group {
+    margin-bottom: 0.75em;
+}
+
+ul.query-tasks {
+    list-style: none;
+    padding-left: 1em;
+    margin: 0.25em 0;
+}
+
+.query-empty {
+    color: var(--fg-muted);
+    font-style: italic;
+}
+
+/* Errores en bloques embebidos */
+.embed-error {
+    margin: 1em 0;
+    padding: 10px 14px;
+    border-left: 4px solid var(--red);
+    background-color: rgba(243, 139, 168, 0.1);
+    border-radius: 0 6px 6px 0;
+    color: var(--fg-secondary);
+    font-size: 0.9em;
+}
+
 /* Horizontal rule */
 hr {
     border: none;
@@ -1142,6 +2175,23 @@ document.addEventListener('DOMContentLoaded', function() {
             li.parentElement.classList.add('contains-task-list');
         }
     });
+
+    // Ocultar el contenido de las secciones plegadas hasta el siguiente encabezado
+    // de nivel igual o superior
+    document.querySelectorAll('.content > .folded').forEach(function(heading) {
+        const level = parseInt(heading.tagName.substring(1));
+        let el = heading.nextElementSibling;
+        while (el && !(/^H[1-6]$/.test(el.tagName) && parseInt(el.tagName.substring(1)) <= level)) {
+            el.classList.add('fold-hidden');
+            el = el.nextElementSibling;
+        }
+    });
+
+    // Saltar al encabezado pedido (]], [[, panel de outline)
+    const target = document.querySelector('.scroll-target');
+    if (target) {
+        target.scrollIntoView({ block: 'start' });
+    }
 });
 
 // Función para obtener posición de scroll (usada por Rust)
@@ -1168,6 +2218,83 @@ function setScrollPercent(percent) {
     }
 }
 
+/// Parsea los atributos de la línea de apertura de un bloque (```base name="X" view="Y")
+fn parse_block_attributes(info: &str) -> Vec<(String, String)> {
+    BLOCK_ATTR_RE
+        .captures_iter(info)
+        .map(|caps| {
+            let value = caps
+                .get(2)
+                .or(caps.get(3))
+                .map(|m| m.as_str())
+                .unwrap_or("");
+            (caps[1].to_string(), value.to_string())
+        })
+        .collect()
+}
+
+/// Enlace interno a una nota (abre la nota al hacer click)
+fn note_link_html(name: &str) -> String {
+    format!(
+        "<a href=\"#\" class=\"internal-link\" data-note=\"{name}\" onclick=\"notifyRust(&quot;open-note&quot;, &quot;{name}&quot;); return false;\">{name}</a>",
+        name = escape_html(name)
+    )
+}
+
+/// Renderiza una lista de notas como tabla HTML con las columnas visibles de una vista
+fn render_notes_table(notes: &[NoteWithProperties], columns: &[ColumnConfig]) -> String {
+    let columns: Vec<&ColumnConfig> = columns.iter().filter(|c| c.visible).collect();
+
+    let mut html = String::from("<table class=\"base-embed-table\"><thead><tr>");
+    for column in &columns {
+        html.push_str(&format!(
+            "<th>{}</th>",
+            escape_html(&column.display_title())
+        ));
+    }
+    html.push_str("</tr></thead><tbody>");
+
+    for note in notes {
+        html.push_str("<tr>");
+        for column in &columns {
+            let cell = match column.property.as_str() {
+                "title" | "name" | "_note" => note_link_html(&note.metadata.name),
+                "created" => note
+                    .metadata
+                    .created_at
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+                "modified" => note
+                    .metadata
+                    .updated_at
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+                property => escape_html(&note.get_display(property)),
+            };
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>");
+    }
+
+    if notes.is_empty() {
+        html.push_str(&format!(
+            "<tr><td colspan=\"{}\" class=\"base-embed-empty\">No notes found</td></tr>",
+            columns.len().max(1)
+        ));
+    }
+
+    html.push_str("</tbody></table>");
+    html
+}
+
+/// Escapa caracteres especiales de HTML
+pub(crate) fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 /// Renderiza markdown a HTML con el tema por defecto
 pub fn render_markdown_to_html(markdown: &str) -> String {
     HtmlRenderer::default().render(markdown)
@@ -1212,6 +2339,59 @@ mod tests {
         assert!(html.contains("My Note"));
     }
 
+    #[test]
+    fn test_heading_links_and_transclusion() {
+        let html = render_markdown_to_html(
+            "Ver [[Acta#Decisiones clave|acuerdos]] y [[Acta#^dec001]]. ^ref001",
+        );
+        assert!(html.contains(r#"data-note="Acta#Decisiones clave""#));
+        assert!(html.contains(">acuerdos</a>"));
+        assert!(html.contains(r#"data-note="Acta#^dec001""#));
+        assert!(html.contains("Acta › ^dec001"));
+        assert!(html.contains(r#"id="block-ref001""#));
+        assert!(!html.contains("^ref001"));
+
+        let root = std::env::temp_dir().join(format!("notnative-embed-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("Actas")).unwrap();
+        std::fs::write(
+            root.join("Actas/Acta.md"),
+            "# Acta\n\n## Decisiones clave\n\n- [ ] Usar **Rust** ^dec001\n\n![[Acta#Otra]]\n\n## Otra\n\nNo incluida.\n",
+        )
+        .unwrap();
+        let renderer = HtmlRenderer::with_base_path(PreviewTheme::default(), root.clone());
+        let html = renderer.render_body(
+            "Antes\n\n![[Acta#Decisiones clave]]\n\n![[Acta#^dec001]]\n\n![[Falta]]\n",
+        );
+        assert_eq!(html.matches(r#"class="transclusion""#).count(), 4);
+        assert!(html.contains("<strong>Rust</strong>"));
+        assert!(html.contains("☐"));
+        assert!(!html.contains("todo-checkbox"));
+        // La sección incluye su propia transclusión de "Otra" (anidada)
+        assert!(html.contains("No incluida."));
+        assert!(html.contains("⚠ Falta"));
+        assert!(!html.contains("fragment-slot"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_math() {
+        let mut renderer = HtmlRenderer::new(PreviewTheme::Dark);
+        renderer.set_outline(PreviewOutline::default());
+        let md = "# Notas\n\nSea $a_1 * b_2$ y #tag.\n\n$$\n\\sum_{i=1}^n i\n$$\nTexto siguiente\n\n## Fin\n\n`$x$`\n";
+        let html = renderer.render_body(md);
+        assert!(html.contains(r#"<span class="math"><math xmlns="http://www.w3.org/1998/Math/MathML" display="inline">"#));
+        assert!(html.contains("<msub><mi>a</mi><mn>1</mn></msub><mo>∗</mo>"));
+        assert!(html.contains(r#"<span class="math math-display"><math"#));
+        assert!(html.contains("<munderover><mo>∑</mo>"));
+        // El texto tras el bloque se sigue procesando como markdown
+        assert!(html.contains("<p>Texto siguiente</p>"));
+        // Los encabezados conservan su línea original
+        assert!(html.contains(r#"data-line="9""#));
+        assert!(html.contains("<code>$x$</code>"));
+        assert!(!html.contains("fragment-slot"));
+    }
+
     #[test]
     fn test_code_blocks() {
         let md = "```rust\nfn main() {}\n```";
@@ -1231,6 +2411,152 @@ mod tests {
         assert!(html.contains("<td>"));
     }
 
+    #[test]
+    fn test_chart_block() {
+        let md = "```chart\ntype: bar\nx: status\n```";
+
+        // Sin BD se muestra un error inline en lugar del bloque de código
+        let html = render_markdown_to_html(md);
+        assert!(html.contains("embed-error"));
+        assert!(!html.contains("<pre>"));
+
+        let db_path = std::env::temp_dir().join("test_chart_block.db");
+        let db = NotesDatabase::new(&db_path).unwrap();
+        db.index_note(
+            "chart-note",
+            "/path/to/chart-note.md",
+            "[status::done]",
+            None,
+        )
+        .unwrap();
+
+        let mut renderer = HtmlRenderer::new(PreviewTheme::Dark);
+        renderer.set_database(db);
+        let html = renderer.render(md);
+        assert!(html.contains("chart-block"));
+        assert!(html.contains("<svg"));
+
+        std::fs::remove_file(db_path).ok();
+    }
+
+    #[test]
+    fn test_base_block() {
+        use crate::core::Base;
+
+        let db_path = std::env::temp_dir().join("test_base_block.db");
+        let db = NotesDatabase::new(&db_path).unwrap();
+        db.index_note("Alpha", "/path/to/Alpha.md", "[status::active]", None)
+            .unwrap();
+        let base = Base::new("Projects");
+        db.create_base("Projects", None, None, &base.serialize().unwrap())
+            .unwrap();
+
+        let mut renderer = HtmlRenderer::new(PreviewTheme::Dark);
+        renderer.set_database(db);
+
+        let html = renderer.render("```base name=\"Projects\" view=\"Default\"\n```");
+        assert!(html.contains("base-embed-table"));
+        assert!(html.contains(r#"data-note="Alpha""#));
+
+        let html = renderer.render("```base name=\"Missing\"\n```");
+        assert!(html.contains("embed-error"));
+
+        std::fs::remove_file(db_path).ok();
+    }
+
+    #[test]
+    fn test_query_block() {
+        let db_path = std::env::temp_dir().join("test_query_block.db");
+        let db = NotesDatabase::new(&db_path).unwrap();
+        db.index_note(
+            "Open",
+            "/path/to/Open.md",
+            "#project [status::open]",
+            Some("Work"),
+        )
+        .unwrap();
+        db.index_note(
+            "Closed",
+            "/path/to/Closed.md",
+            "#project [status::done]",
+            Some("Work"),
+        )
+        .unwrap();
+
+        let mut renderer = HtmlRenderer::new(PreviewTheme::Dark);
+        renderer.set_database(db);
+
+        let html = renderer.render(
+            "```query\nTABLE status FROM #project AND \"Work\" WHERE status != \"done\"\n```",
+        );
+        assert!(html.contains("query-block"));
+        assert!(html.contains(r#"data-note="Open""#));
+        assert!(!html.contains(r#"data-note="Closed""#));
+
+        let html = renderer.render("```query\nLIST FROM #project SORT title ASC LIMIT 1\n```");
+        assert!(html.contains(r#"data-note="Closed""#));
+        assert!(!html.contains(r#"data-note="Open""#));
+
+        // Los errores de parseo se muestran inline
+        let html = renderer.render("```query\nSELECT * FROM notes\n```");
+        assert!(html.contains("embed-error"));
+        assert!(html.contains("Query error"));
+
+        std::fs::remove_file(db_path).ok();
+    }
+
+    #[test]
+    fn test_has_live_blocks() {
+        assert!(HtmlRenderer::has_live_blocks(
+            "text\n```base name=\"X\"\n```"
+        ));
+        assert!(HtmlRenderer::has_live_blocks("```chart\nx: status\n```"));
+        assert!(!HtmlRenderer::has_live_blocks("```rust\nfn main() {}\n```"));
+        assert!(!HtmlRenderer::has_live_blocks(
+            "```mermaid\ngraph TD\nA-->B\n```"
+        ));
+    }
+
+    #[test]
+    fn test_callouts() {
+        let md = "> [!warning]- **Cuidado** aquí\n> Contenido con `código`\n\n> [!TIP]\n> Consejo\n\n> [!info] Solo título\n\n> Cita normal\n> [!note] no es callout\n";
+        let html = HtmlRenderer::new(PreviewTheme::Dark).render(md);
+
+        assert!(html.contains(
+            r#"<details class="callout" data-callout="warning"><summary class="callout-title">"#
+        ));
+        assert!(html.contains("<strong>Cuidado</strong> aquí</span></summary>"));
+        assert!(html.contains("<p>Contenido con <code>código</code></p>\n</div></details>"));
+        // Tipo sin título: se usa el nombre del tipo; sin +/- no se puede plegar
+        assert!(html.contains(r#"<div class="callout" data-callout="tip">"#));
+        assert!(html.contains(r#"<span class="callout-title-text">Tip</span>"#));
+        assert!(html.contains(r#"<span class="callout-title-text">Solo título</span></div><div class="callout-content"></div></div>"#));
+        // Un [!tipo] que no está al principio deja el blockquote como está
+        assert!(html.contains("<blockquote>"));
+        assert_eq!(html.matches(r#"class="callout""#).count(), 3);
+
+        // En exportación (tema Print) los callouts plegados salen abiertos
+        let html = HtmlRenderer::new(PreviewTheme::Print).render(md);
+        assert!(html.contains(
+            r#"<details class="callout" data-callout="warning" open><summary class="callout-title">"#
+        ));
+    }
+
+    #[test]
+    fn test_diagram_blocks() {
+        let md = "# Flujo\n\n```mermaid\ngraph LR\n  A[*Inicio*] --> B\n```\n\n```dot\ndigraph { x -> }\n```\n";
+        let html = HtmlRenderer::new(PreviewTheme::Dark).render(md);
+        assert!(html.contains(r#"<div class="diagram-block"><svg"#));
+        // El contenido del bloque no pasa por markdown
+        assert!(html.contains("*Inicio*"));
+        assert!(html.contains("Graphviz diagram: Line 1"));
+
+        // Un tipo no soportado se queda en un recuadro que dice qué tipos hay
+        let html = HtmlRenderer::new(PreviewTheme::Dark).render("```mermaid\ngantt\n```\n");
+        assert!(html.contains(r#"<div class="embed-error">"#));
+        assert!(html.contains("Unsupported diagram type: mermaid gantt (supported:"));
+    }
+
     #[test]
     fn test_theme_class() {
         let md = "# Test";
@@ -1241,4 +2567,24 @@ mod tests {
         let dark = HtmlRenderer::new(PreviewTheme::Dark).render(md);
         assert!(dark.contains(r#"class="dark""#));
     }
+
+    #[test]
+    fn test_outline_annotations() {
+        let md = "# Uno\n\ntexto\n\n## Dos\n\n```\n# no es encabezado\n```\n\n# Tres\n";
+
+        // Sin outline los encabezados no se anotan
+        let plain = HtmlRenderer::new(PreviewTheme::Light).render_body(md);
+        assert!(!plain.contains("data-line"));
+
+        let mut renderer = HtmlRenderer::new(PreviewTheme::Light);
+        renderer.set_outline(PreviewOutline {
+            folded: [4].into_iter().collect(),
+            scroll_to: Some(10),
+            ..Default::default()
+        });
+        let html = renderer.render_body(md);
+        assert!(html.contains(r#"<h1 data-line="0">Uno</h1>"#));
+        assert!(html.contains(r#"<h2 class="folded" data-line="4">Dos</h2>"#));
+        assert!(html.contains(r#"<h1 class="scroll-target" data-line="10">Tres</h1>"#));
+    }
 }
@@ -0,0 +1,579 @@
+//! Importación de notas desde otras aplicaciones
+//!
+//! Cada importador convierte los ficheros de origen en notas markdown dentro de
+//! una carpeta del vault y copia los adjuntos a la carpeta de assets
+//! (`NotesConfig::assets_dir`). Lo que no se puede convertir no detiene la
+//! importación: queda anotado en el `ImportReport`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::archive::{ArchiveError, MAX_EXTRACTED_SIZE, ZipReader, crc32};
+use super::database::{self, NotesDatabase};
+use super::import_evernote::EvernoteImporter;
+use super::import_keep::KeepImporter;
+use super::import_notion::NotionImporter;
+use super::import_obsidian::ObsidianImporter;
+
+/// Regex para enlaces e imágenes markdown (`[texto](destino)`, `<destino>`)
+pub(crate) static MD_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(!?)\[([^\]\n]*)\]\((?:<([^>\n]+)>|([^)\s]+))(?:\s+"[^"\n]*")?\)"#).unwrap()
+});
+
+/// Nota con el informe, dentro de la carpeta de destino
+const REPORT_NOTE: &str = "Informe de importación";
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Import source not found: {0}")]
+    SourceNotFound(PathBuf),
+    #[error("Unknown import source: {0} (expected obsidian, notion, evernote or keep)")]
+    UnknownSource(String),
+    #[error("Archive error: {0}")]
+    Archive(#[from] ArchiveError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Aplicación de origen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    Obsidian,
+    Notion,
+    Evernote,
+    Keep,
+}
+
+impl ImportSource {
+    pub fn parse(s: &str) -> Result<Self, ImportError> {
+        match s.trim().to_lowercase().as_str() {
+            "obsidian" => Ok(ImportSource::Obsidian),
+            "notion" => Ok(ImportSource::Notion),
+            "evernote" => Ok(ImportSource::Evernote),
+            "keep" | "google-keep" => Ok(ImportSource::Keep),
+            other => Err(ImportError::UnknownSource(other.to_string())),
+        }
+    }
+
+    /// Nombre para mostrar y carpeta de destino por defecto
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImportSource::Obsidian => "Obsidian",
+            ImportSource::Notion => "Notion",
+            ImportSource::Evernote => "Evernote",
+            ImportSource::Keep => "Google Keep",
+        }
+    }
+
+    /// Importar `path` en la carpeta `folder` del vault (por defecto, `name()`)
+    pub fn import(
+        &self,
+        path: &Path,
+        notes_root: &Path,
+        folder: Option<&str>,
+    ) -> Result<ImportReport, ImportError> {
+        let folder = folder.unwrap_or(self.name());
+        match self {
+            ImportSource::Obsidian => ObsidianImporter::new(path, notes_root)
+                .with_folder(folder)
+                .import(),
+            ImportSource::Notion => NotionImporter::new(path, notes_root)
+                .with_folder(folder)
+                .import(),
+            ImportSource::Evernote => EvernoteImporter::new(path, notes_root)
+                .with_folder(folder)
+                .import(),
+            ImportSource::Keep => KeepImporter::new(path, notes_root)
+                .with_folder(folder)
+                .import(),
+        }
+    }
+}
+
+/// Algo que no se pudo convertir
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportIssue {
+    /// Nota afectada (o fichero de origen si no llegó a ser nota)
+    pub note: String,
+    pub message: String,
+}
+
+/// Base creada a partir de una base de datos de origen
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedBase {
+    pub name: String,
+    /// Carpeta con una nota por registro
+    pub folder: String,
+    /// Fichero `.base` con la definición
+    pub file: PathBuf,
+    #[serde(skip)]
+    pub config_yaml: String,
+}
+
+/// Resultado de una importación
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    /// Notas escritas, con su nombre en el vault
+    pub notes: Vec<String>,
+    /// Adjuntos copiados a la carpeta de assets
+    pub attachments: usize,
+    /// Bases creadas (pendientes de registrar con `register_bases`)
+    pub bases: Vec<ImportedBase>,
+    pub issues: Vec<ImportIssue>,
+}
+
+impl ImportReport {
+    pub fn warn(&mut self, note: impl Into<String>, message: impl Into<String>) {
+        self.issues.push(ImportIssue {
+            note: note.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Registrar las Bases en la base de datos de notas: se actualiza la que ya
+    /// exista con el mismo nombre y carpeta, y se avisa si el nombre está ocupado
+    /// por otra
+    pub fn register_bases(&mut self, db: &NotesDatabase) -> database::Result<()> {
+        for base in std::mem::take(&mut self.bases) {
+            match db.get_base_by_name(&base.name)? {
+                Some((id, _, _, source_folder, _, _))
+                    if source_folder.as_deref() == Some(base.folder.as_str()) =>
+                {
+                    db.update_base(id, &base.config_yaml, 0)?;
+                }
+                Some(_) => self.warn(
+                    base.name.clone(),
+                    format!(
+                        "Ya existe una Base con este nombre; la definición queda en {}",
+                        base.file.display()
+                    ),
+                ),
+                None => {
+                    db.create_base(&base.name, None, Some(&base.folder), &base.config_yaml)?;
+                }
+            }
+            self.bases.push(base);
+        }
+        Ok(())
+    }
+
+    /// Guardar el informe como nota `<carpeta>/Informe de importación` y
+    /// devolver su nombre
+    pub fn write_note(
+        &self,
+        notes_root: &Path,
+        folder: &str,
+        source: ImportSource,
+    ) -> std::io::Result<String> {
+        let name = join_name(folder.trim_matches('/'), REPORT_NOTE);
+        write_note(notes_root, &name, &self.to_markdown(source))?;
+        Ok(name)
+    }
+
+    /// Informe en markdown, agrupado por nota
+    pub fn to_markdown(&self, source: ImportSource) -> String {
+        let mut out = format!(
+            "# Importación de {}\n\n- Notas importadas: {}\n- Adjuntos copiados: {}\n",
+            source.name(),
+            self.notes.len(),
+            self.attachments
+        );
+        if !self.bases.is_empty() {
+            out.push_str(&format!("- Bases creadas: {}\n\n", self.bases.len()));
+            for base in &self.bases {
+                out.push_str(&format!("```base name=\"{}\"\n```\n\n", base.name));
+            }
+        }
+        if self.issues.is_empty() {
+            out.push_str("\nTodo se ha convertido sin avisos.\n");
+            return out;
+        }
+
+        out.push_str(&format!("\n## Sin convertir ({})\n", self.issues.len()));
+        let mut current = None;
+        for issue in &self.issues {
+            if current != Some(&issue.note) {
+                out.push_str(&format!("\n### {}\n\n", issue.note));
+                current = Some(&issue.note);
+            }
+            out.push_str(&format!("- {}\n", issue.message));
+        }
+        out
+    }
+}
+
+/// Copia de adjuntos a la carpeta de assets
+///
+/// Los nombres se limpian (sin espacios, que el editor no admite en rutas de
+/// imagen) y no se pisan ficheros existentes; si ya hay uno con el mismo nombre y
+/// contenido se reutiliza, así que repetir una importación no duplica adjuntos.
+pub struct AssetStore {
+    dir: PathBuf,
+    copied: HashMap<PathBuf, PathBuf>,
+    written: usize,
+}
+
+impl AssetStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            copied: HashMap::new(),
+            written: 0,
+        }
+    }
+
+    /// Ficheros nuevos escritos en la carpeta de assets
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Si el fichero de origen ya se copió
+    pub fn contains(&self, source: &Path) -> bool {
+        self.copied.contains_key(source)
+    }
+
+    /// Copiar un fichero (una sola vez por origen) y devolver su ruta en assets
+    pub fn copy(&mut self, source: &Path) -> std::io::Result<PathBuf> {
+        if let Some(path) = self.copied.get(source) {
+            return Ok(path.clone());
+        }
+        let name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "adjunto".to_string());
+        let data = std::fs::read(source)?;
+        let path = self.store(&name, &data)?;
+        self.copied.insert(source.to_path_buf(), path.clone());
+        Ok(path)
+    }
+
+    /// Guardar datos ya decodificados con el nombre indicado
+    pub fn store(&mut self, name: &str, data: &[u8]) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let name = asset_file_name(name);
+        let (stem, extension) = match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+            _ => (name.as_str(), None),
+        };
+
+        let checksum = crc32(data);
+        let mut counter = 0;
+        loop {
+            let candidate = match (counter, extension) {
+                (0, _) => name.clone(),
+                (n, Some(extension)) => format!("{}_{}.{}", stem, n, extension),
+                (n, None) => format!("{}_{}", stem, n),
+            };
+            let path = self.dir.join(&candidate);
+            match std::fs::read(&path) {
+                Ok(existing) if existing.len() == data.len() && crc32(&existing) == checksum => {
+                    return Ok(path);
+                }
+                Ok(_) => counter += 1,
+                Err(_) => {
+                    std::fs::write(&path, data)?;
+                    self.written += 1;
+                    return Ok(path);
+                }
+            }
+        }
+    }
+}
+
+/// Nombre de fichero apto para la carpeta de assets
+fn asset_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            ' ' | '/' | '\\' | '(' | ')' | '[' | ']' | '<' | '>' | '#' | '?' | '%' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let name = name.trim_matches(|c| c == '_' || c == '.');
+    if name.is_empty() {
+        "adjunto".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Nombre de nota válido como fichero: sin separadores ni caracteres reservados
+pub fn sanitize_note_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let name = name.trim().trim_start_matches('.').trim();
+    if name.is_empty() {
+        "Sin título".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Escribir una nota (`Carpeta/Nota`) bajo la raíz del vault
+pub fn write_note(notes_root: &Path, name: &str, content: &str) -> std::io::Result<PathBuf> {
+    let path = notes_root.join(format!("{}.md", name));
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Carpeta de origen: la indicada o, si es un `.zip`, una carpeta temporal con
+/// su contenido que se borra al terminar
+pub(crate) struct SourceDir {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl SourceDir {
+    /// Abrir la carpeta o extraer el ZIP (y los ZIP que contenga en su raíz,
+    /// como las partes `Part-1.zip` de las exportaciones grandes)
+    pub(crate) fn open(path: &Path) -> Result<Self, ImportError> {
+        if path.is_dir() {
+            return Ok(Self {
+                path: path.to_path_buf(),
+                temporary: false,
+            });
+        }
+        let is_zip = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+        if !path.is_file() || !is_zip {
+            return Err(ImportError::SourceNotFound(path.to_path_buf()));
+        }
+
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let dir =
+            std::env::temp_dir().join(format!("notnative-import-{}-{}", std::process::id(), stamp));
+        // Desde aquí la carpeta se borra aunque falle la extracción
+        let source = Self {
+            path: dir.clone(),
+            temporary: true,
+        };
+        // Un único presupuesto para el ZIP y los anidados que contenga
+        let mut budget = MAX_EXTRACTED_SIZE;
+        for extracted in ZipReader::open(path)?.extract_to(&dir, &mut budget)? {
+            let nested = extracted.parent() == Some(dir.as_path())
+                && extracted
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+            if nested {
+                ZipReader::open(&extracted)?.extract_to(&dir, &mut budget)?;
+                std::fs::remove_file(&extracted)?;
+            }
+        }
+        Ok(source)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SourceDir {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// Clave del frontmatter con la identidad de la nota en su origen
+pub(crate) const IMPORT_ID_KEY: &str = "import_id";
+
+/// Nombre para una nota importada (`Carpeta/Título`, `Carpeta/Título 2`...) que
+/// no pisa notas distintas. Una nota existente con el mismo `import_id` en el
+/// frontmatter es la misma nota de una importación anterior: se reutiliza su
+/// nombre, así que repetir la importación la sobrescribe en vez de duplicarla.
+pub(crate) fn claim_note_name(
+    notes_root: &Path,
+    folder: &str,
+    title: &str,
+    import_id: &str,
+    used: &mut HashSet<String>,
+) -> String {
+    let base = join_name(folder, &sanitize_note_name(title));
+    for counter in 1.. {
+        let candidate = match counter {
+            1 => base.clone(),
+            n => format!("{} {}", base, n),
+        };
+        if used.contains(&candidate.to_lowercase()) {
+            continue;
+        }
+        let free = match std::fs::read_to_string(notes_root.join(format!("{}.md", candidate))) {
+            Err(_) => true,
+            Ok(content) => {
+                frontmatter_string(&content, IMPORT_ID_KEY).as_deref() == Some(import_id)
+            }
+        };
+        if free {
+            used.insert(candidate.to_lowercase());
+            return candidate;
+        }
+    }
+    unreachable!("la búsqueda de nombre no tiene límite")
+}
+
+/// Valor de texto de una clave del frontmatter
+fn frontmatter_string(content: &str, key: &str) -> Option<String> {
+    let (yaml, _) = split_frontmatter(content)?;
+    match serde_yaml::from_str::<serde_yaml::Value>(yaml)
+        .ok()?
+        .get(key)?
+    {
+        serde_yaml::Value::String(text) => Some(text.clone()),
+        other => serde_yaml::to_string(other)
+            .ok()
+            .map(|text| text.trim().to_string()),
+    }
+}
+
+/// Fecha para `created`/`updated` en el frontmatter (`2024-01-05T10:30:00Z`)
+pub(crate) fn frontmatter_timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Poner como fecha de modificación del fichero la de la nota original
+pub(crate) fn set_modified(path: &Path, time: chrono::DateTime<chrono::Utc>) {
+    if let Ok(file) = std::fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(time.into());
+    }
+}
+
+/// Etiqueta como tag de NotNative: sin `#` y con guiones en vez de espacios
+pub(crate) fn tag_name(label: &str) -> String {
+    label
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Ficheros de una carpeta (recursivo), sin entradas ocultas como `.obsidian`
+pub(crate) fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            !path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with('.'))
+        })
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Nombre de campo como clave de propiedad (`Fecha límite` → `fecha_límite`)
+pub(crate) fn property_key(key: &str) -> Option<String> {
+    let key: String = key
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .to_lowercase();
+    let key = key.trim_matches('_').to_string();
+    key.chars()
+        .next()
+        .filter(|c| c.is_alphabetic())
+        .map(|_| key.clone())
+}
+
+/// Separar el frontmatter YAML (sin los `---`) del cuerpo
+pub(crate) fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Carpeta de una ruta relativa (`""` en la raíz)
+pub(crate) fn parent_name(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+/// Resolver `.` y `..` de una ruta relativa con `/`
+pub(crate) fn normalize(path: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            _ => {}
+        }
+    }
+    parts.join("/")
+}
+
+/// Unir la carpeta de destino y un nombre relativo (`""` = raíz del vault, o la
+/// propia carpeta si el nombre es vacío)
+pub(crate) fn join_name(folder: &str, name: &str) -> String {
+    if folder.is_empty() {
+        name.to_string()
+    } else if name.is_empty() {
+        folder.to_string()
+    } else {
+        format!("{}/{}", folder, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_store_reuses_identical_files() {
+        let dir = std::env::temp_dir().join("notnative_import_assets_test");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut assets = AssetStore::new(&dir);
+        let first = assets.store("mi foto.png", b"uno").unwrap();
+        assert_eq!(first, dir.join("mi_foto.png"));
+        // Mismo nombre y contenido: se reutiliza; distinto contenido: nombre nuevo
+        assert_eq!(assets.store("mi foto.png", b"uno").unwrap(), first);
+        assert_eq!(
+            assets.store("mi foto.png", b"dos").unwrap(),
+            dir.join("mi_foto_1.png")
+        );
+        assert_eq!(assets.written(), 2);
+
+        assert_eq!(sanitize_note_name("a/b: c?"), "a-b- c-");
+        assert_eq!(sanitize_note_name("  "), "Sin título");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
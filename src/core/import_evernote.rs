@@ -0,0 +1,1088 @@
+//! Importador de exportaciones de Evernote (`.enex`)
+//!
+//! Cada fichero `.enex` es una libreta y pasa a una carpeta con su nombre; se
+//! puede importar un fichero o una carpeta con varios. El contenido ENML se
+//! convierte a markdown:
+//!
+//! - Párrafos, encabezados, listas, citas, tablas, enlaces, negrita, cursiva y
+//!   tachado
+//! - Casillas (`<en-todo>` y las listas de tareas nuevas) a `- [ ]` / `- [x]`
+//! - Bloques de código de Evernote a bloques ```` ``` ````
+//! - Los recursos (`<resource>`, en base64) se guardan en la carpeta de assets y
+//!   cada `<en-media>` pasa a la imagen o al enlace al fichero
+//! - Los enlaces `evernote:///` a notas importadas pasan a `[[enlaces]]`
+//!
+//! Las etiquetas pasan a `tags` y las fechas de creación y modificación a
+//! `created`/`updated` en el frontmatter, junto a un `import_id` que reconoce
+//! la nota al repetir la importación. El contenido cifrado queda en el informe.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use regex::{Captures, Regex};
+use serde_yaml::{Mapping, Value};
+
+use super::export::base64_decode;
+use super::import::{
+    AssetStore, IMPORT_ID_KEY, ImportError, ImportReport, claim_note_name, collect_files,
+    frontmatter_timestamp, join_name, sanitize_note_name, set_modified, tag_name, write_note,
+};
+use super::notes_config::NotesConfig;
+use super::wikilink::WikiLink;
+
+/// Regex para las entidades XML/HTML (`&amp;`, `&#233;`, `&#xE9;`)
+static ENTITY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[xX][0-9a-fA-F]+|#[0-9]+|[A-Za-z]+);").unwrap());
+
+/// Regex para los atributos de una etiqueta
+static ATTRIBUTE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([\w:.-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap()
+});
+
+/// Carpeta de destino por defecto
+const DEFAULT_FOLDER: &str = "Evernote";
+
+// ==================== XML ====================
+
+/// Evento de la lectura de un documento XML
+#[derive(Debug, PartialEq)]
+enum XmlEvent {
+    Start {
+        name: String,
+        attributes: Vec<(String, String)>,
+        empty: bool,
+    },
+    End(String),
+    Text(String),
+}
+
+/// Lector XML mínimo para ENEX y ENML: etiquetas, atributos, texto, CDATA y
+/// entidades (los comentarios, `<?...?>` y `<!DOCTYPE>` se saltan)
+struct XmlReader<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> XmlReader<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, position: 0 }
+    }
+}
+
+impl Iterator for XmlReader<'_> {
+    type Item = XmlEvent;
+
+    fn next(&mut self) -> Option<XmlEvent> {
+        loop {
+            let rest = &self.input[self.position..];
+            if rest.is_empty() {
+                return None;
+            }
+            if !rest.starts_with('<') {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.position += end;
+                return Some(XmlEvent::Text(decode_entities(&rest[..end])));
+            }
+            if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let end = cdata.find("]]>").unwrap_or(cdata.len());
+                self.position = (self.position + 9 + end + 3).min(self.input.len());
+                return Some(XmlEvent::Text(cdata[..end].to_string()));
+            }
+            let skip_to = if rest.starts_with("<!--") {
+                Some("-->")
+            } else if rest.starts_with("<?") {
+                Some("?>")
+            } else if rest.starts_with("<!") {
+                Some(">")
+            } else {
+                None
+            };
+            if let Some(close) = skip_to {
+                self.position += rest.find(close).map_or(rest.len(), |i| i + close.len());
+                continue;
+            }
+
+            // Fin de la etiqueta, sin contar los `>` de los valores entre comillas
+            let mut quote = None;
+            let end = rest.char_indices().skip(1).find_map(|(i, c)| {
+                match (quote, c) {
+                    (None, '"' | '\'') => quote = Some(c),
+                    (Some(open), _) if open == c => quote = None,
+                    (None, '>') => return Some(i),
+                    _ => {}
+                }
+                None
+            });
+            let Some(end) = end else {
+                self.position = self.input.len();
+                return None;
+            };
+            let tag = rest[1..end].trim();
+            self.position += end + 1;
+
+            if let Some(name) = tag.strip_prefix('/') {
+                return Some(XmlEvent::End(name.trim().to_lowercase()));
+            }
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+            let attributes = ATTRIBUTE_RE
+                .captures_iter(&tag[name_end..])
+                .map(|caps| {
+                    let value = caps.get(2).or(caps.get(3)).or(caps.get(4));
+                    (
+                        caps[1].to_lowercase(),
+                        decode_entities(value.map_or("", |m| m.as_str())),
+                    )
+                })
+                .collect();
+            return Some(XmlEvent::Start {
+                name: tag[..name_end].to_lowercase(),
+                attributes,
+                empty,
+            });
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    ENTITY_RE
+        .replace_all(text, |caps: &Captures| {
+            let entity = &caps[1];
+            let code = match entity.strip_prefix('#') {
+                Some(number) => match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                },
+                None => match entity {
+                    "amp" => Some('&' as u32),
+                    "lt" => Some('<' as u32),
+                    "gt" => Some('>' as u32),
+                    "quot" => Some('"' as u32),
+                    "apos" => Some('\'' as u32),
+                    "nbsp" => Some(' ' as u32),
+                    _ => None,
+                },
+            };
+            code.and_then(char::from_u32)
+                .map(String::from)
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+// ==================== ENEX ====================
+
+#[derive(Debug, Default)]
+struct EnexResource {
+    data: String,
+    mime: String,
+    file_name: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct EnexNote {
+    guid: Option<String>,
+    title: String,
+    created: Option<DateTime<Utc>>,
+    updated: Option<DateTime<Utc>>,
+    tags: Vec<String>,
+    author: Option<String>,
+    source_url: Option<String>,
+    content: String,
+    resources: Vec<EnexResource>,
+}
+
+impl EnexNote {
+    /// Identidad de la nota: el `<guid>` si la exportación lo trae; si no, el MD5
+    /// del título, la fecha de creación y el contenido
+    fn import_id(&self) -> String {
+        match &self.guid {
+            Some(guid) => format!("evernote:{}", guid),
+            None => {
+                let created = self.created.map(frontmatter_timestamp).unwrap_or_default();
+                let key = format!("{}\n{}\n{}", self.title, created, self.content);
+                format!("evernote:{}", md5_hex(key.as_bytes()))
+            }
+        }
+    }
+}
+
+/// Fecha de Evernote (`20240105T103000Z`)
+fn parse_enex_date(text: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(text.trim(), "%Y%m%dT%H%M%SZ")
+        .ok()
+        .map(|datetime| datetime.and_utc())
+}
+
+/// Notas de un fichero `.enex`
+fn parse_enex(text: &str) -> Vec<EnexNote> {
+    let mut notes = Vec::new();
+    let mut note: Option<EnexNote> = None;
+    let mut resource: Option<EnexResource> = None;
+    let mut buffer = String::new();
+    for event in XmlReader::new(text) {
+        let name = match event {
+            XmlEvent::Start { name, empty, .. } => {
+                match name.as_str() {
+                    "note" => note = Some(EnexNote::default()),
+                    "resource" => resource = Some(EnexResource::default()),
+                    _ => {}
+                }
+                buffer.clear();
+                if !empty {
+                    continue;
+                }
+                name
+            }
+            XmlEvent::Text(text) => {
+                buffer.push_str(&text);
+                continue;
+            }
+            XmlEvent::End(name) => name,
+        };
+        let value = std::mem::take(&mut buffer);
+
+        if let Some(current) = resource.as_mut() {
+            match name.as_str() {
+                "data" => current.data = value,
+                "mime" => current.mime = value.trim().to_string(),
+                "file-name" => current.file_name = Some(value.trim().to_string()),
+                "resource" => {
+                    if let (Some(done), Some(note)) = (resource.take(), note.as_mut()) {
+                        note.resources.push(done);
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        let Some(current) = note.as_mut() else {
+            continue;
+        };
+        match name.as_str() {
+            "guid" if !value.trim().is_empty() => current.guid = Some(value.trim().to_string()),
+            "title" => current.title = value.trim().to_string(),
+            "created" => current.created = parse_enex_date(&value),
+            "updated" => current.updated = parse_enex_date(&value),
+            "tag" if !value.trim().is_empty() => current.tags.push(value.trim().to_string()),
+            "author" if !value.trim().is_empty() => current.author = Some(value.trim().to_string()),
+            "source-url" if !value.trim().is_empty() => {
+                current.source_url = Some(value.trim().to_string())
+            }
+            "content" => current.content = value,
+            "note" => notes.extend(note.take()),
+            _ => {}
+        }
+    }
+    notes
+}
+
+/// MD5 en hexadecimal: Evernote identifica cada recurso por el MD5 de sus datos
+fn md5_hex(data: &[u8]) -> String {
+    use md5::{Digest, Md5};
+    format!("{:x}", Md5::digest(data))
+}
+
+// ==================== IMPORTADOR ====================
+
+/// Importador de libretas de Evernote (un `.enex` o una carpeta de ellos)
+pub struct EvernoteImporter {
+    path: PathBuf,
+    notes_root: PathBuf,
+    folder: String,
+    assets_dir: PathBuf,
+}
+
+impl EvernoteImporter {
+    pub fn new(path: impl Into<PathBuf>, notes_root: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            notes_root: notes_root.into(),
+            folder: DEFAULT_FOLDER.to_string(),
+            assets_dir: NotesConfig::assets_dir(),
+        }
+    }
+
+    /// Carpeta del workspace donde se crean las libretas (`""` = raíz)
+    pub fn with_folder(mut self, folder: impl Into<String>) -> Self {
+        self.folder = folder.into().trim_matches('/').to_string();
+        self
+    }
+
+    pub fn with_assets_dir(mut self, assets_dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = assets_dir.into();
+        self
+    }
+
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    /// Importar las libretas; repetirlo sobrescribe las notas ya importadas
+    pub fn import(&self) -> Result<ImportReport, ImportError> {
+        let files = if self.path.is_dir() {
+            let mut files = Vec::new();
+            collect_files(&self.path, &mut files)?;
+            files.retain(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("enex"))
+            });
+            files
+        } else if self.path.is_file() {
+            vec![self.path.clone()]
+        } else {
+            return Err(ImportError::SourceNotFound(self.path.clone()));
+        };
+
+        // Primero los nombres de todas las notas, para resolver los enlaces entre
+        // ellas (también entre libretas)
+        let mut used = HashSet::new();
+        let mut notes = Vec::new();
+        let mut by_title: HashMap<String, String> = HashMap::new();
+        for file in &files {
+            let notebook = file
+                .file_stem()
+                .map(|stem| sanitize_note_name(&stem.to_string_lossy()))
+                .unwrap_or_default();
+            let folder = join_name(&self.folder, &notebook);
+            for note in parse_enex(&std::fs::read_to_string(file)?) {
+                let title = match note.title.as_str() {
+                    "" => "Sin título",
+                    title => title,
+                };
+                let name = claim_note_name(
+                    &self.notes_root,
+                    &folder,
+                    title,
+                    &note.import_id(),
+                    &mut used,
+                );
+                by_title
+                    .entry(note.title.to_lowercase())
+                    .or_insert_with(|| name.clone());
+                notes.push((name, note));
+            }
+        }
+
+        let mut report = ImportReport::default();
+        let mut assets = AssetStore::new(&self.assets_dir);
+        for (name, note) in &notes {
+            // Recursos por MD5, que es como los referencia `<en-media hash>`
+            let mut resources = HashMap::new();
+            for resource in &note.resources {
+                let Some(data) = base64_decode(&resource.data) else {
+                    report.warn(name, "Recurso con base64 no válido");
+                    continue;
+                };
+                let file_name = resource.file_name.clone().unwrap_or_else(|| {
+                    let extension = resource.mime.rsplit('/').next().unwrap_or("bin");
+                    format!("evernote.{}", extension)
+                });
+                match assets.store(&file_name, &data) {
+                    Ok(path) => {
+                        let image = resource.mime.starts_with("image/");
+                        resources.insert(md5_hex(&data), (path, file_name, image));
+                    }
+                    Err(e) => report.warn(name, format!("No se pudo guardar {}: {}", file_name, e)),
+                }
+            }
+
+            let mut converter = EnmlConverter::new(name, &resources, &by_title, &mut report);
+            converter.convert(&note.content);
+            let body = converter.finish();
+            let content = format!("{}{}", frontmatter(note), body);
+            let path = write_note(&self.notes_root, name, &content)?;
+            if let Some(updated) = note.updated.or(note.created) {
+                set_modified(&path, updated);
+            }
+            report.notes.push(name.clone());
+        }
+        report.attachments = assets.written();
+        Ok(report)
+    }
+}
+
+fn frontmatter(note: &EnexNote) -> String {
+    let mut map = Mapping::new();
+    if !note.tags.is_empty() {
+        let tags = note.tags.iter().map(|tag| Value::from(tag_name(tag)));
+        map.insert(Value::from("tags"), Value::Sequence(tags.collect()));
+    }
+    let fields = [
+        ("created", note.created.map(frontmatter_timestamp)),
+        ("updated", note.updated.map(frontmatter_timestamp)),
+        ("author", note.author.clone()),
+        ("source_url", note.source_url.clone()),
+        (IMPORT_ID_KEY, Some(note.import_id())),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            map.insert(Value::from(key), Value::from(value));
+        }
+    }
+    match serde_yaml::to_string(&Value::Mapping(map)) {
+        Ok(yaml) if yaml.trim() != "{}" => format!("---\n{}---\n", yaml),
+        _ => String::new(),
+    }
+}
+
+// ==================== ENML → MARKDOWN ====================
+
+/// Lo que hay que hacer al cerrar cada elemento
+enum Frame {
+    None,
+    /// Bloque: saltos de línea antes y después
+    Block(usize),
+    Heading,
+    Inline(&'static str),
+    Link(Option<String>, usize),
+    List,
+    Item,
+    Quote,
+    Code,
+    Table,
+    Row,
+    Cell,
+    Skip,
+}
+
+struct List {
+    ordered: bool,
+    todo: bool,
+    counter: usize,
+    /// Ancho del marcador (sangría de las líneas de continuación)
+    width: usize,
+}
+
+/// Conversión del contenido ENML de una nota
+struct EnmlConverter<'a> {
+    name: &'a str,
+    /// MD5 → (fichero en assets, nombre original, si es imagen)
+    resources: &'a HashMap<String, (PathBuf, String, bool)>,
+    /// Título en minúsculas → nota importada
+    by_title: &'a HashMap<String, String>,
+    report: &'a mut ImportReport,
+    out: String,
+    /// Saltos de línea pendientes antes del siguiente contenido
+    pending: usize,
+    line_started: bool,
+    quote: usize,
+    lists: Vec<List>,
+    /// Marcador del elemento de lista que empieza en la siguiente línea
+    marker: Option<String>,
+    code: bool,
+    /// Tablas abiertas (las anidadas se aplanan en la celda)
+    tables: usize,
+    rows: Vec<Vec<String>>,
+    cell: Option<String>,
+    skip: usize,
+    stack: Vec<Frame>,
+}
+
+impl<'a> EnmlConverter<'a> {
+    fn new(
+        name: &'a str,
+        resources: &'a HashMap<String, (PathBuf, String, bool)>,
+        by_title: &'a HashMap<String, String>,
+        report: &'a mut ImportReport,
+    ) -> Self {
+        Self {
+            name,
+            resources,
+            by_title,
+            report,
+            out: String::new(),
+            pending: 0,
+            line_started: false,
+            quote: 0,
+            lists: Vec::new(),
+            marker: None,
+            code: false,
+            tables: 0,
+            rows: Vec::new(),
+            cell: None,
+            skip: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    fn convert(&mut self, enml: &str) {
+        for event in XmlReader::new(enml) {
+            match event {
+                XmlEvent::Start {
+                    name,
+                    attributes,
+                    empty,
+                } => {
+                    let frame = self.start(&name, &attributes);
+                    if empty {
+                        self.end(frame);
+                    } else {
+                        self.stack.push(frame);
+                    }
+                }
+                XmlEvent::End(_) => {
+                    if let Some(frame) = self.stack.pop() {
+                        self.end(frame);
+                    }
+                }
+                XmlEvent::Text(text) => self.text(&text),
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        while let Some(frame) = self.stack.pop() {
+            self.end(frame);
+        }
+        let mut out = self.out.trim_end().to_string();
+        out.push('\n');
+        if out.trim().is_empty() {
+            String::new()
+        } else {
+            out
+        }
+    }
+
+    /// Pedir al menos `lines` saltos de línea antes del siguiente contenido
+    fn block_break(&mut self, lines: usize) {
+        if self.cell.is_some() {
+            self.write(" ");
+            return;
+        }
+        if self.line_started {
+            self.line_started = false;
+            self.pending = self.pending.max(lines);
+        } else if !self.out.is_empty() {
+            self.pending = self.pending.max(lines);
+        }
+    }
+
+    /// Escribir los saltos pendientes y el prefijo de la línea (cita, lista)
+    fn begin_line(&mut self) {
+        if self.line_started {
+            return;
+        }
+        if !self.out.is_empty() {
+            for line in 0..self.pending.max(1) {
+                if line > 0 && self.quote > 0 {
+                    self.out.push_str(&">".repeat(self.quote));
+                }
+                self.out.push('\n');
+            }
+        }
+        self.pending = 0;
+        self.line_started = true;
+
+        self.out.push_str(&"> ".repeat(self.quote));
+        if let Some((last, outer)) = self.lists.split_last_mut() {
+            let indent: usize = outer.iter().map(|list| list.width).sum();
+            self.out.push_str(&" ".repeat(indent));
+            match self.marker.take() {
+                Some(marker) => {
+                    last.width = if last.ordered { marker.len() } else { 2 };
+                    self.out.push_str(&marker);
+                }
+                None => self.out.push_str(&" ".repeat(last.width)),
+            }
+        }
+    }
+
+    /// Texto en la celda abierta o en la línea actual
+    fn write(&mut self, text: &str) {
+        match self.cell.as_mut() {
+            Some(cell) => cell.push_str(text),
+            None => {
+                self.begin_line();
+                self.out.push_str(text);
+            }
+        }
+    }
+
+    /// Texto escrito hasta ahora en la celda o el documento (para los enlaces)
+    fn written(&self) -> usize {
+        self.cell.as_ref().map_or(self.out.len(), String::len)
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip > 0 {
+            return;
+        }
+        if self.code && self.cell.is_none() {
+            for (i, line) in text.split('\n').enumerate() {
+                if i > 0 {
+                    self.block_break(1);
+                }
+                if !line.is_empty() {
+                    self.write(line);
+                }
+            }
+            return;
+        }
+
+        // Espacios como en HTML: cada secuencia cuenta como uno
+        let mut collapsed = String::with_capacity(text.len());
+        for (i, word) in text.split_whitespace().enumerate() {
+            if i > 0 {
+                collapsed.push(' ');
+            }
+            collapsed.push_str(word);
+        }
+        let line_start = match &self.cell {
+            Some(cell) => cell.is_empty() || cell.ends_with(' '),
+            None => !self.line_started || self.out.ends_with(' '),
+        };
+        if text.starts_with(char::is_whitespace) && !line_start && !collapsed.is_empty() {
+            collapsed.insert(0, ' ');
+        }
+        if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+        if collapsed.is_empty() {
+            if !text.is_empty() && !line_start {
+                self.write(" ");
+            }
+            return;
+        }
+        self.write(&collapsed);
+    }
+
+    fn start(&mut self, name: &str, attributes: &[(String, String)]) -> Frame {
+        if self.skip > 0 {
+            self.skip += 1;
+            return Frame::Skip;
+        }
+        let style = attribute(attributes, "style").unwrap_or_default();
+        match name {
+            "en-crypt" => {
+                self.report
+                    .warn(self.name, "Contenido cifrado de Evernote no importado");
+                self.skip += 1;
+                Frame::Skip
+            }
+            "style" | "script" | "title" | "head" => {
+                self.skip += 1;
+                Frame::Skip
+            }
+            "div" if style.contains("-en-codeblock:true") && !self.code => self.code_start(),
+            "pre" if !self.code => self.code_start(),
+            "div" | "p" | "section" | "article" | "header" | "footer" | "center" => {
+                let lines = if name == "div" || self.code { 1 } else { 2 };
+                self.block_break(lines);
+                Frame::Block(lines)
+            }
+            "br" => {
+                match self.cell.as_mut() {
+                    Some(cell) => cell.push(' '),
+                    None if self.line_started => {
+                        self.line_started = false;
+                        self.pending = 1;
+                    }
+                    None => self.pending += 1,
+                }
+                Frame::None
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block_break(2);
+                let level = name[1..].parse().unwrap_or(1);
+                self.write(&format!("{} ", "#".repeat(level)));
+                Frame::Heading
+            }
+            "b" | "strong" => self.inline("**"),
+            "i" | "em" => self.inline("*"),
+            "s" | "strike" | "del" => self.inline("~~"),
+            "code" if !self.code => self.inline("`"),
+            "span" if style.contains("font-weight: bold") || style.contains("font-weight:bold") => {
+                self.inline("**")
+            }
+            "a" => {
+                let href = attribute(attributes, "href").map(str::to_string);
+                if self.cell.is_none() {
+                    self.begin_line();
+                }
+                Frame::Link(href, self.written())
+            }
+            "ul" | "ol" => {
+                self.block_break(if self.lists.is_empty() { 2 } else { 1 });
+                self.lists.push(List {
+                    ordered: name == "ol",
+                    todo: style.contains("--en-todo:true"),
+                    counter: 0,
+                    width: 2,
+                });
+                Frame::List
+            }
+            "li" => {
+                self.block_break(1);
+                if let Some(list) = self.lists.last_mut() {
+                    list.counter += 1;
+                    self.marker = Some(if list.todo {
+                        let checked = style.contains("--en-checked:true");
+                        format!("- [{}] ", if checked { "x" } else { " " })
+                    } else if list.ordered {
+                        format!("{}. ", list.counter)
+                    } else {
+                        "- ".to_string()
+                    });
+                }
+                Frame::Item
+            }
+            "en-todo" => {
+                let checked = attribute(attributes, "checked") == Some("true");
+                let checkbox = format!("[{}] ", if checked { "x" } else { " " });
+                match self.marker.as_mut() {
+                    // Casilla al principio de un elemento de lista: `- [ ] `
+                    Some(marker) if marker == "- " => marker.push_str(&checkbox),
+                    _ if !self.line_started && self.cell.is_none() => {
+                        self.write(&format!("- {}", checkbox))
+                    }
+                    _ => self.write(&checkbox),
+                }
+                Frame::None
+            }
+            "blockquote" => {
+                self.block_break(2);
+                self.quote += 1;
+                Frame::Quote
+            }
+            "hr" => {
+                self.block_break(2);
+                self.write("---");
+                self.block_break(2);
+                Frame::None
+            }
+            "en-media" => {
+                let hash = attribute(attributes, "hash").unwrap_or_default();
+                match self.resources.get(&hash.to_lowercase()) {
+                    Some((path, file_name, image)) => {
+                        let label = Path::new(file_name)
+                            .file_stem()
+                            .map(|stem| stem.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        let bang = if *image { "!" } else { "" };
+                        self.write(&format!("{}[{}]({})", bang, label, path.display()));
+                    }
+                    None => self.report.warn(
+                        self.name,
+                        format!("Recurso no encontrado en la exportación: {}", hash),
+                    ),
+                }
+                Frame::None
+            }
+            "img" => {
+                let alt = attribute(attributes, "alt").unwrap_or_default();
+                if let Some(src) = attribute(attributes, "src") {
+                    self.write(&format!("![{}]({})", alt, src));
+                }
+                Frame::None
+            }
+            "table" => {
+                self.tables += 1;
+                if self.tables == 1 {
+                    self.block_break(2);
+                    self.rows.clear();
+                }
+                Frame::Table
+            }
+            "tr" if self.tables == 1 => {
+                self.rows.push(Vec::new());
+                Frame::Row
+            }
+            "td" | "th" if self.tables == 1 => {
+                self.cell = Some(String::new());
+                Frame::Cell
+            }
+            _ => Frame::None,
+        }
+    }
+
+    fn code_start(&mut self) -> Frame {
+        self.block_break(2);
+        self.write("```");
+        self.block_break(1);
+        self.code = true;
+        Frame::Code
+    }
+
+    fn inline(&mut self, marker: &'static str) -> Frame {
+        self.write(marker);
+        Frame::Inline(marker)
+    }
+
+    fn end(&mut self, frame: Frame) {
+        match frame {
+            Frame::None => {}
+            Frame::Skip => self.skip -= 1,
+            Frame::Block(lines) => self.block_break(lines),
+            Frame::Heading => self.block_break(2),
+            Frame::Inline(marker) => self.write(marker),
+            Frame::Link(href, start) => self.link(href, start),
+            Frame::List => {
+                self.lists.pop();
+                self.marker = None;
+                self.block_break(if self.lists.is_empty() { 2 } else { 1 });
+            }
+            Frame::Item => self.block_break(1),
+            Frame::Quote => {
+                self.block_break(2);
+                self.quote = self.quote.saturating_sub(1);
+            }
+            Frame::Code => {
+                self.code = false;
+                self.block_break(1);
+                self.write("```");
+                self.block_break(2);
+            }
+            Frame::Table => {
+                self.tables -= 1;
+                if self.tables == 0 {
+                    self.table();
+                }
+            }
+            Frame::Row => {}
+            Frame::Cell => {
+                if let Some(cell) = self.cell.take() {
+                    let cell = cell.trim().replace('|', "\\|");
+                    if let Some(row) = self.rows.last_mut() {
+                        row.push(cell);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Cerrar un enlace: `[texto](url)`, `[[Nota]]` si apunta a una nota
+    /// importada, o solo el texto
+    fn link(&mut self, href: Option<String>, start: usize) {
+        let buffer = self.cell.as_mut().unwrap_or(&mut self.out);
+        let start = start.min(buffer.len());
+        let text = buffer[start..].trim().to_string();
+        let Some(href) = href.filter(|href| !href.is_empty()) else {
+            return;
+        };
+
+        let replacement = if href.starts_with("evernote:") {
+            match self.by_title.get(&text.to_lowercase()) {
+                Some(note) => WikiLink {
+                    note: note.clone(),
+                    target: None,
+                    alias: None,
+                    embed: false,
+                }
+                .to_markdown(),
+                None => {
+                    self.report.warn(
+                        self.name,
+                        format!("Enlace a una nota que no está en la exportación: {}", text),
+                    );
+                    return;
+                }
+            }
+        } else if text.is_empty() || text == href {
+            format!("<{}>", href)
+        } else {
+            format!("[{}]({})", text, href)
+        };
+        let buffer = self.cell.as_mut().unwrap_or(&mut self.out);
+        buffer.truncate(start);
+        buffer.push_str(&replacement);
+    }
+
+    /// Escribir la tabla acumulada (la primera fila es la cabecera)
+    fn table(&mut self) {
+        let rows = std::mem::take(&mut self.rows);
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        for (i, row) in rows.iter().enumerate() {
+            let cells: Vec<&str> = (0..columns)
+                .map(|column| row.get(column).map_or("", String::as_str))
+                .collect();
+            self.write(&format!("| {} |", cells.join(" | ")));
+            self.block_break(1);
+            if i == 0 {
+                self.write(&format!("|{}", " --- |".repeat(columns)));
+                self.block_break(1);
+            }
+        }
+        self.block_break(2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_md5_and_xml_reader() {
+        assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(
+            md5_hex(b"The quick brown fox jumps over the lazy dog"),
+            "9e107d9d372bb6826bd81d3542a419d6"
+        );
+        assert_eq!(md5_hex(&[b'a'; 100]), "36a92cc94a9e0fa21f625f8bfb007adf");
+
+        let events: Vec<XmlEvent> =
+            XmlReader::new("<?xml version=\"1.0\"?><!-- x --><a href='1>2'>T&amp;&#233;<b/></a>")
+                .collect();
+        assert_eq!(
+            events,
+            [
+                XmlEvent::Start {
+                    name: "a".to_string(),
+                    attributes: vec![("href".to_string(), "1>2".to_string())],
+                    empty: false
+                },
+                XmlEvent::Text("T&é".to_string()),
+                XmlEvent::Start {
+                    name: "b".to_string(),
+                    attributes: vec![],
+                    empty: true
+                },
+                XmlEvent::End("a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_enex() {
+        let root = std::env::temp_dir().join("notnative_evernote_test");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        let enml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd">
+<en-note><div>Hola <b>mundo</b> y <a href="https://example.com">un enlace</a>.</div>
+<div><br/></div>
+<div><en-todo checked="true"/>Comprar pan</div><div><en-todo/>Llamar</div>
+<h2>Lista</h2><ul><li>Uno<ul><li>Dos</li></ul></li></ul>
+<ul style="--en-todo:true;"><li style="--en-checked:false;"><div>Tarea nueva</div></li></ul>
+<div style="box-sizing: border-box; -en-codeblock:true;"><div>let x = 1;</div><div>x &lt; 2</div></div>
+<table><tr><td>A</td><td>B</td></tr><tr><td>1</td><td>2 | 3</td></tr></table>
+<div><en-media hash="bff139fa05ac583f685a523ab3d110a0" type="image/png"/></div>
+<div>Ver <a href="evernote:///view/1/s1/abc/abc/">Otra nota</a></div>
+<en-crypt>secreto</en-crypt></en-note>"#;
+        let enex = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export4.dtd">
+<en-export export-date="20240110T120000Z" application="Evernote" version="10.0">
+<note><title>Primera</title><created>20240105T103000Z</created><updated>20240106T090000Z</updated>
+<tag>trabajo</tag><tag>ideas sueltas</tag>
+<note-attributes><source-url>https://example.com/a</source-url></note-attributes>
+<content><![CDATA[{}]]></content>
+<resource><data encoding="base64">
+cG5n
+</data><mime>image/png</mime><resource-attributes><file-name>mi foto.png</file-name></resource-attributes></resource>
+</note>
+<note><title>Otra nota</title><created>20240107T080000Z</created><content><![CDATA[<en-note>Texto</en-note>]]></content></note>
+</en-export>"#,
+            enml
+        );
+        std::fs::write(root.join("Libreta.enex"), &enex).unwrap();
+
+        let notes = root.join("notes");
+        let assets = root.join("assets");
+        let import = || {
+            EvernoteImporter::new(root.join("Libreta.enex"), &notes)
+                .with_assets_dir(&assets)
+                .import()
+                .unwrap()
+        };
+        let report = import();
+        assert_eq!(
+            report.notes,
+            ["Evernote/Libreta/Primera", "Evernote/Libreta/Otra nota"]
+        );
+        assert_eq!(report.attachments, 1);
+        assert_eq!(
+            report
+                .issues
+                .iter()
+                .map(|i| i.message.as_str())
+                .collect::<Vec<_>>(),
+            ["Contenido cifrado de Evernote no importado"]
+        );
+
+        let primera = std::fs::read_to_string(notes.join("Evernote/Libreta/Primera.md")).unwrap();
+        let expected = format!(
+            "---
+tags:
+- trabajo
+- ideas-sueltas
+created: 2024-01-05T10:30:00Z
+updated: 2024-01-06T09:00:00Z
+source_url: https://example.com/a
+import_id: {}
+---
+Hola **mundo** y [un enlace](https://example.com).
+
+- [x] Comprar pan
+- [ ] Llamar
+
+## Lista
+
+- Uno
+  - Dos
+
+- [ ] Tarea nueva
+
+```
+let x = 1;
+x < 2
+```
+
+| A | B |
+| --- | --- |
+| 1 | 2 \\| 3 |
+
+![mi foto]({})
+Ver [[Evernote/Libreta/Otra nota]]
+",
+            parse_enex(&enex)[0].import_id(),
+            assets.join("mi_foto.png").display()
+        );
+        assert_eq!(primera, expected);
+
+        // Repetir la importación no duplica notas ni adjuntos
+        let again = import();
+        assert_eq!(again.notes, report.notes);
+        assert_eq!(again.attachments, 0);
+        // Otra nota distinta con el mismo título no pisa la importada, aunque
+        // coincida su fecha de creación: solo cuenta el import_id
+        std::fs::write(
+            notes.join("Evernote/Libreta/Otra nota.md"),
+            "---\ncreated: 2024-01-07T08:00:00Z\n---\nMía\n",
+        )
+        .unwrap();
+        assert_eq!(import().notes[1], "Evernote/Libreta/Otra nota 2");
+
+        // Sin <created> la nota se reconoce por su import_id
+        std::fs::write(
+            root.join("Sin fecha.enex"),
+            "<en-export><note><title>Suelta</title><content><![CDATA[<en-note>Hola</en-note>]]></content></note></en-export>",
+        )
+        .unwrap();
+        let undated = || {
+            EvernoteImporter::new(root.join("Sin fecha.enex"), &notes)
+                .with_assets_dir(&assets)
+                .import()
+                .unwrap()
+                .notes
+        };
+        assert_eq!(undated(), ["Evernote/Sin fecha/Suelta"]);
+        assert_eq!(undated(), ["Evernote/Sin fecha/Suelta"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,538 @@
+//! Importador de Google Keep (exportación de Google Takeout)
+//!
+//! Takeout guarda cada nota de Keep como un `.json` (con su `.html` al lado,
+//! que se ignora) y los adjuntos en la misma carpeta. El importador:
+//!
+//! - Convierte las listas de Keep en tareas `- [ ]` / `- [x]`
+//! - Pasa las etiquetas a `tags`, y las fechas de creación y edición a
+//!   `created`/`updated` en el frontmatter (además de `pinned` y `color`), con
+//!   el nombre del JSON en `import_id` para reconocer la nota al repetir la importación
+//! - Copia los adjuntos a la carpeta de assets
+//! - Deja las notas archivadas en la subcarpeta `Archivadas` y salta las de la
+//!   papelera
+//!
+//! Acepta la carpeta `Keep` extraída o el `.zip` de Takeout tal cual.
+
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+use super::import::{
+    AssetStore, IMPORT_ID_KEY, ImportError, ImportReport, SourceDir, claim_note_name,
+    collect_files, frontmatter_timestamp, join_name, set_modified, tag_name, write_note,
+};
+use super::notes_config::NotesConfig;
+
+/// Carpeta de destino por defecto
+const DEFAULT_FOLDER: &str = "Google Keep";
+
+/// Subcarpeta de las notas archivadas
+const ARCHIVED_FOLDER: &str = "Archivadas";
+
+/// Longitud máxima del título sacado de la primera línea
+const TITLE_MAX_CHARS: usize = 60;
+
+/// Nota de Keep tal como la exporta Takeout
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct KeepNote {
+    title: String,
+    text_content: Option<String>,
+    list_content: Option<Vec<KeepListItem>>,
+    labels: Vec<KeepLabel>,
+    attachments: Vec<KeepAttachment>,
+    annotations: Vec<KeepAnnotation>,
+    created_timestamp_usec: Option<i64>,
+    user_edited_timestamp_usec: Option<i64>,
+    is_trashed: bool,
+    is_archived: bool,
+    is_pinned: bool,
+    color: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct KeepListItem {
+    text: String,
+    is_checked: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct KeepLabel {
+    name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct KeepAttachment {
+    file_path: String,
+    mimetype: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct KeepAnnotation {
+    url: String,
+    title: String,
+}
+
+impl KeepNote {
+    fn created(&self) -> Option<DateTime<Utc>> {
+        self.created_timestamp_usec
+            .and_then(DateTime::from_timestamp_micros)
+    }
+
+    fn updated(&self) -> Option<DateTime<Utc>> {
+        self.user_edited_timestamp_usec
+            .and_then(DateTime::from_timestamp_micros)
+    }
+
+    /// Título de la nota o, si no tiene, su primera línea (o la fecha)
+    fn display_title(&self) -> String {
+        if !self.title.trim().is_empty() {
+            return self.title.trim().to_string();
+        }
+        let first_line = match (&self.text_content, &self.list_content) {
+            (Some(text), _) if !text.trim().is_empty() => text.trim().lines().next(),
+            (_, Some(items)) => items.first().map(|item| item.text.trim()),
+            _ => None,
+        };
+        match first_line.filter(|line| !line.is_empty()) {
+            Some(line) if line.chars().count() > TITLE_MAX_CHARS => {
+                let cut: String = line.chars().take(TITLE_MAX_CHARS).collect();
+                format!("{}…", cut.trim_end())
+            }
+            Some(line) => line.to_string(),
+            None => match self.created() {
+                Some(created) => format!("Nota de Keep {}", created.format("%Y-%m-%d %H-%M")),
+                None => "Nota de Keep".to_string(),
+            },
+        }
+    }
+}
+
+/// Importador de una exportación de Google Keep (carpeta o `.zip` de Takeout)
+pub struct KeepImporter {
+    path: PathBuf,
+    notes_root: PathBuf,
+    folder: String,
+    assets_dir: PathBuf,
+}
+
+impl KeepImporter {
+    pub fn new(path: impl Into<PathBuf>, notes_root: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            notes_root: notes_root.into(),
+            folder: DEFAULT_FOLDER.to_string(),
+            assets_dir: NotesConfig::assets_dir(),
+        }
+    }
+
+    /// Carpeta del workspace donde se crean las notas (`""` = raíz)
+    pub fn with_folder(mut self, folder: impl Into<String>) -> Self {
+        self.folder = folder.into().trim_matches('/').to_string();
+        self
+    }
+
+    pub fn with_assets_dir(mut self, assets_dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = assets_dir.into();
+        self
+    }
+
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    /// Importar las notas; repetirlo sobrescribe las notas ya importadas
+    pub fn import(&self) -> Result<ImportReport, ImportError> {
+        let source = SourceDir::open(&self.path)?;
+        let root = source.path();
+        let mut files = Vec::new();
+        collect_files(root, &mut files)?;
+        files.retain(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        });
+        // En un Takeout completo, solo los JSON de la carpeta `Keep`
+        let keep_files: Vec<PathBuf> = files
+            .iter()
+            .filter(|path| {
+                path.strip_prefix(root)
+                    .is_ok_and(|relative| relative.components().any(|c| c.as_os_str() == "Keep"))
+            })
+            .cloned()
+            .collect();
+        if !keep_files.is_empty() {
+            files = keep_files;
+        }
+
+        let mut report = ImportReport::default();
+        let mut assets = AssetStore::new(&self.assets_dir);
+        let mut used = HashSet::new();
+        let archived_folder = join_name(&self.folder, ARCHIVED_FOLDER);
+        for file in &files {
+            let source_name = file
+                .strip_prefix(root)
+                .unwrap_or(file)
+                .to_string_lossy()
+                .to_string();
+            let Ok(note) = serde_json::from_str::<KeepNote>(&std::fs::read_to_string(file)?) else {
+                report.warn(source_name, "JSON no reconocido como nota de Keep");
+                continue;
+            };
+            if note.text_content.is_none() && note.list_content.is_none() {
+                // Otros JSON de Takeout (etiquetas, ajustes...)
+                continue;
+            }
+            if note.is_trashed {
+                report.warn(source_name, "Nota en la papelera: no importada");
+                continue;
+            }
+
+            let folder = if note.is_archived {
+                &archived_folder
+            } else {
+                &self.folder
+            };
+            let import_id = import_id(file);
+            let name = claim_note_name(
+                &self.notes_root,
+                folder,
+                &note.display_title(),
+                &import_id,
+                &mut used,
+            );
+
+            let mut body = note_body(&note);
+            let directory = file.parent().unwrap_or(root);
+            for attachment in &note.attachments {
+                match find_attachment(directory, &attachment.file_path) {
+                    Some(path) => match assets.copy(&path) {
+                        Ok(asset) => {
+                            let label = path
+                                .file_stem()
+                                .map(|stem| stem.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            let bang = if attachment.mimetype.starts_with("image/") {
+                                "!"
+                            } else {
+                                ""
+                            };
+                            body.push_str(&format!("\n{}[{}]({})\n", bang, label, asset.display()));
+                        }
+                        Err(e) => report.warn(
+                            &name,
+                            format!("No se pudo copiar {}: {}", attachment.file_path, e),
+                        ),
+                    },
+                    None => report.warn(
+                        &name,
+                        format!("Adjunto no encontrado: {}", attachment.file_path),
+                    ),
+                }
+            }
+
+            let content = format!("{}{}", frontmatter(&note, &import_id), body);
+            let path = write_note(&self.notes_root, &name, &content)?;
+            if let Some(updated) = note.updated().or(note.created()) {
+                set_modified(&path, updated);
+            }
+            report.notes.push(name);
+        }
+        report.attachments = assets.written();
+        Ok(report)
+    }
+}
+
+/// Texto de la nota, con las listas como tareas y los enlaces de Keep al final
+fn note_body(note: &KeepNote) -> String {
+    let mut body = String::new();
+    if let Some(text) = note
+        .text_content
+        .as_deref()
+        .filter(|text| !text.trim().is_empty())
+    {
+        body.push_str(text.trim_end());
+        body.push('\n');
+    }
+    if let Some(items) = &note.list_content {
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        for item in items {
+            let mark = if item.is_checked { "x" } else { " " };
+            body.push_str(&format!("- [{}] {}\n", mark, item.text.trim()));
+        }
+    }
+    let links: Vec<&KeepAnnotation> = note
+        .annotations
+        .iter()
+        .filter(|annotation| !annotation.url.is_empty())
+        .collect();
+    if !links.is_empty() {
+        body.push('\n');
+        for link in links {
+            match link.title.trim() {
+                "" => body.push_str(&format!("- <{}>\n", link.url)),
+                title => body.push_str(&format!("- [{}]({})\n", title, link.url)),
+            }
+        }
+    }
+    body
+}
+
+/// Adjunto junto al JSON. Takeout a veces cambia la extensión (`.jpeg` en el
+/// JSON y `.jpg` en disco), así que si no existe se busca por el nombre sin ella.
+/// Solo se aceptan nombres de fichero sueltos que queden dentro de `directory`
+fn find_attachment(directory: &Path, file_path: &str) -> Option<PathBuf> {
+    let mut components = Path::new(file_path).components();
+    let name = match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) => name,
+        _ => return None,
+    };
+    let inside = |path: &Path| {
+        let root = directory.canonicalize().ok()?;
+        let resolved = path.canonicalize().ok()?;
+        (resolved.is_file() && resolved.parent() == Some(root.as_path())).then_some(resolved)
+    };
+
+    if let Some(path) = inside(&directory.join(name)) {
+        return Some(path);
+    }
+    let stem = Path::new(name).file_stem()?;
+    std::fs::read_dir(directory)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|candidate| {
+            candidate.file_stem() == Some(stem)
+                && candidate
+                    .extension()
+                    .is_none_or(|ext| ext != "json" && ext != "html")
+        })
+        .find_map(|candidate| inside(&candidate))
+}
+
+/// Identidad de la nota en Keep: el nombre de su JSON, único dentro de Takeout
+/// y presente aunque falten las fechas
+fn import_id(file: &Path) -> String {
+    let stem = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!("keep:{}", stem)
+}
+
+fn frontmatter(note: &KeepNote, import_id: &str) -> String {
+    let mut map = Mapping::new();
+    let tags: Vec<Value> = note
+        .labels
+        .iter()
+        .map(|label| tag_name(&label.name))
+        .filter(|tag| !tag.is_empty())
+        .map(Value::from)
+        .collect();
+    if !tags.is_empty() {
+        map.insert(Value::from("tags"), Value::Sequence(tags));
+    }
+    if let Some(created) = note.created() {
+        map.insert(
+            Value::from("created"),
+            Value::from(frontmatter_timestamp(created)),
+        );
+    }
+    if let Some(updated) = note.updated() {
+        map.insert(
+            Value::from("updated"),
+            Value::from(frontmatter_timestamp(updated)),
+        );
+    }
+    if note.is_pinned {
+        map.insert(Value::from("pinned"), Value::from(true));
+    }
+    if let Some(color) = note.color.as_deref().filter(|color| *color != "DEFAULT") {
+        map.insert(Value::from("color"), Value::from(color.to_lowercase()));
+    }
+    map.insert(Value::from(IMPORT_ID_KEY), Value::from(import_id));
+    match serde_yaml::to_string(&Value::Mapping(map)) {
+        Ok(yaml) => format!("---\n{}---\n", yaml),
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_keep_takeout() {
+        let root = std::env::temp_dir().join("notnative_keep_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let keep = root.join("Takeout/Keep");
+        std::fs::create_dir_all(&keep).unwrap();
+        std::fs::write(
+            keep.join("Compra.json"),
+            r#"{"color":"YELLOW","isTrashed":false,"isPinned":true,"isArchived":false,
+                "listContent":[{"text":"Leche","isChecked":false},{"text":"Pan","isChecked":true}],
+                "title":"Compra","userEditedTimestampUsec":1704531600000000,
+                "createdTimestampUsec":1704450600000000,
+                "labels":[{"name":"casa"},{"name":"lista de la compra"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            keep.join("Idea.json"),
+            r#"{"color":"DEFAULT","isTrashed":false,"isPinned":false,"isArchived":true,
+                "textContent":"Una idea para el proyecto\nCon más detalle","title":"",
+                "createdTimestampUsec":1704537000000000,
+                "attachments":[{"filePath":"foto.jpeg","mimetype":"image/jpeg"}],
+                "annotations":[{"url":"https://example.com","title":"Ejemplo","source":"WEBLINK"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(keep.join("foto.jpg"), b"jpg").unwrap();
+        std::fs::write(
+            keep.join("Borrada.json"),
+            r#"{"isTrashed":true,"textContent":"x","title":"Borrada"}"#,
+        )
+        .unwrap();
+        std::fs::write(keep.join("Labels.json"), r#"{"labels":[]}"#).unwrap();
+
+        let notes = root.join("notes");
+        let assets = root.join("assets");
+        let import = || {
+            KeepImporter::new(root.join("Takeout"), &notes)
+                .with_assets_dir(&assets)
+                .import()
+                .unwrap()
+        };
+        let report = import();
+        assert_eq!(
+            report.notes,
+            [
+                "Google Keep/Compra",
+                "Google Keep/Archivadas/Una idea para el proyecto"
+            ]
+        );
+        assert_eq!(report.attachments, 1);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].note, "Keep/Borrada.json");
+
+        let compra = std::fs::read_to_string(notes.join("Google Keep/Compra.md")).unwrap();
+        assert_eq!(
+            compra,
+            "---
+tags:
+- casa
+- lista-de-la-compra
+created: 2024-01-05T10:30:00Z
+updated: 2024-01-06T09:00:00Z
+pinned: true
+color: yellow
+import_id: keep:Compra
+---
+- [ ] Leche
+- [x] Pan
+"
+        );
+        let idea = std::fs::read_to_string(
+            notes.join("Google Keep/Archivadas/Una idea para el proyecto.md"),
+        )
+        .unwrap();
+        assert_eq!(
+            idea,
+            format!(
+                "---
+created: 2024-01-06T10:30:00Z
+import_id: keep:Idea
+---
+Una idea para el proyecto
+Con más detalle
+
+- [Ejemplo](https://example.com)
+
+![foto]({})
+",
+                assets.join("foto.jpg").display()
+            )
+        );
+
+        // Repetir la importación no duplica notas ni adjuntos
+        let again = import();
+        assert_eq!(again.notes, report.notes);
+        assert_eq!(again.attachments, 0);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_import_keep_without_timestamp_keeps_existing_note() {
+        let root = std::env::temp_dir().join("notnative_keep_collision_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let keep = root.join("Takeout/Keep");
+        std::fs::create_dir_all(&keep).unwrap();
+        std::fs::write(
+            keep.join("Nota.json"),
+            r#"{"isTrashed":false,"isArchived":false,"textContent":"De Keep","title":"Nota"}"#,
+        )
+        .unwrap();
+        let notes = root.join("notes");
+        std::fs::create_dir_all(notes.join("Google Keep")).unwrap();
+        std::fs::write(notes.join("Google Keep/Nota.md"), "Mi nota\n").unwrap();
+
+        // La nota existente no tiene import_id, así que no es la de Keep: ambas se conservan
+        let report = KeepImporter::new(root.join("Takeout"), &notes)
+            .with_assets_dir(root.join("assets"))
+            .import()
+            .unwrap();
+        assert_eq!(report.notes, ["Google Keep/Nota 2"]);
+        assert_eq!(
+            std::fs::read_to_string(notes.join("Google Keep/Nota.md")).unwrap(),
+            "Mi nota\n"
+        );
+        assert!(
+            std::fs::read_to_string(notes.join("Google Keep/Nota 2.md"))
+                .unwrap()
+                .contains("De Keep")
+        );
+
+        // La importada sí se reconoce por el nombre de su JSON: repetir no la duplica
+        let again = KeepImporter::new(root.join("Takeout"), &notes)
+            .with_assets_dir(root.join("assets"))
+            .import()
+            .unwrap();
+        assert_eq!(again.notes, ["Google Keep/Nota 2"]);
+        assert!(!notes.join("Google Keep/Nota 3.md").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_find_attachment_stays_in_directory() {
+        let root = std::env::temp_dir().join("notnative_keep_attachment_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let keep = root.join("Keep");
+        std::fs::create_dir_all(keep.join("sub")).unwrap();
+        std::fs::write(keep.join("foto.jpg"), b"jpg").unwrap();
+        std::fs::write(keep.join("sub/otra.jpg"), b"jpg").unwrap();
+        std::fs::write(root.join("secreto.jpg"), b"no").unwrap();
+
+        assert_eq!(
+            find_attachment(&keep, "foto.jpeg"),
+            Some(keep.join("foto.jpg").canonicalize().unwrap())
+        );
+        assert_eq!(find_attachment(&keep, "../secreto.jpg"), None);
+        assert_eq!(find_attachment(&keep, "sub/otra.jpg"), None);
+        assert_eq!(
+            find_attachment(&keep, &root.join("secreto.jpg").to_string_lossy()),
+            None
+        );
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("secreto.jpg"), keep.join("enlace.jpg")).unwrap();
+            assert_eq!(find_attachment(&keep, "enlace.jpg"), None);
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,1054 @@
+//! Importador de exportaciones de Notion (Markdown y CSV)
+//!
+//! Notion exporta cada página como `Título <hash>.md`, con sus subpáginas e
+//! imágenes en una carpeta `Título <hash>/`, y cada base de datos como un CSV
+//! (`Tabla <hash>.csv`, o `Tabla <hash>_all.csv` con todas las filas) más una
+//! carpeta con una página por fila. El importador:
+//!
+//! - Quita los hashes de los nombres y conserva la jerarquía de carpetas
+//! - Convierte los enlaces entre páginas (relativos o `notion.so/...<hash>`) en
+//!   `[[enlaces]]` y copia las imágenes a la carpeta de assets
+//! - Convierte cada base de datos en una carpeta con una nota por fila, con las
+//!   columnas del CSV como frontmatter tipado, y crea su `.base` con las mismas
+//!   columnas (los enlaces al CSV pasan a bloques ```` ```base ````)
+//! - Los bloques `<aside>` pasan a callouts
+//!
+//! Acepta la carpeta extraída o el `.zip` tal cual lo descarga Notion.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+use serde_yaml::{Mapping, Value};
+
+use super::base::{Base, BaseView, ColumnConfig};
+use super::book::map_prose;
+use super::html_renderer::url_decode;
+use super::import::{
+    AssetStore, IMPORT_ID_KEY, ImportError, ImportReport, ImportedBase, MD_LINK_RE, SourceDir,
+    claim_note_name, collect_files, join_name, normalize, parent_name, property_key,
+    sanitize_note_name, write_note,
+};
+use super::notes_config::NotesConfig;
+use super::wikilink::WikiLink;
+
+/// Regex para el hash que Notion añade a cada nombre (`Tareas 1a2b…`)
+static HASH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.*?)\s+([0-9a-f]{32})$").unwrap());
+
+/// Regex para enlaces absolutos a páginas (`https://www.notion.so/Titulo-<hash>`)
+static NOTION_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^https?://(?:www\.)?notion\.(?:so|site)/\S*?([0-9a-f]{32})(?:[?#]\S*)?$").unwrap()
+});
+
+/// Regex para una relación en el CSV: `Título (Ruta%20<hash>.md)`
+static RELATION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([^,()]+?) \(([^()]+\.md)\)").unwrap());
+
+/// Carpeta de destino por defecto
+const DEFAULT_FOLDER: &str = "Notion";
+
+/// Formatos de fecha con hora que usa Notion en los CSV (y equivalentes ISO)
+const DATETIME_FORMATS: [&str; 5] = [
+    "%B %d, %Y %I:%M %p",
+    "%B %d, %Y %H:%M",
+    "%Y/%m/%d %H:%M",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+];
+
+const DATE_FORMATS: [&str; 4] = ["%B %d, %Y", "%Y/%m/%d", "%Y-%m-%d", "%m/%d/%Y"];
+
+/// Tipo de una columna, deducido de sus valores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnKind {
+    Checkbox,
+    Number,
+    /// Fecha; `range` si algún valor es un intervalo `A → B`
+    Date {
+        time: bool,
+        range: bool,
+    },
+    /// Enlaces a páginas de otra base de datos
+    Relation,
+    /// Opciones múltiples (`a, b`) → lista
+    MultiSelect,
+    /// Una opción de un conjunto que se repite → texto
+    Select,
+    Text,
+}
+
+struct Column {
+    /// Cabecera del CSV
+    header: String,
+    /// Clave de la propiedad
+    key: String,
+    kind: ColumnKind,
+}
+
+/// Base de datos exportada como CSV
+struct Database {
+    name: String,
+    /// Ruta relativa del CSV en la exportación
+    csv: String,
+    /// Carpeta de la exportación con las páginas de las filas
+    rows_dir: String,
+    /// Carpeta del workspace con una nota por fila
+    folder: String,
+    /// Cabecera de la columna de título (la primera)
+    title_header: String,
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+}
+
+/// Páginas y adjuntos de la exportación
+#[derive(Default)]
+struct ExportIndex {
+    /// Ruta relativa de origen → nombre de la nota
+    pages: HashMap<String, String>,
+    /// Hash de Notion → nombre de la nota
+    by_hash: HashMap<String, String>,
+    /// Ruta relativa de un CSV → nombre de la base
+    databases: HashMap<String, String>,
+    /// Ruta relativa de un adjunto → fichero
+    attachments: HashMap<String, PathBuf>,
+}
+
+/// Quitar el hash de un nombre: `(nombre, hash)`
+fn strip_hash(name: &str) -> (&str, Option<&str>) {
+    match HASH_RE.captures(name) {
+        Some(caps) => (
+            caps.get(1).map_or(name, |m| m.as_str()),
+            caps.get(2).map(|m| m.as_str()),
+        ),
+        None => (name, None),
+    }
+}
+
+/// Ruta relativa sin hashes ni extensión (`Proyecto <hash>/Plan <hash>.md` →
+/// `Proyecto/Plan`)
+fn clean_path(relative: &str) -> String {
+    let (dir, file) = match relative.rsplit_once('/') {
+        Some((dir, file)) => (Some(dir), file),
+        None => (None, relative),
+    };
+    let stem = match file.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => file,
+    };
+    let stem = stem.strip_suffix("_all").unwrap_or(stem);
+    dir.into_iter()
+        .flat_map(|dir| dir.split('/'))
+        .chain(std::iter::once(stem))
+        .map(|part| strip_hash(part).0)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Frontmatter de una nota importada: sus propiedades más el `import_id`
+fn frontmatter_block(mut map: Mapping, import_id: &str) -> String {
+    map.insert(Value::from(IMPORT_ID_KEY), Value::from(import_id));
+    match serde_yaml::to_string(&Value::Mapping(map)) {
+        Ok(yaml) => format!("---\n{}---\n", yaml),
+        Err(_) => String::new(),
+    }
+}
+
+/// Filas de un CSV (RFC 4180: comillas dobles y saltos de línea en los campos)
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                c => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| row.iter().any(|field| !field.is_empty()));
+    rows
+}
+
+/// Fecha normalizada y si lleva hora
+type ParsedDate = (String, bool);
+
+/// Fecha de Notion (`January 5, 2024`, `January 5, 2024 3:30 PM`, ISO...) como
+/// `YYYY-MM-DD` o `YYYY-MM-DDTHH:MM:SS`, y si lleva hora
+fn parse_date(text: &str) -> Option<ParsedDate> {
+    let text = text.trim();
+    // Zona horaria entre paréntesis: `January 5, 2024 3:30 PM (GMT+1)`
+    let text = match text.rsplit_once(" (") {
+        Some((date, zone)) if zone.ends_with(')') => date,
+        _ => text,
+    };
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|datetime| (datetime.format("%Y-%m-%dT%H:%M:%S").to_string(), true))
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| chrono::NaiveDate::parse_from_str(text, format).ok())
+                .map(|date| (date.format("%Y-%m-%d").to_string(), false))
+        })
+}
+
+/// Celda de fecha, que puede ser un intervalo `inicio → fin`
+fn parse_date_cell(text: &str) -> Option<(ParsedDate, Option<ParsedDate>)> {
+    match text.split_once('→') {
+        Some((start, end)) => Some((parse_date(start)?, Some(parse_date(end)?))),
+        None => Some((parse_date(text)?, None)),
+    }
+}
+
+/// Opciones de una celda de selección múltiple
+fn split_options(text: &str) -> impl Iterator<Item = &str> {
+    text.split(", ")
+        .map(str::trim)
+        .filter(|option| !option.is_empty())
+}
+
+/// Tipo de una columna a partir de sus valores (las celdas vacías no cuentan)
+fn infer_kind(key: &str, values: &[&str]) -> ColumnKind {
+    let values: Vec<&str> = values
+        .iter()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .collect();
+    if values.is_empty() {
+        return ColumnKind::Text;
+    }
+    if values.iter().all(|value| matches!(*value, "Yes" | "No")) {
+        return ColumnKind::Checkbox;
+    }
+    if values
+        .iter()
+        .all(|value| value.parse::<f64>().is_ok_and(f64::is_finite))
+    {
+        return ColumnKind::Number;
+    }
+    let dates: Option<Vec<_>> = values.iter().map(|value| parse_date_cell(value)).collect();
+    if let Some(dates) = dates {
+        return ColumnKind::Date {
+            time: dates
+                .iter()
+                .any(|(start, end)| start.1 || end.as_ref().is_some_and(|end| end.1)),
+            range: dates.iter().any(|(_, end)| end.is_some()),
+        };
+    }
+    if values.iter().all(|value| {
+        RELATION_RE.is_match(value)
+            && RELATION_RE
+                .replace_all(value, "")
+                .chars()
+                .all(|c| c == ',' || c.is_whitespace())
+    }) {
+        return ColumnKind::Relation;
+    }
+
+    // Selección: valores cortos que se repiten entre filas
+    let options: Vec<&str> = values
+        .iter()
+        .flat_map(|value| split_options(value))
+        .collect();
+    let distinct: HashSet<&str> = options.iter().copied().collect();
+    let short = options
+        .iter()
+        .all(|option| option.chars().count() <= 60 && !option.contains('\n'));
+    let multiple = values.iter().any(|value| split_options(value).count() > 1);
+    if key == "tags" || (short && distinct.len() < options.len()) {
+        if multiple || key == "tags" {
+            ColumnKind::MultiSelect
+        } else {
+            ColumnKind::Select
+        }
+    } else {
+        ColumnKind::Text
+    }
+}
+
+/// Importador de una exportación de Notion (carpeta o `.zip`)
+pub struct NotionImporter {
+    path: PathBuf,
+    notes_root: PathBuf,
+    folder: String,
+    assets_dir: PathBuf,
+}
+
+impl NotionImporter {
+    pub fn new(path: impl Into<PathBuf>, notes_root: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            notes_root: notes_root.into(),
+            folder: DEFAULT_FOLDER.to_string(),
+            assets_dir: NotesConfig::assets_dir(),
+        }
+    }
+
+    /// Carpeta del workspace donde se crean las notas (`""` = raíz)
+    pub fn with_folder(mut self, folder: impl Into<String>) -> Self {
+        self.folder = folder.into().trim_matches('/').to_string();
+        self
+    }
+
+    pub fn with_assets_dir(mut self, assets_dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = assets_dir.into();
+        self
+    }
+
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    /// Importar la exportación; repetirlo sobrescribe las notas y las Bases
+    pub fn import(&self) -> Result<ImportReport, ImportError> {
+        let source = SourceDir::open(&self.path)?;
+        let root = source.path();
+        let mut files = Vec::new();
+        collect_files(root, &mut files)?;
+
+        let mut report = ImportReport::default();
+        let mut index = ExportIndex::default();
+        let mut used = HashSet::new();
+        let mut pages = Vec::new();
+        let mut csvs: Vec<(String, PathBuf)> = Vec::new();
+        for path in files {
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let extension = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            match extension.as_str() {
+                "md" => {
+                    let stem = path
+                        .file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    // El id de la página (el hash del nombre) identifica la nota entre
+                    // exportaciones; sin él, la ruta en la exportación
+                    let hash = strip_hash(&stem).1;
+                    let import_id = format!("notion:{}", hash.unwrap_or(&relative));
+                    let clean = clean_path(&relative);
+                    let (folder, title) = clean.rsplit_once('/').unwrap_or(("", &clean));
+                    let name = claim_note_name(
+                        &self.notes_root,
+                        &join_name(&self.folder, folder),
+                        title,
+                        &import_id,
+                        &mut used,
+                    );
+                    if let Some(hash) = hash {
+                        index.by_hash.insert(hash.to_string(), name.clone());
+                    }
+                    index.pages.insert(relative.clone(), name.clone());
+                    pages.push((path, relative, name, import_id));
+                }
+                "csv" => csvs.push((relative, path)),
+                _ => {
+                    index.attachments.insert(relative, path);
+                }
+            }
+        }
+
+        let databases = self.databases(&csvs, &mut index, &mut report)?;
+
+        // Filas de cada base de datos ↔ páginas de su carpeta, por título
+        let mut row_pages: HashMap<String, Vec<usize>> = HashMap::new();
+        for (position, (_, relative, ..)) in pages.iter().enumerate() {
+            let stem = relative.rsplit('/').next().unwrap_or(relative);
+            let key = format!(
+                "{}/{}",
+                parent_name(relative),
+                clean_path(stem).to_lowercase()
+            );
+            row_pages.entry(key).or_default().push(position);
+        }
+        let mut page_rows: HashMap<usize, (usize, usize)> = HashMap::new();
+        let mut orphan_rows = Vec::new();
+        for (db, database) in databases.iter().enumerate() {
+            for (row, cells) in database.rows.iter().enumerate() {
+                let title = sanitize_note_name(cells.first().map_or("", String::as_str));
+                let key = format!("{}/{}", database.rows_dir, title.to_lowercase());
+                let page = row_pages
+                    .get_mut(&key)
+                    .and_then(|candidates| (!candidates.is_empty()).then(|| candidates.remove(0)));
+                match page {
+                    Some(page) => {
+                        page_rows.insert(page, (db, row));
+                    }
+                    None => orphan_rows.push((db, row, title)),
+                }
+            }
+        }
+
+        let mut assets = AssetStore::new(&self.assets_dir);
+        for (position, (path, relative, name, import_id)) in pages.iter().enumerate() {
+            let content = std::fs::read_to_string(path)?;
+            let mut converter = PageConverter {
+                index: &index,
+                relative,
+                name,
+                assets: &mut assets,
+                report: &mut report,
+            };
+            let converted = match page_rows.get(&position) {
+                Some(&(db, row)) => {
+                    let database = &databases[db];
+                    let frontmatter = converter.frontmatter(database, &database.rows[row]);
+                    frontmatter_block(frontmatter, import_id)
+                        + &converter.convert(&strip_properties(&content, database))
+                }
+                None => frontmatter_block(Mapping::new(), import_id) + &converter.convert(&content),
+            };
+            write_note(&self.notes_root, name, &converted)?;
+            report.notes.push(name.clone());
+        }
+
+        // Filas sin página en la exportación: nota solo con las propiedades
+        for (db, row, title) in orphan_rows {
+            let database = &databases[db];
+            let import_id = format!("notion:{}#{}", database.csv, title);
+            let name = claim_note_name(
+                &self.notes_root,
+                &database.folder,
+                &title,
+                &import_id,
+                &mut used,
+            );
+            let mut converter = PageConverter {
+                index: &index,
+                relative: &database.csv,
+                name: &name,
+                assets: &mut assets,
+                report: &mut report,
+            };
+            let frontmatter = converter.frontmatter(database, &database.rows[row]);
+            let content = format!(
+                "{}# {}\n",
+                frontmatter_block(frontmatter, &import_id),
+                title
+            );
+            write_note(&self.notes_root, &name, &content)?;
+            report.notes.push(name);
+        }
+
+        for database in &databases {
+            self.write_base(database, &mut report)?;
+        }
+        report.attachments = assets.written();
+        Ok(report)
+    }
+
+    /// Leer los CSV (con `_all.csv` y `.csv` de la misma base, gana `_all`)
+    fn databases(
+        &self,
+        csvs: &[(String, PathBuf)],
+        index: &mut ExportIndex,
+        report: &mut ImportReport,
+    ) -> Result<Vec<Database>, ImportError> {
+        let mut chosen: Vec<(String, &String, &PathBuf)> = Vec::new();
+        for (relative, path) in csvs {
+            let without_extension = relative.strip_suffix(".csv").unwrap_or(relative);
+            let rows_dir = without_extension
+                .strip_suffix("_all")
+                .unwrap_or(without_extension)
+                .to_string();
+            match chosen.iter_mut().find(|(dir, _, _)| *dir == rows_dir) {
+                Some(entry) if without_extension.ends_with("_all") => {
+                    *entry = (rows_dir, relative, path)
+                }
+                Some(_) => {}
+                None => chosen.push((rows_dir, relative, path)),
+            }
+        }
+
+        let mut databases = Vec::new();
+        for (rows_dir, csv, path) in chosen {
+            let clean = clean_path(csv);
+            let name = clean.rsplit('/').next().unwrap_or(&clean).to_string();
+            for (relative, _) in csvs {
+                let suffix = relative.strip_prefix(&rows_dir);
+                if matches!(suffix, Some(".csv" | "_all.csv")) {
+                    index.databases.insert(relative.clone(), name.clone());
+                }
+            }
+
+            let mut rows = parse_csv(&std::fs::read_to_string(path)?);
+            if rows.is_empty() {
+                report.warn(csv.clone(), "CSV vacío: base de datos no importada");
+                continue;
+            }
+            let headers = rows.remove(0);
+            let mut keys = HashSet::new();
+            let mut columns = Vec::new();
+            for (position, header) in headers.iter().enumerate().skip(1) {
+                let key = property_key(header).unwrap_or_else(|| format!("campo_{}", position));
+                let key = match keys.contains(&key) {
+                    true => (2..)
+                        .map(|n| format!("{}_{}", key, n))
+                        .find(|candidate| !keys.contains(candidate))
+                        .unwrap_or(key),
+                    false => key,
+                };
+                keys.insert(key.clone());
+                let values: Vec<&str> = rows
+                    .iter()
+                    .map(|row| row.get(position).map_or("", String::as_str))
+                    .collect();
+                columns.push(Column {
+                    header: header.clone(),
+                    kind: infer_kind(&key, &values),
+                    key,
+                });
+            }
+
+            databases.push(Database {
+                folder: join_name(&self.folder, &clean),
+                name,
+                csv: csv.clone(),
+                rows_dir,
+                title_header: headers.first().cloned().unwrap_or_default(),
+                columns,
+                rows,
+            });
+        }
+        Ok(databases)
+    }
+
+    /// Crear `<carpeta>.base` junto a la carpeta de la base de datos
+    fn write_base(&self, database: &Database, report: &mut ImportReport) -> std::io::Result<()> {
+        let mut columns = vec![ColumnConfig::new("title").with_title(&database.title_header)];
+        for column in &database.columns {
+            columns.push(ColumnConfig::new(&column.key).with_title(&column.header));
+            if let ColumnKind::Date { range: true, .. } = column.kind {
+                columns.push(
+                    ColumnConfig::new(format!("{}_end", column.key))
+                        .with_title(format!("{} (fin)", column.header)),
+                );
+            }
+        }
+
+        let mut table = BaseView::table("Tabla");
+        table.columns = columns.clone();
+        table.sort = None;
+        let mut base = Base::with_view(&database.name, table);
+        base.description = Some(format!("Importada de Notion ({})", database.csv));
+        base.source_folder = Some(database.folder.clone());
+        if let Some(select) = database
+            .columns
+            .iter()
+            .find(|column| column.kind == ColumnKind::Select)
+        {
+            let mut board = BaseView::board("Tablero", &select.key);
+            board.columns = columns;
+            board.sort = None;
+            base.add_view(board);
+        }
+
+        let config_yaml = match base.serialize() {
+            Ok(yaml) => yaml,
+            Err(e) => {
+                report.warn(&database.name, format!("No se pudo crear la Base: {}", e));
+                return Ok(());
+            }
+        };
+        let file = self.notes_root.join(format!("{}.base", database.folder));
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&file, &config_yaml)?;
+        report.bases.push(ImportedBase {
+            name: database.name.clone(),
+            folder: database.folder.clone(),
+            file,
+            config_yaml,
+        });
+        Ok(())
+    }
+}
+
+/// Quitar las líneas `Columna: valor` que Notion pone tras el título de una
+/// fila (ya están en el frontmatter)
+fn strip_properties(content: &str, database: &Database) -> String {
+    let headers: HashSet<&str> = std::iter::once(database.title_header.as_str())
+        .chain(database.columns.iter().map(|column| column.header.as_str()))
+        .collect();
+    let mut lines = content.split_inclusive('\n').peekable();
+    let mut out = String::new();
+    // Título y líneas en blanco
+    while let Some(line) = lines.next_if(|line| line.starts_with("# ") || line.trim().is_empty()) {
+        out.push_str(line);
+    }
+    let mut stripped = false;
+    while lines
+        .next_if(|line| {
+            line.split_once(':')
+                .is_some_and(|(key, _)| headers.contains(key.trim()))
+        })
+        .is_some()
+    {
+        stripped = true;
+    }
+    if stripped {
+        lines.next_if(|line| line.trim().is_empty());
+    }
+    out.extend(lines);
+    out
+}
+
+/// Conversión de una página
+struct PageConverter<'a> {
+    index: &'a ExportIndex,
+    /// Ruta relativa en la exportación
+    relative: &'a str,
+    /// Nombre de la nota en el workspace
+    name: &'a str,
+    assets: &'a mut AssetStore,
+    report: &'a mut ImportReport,
+}
+
+impl PageConverter<'_> {
+    fn convert(&mut self, content: &str) -> String {
+        let mut out = String::new();
+        let mut fence: Option<String> = None;
+        let mut in_aside = false;
+        for line in content.split_inclusive('\n') {
+            let (text, newline) = match line.strip_suffix('\n') {
+                Some(text) => (text.strip_suffix('\r').unwrap_or(text), "\n"),
+                None => (line, ""),
+            };
+            let trimmed = text.trim_start();
+
+            if let Some(marker) = &fence {
+                if trimmed.starts_with(marker.as_str()) {
+                    fence = None;
+                }
+                out.push_str(text);
+                out.push_str(newline);
+                continue;
+            }
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                fence = Some(
+                    trimmed
+                        .chars()
+                        .take_while(|&c| c == '`' || c == '~')
+                        .collect(),
+                );
+                out.push_str(text);
+                out.push_str(newline);
+                continue;
+            }
+
+            // Callouts: `<aside>` ... `</aside>`
+            match trimmed.trim_end() {
+                "<aside>" => {
+                    in_aside = true;
+                    out.push_str("> [!note]\n");
+                    continue;
+                }
+                "</aside>" if in_aside => {
+                    in_aside = false;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let converted = self.line(text);
+            if in_aside {
+                out.push_str(if converted.is_empty() { ">" } else { "> " });
+            }
+            out.push_str(&converted);
+            out.push_str(newline);
+        }
+        out
+    }
+
+    /// Frontmatter de una fila con los valores tipados de sus columnas
+    fn frontmatter(&mut self, database: &Database, row: &[String]) -> Mapping {
+        let mut map = Mapping::new();
+        for (position, column) in database.columns.iter().enumerate() {
+            let cell = row.get(position + 1).map_or("", |cell| cell.trim());
+            if cell.is_empty() {
+                continue;
+            }
+            let key = Value::from(column.key.as_str());
+            let value = match column.kind {
+                ColumnKind::Checkbox => Value::Bool(cell == "Yes"),
+                ColumnKind::Number => match cell.parse::<i64>() {
+                    Ok(number) => Value::from(number),
+                    Err(_) => Value::from(cell.parse::<f64>().unwrap_or_default()),
+                },
+                ColumnKind::Date { .. } => {
+                    let Some(((start, _), end)) = parse_date_cell(cell) else {
+                        continue;
+                    };
+                    if let Some((end, _)) = end {
+                        map.insert(key.clone(), Value::from(start));
+                        map.insert(Value::from(format!("{}_end", column.key)), Value::from(end));
+                        continue;
+                    }
+                    Value::from(start)
+                }
+                ColumnKind::Relation => Value::Sequence(
+                    RELATION_RE
+                        .captures_iter(cell)
+                        .map(|caps| {
+                            let target = normalize(&format!(
+                                "{}/{}",
+                                parent_name(&database.csv),
+                                url_decode(&caps[2])
+                            ));
+                            match self.index.pages.get(&target) {
+                                Some(note) => Value::from(format!("[[{}]]", note)),
+                                None => {
+                                    self.report.warn(
+                                        self.name,
+                                        format!("Relación sin resolver: {}", caps[1].trim()),
+                                    );
+                                    Value::from(caps[1].trim())
+                                }
+                            }
+                        })
+                        .collect(),
+                ),
+                ColumnKind::MultiSelect if column.key == "tags" => Value::Sequence(
+                    split_options(cell)
+                        .map(|tag| {
+                            Value::from(tag.split_whitespace().collect::<Vec<_>>().join("-"))
+                        })
+                        .collect(),
+                ),
+                ColumnKind::MultiSelect => {
+                    Value::Sequence(split_options(cell).map(Value::from).collect())
+                }
+                ColumnKind::Select | ColumnKind::Text => Value::from(cell),
+            };
+            map.insert(key, value);
+        }
+        map
+    }
+
+    fn line(&mut self, line: &str) -> String {
+        // Enlace suelto a un CSV: la base de datos en línea de la página
+        if let Some(caps) = MD_LINK_RE.captures(line.trim())
+            && caps[0].len() == line.trim().len()
+            && caps[1].is_empty()
+        {
+            let destination = caps.get(3).or(caps.get(4)).map_or("", |m| m.as_str());
+            if let Some(base) = self.database(destination) {
+                return format!("```base name=\"{}\"\n```", base);
+            }
+        }
+
+        map_prose(line, &mut |text| {
+            MD_LINK_RE
+                .replace_all(text, |caps: &Captures| {
+                    let destination = caps.get(3).or(caps.get(4)).map_or("", |m| m.as_str());
+                    self.link(!caps[1].is_empty(), &caps[2], destination)
+                        .unwrap_or_else(|| caps[0].to_string())
+                })
+                .into_owned()
+        })
+    }
+
+    /// Ruta relativa en la exportación de un destino relativo a esta página
+    fn target(&self, destination: &str) -> String {
+        normalize(&format!(
+            "{}/{}",
+            parent_name(self.relative),
+            url_decode(destination)
+        ))
+    }
+
+    fn database(&self, destination: &str) -> Option<&str> {
+        if !destination.to_lowercase().ends_with(".csv") {
+            return None;
+        }
+        self.index
+            .databases
+            .get(&self.target(destination))
+            .map(String::as_str)
+    }
+
+    /// Enlace o imagen markdown convertido, o `None` para dejarlo como está
+    fn link(&mut self, image: bool, text: &str, destination: &str) -> Option<String> {
+        let wikilink = |note: &str| {
+            WikiLink {
+                note: note.to_string(),
+                target: None,
+                alias: (!text.is_empty() && !note.ends_with(&format!("/{}", text)) && note != text)
+                    .then(|| text.to_string()),
+                embed: false,
+            }
+            .to_markdown()
+        };
+
+        if let Some(caps) = NOTION_URL_RE.captures(destination) {
+            return self.index.by_hash.get(&caps[1]).map(|note| wikilink(note));
+        }
+        let external = destination.contains("://")
+            || destination.starts_with("mailto:")
+            || destination.starts_with("data:")
+            || destination.starts_with('#');
+        if external {
+            return None;
+        }
+
+        let target = self.target(destination);
+        if let Some(note) = self.index.pages.get(&target) {
+            return Some(wikilink(note));
+        }
+        if let Some(base) = self.database(destination) {
+            return Some(format!("**{}**", base));
+        }
+        let Some(source) = self.index.attachments.get(&target) else {
+            self.report.warn(
+                self.name,
+                format!("Enlace sin resolver: {}", url_decode(destination)),
+            );
+            return None;
+        };
+        match self.assets.copy(source) {
+            Ok(path) => {
+                let bang = if image { "!" } else { "" };
+                Some(format!("{}[{}]({})", bang, text, path.display()))
+            }
+            Err(e) => {
+                self.report.warn(
+                    self.name,
+                    format!("No se pudo copiar {}: {}", source.display(), e),
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_and_infer_columns() {
+        let rows = parse_csv(
+            "\u{feff}Name,Notas,Hecho\r\n\"Uno, dos\",\"línea 1\nlínea \"\"2\"\"\",Yes\r\nTres,,No\r\n\r\n",
+        );
+        assert_eq!(
+            rows,
+            [
+                vec!["Name", "Notas", "Hecho"],
+                vec!["Uno, dos", "línea 1\nlínea \"2\"", "Yes"],
+                vec!["Tres", "", "No"],
+            ]
+        );
+
+        assert_eq!(
+            clean_path(
+                "Proyecto 0123456789abcdef0123456789abcdef/Tareas 0123456789abcdef0123456789abcdef_all.csv"
+            ),
+            "Proyecto/Tareas"
+        );
+        assert_eq!(
+            parse_date("January 5, 2024 3:30 PM (GMT+1)"),
+            Some(("2024-01-05T15:30:00".to_string(), true))
+        );
+        assert_eq!(
+            infer_kind("hecho", &["Yes", "No", ""]),
+            ColumnKind::Checkbox
+        );
+        assert_eq!(infer_kind("horas", &["1", "2.5"]), ColumnKind::Number);
+        assert_eq!(
+            infer_kind(
+                "fecha",
+                &["January 5, 2024 → January 8, 2024", "March 1, 2024"]
+            ),
+            ColumnKind::Date {
+                time: false,
+                range: true
+            }
+        );
+        assert_eq!(
+            infer_kind("estado", &["Hecho", "Pendiente", "Hecho"]),
+            ColumnKind::Select
+        );
+        assert_eq!(
+            infer_kind("áreas", &["Casa, Trabajo", "Casa"]),
+            ColumnKind::MultiSelect
+        );
+        assert_eq!(
+            infer_kind("notas", &["Algo", "Otra cosa"]),
+            ColumnKind::Text
+        );
+        assert_eq!(
+            infer_kind(
+                "proyecto",
+                &["Web (../Proyectos/Web%20abc.md), App (App.md)"]
+            ),
+            ColumnKind::Relation
+        );
+    }
+
+    #[test]
+    fn test_import_notion_export() {
+        let root = std::env::temp_dir().join("notnative_notion_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let export = root.join("export");
+        let hash = |n: u8| format!("{:032x}", n);
+        let home = format!("Inicio {}", hash(1));
+        let tasks = format!("Tareas {}", hash(2));
+        std::fs::create_dir_all(export.join(&home).join(&tasks)).unwrap();
+        std::fs::write(export.join(&home).join("foto 1.png"), b"png").unwrap();
+        std::fs::write(
+            export.join(format!("{}.md", home)),
+            format!(
+                "# Inicio\n\nVer [Escribir informe](Inicio%20{h1}/Tareas%20{h2}/Escribir%20informe%20{h3}.md) y [otra](https://www.notion.so/Inicio-{h1}).\n\n![foto 1.png](Inicio%20{h1}/foto%201.png)\n\n<aside>\n💡 Ojo\n</aside>\n\n[Tareas](Inicio%20{h1}/Tareas%20{h2}.csv)\n",
+                h1 = hash(1),
+                h2 = hash(2),
+                h3 = hash(3)
+            ),
+        )
+        .unwrap();
+        let csv = format!(
+            "Name,Estado,Etiquetas,Fecha,Horas,Hecho,Depende de\nEscribir informe,En curso,\"Trabajo, Urgente\",\"January 5, 2024\",2.5,No,\nRevisar,Hecho,Trabajo,\"January 8, 2024 → January 9, 2024\",1,Yes,Escribir informe (Tareas%20{h2}/Escribir%20informe%20{h3}.md)\nArchivar,Hecho,,,,No,\n",
+            h2 = hash(2),
+            h3 = hash(3)
+        );
+        std::fs::write(
+            export.join(&home).join(format!("{}.csv", tasks)),
+            "Name\nEscribir informe\n",
+        )
+        .unwrap();
+        std::fs::write(export.join(&home).join(format!("{}_all.csv", tasks)), csv).unwrap();
+        std::fs::write(
+            export.join(&home).join(&tasks).join(format!("Escribir informe {}.md", hash(3))),
+            "# Escribir informe\n\nEstado: En curso\nEtiquetas: Trabajo, Urgente\n\nCuerpo del informe.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            export
+                .join(&home)
+                .join(&tasks)
+                .join(format!("Revisar {}.md", hash(4))),
+            "# Revisar\n\nEstado: Hecho\n",
+        )
+        .unwrap();
+
+        let notes = root.join("notes");
+        let assets = root.join("assets");
+        let report = NotionImporter::new(&export, &notes)
+            .with_folder("Notion")
+            .with_assets_dir(&assets)
+            .import()
+            .unwrap();
+        assert_eq!(
+            report.notes,
+            [
+                "Notion/Inicio/Tareas/Escribir informe",
+                "Notion/Inicio/Tareas/Revisar",
+                "Notion/Inicio",
+                "Notion/Inicio/Tareas/Archivar",
+            ]
+        );
+        assert_eq!(report.attachments, 1);
+
+        let inicio = std::fs::read_to_string(notes.join("Notion/Inicio.md")).unwrap();
+        let expected = [
+            "Ver [[Notion/Inicio/Tareas/Escribir informe]] y [[Notion/Inicio|otra]].".to_string(),
+            format!("![foto 1.png]({})", assets.join("foto_1.png").display()),
+            "> [!note]\n> 💡 Ojo\n".to_string(),
+            "```base name=\"Tareas\"\n```".to_string(),
+        ];
+        for text in expected {
+            assert!(inicio.contains(&text), "falta {:?} en:\n{}", text, inicio);
+        }
+
+        let informe =
+            std::fs::read_to_string(notes.join("Notion/Inicio/Tareas/Escribir informe.md"))
+                .unwrap();
+        assert_eq!(
+            informe,
+            format!(
+                "---\nestado: En curso\netiquetas:\n- Trabajo\n- Urgente\nfecha: 2024-01-05\nhoras: 2.5\nhecho: false\nimport_id: notion:{}\n---\n# Escribir informe\n\nCuerpo del informe.\n",
+                hash(3)
+            )
+        );
+        let revisar =
+            std::fs::read_to_string(notes.join("Notion/Inicio/Tareas/Revisar.md")).unwrap();
+        assert!(revisar.contains("fecha: 2024-01-08\nfecha_end: 2024-01-09\nhoras: 1\nhecho: true\ndepende_de:\n- '[[Notion/Inicio/Tareas/Escribir informe]]'\n"), "{}", revisar);
+        assert!(revisar.ends_with("---\n# Revisar\n\n"));
+
+        assert_eq!(report.bases.len(), 1);
+        let base = Base::load(&notes.join("Notion/Inicio/Tareas.base")).unwrap();
+        assert_eq!(base.source_folder.as_deref(), Some("Notion/Inicio/Tareas"));
+        let titles: Vec<String> = base.views[0]
+            .columns
+            .iter()
+            .map(|column| column.display_title())
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                "Name",
+                "Estado",
+                "Etiquetas",
+                "Fecha",
+                "Fecha (fin)",
+                "Horas",
+                "Hecho",
+                "Depende de"
+            ]
+        );
+        assert_eq!(base.views[1].group_by.as_deref(), Some("estado"));
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+
+        // Repetir la importación sobrescribe las mismas notas; una nota ajena con
+        // el mismo nombre se conserva y la importada toma otro
+        std::fs::write(notes.join("Notion/Inicio/Tareas/Revisar.md"), "mía\n").unwrap();
+        let again = NotionImporter::new(&export, &notes)
+            .with_folder("Notion")
+            .with_assets_dir(&assets)
+            .import()
+            .unwrap();
+        assert_eq!(
+            again.notes,
+            [
+                "Notion/Inicio/Tareas/Escribir informe",
+                "Notion/Inicio/Tareas/Revisar 2",
+                "Notion/Inicio",
+                "Notion/Inicio/Tareas/Archivar",
+            ]
+        );
+        let mine = std::fs::read_to_string(notes.join("Notion/Inicio/Tareas/Revisar.md")).unwrap();
+        assert_eq!(mine, "mía\n");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,753 @@
+//! Importador de vaults de Obsidian
+//!
+//! Copia las notas del vault a una carpeta del workspace conservando las
+//! subcarpetas y convierte lo que NotNative escribe distinto:
+//!
+//! - `[[Nota|alias]]`, `![[Nota#Encabezado]]` y los enlaces markdown a `.md`
+//!   pasan a enlaces con el nombre completo de la nota importada (los alias del
+//!   frontmatter también se resuelven)
+//! - `![[imagen.png]]` y las imágenes relativas se copian a la carpeta de assets
+//! - `tags`/`aliases` del frontmatter se normalizan a listas
+//! - Los campos Dataview `clave:: valor`, `[clave:: valor]` y `(clave:: valor)`
+//!   pasan a propiedades inline `[clave::valor]`
+//! - Callouts de tipos propios, `%%comentarios%%` y `==resaltado==`
+//!
+//! Las consultas Dataview, los lienzos y los adjuntos que no se encuentran quedan
+//! en el informe.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+use serde_yaml::Value;
+
+use super::book::map_prose;
+use super::export::mime_type;
+use super::html_renderer::{callout_style, url_decode};
+use super::import::{
+    AssetStore, IMPORT_ID_KEY, ImportError, ImportReport, MD_LINK_RE, claim_note_name,
+    collect_files, join_name, normalize, parent_name, property_key, split_frontmatter, write_note,
+};
+use super::notes_config::NotesConfig;
+use super::wikilink::{LinkTarget, WIKI_LINK_RE, WikiLink};
+
+/// Regex para la primera línea de un callout (`> [!tipo]+ Título`)
+static CALLOUT_LINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\s*(?:>\s*)+)\[!([A-Za-z][\w-]*)\]([+-]?)[ \t]*(.*)$").unwrap()
+});
+
+/// Regex para un campo Dataview que ocupa la línea (`clave:: valor`, también en
+/// listas y citas)
+static DATAVIEW_LINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(\s*(?:[-*+]\s+|>\s*)?)\**(\p{L}[\p{L}\p{N}_ /-]*?)\**::[ \t]*(.*)$").unwrap()
+});
+
+/// Regex para campos Dataview en línea: `[clave:: valor]` y `(clave:: valor)`
+static DATAVIEW_INLINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"([\[(])(\p{L}[\p{L}\p{N}_ -]*?)::[ \t]*((?:\[\[[^\]\n]*\]\]|[^\[\]()\n])*)([\])])")
+        .unwrap()
+});
+
+/// Regex para `==resaltado==`
+static HIGHLIGHT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"==([^=\s](?:[^=\n]*[^=\s])?)==").unwrap());
+
+/// Regex para el tamaño de una imagen embebida (`![[foto.png|300]]`, `|300x200`)
+static IMAGE_SIZE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+(?:x\d+)?$").unwrap());
+
+/// Carpeta de destino por defecto
+const DEFAULT_FOLDER: &str = "Obsidian";
+
+/// Notas y adjuntos del vault, para resolver enlaces como lo hace Obsidian
+#[derive(Default)]
+struct VaultIndex {
+    /// Ruta relativa en minúsculas sin `.md` → ruta relativa (`Proyectos/Plan`)
+    by_path: HashMap<String, String>,
+    /// Nombre base en minúsculas → rutas relativas
+    by_base: HashMap<String, Vec<String>>,
+    /// Alias del frontmatter en minúsculas → ruta relativa
+    aliases: HashMap<String, String>,
+    /// Ruta relativa de un adjunto en minúsculas → fichero
+    attachments_by_path: HashMap<String, PathBuf>,
+    /// Nombre de fichero en minúsculas → ficheros
+    attachments_by_name: HashMap<String, Vec<PathBuf>>,
+    /// Ruta relativa → nombre de la nota en el workspace
+    names: HashMap<String, String>,
+}
+
+impl VaultIndex {
+    /// Nota a la que apunta `target` desde la nota `from` (ruta completa, nombre
+    /// más corto o alias; con varias candidatas gana la de la misma carpeta)
+    fn resolve_note(&self, target: &str, from: &str) -> Option<&str> {
+        let target = target.trim().trim_start_matches('/');
+        let target = target.strip_suffix(".md").unwrap_or(target);
+        let key = target.to_lowercase();
+        if let Some(path) = self.by_path.get(&key) {
+            return Some(path);
+        }
+
+        let base = key.rsplit('/').next().unwrap_or(&key);
+        let candidates: Vec<&String> = self
+            .by_base
+            .get(base)
+            .into_iter()
+            .flatten()
+            .filter(|path| !key.contains('/') || path.to_lowercase().ends_with(&key))
+            .collect();
+        let folder = parent_name(from);
+        candidates
+            .iter()
+            .find(|path| parent_name(path) == folder)
+            .or_else(|| candidates.iter().min_by_key(|path| path.len()))
+            .map(|path| path.as_str())
+            .or_else(|| self.aliases.get(&key).map(String::as_str))
+    }
+
+    /// Nombre en el workspace de la nota con ruta relativa `relative`
+    fn note_name(&self, relative: &str) -> &str {
+        self.names.get(relative).map_or(relative, String::as_str)
+    }
+
+    /// Adjunto al que apunta `target` desde la nota `from`: relativo a su carpeta,
+    /// a la raíz del vault o solo por nombre de fichero
+    fn resolve_attachment(&self, target: &str, from: &str) -> Option<&PathBuf> {
+        let target = target.trim().trim_start_matches('/');
+        let relative = normalize(&format!("{}/{}", parent_name(from), target));
+        [relative, normalize(target)]
+            .iter()
+            .find_map(|path| self.attachments_by_path.get(&path.to_lowercase()))
+            .or_else(|| {
+                let name = target.rsplit('/').next()?.to_lowercase();
+                self.attachments_by_name.get(&name)?.first()
+            })
+    }
+}
+
+/// Identidad de una nota del vault para reconocerla al repetir la importación
+fn import_id(relative: &str) -> String {
+    format!("obsidian:{}", relative)
+}
+
+/// Ruta relativa con `/` y sin `.md`
+fn relative_name(vault: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(vault).unwrap_or(path);
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    relative
+        .strip_suffix(".md")
+        .map(str::to_string)
+        .unwrap_or(relative)
+}
+
+/// Valores de una lista del frontmatter (`[a, b]`, `a, b` o `a b` si `split_spaces`)
+fn yaml_list(value: &Value, split_spaces: bool) -> Vec<String> {
+    let items: Vec<String> = match value {
+        Value::Sequence(items) => items.iter().filter_map(yaml_scalar).collect(),
+        Value::String(text) => text
+            .split(|c: char| c == ',' || (split_spaces && c.is_whitespace()))
+            .map(str::to_string)
+            .collect(),
+        other => yaml_scalar(other).into_iter().collect(),
+    };
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn yaml_scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+/// Importador de un vault de Obsidian
+pub struct ObsidianImporter {
+    vault: PathBuf,
+    notes_root: PathBuf,
+    folder: String,
+    assets_dir: PathBuf,
+}
+
+impl ObsidianImporter {
+    pub fn new(vault: impl Into<PathBuf>, notes_root: impl Into<PathBuf>) -> Self {
+        Self {
+            vault: vault.into(),
+            notes_root: notes_root.into(),
+            folder: DEFAULT_FOLDER.to_string(),
+            assets_dir: NotesConfig::assets_dir(),
+        }
+    }
+
+    /// Carpeta del workspace donde se copian las notas (`""` = raíz)
+    pub fn with_folder(mut self, folder: impl Into<String>) -> Self {
+        self.folder = folder.into().trim_matches('/').to_string();
+        self
+    }
+
+    pub fn with_assets_dir(mut self, assets_dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = assets_dir.into();
+        self
+    }
+
+    pub fn folder(&self) -> &str {
+        &self.folder
+    }
+
+    /// Importar el vault; repetirlo sobrescribe las notas importadas
+    pub fn import(&self) -> Result<ImportReport, ImportError> {
+        if !self.vault.is_dir() {
+            return Err(ImportError::SourceNotFound(self.vault.clone()));
+        }
+        let mut files = Vec::new();
+        collect_files(&self.vault, &mut files)?;
+
+        let mut report = ImportReport::default();
+        let mut index = VaultIndex::default();
+        let mut notes = Vec::new();
+        for path in files {
+            let relative = relative_name(&self.vault, &path);
+            let extension = path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            match extension.as_str() {
+                "md" => {
+                    let content = std::fs::read_to_string(&path)?;
+                    let base = relative.rsplit('/').next().unwrap_or(&relative);
+                    index
+                        .by_base
+                        .entry(base.to_lowercase())
+                        .or_default()
+                        .push(relative.clone());
+                    index
+                        .by_path
+                        .insert(relative.to_lowercase(), relative.clone());
+                    notes.push((relative, content));
+                }
+                "canvas" => report.warn(relative, "Lienzo de Obsidian no importado"),
+                _ => {
+                    let name = path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_lowercase())
+                        .unwrap_or_default();
+                    index
+                        .attachments_by_path
+                        .insert(relative.to_lowercase(), path.clone());
+                    index
+                        .attachments_by_name
+                        .entry(name)
+                        .or_default()
+                        .push(path);
+                }
+            }
+        }
+
+        // Los alias se conocen antes de convertir, para resolver `[[Alias]]`
+        for (relative, content) in &notes {
+            let Some((yaml, _)) = split_frontmatter(content) else {
+                continue;
+            };
+            let Ok(Value::Mapping(map)) = serde_yaml::from_str::<Value>(yaml) else {
+                continue;
+            };
+            for key in ["aliases", "alias"] {
+                if let Some(value) = map.get(key) {
+                    for alias in yaml_list(value, false) {
+                        index
+                            .aliases
+                            .entry(alias.to_lowercase())
+                            .or_insert_with(|| relative.clone());
+                    }
+                }
+            }
+        }
+
+        // Nombres antes de convertir, para que los enlaces apunten a la nota reclamada:
+        // la misma ruta del vault reutiliza su nota de una importación anterior
+        let mut used = HashSet::new();
+        for (relative, _) in &notes {
+            let (folder, title) = relative.rsplit_once('/').unwrap_or(("", relative));
+            let name = claim_note_name(
+                &self.notes_root,
+                &join_name(&self.folder, folder),
+                title,
+                &import_id(relative),
+                &mut used,
+            );
+            index.names.insert(relative.clone(), name);
+        }
+
+        let mut assets = AssetStore::new(&self.assets_dir);
+        for (relative, content) in &notes {
+            let name = index.note_name(relative).to_string();
+            let mut converter = NoteConverter {
+                importer: self,
+                index: &index,
+                relative,
+                name: &name,
+                assets: &mut assets,
+                report: &mut report,
+            };
+            let converted = converter.convert(content);
+            write_note(&self.notes_root, &name, &converted)?;
+            report.notes.push(name);
+        }
+
+        let unused = index
+            .attachments_by_path
+            .values()
+            .filter(|path| !assets.contains(path))
+            .count();
+        if unused > 0 {
+            report.warn(
+                self.vault.display().to_string(),
+                format!(
+                    "{} adjuntos sin referencias en las notas no se copiaron",
+                    unused
+                ),
+            );
+        }
+        report.attachments = assets.written();
+        Ok(report)
+    }
+}
+
+/// Conversión de una nota
+struct NoteConverter<'a> {
+    importer: &'a ObsidianImporter,
+    index: &'a VaultIndex,
+    /// Ruta relativa en el vault, sin `.md`
+    relative: &'a str,
+    /// Nombre de la nota en el workspace
+    name: &'a str,
+    assets: &'a mut AssetStore,
+    report: &'a mut ImportReport,
+}
+
+impl NoteConverter<'_> {
+    fn convert(&mut self, content: &str) -> String {
+        let (mut frontmatter, body) = match split_frontmatter(content) {
+            Some((yaml, body)) => match self.frontmatter(yaml) {
+                Some(frontmatter) => (frontmatter, body),
+                None => (serde_yaml::Mapping::new(), content),
+            },
+            None => (serde_yaml::Mapping::new(), content),
+        };
+        frontmatter.insert(
+            Value::from(IMPORT_ID_KEY),
+            Value::from(import_id(self.relative)),
+        );
+
+        let mut out = serde_yaml::to_string(&Value::Mapping(frontmatter))
+            .map(|yaml| format!("---\n{}---\n", yaml))
+            .unwrap_or_default();
+        let mut fence: Option<(String, String)> = None;
+        let mut in_comment = false;
+        for line in body.split_inclusive('\n') {
+            let (text, newline) = match line.strip_suffix('\n') {
+                Some(text) => (text.strip_suffix('\r').unwrap_or(text), "\n"),
+                None => (line, ""),
+            };
+            let trimmed = text.trim_start();
+
+            if let Some((marker, _)) = &fence {
+                if trimmed.starts_with(marker.as_str()) {
+                    fence = None;
+                }
+                out.push_str(text);
+                out.push_str(newline);
+                continue;
+            }
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                let marker: String = trimmed
+                    .chars()
+                    .take_while(|&c| c == '`' || c == '~')
+                    .collect();
+                let language = trimmed[marker.len()..].trim().to_lowercase();
+                if language == "dataview" || language == "dataviewjs" {
+                    self.report.warn(
+                        self.name,
+                        "Consulta Dataview sin convertir (se conserva como bloque de código)",
+                    );
+                }
+                fence = Some((marker, language));
+                out.push_str(text);
+                out.push_str(newline);
+                continue;
+            }
+
+            let converted = self.line(text, &mut in_comment);
+            out.push_str(&converted);
+            out.push_str(newline);
+        }
+        if in_comment {
+            out.push_str("-->\n");
+        }
+        out
+    }
+
+    /// Frontmatter con `tags` y `aliases` como listas; `None` si el YAML no es válido
+    fn frontmatter(&mut self, yaml: &str) -> Option<serde_yaml::Mapping> {
+        let map = match serde_yaml::from_str::<Value>(yaml) {
+            Ok(Value::Mapping(map)) => map,
+            Ok(Value::Null) => return Some(serde_yaml::Mapping::new()),
+            _ => {
+                self.report
+                    .warn(self.name, "Frontmatter YAML no válido (se deja como texto)");
+                return None;
+            }
+        };
+
+        let mut out = serde_yaml::Mapping::new();
+        for (key, value) in map {
+            let name = key.as_str().unwrap_or_default();
+            match name {
+                "tags" | "tag" => {
+                    let mut tags: Vec<String> = yaml_list(&value, true)
+                        .into_iter()
+                        .map(|tag| tag.trim_start_matches('#').to_string())
+                        .filter(|tag| !tag.is_empty())
+                        .collect();
+                    tags.dedup();
+                    out.insert(
+                        Value::from("tags"),
+                        Value::Sequence(tags.into_iter().map(Value::from).collect()),
+                    );
+                }
+                "aliases" | "alias" => {
+                    let aliases = yaml_list(&value, false);
+                    out.insert(
+                        Value::from("aliases"),
+                        Value::Sequence(aliases.into_iter().map(Value::from).collect()),
+                    );
+                }
+                _ => {
+                    out.insert(key, value);
+                }
+            }
+        }
+        Some(out)
+    }
+
+    fn line(&mut self, line: &str, in_comment: &mut bool) -> String {
+        // Callouts de tipos que NotNative no conoce: nota con el tipo como título
+        if let Some(caps) = CALLOUT_LINE_RE.captures(line) {
+            let kind = caps[2].to_lowercase();
+            if kind != "note" && callout_style(&kind).name == "note" {
+                self.report.warn(
+                    self.name,
+                    format!("Callout de tipo '{}' convertido en 'note'", kind),
+                );
+                let title = match caps[4].trim() {
+                    "" => {
+                        let mut chars = kind.chars();
+                        chars
+                            .next()
+                            .map(|first| first.to_uppercase().chain(chars).collect())
+                            .unwrap_or_default()
+                    }
+                    title => title.to_string(),
+                };
+                let line = format!("{}[!note]{} {}", &caps[1], &caps[3], title);
+                return self.prose(&line, in_comment);
+            }
+        }
+
+        // Campo Dataview en su propia línea
+        if let Some(caps) = DATAVIEW_LINE_RE.captures(line)
+            && !caps[2].contains("://")
+            && let Some(key) = property_key(&caps[2])
+        {
+            let value = self.property_value(&caps[3]);
+            let line = format!("{}[{}::{}]", &caps[1], key, value);
+            return self.prose(&line, in_comment);
+        }
+
+        self.prose(line, in_comment)
+    }
+
+    /// Conversiones en el texto fuera de código en línea
+    fn prose(&mut self, line: &str, in_comment: &mut bool) -> String {
+        map_prose(line, &mut |text| {
+            // `%%comentario%%`, que puede abarcar varias líneas
+            let mut text = text.to_string();
+            while let Some(position) = text.find("%%") {
+                let marker = if *in_comment { "-->" } else { "<!--" };
+                text.replace_range(position..position + 2, marker);
+                *in_comment = !*in_comment;
+            }
+            if *in_comment {
+                return text;
+            }
+
+            let text = DATAVIEW_INLINE_RE.replace_all(&text, |caps: &Captures| {
+                let closing = if &caps[1] == "[" { "]" } else { ")" };
+                match property_key(&caps[2]) {
+                    Some(key) if &caps[4] == closing => {
+                        format!("[{}::{}]", key, self.property_value(&caps[3]))
+                    }
+                    _ => caps[0].to_string(),
+                }
+            });
+            let text = HIGHLIGHT_RE.replace_all(&text, "<mark>$1</mark>");
+            let text = WIKI_LINK_RE.replace_all(&text, |caps: &Captures| {
+                self.wikilink(&caps[2], !caps[1].is_empty())
+                    .unwrap_or_else(|| caps[0].to_string())
+            });
+            MD_LINK_RE
+                .replace_all(&text, |caps: &Captures| {
+                    let destination = caps.get(3).or(caps.get(4)).map_or("", |m| m.as_str());
+                    self.markdown_link(!caps[1].is_empty(), &caps[2], destination)
+                        .unwrap_or_else(|| caps[0].to_string())
+                })
+                .into_owned()
+        })
+    }
+
+    /// Valor de un campo Dataview: los `[[enlaces]]` pasan a relaciones `@Nota`
+    fn property_value(&mut self, value: &str) -> String {
+        let value = WIKI_LINK_RE.replace_all(value.trim(), |caps: &Captures| {
+            let link = WikiLink::parse(&caps[2]);
+            match self.index.resolve_note(&link.note, self.relative) {
+                Some(relative) => format!("@{}", self.index.note_name(relative)),
+                None => format!("@{}", link.note),
+            }
+        });
+        if value.contains(']') {
+            self.report.warn(
+                self.name,
+                format!("Valor de propiedad con ']' recortado: {}", value),
+            );
+        }
+        value.replace(']', "")
+    }
+
+    /// `[[...]]` / `![[...]]` convertido, o `None` para dejarlo como está
+    fn wikilink(&mut self, inner: &str, embed: bool) -> Option<String> {
+        // En tablas Obsidian escapa el separador del alias: `[[Nota\|alias]]`
+        let escaped = inner.contains("\\|");
+        let mut link = WikiLink::parse(&inner.replace("\\|", "|"));
+        link.embed = embed;
+        if link.note.is_empty() {
+            return None;
+        }
+
+        let extension = Path::new(&link.note)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        if extension.as_deref().is_some_and(|ext| ext != "md") {
+            let path = self.attachment(&link.note)?;
+            let image = mime_type(&path).starts_with("image/");
+            let file_name = Path::new(&link.note)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            return Some(match (embed && image, link.alias) {
+                (true, Some(size)) if IMAGE_SIZE_RE.is_match(&size) => {
+                    self.report.warn(
+                        self.name,
+                        format!("Tamaño de imagen ignorado en ![[{}]]", inner),
+                    );
+                    format!("![{}]({})", file_stem(&file_name), path.display())
+                }
+                (true, alias) => format!(
+                    "![{}]({})",
+                    alias.unwrap_or_else(|| file_stem(&file_name)),
+                    path.display()
+                ),
+                (false, alias) => {
+                    format!("[{}]({})", alias.unwrap_or(file_name), path.display())
+                }
+            });
+        }
+
+        let written = link
+            .note
+            .strip_suffix(".md")
+            .unwrap_or(&link.note)
+            .to_string();
+        let relative = self.index.resolve_note(&written, self.relative)?;
+        // Obsidian solo admite encabezados anidados como `Nota#H1#H2`: queda el último
+        if let Some(LinkTarget::Heading(heading)) = &link.target
+            && let Some((_, last)) = heading.rsplit_once('#')
+        {
+            link.target = Some(LinkTarget::Heading(last.trim().to_string()));
+        }
+        if !embed && link.alias.is_none() {
+            let mut shown = link.clone();
+            shown.note = written;
+            link.alias = Some(shown.label());
+        }
+        link.note = self.index.note_name(relative).to_string();
+
+        let markdown = link.to_markdown();
+        Some(if escaped {
+            markdown.replace('|', "\\|")
+        } else {
+            markdown
+        })
+    }
+
+    /// Enlace o imagen markdown con destino local convertido
+    fn markdown_link(&mut self, image: bool, text: &str, destination: &str) -> Option<String> {
+        let external = destination.contains("://")
+            || destination.starts_with("mailto:")
+            || destination.starts_with("data:")
+            || destination.starts_with('#')
+            || destination.starts_with('/');
+        if external {
+            return None;
+        }
+        let destination = url_decode(destination);
+        let (target, anchor) = match destination.split_once('#') {
+            Some((target, anchor)) => (target, Some(anchor)),
+            None => (destination.as_str(), None),
+        };
+        let extension = Path::new(target)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+
+        if extension.is_none() || extension.as_deref() == Some("md") {
+            let relative = self.index.resolve_note(target, self.relative)?;
+            let link = WikiLink {
+                note: self.index.note_name(relative).to_string(),
+                target: anchor.map(|anchor| match anchor.strip_prefix('^') {
+                    Some(id) => LinkTarget::Block(id.to_string()),
+                    None => LinkTarget::Heading(anchor.to_string()),
+                }),
+                alias: (!image && !text.is_empty()).then(|| text.to_string()),
+                embed: image,
+            };
+            return Some(link.to_markdown());
+        }
+
+        let path = self.attachment(target)?;
+        let bang = if image { "!" } else { "" };
+        Some(format!("{}[{}]({})", bang, text, path.display()))
+    }
+
+    /// Copiar un adjunto a assets; si no existe se anota y se deja el enlace
+    fn attachment(&mut self, target: &str) -> Option<PathBuf> {
+        let Some(source) = self.index.resolve_attachment(target, self.relative) else {
+            self.report
+                .warn(self.name, format!("Adjunto no encontrado: {}", target));
+            return None;
+        };
+        match self.assets.copy(source) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                self.report.warn(
+                    self.name,
+                    format!("No se pudo copiar {}: {}", source.display(), e),
+                );
+                None
+            }
+        }
+    }
+}
+
+fn file_stem(file_name: &str) -> String {
+    Path::new(file_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_obsidian_vault() {
+        let root = std::env::temp_dir().join("notnative_obsidian_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let vault = root.join("vault");
+        std::fs::create_dir_all(vault.join(".obsidian")).unwrap();
+        std::fs::create_dir_all(vault.join("Proyectos")).unwrap();
+        std::fs::create_dir_all(vault.join("adjuntos")).unwrap();
+        std::fs::write(vault.join(".obsidian/app.json"), "{}").unwrap();
+        std::fs::write(vault.join("adjuntos/mapa final.png"), b"png").unwrap();
+        std::fs::write(vault.join("adjuntos/sin usar.pdf"), b"pdf").unwrap();
+        std::fs::write(vault.join("Lienzo.canvas"), "{}").unwrap();
+        std::fs::write(
+            vault.join("Proyectos/Plan.md"),
+            "---\ntags: \"#proyecto, tarea\"\naliases: El plan\ncssclasses: [ancha]\n---\n# Plan\n\nEstado:: en curso\n- **Fecha límite**:: [[Inicio]]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            vault.join("Inicio.md"),
+            "Ver [[Plan|el plan]], [[El plan]] y [[Proyectos/Plan#Plan]].\n\n![[mapa final.png|300]]\n![otro](adjuntos/mapa%20final.png) [doc](Proyectos/Plan.md)\n\n> [!custom]- Ojo\n> texto (prioridad:: alta) ==importante== %%oculto%%\n\n```dataview\nLIST\n```\n\n`[[Plan]]` ![[falta.png]]\n",
+        )
+        .unwrap();
+
+        let notes = root.join("notes");
+        let assets = root.join("assets");
+        let report = ObsidianImporter::new(&vault, &notes)
+            .with_folder("Importado/")
+            .with_assets_dir(&assets)
+            .import()
+            .unwrap();
+        assert_eq!(
+            report.notes,
+            ["Importado/Inicio", "Importado/Proyectos/Plan"]
+        );
+        assert_eq!(report.attachments, 1);
+
+        let plan = std::fs::read_to_string(notes.join("Importado/Proyectos/Plan.md")).unwrap();
+        assert!(
+            plan.starts_with("---\ntags:\n- proyecto\n- tarea\naliases:\n- El plan\ncssclasses:")
+        );
+        assert!(plan.contains("\n[estado::en curso]\n"));
+        assert!(plan.contains("\n- [fecha_límite::@Importado/Inicio]\n"));
+        assert!(plan.contains("import_id: obsidian:Proyectos/Plan\n"));
+
+        let image = assets.join("mapa_final.png");
+        let inicio = std::fs::read_to_string(notes.join("Importado/Inicio.md")).unwrap();
+        let expected = [
+            "Ver [[Importado/Proyectos/Plan|el plan]], [[Importado/Proyectos/Plan|El plan]] y [[Importado/Proyectos/Plan#Plan|Proyectos/Plan › Plan]].".to_string(),
+            format!("![mapa final]({})", image.display()),
+            format!("![otro]({}) [[Importado/Proyectos/Plan|doc]]", image.display()),
+            "> [!note]- Ojo".to_string(),
+            "> texto [prioridad::alta] <mark>importante</mark> <!--oculto-->".to_string(),
+            "`[[Plan]]` ![[falta.png]]".to_string(),
+        ];
+        for line in expected {
+            assert!(inicio.contains(&line), "falta {:?} en:\n{}", line, inicio);
+        }
+
+        let messages: Vec<&str> = report.issues.iter().map(|i| i.message.as_str()).collect();
+        assert!(messages.contains(&"Lienzo de Obsidian no importado"));
+        assert!(messages.contains(&"Callout de tipo 'custom' convertido en 'note'"));
+        assert!(messages.contains(&"Adjunto no encontrado: falta.png"));
+        assert!(messages.iter().any(|m| m.starts_with("Consulta Dataview")));
+        assert!(messages.iter().any(|m| m.starts_with("Tamaño de imagen")));
+        assert!(messages.contains(&"1 adjuntos sin referencias en las notas no se copiaron"));
+
+        // Repetir la importación no duplica adjuntos ni notas, y una nota del
+        // workspace que no viene del vault no se sobrescribe
+        std::fs::write(vault.join("Otra.md"), "del vault\n").unwrap();
+        std::fs::write(notes.join("Importado/Otra.md"), "mía\n").unwrap();
+        let again = ObsidianImporter::new(&vault, &notes)
+            .with_folder("Importado")
+            .with_assets_dir(&assets)
+            .import()
+            .unwrap();
+        assert_eq!(again.attachments, 0);
+        assert_eq!(
+            again.notes,
+            [
+                "Importado/Inicio",
+                "Importado/Otra 2",
+                "Importado/Proyectos/Plan"
+            ]
+        );
+        let mine = std::fs::read_to_string(notes.join("Importado/Otra.md")).unwrap();
+        assert_eq!(mine, "mía\n");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,686 @@
+//! Lenguaje de queries inline estilo Dataview
+//!
+//! Permite escribir en una nota bloques como:
+//!
+//! ```text
+//! TABLE title, status, due FROM #project AND "Work"
+//! WHERE status != "done"
+//! SORT due ASC
+//! LIMIT 20
+//! ```
+//!
+//! La query se traduce a las estructuras existentes de Bases
+//! (`Filter`, `FilterGroup`, `SortConfig`) y se ejecuta con `BaseQueryEngine`.
+//! Formas soportadas: `TABLE [WITHOUT ID] campos`, `LIST [campo]` y `TASK`.
+
+use regex::Regex;
+use std::sync::LazyLock;
+use thiserror::Error;
+
+use super::base::{
+    BaseView, ColumnConfig, Filter, FilterGroup, FilterLogic, FilterOperator, SortConfig,
+    SortDirection,
+};
+use super::property::PropertyValue;
+
+/// Regex para fechas ISO (YYYY-MM-DD)
+static DATE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+
+/// Regex para tareas markdown (- [ ] texto / - [x] texto)
+static TASK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*[-*+] \[([ xX])\] (.*)$").unwrap());
+
+// ============================================================================
+// ERRORES
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum QueryError {
+    #[error("Expected {expected}, found '{found}'")]
+    Unexpected {
+        expected: &'static str,
+        found: String,
+    },
+
+    #[error("Unexpected end of query, expected {0}")]
+    UnexpectedEnd(&'static str),
+
+    #[error("Unterminated string")]
+    UnterminatedString,
+
+    #[error("Duplicated clause: {0}")]
+    DuplicatedClause(&'static str),
+
+    #[error("Mixing AND and OR in the same clause is not supported")]
+    MixedLogic,
+
+    #[error("Only one SORT key is supported")]
+    MultipleSortKeys,
+}
+
+pub type Result<T> = std::result::Result<T, QueryError>;
+
+// ============================================================================
+// QUERY
+// ============================================================================
+
+/// Forma de la query (qué se muestra)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryKind {
+    /// Tabla con columnas
+    Table,
+    /// Lista de notas (opcionalmente con un campo)
+    List,
+    /// Tareas (- [ ]) de las notas que coinciden
+    Task,
+}
+
+/// Query inline parseada
+#[derive(Debug, Clone)]
+pub struct InlineQuery {
+    /// Forma de la query
+    pub kind: QueryKind,
+
+    /// Columnas (TABLE) o campo extra (LIST)
+    pub fields: Vec<ColumnConfig>,
+
+    /// Fuentes del FROM: tags (`#tag`) y carpetas (`"Carpeta"`, coincidencia exacta)
+    pub sources: FilterGroup,
+
+    /// Condiciones del WHERE
+    pub filter: FilterGroup,
+
+    /// Ordenamiento
+    pub sort: Option<SortConfig>,
+
+    /// Número máximo de resultados
+    pub limit: Option<usize>,
+}
+
+/// Una tarea extraída del contenido de una nota
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryTask {
+    pub completed: bool,
+    pub text: String,
+    /// Línea (0-indexed) dentro de la nota
+    pub line: usize,
+}
+
+impl InlineQuery {
+    /// Parsear el texto de un bloque de query
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        QueryParser { tokens, pos: 0 }.parse()
+    }
+
+    /// Convertir a una vista de Base equivalente (filtros del WHERE, columnas y orden)
+    pub fn to_view(&self) -> BaseView {
+        let mut view = match self.kind {
+            QueryKind::Table => BaseView::table("Query"),
+            QueryKind::List | QueryKind::Task => BaseView::list("Query"),
+        };
+        view.filter = self.filter.clone();
+        view.columns = self.fields.clone();
+        view.sort = self.sort.clone();
+        view
+    }
+}
+
+/// Extraer las tareas markdown de un contenido
+pub fn extract_tasks(content: &str) -> Vec<QueryTask> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            TASK_RE.captures(text).map(|caps| QueryTask {
+                completed: &caps[1] != " ",
+                text: caps[2].trim().to_string(),
+                line,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// TOKENIZER
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Tag(String),
+    Op(&'static str),
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(w) => w.clone(),
+            Token::Str(s) => format!("\"{}\"", s),
+            Token::Tag(t) => format!("#{}", t),
+            Token::Op(op) => op.to_string(),
+            Token::Comma => ",".to_string(),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, ',' | '"' | '=' | '!' | '<' | '>' | '#')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            ',' => {
+                chars.next();
+                tokens.push(Token::Comma);
+            }
+            '"' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => s.push(c),
+                        None => return Err(QueryError::UnterminatedString),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            '#' => {
+                chars.next();
+                let mut tag = String::new();
+                while let Some(c) = chars.next_if(|c| is_word_char(*c)) {
+                    tag.push(c);
+                }
+                tokens.push(Token::Tag(tag));
+            }
+            '=' | '<' | '>' => {
+                chars.next();
+                let or_equal = chars.next_if_eq(&'=').is_some();
+                let op = match (c, or_equal) {
+                    ('<', true) => "<=",
+                    ('<', false) => "<",
+                    ('>', true) => ">=",
+                    ('>', false) => ">",
+                    _ => "=",
+                };
+                tokens.push(Token::Op(op));
+            }
+            '!' => {
+                chars.next();
+                if chars.next_if_eq(&'=').is_some() {
+                    tokens.push(Token::Op("!="));
+                } else {
+                    // Negación pegada a una palabra: !contains, !status
+                    let mut word = String::from("!");
+                    while let Some(c) = chars.next_if(|c| is_word_char(*c)) {
+                        word.push(c);
+                    }
+                    tokens.push(Token::Word(word));
+                }
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|c| is_word_char(*c)) {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// ============================================================================
+// PARSER
+// ============================================================================
+
+const CLAUSE_KEYWORDS: [&str; 4] = ["FROM", "WHERE", "SORT", "LIMIT"];
+
+struct QueryParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl QueryParser {
+    fn parse(mut self) -> Result<InlineQuery> {
+        let kind = match self.next() {
+            Some(t) if t.is_keyword("TABLE") => QueryKind::Table,
+            Some(t) if t.is_keyword("LIST") => QueryKind::List,
+            Some(t) if t.is_keyword("TASK") => QueryKind::Task,
+            Some(t) => {
+                return Err(QueryError::Unexpected {
+                    expected: "TABLE, LIST or TASK",
+                    found: t.describe(),
+                });
+            }
+            None => return Err(QueryError::UnexpectedEnd("TABLE, LIST or TASK")),
+        };
+
+        let fields = match kind {
+            QueryKind::Table => self.parse_table_fields()?,
+            QueryKind::List if !self.at_clause() => vec![self.parse_field()?],
+            _ => Vec::new(),
+        };
+
+        let mut query = InlineQuery {
+            kind,
+            fields,
+            sources: FilterGroup::default(),
+            filter: FilterGroup::default(),
+            sort: None,
+            limit: None,
+        };
+
+        let (mut seen_from, mut seen_where) = (false, false);
+        while let Some(token) = self.next() {
+            if token.is_keyword("FROM") {
+                if std::mem::replace(&mut seen_from, true) {
+                    return Err(QueryError::DuplicatedClause("FROM"));
+                }
+                query.sources = self.parse_sources()?;
+            } else if token.is_keyword("WHERE") {
+                if std::mem::replace(&mut seen_where, true) {
+                    return Err(QueryError::DuplicatedClause("WHERE"));
+                }
+                query.filter = self.parse_conditions()?;
+            } else if token.is_keyword("SORT") {
+                if query.sort.is_some() {
+                    return Err(QueryError::DuplicatedClause("SORT"));
+                }
+                query.sort = Some(self.parse_sort()?);
+            } else if token.is_keyword("LIMIT") {
+                if query.limit.is_some() {
+                    return Err(QueryError::DuplicatedClause("LIMIT"));
+                }
+                query.limit = Some(self.parse_limit()?);
+            } else {
+                return Err(QueryError::Unexpected {
+                    expected: "FROM, WHERE, SORT or LIMIT",
+                    found: token.describe(),
+                });
+            }
+        }
+
+        Ok(query)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn at_clause(&self) -> bool {
+        match self.peek() {
+            Some(token) => CLAUSE_KEYWORDS.iter().any(|kw| token.is_keyword(kw)),
+            None => true,
+        }
+    }
+
+    /// Consumir el siguiente token si es la palabra clave indicada
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().is_some_and(|t| t.is_keyword(keyword)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_word(&mut self, expected: &'static str) -> Result<String> {
+        match self.next() {
+            Some(Token::Word(w)) => Ok(w),
+            Some(t) => Err(QueryError::Unexpected {
+                expected,
+                found: t.describe(),
+            }),
+            None => Err(QueryError::UnexpectedEnd(expected)),
+        }
+    }
+
+    /// TABLE [WITHOUT ID] campo [AS "Título"], ...
+    fn parse_table_fields(&mut self) -> Result<Vec<ColumnConfig>> {
+        let without_id = if self.eat_keyword("WITHOUT") {
+            if !self.eat_keyword("ID") {
+                return Err(self.unexpected("ID"));
+            }
+            true
+        } else {
+            false
+        };
+
+        let mut fields = Vec::new();
+        if !self.at_clause() {
+            fields.push(self.parse_field()?);
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                fields.push(self.parse_field()?);
+            }
+        }
+
+        // La columna del título va siempre primero salvo con WITHOUT ID
+        let has_title = fields
+            .iter()
+            .any(|f| matches!(f.property.as_str(), "title" | "name"));
+        if !without_id && !has_title {
+            fields.insert(0, ColumnConfig::new("title"));
+        }
+
+        Ok(fields)
+    }
+
+    /// campo [AS "Título"]
+    fn parse_field(&mut self) -> Result<ColumnConfig> {
+        let property = self.expect_word("a property name")?;
+        let mut column = ColumnConfig::new(property);
+
+        if self.eat_keyword("AS") {
+            match self.next() {
+                Some(Token::Str(title)) | Some(Token::Word(title)) => {
+                    column = column.with_title(title);
+                }
+                Some(t) => {
+                    return Err(QueryError::Unexpected {
+                        expected: "a column title",
+                        found: t.describe(),
+                    });
+                }
+                None => return Err(QueryError::UnexpectedEnd("a column title")),
+            }
+        }
+
+        Ok(column)
+    }
+
+    /// FROM #tag AND "Carpeta" ...
+    fn parse_sources(&mut self) -> Result<FilterGroup> {
+        let mut filters = Vec::new();
+        let logic = self.parse_logic_chain(|parser| {
+            let filter = match parser.next() {
+                Some(Token::Tag(tag)) => Filter::has_tag(tag),
+                Some(Token::Str(folder)) => Filter::equals("folder", PropertyValue::Text(folder)),
+                Some(t) => {
+                    return Err(QueryError::Unexpected {
+                        expected: "a #tag or a \"folder\"",
+                        found: t.describe(),
+                    });
+                }
+                None => return Err(QueryError::UnexpectedEnd("a #tag or a \"folder\"")),
+            };
+            filters.push(filter);
+            Ok(())
+        })?;
+
+        Ok(FilterGroup { filters, logic })
+    }
+
+    /// WHERE cond AND|OR cond ...
+    fn parse_conditions(&mut self) -> Result<FilterGroup> {
+        let mut filters = Vec::new();
+        let logic = self.parse_logic_chain(|parser| {
+            filters.push(parser.parse_condition()?);
+            Ok(())
+        })?;
+
+        Ok(FilterGroup { filters, logic })
+    }
+
+    /// Parsear elementos unidos por AND/OR (sin mezclar) y devolver la lógica usada
+    fn parse_logic_chain(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<()>,
+    ) -> Result<FilterLogic> {
+        item(self)?;
+
+        let mut logic = None;
+        loop {
+            let current = if self.eat_keyword("AND") {
+                FilterLogic::And
+            } else if self.eat_keyword("OR") {
+                FilterLogic::Or
+            } else {
+                break;
+            };
+
+            if logic.as_ref().is_some_and(|l| *l != current) {
+                return Err(QueryError::MixedLogic);
+            }
+            logic = Some(current);
+            item(self)?;
+        }
+
+        Ok(logic.unwrap_or_default())
+    }
+
+    /// propiedad op valor | propiedad IS [NOT] EMPTY | propiedad | !propiedad
+    fn parse_condition(&mut self) -> Result<Filter> {
+        let property = self.expect_word("a property name")?;
+
+        if let Some(negated) = property.strip_prefix('!') {
+            return Ok(Filter::new(
+                normalize_property(negated),
+                FilterOperator::IsEmpty,
+                PropertyValue::Null,
+            ));
+        }
+        let property = normalize_property(&property);
+
+        if self.eat_keyword("IS") {
+            let operator = if self.eat_keyword("NOT") {
+                FilterOperator::IsNotEmpty
+            } else {
+                FilterOperator::IsEmpty
+            };
+            if !self.eat_keyword("EMPTY") {
+                return Err(self.unexpected("EMPTY"));
+            }
+            return Ok(Filter::new(property, operator, PropertyValue::Null));
+        }
+
+        let operator = match self.peek() {
+            Some(Token::Op(op)) => match *op {
+                "=" => FilterOperator::Equals,
+                "!=" => FilterOperator::NotEquals,
+                ">" => FilterOperator::GreaterThan,
+                ">=" => FilterOperator::GreaterOrEqual,
+                "<" => FilterOperator::LessThan,
+                _ => FilterOperator::LessOrEqual,
+            },
+            Some(Token::Word(w)) => match w.to_lowercase().as_str() {
+                "contains" => FilterOperator::Contains,
+                "!contains" => FilterOperator::NotContains,
+                "startswith" => FilterOperator::StartsWith,
+                "endswith" => FilterOperator::EndsWith,
+                // Sin operador: la propiedad debe existir
+                _ => return Ok(Filter::is_not_empty(property)),
+            },
+            _ => return Ok(Filter::is_not_empty(property)),
+        };
+        self.pos += 1;
+
+        let value = match self.next() {
+            Some(Token::Str(s)) if DATE_RE.is_match(&s) => PropertyValue::Date(s),
+            Some(Token::Str(s)) => PropertyValue::Text(s),
+            Some(Token::Tag(tag)) => PropertyValue::Text(tag),
+            Some(Token::Word(w)) => parse_bare_value(&w),
+            Some(t) => {
+                return Err(QueryError::Unexpected {
+                    expected: "a value",
+                    found: t.describe(),
+                });
+            }
+            None => return Err(QueryError::UnexpectedEnd("a value")),
+        };
+
+        Ok(Filter::new(property, operator, value))
+    }
+
+    /// SORT propiedad [ASC|DESC]
+    fn parse_sort(&mut self) -> Result<SortConfig> {
+        let property = normalize_property(&self.expect_word("a property name")?);
+        let direction = if self.eat_keyword("DESC") {
+            SortDirection::Desc
+        } else {
+            self.eat_keyword("ASC");
+            SortDirection::Asc
+        };
+
+        if self.peek() == Some(&Token::Comma) {
+            return Err(QueryError::MultipleSortKeys);
+        }
+
+        Ok(SortConfig {
+            property,
+            direction,
+        })
+    }
+
+    /// LIMIT n
+    fn parse_limit(&mut self) -> Result<usize> {
+        let word = self.expect_word("a number")?;
+        word.parse().map_err(|_| QueryError::Unexpected {
+            expected: "a number",
+            found: word,
+        })
+    }
+
+    fn unexpected(&self, expected: &'static str) -> QueryError {
+        match self.peek() {
+            Some(t) => QueryError::Unexpected {
+                expected,
+                found: t.describe(),
+            },
+            None => QueryError::UnexpectedEnd(expected),
+        }
+    }
+}
+
+/// Alias de propiedades built-in a los nombres que usa `BaseQueryEngine`
+fn normalize_property(property: &str) -> String {
+    match property {
+        "created" => "created_at".to_string(),
+        "modified" | "updated" => "updated_at".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Valor sin comillas: número, booleano, fecha, `today` o texto
+fn parse_bare_value(word: &str) -> PropertyValue {
+    match word.to_lowercase().as_str() {
+        "true" => return PropertyValue::Checkbox(true),
+        "false" => return PropertyValue::Checkbox(false),
+        "today" => {
+            return PropertyValue::Date(chrono::Local::now().format("%Y-%m-%d").to_string());
+        }
+        _ => {}
+    }
+
+    if DATE_RE.is_match(word) {
+        PropertyValue::Date(word.to_string())
+    } else if let Ok(n) = word.parse::<f64>() {
+        PropertyValue::Number(n)
+    } else {
+        PropertyValue::Text(word.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_table_query() {
+        let query = InlineQuery::parse(
+            r#"TABLE title, status, due AS "Due date" FROM #project AND "Work"
+               WHERE status != "done" SORT due ASC LIMIT 20"#,
+        )
+        .unwrap();
+
+        assert_eq!(query.kind, QueryKind::Table);
+        let props: Vec<&str> = query.fields.iter().map(|f| f.property.as_str()).collect();
+        assert_eq!(props, vec!["title", "status", "due"]);
+        assert_eq!(query.fields[2].display_title(), "Due date");
+
+        assert_eq!(query.sources.filters.len(), 2);
+        assert_eq!(query.sources.filters[0].property, "tags");
+        assert_eq!(query.sources.filters[1].property, "folder");
+
+        assert_eq!(query.filter.filters.len(), 1);
+        assert_eq!(query.filter.filters[0].operator, FilterOperator::NotEquals);
+
+        let sort = query.sort.unwrap();
+        assert_eq!(sort.property, "due");
+        assert_eq!(sort.direction, SortDirection::Asc);
+        assert_eq!(query.limit, Some(20));
+    }
+
+    #[test]
+    fn test_parse_list_and_task() {
+        let query = InlineQuery::parse("LIST status FROM #a OR #b").unwrap();
+        assert_eq!(query.kind, QueryKind::List);
+        assert_eq!(query.fields.len(), 1);
+        assert_eq!(query.sources.logic, FilterLogic::Or);
+
+        let query = InlineQuery::parse("task where priority >= 2 and !archived").unwrap();
+        assert_eq!(query.kind, QueryKind::Task);
+        assert_eq!(query.filter.filters[0].value, PropertyValue::Number(2.0));
+        assert_eq!(query.filter.filters[1].operator, FilterOperator::IsEmpty);
+
+        // TABLE sin columnas incluye el título
+        let query = InlineQuery::parse("TABLE").unwrap();
+        assert_eq!(query.fields.len(), 1);
+        let query = InlineQuery::parse("TABLE WITHOUT ID status").unwrap();
+        assert_eq!(query.fields[0].property, "status");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            InlineQuery::parse("SELECT *"),
+            Err(QueryError::Unexpected { .. })
+        ));
+        assert!(matches!(
+            InlineQuery::parse("LIST WHERE status = \"open"),
+            Err(QueryError::UnterminatedString)
+        ));
+        assert_eq!(
+            InlineQuery::parse("LIST WHERE a = 1 AND b = 2 OR c = 3").unwrap_err(),
+            QueryError::MixedLogic
+        );
+        assert_eq!(
+            InlineQuery::parse("LIST WHERE a =").unwrap_err(),
+            QueryError::UnexpectedEnd("a value")
+        );
+        assert!(InlineQuery::parse("LIST LIMIT many").is_err());
+        assert!(InlineQuery::parse("LIST SORT a, b").is_err());
+    }
+
+    #[test]
+    fn test_extract_tasks() {
+        let tasks = extract_tasks("# Title\n- [ ] open task\n  - [x] done task\ntext");
+        assert_eq!(tasks.len(), 2);
+        assert!(!tasks[0].completed);
+        assert_eq!(tasks[0].text, "open task");
+        assert!(tasks[1].completed);
+        assert_eq!(tasks[1].line, 2);
+    }
+}
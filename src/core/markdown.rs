@@ -23,6 +23,16 @@ pub enum StyleType {
     YouTubeVideo { video_id: String, url: String },
 }
 
+/// Encabezado markdown (`#` o subrayado) y la línea donde empieza
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// Nivel 1-6
+    pub level: u8,
+    pub title: String,
+    /// Línea del encabezado (0-based)
+    pub line: usize,
+}
+
 /// Parser de markdown que extrae información de estilo
 pub struct MarkdownParser {
     text: String,
@@ -182,6 +192,44 @@ impl MarkdownParser {
 
         styles
     }
+
+    /// Encabezados del documento en orden, con las mismas opciones que el preview HTML
+    /// (los `#` dentro de bloques de código no cuentan)
+    pub fn headings(&self) -> Vec<Heading> {
+        let mut options = Options::empty();
+        options.insert(Options::ENABLE_TABLES);
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+        options.insert(Options::ENABLE_TASKLISTS);
+        options.insert(Options::ENABLE_FOOTNOTES);
+
+        let mut headings = Vec::new();
+        let mut current: Option<(u8, usize, String)> = None;
+
+        for (event, range) in Parser::new_ext(&self.text, options).into_offset_iter() {
+            match event {
+                Event::Start(Tag::Heading { level, .. }) => {
+                    current = Some((level as u8, range.start, String::new()));
+                }
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((_, _, title)) = current.as_mut() {
+                        title.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::Heading(_)) => {
+                    if let Some((level, start, title)) = current.take() {
+                        headings.push(Heading {
+                            level,
+                            title: title.trim().to_string(),
+                            line: self.text[..start].matches('\n').count(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        headings
+    }
 }
 
 #[cfg(test)]
@@ -199,6 +247,18 @@ mod tests {
         assert!(styles.iter().any(|s| s.style_type == StyleType::Heading3));
     }
 
+    #[test]
+    fn test_headings() {
+        let text = "# Uno\n\n```\n# no\n```\nDos `x`\n---\n### Tres".to_string();
+        let headings = MarkdownParser::new(text).headings();
+
+        assert_eq!(headings.len(), 3);
+        assert_eq!((headings[0].level, headings[0].line), (1, 0));
+        assert_eq!(headings[1].title, "Dos x");
+        assert_eq!((headings[1].level, headings[1].line), (2, 5));
+        assert_eq!((headings[2].level, headings[2].line), (3, 7));
+    }
+
     #[test]
     fn test_bold_italic() {
         let text = "**bold** and *italic*".to_string();
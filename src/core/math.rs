@@ -0,0 +1,1119 @@
+//! Fórmulas LaTeX en las notas: `$...$` (en línea) y `$$...$$` (en bloque)
+//!
+//! Las fórmulas se convierten a MathML, que WebKit dibuja de forma nativa: no hace
+//! falta ninguna librería JavaScript ni conexión, y el HTML exportado las conserva.
+//! Se cubre el subconjunto de LaTeX habitual en apuntes (fracciones, raíces,
+//! índices, letras griegas, operadores, acentos, fuentes, `\left...\right` y
+//! entornos de matrices/casos/alineados). Los comandos desconocidos se muestran
+//! resaltados en vez de romper la fórmula.
+
+use std::ops::Range;
+
+/// Una fórmula encontrada en el texto
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathSpan {
+    /// Rango en bytes de la fórmula, delimitadores incluidos
+    pub range: Range<usize>,
+    /// Código LaTeX sin los delimitadores
+    pub tex: String,
+    /// `$$...$$` (bloque) o `$...$` (en línea)
+    pub display: bool,
+}
+
+/// Buscar las fórmulas de un texto markdown, fuera de bloques y spans de código.
+///
+/// Sigue las reglas de Pandoc para `$...$`: el `$` de apertura no puede ir seguido
+/// de un espacio, el de cierre no puede ir precedido de un espacio ni seguido de un
+/// dígito, y `\$` es un dólar literal. Así "cuesta $5 y $10" no es una fórmula.
+pub fn find_math(text: &str) -> Vec<MathSpan> {
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut in_fence: Option<&str> = None;
+    let mut line_start = 0;
+
+    while line_start < text.len() {
+        let line_end = text[line_start..]
+            .find('\n')
+            .map_or(text.len(), |i| line_start + i + 1);
+        let trimmed = text[line_start..line_end].trim_start();
+
+        let fence = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(*f));
+        match (in_fence, fence) {
+            (Some(open), Some(close)) if open == close => {
+                in_fence = None;
+                line_start = line_end;
+                continue;
+            }
+            (Some(_), _) => {
+                line_start = line_end;
+                continue;
+            }
+            (None, Some(open)) => {
+                in_fence = Some(open);
+                line_start = line_end;
+                continue;
+            }
+            (None, None) => {}
+        }
+
+        // Recorrer la línea; una fórmula o un span de código puede seguir en las siguientes
+        let mut i = line_start;
+        let mut next_line = line_end;
+        while i < line_end {
+            match bytes[i] {
+                // Saltar el carácter escapado entero, aunque ocupe varios bytes
+                b'\\' => i += text[i + 1..].chars().next().map_or(1, char::len_utf8) + 1,
+                b'`' => {
+                    let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                    let ticks = &text[i..i + run];
+                    i = match find_closing_backticks(text, i + run, ticks) {
+                        Some(end) => end,
+                        None => i + run,
+                    };
+                }
+                b'$' => match math_at(text, i) {
+                    Some(span) => {
+                        i = span.range.end;
+                        spans.push(span);
+                    }
+                    None => i += 1,
+                },
+                _ => i += 1,
+            }
+            if i > next_line {
+                next_line = text
+                    .get(i..)
+                    .and_then(|rest| rest.find('\n'))
+                    .map_or(text.len(), |n| i + n + 1);
+            }
+        }
+        line_start = next_line.max(line_end);
+    }
+
+    spans
+}
+
+/// Cierre de un span de código con el mismo número de backticks (sin cruzar párrafos)
+fn find_closing_backticks(text: &str, from: usize, ticks: &str) -> Option<usize> {
+    let paragraph_end = text[from..].find("\n\n").map_or(text.len(), |i| from + i);
+    let mut search = from;
+    while let Some(pos) = text[search..paragraph_end].find(ticks) {
+        let start = search + pos;
+        let end = start + ticks.len();
+        if text.as_bytes().get(end) != Some(&b'`') {
+            return Some(end);
+        }
+        search = end + text[end..].bytes().take_while(|&b| b == b'`').count();
+    }
+    None
+}
+
+/// Fórmula que empieza en el `$` de la posición `start`
+fn math_at(text: &str, start: usize) -> Option<MathSpan> {
+    let bytes = text.as_bytes();
+    let paragraph_end = text[start..].find("\n\n").map_or(text.len(), |i| start + i);
+
+    if text[start..].starts_with("$$") {
+        let body = start + 2;
+        let close = find_unescaped(&text[body..paragraph_end], "$$")? + body;
+        let tex = text[body..close].trim();
+        return (!tex.is_empty()).then(|| MathSpan {
+            range: start..close + 2,
+            tex: tex.to_string(),
+            display: true,
+        });
+    }
+
+    let body = start + 1;
+    if bytes.get(body).is_none_or(|b| b.is_ascii_whitespace()) {
+        return None;
+    }
+    let mut search = body;
+    while let Some(pos) = find_unescaped(&text[search..paragraph_end], "$") {
+        let close = search + pos;
+        let before_ok = !bytes[close - 1].is_ascii_whitespace();
+        let after_ok = !bytes.get(close + 1).is_some_and(u8::is_ascii_digit);
+        if before_ok && after_ok && close > body {
+            return Some(MathSpan {
+                range: start..close + 1,
+                tex: text[body..close].to_string(),
+                display: false,
+            });
+        }
+        search = close + 1;
+    }
+    None
+}
+
+/// Posición de `pattern` que no está escapado con `\`
+fn find_unescaped(text: &str, pattern: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < text.len() {
+        if bytes[i] == b'\\' {
+            i += 2;
+        } else if bytes[i..].starts_with(pattern.as_bytes()) {
+            return Some(i);
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Convertir una fórmula LaTeX a un elemento `<math>` de MathML.
+/// El código original se conserva como anotación (para copiarlo o exportarlo).
+pub fn tex_to_mathml(tex: &str, display: bool) -> String {
+    let mut parser = TexParser::new(tex, display);
+    let mut nodes = Vec::new();
+    loop {
+        nodes.extend(parser.parse_sequence());
+        if parser.at_end() {
+            break;
+        }
+        // Un `}`, `&`, `\\` o `\right` sin pareja: se ignora y se sigue
+        parser.skip_stopper();
+    }
+
+    format!(
+        r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="{}"><semantics>{}<annotation encoding="application/x-tex">{}</annotation></semantics></math>"#,
+        if display { "block" } else { "inline" },
+        mrow(nodes),
+        escape(tex.trim())
+    )
+}
+
+/// Elemento de la fórmula ya convertido
+struct Atom {
+    mathml: String,
+    /// Operador grande cuyos índices van encima/debajo en bloque (∑, lim...)
+    limits: bool,
+}
+
+impl Atom {
+    fn new(mathml: String) -> Self {
+        Self {
+            mathml,
+            limits: false,
+        }
+    }
+}
+
+struct TexParser {
+    chars: Vec<char>,
+    pos: usize,
+    display: bool,
+    /// `mathvariant` activo dentro de \mathbf{...}, \mathbb{...}...
+    variant: Option<&'static str>,
+}
+
+impl TexParser {
+    fn new(tex: &str, display: bool) -> Self {
+        Self {
+            chars: tex.chars().collect(),
+            pos: 0,
+            display,
+            variant: None,
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Nombre del comando en la posición actual (sin consumirlo)
+    fn peek_command(&self) -> Option<String> {
+        if self.peek() != Some('\\') {
+            return None;
+        }
+        let rest = &self.chars[self.pos + 1..];
+        let letters: String = rest
+            .iter()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect();
+        if letters.is_empty() {
+            rest.first().map(|c| c.to_string())
+        } else {
+            Some(letters)
+        }
+    }
+
+    fn read_command(&mut self) -> String {
+        let name = self.peek_command().unwrap_or_default();
+        self.pos += 1 + name.chars().count();
+        name
+    }
+
+    /// ¿Termina aquí la secuencia actual? (`}`, `&`, `\\`, `\right`, `\end`)
+    fn at_stopper(&self) -> bool {
+        match self.peek() {
+            Some('}' | '&') => true,
+            Some('\\') => matches!(
+                self.peek_command().as_deref(),
+                Some("\\" | "right" | "end" | "cr")
+            ),
+            _ => false,
+        }
+    }
+
+    fn skip_stopper(&mut self) {
+        match self.peek() {
+            Some('\\') => {
+                let name = self.read_command();
+                if name == "right" {
+                    self.read_delimiter();
+                } else if name == "end" {
+                    self.read_raw_group();
+                }
+            }
+            Some(_) => self.pos += 1,
+            None => {}
+        }
+    }
+
+    /// Secuencia de elementos hasta fin de texto o un separador
+    fn parse_sequence(&mut self) -> Vec<String> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.at_end() || self.at_stopper() {
+                return nodes;
+            }
+            if let Some(node) = self.parse_term() {
+                nodes.push(node);
+            }
+        }
+    }
+
+    /// Un elemento con sus posibles subíndices, superíndices y primas
+    fn parse_term(&mut self) -> Option<String> {
+        let base = self.parse_atom()?;
+        let mut sub = None;
+        let mut sup: Option<String> = None;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.pos += 1;
+                    sub = Some(self.parse_argument());
+                }
+                Some('^') if sup.is_none() => {
+                    self.pos += 1;
+                    sup = Some(self.parse_argument());
+                }
+                Some('\\')
+                    if matches!(self.peek_command().as_deref(), Some("limits" | "nolimits")) =>
+                {
+                    self.read_command();
+                }
+                Some('\'') => {
+                    let mut primes = String::new();
+                    while self.peek() == Some('\'') {
+                        self.pos += 1;
+                        primes.push('′');
+                    }
+                    let primes = format!("<mo>{}</mo>", primes);
+                    sup = Some(match sup {
+                        Some(sup) => mrow(vec![primes, sup]),
+                        None => primes,
+                    });
+                }
+                _ => break,
+            }
+        }
+
+        let (under, over) = if base.limits && self.display {
+            ("munder", "mover")
+        } else {
+            ("msub", "msup")
+        };
+        Some(match (sub, sup) {
+            (None, None) => base.mathml,
+            (Some(sub), None) => format!("<{under}>{}{}</{under}>", base.mathml, sub),
+            (None, Some(sup)) => format!("<{over}>{}{}</{over}>", base.mathml, sup),
+            (Some(sub), Some(sup)) => {
+                let both = if base.limits && self.display {
+                    "munderover"
+                } else {
+                    "msubsup"
+                };
+                format!("<{both}>{}{}{}</{both}>", base.mathml, sub, sup)
+            }
+        })
+    }
+
+    /// Argumento de un comando o índice: `{grupo}` o un único símbolo
+    fn parse_argument(&mut self) -> String {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.parse_group(),
+            Some(c) if c.is_ascii_digit() => {
+                self.pos += 1;
+                format!("<mn>{}</mn>", c)
+            }
+            Some(_) if !self.at_stopper() => self
+                .parse_atom()
+                .map(|atom| atom.mathml)
+                .unwrap_or_else(|| "<mrow></mrow>".to_string()),
+            _ => "<mrow></mrow>".to_string(),
+        }
+    }
+
+    /// `{...}` como un único `<mrow>`
+    fn parse_group(&mut self) -> String {
+        self.pos += 1;
+        let mut nodes = Vec::new();
+        loop {
+            nodes.extend(self.parse_sequence());
+            match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                None => break,
+                // `&` o `\\` fuera de un entorno: se ignoran
+                Some(_) => {
+                    if matches!(self.peek_command().as_deref(), Some("right" | "end")) {
+                        break;
+                    }
+                    self.skip_stopper();
+                }
+            }
+        }
+        mrow(nodes)
+    }
+
+    /// Contenido literal de `{...}` (texto, nombres de entorno)
+    fn read_raw_group(&mut self) -> String {
+        self.skip_whitespace();
+        if self.peek() != Some('{') {
+            return String::new();
+        }
+        self.pos += 1;
+        let mut depth = 0;
+        let mut raw = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                '\\' => {
+                    // \{ \} \$ ... dentro de \text
+                    if let Some(next) = self.peek().filter(|n| !n.is_ascii_alphabetic()) {
+                        self.pos += 1;
+                        raw.push(next);
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            raw.push(c);
+        }
+        raw
+    }
+
+    /// Argumento opcional `[...]` (índice de \sqrt)
+    fn parse_optional(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        if self.peek() != Some('[') {
+            return None;
+        }
+        self.pos += 1;
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == ']' {
+                self.pos += 1;
+                break;
+            }
+            if self.at_stopper() {
+                break;
+            }
+            self.skip_whitespace();
+            if self.peek() == Some(']') {
+                continue;
+            }
+            if let Some(node) = self.parse_term() {
+                nodes.push(node);
+            }
+        }
+        Some(mrow(nodes))
+    }
+
+    fn parse_atom(&mut self) -> Option<Atom> {
+        self.skip_whitespace();
+        let c = self.peek()?;
+        match c {
+            '{' => Some(Atom::new(self.parse_group())),
+            '\\' => self.parse_command(),
+            '0'..='9' | '.'
+                if c != '.'
+                    || self
+                        .chars
+                        .get(self.pos + 1)
+                        .is_some_and(char::is_ascii_digit) =>
+            {
+                let mut number = String::new();
+                while let Some(d) = self.peek() {
+                    let decimal = d == '.'
+                        && self
+                            .chars
+                            .get(self.pos + 1)
+                            .is_some_and(char::is_ascii_digit);
+                    if d.is_ascii_digit() || decimal {
+                        number.push(d);
+                        self.pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                Some(Atom::new(format!("<mn>{}</mn>", number)))
+            }
+            '~' => {
+                self.pos += 1;
+                Some(Atom::new(space("0.25em")))
+            }
+            c if c.is_alphabetic() => {
+                self.pos += 1;
+                Some(Atom::new(self.identifier(&c.to_string())))
+            }
+            _ => {
+                self.pos += 1;
+                let op = match c {
+                    '-' => "−".to_string(),
+                    '*' => "∗".to_string(),
+                    _ => escape(&c.to_string()),
+                };
+                Some(Atom::new(format!("<mo>{}</mo>", op)))
+            }
+        }
+    }
+
+    /// `<mi>` con la fuente activa (las mayúsculas griegas van en redonda)
+    fn identifier(&self, name: &str) -> String {
+        let upright_greek = name.chars().all(|c| ('Α'..='Ω').contains(&c));
+        match self.variant.or(upright_greek.then_some("normal")) {
+            Some(variant) => format!(r#"<mi mathvariant="{}">{}</mi>"#, variant, escape(name)),
+            None => format!("<mi>{}</mi>", escape(name)),
+        }
+    }
+
+    fn parse_command(&mut self) -> Option<Atom> {
+        let name = self.read_command();
+        let command = name.as_str();
+
+        if let Some(letter) = greek(command) {
+            return Some(Atom::new(self.identifier(letter)));
+        }
+        if let Some(symbol) = identifier_symbol(command) {
+            return Some(Atom::new(format!("<mi>{}</mi>", symbol)));
+        }
+        if let Some(op) = operator(command) {
+            return Some(Atom::new(format!("<mo>{}</mo>", escape(op))));
+        }
+        if let Some(op) = big_operator(command) {
+            return Some(Atom {
+                mathml: format!("<mo>{}</mo>", op),
+                limits: !op.starts_with(['∫', '∬', '∭', '∮']),
+            });
+        }
+        if let Some(limits) = function_name(command) {
+            return Some(Atom {
+                mathml: format!("<mi>{}</mi>", command),
+                limits,
+            });
+        }
+        if let Some(width) = spacing(command) {
+            return Some(Atom::new(space(width)));
+        }
+        if let Some(variant) = font_variant(command) {
+            let previous = self.variant.replace(variant);
+            let arg = self.parse_argument();
+            self.variant = previous;
+            return Some(Atom::new(arg));
+        }
+        if let Some((accent, over)) = accent(command) {
+            let arg = self.parse_argument();
+            let tag = if over { "mover" } else { "munder" };
+            let attr = if over { "accent" } else { "accentunder" };
+            return Some(Atom::new(format!(
+                r#"<{tag} {attr}="true">{}<mo stretchy="true">{}</mo></{tag}>"#,
+                arg, accent
+            )));
+        }
+
+        let mathml = match command {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let num = self.parse_argument();
+                let den = self.parse_argument();
+                format!("<mfrac>{}{}</mfrac>", num, den)
+            }
+            "binom" | "dbinom" | "tbinom" => {
+                let n = self.parse_argument();
+                let k = self.parse_argument();
+                format!(
+                    r#"<mrow><mo>(</mo><mfrac linethickness="0">{}{}</mfrac><mo>)</mo></mrow>"#,
+                    n, k
+                )
+            }
+            "sqrt" => match self.parse_optional() {
+                Some(index) => format!("<mroot>{}{}</mroot>", self.parse_argument(), index),
+                None => format!("<msqrt>{}</msqrt>", self.parse_argument()),
+            },
+            "text" | "textrm" | "textnormal" | "mbox" | "textit" | "textbf" => {
+                let text = self.read_raw_group();
+                let variant = match command {
+                    "textit" => r#" mathvariant="italic""#,
+                    "textbf" => r#" mathvariant="bold""#,
+                    _ => "",
+                };
+                format!("<mtext{}>{}</mtext>", variant, escape(&text))
+            }
+            "operatorname" => {
+                let name = self.read_raw_group();
+                format!("<mi>{}</mi>", escape(name.trim()))
+            }
+            "overbrace" | "underbrace" => {
+                let arg = self.parse_argument();
+                let (tag, brace) = if command == "overbrace" {
+                    ("mover", "⏞")
+                } else {
+                    ("munder", "⏟")
+                };
+                return Some(Atom {
+                    mathml: format!(
+                        r#"<{tag}>{}<mo stretchy="true">{}</mo></{tag}>"#,
+                        arg, brace
+                    ),
+                    limits: true,
+                });
+            }
+            "left" => self.parse_left_right(),
+            "middle" => format!(
+                r#"<mo stretchy="true">{}</mo>"#,
+                escape(&self.read_delimiter())
+            ),
+            "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl" | "Bigr" | "biggl"
+            | "biggr" | "Biggl" | "Biggr" => {
+                let size = match command.trim_end_matches(['l', 'r']) {
+                    "big" => "1.2em",
+                    "Big" => "1.8em",
+                    "bigg" => "2.4em",
+                    _ => "3em",
+                };
+                format!(
+                    r#"<mo minsize="{size}" maxsize="{size}">{}</mo>"#,
+                    escape(&self.read_delimiter())
+                )
+            }
+            "begin" => {
+                let env = self.read_raw_group();
+                self.parse_environment(&env)
+            }
+            "displaystyle" | "textstyle" => {
+                let rest = self.parse_sequence();
+                format!(
+                    r#"<mstyle displaystyle="{}">{}</mstyle>"#,
+                    command == "displaystyle",
+                    rest.join("")
+                )
+            }
+            // Comandos de solo presentación que no cambian el contenido
+            "limits" | "nolimits" | "nonumber" | "notag" => return None,
+            "color" | "textcolor" => {
+                let color = self.read_raw_group();
+                let arg = if command == "textcolor" {
+                    self.parse_argument()
+                } else {
+                    mrow(self.parse_sequence())
+                };
+                format!(r#"<mstyle mathcolor="{}">{}</mstyle>"#, escape(&color), arg)
+            }
+            _ => format!(r#"<merror><mtext>\{}</mtext></merror>"#, escape(command)),
+        };
+        Some(Atom::new(mathml))
+    }
+
+    /// Delimitador de \left, \right, \middle y \big: `(`, `\{`, `\langle`, `.`...
+    fn read_delimiter(&mut self) -> String {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('\\') => {
+                let name = self.read_command();
+                match name.as_str() {
+                    "{" | "lbrace" => "{".to_string(),
+                    "}" | "rbrace" => "}".to_string(),
+                    "|" | "Vert" | "rVert" | "lVert" => "‖".to_string(),
+                    "vert" | "lvert" | "rvert" => "|".to_string(),
+                    other => operator(other).unwrap_or("").to_string(),
+                }
+            }
+            Some('.') => {
+                self.pos += 1;
+                String::new()
+            }
+            Some(c) => {
+                self.pos += 1;
+                c.to_string()
+            }
+            None => String::new(),
+        }
+    }
+
+    fn parse_left_right(&mut self) -> String {
+        let open = self.read_delimiter();
+        let mut nodes = Vec::new();
+        let mut close = String::new();
+        loop {
+            nodes.extend(self.parse_sequence());
+            match self.peek_command().as_deref() {
+                Some("right") => {
+                    self.read_command();
+                    close = self.read_delimiter();
+                    break;
+                }
+                Some("end") => break,
+                _ if self.at_end() || self.peek() == Some('}') => break,
+                _ => self.skip_stopper(),
+            }
+        }
+        let fence = |d: &str| match d {
+            "" => String::new(),
+            d => format!(r#"<mo fence="true" stretchy="true">{}</mo>"#, escape(d)),
+        };
+        format!(
+            "<mrow>{}{}{}</mrow>",
+            fence(&open),
+            nodes.join(""),
+            fence(&close)
+        )
+    }
+
+    /// Entornos `matrix`, `cases`, `aligned`... como `<mtable>`
+    fn parse_environment(&mut self, env: &str) -> String {
+        let env = env.trim();
+        if env == "array" {
+            // La especificación de columnas ({cc|l}) no se usa
+            self.read_raw_group();
+        }
+
+        let mut rows: Vec<Vec<String>> = vec![Vec::new()];
+        let mut cell = Vec::new();
+        loop {
+            cell.extend(self.parse_sequence());
+            match self.peek() {
+                Some('&') => {
+                    self.pos += 1;
+                    rows.last_mut()
+                        .unwrap()
+                        .push(mrow(std::mem::take(&mut cell)));
+                }
+                Some('}') => self.pos += 1,
+                Some('\\') => match self.read_command().as_str() {
+                    "end" => {
+                        self.read_raw_group();
+                        break;
+                    }
+                    "right" => {
+                        self.read_delimiter();
+                    }
+                    _ => {
+                        // \\ o \cr: nueva fila (ignorando un espaciado opcional [2pt])
+                        self.skip_whitespace();
+                        if self.peek() == Some('[') {
+                            while self.peek().is_some_and(|c| c != ']') {
+                                self.pos += 1;
+                            }
+                            self.pos += 1;
+                        }
+                        rows.last_mut()
+                            .unwrap()
+                            .push(mrow(std::mem::take(&mut cell)));
+                        rows.push(Vec::new());
+                    }
+                },
+                _ => break,
+            }
+        }
+        rows.last_mut().unwrap().push(mrow(cell));
+        // Un \\ final no añade una fila vacía
+        if rows.len() > 1
+            && rows
+                .last()
+                .is_some_and(|row| row.iter().all(|c| c == "<mrow></mrow>"))
+        {
+            rows.pop();
+        }
+
+        let columnalign = match env {
+            "cases" | "dcases" => r#" columnalign="left left""#,
+            "aligned" | "align" | "align*" | "split" | "alignat" | "alignat*" => {
+                r#" columnalign="right left right left right left""#
+            }
+            _ => "",
+        };
+        let table = format!(
+            "<mtable{}>{}</mtable>",
+            columnalign,
+            rows.iter()
+                .map(|row| {
+                    let cells: String = row.iter().map(|c| format!("<mtd>{}</mtd>", c)).collect();
+                    format!("<mtr>{}</mtr>", cells)
+                })
+                .collect::<String>()
+        );
+
+        let (open, close) = match env {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" => ("{", "}"),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            "cases" | "dcases" => ("{", ""),
+            _ => ("", ""),
+        };
+        if open.is_empty() {
+            return table;
+        }
+        let fence = |d: &str| match d {
+            "" => String::new(),
+            d => format!(r#"<mo fence="true" stretchy="true">{}</mo>"#, d),
+        };
+        format!("<mrow>{}{}{}</mrow>", fence(open), table, fence(close))
+    }
+}
+
+/// Agrupar varios nodos en un `<mrow>` (uno solo se deja tal cual)
+fn mrow(mut nodes: Vec<String>) -> String {
+    if nodes.len() == 1 {
+        nodes.pop().unwrap()
+    } else {
+        format!("<mrow>{}</mrow>", nodes.concat())
+    }
+}
+
+fn space(width: &str) -> String {
+    format!(r#"<mspace width="{}"></mspace>"#, width)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn greek(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        _ => return None,
+    })
+}
+
+/// Símbolos que se comportan como identificadores (∞, ∂, ℏ...)
+fn identifier_symbol(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "emptyset" | "varnothing" => "∅",
+        "imath" => "ı",
+        "jmath" => "ȷ",
+        "wp" => "℘",
+        _ => return None,
+    })
+}
+
+fn operator(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "cdot" => "⋅",
+        "times" => "×",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "•",
+        "oplus" => "⊕",
+        "ominus" => "⊖",
+        "otimes" => "⊗",
+        "odot" => "⊙",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "doteq" => "≐",
+        "prec" => "≺",
+        "succ" => "≻",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "lnot" | "neg" => "¬",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "to" | "rightarrow" => "→",
+        "gets" | "leftarrow" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" => "⇔",
+        "implies" | "Longrightarrow" => "⟹",
+        "impliedby" | "Longleftarrow" => "⟸",
+        "iff" | "Longleftrightarrow" => "⟺",
+        "longrightarrow" => "⟶",
+        "longleftarrow" => "⟵",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "angle" => "∠",
+        "triangle" => "△",
+        "degree" => "°",
+        "prime" => "′",
+        "ldots" | "dots" | "dotsc" => "…",
+        "cdots" | "dotsb" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "vert" | "lvert" | "rvert" => "|",
+        "Vert" | "lVert" | "rVert" | "|" => "‖",
+        "colon" => ":",
+        "vdash" => "⊢",
+        "models" => "⊨",
+        "therefore" => "∴",
+        "because" => "∵",
+        "{" | "lbrace" => "{",
+        "}" | "rbrace" => "}",
+        "%" => "%",
+        "$" => "$",
+        "&" => "&",
+        "#" => "#",
+        "_" => "_",
+        _ => return None,
+    })
+}
+
+fn big_operator(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        "bigoplus" => "⨁",
+        "bigotimes" => "⨂",
+        "bigvee" => "⋁",
+        "bigwedge" => "⋀",
+        _ => return None,
+    })
+}
+
+/// Funciones con nombre en redonda; el valor indica si llevan límites (lim, max...)
+fn function_name(command: &str) -> Option<bool> {
+    match command {
+        "lim" | "limsup" | "liminf" | "max" | "min" | "sup" | "inf" | "det" | "gcd" | "Pr"
+        | "argmax" | "argmin" => Some(true),
+        "sin" | "cos" | "tan" | "cot" | "sec" | "csc" | "arcsin" | "arccos" | "arctan" | "sinh"
+        | "cosh" | "tanh" | "coth" | "log" | "ln" | "lg" | "exp" | "deg" | "dim" | "ker"
+        | "arg" | "hom" | "mod" | "bmod" => Some(false),
+        _ => None,
+    }
+}
+
+fn spacing(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "," | "thinspace" => "0.1667em",
+        ":" | ">" | "medspace" => "0.2222em",
+        ";" | "thickspace" => "0.2778em",
+        "!" | "negthinspace" => "-0.1667em",
+        " " => "0.25em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    })
+}
+
+fn font_variant(command: &str) -> Option<&'static str> {
+    Some(match command {
+        "mathbf" => "bold",
+        "boldsymbol" | "bm" => "bold-italic",
+        "mathit" => "italic",
+        "mathrm" => "normal",
+        "mathbb" => "double-struck",
+        "mathcal" => "script",
+        "mathscr" => "script",
+        "mathfrak" => "fraktur",
+        "mathsf" => "sans-serif",
+        "mathtt" => "monospace",
+        _ => return None,
+    })
+}
+
+/// Acentos: (símbolo, encima)
+fn accent(command: &str) -> Option<(&'static str, bool)> {
+    Some(match command {
+        "hat" | "widehat" => ("^", true),
+        "tilde" | "widetilde" => ("~", true),
+        "bar" | "overline" => ("‾", true),
+        "vec" | "overrightarrow" => ("→", true),
+        "overleftarrow" => ("←", true),
+        "dot" => ("˙", true),
+        "ddot" => ("¨", true),
+        "check" => ("ˇ", true),
+        "breve" => ("˘", true),
+        "acute" => ("´", true),
+        "grave" => ("`", true),
+        "underline" => ("_", false),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_math() {
+        let text = "Energía $E = mc^2$ y coste $5 o $10.\n\n$$\n\\int_0^1 x\\,dx\n$$\n\n`$no$` y \\$literal\\$\n\n```\n$x$\n```\n";
+        let spans = find_math(text);
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].tex, "E = mc^2");
+        assert!(!spans[0].display);
+        assert_eq!(&text[spans[0].range.clone()], "$E = mc^2$");
+        assert_eq!(spans[1].tex, "\\int_0^1 x\\,dx");
+        assert!(spans[1].display);
+
+        // Sin cierre válido no hay fórmula
+        assert!(find_math("precio $ 3 y $4").is_empty());
+        assert!(find_math("$a\n\nb$").is_empty());
+
+        // Un escape delante de un carácter multibyte no parte el carácter
+        let spans = find_math("\\é $x$");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].tex, "x");
+    }
+
+    #[test]
+    fn test_tex_to_mathml() {
+        let ml = tex_to_mathml(r"\frac{a}{b} + \sqrt[3]{x^2}", false);
+        assert!(
+            ml.starts_with(r#"<math xmlns="http://www.w3.org/1998/Math/MathML" display="inline">"#)
+        );
+        assert!(ml.contains("<mfrac><mi>a</mi><mi>b</mi></mfrac>"));
+        assert!(ml.contains("<mroot><msup><mi>x</mi><mn>2</mn></msup><mn>3</mn></mroot>"));
+        assert!(ml.contains(
+            r#"<annotation encoding="application/x-tex">\frac{a}{b} + \sqrt[3]{x^2}</annotation>"#
+        ));
+
+        // Límites encima/debajo solo en bloque
+        let display = tex_to_mathml(r"\sum_{i=1}^{n} i", true);
+        assert!(display.contains("<munderover><mo>∑</mo>"));
+        let inline = tex_to_mathml(r"\sum_{i=1}^{n} i", false);
+        assert!(inline.contains("<msubsup><mo>∑</mo>"));
+
+        // Letras griegas, fuentes, texto y escapes
+        let ml = tex_to_mathml(r"\alpha < \Omega \in \mathbb{R} \text{ si } x'", false);
+        assert!(ml.contains("<mi>α</mi><mo>&lt;</mo>"));
+        assert!(ml.contains(r#"<mi mathvariant="normal">Ω</mi>"#));
+        assert!(ml.contains(r#"<mi mathvariant="double-struck">R</mi>"#));
+        assert!(ml.contains("<mtext> si </mtext>"));
+        assert!(ml.contains("<msup><mi>x</mi><mo>′</mo></msup>"));
+
+        // Entornos y delimitadores
+        let ml = tex_to_mathml(
+            r"\left( \begin{pmatrix} 1 & 2 \\ 3 & 4 \end{pmatrix} \right]",
+            true,
+        );
+        assert!(ml.contains(r#"<mo fence="true" stretchy="true">(</mo>"#));
+        assert!(ml.contains("<mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>2</mn></mtd></mtr><mtr><mtd><mn>3</mn></mtd><mtd><mn>4</mn></mtd></mtr></mtable>"));
+        assert!(ml.contains(r#"<mo fence="true" stretchy="true">]</mo>"#));
+
+        // Comandos desconocidos y llaves sin cerrar no rompen la fórmula
+        let ml = tex_to_mathml(r"\foo{x} + {y", false);
+        assert!(ml.contains(r"<merror><mtext>\foo</mtext></merror>"));
+        assert!(ml.contains("<mi>y</mi>"));
+    }
+}
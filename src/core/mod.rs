@@ -1,39 +1,102 @@
+pub mod archive;
 pub mod base;
 pub mod base_query;
 pub mod base_writer;
+pub mod book;
+pub mod chart;
 pub mod command;
 pub mod database;
+pub mod diagram;
 pub mod editor_mode;
 pub mod embedding_config;
+pub mod ex_command;
+pub mod export;
 pub mod formula;
 pub mod frontmatter;
 pub mod html_renderer;
+pub mod import;
+pub mod import_evernote;
+pub mod import_keep;
+pub mod import_notion;
+pub mod import_obsidian;
 pub mod inline_property;
+pub mod inline_query;
 pub mod markdown;
+pub mod math;
 pub mod note_buffer;
 pub mod note_file;
 pub mod notes_config;
+pub mod outline;
+pub mod periodic;
 pub mod property;
+pub mod publish;
+pub mod registers;
+pub mod spellcheck;
+pub mod table;
+pub mod template;
 pub mod text_chunker;
+pub mod vault_git;
+pub mod wikilink;
 pub mod xlsx_export;
 
 pub use base::{
-    Base, BaseView, CellFormat, ColumnConfig, Filter, FilterGroup, FilterOperator, SortConfig,
-    SortDirection, SourceType, SpecialCellContent, SpecialRow, ViewType,
+    Base, BaseView, CellFormat, ChartAggregation, ChartConfig, ChartKind, ColumnConfig, Filter,
+    FilterGroup, FilterOperator, SortConfig, SortDirection, SourceType, SpecialCellContent,
+    SpecialRow, ViewType,
 };
 pub use base_query::{BaseQueryEngine, NoteWithProperties, PropertyAggregation};
-pub use base_writer::BaseWriter;
-pub use command::{CommandParser, EditorAction, KeyModifiers};
-pub use database::{GroupedRecord, InlinePropertyRow, NoteMetadata, NotesDatabase, SearchResult};
+pub use base_writer::{BaseWriter, BulkOperation, BulkTransaction};
+pub use book::{BookCompiler, BookError, BookFormat, BookOptions, MarkupMode, order_notes};
+pub use chart::{ChartRenderer, render_chart_svg};
+pub use command::{
+    CommandParser, EditorAction, FoldCommand, KeyModifiers, Motion, Operator, OperatorTarget,
+    SectionEdit, TextObject, TextObjectKind, key_to_char,
+};
+pub use database::{
+    GroupedRecord, InlinePropertyRow, NoteMetadata, NotesDatabase, SavedSearch, SearchQuery,
+    SearchResult,
+};
+pub use diagram::{DiagramError, DiagramKind, render_diagram, render_diagram_cached};
 pub use editor_mode::EditorMode;
 pub use embedding_config::{EmbeddingConfig, IndexStats};
+pub use ex_command::{
+    CommandHistory, CompletionKind, ExCommand, ExCommandKind, ExContext, ExError, PeriodicTarget,
+};
+pub use export::{
+    ExportError, ExportFormat, ExportNote, Margins, NoteExporter, PageSetup, PageSize, PdfEngine,
+};
 pub use formula::{CellGrid, CellRange, CellRef, CellValue, FormulaError};
 pub use frontmatter::{extract_all_tags, extract_inline_tags, extract_tags};
-pub use html_renderer::{HtmlRenderer, PreviewColors, PreviewTheme};
+pub use html_renderer::{HtmlRenderer, PreviewColors, PreviewOutline, PreviewTheme};
+pub use import::{ImportError, ImportIssue, ImportReport, ImportSource, ImportedBase};
+pub use import_evernote::EvernoteImporter;
+pub use import_keep::KeepImporter;
+pub use import_notion::NotionImporter;
+pub use import_obsidian::ObsidianImporter;
 pub use inline_property::{InlineProperty, InlinePropertyParser};
-pub use markdown::{MarkdownParser, StyleType};
-pub use note_buffer::NoteBuffer;
+pub use inline_query::{InlineQuery, QueryError, QueryKind};
+pub use markdown::{Heading, MarkdownParser, StyleType};
+pub use math::{MathSpan, find_math, tex_to_mathml};
+pub use note_buffer::{NoteBuffer, OperatorOutcome, UndoHistory};
 pub use note_file::{NoteFile, NotesDirectory};
 pub use notes_config::NotesConfig;
+pub use outline::{Folds, Outline, SectionEditResult, edit_section};
+pub use periodic::{
+    EnsuredNote, Period, PeriodConfig, PeriodicError, PeriodicNote, PeriodicNotesConfig,
+};
 pub use property::{Property, PropertyValue};
+pub use publish::{PublishError, PublishReport, SitePublisher};
+pub use registers::{Register, Registers};
+pub use spellcheck::{Dictionary, Misspelling, SpellChecker, SpellError};
+pub use table::{
+    ColumnAlignment, MarkdownTable, TableEdit, TableEditResult, TableError, edit_table,
+    move_to_cell,
+};
+pub use template::{
+    PromptField, RenderedTemplate, Template, TemplateContext, TemplateError, TemplateLibrary,
+};
 pub use text_chunker::{ChunkConfig, TextChunk, TextChunker};
+pub use vault_git::{
+    CommitInfo, Conflict, GitCommand, GitConfig, GitError, PullOutcome, Resolution, VaultGit,
+};
+pub use wikilink::{LinkTarget, WikiLink};
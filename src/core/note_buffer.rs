@@ -1,21 +1,51 @@
+use regex::Regex;
 use ropey::Rope;
+use serde::{Deserialize, Serialize};
 use std::ops::Range;
+use std::sync::LazyLock;
+
+use super::command::{Motion, Operator, OperatorTarget, TextObject, TextObjectKind};
+use super::outline::Outline;
+
+// Enlaces markdown [texto](url) y wikilinks [[Nota]]
+static LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[([^\]]+)\]\]|!?\[([^\]]*)\]\([^)]*\)").unwrap());
+
+// Propiedades inline [clave::valor, otra::valor]
+static PROPERTY_GROUP_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\[\]]*::[^\[\]]*)\]").unwrap());
+
+/// Indentación que añade el operador `>`
+const INDENT: &str = "\t";
+
+/// Grupos de undo que se guardan como máximo por nota
+const MAX_PERSISTED_GROUPS: usize = 200;
+/// Tamaño máximo (bytes de texto) del historial guardado por nota
+const MAX_PERSISTED_BYTES: usize = 256 * 1024;
 
 /// Representa el estado de un buffer de texto usando Rope para edición eficiente.
 #[derive(Debug, Clone)]
 pub struct NoteBuffer {
     /// El contenido de texto subyacente, almacenado como Rope para operaciones O(log n)
     rope: Rope,
-    /// Historial de operaciones para undo
-    undo_stack: Vec<BufferEdit>,
-    /// Historial de operaciones para redo
-    redo_stack: Vec<BufferEdit>,
-    /// Límite de operaciones en el historial
+    /// Historial de grupos de ediciones para undo
+    undo_stack: Vec<UndoGroup>,
+    /// Historial de grupos de ediciones para redo
+    redo_stack: Vec<UndoGroup>,
+    /// Límite de grupos en el historial
     max_history: usize,
+    /// Grupo que sigue aceptando ediciones (racha de escritura o grupo explícito)
+    open_group: Option<OpenGroup>,
+    /// Revisión que recibirá el próximo grupo
+    next_revision: u64,
+    /// Revisión del texto cuando el stack de undo está vacío
+    base_revision: u64,
+    /// Revisión que coincide con el contenido guardado en disco
+    saved_revision: u64,
 }
 
 /// Representa una edición atómica en el buffer
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct BufferEdit {
     /// Tipo de operación realizada
     kind: EditKind,
@@ -25,21 +55,122 @@ struct BufferEdit {
     text: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum EditKind {
     Insert,
     Delete,
 }
 
+impl BufferEdit {
+    /// Si la edición es una sola pulsación (un carácter escrito o borrado)
+    fn is_keystroke(&self) -> bool {
+        self.text.chars().count() == 1
+    }
+
+    /// Si `next` continúa la escritura de esta edición (misma racha de tecleo)
+    fn continues_with(&self, next: &BufferEdit) -> bool {
+        match (self.kind, next.kind) {
+            (EditKind::Insert, EditKind::Insert) => next.range.start == self.range.end,
+            // Backspace justo después de escribir
+            (EditKind::Insert, EditKind::Delete) => next.range.end == self.range.end,
+            // Backspace o Supr seguidos
+            (EditKind::Delete, EditKind::Delete) => {
+                next.range.end == self.range.start || next.range.start == self.range.start
+            }
+            (EditKind::Delete, EditKind::Insert) => next.range.start == self.range.start,
+        }
+    }
+}
+
+/// Paso de undo: todas las ediciones de un comando, una racha de escritura o un pegado
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct UndoGroup {
+    /// Identificador del estado del texto tras aplicar el grupo
+    revision: u64,
+    edits: Vec<BufferEdit>,
+}
+
+impl UndoGroup {
+    fn text_len(&self) -> usize {
+        self.edits.iter().map(|edit| edit.text.len()).sum()
+    }
+}
+
+/// Grupo de undo abierto
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OpenGroup {
+    /// Pulsaciones consecutivas: se agrupan mientras sean contiguas
+    Typing,
+    /// Grupo abierto con `begin_undo_group` (anidable)
+    Explicit { depth: usize, started: bool },
+}
+
+/// Historial de undo/redo de una nota, para guardarlo entre sesiones
+///
+/// Solo es válido para el texto del que se tomó: `NoteBuffer::restore_history`
+/// lo descarta si la nota cambió fuera del editor.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoHistory {
+    undo: Vec<UndoGroup>,
+    redo: Vec<UndoGroup>,
+    next_revision: u64,
+    base_revision: u64,
+    /// Huella del texto al que corresponde el historial
+    fingerprint: u64,
+}
+
+impl UndoHistory {
+    /// Si no hay nada que deshacer ni rehacer
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty() && self.redo.is_empty()
+    }
+}
+
+/// Huella FNV-1a del texto (estable entre versiones, a diferencia de `DefaultHasher`)
+fn fingerprint(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Resultado de aplicar un operador vim al buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperatorOutcome {
+    /// Nueva posición del cursor
+    pub cursor: usize,
+    /// Texto afectado (borrado, cambiado o copiado)
+    pub text: String,
+    /// Si el operador actuó sobre líneas completas
+    pub linewise: bool,
+}
+
+/// Clase de carácter para los movimientos por palabras
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punct,
+}
+
+fn char_class(c: char, big_word: bool) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Blank
+    } else if big_word || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+/// Convertir un offset en bytes de `text` a offset en caracteres
+fn byte_to_char(text: &str, byte: usize) -> usize {
+    text[..byte].chars().count()
+}
+
 impl NoteBuffer {
     /// Crea un nuevo buffer vacío
     pub fn new() -> Self {
-        Self {
-            rope: Rope::new(),
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
-            max_history: 1000,
-        }
+        Self::from_text("")
     }
 
     /// Crea un buffer desde un texto existente
@@ -49,6 +180,10 @@ impl NoteBuffer {
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             max_history: 1000,
+            open_group: None,
+            next_revision: 1,
+            base_revision: 0,
+            saved_revision: 0,
         }
     }
 
@@ -115,10 +250,54 @@ impl NoteBuffer {
         self.redo_stack.clear();
     }
 
-    /// Reemplaza un rango de texto con nuevo contenido
+    /// Reemplaza un rango de texto con nuevo contenido (un solo paso de undo)
     pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        self.begin_undo_group();
         self.delete(range.clone());
         self.insert(range.start, text);
+        self.end_undo_group();
+    }
+
+    /// Sustituye todo el contenido como un solo paso de undo
+    ///
+    /// Solo se registra el tramo que cambia, así el historial no guarda la nota entera
+    /// cuando una herramienta externa (IA, MCP) modifica unas pocas líneas.
+    pub fn set_text(&mut self, text: &str) {
+        let current = self.to_string();
+        if current == text {
+            return;
+        }
+
+        let prefix = current
+            .chars()
+            .zip(text.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let current_len = current.chars().count();
+        let text_len = text.chars().count();
+        let suffix = current
+            .chars()
+            .rev()
+            .zip(text.chars().rev())
+            .take_while(|(a, b)| a == b)
+            .count()
+            .min(current_len - prefix)
+            .min(text_len - prefix);
+
+        let replacement: String = text
+            .chars()
+            .skip(prefix)
+            .take(text_len - prefix - suffix)
+            .collect();
+
+        self.begin_undo_group();
+        if current_len - suffix > prefix {
+            self.delete(prefix..current_len - suffix);
+        }
+        if !replacement.is_empty() {
+            self.insert(prefix, &replacement);
+        }
+        self.end_undo_group();
     }
 
     /// Obtiene una línea específica como String
@@ -137,40 +316,46 @@ impl NoteBuffer {
         Some(self.rope.slice(range).to_string())
     }
 
-    /// Deshace la última operación
+    /// Deshace el último grupo de ediciones
     pub fn undo(&mut self) -> bool {
-        if let Some(edit) = self.undo_stack.pop() {
-            match edit.kind {
-                EditKind::Insert => {
-                    // Revertir inserción eliminando el texto insertado
-                    self.rope.remove(edit.range.clone());
-                }
-                EditKind::Delete => {
-                    // Revertir eliminación insertando el texto eliminado
-                    self.rope.insert(edit.range.start, &edit.text);
+        self.close_open_group();
+        if let Some(group) = self.undo_stack.pop() {
+            for edit in group.edits.iter().rev() {
+                match edit.kind {
+                    EditKind::Insert => {
+                        // Revertir inserción eliminando el texto insertado
+                        self.rope.remove(edit.range.clone());
+                    }
+                    EditKind::Delete => {
+                        // Revertir eliminación insertando el texto eliminado
+                        self.rope.insert(edit.range.start, &edit.text);
+                    }
                 }
             }
-            self.redo_stack.push(edit);
+            self.redo_stack.push(group);
             true
         } else {
             false
         }
     }
 
-    /// Rehace la última operación deshecha
+    /// Rehace el último grupo deshecho
     pub fn redo(&mut self) -> bool {
-        if let Some(edit) = self.redo_stack.pop() {
-            match edit.kind {
-                EditKind::Insert => {
-                    // Rehacer inserción
-                    self.rope.insert(edit.range.start, &edit.text);
-                }
-                EditKind::Delete => {
-                    // Rehacer eliminación
-                    self.rope.remove(edit.range.clone());
+        self.close_open_group();
+        if let Some(group) = self.redo_stack.pop() {
+            for edit in &group.edits {
+                match edit.kind {
+                    EditKind::Insert => {
+                        // Rehacer inserción
+                        self.rope.insert(edit.range.start, &edit.text);
+                    }
+                    EditKind::Delete => {
+                        // Rehacer eliminación
+                        self.rope.remove(edit.range.clone());
+                    }
                 }
             }
-            self.undo_stack.push(edit);
+            self.undo_stack.push(group);
             true
         } else {
             false
@@ -195,12 +380,166 @@ impl NoteBuffer {
         !self.redo_stack.is_empty()
     }
 
-    /// Agrega una edición al stack de undo, respetando el límite
+    /// Abrir un grupo de undo: las ediciones hasta `end_undo_group` se deshacen juntas
+    ///
+    /// Los grupos se pueden anidar (una macro que ejecuta varios comandos es un solo paso).
+    pub fn begin_undo_group(&mut self) {
+        self.open_group = Some(match self.open_group {
+            Some(OpenGroup::Explicit { depth, started }) => OpenGroup::Explicit {
+                depth: depth + 1,
+                started,
+            },
+            _ => OpenGroup::Explicit {
+                depth: 1,
+                started: false,
+            },
+        });
+    }
+
+    /// Cerrar el grupo abierto con `begin_undo_group`
+    pub fn end_undo_group(&mut self) {
+        self.open_group = match self.open_group {
+            Some(OpenGroup::Explicit { depth, started }) if depth > 1 => {
+                Some(OpenGroup::Explicit {
+                    depth: depth - 1,
+                    started,
+                })
+            }
+            Some(OpenGroup::Explicit { .. }) => None,
+            other => other,
+        };
+    }
+
+    /// Terminar la racha de escritura actual (p. ej. al salir de modo Insert)
+    pub fn seal_undo_group(&mut self) {
+        if self.open_group == Some(OpenGroup::Typing) {
+            self.open_group = None;
+        }
+    }
+
+    /// Marcar el estado actual como el guardado en disco
+    pub fn mark_saved(&mut self) {
+        self.seal_undo_group();
+        self.saved_revision = self.current_revision();
+    }
+
+    /// Sustituir el contenido por el que ya está en disco (p. ej. una versión
+    /// restaurada). El cambio se puede deshacer y queda como guardado
+    pub fn load_saved(&mut self, text: &str) {
+        self.set_text(text);
+        self.mark_saved();
+    }
+
+    /// Si el contenido coincide con la última versión guardada
+    pub fn is_at_saved_state(&self) -> bool {
+        self.current_revision() == self.saved_revision
+    }
+
+    /// Copia del historial para guardarlo, limitada en grupos y en tamaño
+    ///
+    /// Se descartan los grupos de undo más antiguos y los de redo más lejanos.
+    pub fn history(&self) -> UndoHistory {
+        let mut budget = MAX_PERSISTED_BYTES;
+        let mut keep = |groups: &[UndoGroup]| {
+            groups
+                .iter()
+                .rev()
+                .take(MAX_PERSISTED_GROUPS)
+                .take_while(|group| {
+                    let len = group.text_len();
+                    let fits = len <= budget;
+                    budget = budget.saturating_sub(len);
+                    fits
+                })
+                .count()
+        };
+        let undo_kept = keep(&self.undo_stack);
+        let redo_kept = keep(&self.redo_stack);
+
+        let undo_dropped = self.undo_stack.len() - undo_kept;
+        let base_revision = match undo_dropped {
+            0 => self.base_revision,
+            n => self.undo_stack[n - 1].revision,
+        };
+
+        UndoHistory {
+            undo: self.undo_stack[undo_dropped..].to_vec(),
+            redo: self.redo_stack[self.redo_stack.len() - redo_kept..].to_vec(),
+            next_revision: self.next_revision,
+            base_revision,
+            fingerprint: fingerprint(&self.to_string()),
+        }
+    }
+
+    /// Recuperar un historial guardado con `history`
+    ///
+    /// Devuelve `false` (y no cambia nada) si el texto actual no es el mismo del que
+    /// se tomó el historial. El estado actual pasa a considerarse el guardado.
+    pub fn restore_history(&mut self, history: UndoHistory) -> bool {
+        if history.fingerprint != fingerprint(&self.to_string()) {
+            return false;
+        }
+        self.undo_stack = history.undo;
+        self.redo_stack = history.redo;
+        self.next_revision = history.next_revision.max(1);
+        self.base_revision = history.base_revision;
+        self.open_group = None;
+        self.saved_revision = self.current_revision();
+        true
+    }
+
+    /// Revisión del texto actual (la del último grupo aplicado)
+    fn current_revision(&self) -> u64 {
+        self.undo_stack
+            .last()
+            .map_or(self.base_revision, |group| group.revision)
+    }
+
+    /// Que la próxima edición empiece un grupo nuevo sin salir de un grupo explícito
+    fn close_open_group(&mut self) {
+        self.open_group = match self.open_group {
+            Some(OpenGroup::Explicit { depth, .. }) => Some(OpenGroup::Explicit {
+                depth,
+                started: false,
+            }),
+            _ => None,
+        };
+    }
+
+    /// Agrega una edición al historial: al grupo abierto o a uno nuevo, respetando el límite
     fn push_undo(&mut self, edit: BufferEdit) {
+        let coalesce = match self.open_group {
+            Some(OpenGroup::Explicit { started, .. }) => started,
+            Some(OpenGroup::Typing) => {
+                edit.is_keystroke()
+                    && self
+                        .undo_stack
+                        .last()
+                        .and_then(|group| group.edits.last())
+                        .is_some_and(|last| last.continues_with(&edit))
+            }
+            None => false,
+        };
+        if coalesce && let Some(group) = self.undo_stack.last_mut() {
+            group.edits.push(edit);
+            return;
+        }
+
+        match &mut self.open_group {
+            Some(OpenGroup::Explicit { started, .. }) => *started = true,
+            open => *open = edit.is_keystroke().then_some(OpenGroup::Typing),
+        }
+
         if self.undo_stack.len() >= self.max_history {
-            self.undo_stack.remove(0);
+            let dropped = self.undo_stack.remove(0);
+            self.base_revision = dropped.revision;
         }
-        self.undo_stack.push(edit);
+        let revision = self.next_revision;
+        self.next_revision += 1;
+        self.undo_stack.push(UndoGroup {
+            revision,
+            edits: vec![edit],
+        });
     }
 
     /// Convierte un índice de línea y columna a índice de carácter
@@ -226,6 +565,667 @@ impl NoteBuffer {
         let col = char_idx - line_start;
         Some((line, col))
     }
+
+    // === Gramática vim: movimientos, objetos de texto y operadores ===
+
+    fn char_at(&self, idx: usize) -> Option<char> {
+        (idx < self.len_chars()).then(|| self.rope.char(idx))
+    }
+
+    fn class_at(&self, idx: usize, big_word: bool) -> CharClass {
+        self.char_at(idx)
+            .map(|c| char_class(c, big_word))
+            .unwrap_or(CharClass::Blank)
+    }
+
+    fn line_of(&self, char_idx: usize) -> usize {
+        self.rope.char_to_line(char_idx.min(self.len_chars()))
+    }
+
+    fn last_line(&self) -> usize {
+        self.len_lines().saturating_sub(1)
+    }
+
+    /// Inicio de la línea, fin del contenido (sin `\n`) y fin incluyendo `\n`
+    fn line_bounds(&self, line: usize) -> (usize, usize, usize) {
+        let line = line.min(self.last_line());
+        let start = self.rope.line_to_char(line);
+        let next = if line + 1 < self.len_lines() {
+            self.rope.line_to_char(line + 1)
+        } else {
+            self.len_chars()
+        };
+        let end = if next > start && self.char_at(next - 1) == Some('\n') {
+            next - 1
+        } else {
+            next
+        };
+        (start, end, next)
+    }
+
+    fn is_blank_line(&self, line: usize) -> bool {
+        let (start, end, _) = self.line_bounds(line);
+        (start..end).all(|i| self.char_at(i).is_some_and(char::is_whitespace))
+    }
+
+    fn first_non_blank(&self, line: usize) -> usize {
+        let (start, end, _) = self.line_bounds(line);
+        (start..end)
+            .find(|&i| !matches!(self.char_at(i), Some(' ' | '\t')))
+            .unwrap_or(end)
+    }
+
+    /// Rango de caracteres de las líneas `first..=last` (incluyendo el último `\n`)
+    fn lines_range(&self, first: usize, last: usize) -> Range<usize> {
+        let (start, _, _) = self.line_bounds(first);
+        let (_, _, end) = self.line_bounds(last.max(first));
+        start..end
+    }
+
+    /// Calcular la posición destino de un movimiento
+    pub fn motion_target(&self, pos: usize, motion: Motion, count: usize) -> usize {
+        let count = count.max(1);
+        let len = self.len_chars();
+        let pos = pos.min(len);
+        let line = self.line_of(pos);
+        let (line_start, line_end, _) = self.line_bounds(line);
+
+        match motion {
+            Motion::Left => pos.saturating_sub(count).max(line_start),
+            Motion::Right => (pos + count).min(line_end),
+            Motion::Up | Motion::Down => {
+                let target_line = if motion == Motion::Up {
+                    line.saturating_sub(count)
+                } else {
+                    (line + count).min(self.last_line())
+                };
+                let (start, end, _) = self.line_bounds(target_line);
+                (start + (pos - line_start)).min(end)
+            }
+            Motion::WordForward | Motion::BigWordForward => {
+                let big = motion == Motion::BigWordForward;
+                (0..count).fold(pos, |p, _| self.next_word_start(p, big))
+            }
+            Motion::WordBackward | Motion::BigWordBackward => {
+                let big = motion == Motion::BigWordBackward;
+                (0..count).fold(pos, |p, _| self.prev_word_start(p, big))
+            }
+            Motion::WordEnd | Motion::BigWordEnd => {
+                let big = motion == Motion::BigWordEnd;
+                (0..count).fold(pos, |p, _| self.word_end(p, big))
+            }
+            Motion::LineStart => line_start,
+            Motion::FirstNonBlank => self.first_non_blank(line),
+            Motion::LineEnd => self.line_bounds(line + count - 1).1,
+            Motion::DocStart => 0,
+            Motion::DocEnd => len,
+            Motion::GotoLine(n) => self.first_non_blank(n.saturating_sub(1).min(self.last_line())),
+            Motion::ParagraphForward => (0..count).fold(pos, |p, _| self.paragraph_forward(p)),
+            Motion::ParagraphBackward => (0..count).fold(pos, |p, _| self.paragraph_backward(p)),
+            Motion::SectionForward => {
+                match Outline::parse(&self.to_string()).next_heading(line, count) {
+                    Some(heading) => self.line_bounds(heading.line).0,
+                    None => len,
+                }
+            }
+            Motion::SectionBackward => {
+                match Outline::parse(&self.to_string()).previous_heading(line, count) {
+                    Some(heading) => self.line_bounds(heading.line).0,
+                    None => 0,
+                }
+            }
+            Motion::FindChar(c) | Motion::TillChar(c) => {
+                let found = (pos + 1..line_end)
+                    .filter(|&i| self.char_at(i) == Some(c))
+                    .nth(count - 1);
+                match (found, motion) {
+                    (Some(i), Motion::TillChar(_)) => (i - 1).max(pos),
+                    (Some(i), _) => i,
+                    (None, _) => pos,
+                }
+            }
+            Motion::FindCharBackward(c) | Motion::TillCharBackward(c) => {
+                let found = (line_start..pos)
+                    .rev()
+                    .filter(|&i| self.char_at(i) == Some(c))
+                    .nth(count - 1);
+                match (found, motion) {
+                    (Some(i), Motion::TillCharBackward(_)) => (i + 1).min(pos),
+                    (Some(i), _) => i,
+                    (None, _) => pos,
+                }
+            }
+            Motion::MatchingPair => self.matching_pair(pos).unwrap_or(pos),
+        }
+    }
+
+    fn next_word_start(&self, pos: usize, big: bool) -> usize {
+        let len = self.len_chars();
+        if pos >= len {
+            return len;
+        }
+        let mut i = pos;
+        let class = self.class_at(i, big);
+        if class != CharClass::Blank {
+            while i < len && self.class_at(i, big) == class {
+                i += 1;
+            }
+        }
+        while i < len && self.class_at(i, big) == CharClass::Blank {
+            // Las líneas vacías cuentan como palabra
+            if self.char_at(i) == Some('\n') && self.char_at(i + 1) == Some('\n') {
+                return i + 1;
+            }
+            i += 1;
+        }
+        i
+    }
+
+    fn prev_word_start(&self, pos: usize, big: bool) -> usize {
+        if pos == 0 {
+            return 0;
+        }
+        let mut i = pos - 1;
+        while i > 0 && self.class_at(i, big) == CharClass::Blank {
+            i -= 1;
+        }
+        let class = self.class_at(i, big);
+        if class == CharClass::Blank {
+            return 0;
+        }
+        while i > 0 && self.class_at(i - 1, big) == class {
+            i -= 1;
+        }
+        i
+    }
+
+    fn word_end(&self, pos: usize, big: bool) -> usize {
+        let len = self.len_chars();
+        let mut i = pos + 1;
+        while i < len && self.class_at(i, big) == CharClass::Blank {
+            i += 1;
+        }
+        if i >= len {
+            return len.saturating_sub(1).max(pos);
+        }
+        let class = self.class_at(i, big);
+        while i + 1 < len && self.class_at(i + 1, big) == class {
+            i += 1;
+        }
+        i
+    }
+
+    fn paragraph_forward(&self, pos: usize) -> usize {
+        let last = self.last_line();
+        let mut line = self.line_of(pos);
+        while line < last && self.is_blank_line(line) {
+            line += 1;
+        }
+        while line < last && !self.is_blank_line(line) {
+            line += 1;
+        }
+        if self.is_blank_line(line) {
+            self.line_bounds(line).0
+        } else {
+            self.len_chars()
+        }
+    }
+
+    fn paragraph_backward(&self, pos: usize) -> usize {
+        let mut line = self.line_of(pos);
+        while line > 0 && self.is_blank_line(line) {
+            line -= 1;
+        }
+        while line > 0 && !self.is_blank_line(line) {
+            line -= 1;
+        }
+        self.line_bounds(line).0
+    }
+
+    /// `%`: primer paréntesis/corchete/llave desde el cursor y su pareja
+    fn matching_pair(&self, pos: usize) -> Option<usize> {
+        const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+        let (_, line_end, _) = self.line_bounds(self.line_of(pos));
+        let (start, c) = (pos..line_end).find_map(|i| {
+            self.char_at(i)
+                .filter(|c| PAIRS.iter().any(|(o, cl)| c == o || c == cl))
+                .map(|c| (i, c))
+        })?;
+
+        if let Some(&(open, close)) = PAIRS.iter().find(|(o, _)| *o == c) {
+            self.find_close(start, open, close)
+        } else {
+            let &(open, close) = PAIRS.iter().find(|(_, cl)| *cl == c)?;
+            self.find_open(start, open, close)
+        }
+    }
+
+    /// Buscar hacia delante el cierre que empareja con la apertura en `open_idx`
+    fn find_close(&self, open_idx: usize, open: char, close: char) -> Option<usize> {
+        let mut depth = 0usize;
+        for i in open_idx + 1..self.len_chars() {
+            match self.char_at(i) {
+                Some(c) if c == close && depth == 0 => return Some(i),
+                Some(c) if c == close => depth -= 1,
+                Some(c) if c == open => depth += 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Buscar hacia atrás la apertura sin emparejar antes de `from`
+    fn find_open(&self, from: usize, open: char, close: char) -> Option<usize> {
+        let mut depth = 0usize;
+        for i in (0..from).rev() {
+            match self.char_at(i) {
+                Some(c) if c == open && depth == 0 => return Some(i),
+                Some(c) if c == open => depth -= 1,
+                Some(c) if c == close => depth += 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Rango que cubre un objeto de texto en la posición del cursor
+    pub fn text_object_range(&self, pos: usize, object: TextObject) -> Option<Range<usize>> {
+        let inner = object.inner;
+        match object.kind {
+            TextObjectKind::Word => self.word_object(pos, false, inner),
+            TextObjectKind::BigWord => self.word_object(pos, true, inner),
+            TextObjectKind::Paragraph => self.paragraph_object(pos, inner),
+            TextObjectKind::Quote(q) => self.quote_object(pos, q, inner),
+            TextObjectKind::Pair(open, close) => {
+                let start = if self.char_at(pos) == Some(open) {
+                    pos
+                } else {
+                    self.find_open(pos, open, close)?
+                };
+                let end = self.find_close(start, open, close)?;
+                Some(if inner {
+                    start + 1..end
+                } else {
+                    start..end + 1
+                })
+            }
+            TextObjectKind::Link => self.link_object(pos, inner),
+            TextObjectKind::InlineProperty => self.property_object(pos, inner),
+            TextObjectKind::CodeBlock => self
+                .code_block_object(pos, inner)
+                .or_else(|| self.quote_object(pos, '`', inner)),
+        }
+    }
+
+    fn word_object(&self, pos: usize, big: bool, inner: bool) -> Option<Range<usize>> {
+        let c = self.char_at(pos).filter(|&c| c != '\n')?;
+        let class = char_class(c, big);
+        let same = |i: usize| {
+            self.char_at(i)
+                .is_some_and(|c| c != '\n' && char_class(c, big) == class)
+        };
+        let is_space = |i: usize| matches!(self.char_at(i), Some(' ' | '\t'));
+
+        let mut start = pos;
+        while start > 0 && same(start - 1) {
+            start -= 1;
+        }
+        let mut end = pos + 1;
+        while same(end) {
+            end += 1;
+        }
+        if inner {
+            return Some(start..end);
+        }
+
+        if class == CharClass::Blank {
+            // `aw` sobre espacios: los espacios más la palabra siguiente
+            let next_class = self.class_at(end, big);
+            while self.char_at(end).is_some_and(|c| c != '\n')
+                && self.class_at(end, big) == next_class
+            {
+                end += 1;
+            }
+        } else if is_space(end) {
+            while is_space(end) {
+                end += 1;
+            }
+        } else {
+            while start > 0 && is_space(start - 1) {
+                start -= 1;
+            }
+        }
+        Some(start..end)
+    }
+
+    fn paragraph_object(&self, pos: usize, inner: bool) -> Option<Range<usize>> {
+        let last = self.last_line();
+        let line = self.line_of(pos);
+        let blank = self.is_blank_line(line);
+        let mut first = line;
+        while first > 0 && self.is_blank_line(first - 1) == blank {
+            first -= 1;
+        }
+        let mut end = line;
+        while end < last && self.is_blank_line(end + 1) == blank {
+            end += 1;
+        }
+        if !inner && !blank {
+            while end < last && self.is_blank_line(end + 1) {
+                end += 1;
+            }
+        }
+        let range = self.lines_range(first, end);
+        (!range.is_empty()).then_some(range)
+    }
+
+    fn quote_object(&self, pos: usize, quote: char, inner: bool) -> Option<Range<usize>> {
+        let (start, end, _) = self.line_bounds(self.line_of(pos));
+        let quotes: Vec<usize> = (start..end)
+            .filter(|&i| self.char_at(i) == Some(quote))
+            .collect();
+        let (open, close) = quotes
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .find(|&(_, close)| close >= pos)?;
+        Some(if inner {
+            open + 1..close
+        } else {
+            open..close + 1
+        })
+    }
+
+    /// Texto de la línea del cursor, su inicio y la columna del cursor en bytes
+    fn line_text_at(&self, pos: usize) -> (String, usize, usize) {
+        let (start, end, _) = self.line_bounds(self.line_of(pos));
+        let text = self.rope.slice(start..end).to_string();
+        let col_byte = text
+            .char_indices()
+            .nth(pos - start)
+            .map(|(b, _)| b)
+            .unwrap_or(text.len());
+        (text, start, col_byte)
+    }
+
+    fn link_object(&self, pos: usize, inner: bool) -> Option<Range<usize>> {
+        let (text, line_start, col) = self.line_text_at(pos);
+        let caps = LINK_RE.captures_iter(&text).find(|caps| {
+            let m = caps.get(0).unwrap();
+            m.start() <= col && col < m.end()
+        })?;
+        let m = if inner {
+            caps.get(1).or_else(|| caps.get(2))?
+        } else {
+            caps.get(0)?
+        };
+        Some(line_start + byte_to_char(&text, m.start())..line_start + byte_to_char(&text, m.end()))
+    }
+
+    fn property_object(&self, pos: usize, inner: bool) -> Option<Range<usize>> {
+        let (text, line_start, col) = self.line_text_at(pos);
+        let caps = PROPERTY_GROUP_RE.captures_iter(&text).find(|caps| {
+            let m = caps.get(0).unwrap();
+            m.start() <= col && col < m.end()
+        })?;
+        let whole = caps.get(0)?;
+        let to_chars = |a: usize, b: usize| {
+            line_start + byte_to_char(&text, a)..line_start + byte_to_char(&text, b)
+        };
+        if !inner {
+            return Some(to_chars(whole.start(), whole.end()));
+        }
+
+        // Valor del par clave::valor bajo el cursor
+        let content = caps.get(1)?;
+        let mut seg_start = content.start();
+        for segment in content.as_str().split(',') {
+            let seg_end = seg_start + segment.len();
+            if col <= seg_end || seg_end == content.end() {
+                let sep = segment.find("::")?;
+                let value_offset = sep + segment[sep..].chars().take_while(|&c| c == ':').count();
+                let value = &segment[value_offset..];
+                let value_start =
+                    seg_start + value_offset + (value.len() - value.trim_start().len());
+                let value_end = seg_start + value_offset + value.trim_end().len();
+                return Some(to_chars(value_start, value_end.max(value_start)));
+            }
+            seg_start = seg_end + 1;
+        }
+        None
+    }
+
+    fn code_block_object(&self, pos: usize, inner: bool) -> Option<Range<usize>> {
+        let cursor_line = self.line_of(pos);
+        let is_fence = |line: usize| {
+            let (start, end, _) = self.line_bounds(line);
+            let text = self.rope.slice(start..end).to_string();
+            let text = text.trim_start();
+            text.starts_with("```") || text.starts_with("~~~")
+        };
+
+        let mut open: Option<usize> = None;
+        for line in 0..=self.last_line() {
+            if !is_fence(line) {
+                continue;
+            }
+            match open {
+                None => open = Some(line),
+                Some(first) => {
+                    if (first..=line).contains(&cursor_line) {
+                        return Some(if inner {
+                            self.line_bounds(first + 1).0..self.line_bounds(line).0
+                        } else {
+                            self.lines_range(first, line)
+                        });
+                    }
+                    open = None;
+                }
+            }
+        }
+        None
+    }
+
+    /// Rango sobre el que actúa un operador y si es por líneas
+    pub fn operator_range(
+        &self,
+        pos: usize,
+        operator: Operator,
+        target: &OperatorTarget,
+        count: usize,
+    ) -> Option<(Range<usize>, bool)> {
+        let count = count.max(1);
+        let len = self.len_chars();
+        let pos = pos.min(len);
+
+        match *target {
+            OperatorTarget::Line => {
+                let line = self.line_of(pos);
+                let range = self.lines_range(line, (line + count - 1).min(self.last_line()));
+                (!range.is_empty()).then_some((range, true))
+            }
+            OperatorTarget::TextObject(object) => self
+                .text_object_range(pos, object)
+                .filter(|r| !r.is_empty())
+                .map(|r| (r, object.kind == TextObjectKind::Paragraph)),
+            OperatorTarget::Motion(motion) if motion.is_linewise() => {
+                let target = self.motion_target(pos, motion, count);
+                let (a, b) = (self.line_of(pos), self.line_of(target));
+                let range = self.lines_range(a.min(b), a.max(b));
+                (!range.is_empty()).then_some((range, true))
+            }
+            OperatorTarget::Motion(motion) => {
+                let word_forward = matches!(motion, Motion::WordForward | Motion::BigWordForward);
+
+                // `cw` sobre una palabra se comporta como `ce` (sin cruzar a la siguiente)
+                if operator == Operator::Change
+                    && word_forward
+                    && self.class_at(pos, false) != CharClass::Blank
+                {
+                    let big = motion == Motion::BigWordForward;
+                    let class = self.class_at(pos, big);
+                    let mut end = pos;
+                    while end + 1 < len && self.class_at(end + 1, big) == class {
+                        end += 1;
+                    }
+                    for _ in 1..count {
+                        end = self.word_end(end, big);
+                    }
+                    return Some((pos..(end + 1).min(len), false));
+                }
+
+                let target = self.motion_target(pos, motion, count);
+                let (start, mut end) = (pos.min(target), pos.max(target));
+                if motion.is_inclusive() && target != pos {
+                    end = (end + 1).min(len);
+                }
+
+                // `dw` sobre la última palabra no se come el salto de línea
+                if word_forward && self.line_of(end) > self.line_of(start) {
+                    let line_start = self.line_bounds(self.line_of(end)).0;
+                    if (line_start..end).all(|i| self.class_at(i, false) == CharClass::Blank) {
+                        end = line_start.saturating_sub(1).max(start);
+                    }
+                }
+
+                (start < end).then_some((start..end, false))
+            }
+        }
+    }
+
+    /// Aplicar un operador vim (d, c, y, >, <) y devolver el texto afectado
+    pub fn apply_operator(
+        &mut self,
+        pos: usize,
+        operator: Operator,
+        target: &OperatorTarget,
+        count: usize,
+    ) -> Option<OperatorOutcome> {
+        let (mut range, linewise) = self.operator_range(pos, operator, target, count)?;
+
+        // `cc` conserva la línea (solo vacía su contenido)
+        if linewise && operator == Operator::Change && self.char_at(range.end - 1) == Some('\n') {
+            range.end -= 1;
+        }
+
+        let mut text = self.slice(range.clone())?;
+        if linewise && operator != Operator::Change && !text.ends_with('\n') {
+            text.push('\n');
+        }
+
+        let cursor = match operator {
+            Operator::Yank => {
+                if linewise {
+                    pos
+                } else {
+                    range.start
+                }
+            }
+            Operator::Delete => {
+                // Al borrar las últimas líneas, eliminar el salto de línea anterior
+                if linewise
+                    && range.end == self.len_chars()
+                    && range.start > 0
+                    && self.char_at(range.end - 1) != Some('\n')
+                {
+                    range.start -= 1;
+                }
+                self.delete(range.clone());
+                if linewise {
+                    self.first_non_blank(self.line_of(range.start))
+                } else {
+                    range.start.min(self.len_chars())
+                }
+            }
+            Operator::Change => {
+                if !range.is_empty() {
+                    self.delete(range.clone());
+                }
+                range.start
+            }
+            Operator::Indent | Operator::Outdent => {
+                let first = self.line_of(range.start);
+                let last = self.line_of(range.end.saturating_sub(1).max(range.start));
+                for line in (first..=last).rev() {
+                    let (start, end, _) = self.line_bounds(line);
+                    if operator == Operator::Indent {
+                        if start < end {
+                            self.insert(start, INDENT);
+                        }
+                    } else {
+                        let removable = if self.char_at(start) == Some('\t') {
+                            1
+                        } else {
+                            (start..end)
+                                .take(4)
+                                .take_while(|&i| self.char_at(i) == Some(' '))
+                                .count()
+                        };
+                        if removable > 0 {
+                            self.delete(start..start + removable);
+                        }
+                    }
+                }
+                self.first_non_blank(first)
+            }
+        };
+
+        Some(OperatorOutcome {
+            cursor,
+            text,
+            linewise,
+        })
+    }
+
+    /// Pegar el contenido de un registro (`p`/`P`) y devolver la nueva posición del cursor
+    ///
+    /// Los registros de líneas se pegan debajo (o encima) de la línea actual; el
+    /// resto, después (o antes) del carácter bajo el cursor.
+    pub fn put(
+        &mut self,
+        pos: usize,
+        text: &str,
+        linewise: bool,
+        before: bool,
+        count: usize,
+    ) -> usize {
+        if text.is_empty() {
+            return pos.min(self.len_chars());
+        }
+        let count = count.max(1);
+        let pos = pos.min(self.len_chars());
+
+        if linewise {
+            let mut block = text.to_string();
+            if !block.ends_with('\n') {
+                block.push('\n');
+            }
+            let block = block.repeat(count);
+            let line = self.line_of(pos);
+            let (start, end, next) = self.line_bounds(line);
+
+            let at = if before {
+                start
+            } else if next > end {
+                next
+            } else {
+                // Última línea sin `\n`: añadirlo antes del bloque y quitar el del final
+                self.insert(end, &format!("\n{}", block.trim_end_matches('\n')));
+                return self.first_non_blank(line + 1);
+            };
+            self.insert(at, &block);
+            self.first_non_blank(self.line_of(at))
+        } else {
+            let at = if before || matches!(self.char_at(pos), None | Some('\n')) {
+                pos
+            } else {
+                pos + 1
+            };
+            let inserted = text.repeat(count);
+            let len = inserted.chars().count();
+            self.insert(at, &inserted);
+            at + len - 1
+        }
+    }
 }
 
 impl Default for NoteBuffer {
@@ -297,4 +1297,365 @@ mod tests {
         assert_eq!(buffer.char_to_line_col(4), Some((1, 0)));
         assert_eq!(buffer.char_to_line_col(11), Some((2, 1)));
     }
+
+    fn operate(
+        text: &str,
+        pos: usize,
+        operator: Operator,
+        target: OperatorTarget,
+        count: usize,
+    ) -> (String, OperatorOutcome) {
+        let mut buffer = NoteBuffer::from_text(text);
+        let outcome = buffer
+            .apply_operator(pos, operator, &target, count)
+            .unwrap();
+        (buffer.to_string(), outcome)
+    }
+
+    fn object(kind: TextObjectKind, inner: bool) -> OperatorTarget {
+        OperatorTarget::TextObject(TextObject { kind, inner })
+    }
+
+    #[test]
+    fn test_word_motions() {
+        let buffer = NoteBuffer::from_text("foo bar.baz qux\nnext");
+
+        assert_eq!(buffer.motion_target(0, Motion::WordForward, 1), 4);
+        assert_eq!(buffer.motion_target(4, Motion::WordForward, 1), 7);
+        assert_eq!(buffer.motion_target(4, Motion::BigWordForward, 1), 12);
+        assert_eq!(buffer.motion_target(12, Motion::WordForward, 1), 16);
+        assert_eq!(buffer.motion_target(12, Motion::WordBackward, 2), 7);
+        assert_eq!(buffer.motion_target(0, Motion::WordEnd, 1), 2);
+        assert_eq!(buffer.motion_target(0, Motion::FindChar('a'), 2), 9);
+        assert_eq!(buffer.motion_target(0, Motion::TillChar('.'), 1), 6);
+        assert_eq!(buffer.motion_target(0, Motion::GotoLine(2), 1), 16);
+    }
+
+    #[test]
+    fn test_section_motions() {
+        let buffer = NoteBuffer::from_text("# A\ntexto\n## B\nmás\n# C");
+
+        assert_eq!(buffer.motion_target(4, Motion::SectionForward, 1), 10);
+        assert_eq!(buffer.motion_target(4, Motion::SectionForward, 5), 19);
+        assert_eq!(buffer.motion_target(19, Motion::SectionBackward, 1), 10);
+        assert_eq!(buffer.motion_target(19, Motion::SectionBackward, 2), 0);
+        assert_eq!(buffer.motion_target(2, Motion::SectionBackward, 1), 0);
+    }
+
+    #[test]
+    fn test_matching_pair_and_paragraphs() {
+        let buffer = NoteBuffer::from_text("f(a, (b)) x\n\nsecond\npara\n\nend");
+
+        assert_eq!(buffer.motion_target(0, Motion::MatchingPair, 1), 8);
+        assert_eq!(buffer.motion_target(8, Motion::MatchingPair, 1), 1);
+        assert_eq!(buffer.motion_target(0, Motion::ParagraphForward, 1), 12);
+        assert_eq!(buffer.motion_target(14, Motion::ParagraphForward, 1), 25);
+        assert_eq!(buffer.motion_target(25, Motion::ParagraphBackward, 1), 12);
+    }
+
+    #[test]
+    fn test_delete_operators() {
+        let (text, outcome) = operate(
+            "one two three four",
+            0,
+            Operator::Delete,
+            OperatorTarget::Motion(Motion::WordForward),
+            3,
+        );
+        assert_eq!(text, "four");
+        assert_eq!(outcome.text, "one two three ");
+
+        // `dw` en la última palabra no borra el salto de línea
+        let (text, _) = operate(
+            "a last\nb",
+            2,
+            Operator::Delete,
+            OperatorTarget::Motion(Motion::WordForward),
+            1,
+        );
+        assert_eq!(text, "a \nb");
+
+        let (text, outcome) = operate(
+            "keep\ndrop me\nkeep",
+            7,
+            Operator::Delete,
+            OperatorTarget::Line,
+            1,
+        );
+        assert_eq!(text, "keep\nkeep");
+        assert!(outcome.linewise);
+        assert_eq!(outcome.text, "drop me\n");
+
+        // Borrar la última línea elimina también el salto anterior
+        let (text, _) = operate("a\nb\nc", 4, Operator::Delete, OperatorTarget::Line, 1);
+        assert_eq!(text, "a\nb");
+
+        let (text, _) = operate(
+            "abc def",
+            1,
+            Operator::Delete,
+            OperatorTarget::Motion(Motion::LineEnd),
+            1,
+        );
+        assert_eq!(text, "a");
+    }
+
+    #[test]
+    fn test_change_and_yank() {
+        // `cw` se comporta como `ce`
+        let (text, outcome) = operate(
+            "hello world",
+            0,
+            Operator::Change,
+            OperatorTarget::Motion(Motion::WordForward),
+            1,
+        );
+        assert_eq!(text, " world");
+        assert_eq!(outcome.cursor, 0);
+
+        // `cc` conserva la línea
+        let (text, outcome) = operate("a\n  b c\nd", 3, Operator::Change, OperatorTarget::Line, 1);
+        assert_eq!(text, "a\n\nd");
+        assert_eq!(outcome.cursor, 2);
+
+        let (text, outcome) = operate(
+            "para one\nline two\n\nother",
+            3,
+            Operator::Yank,
+            object(TextObjectKind::Paragraph, false),
+            1,
+        );
+        assert_eq!(text, "para one\nline two\n\nother");
+        assert_eq!(outcome.text, "para one\nline two\n\n");
+    }
+
+    #[test]
+    fn test_indent_operators() {
+        let (text, _) = operate("a\nb\n\nc", 0, Operator::Indent, OperatorTarget::Line, 3);
+        assert_eq!(text, "\ta\n\tb\n\nc");
+
+        let (text, _) = operate(
+            "    a\n\tb",
+            0,
+            Operator::Outdent,
+            OperatorTarget::Motion(Motion::Down),
+            1,
+        );
+        assert_eq!(text, "a\nb");
+    }
+
+    #[test]
+    fn test_text_objects() {
+        let buffer = NoteBuffer::from_text("say \"hi there\" (a (b) c) end");
+        let range = |pos, kind, inner| {
+            buffer
+                .text_object_range(pos, TextObject { kind, inner })
+                .map(|r| buffer.slice(r).unwrap())
+        };
+
+        assert_eq!(range(1, TextObjectKind::Word, true), Some("say".into()));
+        assert_eq!(range(1, TextObjectKind::Word, false), Some("say ".into()));
+        assert_eq!(
+            range(6, TextObjectKind::Quote('"'), true),
+            Some("hi there".into())
+        );
+        assert_eq!(
+            range(6, TextObjectKind::Quote('"'), false),
+            Some("\"hi there\"".into())
+        );
+        assert_eq!(
+            range(16, TextObjectKind::Pair('(', ')'), true),
+            Some("a (b) c".into())
+        );
+        assert_eq!(
+            range(20, TextObjectKind::Pair('(', ')'), false),
+            Some("(b)".into())
+        );
+    }
+
+    #[test]
+    fn test_markdown_text_objects() {
+        let text = "See [docs](https://x.y) and [[Other Note]]\n[estado::abierto, prioridad:: alta]\n```rust\nfn main() {}\n```\n";
+        let buffer = NoteBuffer::from_text(text);
+        let range = |pos, kind, inner| {
+            buffer
+                .text_object_range(pos, TextObject { kind, inner })
+                .map(|r| buffer.slice(r).unwrap())
+        };
+
+        assert_eq!(range(6, TextObjectKind::Link, true), Some("docs".into()));
+        assert_eq!(
+            range(6, TextObjectKind::Link, false),
+            Some("[docs](https://x.y)".into())
+        );
+        assert_eq!(
+            range(32, TextObjectKind::Link, true),
+            Some("Other Note".into())
+        );
+
+        let line2 = text.find("[estado").unwrap();
+        assert_eq!(
+            range(line2 + 3, TextObjectKind::InlineProperty, true),
+            Some("abierto".into())
+        );
+        assert_eq!(
+            range(line2 + 20, TextObjectKind::InlineProperty, true),
+            Some("alta".into())
+        );
+        assert_eq!(
+            range(line2 + 3, TextObjectKind::InlineProperty, false),
+            Some("[estado::abierto, prioridad:: alta]".into())
+        );
+
+        let code = text.find("fn main").unwrap();
+        assert_eq!(
+            range(code, TextObjectKind::CodeBlock, true),
+            Some("fn main() {}\n".into())
+        );
+        assert_eq!(
+            range(code, TextObjectKind::CodeBlock, false),
+            Some("```rust\nfn main() {}\n```\n".into())
+        );
+    }
+
+    #[test]
+    fn test_put() {
+        // Caracteres: `p` después del cursor, `P` antes, con contador
+        let mut buffer = NoteBuffer::from_text("abc");
+        assert_eq!(buffer.put(0, "XY", false, false, 1), 2);
+        assert_eq!(buffer.to_string(), "aXYbc");
+        assert_eq!(buffer.put(0, "-", false, true, 3), 2);
+        assert_eq!(buffer.to_string(), "---aXYbc");
+
+        // Líneas: debajo o encima de la línea actual
+        let mut buffer = NoteBuffer::from_text("uno\n  dos\ntres");
+        assert_eq!(buffer.put(1, "nueva\n", true, false, 1), 4);
+        assert_eq!(buffer.to_string(), "uno\nnueva\n  dos\ntres");
+        assert_eq!(buffer.put(12, "  arriba\n", true, true, 1), 12);
+        assert_eq!(buffer.to_string(), "uno\nnueva\n  arriba\n  dos\ntres");
+
+        // Última línea sin salto final
+        let mut buffer = NoteBuffer::from_text("a\nb");
+        assert_eq!(buffer.put(2, "c\n", true, false, 2), 4);
+        assert_eq!(buffer.to_string(), "a\nb\nc\nc");
+    }
+
+    #[test]
+    fn test_undo_groups() {
+        let mut buffer = NoteBuffer::from_text("x");
+
+        // Una racha de escritura (con backspace) es un solo paso
+        for (i, c) in "hola".chars().enumerate() {
+            buffer.insert(1 + i, &c.to_string());
+        }
+        buffer.delete(4..5);
+        buffer.insert(4, "o");
+        assert_eq!(buffer.to_string(), "xholo");
+
+        // Escribir en otro sitio empieza otro paso
+        buffer.insert(0, "!");
+        buffer.undo();
+        assert_eq!(buffer.to_string(), "xholo");
+        buffer.undo();
+        assert_eq!(buffer.to_string(), "x");
+
+        // Un grupo explícito (anidado) se deshace de una vez
+        buffer.begin_undo_group();
+        buffer.insert(1, "a");
+        buffer.begin_undo_group();
+        buffer.insert(0, "b");
+        buffer.end_undo_group();
+        buffer.replace(0..1, "cc");
+        buffer.end_undo_group();
+        assert_eq!(buffer.to_string(), "ccxa");
+        assert!(buffer.undo());
+        assert_eq!(buffer.to_string(), "x");
+        assert!(buffer.redo());
+        assert_eq!(buffer.to_string(), "ccxa");
+
+        // Tras sellar la racha, la siguiente pulsación es otro paso
+        buffer.insert(4, "1");
+        buffer.seal_undo_group();
+        buffer.insert(5, "2");
+        buffer.undo();
+        assert_eq!(buffer.to_string(), "ccxa1");
+    }
+
+    #[test]
+    fn test_saved_state_and_set_text() {
+        let mut buffer = NoteBuffer::from_text("uno\ndos\ntres");
+        assert!(buffer.is_at_saved_state());
+
+        buffer.set_text("uno\nDOS\ntres");
+        assert!(!buffer.is_at_saved_state());
+        buffer.mark_saved();
+        assert!(buffer.is_at_saved_state());
+
+        buffer.undo();
+        assert_eq!(buffer.to_string(), "uno\ndos\ntres");
+        assert!(!buffer.is_at_saved_state());
+        buffer.redo();
+        assert!(buffer.is_at_saved_state());
+
+        // Deshacer y editar otra cosa no vuelve al estado guardado
+        buffer.undo();
+        buffer.set_text("uno\nDOS\ntres");
+        assert!(!buffer.is_at_saved_state());
+    }
+
+    #[test]
+    fn test_load_saved_keeps_unsaved_edits_undoable() {
+        let mut buffer = NoteBuffer::from_text("uno\n");
+        buffer.insert(4, "sin guardar\n");
+        assert!(!buffer.is_at_saved_state());
+
+        // Versión restaurada en disco: no queda nada pendiente de guardar
+        buffer.load_saved("versión antigua\n");
+        assert_eq!(buffer.to_string(), "versión antigua\n");
+        assert!(buffer.is_at_saved_state());
+
+        // Lo que no se había guardado se recupera con deshacer
+        buffer.undo();
+        assert_eq!(buffer.to_string(), "uno\nsin guardar\n");
+        assert!(!buffer.is_at_saved_state());
+    }
+
+    #[test]
+    fn test_persisted_history() {
+        let mut buffer = NoteBuffer::from_text("a");
+        buffer.insert(1, "bc");
+        buffer.insert(3, "de");
+        buffer.undo();
+        buffer.mark_saved();
+
+        let json = serde_json::to_string(&buffer.history()).unwrap();
+        let history: UndoHistory = serde_json::from_str(&json).unwrap();
+
+        // Solo se recupera sobre el mismo texto
+        assert!(!NoteBuffer::from_text("otro").restore_history(history.clone()));
+
+        let mut restored = NoteBuffer::from_text("abc");
+        assert!(restored.restore_history(history));
+        assert!(restored.is_at_saved_state());
+        assert!(restored.redo());
+        assert_eq!(restored.to_string(), "abcde");
+        restored.undo();
+        restored.undo();
+        assert_eq!(restored.to_string(), "a");
+
+        // El historial guardado está acotado; el más antiguo se pierde
+        let mut long = NoteBuffer::new();
+        for i in 0..MAX_PERSISTED_GROUPS + 10 {
+            long.insert(long.len_chars(), &format!("{i},"));
+        }
+        let history = long.history();
+        let mut restored = NoteBuffer::from_text(&long.to_string());
+        assert!(restored.restore_history(history));
+        let mut undone = 0;
+        while restored.undo() {
+            undone += 1;
+        }
+        assert_eq!(undone, MAX_PERSISTED_GROUPS);
+        assert!(!restored.is_at_saved_state());
+    }
 }
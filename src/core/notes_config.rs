@@ -4,6 +4,10 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use super::embedding_config::EmbeddingConfig;
+use super::periodic::PeriodicNotesConfig;
+use super::registers::Registers;
+use super::template::{DEFAULT_TEMPLATES_FOLDER, TemplateLibrary};
+use super::vault_git::GitConfig;
 
 /// Configuración del asistente AI
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,12 +106,31 @@ pub struct NotesConfig {
     /// Mostrar barra de herramientas de formato en modo INSERT
     #[serde(default = "default_show_format_toolbar")]
     pub show_format_toolbar: bool,
+    /// Registros y macros vim (se conservan entre sesiones)
+    #[serde(default)]
+    pub vim_registers: Registers,
+    /// Carpeta de plantillas, relativa al directorio de notas
+    #[serde(default = "default_templates_folder")]
+    pub templates_folder: String,
+    /// Plantilla por defecto de cada carpeta (carpeta -> plantilla; "" = raíz)
+    #[serde(default)]
+    pub folder_templates: HashMap<String, String>,
+    /// Notas diarias, semanales, mensuales y anuales
+    #[serde(default)]
+    pub periodic_notes: PeriodicNotesConfig,
+    /// Historial Git del vault
+    #[serde(default)]
+    pub git: GitConfig,
 }
 
 fn default_show_format_toolbar() -> bool {
     true
 }
 
+fn default_templates_folder() -> String {
+    DEFAULT_TEMPLATES_FOLDER.to_string()
+}
+
 impl Default for NotesConfig {
     fn default() -> Self {
         Self::new()
@@ -130,6 +153,11 @@ impl NotesConfig {
             onboarding_completed: false,
             last_seen_version: None,
             show_format_toolbar: default_show_format_toolbar(),
+            vim_registers: Registers::default(),
+            templates_folder: default_templates_folder(),
+            folder_templates: HashMap::new(),
+            periodic_notes: PeriodicNotesConfig::default(),
+            git: GitConfig::default(),
         }
     }
 
@@ -277,6 +305,14 @@ impl NotesConfig {
             .join("assets")
     }
 
+    /// Directorio de diccionarios Hunspell propios y del diccionario personal
+    pub fn dictionaries_dir() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("notnative")
+            .join("dictionaries")
+    }
+
     /// Asegura que el directorio de assets exista
     pub fn ensure_assets_dir() -> Result<PathBuf> {
         let assets_dir = Self::assets_dir();
@@ -423,4 +459,54 @@ impl NotesConfig {
     pub fn set_show_format_toolbar(&mut self, show: bool) {
         self.show_format_toolbar = show;
     }
+
+    /// Obtiene los registros vim guardados
+    pub fn get_vim_registers(&self) -> &Registers {
+        &self.vim_registers
+    }
+
+    /// Guarda los registros vim de la sesión actual
+    pub fn set_vim_registers(&mut self, registers: Registers) {
+        self.vim_registers = registers;
+    }
+
+    /// Biblioteca de plantillas dentro del directorio de notas
+    pub fn template_library(&self, notes_root: &Path) -> TemplateLibrary {
+        TemplateLibrary::new(notes_root.join(&self.templates_folder))
+    }
+
+    /// Plantilla de una carpeta, heredada de la carpeta padre más cercana
+    /// (las notas de la propia carpeta de plantillas no reciben ninguna)
+    pub fn template_for_folder(&self, folder: Option<&str>) -> Option<&str> {
+        let mut folder = folder.unwrap_or("").trim_matches('/');
+        let templates_folder = self.templates_folder.trim_matches('/');
+        if folder == templates_folder || folder.starts_with(&format!("{}/", templates_folder)) {
+            return None;
+        }
+        loop {
+            if let Some(template) = self.folder_templates.get(folder) {
+                return Some(template);
+            }
+            if folder.is_empty() {
+                return None;
+            }
+            folder = folder
+                .rsplit_once('/')
+                .map(|(parent, _)| parent)
+                .unwrap_or("");
+        }
+    }
+
+    /// Asigna (o quita, con `None`) la plantilla por defecto de una carpeta
+    pub fn set_folder_template(&mut self, folder: &str, template: Option<String>) {
+        let folder = folder.trim_matches('/').to_string();
+        match template.filter(|t| !t.is_empty()) {
+            Some(template) => {
+                self.folder_templates.insert(folder, template);
+            }
+            None => {
+                self.folder_templates.remove(&folder);
+            }
+        }
+    }
 }
@@ -0,0 +1,503 @@
+//! Estructura de una nota a partir de sus encabezados
+//!
+//! - `Outline`: encabezados con el rango de líneas de su sección (subsecciones incluidas)
+//! - `Folds`: secciones plegadas en el preview (`za`, `zM`, `zR`...)
+//! - `edit_section`: mover o promover/degradar una sección con todo su subárbol
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use super::command::{FoldCommand, SectionEdit};
+use super::markdown::{Heading, MarkdownParser};
+
+/// Encabezados de una nota y las secciones que abarcan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outline {
+    headings: Vec<Heading>,
+    /// Líneas de cada sección: desde el encabezado hasta el siguiente de nivel igual o superior
+    sections: Vec<Range<usize>>,
+}
+
+impl Outline {
+    /// Construir el outline de un texto (se ignoran los encabezados del frontmatter)
+    pub fn parse(text: &str) -> Self {
+        let skip = frontmatter_lines(text);
+        let headings: Vec<Heading> = MarkdownParser::new(text.to_string())
+            .headings()
+            .into_iter()
+            .filter(|heading| heading.line >= skip)
+            .collect();
+
+        let line_count = text.split_inclusive('\n').count();
+        let sections = headings
+            .iter()
+            .enumerate()
+            .map(|(i, heading)| {
+                let end = headings[i + 1..]
+                    .iter()
+                    .find(|next| next.level <= heading.level)
+                    .map_or(line_count, |next| next.line);
+                heading.line..end.max(heading.line + 1)
+            })
+            .collect();
+
+        Self { headings, sections }
+    }
+
+    pub fn headings(&self) -> &[Heading] {
+        &self.headings
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headings.is_empty()
+    }
+
+    /// Líneas de la sección del encabezado `index`
+    pub fn section_lines(&self, index: usize) -> Option<Range<usize>> {
+        self.sections.get(index).cloned()
+    }
+
+    /// Sección más interna que contiene `line` (la sección "actual" del cursor)
+    pub fn section_at(&self, line: usize) -> Option<usize> {
+        self.sections
+            .iter()
+            .enumerate()
+            .filter(|(_, range)| range.contains(&line))
+            .map(|(i, _)| i)
+            .next_back()
+    }
+
+    /// Índice del encabezado que empieza en `line`
+    pub fn heading_at(&self, line: usize) -> Option<usize> {
+        self.headings
+            .iter()
+            .position(|heading| heading.line == line)
+    }
+
+    /// `count`-ésimo encabezado después de `line` (`]]`); el último si no hay tantos
+    pub fn next_heading(&self, line: usize, count: usize) -> Option<&Heading> {
+        let mut after = self.headings.iter().filter(|heading| heading.line > line);
+        let last = after.clone().next_back();
+        after.nth(count.max(1) - 1).or(last)
+    }
+
+    /// `count`-ésimo encabezado antes de `line` (`[[`); el primero si no hay tantos
+    pub fn previous_heading(&self, line: usize, count: usize) -> Option<&Heading> {
+        let mut before = self
+            .headings
+            .iter()
+            .rev()
+            .filter(|heading| heading.line < line);
+        let first = before.clone().next_back();
+        before.nth(count.max(1) - 1).or(first)
+    }
+
+    /// Tabla de contenidos como lista markdown indentada por nivel
+    pub fn to_markdown_list(&self) -> String {
+        let min_level = self.headings.iter().map(|h| h.level).min().unwrap_or(1);
+        self.headings
+            .iter()
+            .map(|heading| {
+                format!(
+                    "{}- {}\n",
+                    "  ".repeat((heading.level - min_level) as usize),
+                    heading.title
+                )
+            })
+            .collect()
+    }
+}
+
+/// Número de líneas que ocupa el frontmatter YAML (0 si no hay)
+pub(crate) fn frontmatter_lines(text: &str) -> usize {
+    let mut lines = text.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return 0;
+    }
+    lines
+        .position(|line| line.trim_end() == "---")
+        .map_or(0, |end| end + 2)
+}
+
+/// Secciones plegadas, identificadas por la línea de su encabezado
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Folds {
+    lines: BTreeSet<usize>,
+}
+
+impl Folds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_folded(&self, heading_line: usize) -> bool {
+        self.lines.contains(&heading_line)
+    }
+
+    /// Líneas de los encabezados plegados
+    pub fn lines(&self) -> impl Iterator<Item = usize> + '_ {
+        self.lines.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Aplicar un comando `z` con el cursor en `line`; devuelve si cambió algo
+    pub fn apply(&mut self, command: FoldCommand, outline: &Outline, line: usize) -> bool {
+        let before = self.lines.clone();
+        match command {
+            FoldCommand::Toggle => {
+                // Si el cursor está dentro de un pliegue, se abre el más externo
+                match self.outermost_fold(outline, line) {
+                    Some(folded) => {
+                        self.lines.remove(&folded);
+                    }
+                    None => {
+                        if let Some(index) = outline.section_at(line) {
+                            self.lines.insert(outline.headings[index].line);
+                        }
+                    }
+                }
+            }
+            FoldCommand::Open => {
+                if let Some(folded) = self.outermost_fold(outline, line) {
+                    self.lines.remove(&folded);
+                }
+            }
+            FoldCommand::Close => {
+                if let Some(index) = outline.section_at(line) {
+                    self.lines.insert(outline.headings[index].line);
+                }
+            }
+            FoldCommand::CloseAll => {
+                // Solo las secciones con contenido bajo el encabezado
+                self.lines = outline
+                    .headings
+                    .iter()
+                    .zip(&outline.sections)
+                    .filter(|(_, range)| range.len() > 1)
+                    .map(|(heading, _)| heading.line)
+                    .collect();
+            }
+            FoldCommand::OpenAll => self.lines.clear(),
+        }
+        self.lines != before
+    }
+
+    /// Abrir los pliegues que ocultan `line` (al saltar a un encabezado)
+    pub fn reveal(&mut self, outline: &Outline, line: usize) {
+        for (heading, range) in outline.headings.iter().zip(&outline.sections) {
+            if heading.line < line && range.contains(&line) {
+                self.lines.remove(&heading.line);
+            }
+        }
+    }
+
+    /// Si `line` queda oculta dentro de una sección plegada
+    pub fn is_hidden(&self, outline: &Outline, line: usize) -> bool {
+        self.outermost_fold(outline, line)
+            .is_some_and(|heading_line| heading_line < line)
+    }
+
+    /// Descartar pliegues cuyo encabezado ya no existe
+    pub fn retain_headings(&mut self, outline: &Outline) {
+        self.lines
+            .retain(|line| outline.heading_at(*line).is_some());
+    }
+
+    /// Encabezado plegado más externo cuya sección contiene `line`
+    fn outermost_fold(&self, outline: &Outline, line: usize) -> Option<usize> {
+        outline
+            .headings
+            .iter()
+            .zip(&outline.sections)
+            .find(|(heading, range)| self.lines.contains(&heading.line) && range.contains(&line))
+            .map(|(heading, _)| heading.line)
+    }
+}
+
+/// Resultado de editar una sección: texto nuevo y línea donde queda su encabezado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionEditResult {
+    pub text: String,
+    pub heading_line: usize,
+}
+
+/// Mover o promover/degradar la sección actual (la que contiene `line`) `count` veces
+///
+/// Devuelve `None` si no hay sección o el cambio no es posible (primera sección al
+/// subir, nivel fuera de 1-6...).
+pub fn edit_section(
+    text: &str,
+    line: usize,
+    edit: SectionEdit,
+    count: usize,
+) -> Option<SectionEditResult> {
+    let mut text = text.to_string();
+    let mut line = line;
+    let mut changed = false;
+
+    for _ in 0..count.max(1) {
+        let outline = Outline::parse(&text);
+        let index = outline.section_at(line)?;
+        let result = match edit {
+            SectionEdit::MoveUp => move_section(&text, &outline, index, true),
+            SectionEdit::MoveDown => move_section(&text, &outline, index, false),
+            SectionEdit::Promote => shift_section(&text, &outline, index, -1),
+            SectionEdit::Demote => shift_section(&text, &outline, index, 1),
+        };
+        match result {
+            Some(result) => {
+                text = result.text;
+                line = result.heading_line;
+                changed = true;
+            }
+            None => break,
+        }
+    }
+
+    changed.then_some(SectionEditResult {
+        text,
+        heading_line: line,
+    })
+}
+
+/// Intercambiar una sección con su hermana anterior o siguiente del mismo nivel
+fn move_section(
+    text: &str,
+    outline: &Outline,
+    index: usize,
+    up: bool,
+) -> Option<SectionEditResult> {
+    let level = outline.headings[index].level;
+    let current = outline.sections[index].clone();
+
+    let sibling = if up {
+        let previous = outline.headings[..index]
+            .iter()
+            .rposition(|heading| heading.level <= level)?;
+        (outline.headings[previous].level == level).then_some(previous)?
+    } else {
+        let next = outline.heading_at(current.end)?;
+        (outline.headings[next].level == level).then_some(next)?
+    };
+    let other = outline.sections[sibling].clone();
+
+    let (mut lines, trailing_newline) = split_lines(text);
+    let (first, second) = if up {
+        (other, current)
+    } else {
+        (current, other)
+    };
+    let moved: Vec<String> = lines[second.clone()]
+        .iter()
+        .chain(&lines[first.clone()])
+        .cloned()
+        .collect();
+    lines.splice(first.start..second.end, moved);
+
+    let heading_line = if up {
+        first.start
+    } else {
+        first.start + second.len()
+    };
+    Some(SectionEditResult {
+        text: join_lines(&lines, trailing_newline),
+        heading_line,
+    })
+}
+
+/// Cambiar el nivel del encabezado de una sección y de todos sus subencabezados
+fn shift_section(
+    text: &str,
+    outline: &Outline,
+    index: usize,
+    delta: i8,
+) -> Option<SectionEditResult> {
+    let range = outline.sections[index].clone();
+    let (mut lines, trailing_newline) = split_lines(text);
+
+    // Solo se editan encabezados ATX o subrayados escritos al inicio de la línea;
+    // los que van dentro de citas o listas (`> ## Foo`) se dejan como están
+    let subtree: Vec<(&Heading, HeadingSyntax)> = outline
+        .headings
+        .iter()
+        .filter(|heading| range.contains(&heading.line))
+        .filter_map(|heading| Some((heading, heading_syntax(&lines, heading.line)?)))
+        .collect();
+    if subtree
+        .iter()
+        .any(|(heading, _)| !(1..=6).contains(&(heading.level as i8 + delta)))
+    {
+        return None;
+    }
+
+    // De abajo arriba para que quitar subrayados no desplace las líneas pendientes
+    for (heading, syntax) in subtree.iter().rev() {
+        let level = (heading.level as i8 + delta) as usize;
+        let trimmed = lines[heading.line].trim_start();
+        match syntax {
+            HeadingSyntax::Atx => {
+                let rest = trimmed.trim_start_matches('#');
+                lines[heading.line] = format!("{}{}", "#".repeat(level), rest);
+            }
+            HeadingSyntax::Setext => {
+                // Encabezado subrayado (=== / ---): convertir a `#`
+                lines[heading.line] = format!("{} {}\n", "#".repeat(level), trimmed.trim_end());
+                lines.remove(heading.line + 1);
+            }
+        }
+    }
+
+    Some(SectionEditResult {
+        text: join_lines(&lines, trailing_newline),
+        heading_line: outline.headings[index].line,
+    })
+}
+
+/// Cómo está escrito un encabezado en el texto
+enum HeadingSyntax {
+    /// `## Título`
+    Atx,
+    /// `Título` con `===` o `---` en la línea siguiente
+    Setext,
+}
+
+fn heading_syntax(lines: &[String], line: usize) -> Option<HeadingSyntax> {
+    let trimmed = lines.get(line)?.trim_start();
+    if trimmed.starts_with('#') {
+        return Some(HeadingSyntax::Atx);
+    }
+    // El texto de un subrayado dentro de una cita o una lista empieza por su marcador
+    let in_container = trimmed.starts_with(['>', '-', '*', '+'])
+        || (trimmed.starts_with(|c: char| c.is_ascii_digit())
+            && trimmed
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .starts_with(['.', ')']));
+    if in_container {
+        return None;
+    }
+    let underline = lines.get(line + 1)?.trim();
+    let is_underline =
+        |marker: char| !underline.is_empty() && underline.chars().all(|c| c == marker);
+    (is_underline('=') || is_underline('-')).then_some(HeadingSyntax::Setext)
+}
+
+/// Líneas del texto, todas terminadas en `\n`, y si el original terminaba en `\n`
+fn split_lines(text: &str) -> (Vec<String>, bool) {
+    let lines = text
+        .split_inclusive('\n')
+        .map(|line| {
+            if line.ends_with('\n') {
+                line.to_string()
+            } else {
+                format!("{line}\n")
+            }
+        })
+        .collect();
+    (lines, text.ends_with('\n') || text.is_empty())
+}
+
+fn join_lines(lines: &[String], trailing_newline: bool) -> String {
+    let mut text = lines.concat();
+    if !trailing_newline {
+        text.pop();
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: x\n---\n# A\na\n## A1\na1\n## A2\na2\n# B\nb";
+
+    #[test]
+    fn test_outline_sections() {
+        let outline = Outline::parse(NOTE);
+        let titles: Vec<&str> = outline
+            .headings()
+            .iter()
+            .map(|h| h.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["A", "A1", "A2", "B"]);
+
+        assert_eq!(outline.section_lines(0), Some(3..9));
+        assert_eq!(outline.section_lines(1), Some(5..7));
+        assert_eq!(outline.section_lines(3), Some(9..11));
+        assert_eq!(outline.section_at(6), Some(1));
+        assert_eq!(outline.section_at(4), Some(0));
+        assert_eq!(outline.section_at(0), None);
+
+        assert_eq!(outline.next_heading(3, 2).unwrap().title, "A2");
+        assert_eq!(outline.next_heading(3, 9).unwrap().title, "B");
+        assert_eq!(outline.previous_heading(10, 1).unwrap().title, "B");
+        assert_eq!(outline.previous_heading(10, 2).unwrap().title, "A2");
+        assert_eq!(outline.to_markdown_list(), "- A\n  - A1\n  - A2\n- B\n");
+    }
+
+    #[test]
+    fn test_folds() {
+        let outline = Outline::parse(NOTE);
+        let mut folds = Folds::new();
+
+        assert!(folds.apply(FoldCommand::Toggle, &outline, 6));
+        assert!(folds.is_folded(5));
+        assert!(folds.is_hidden(&outline, 6));
+        assert!(!folds.is_hidden(&outline, 5));
+
+        // za dentro de un pliegue lo abre
+        folds.apply(FoldCommand::Toggle, &outline, 6);
+        assert!(folds.is_empty());
+
+        folds.apply(FoldCommand::CloseAll, &outline, 0);
+        assert_eq!(folds.lines().collect::<Vec<_>>(), vec![3, 5, 7, 9]);
+        folds.reveal(&outline, 8);
+        assert_eq!(folds.lines().collect::<Vec<_>>(), vec![5, 9]);
+        assert!(folds.apply(FoldCommand::OpenAll, &outline, 0));
+        assert!(!folds.apply(FoldCommand::OpenAll, &outline, 0));
+    }
+
+    #[test]
+    fn test_move_sections() {
+        // A2 sube por encima de A1 con su contenido
+        let result = edit_section(NOTE, 8, SectionEdit::MoveUp, 1).unwrap();
+        assert_eq!(
+            result.text,
+            "---\ntitle: x\n---\n# A\na\n## A2\na2\n## A1\na1\n# B\nb"
+        );
+        assert_eq!(result.heading_line, 5);
+
+        // A baja por debajo de B con todo su subárbol
+        let result = edit_section(NOTE, 3, SectionEdit::MoveDown, 1).unwrap();
+        assert_eq!(
+            result.text,
+            "---\ntitle: x\n---\n# B\nb\n# A\na\n## A1\na1\n## A2\na2"
+        );
+        assert_eq!(result.heading_line, 5);
+
+        // A1 es la primera de su nivel dentro de A: no puede subir
+        assert!(edit_section(NOTE, 5, SectionEdit::MoveUp, 1).is_none());
+    }
+
+    #[test]
+    fn test_promote_and_demote() {
+        let result = edit_section(NOTE, 4, SectionEdit::Demote, 1).unwrap();
+        assert_eq!(
+            result.text,
+            "---\ntitle: x\n---\n## A\na\n### A1\na1\n### A2\na2\n# B\nb"
+        );
+        assert!(edit_section(NOTE, 4, SectionEdit::Promote, 1).is_none());
+
+        // Los encabezados subrayados se convierten a `#`
+        let result = edit_section("Título\n===\ntexto\n", 0, SectionEdit::Demote, 2).unwrap();
+        assert_eq!(result.text, "### Título\ntexto\n");
+        assert_eq!(result.heading_line, 0);
+
+        // Un encabezado dentro de una cita no se toca ni se come la línea siguiente
+        let note = "# A\n> ## Foo\n> cita\ntexto\n";
+        let result = edit_section(note, 0, SectionEdit::Demote, 1).unwrap();
+        assert_eq!(result.text, "## A\n> ## Foo\n> cita\ntexto\n");
+    }
+}
@@ -0,0 +1,601 @@
+//! Notas periódicas: diarias, semanales, mensuales y anuales
+//!
+//! Cada periodo tiene su carpeta, un patrón de nombre (tokens estilo Moment,
+//! los mismos que `{{date:...}}` en las plantillas) y una plantilla opcional.
+//! Las notas nuevas enlazan con la nota del periodo superior
+//! (día → semana → mes → año) y la diaria puede arrastrar las tareas sin
+//! terminar de la anterior.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::template::{
+    Template, TemplateContext, TemplateError, TemplateLibrary, format_date,
+};
+
+/// Días hacia atrás en los que se busca la nota diaria anterior
+const CARRY_OVER_LOOKBACK_DAYS: u64 = 31;
+
+/// Tarea sin terminar (`- [ ] algo`)
+static UNFINISHED_TASK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*[-*+] \[ \]\s+\S").unwrap());
+
+#[derive(Debug, Error)]
+pub enum PeriodicError {
+    #[error("Unknown period: {0}")]
+    UnknownPeriod(String),
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+    #[error("Template error: {0}")]
+    Template(#[from] TemplateError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, PeriodicError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Period {
+    pub const ALL: [Period; 4] = [
+        Period::Daily,
+        Period::Weekly,
+        Period::Monthly,
+        Period::Yearly,
+    ];
+
+    /// Acepta `daily`, `day`, `diaria`... (mayúsculas indiferentes)
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "daily" | "day" | "d" | "today" | "diaria" | "dia" | "día" | "hoy" => {
+                Ok(Period::Daily)
+            }
+            "weekly" | "week" | "w" | "semanal" | "semana" => Ok(Period::Weekly),
+            "monthly" | "month" | "m" | "mensual" | "mes" => Ok(Period::Monthly),
+            "yearly" | "year" | "y" | "anual" | "año" => Ok(Period::Yearly),
+            other => Err(PeriodicError::UnknownPeriod(other.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+            Period::Monthly => "monthly",
+            Period::Yearly => "yearly",
+        }
+    }
+
+    /// Periodo que contiene a este (el año no tiene)
+    pub fn parent(&self) -> Option<Period> {
+        match self {
+            Period::Daily => Some(Period::Weekly),
+            Period::Weekly => Some(Period::Monthly),
+            Period::Monthly => Some(Period::Yearly),
+            Period::Yearly => None,
+        }
+    }
+
+    /// Primer día del periodo que contiene `date` (las semanas empiezan en lunes)
+    pub fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Daily => date,
+            Period::Weekly => date
+                .checked_sub_days(Days::new(date.weekday().num_days_from_monday() as u64))
+                .unwrap_or(date),
+            Period::Monthly => date.with_day(1).unwrap_or(date),
+            Period::Yearly => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date),
+        }
+    }
+
+    /// Inicio del periodo desplazado `offset` periodos (negativo = hacia atrás)
+    ///
+    /// Falla si el resultado queda fuera del rango de fechas (`:daily 99999999`).
+    pub fn shift(&self, date: NaiveDate, offset: i32) -> Result<NaiveDate> {
+        let start = self.start(date);
+        let offset = offset as i64;
+        let days = |count: i64| {
+            let magnitude = Days::new(count.unsigned_abs());
+            if count >= 0 {
+                start.checked_add_days(magnitude)
+            } else {
+                start.checked_sub_days(magnitude)
+            }
+        };
+        let months = |count: i64| {
+            let magnitude = Months::new(u32::try_from(count.unsigned_abs()).ok()?);
+            if count >= 0 {
+                start.checked_add_months(magnitude)
+            } else {
+                start.checked_sub_months(magnitude)
+            }
+        };
+        match self {
+            Period::Daily => days(offset),
+            Period::Weekly => days(offset * 7),
+            Period::Monthly => months(offset),
+            Period::Yearly => months(offset * 12),
+        }
+        .ok_or_else(|| {
+            PeriodicError::InvalidDate(format!("{} {:+} {}", start, offset, self.as_str()))
+        })
+    }
+}
+
+/// Carpeta, patrón de nombre y plantilla de un tipo de nota periódica
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodConfig {
+    /// Carpeta relativa al directorio de notas ("" = raíz)
+    pub folder: String,
+    /// Patrón del nombre (`YYYY-MM-DD`, `GGGG-[W]WW`, `YYYY-MM`, `YYYY`)
+    pub format: String,
+    /// Plantilla (nombre dentro de la carpeta de plantillas)
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+impl PeriodConfig {
+    fn new(folder: &str, format: &str) -> Self {
+        Self {
+            folder: folder.to_string(),
+            format: format.to_string(),
+            template: None,
+        }
+    }
+
+    fn daily() -> Self {
+        Self::new("Daily", "YYYY-MM-DD")
+    }
+
+    fn weekly() -> Self {
+        Self::new("Weekly", "GGGG-[W]WW")
+    }
+
+    fn monthly() -> Self {
+        Self::new("Monthly", "YYYY-MM")
+    }
+
+    fn yearly() -> Self {
+        Self::new("Yearly", "YYYY")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodicNotesConfig {
+    #[serde(default = "PeriodConfig::daily")]
+    pub daily: PeriodConfig,
+    #[serde(default = "PeriodConfig::weekly")]
+    pub weekly: PeriodConfig,
+    #[serde(default = "PeriodConfig::monthly")]
+    pub monthly: PeriodConfig,
+    #[serde(default = "PeriodConfig::yearly")]
+    pub yearly: PeriodConfig,
+    /// Copiar las tareas sin terminar de la nota diaria anterior
+    #[serde(default)]
+    pub carry_over_tasks: bool,
+}
+
+impl Default for PeriodicNotesConfig {
+    fn default() -> Self {
+        Self {
+            daily: PeriodConfig::daily(),
+            weekly: PeriodConfig::weekly(),
+            monthly: PeriodConfig::monthly(),
+            yearly: PeriodConfig::yearly(),
+            carry_over_tasks: false,
+        }
+    }
+}
+
+/// Una nota periódica concreta (exista o no en disco)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeriodicNote {
+    pub period: Period,
+    /// Primer día del periodo
+    pub date: NaiveDate,
+    pub folder: Option<String>,
+    /// Nombre del archivo sin `.md`
+    pub title: String,
+}
+
+impl PeriodicNote {
+    /// Nombre con carpeta, como se usa en `[[enlaces]]`
+    pub fn name(&self) -> String {
+        match &self.folder {
+            Some(folder) => format!("{}/{}", folder, self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    pub fn path(&self, notes_root: &Path) -> PathBuf {
+        notes_root.join(format!("{}.md", self.name()))
+    }
+}
+
+/// Resultado de abrir (y crear si hacía falta) una nota periódica
+#[derive(Debug, Clone)]
+pub struct EnsuredNote {
+    pub note: PeriodicNote,
+    pub path: PathBuf,
+    pub content: String,
+    pub created: bool,
+}
+
+impl PeriodicNotesConfig {
+    pub fn get(&self, period: Period) -> &PeriodConfig {
+        match period {
+            Period::Daily => &self.daily,
+            Period::Weekly => &self.weekly,
+            Period::Monthly => &self.monthly,
+            Period::Yearly => &self.yearly,
+        }
+    }
+
+    pub fn get_mut(&mut self, period: Period) -> &mut PeriodConfig {
+        match period {
+            Period::Daily => &mut self.daily,
+            Period::Weekly => &mut self.weekly,
+            Period::Monthly => &mut self.monthly,
+            Period::Yearly => &mut self.yearly,
+        }
+    }
+
+    /// Nota del periodo que contiene `date`
+    pub fn note(&self, period: Period, date: NaiveDate) -> PeriodicNote {
+        let config = self.get(period);
+        let start = period.start(date);
+        let datetime = start.and_hms_opt(0, 0, 0).unwrap_or_default();
+        let folder = config.folder.trim_matches('/');
+        PeriodicNote {
+            period,
+            date: start,
+            folder: (!folder.is_empty()).then(|| folder.to_string()),
+            title: format_date(&datetime, &config.format, "en"),
+        }
+    }
+
+    pub fn today(&self, period: Period) -> PeriodicNote {
+        self.note(period, Local::now().date_naive())
+    }
+
+    pub fn shifted(&self, note: &PeriodicNote, offset: i32) -> Result<PeriodicNote> {
+        Ok(self.note(note.period, note.period.shift(note.date, offset)?))
+    }
+
+    /// Nota del periodo superior; la semana pertenece al mes de su jueves (ISO)
+    pub fn parent(&self, note: &PeriodicNote) -> Option<PeriodicNote> {
+        let parent = note.period.parent()?;
+        let date = match note.period {
+            Period::Weekly => note.date.checked_add_days(Days::new(3))?,
+            _ => note.date,
+        };
+        Some(self.note(parent, date))
+    }
+
+    /// Reconocer una nota periódica por su nombre (`Daily/2025-03-07`)
+    pub fn note_for_name(&self, name: &str) -> Option<PeriodicNote> {
+        let name = name.trim().trim_end_matches(".md");
+        let (folder, title) = match name.rsplit_once('/') {
+            Some((folder, title)) => (folder, title),
+            None => ("", name),
+        };
+        Period::ALL.into_iter().find_map(|period| {
+            let config = self.get(period);
+            if config.folder.trim_matches('/') != folder {
+                return None;
+            }
+            let date = parse_title(period, &config.format, title)?;
+            let note = self.note(period, date);
+            (note.title == title).then_some(note)
+        })
+    }
+
+    /// Abrir la nota del periodo, creándola con su plantilla si no existe
+    pub fn ensure(
+        &self,
+        notes_root: &Path,
+        library: &TemplateLibrary,
+        note: &PeriodicNote,
+        language: &str,
+    ) -> Result<EnsuredNote> {
+        let path = note.path(notes_root);
+        if path.is_file() {
+            return Ok(EnsuredNote {
+                note: note.clone(),
+                content: fs::read_to_string(&path)?,
+                path,
+                created: false,
+            });
+        }
+
+        let template = match &self.get(note.period).template {
+            Some(name) if !name.trim().is_empty() => Some(library.load(name)?),
+            _ => None,
+        };
+        let carried = if note.period == Period::Daily && self.carry_over_tasks {
+            self.previous_unfinished_tasks(notes_root, note)
+        } else {
+            Vec::new()
+        };
+        let content = self.render(note, template.as_ref(), &carried, language);
+
+        if let Some(parent_dir) = path.parent() {
+            fs::create_dir_all(parent_dir)?;
+        }
+        fs::write(&path, &content)?;
+        Ok(EnsuredNote {
+            note: note.clone(),
+            path,
+            content,
+            created: true,
+        })
+    }
+
+    /// Contenido de una nota nueva.
+    ///
+    /// Las plantillas reciben `{{previous}}`, `{{next}}`, `{{parent}}`,
+    /// `{{period}}` y `{{carried_tasks}}`; si no enlazan al periodo superior
+    /// o no colocan las tareas arrastradas, se añaden igualmente.
+    pub fn render(
+        &self,
+        note: &PeriodicNote,
+        template: Option<&Template>,
+        carried: &[String],
+        language: &str,
+    ) -> String {
+        let neighbour = |offset| {
+            self.shifted(note, offset)
+                .map(|note| note.name())
+                .unwrap_or_default()
+        };
+        let previous = neighbour(-1);
+        let next = neighbour(1);
+        let parent = self.parent(note).map(|parent| parent.name());
+        let carried_text = carried.join("\n");
+
+        let mut content = match template {
+            Some(template) => {
+                let date = note.date.and_time(Local::now().time());
+                let context = TemplateContext::new(note.title.clone())
+                    .with_folder(note.folder.clone())
+                    .with_date(date)
+                    .with_language(language)
+                    .with_value("previous", previous.clone())
+                    .with_value("next", next.clone())
+                    .with_value("parent", parent.clone().unwrap_or_default())
+                    .with_value("period", note.period.as_str())
+                    .with_value("carried_tasks", carried_text.clone());
+                let content = template.render(&context).content;
+                match &parent {
+                    Some(parent) if !content.contains(&format!("[[{}]]", parent)) => {
+                        insert_after_frontmatter(&content, &format!("↑ [[{}]]\n\n", parent))
+                    }
+                    _ => content,
+                }
+            }
+            None => {
+                let mut navigation = format!("← [[{}]] · [[{}]] →", previous, next);
+                if let Some(parent) = &parent {
+                    navigation = format!("↑ [[{}]] · {}", parent, navigation);
+                }
+                format!("# {}\n\n{}\n\n", note.title, navigation)
+            }
+        };
+
+        if !carried.is_empty() && !content.contains(&carried_text) {
+            let heading = if language.starts_with("es") {
+                "## Pendientes"
+            } else {
+                "## Carried over"
+            };
+            if !content.is_empty() && !content.ends_with("\n\n") {
+                content.push_str(if content.ends_with('\n') {
+                    "\n"
+                } else {
+                    "\n\n"
+                });
+            }
+            content.push_str(&format!("{}\n\n{}\n", heading, carried_text));
+        }
+        content
+    }
+
+    /// Tareas sin terminar de la nota diaria existente más reciente antes de `note`
+    pub fn previous_unfinished_tasks(&self, notes_root: &Path, note: &PeriodicNote) -> Vec<String> {
+        (1..=CARRY_OVER_LOOKBACK_DAYS)
+            .map_while(|days| note.date.checked_sub_days(Days::new(days)))
+            .map(|date| self.note(Period::Daily, date))
+            .find_map(|previous| fs::read_to_string(previous.path(notes_root)).ok())
+            .map(|content| unfinished_tasks(&content))
+            .unwrap_or_default()
+    }
+}
+
+/// Líneas `- [ ] ...` de una nota (con su sangría)
+pub fn unfinished_tasks(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter(|line| UNFINISHED_TASK.is_match(line))
+        .map(|line| line.trim_end().to_string())
+        .collect()
+}
+
+/// Parsear una fecha en formato `YYYY-MM-DD`
+pub fn parse_date(text: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+        .map_err(|_| PeriodicError::InvalidDate(text.to_string()))
+}
+
+fn insert_after_frontmatter(content: &str, text: &str) -> String {
+    if let Some(rest) = content.strip_prefix("---\n")
+        && let Some(end) = rest.find("\n---\n")
+    {
+        let split = 4 + end + 5;
+        let (frontmatter, body) = content.split_at(split);
+        return format!("{}{}{}", frontmatter, text, body.trim_start_matches('\n'));
+    }
+    format!("{}{}", text, content)
+}
+
+/// Traducir un patrón Moment a `strftime` (solo tokens numéricos)
+fn chrono_format(format: &str) -> Option<String> {
+    const TOKENS: [(&str, &str); 9] = [
+        ("YYYY", "%Y"),
+        ("GGGG", "%G"),
+        ("YY", "%y"),
+        ("MM", "%m"),
+        ("DD", "%d"),
+        ("ww", "%V"),
+        ("WW", "%V"),
+        ("M", "%m"),
+        ("D", "%d"),
+    ];
+    let mut output = String::new();
+    let mut rest = format;
+    while !rest.is_empty() {
+        if let Some(literal) = rest.strip_prefix('[')
+            && let Some(close) = literal.find(']')
+        {
+            output.push_str(&literal[..close].replace('%', "%%"));
+            rest = &literal[close + 1..];
+            continue;
+        }
+        if let Some((token, spec)) = TOKENS.iter().find(|(token, _)| rest.starts_with(token)) {
+            output.push_str(spec);
+            rest = &rest[token.len()..];
+            continue;
+        }
+        let c = rest.chars().next()?;
+        if c.is_ascii_alphabetic() {
+            // Nombres de mes/día u otros tokens que no se pueden leer de vuelta
+            return None;
+        }
+        if c == '%' {
+            output.push('%');
+        }
+        output.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    Some(output)
+}
+
+fn parse_title(period: Period, format: &str, title: &str) -> Option<NaiveDate> {
+    let spec = chrono_format(format)?;
+    let (text, spec) = match period {
+        Period::Daily => (title.to_string(), spec),
+        Period::Weekly => (format!("{}|1", title), format!("{}|%u", spec)),
+        Period::Monthly => (format!("{}|1", title), format!("{}|%d", spec)),
+        Period::Yearly => (format!("{}|1|1", title), format!("{}|%m|%d", spec)),
+    };
+    NaiveDate::parse_from_str(&text, &spec).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_names_navigation_and_parents() {
+        let config = PeriodicNotesConfig::default();
+        let day = config.note(Period::Daily, date(2025, 3, 7));
+        assert_eq!(day.name(), "Daily/2025-03-07");
+        assert_eq!(config.shifted(&day, 1).unwrap().name(), "Daily/2025-03-08");
+
+        let week = config.parent(&day).unwrap();
+        assert_eq!(week.name(), "Weekly/2025-W10");
+        assert_eq!(week.date, date(2025, 3, 3));
+        assert_eq!(config.parent(&week).unwrap().name(), "Monthly/2025-03");
+
+        // La semana 1 de 2025 empieza el 30/12/2024 y pertenece a enero
+        let first_week = config.note(Period::Weekly, date(2024, 12, 31));
+        assert_eq!(first_week.name(), "Weekly/2025-W01");
+        assert_eq!(
+            config.parent(&first_week).unwrap().name(),
+            "Monthly/2025-01"
+        );
+
+        let month = config.note(Period::Monthly, date(2025, 1, 31));
+        assert_eq!(
+            config.shifted(&month, -1).unwrap().name(),
+            "Monthly/2024-12"
+        );
+
+        // Desplazamientos fuera del rango de fechas fallan en lugar de desbordar
+        assert!(Period::Daily.shift(date(2025, 3, 7), 99_999_999).is_err());
+        assert!(Period::Weekly.shift(date(2025, 3, 7), i32::MAX).is_err());
+        assert!(Period::Weekly.shift(date(2025, 3, 7), i32::MIN).is_err());
+        assert!(Period::Yearly.shift(date(2025, 3, 7), i32::MAX).is_err());
+        assert_eq!(
+            Period::Weekly.shift(date(2025, 3, 7), -2).unwrap(),
+            date(2025, 2, 17)
+        );
+        let year = config.parent(&month).unwrap();
+        assert_eq!(year.name(), "Yearly/2025");
+        assert!(config.parent(&year).is_none());
+
+        for note in [&day, &week, &first_week, &month, &year] {
+            assert_eq!(config.note_for_name(&note.name()).as_ref(), Some(note));
+        }
+        assert!(config.note_for_name("Daily/notas sueltas").is_none());
+        assert!(config.note_for_name("2025-03-07").is_none());
+    }
+
+    #[test]
+    fn test_render_and_carry_over() {
+        let root = std::env::temp_dir().join(format!("notnative-periodic-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let mut config = PeriodicNotesConfig {
+            carry_over_tasks: true,
+            ..Default::default()
+        };
+        let library = TemplateLibrary::new(root.join("Templates"));
+
+        fs::create_dir_all(root.join("Daily")).unwrap();
+        fs::write(
+            root.join("Daily/2025-03-05.md"),
+            "# Miércoles\n\n- [x] hecho\n- [ ] llamar a Ana\n  - [ ] preparar agenda\n- [ ] \n",
+        )
+        .unwrap();
+
+        let note = config.note(Period::Daily, date(2025, 3, 7));
+        let ensured = config.ensure(&root, &library, &note, "es").unwrap();
+        assert!(ensured.created);
+        assert_eq!(
+            ensured.content,
+            "# 2025-03-07\n\n↑ [[Weekly/2025-W10]] · ← [[Daily/2025-03-06]] · [[Daily/2025-03-08]] →\n\n## Pendientes\n\n- [ ] llamar a Ana\n  - [ ] preparar agenda\n"
+        );
+        assert!(!config.ensure(&root, &library, &note, "es").unwrap().created);
+
+        fs::create_dir_all(root.join("Templates")).unwrap();
+        fs::write(
+            root.join("Templates/Semana.md"),
+            "---\ntipo: semana\n---\n# Semana {{date:WW}}\n",
+        )
+        .unwrap();
+        config.weekly.template = Some("Semana".into());
+        let week = config.parent(&note).unwrap();
+        let ensured = config.ensure(&root, &library, &week, "es").unwrap();
+        assert_eq!(
+            ensured.content,
+            "---\ntipo: semana\n---\n↑ [[Monthly/2025-03]]\n\n# Semana 10\n"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,978 @@
+//! Publicación de una carpeta de notas como sitio web estático
+//!
+//! Cada nota se convierte en una página con `HtmlRenderer` (tema `Print`, sin
+//! JavaScript de la app). Los `[[enlaces]]` y las `@menciones` a notas publicadas
+//! pasan a URLs relativas, y se generan además páginas por tag, una sección de
+//! backlinks en cada nota, un índice de búsqueda JSON y un feed RSS. Las notas con
+//! `publish: false` en el frontmatter no se publican.
+//!
+//! Las páginas generadas (tags, búsqueda) van bajo `_site/` para no chocar con las
+//! notas, y un manifiesto con los ficheros escritos permite borrar en la siguiente
+//! publicación los que ya no se generan sin tocar nada más de la carpeta de salida.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use regex::Regex;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::export::{
+    FILE_IMG_RE, TAG_ANCHOR_RE, TAG_STRIP_RE, add_heading_ids, href_encode, resolve_exported,
+    rewrite_links,
+};
+use super::frontmatter::{Frontmatter, extract_all_tags};
+use super::html_renderer::{HtmlRenderer, PreviewTheme, escape_html, url_decode};
+use super::wikilink::{find_links, heading_id};
+
+/// Regex para la etiqueta de apertura del body
+static BODY_OPEN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<body[^>]*>").unwrap());
+
+/// Regex para espacios repetidos en el texto plano del índice de búsqueda
+static WHITESPACE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+
+/// Carpeta reservada para las páginas generadas (las notas dentro no se publican)
+const SITE_DIR: &str = "_site";
+
+/// Carpeta de las páginas de tags dentro del sitio
+const TAGS_DIR: &str = "_site/tags";
+
+/// Página del buscador (junto a su índice `search-index.json`)
+const SEARCH_PAGE: &str = "_site/search";
+
+/// Ficheros escritos por la última publicación, relativos a la carpeta de salida
+const MANIFEST_FILE: &str = ".notnative-publish";
+
+/// Carpeta de las imágenes copiadas
+const ASSETS_DIR: &str = "assets";
+
+/// Longitud máxima del texto de cada nota en el índice de búsqueda
+const SEARCH_TEXT_LIMIT: usize = 20_000;
+
+/// Longitud del resumen de cada entrada del feed
+const FEED_SUMMARY_LIMIT: usize = 300;
+
+/// Estilos propios del sitio, añadidos a los del preview
+const SITE_CSS: &str = r#"
+body.print {
+    max-width: 860px;
+    margin: 0 auto;
+}
+.site-nav {
+    display: flex;
+    gap: 16px;
+    align-items: baseline;
+    padding-bottom: 12px;
+    margin-bottom: 24px;
+    border-bottom: 1px solid var(--border);
+    font-size: 14px;
+}
+.site-nav .site-title {
+    font-weight: 600;
+    margin-right: auto;
+}
+.site-footer {
+    margin-top: 48px;
+    padding-top: 12px;
+    border-top: 1px solid var(--border);
+    font-size: 14px;
+    color: var(--fg-secondary);
+}
+.site-footer h2 {
+    font-size: 15px;
+    margin: 12px 0 4px;
+}
+.site-footer ul {
+    margin: 0;
+}
+.page-date {
+    color: var(--fg-muted);
+    font-size: 13px;
+}
+#search-input {
+    width: 100%;
+    padding: 8px 12px;
+    font-size: 16px;
+    border: 1px solid var(--border);
+    border-radius: 6px;
+}
+"#;
+
+/// Buscador de `search.html`: filtra `search-index.json` en el navegador
+const SEARCH_JS: &str = r#"
+const input = document.getElementById('search-input');
+const results = document.getElementById('search-results');
+let index = [];
+fetch('search-index.json').then(r => r.json()).then(data => { index = data; search(); });
+function search() {
+    const terms = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+    results.innerHTML = '';
+    if (terms.length === 0) return;
+    index
+        .filter(page => {
+            const text = (page.title + ' ' + page.tags.join(' ') + ' ' + page.text).toLowerCase();
+            return terms.every(term => text.includes(term));
+        })
+        .slice(0, 50)
+        .forEach(page => {
+            const li = document.createElement('li');
+            const a = document.createElement('a');
+            a.href = '../' + page.url;
+            a.textContent = page.title;
+            li.appendChild(a);
+            results.appendChild(li);
+        });
+}
+input.addEventListener('input', search);
+"#;
+
+#[derive(Debug, Error)]
+pub enum PublishError {
+    #[error("Folder not found: {0}")]
+    FolderNotFound(PathBuf),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Resultado de una publicación
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublishReport {
+    pub output_dir: PathBuf,
+    /// Notas publicadas
+    pub pages: usize,
+    /// Páginas de tags generadas
+    pub tags: usize,
+    /// Notas excluidas con `publish: false` o dentro de la carpeta reservada `_site`
+    pub excluded: Vec<String>,
+}
+
+/// Nota que se publica, con su nombre relativo a la carpeta publicada
+#[derive(Debug, Clone)]
+struct Page {
+    name: String,
+    title: String,
+    markdown: String,
+    tags: Vec<String>,
+    date: DateTime<Local>,
+}
+
+impl Page {
+    fn url(&self) -> String {
+        format!("{}.html", href_encode(&self.name))
+    }
+}
+
+/// Entrada de `search-index.json`
+#[derive(Debug, Serialize)]
+struct SearchEntry<'a> {
+    title: &'a str,
+    url: String,
+    tags: &'a [String],
+    date: String,
+    text: String,
+}
+
+/// Publicador de una carpeta del vault como sitio estático
+pub struct SitePublisher {
+    notes_root: PathBuf,
+    folder: String,
+    title: Option<String>,
+    base_url: Option<String>,
+    feed_limit: usize,
+}
+
+impl SitePublisher {
+    /// `folder` es relativa a `notes_root`; vacía publica todo el vault
+    pub fn new(notes_root: impl Into<PathBuf>, folder: impl Into<String>) -> Self {
+        Self {
+            notes_root: notes_root.into(),
+            folder: folder.into().trim_matches('/').to_string(),
+            title: None,
+            base_url: None,
+            feed_limit: 50,
+        }
+    }
+
+    /// Título del sitio (por defecto, el nombre de la carpeta)
+    pub fn with_title(mut self, title: Option<String>) -> Self {
+        self.title = title.filter(|t| !t.trim().is_empty());
+        self
+    }
+
+    /// URL pública del sitio, para los enlaces absolutos del feed RSS
+    pub fn with_base_url(mut self, base_url: Option<String>) -> Self {
+        self.base_url = base_url
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty());
+        self
+    }
+
+    pub fn with_feed_limit(mut self, feed_limit: usize) -> Self {
+        self.feed_limit = feed_limit;
+        self
+    }
+
+    fn folder_path(&self) -> PathBuf {
+        if self.folder.is_empty() {
+            self.notes_root.clone()
+        } else {
+            self.notes_root.join(&self.folder)
+        }
+    }
+
+    fn site_title(&self) -> String {
+        self.title.clone().unwrap_or_else(|| {
+            self.folder_path()
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Notes".to_string())
+        })
+    }
+
+    /// Generar el sitio en `output_dir`
+    pub fn publish(&self, output_dir: &Path) -> Result<PublishReport, PublishError> {
+        let folder = self.folder_path();
+        if !folder.is_dir() {
+            return Err(PublishError::FolderNotFound(folder));
+        }
+
+        let mut excluded = Vec::new();
+        let mut pages = Vec::new();
+        collect_pages(&folder, &folder, &mut pages, &mut excluded)?;
+        pages.sort_by_key(|page| page.name.to_lowercase());
+        excluded.sort();
+
+        let names: Vec<String> = pages.iter().map(|page| page.name.clone()).collect();
+        let backlinks = backlinks(&pages, &names);
+        let mut tags: BTreeMap<String, Vec<&Page>> = BTreeMap::new();
+        for page in &pages {
+            for tag in &page.tags {
+                tags.entry(tag.clone()).or_default().push(page);
+            }
+        }
+
+        std::fs::create_dir_all(output_dir)?;
+        let mut site = SiteOutput::new(output_dir);
+        let mut assets = HashMap::new();
+        let mut search = Vec::with_capacity(pages.len());
+
+        for page in &pages {
+            let mut footer = String::new();
+            if !page.tags.is_empty() {
+                let depth = page.name.matches('/').count();
+                let links: Vec<String> = page
+                    .tags
+                    .iter()
+                    .map(|tag| {
+                        format!(
+                            r#"<a class="tag-link" href="{}{}">#{}</a>"#,
+                            "../".repeat(depth),
+                            tag_url(tag),
+                            escape_html(tag)
+                        )
+                    })
+                    .collect();
+                footer.push_str(&format!("<p class=\"page-tags\">{}</p>\n", links.join(" ")));
+            }
+            if let Some(sources) = backlinks.get(&page.name) {
+                footer.push_str("<section class=\"backlinks\">\n<h2>Backlinks</h2>\n<ul>\n");
+                for source in sources {
+                    footer.push_str(&format!(
+                        "<li><a class=\"internal-link\" href=\"{}\">{}</a></li>\n",
+                        relative_url(&page.name, source),
+                        escape_html(source.rsplit('/').next().unwrap_or(source))
+                    ));
+                }
+                footer.push_str("</ul>\n</section>\n");
+            }
+
+            let markdown = link_mentions(&page.markdown, &names);
+            let html = self.page_html(&page.name, &page.title, &markdown, &footer, &names);
+            let html = copy_images(&html, &page.name, &mut site, &mut assets)?;
+            site.write_page(&page.name, &html)?;
+
+            search.push(SearchEntry {
+                title: &page.title,
+                url: page.url(),
+                tags: &page.tags,
+                date: page.date.format("%Y-%m-%d").to_string(),
+                text: truncate(&html_to_text(html_body(&html)), SEARCH_TEXT_LIMIT),
+            });
+        }
+
+        // Páginas de tags
+        let mut tag_index = String::from("# Tags\n\n");
+        for (tag, tagged) in &tags {
+            tag_index.push_str(&format!(
+                "- [#{}]({}.html) ({})\n",
+                tag,
+                href_encode(&tag_slug(tag)),
+                tagged.len()
+            ));
+            let list: String = tagged
+                .iter()
+                .map(|page| format!("- [[{}|{}]]\n", page.name, page.title))
+                .collect();
+            let name = format!("{}/{}", TAGS_DIR, tag_slug(tag));
+            let html = self.page_html(
+                &name,
+                &format!("#{}", tag),
+                &format!("# #{}\n\n{}", tag, list),
+                "",
+                &names,
+            );
+            site.write_page(&name, &html)?;
+        }
+        let name = format!("{}/index", TAGS_DIR);
+        let html = self.page_html(&name, "Tags", &tag_index, "", &names);
+        site.write_page(&name, &html)?;
+
+        // Portada: la nota `index` si existe; si no, la lista de notas por carpeta
+        if !names.iter().any(|name| name == "index") {
+            let html = self.page_html(
+                "index",
+                &self.site_title(),
+                &self.index_markdown(&pages),
+                "",
+                &names,
+            );
+            site.write_page("index", &html)?;
+        }
+
+        // Búsqueda
+        site.write(
+            &format!("{}/search-index.json", SITE_DIR),
+            serde_json::to_string(&search)?,
+        )?;
+        let html = self.page_html(
+            SEARCH_PAGE,
+            "Buscar",
+            "# Buscar\n\n<input id=\"search-input\" type=\"search\" autofocus>\n\n<ul id=\"search-results\"></ul>\n",
+            "",
+            &names,
+        );
+        let html = html.replacen(
+            "</body>",
+            &format!("<script>{}</script>\n</body>", SEARCH_JS),
+            1,
+        );
+        site.write_page(SEARCH_PAGE, &html)?;
+
+        site.write("feed.xml", self.feed_xml(&pages))?;
+        site.finish()?;
+
+        Ok(PublishReport {
+            output_dir: output_dir.to_path_buf(),
+            pages: pages.len(),
+            tags: tags.len(),
+            excluded,
+        })
+    }
+
+    /// Página completa del sitio: la nota renderizada con navegación y pie
+    fn page_html(
+        &self,
+        name: &str,
+        title: &str,
+        markdown: &str,
+        footer: &str,
+        names: &[String],
+    ) -> String {
+        let mut renderer =
+            HtmlRenderer::with_base_path(PreviewTheme::Print, self.notes_root.clone());
+        // Solo se transcluyen notas publicadas
+        let folder = self.folder_path();
+        renderer.set_embeddable(names.iter().map(|name| folder.join(format!("{}.md", name))));
+        let html = renderer.render(markdown);
+
+        let root = "../".repeat(name.matches('/').count());
+        let html = TAG_ANCHOR_RE.replace_all(&html, |caps: &regex::Captures| {
+            format!(
+                r#"<a class="tag-link" href="{}{}">"#,
+                root,
+                tag_url(&url_decode(&caps[1]))
+            )
+        });
+        let html = add_heading_ids(&rewrite_links(&html, name, names));
+
+        let site_title = escape_html(&self.site_title());
+        let head = format!(
+            "<title>{} · {}</title>\n    <link rel=\"alternate\" type=\"application/rss+xml\" title=\"{}\" href=\"{}feed.xml\">\n    <style>{}</style>\n</head>",
+            escape_html(title),
+            site_title,
+            site_title,
+            root,
+            SITE_CSS
+        );
+        let nav = format!(
+            "<nav class=\"site-nav\"><a class=\"site-title\" href=\"{root}index.html\">{site_title}</a><a href=\"{root}{TAGS_DIR}/index.html\">Tags</a><a href=\"{root}{SEARCH_PAGE}.html\">Buscar</a><a href=\"{root}feed.xml\">RSS</a></nav>"
+        );
+        let footer = if footer.is_empty() {
+            String::new()
+        } else {
+            format!("<footer class=\"site-footer\">\n{}</footer>\n", footer)
+        };
+
+        let html = html.replacen("</head>", &head, 1);
+        let html = BODY_OPEN_RE.replacen(&html, 1, |caps: &regex::Captures| {
+            format!("{}\n    {}", &caps[0], nav)
+        });
+        html.replacen("</body>", &format!("{}</body>", footer), 1)
+    }
+
+    /// Markdown de la portada generada
+    fn index_markdown(&self, pages: &[Page]) -> String {
+        let mut folders: BTreeMap<&str, Vec<&Page>> = BTreeMap::new();
+        for page in pages {
+            let folder = page.name.rsplit_once('/').map_or("", |(folder, _)| folder);
+            folders.entry(folder).or_default().push(page);
+        }
+        let mut markdown = format!("# {}\n", self.site_title());
+        for (folder, pages) in folders {
+            if !folder.is_empty() {
+                markdown.push_str(&format!("\n## {}\n", folder));
+            }
+            markdown.push('\n');
+            for page in pages {
+                markdown.push_str(&format!("- [[{}|{}]]\n", page.name, page.title));
+            }
+        }
+        markdown
+    }
+
+    /// Feed RSS 2.0 con las notas más recientes
+    fn feed_xml(&self, pages: &[Page]) -> String {
+        let base = self
+            .base_url
+            .as_ref()
+            .map(|url| format!("{}/", url))
+            .unwrap_or_default();
+        let mut recent: Vec<&Page> = pages.iter().collect();
+        recent.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.name.cmp(&b.name)));
+
+        let title = escape_html(&self.site_title());
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n<channel>\n<title>{}</title>\n<link>{}index.html</link>\n<description>{}</description>\n<lastBuildDate>{}</lastBuildDate>\n",
+            title,
+            escape_html(&base),
+            title,
+            Local::now().to_rfc2822()
+        );
+        for page in recent.into_iter().take(self.feed_limit) {
+            let link = escape_html(&format!("{}{}", base, page.url()));
+            let summary = truncate(
+                &html_to_text(&HtmlRenderer::new(PreviewTheme::Print).render_body(&page.markdown)),
+                FEED_SUMMARY_LIMIT,
+            );
+            xml.push_str(&format!(
+                "<item>\n<title>{}</title>\n<link>{}</link>\n<guid>{}</guid>\n<pubDate>{}</pubDate>\n",
+                escape_html(&page.title),
+                link,
+                link,
+                page.date.to_rfc2822()
+            ));
+            for tag in &page.tags {
+                xml.push_str(&format!("<category>{}</category>\n", escape_html(tag)));
+            }
+            xml.push_str(&format!(
+                "<description>{}</description>\n</item>\n",
+                escape_html(&summary)
+            ));
+        }
+        xml.push_str("</channel>\n</rss>\n");
+        xml
+    }
+}
+
+/// Leer las notas de `dir` (recursivo), separando las excluidas
+fn collect_pages(
+    root: &Path,
+    dir: &Path,
+    pages: &mut Vec<Page>,
+    excluded: &mut Vec<String>,
+) -> Result<(), PublishError> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect_pages(root, &path, pages, excluded)?;
+            continue;
+        }
+        if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            continue;
+        }
+
+        let name = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/");
+        let content = std::fs::read_to_string(&path)?;
+        let (frontmatter, body) = Frontmatter::parse_or_empty(&content);
+        let reserved = name.split('/').next() == Some(SITE_DIR);
+        if reserved || !is_published(&frontmatter) {
+            excluded.push(name);
+            continue;
+        }
+
+        let modified = std::fs::metadata(&path)
+            .and_then(|meta| meta.modified())
+            .map(DateTime::<Local>::from)
+            .unwrap_or_else(|_| Local::now());
+        let date = frontmatter
+            .date
+            .as_deref()
+            .and_then(parse_date)
+            .unwrap_or(modified);
+        let title = frontmatter
+            .title
+            .clone()
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| name.rsplit('/').next().unwrap_or(&name).to_string());
+
+        pages.push(Page {
+            name,
+            title,
+            tags: extract_all_tags(&content),
+            markdown: body,
+            date,
+        });
+    }
+    Ok(())
+}
+
+/// `publish: false` (o `"false"`/`"no"`) deja la nota fuera del sitio
+fn is_published(frontmatter: &Frontmatter) -> bool {
+    match frontmatter.custom.get("publish") {
+        Some(serde_yaml::Value::Bool(publish)) => *publish,
+        Some(serde_yaml::Value::String(value)) => {
+            !matches!(value.trim().to_lowercase().as_str(), "false" | "no")
+        }
+        _ => true,
+    }
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Local>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Local))
+        .ok()
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+            Local
+                .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+                .single()
+        })
+}
+
+/// Notas publicadas que enlazan (o mencionan) a cada nota
+fn backlinks(pages: &[Page], names: &[String]) -> HashMap<String, Vec<String>> {
+    let mut backlinks: HashMap<String, Vec<String>> = HashMap::new();
+    for page in pages {
+        let markdown = link_mentions(&page.markdown, names);
+        for (_, link) in find_links(&markdown) {
+            let Some(target) = resolve_exported(&link.note, names) else {
+                continue;
+            };
+            let sources = backlinks.entry(target.to_string()).or_default();
+            if target != page.name && !sources.contains(&page.name) {
+                sources.push(page.name.clone());
+            }
+        }
+    }
+    backlinks.retain(|_, sources| !sources.is_empty());
+    backlinks
+}
+
+/// `@Nota` → `[[Nota|@Nota]]` cuando la mención corresponde a una nota publicada.
+/// Como en el editor, la mención empieza tras un espacio, `(`, `[` o `,` y puede
+/// incluir espacios; se toma el prefijo más largo que sea una nota. El código
+/// (bloques y spans) se deja intacto.
+fn link_mentions(markdown: &str, names: &[String]) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut in_fence = false;
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            out.push_str(line);
+            continue;
+        }
+        if in_fence || !line.contains('@') {
+            out.push_str(line);
+            continue;
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut in_code = false;
+        let mut pos = 0;
+        while pos < chars.len() {
+            let ch = chars[pos];
+            if ch == '`' {
+                in_code = !in_code;
+            }
+            let starts_mention = ch == '@'
+                && !in_code
+                && (pos == 0 || matches!(chars[pos - 1], ' ' | '\t' | '(' | '[' | ','));
+            if !starts_mention {
+                out.push(ch);
+                pos += 1;
+                continue;
+            }
+
+            let mut end = pos + 1;
+            while end < chars.len()
+                && (chars[end].is_alphanumeric() || matches!(chars[end], '-' | '_' | ' ' | '/'))
+            {
+                end += 1;
+            }
+            // Prefijo más largo (acabado en fin de palabra) que sea una nota
+            let candidate: String = chars[pos + 1..end].iter().collect();
+            let found = candidate
+                .char_indices()
+                .filter(|(_, c)| *c == ' ')
+                .map(|(i, _)| i)
+                .chain(std::iter::once(candidate.len()))
+                .rev()
+                .map(|i| candidate[..i].trim_end())
+                .filter(|text| !text.is_empty())
+                .find_map(|text| resolve_exported(text, names).map(|name| (text, name)));
+            match found {
+                Some((text, name)) => {
+                    out.push_str(&format!("[[{}|@{}]]", name, text));
+                    pos += 1 + text.chars().count();
+                }
+                None => {
+                    out.push(ch);
+                    pos += 1;
+                }
+            }
+        }
+    }
+    out
+}
+
+fn tag_slug(tag: &str) -> String {
+    let slug = heading_id(tag.trim_start_matches('#'));
+    if slug.is_empty() {
+        "tag".to_string()
+    } else {
+        slug
+    }
+}
+
+fn tag_url(tag: &str) -> String {
+    format!("{}/{}.html", TAGS_DIR, href_encode(&tag_slug(tag)))
+}
+
+/// URL de la nota `target` vista desde la página `current`
+fn relative_url(current: &str, target: &str) -> String {
+    format!(
+        "{}{}.html",
+        "../".repeat(current.matches('/').count()),
+        href_encode(target)
+    )
+}
+
+/// Carpeta de salida que recuerda qué ficheros se escriben en ella
+struct SiteOutput {
+    dir: PathBuf,
+    written: Vec<String>,
+}
+
+impl SiteOutput {
+    fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            written: Vec::new(),
+        }
+    }
+
+    /// Escribir un fichero (`relative` con `/`, relativo a la carpeta de salida)
+    fn write(&mut self, relative: &str, contents: impl AsRef<[u8]>) -> Result<(), PublishError> {
+        let path = self.dir.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)?;
+        self.written.push(relative.to_string());
+        Ok(())
+    }
+
+    fn write_page(&mut self, name: &str, html: &str) -> Result<(), PublishError> {
+        self.write(&format!("{}.html", name), html)
+    }
+
+    fn copy(&mut self, source: &Path, relative: &str) -> std::io::Result<()> {
+        let target = self.dir.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(source, target)?;
+        self.written.push(relative.to_string());
+        Ok(())
+    }
+
+    /// Borrar lo que escribió la publicación anterior y esta no (notas renombradas,
+    /// despublicadas o tags que ya no se usan) y guardar el manifiesto nuevo.
+    /// Solo se borran ficheros del manifiesto: el resto de la carpeta no se toca.
+    fn finish(self) -> Result<(), PublishError> {
+        let manifest = self.dir.join(MANIFEST_FILE);
+        let previous = std::fs::read_to_string(&manifest).unwrap_or_default();
+        for stale in previous.lines() {
+            let inside = !stale.is_empty()
+                && Path::new(stale)
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)));
+            if !inside || self.written.iter().any(|written| written == stale) {
+                continue;
+            }
+            let path = self.dir.join(stale);
+            if std::fs::remove_file(&path).is_ok() {
+                // Quitar también las carpetas que se hayan quedado vacías
+                let mut dir = path.parent();
+                while let Some(parent) = dir.filter(|parent| *parent != self.dir) {
+                    if std::fs::remove_dir(parent).is_err() {
+                        break;
+                    }
+                    dir = parent.parent();
+                }
+            }
+        }
+
+        let mut written = self.written;
+        written.sort();
+        written.dedup();
+        std::fs::write(manifest, written.join("\n"))?;
+        Ok(())
+    }
+}
+
+/// Copiar las imágenes locales a `assets/` y apuntar a la copia
+fn copy_images(
+    html: &str,
+    current: &str,
+    site: &mut SiteOutput,
+    assets: &mut HashMap<PathBuf, String>,
+) -> Result<String, PublishError> {
+    let mut error = None;
+    let html = FILE_IMG_RE.replace_all(html, |caps: &regex::Captures| {
+        let raw = &caps[1];
+        let Some(source) = [PathBuf::from(raw), PathBuf::from(url_decode(raw))]
+            .into_iter()
+            .find(|path| path.is_file())
+        else {
+            return caps[0].to_string();
+        };
+        let file = match assets.get(&source) {
+            Some(file) => file.clone(),
+            None => {
+                let stem = source
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "image".to_string());
+                let mut file = stem.clone();
+                let mut n = 1;
+                while assets.values().any(|used| *used == file) {
+                    file = format!("{}-{}", n, stem);
+                    n += 1;
+                }
+                if let Err(e) = site.copy(&source, &format!("{}/{}", ASSETS_DIR, file)) {
+                    error.get_or_insert(e);
+                }
+                assets.insert(source, file.clone());
+                file
+            }
+        };
+        format!(
+            r#"<img src="{}{}/{}""#,
+            "../".repeat(current.matches('/').count()),
+            ASSETS_DIR,
+            href_encode(&file)
+        )
+    });
+    match error {
+        Some(e) => Err(e.into()),
+        None => Ok(html.to_string()),
+    }
+}
+
+/// Contenido del body sin la navegación ni el pie del sitio
+fn html_body(html: &str) -> &str {
+    let start = html.find("</nav>").map_or(0, |i| i + "</nav>".len());
+    let end = html
+        .find("<footer class=\"site-footer\">")
+        .or_else(|| html.rfind("</body>"))
+        .unwrap_or(html.len());
+    html.get(start..end).unwrap_or(html)
+}
+
+/// Texto plano de un fragmento HTML
+fn html_to_text(html: &str) -> String {
+    let text = TAG_STRIP_RE.replace_all(html, " ");
+    let text = text
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    WHITESPACE_RE.replace_all(&text, " ").trim().to_string()
+}
+
+fn truncate(text: &str, limit: usize) -> String {
+    match text.char_indices().nth(limit) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_mentions() {
+        let names = vec!["Onboarding".to_string(), "Equipo/Ana López".to_string()];
+        assert_eq!(
+            link_mentions("Ver @Onboarding y (@Ana López, hoy)", &names),
+            "Ver [[Onboarding|@Onboarding]] y ([[Equipo/Ana López|@Ana López]], hoy)"
+        );
+        assert_eq!(
+            link_mentions("correo ana@onboarding.com `@Onboarding` @Nadie", &names),
+            "correo ana@onboarding.com `@Onboarding` @Nadie"
+        );
+        assert_eq!(
+            link_mentions("```\n@Onboarding\n```\n", &names),
+            "```\n@Onboarding\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_publish_site() {
+        let root = std::env::temp_dir().join("notnative_publish_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let handbook = root.join("Handbook");
+        std::fs::create_dir_all(handbook.join("Guías")).unwrap();
+        std::fs::create_dir_all(root.join("img")).unwrap();
+        std::fs::write(root.join("img/logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        std::fs::write(
+            handbook.join("Bienvenida.md"),
+            "---\ntags: [equipo]\ndate: 2026-03-01\n---\n# Hola\n\nEmpieza por [[Onboarding#Primer día]] o pregunta a @Onboarding.\n\n![logo](img/logo.png)\n\n[[Secreto]]\n\n![[Secreto]]\n\n![[Onboarding#Primer día]]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            handbook.join("Guías/Onboarding.md"),
+            "---\ntitle: Guía de onboarding\ndate: 2026-04-01\n---\n## Primer día\n\nVuelve a [[Bienvenida]]. #equipo #proceso\n",
+        )
+        .unwrap();
+        std::fs::write(
+            handbook.join("Secreto.md"),
+            "---\npublish: false\n---\nNo publicar [[Bienvenida]]\n",
+        )
+        .unwrap();
+
+        let out = root.join("site");
+        let report = SitePublisher::new(&root, "Handbook")
+            .with_title(Some("Manual".to_string()))
+            .with_base_url(Some("https://example.com/manual/".to_string()))
+            .publish(&out)
+            .unwrap();
+        assert_eq!(report.pages, 2);
+        assert_eq!(report.tags, 2);
+        assert_eq!(report.excluded, vec!["Secreto".to_string()]);
+        assert!(!out.join("Secreto.html").exists());
+
+        let welcome = std::fs::read_to_string(out.join("Bienvenida.html")).unwrap();
+        assert!(welcome.contains("<title>Bienvenida · Manual</title>"));
+        assert!(
+            welcome
+                .contains(r##"<a href="Guías/Onboarding.html#primer-día" class="internal-link">"##)
+        );
+        assert!(
+            welcome.contains(
+                r#"<a href="Guías/Onboarding.html" class="internal-link">@Onboarding</a>"#
+            )
+        );
+        assert!(welcome.contains(r#"<a class="internal-link unresolved">Secreto</a>"#));
+        // Transcluir una nota sin publicar no filtra su contenido
+        assert!(!welcome.contains("No publicar"));
+        assert!(welcome.contains(r#"<p class="transclusion-missing">⚠ Secreto</p>"#));
+        assert!(welcome.contains("Vuelve a"));
+        assert!(welcome.contains(r#"<img src="assets/logo.png""#));
+        assert!(out.join("assets/logo.png").is_file());
+        assert!(
+            welcome.contains(r#"<a class="tag-link" href="_site/tags/equipo.html">#equipo</a>"#)
+        );
+        assert!(!welcome.contains("notifyRust"));
+        assert!(!welcome.contains("file://"));
+
+        let guide = std::fs::read_to_string(out.join("Guías/Onboarding.html")).unwrap();
+        assert!(guide.contains(r#"<a class="site-title" href="../index.html">Manual</a>"#));
+        assert!(guide.contains(r#"<h2 id="primer-día">Primer día</h2>"#));
+        assert!(guide.contains(r#"<a class="tag-link" href="../_site/tags/proceso.html">"#));
+        assert!(guide.contains("<h2>Backlinks</h2>"));
+        assert!(
+            guide.contains(r#"<a class="internal-link" href="../Bienvenida.html">Bienvenida</a>"#)
+        );
+
+        let tag = std::fs::read_to_string(out.join("_site/tags/equipo.html")).unwrap();
+        assert!(tag.contains(r#"href="../../Guías/Onboarding.html""#));
+        assert!(tag.contains(r#"href="../../Bienvenida.html""#));
+        assert!(out.join("_site/tags/index.html").is_file());
+        let index = std::fs::read_to_string(out.join("index.html")).unwrap();
+        assert!(index.contains("Guía de onboarding"));
+
+        let search: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(out.join("_site/search-index.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(search.as_array().unwrap().len(), 2);
+        assert_eq!(search[0]["url"], "Bienvenida.html");
+        assert!(search[0]["text"].as_str().unwrap().contains("Empieza por"));
+        assert!(!search[0]["text"].as_str().unwrap().contains("Backlinks"));
+        assert!(!search[0]["text"].as_str().unwrap().contains("No publicar"));
+        assert!(out.join("_site/search.html").is_file());
+
+        let feed = std::fs::read_to_string(out.join("feed.xml")).unwrap();
+        let first = feed.find("Guía de onboarding").unwrap();
+        let second = feed.find("<title>Bienvenida</title>").unwrap();
+        assert!(first < second);
+        assert!(feed.contains("<link>https://example.com/manual/Bienvenida.html</link>"));
+        assert!(!feed.contains("No publicar"));
+
+        // Notas llamadas como las páginas generadas: no se pisan
+        std::fs::create_dir_all(handbook.join("tags")).unwrap();
+        std::fs::write(handbook.join("search.md"), "# Mi búsqueda\n").unwrap();
+        std::fs::write(handbook.join("tags/index.md"), "# Mis tags\n").unwrap();
+        std::fs::create_dir_all(handbook.join("_site")).unwrap();
+        std::fs::write(handbook.join("_site/search.md"), "# Reservada\n").unwrap();
+
+        // Republicar sin una nota ni su tag borra sus páginas, pero nada ajeno
+        std::fs::remove_file(handbook.join("Guías/Onboarding.md")).unwrap();
+        std::fs::write(out.join("CNAME"), "example.com").unwrap();
+        let report = SitePublisher::new(&root, "Handbook").publish(&out).unwrap();
+        assert_eq!(report.excluded, vec!["Secreto", "_site/search"]);
+        assert!(!out.join("Guías/Onboarding.html").exists());
+        assert!(!out.join("Guías").exists());
+        assert!(!out.join("_site/tags/proceso.html").exists());
+        assert!(out.join("_site/tags/equipo.html").is_file());
+        assert!(out.join("CNAME").is_file());
+        assert!(out.join("assets/logo.png").is_file());
+        let search = std::fs::read_to_string(out.join("search.html")).unwrap();
+        assert!(search.contains("Mi búsqueda"));
+        let search = std::fs::read_to_string(out.join("_site/search.html")).unwrap();
+        assert!(search.contains("search-input"));
+        let tags = std::fs::read_to_string(out.join("tags/index.html")).unwrap();
+        assert!(tags.contains("Mis tags"));
+    }
+}
@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Registro sin nombre (`""`): destino de cualquier copia o borrado
+pub const UNNAMED_REGISTER: char = '"';
+/// Registro de la última copia (`"0`)
+pub const YANK_REGISTER: char = '0';
+/// Registro de borrados pequeños, dentro de una línea (`"-`)
+pub const SMALL_DELETE_REGISTER: char = '-';
+/// Registro agujero negro (`"_`): lo que se escribe en él se descarta
+pub const BLACK_HOLE_REGISTER: char = '_';
+
+/// Tamaño máximo (en bytes) de un registro que se guarda entre sesiones
+pub const MAX_PERSISTED_REGISTER_LEN: usize = 16 * 1024;
+
+/// Tecla Escape dentro de una macro grabada
+pub const MACRO_ESCAPE: char = '\u{1b}';
+
+/// Contenido de un registro vim
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Register {
+    pub text: String,
+    /// Si el texto son líneas completas (`yy`, `dd`): `p` pega debajo de la línea
+    #[serde(default)]
+    pub linewise: bool,
+}
+
+/// Registros vim: sin nombre, `0`, `1`-`9` (borrados), `-`, `a`-`z` y macros
+///
+/// Se serializa dentro de `NotesConfig` para conservar los registros entre sesiones.
+/// Los registros `+` y `*` (portapapeles del sistema) no se guardan aquí: los
+/// resuelve la aplicación.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Registers {
+    #[serde(default)]
+    slots: BTreeMap<char, Register>,
+    /// Última macro ejecutada (para `@@`)
+    #[serde(default)]
+    last_macro: Option<char>,
+}
+
+impl Registers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Si `name` es un registro que se puede usar tras `"`
+    pub fn is_valid_name(name: char) -> bool {
+        name.is_ascii_alphanumeric()
+            || matches!(
+                name,
+                UNNAMED_REGISTER | SMALL_DELETE_REGISTER | BLACK_HOLE_REGISTER
+            )
+            || Self::is_clipboard(name)
+    }
+
+    /// Si `name` es el portapapeles del sistema (`"+` o `"*`)
+    pub fn is_clipboard(name: char) -> bool {
+        matches!(name, '+' | '*')
+    }
+
+    /// Si `name` puede guardar una macro (`qa`...`qz`, `q0`...`q9`)
+    pub fn is_macro_name(name: char) -> bool {
+        name.is_ascii_alphanumeric()
+    }
+
+    /// Contenido de un registro (`A`-`Z` leen su equivalente en minúscula)
+    pub fn get(&self, name: char) -> Option<&Register> {
+        self.slots.get(&name.to_ascii_lowercase())
+    }
+
+    /// Registros con contenido, ordenados por nombre
+    pub fn iter(&self) -> impl Iterator<Item = (char, &Register)> {
+        self.slots.iter().map(|(name, register)| (*name, register))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Copia para guardar en la configuración: sin los registros de más de
+    /// [`MAX_PERSISTED_REGISTER_LEN`] bytes (p. ej. `dG` sobre una nota grande)
+    pub fn persisted(&self) -> Self {
+        Self {
+            slots: self
+                .slots
+                .iter()
+                .filter(|(_, register)| register.text.len() <= MAX_PERSISTED_REGISTER_LEN)
+                .map(|(name, register)| (*name, register.clone()))
+                .collect(),
+            last_macro: self.last_macro,
+        }
+    }
+
+    /// Guardar una copia (`y`): sin registro explícito va a `""` y `"0`
+    pub fn yank(&mut self, name: Option<char>, text: &str, linewise: bool) {
+        let register = Register {
+            text: text.to_string(),
+            linewise,
+        };
+        match name {
+            Some(BLACK_HOLE_REGISTER) => {}
+            Some(name) if name != UNNAMED_REGISTER => self.store_named(name, register),
+            _ => {
+                self.slots.insert(YANK_REGISTER, register.clone());
+                self.slots.insert(UNNAMED_REGISTER, register);
+            }
+        }
+    }
+
+    /// Guardar un borrado (`d`, `c`, `x`)
+    ///
+    /// Sin registro explícito, los borrados de varias líneas desplazan `"1`..`"9`
+    /// y los de dentro de una línea van a `"-`, como en vim.
+    pub fn delete(&mut self, name: Option<char>, text: &str, linewise: bool) {
+        let register = Register {
+            text: text.to_string(),
+            linewise,
+        };
+        match name {
+            Some(BLACK_HOLE_REGISTER) => {}
+            Some(name) if name != UNNAMED_REGISTER => self.store_named(name, register),
+            _ => {
+                if linewise || text.contains('\n') {
+                    for n in (1..9u32).rev() {
+                        let from = char::from_digit(n, 10).unwrap_or('1');
+                        let to = char::from_digit(n + 1, 10).unwrap_or('9');
+                        match self.slots.remove(&from) {
+                            Some(shifted) => self.slots.insert(to, shifted),
+                            None => self.slots.remove(&to),
+                        };
+                    }
+                    self.slots.insert('1', register.clone());
+                } else {
+                    self.slots.insert(SMALL_DELETE_REGISTER, register.clone());
+                }
+                self.slots.insert(UNNAMED_REGISTER, register);
+            }
+        }
+    }
+
+    /// Guardar las teclas de una macro grabada con `q{reg}`
+    pub fn set_macro(&mut self, name: char, keys: &str) {
+        if Self::is_macro_name(name) {
+            let lower = name.to_ascii_lowercase();
+            if name.is_ascii_uppercase() {
+                self.slots.entry(lower).or_default().text.push_str(keys);
+            } else {
+                self.slots.insert(
+                    lower,
+                    Register {
+                        text: keys.to_string(),
+                        linewise: false,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Teclas de la macro guardada en `name`, y recordarla para `@@`
+    pub fn take_macro(&mut self, name: char) -> Option<String> {
+        let keys = self.get(name)?.text.clone();
+        self.last_macro = Some(name.to_ascii_lowercase());
+        Some(keys)
+    }
+
+    /// Registro de la última macro ejecutada
+    pub fn last_macro(&self) -> Option<char> {
+        self.last_macro
+    }
+
+    /// Registro con nombre: en mayúscula se añade al final del contenido actual
+    fn store_named(&mut self, name: char, register: Register) {
+        if !name.is_ascii_alphanumeric() {
+            return;
+        }
+        let lower = name.to_ascii_lowercase();
+        let stored = if name.is_ascii_uppercase() {
+            match self.slots.remove(&lower) {
+                Some(mut existing) => {
+                    if (existing.linewise || register.linewise) && !existing.text.ends_with('\n') {
+                        existing.text.push('\n');
+                    }
+                    existing.text.push_str(&register.text);
+                    existing.linewise |= register.linewise;
+                    existing
+                }
+                None => register,
+            }
+        } else {
+            register
+        };
+        self.slots.insert(UNNAMED_REGISTER, stored.clone());
+        self.slots.insert(lower, stored);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yank_and_delete_registers() {
+        let mut registers = Registers::new();
+
+        registers.yank(None, "hola", false);
+        assert_eq!(registers.get('"').unwrap().text, "hola");
+        assert_eq!(registers.get('0').unwrap().text, "hola");
+
+        // Borrados pequeños van a "- y no tocan "0
+        registers.delete(None, "x", false);
+        assert_eq!(registers.get('-').unwrap().text, "x");
+        assert_eq!(registers.get('"').unwrap().text, "x");
+        assert_eq!(registers.get('0').unwrap().text, "hola");
+
+        // Los borrados de líneas se desplazan por "1.."9
+        registers.delete(None, "uno\n", true);
+        registers.delete(None, "dos\n", true);
+        assert_eq!(registers.get('1').unwrap().text, "dos\n");
+        assert_eq!(registers.get('2').unwrap().text, "uno\n");
+        assert!(registers.get('1').unwrap().linewise);
+
+        for i in 0..12 {
+            registers.delete(None, &format!("{i}\n"), true);
+        }
+        assert_eq!(registers.get('9').unwrap().text, "3\n");
+
+        // El agujero negro no modifica nada
+        registers.delete(Some('_'), "perdido", false);
+        assert_eq!(registers.get('"').unwrap().text, "11\n");
+    }
+
+    #[test]
+    fn test_named_registers_and_append() {
+        let mut registers = Registers::new();
+
+        registers.yank(Some('a'), "uno", false);
+        assert_eq!(registers.get('a').unwrap().text, "uno");
+        assert_eq!(registers.get('"').unwrap().text, "uno");
+        assert!(registers.get('0').is_none());
+
+        registers.yank(Some('A'), " dos", false);
+        assert_eq!(registers.get('a').unwrap().text, "uno dos");
+
+        // Añadir líneas a un registro de caracteres lo convierte en líneas
+        registers.yank(Some('A'), "tres\n", true);
+        let register = registers.get('A').unwrap();
+        assert_eq!(register.text, "uno dos\ntres\n");
+        assert!(register.linewise);
+    }
+
+    #[test]
+    fn test_macros_and_persistence() {
+        let mut registers = Registers::new();
+        registers.set_macro('q', "dwj");
+        registers.set_macro('Q', "x");
+        assert_eq!(registers.take_macro('q').as_deref(), Some("dwjx"));
+        assert_eq!(registers.last_macro(), Some('q'));
+        assert!(registers.take_macro('z').is_none());
+
+        registers.yank(None, "línea\n", true);
+        let json = serde_json::to_string(&registers).unwrap();
+        let restored: Registers = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, registers);
+
+        // Los registros enormes no se guardan entre sesiones
+        registers.delete(None, &"x\n".repeat(MAX_PERSISTED_REGISTER_LEN), true);
+        let persisted = registers.persisted();
+        assert!(persisted.get('1').is_none());
+        assert!(persisted.get('"').is_none());
+        assert_eq!(persisted.get('0').unwrap().text, "línea\n");
+        assert_eq!(persisted.get('q'), registers.get('q'));
+    }
+}
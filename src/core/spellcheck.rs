@@ -0,0 +1,920 @@
+//! Corrector ortográfico offline con diccionarios Hunspell (`.aff` + `.dic`)
+//!
+//! Se implementa el subconjunto de Hunspell que usan los diccionarios de
+//! LibreOffice para español e inglés:
+//!
+//! - reglas `PFX` / `SFX` con condición, producto cruzado y sufijos de dos niveles
+//!   (clases de continuación)
+//! - `FLAG` (`char`, `long`, `num`, `UTF-8`), alias `AF`, `NEEDAFFIX`, `FORBIDDENWORD`
+//! - `TRY` y `REP` para las sugerencias, `SET` (UTF-8 o ISO-8859)
+//!
+//! Las palabras compuestas (`COMPOUND*`) no se soportan.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use thiserror::Error;
+
+use super::notes_config::NotesConfig;
+
+/// Máximo de sugerencias que se ofrecen para una palabra
+pub const MAX_SUGGESTIONS: usize = 8;
+
+/// Errores al cargar diccionarios
+#[derive(Debug, Error)]
+pub enum SpellError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("No dictionary found for language: {0}")]
+    DictionaryNotFound(String),
+}
+
+pub type Result<T> = std::result::Result<T, SpellError>;
+
+type Flag = u32;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum FlagMode {
+    #[default]
+    Char,
+    Long,
+    Num,
+}
+
+/// Parte de la condición de un afijo (`[^aeiou]`, `.`, `y`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ConditionPart {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl ConditionPart {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ConditionPart::Any => true,
+            ConditionPart::Char(expected) => c == *expected,
+            ConditionPart::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Vec<ConditionPart>,
+    /// Flags de continuación (`SFX A 0 s/B .`): afijos que pueden añadirse después
+    continuation: Vec<Flag>,
+}
+
+impl Affix {
+    /// La condición se aplica al final de la raíz (sufijos) o al principio (prefijos)
+    fn condition_matches(&self, stem: &str, suffix: bool) -> bool {
+        let chars: Vec<char> = stem.chars().collect();
+        if chars.len() < self.condition.len() {
+            return false;
+        }
+        let offset = if suffix {
+            chars.len() - self.condition.len()
+        } else {
+            0
+        };
+        self.condition
+            .iter()
+            .zip(&chars[offset..])
+            .all(|(part, &c)| part.matches(c))
+    }
+}
+
+/// Diccionario Hunspell cargado en memoria
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: HashMap<String, Vec<Flag>>,
+    /// Prefijos y sufijos indexados por el texto que añaden
+    prefixes: HashMap<String, Vec<Affix>>,
+    suffixes: HashMap<String, Vec<Affix>>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+    need_affix: Option<Flag>,
+    forbidden: Option<Flag>,
+}
+
+impl Dictionary {
+    /// Cargar un diccionario desde sus archivos `.aff` y `.dic`
+    pub fn from_files(aff_path: &Path, dic_path: &Path) -> Result<Self> {
+        let aff = decode(&fs::read(aff_path)?, None);
+        let encoding = aff
+            .lines()
+            .find_map(|line| line.strip_prefix("SET "))
+            .map(|set| set.trim().to_uppercase());
+        let aff = match encoding.as_deref() {
+            Some("UTF-8") | None => aff,
+            _ => decode(&fs::read(aff_path)?, encoding.as_deref()),
+        };
+        let dic = decode(&fs::read(dic_path)?, encoding.as_deref());
+        Ok(Self::parse(&aff, &dic))
+    }
+
+    /// Interpretar el contenido de un `.aff` y un `.dic`
+    pub fn parse(aff: &str, dic: &str) -> Self {
+        let mut dictionary = Self::default();
+        let mut mode = FlagMode::default();
+        let mut aliases: Vec<Vec<Flag>> = Vec::new();
+
+        // Primera pasada: opciones que afectan a cómo se leen los flags
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", "long", ..] => mode = FlagMode::Long,
+                ["FLAG", "num", ..] => mode = FlagMode::Num,
+                ["AF", flags, ..] if !flags.chars().all(|c| c.is_ascii_digit()) => {
+                    aliases.push(parse_flags(flags, mode));
+                }
+                _ => {}
+            }
+        }
+        let flags_of = |text: &str| -> Vec<Flag> {
+            if !aliases.is_empty()
+                && let Ok(index) = text.parse::<usize>()
+            {
+                return aliases
+                    .get(index.wrapping_sub(1))
+                    .cloned()
+                    .unwrap_or_default();
+            }
+            parse_flags(text, mode)
+        };
+
+        let mut cross_products: HashMap<(bool, Flag), bool> = HashMap::new();
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["TRY", chars, ..] => dictionary.try_chars = chars.chars().collect(),
+                // La línea de cabecera (`REP 12`) solo tiene un campo
+                ["REP", from, to, ..] => dictionary
+                    .replacements
+                    .push((from.replace('_', " "), to.replace('_', " "))),
+                ["NEEDAFFIX", flag, ..] => dictionary.need_affix = flags_of(flag).first().copied(),
+                ["FORBIDDENWORD", flag, ..] => {
+                    dictionary.forbidden = flags_of(flag).first().copied()
+                }
+                [kind @ ("PFX" | "SFX"), flag, cross, count] if count.parse::<usize>().is_ok() => {
+                    if let Some(&flag) = flags_of(flag).first() {
+                        cross_products.insert((*kind == "SFX", flag), *cross == "Y");
+                    }
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let suffix = *kind == "SFX";
+                    let Some(&flag) = flags_of(flag).first() else {
+                        continue;
+                    };
+                    let (add, continuation) = match add.split_once('/') {
+                        Some((add, flags)) => (add, flags_of(flags)),
+                        None => (*add, Vec::new()),
+                    };
+                    let affix = Affix {
+                        flag,
+                        cross_product: cross_products
+                            .get(&(suffix, flag))
+                            .copied()
+                            .unwrap_or(false),
+                        strip: zero_as_empty(strip),
+                        add: zero_as_empty(add),
+                        condition: parse_condition(rest.first().copied().unwrap_or(".")),
+                        continuation,
+                    };
+                    let table = if suffix {
+                        &mut dictionary.suffixes
+                    } else {
+                        &mut dictionary.prefixes
+                    };
+                    table.entry(affix.add.clone()).or_default().push(affix);
+                }
+                _ => {}
+            }
+        }
+
+        // La primera línea del .dic es el número aproximado de entradas
+        for line in dic.lines().skip(1) {
+            let entry = line.split('\t').next().unwrap_or("").trim();
+            let entry = entry.split_whitespace().next().unwrap_or("");
+            if entry.is_empty() {
+                continue;
+            }
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => (word, flags_of(flags)),
+                None => (entry, Vec::new()),
+            };
+            dictionary
+                .words
+                .entry(word.to_string())
+                .or_default()
+                .extend(flags);
+        }
+        dictionary
+    }
+
+    /// Número de palabras base del diccionario
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Si la palabra está bien escrita (con las variantes de mayúsculas habituales)
+    pub fn check(&self, word: &str) -> bool {
+        if word.is_empty() || self.check_exact(word) {
+            return true;
+        }
+        let lower = word.to_lowercase();
+        if lower == word {
+            return false;
+        }
+        // "Casa" vale si existe "casa"; "CASA" también si existe "Casa" (nombres propios)
+        self.check_exact(&lower)
+            || (is_all_uppercase(word) && self.check_exact(&capitalize(&lower)))
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if self.forbidden.is_some_and(|f| flags.contains(&f)) {
+                return false;
+            }
+            if !self.need_affix.is_some_and(|f| flags.contains(&f)) {
+                return true;
+            }
+        }
+        self.check_suffixed(word, None) || self.check_prefixed(word)
+    }
+
+    /// Raíz válida para un afijo (y para el prefijo, si se combinan)
+    fn stem_has(&self, stem: &str, flag: Flag, prefix: Option<&Affix>) -> bool {
+        self.words.get(stem).is_some_and(|flags| {
+            flags.contains(&flag)
+                && prefix.is_none_or(|prefix| flags.contains(&prefix.flag))
+                && !self.forbidden.is_some_and(|f| flags.contains(&f))
+        })
+    }
+
+    fn check_suffixed(&self, word: &str, prefix: Option<&Affix>) -> bool {
+        for (stem_base, ending) in splits_from_end(word) {
+            let Some(affixes) = self.suffixes.get(ending) else {
+                continue;
+            };
+            for affix in affixes {
+                if prefix.is_some_and(|prefix| !(prefix.cross_product && affix.cross_product)) {
+                    continue;
+                }
+                let stem = format!("{}{}", stem_base, affix.strip);
+                if stem.is_empty() || !affix.condition_matches(&stem, true) {
+                    continue;
+                }
+                if self.stem_has(&stem, affix.flag, prefix)
+                    || (prefix.is_none() && self.check_inner_suffix(&stem, affix.flag))
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Segundo nivel de sufijos: `stem + interior + exterior`, donde el sufijo
+    /// interior permite continuar con el exterior
+    fn check_inner_suffix(&self, word: &str, outer: Flag) -> bool {
+        splits_from_end(word).any(|(stem_base, ending)| {
+            self.suffixes.get(ending).is_some_and(|affixes| {
+                affixes.iter().any(|affix| {
+                    let stem = format!("{}{}", stem_base, affix.strip);
+                    affix.continuation.contains(&outer)
+                        && !stem.is_empty()
+                        && affix.condition_matches(&stem, true)
+                        && self.stem_has(&stem, affix.flag, None)
+                })
+            })
+        })
+    }
+
+    fn check_prefixed(&self, word: &str) -> bool {
+        for (beginning, rest) in splits_from_start(word) {
+            let Some(affixes) = self.prefixes.get(beginning) else {
+                continue;
+            };
+            for affix in affixes {
+                let stem = format!("{}{}", affix.strip, rest);
+                if stem.is_empty() || !affix.condition_matches(&stem, false) {
+                    continue;
+                }
+                if self.stem_has(&stem, affix.flag, None)
+                    || (affix.cross_product && self.check_suffixed(&stem, Some(affix)))
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Sugerencias para una palabra mal escrita (REP, una edición de distancia y
+    /// división en dos palabras)
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let mut suggestions: Vec<String> = Vec::new();
+        let push = |candidate: String, suggestions: &mut Vec<String>| {
+            if candidate != word && !suggestions.contains(&candidate) {
+                suggestions.push(candidate);
+            }
+        };
+
+        for (from, to) in &self.replacements {
+            for (index, _) in word.match_indices(from.as_str()) {
+                let candidate = format!("{}{}{}", &word[..index], to, &word[index + from.len()..]);
+                if self.check_words(&candidate) {
+                    push(candidate, &mut suggestions);
+                }
+            }
+        }
+
+        let chars: Vec<char> = word.chars().collect();
+        let alphabet: Vec<char> = if self.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.try_chars.clone()
+        };
+        let mut candidates: Vec<String> = Vec::new();
+        for i in 0..chars.len() {
+            // Letras intercambiadas
+            if i + 1 < chars.len() {
+                let mut swapped = chars.clone();
+                swapped.swap(i, i + 1);
+                candidates.push(swapped.into_iter().collect());
+            }
+            // Letra incorrecta
+            for &c in &alphabet {
+                if c != chars[i] {
+                    let mut replaced = chars.clone();
+                    replaced[i] = c;
+                    candidates.push(replaced.into_iter().collect());
+                }
+            }
+        }
+        for i in 0..=chars.len() {
+            // Letra que falta
+            for &c in &alphabet {
+                let mut inserted = chars.clone();
+                inserted.insert(i, c);
+                candidates.push(inserted.into_iter().collect());
+            }
+            // Letra de más
+            if i < chars.len() {
+                let mut removed = chars.clone();
+                removed.remove(i);
+                candidates.push(removed.into_iter().collect());
+            }
+        }
+        for candidate in candidates {
+            if suggestions.len() >= MAX_SUGGESTIONS {
+                break;
+            }
+            if self.check(&candidate) {
+                push(candidate, &mut suggestions);
+            }
+        }
+
+        // Palabras pegadas
+        for i in 1..chars.len() {
+            let (left, right): (String, String) =
+                (chars[..i].iter().collect(), chars[i..].iter().collect());
+            if left.chars().count() > 1
+                && right.chars().count() > 1
+                && self.check(&left)
+                && self.check(&right)
+            {
+                push(format!("{} {}", left, right), &mut suggestions);
+            }
+        }
+
+        if is_capitalized(word) {
+            suggestions = suggestions.iter().map(|s| capitalize(s)).collect();
+            suggestions.dedup();
+        }
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    /// Comprobar un texto de varias palabras (candidatos de REP con espacios)
+    fn check_words(&self, text: &str) -> bool {
+        text.split(' ').all(|word| self.check(word))
+    }
+}
+
+/// Palabra mal escrita dentro de un texto (rango en caracteres, como los offsets de GTK)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    pub range: Range<usize>,
+    pub word: String,
+}
+
+/// Diccionarios por idioma (cargados al usarse) y diccionario personal
+#[derive(Debug, Default)]
+pub struct SpellChecker {
+    search_dirs: Vec<PathBuf>,
+    dictionaries: HashMap<String, Option<Dictionary>>,
+    personal_words: HashSet<String>,
+    personal_path: Option<PathBuf>,
+}
+
+impl SpellChecker {
+    pub fn new(search_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            search_dirs,
+            ..Self::default()
+        }
+    }
+
+    /// Carpetas donde se buscan diccionarios: la de la aplicación y las del sistema
+    pub fn default_search_dirs() -> Vec<PathBuf> {
+        vec![
+            NotesConfig::dictionaries_dir(),
+            PathBuf::from("/usr/share/hunspell"),
+            PathBuf::from("/usr/share/myspell"),
+            PathBuf::from("/usr/share/myspell/dicts"),
+        ]
+    }
+
+    /// Usar (y cargar) un diccionario personal: una palabra por línea
+    pub fn with_personal_dictionary(mut self, path: PathBuf) -> Self {
+        if let Ok(content) = fs::read_to_string(&path) {
+            self.personal_words = content
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        self.personal_path = Some(path);
+        self
+    }
+
+    /// Añadir un diccionario ya cargado (tests, diccionarios empaquetados)
+    pub fn insert_dictionary(&mut self, language: &str, dictionary: Dictionary) {
+        self.dictionaries
+            .insert(normalize_language(language), Some(dictionary));
+    }
+
+    /// Diccionario de un idioma (`es`, `en_US`, `es-MX`...), cargándolo la primera vez
+    pub fn dictionary(&mut self, language: &str) -> Option<&Dictionary> {
+        let language = normalize_language(language);
+        if !self.dictionaries.contains_key(&language) {
+            let loaded = find_dictionary(&self.search_dirs, &language)
+                .ok_or_else(|| SpellError::DictionaryNotFound(language.clone()))
+                .and_then(|(aff, dic)| Dictionary::from_files(&aff, &dic));
+            let dictionary = match loaded {
+                Ok(dictionary) => Some(dictionary),
+                Err(e) => {
+                    eprintln!("Corrector ortográfico: {}", e);
+                    None
+                }
+            };
+            self.dictionaries.insert(language.clone(), dictionary);
+        }
+        self.dictionaries.get(&language)?.as_ref()
+    }
+
+    fn is_personal(&self, word: &str) -> bool {
+        self.personal_words.contains(word) || self.personal_words.contains(&word.to_lowercase())
+    }
+
+    /// Palabras mal escritas del texto (vacío si no hay diccionario para el idioma)
+    pub fn misspellings(&mut self, text: &str, language: &str) -> Vec<Misspelling> {
+        if self.dictionary(language).is_none() {
+            return Vec::new();
+        }
+        let words = checkable_words(text);
+        let dictionary = self.dictionary(language).expect("cargado arriba");
+        let misspelled: Vec<Misspelling> = words
+            .into_iter()
+            .filter(|(_, word)| !dictionary.check(word))
+            .map(|(range, word)| Misspelling { range, word })
+            .collect();
+        misspelled
+            .into_iter()
+            .filter(|misspelling| !self.is_personal(&misspelling.word))
+            .collect()
+    }
+
+    pub fn suggest(&mut self, word: &str, language: &str) -> Vec<String> {
+        self.dictionary(language)
+            .map(|dictionary| dictionary.suggest(word))
+            .unwrap_or_default()
+    }
+
+    /// Añadir una palabra al diccionario personal (y a su archivo, si lo hay)
+    pub fn add_to_personal(&mut self, word: &str) -> Result<()> {
+        if !self.personal_words.insert(word.to_string()) {
+            return Ok(());
+        }
+        if let Some(path) = &self.personal_path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            writeln!(file, "{}", word)?;
+        }
+        Ok(())
+    }
+}
+
+/// Buscar `{idioma}.aff` / `.dic`; sin región se prueba la habitual (`es` → `es_ES`)
+/// y luego cualquier otra (`es_MX`)
+pub fn find_dictionary(dirs: &[PathBuf], language: &str) -> Option<(PathBuf, PathBuf)> {
+    let language = normalize_language(language);
+    let mut names = vec![language.clone()];
+    if !language.contains('_') {
+        names.push(format!("{}_{}", language, language.to_uppercase()));
+        if language == "en" {
+            names.push("en_US".to_string());
+        }
+    }
+
+    for dir in dirs {
+        for name in &names {
+            let aff = dir.join(format!("{}.aff", name));
+            let dic = dir.join(format!("{}.dic", name));
+            if aff.is_file() && dic.is_file() {
+                return Some((aff, dic));
+            }
+        }
+    }
+
+    // Cualquier variante regional del idioma
+    let base = language.split('_').next().unwrap_or(&language).to_string();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut candidates: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == "dic")
+                    && path.file_stem().is_some_and(|stem| {
+                        stem.to_string_lossy().starts_with(&format!("{}_", base))
+                    })
+                    && path.with_extension("aff").is_file()
+            })
+            .collect();
+        candidates.sort();
+        if let Some(dic) = candidates.into_iter().next() {
+            return Some((dic.with_extension("aff"), dic));
+        }
+    }
+    None
+}
+
+/// `es-MX` → `es_MX`
+fn normalize_language(language: &str) -> String {
+    language.trim().replace('-', "_")
+}
+
+/// Idioma de la nota según la clave `lang` del frontmatter
+pub fn note_language(text: &str) -> Option<String> {
+    let (frontmatter, _) = super::frontmatter::Frontmatter::parse_or_empty(text);
+    frontmatter
+        .custom
+        .get("lang")
+        .and_then(|value| value.as_str())
+        .map(normalize_language)
+        .filter(|lang| !lang.is_empty())
+}
+
+static WORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\p{L}\p{M}\p{N}_]+(?:['’][\p{L}\p{M}]+)*").unwrap());
+
+/// Fragmentos que no se revisan: código inline, enlaces wiki, destinos de enlaces,
+/// URLs, emails, HTML y propiedades inline
+static IGNORED_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"`[^`\n]+`",
+        r"|\[\[[^\]\n]*\]\]",
+        r"|\]\([^)\n]*\)",
+        r"|\[[^\[\]\n]*::[^\[\]\n]*\]",
+        r"|(?:https?|ftp|file)://\S+|www\.\S+",
+        r"|[\w.+-]+@[\w-]+\.[\w.-]+",
+        r"|</?[a-zA-Z][^>\n]*>",
+    ))
+    .unwrap()
+});
+
+/// `#tag` y `@Nota` (el carácter anterior se comprueba a mano)
+static TAG_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[#@][\p{L}\p{N}_][\p{L}\p{N}_/-]*").unwrap());
+
+/// Palabras que hay que revisar, con su rango en caracteres
+///
+/// Se saltan el frontmatter, los bloques de código, el código inline, los enlaces
+/// (solo su destino en `[texto](url)`), los tags, las propiedades inline, las
+/// palabras con números y las siglas en mayúsculas.
+pub fn checkable_words(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut ignored: Vec<Range<usize>> = Vec::new();
+
+    // Frontmatter y bloques de código, por líneas
+    let mut offset = 0;
+    let mut block_start: Option<usize> = None;
+    let mut in_frontmatter = text.starts_with("---\n") || text.starts_with("---\r\n");
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        if in_frontmatter {
+            if index > 0 && (trimmed == "---" || trimmed == "...") {
+                ignored.push(0..offset + line.len());
+                in_frontmatter = false;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            match block_start.take() {
+                Some(start) => ignored.push(start..offset + line.len()),
+                None => block_start = Some(offset),
+            }
+        }
+        offset += line.len();
+    }
+    if let Some(start) = block_start {
+        ignored.push(start..text.len());
+    }
+
+    ignored.extend(IGNORED_REGEX.find_iter(text).map(|m| m.range()));
+    ignored.extend(
+        TAG_REGEX
+            .find_iter(text)
+            .filter(|m| {
+                text[..m.start()]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|c| !c.is_alphanumeric() && c != '&')
+            })
+            .map(|m| m.range()),
+    );
+
+    let mut words = Vec::new();
+    let mut char_index = 0;
+    let mut byte_index = 0;
+    for m in WORD_REGEX.find_iter(text) {
+        char_index += text[byte_index..m.start()].chars().count();
+        byte_index = m.start();
+        let word = m.as_str();
+        let length = word.chars().count();
+        let start = char_index;
+
+        let skip = ignored
+            .iter()
+            .any(|range| range.start < m.end() && m.start() < range.end)
+            || length < 2
+            || word.chars().any(|c| c.is_numeric() || c == '_')
+            || is_all_uppercase(word);
+        if !skip {
+            words.push((start..start + length, word.replace('’', "'")));
+        }
+    }
+    words
+}
+
+fn parse_flags(text: &str, mode: FlagMode) -> Vec<Flag> {
+    match mode {
+        FlagMode::Char => text.chars().map(|c| c as Flag).collect(),
+        FlagMode::Long => text
+            .chars()
+            .collect::<Vec<char>>()
+            .chunks(2)
+            .map(|pair| pair.iter().fold(0, |acc, &c| (acc << 16) | c as Flag))
+            .collect(),
+        FlagMode::Num => text
+            .split(',')
+            .filter_map(|number| number.trim().parse().ok())
+            .collect(),
+    }
+}
+
+fn parse_condition(condition: &str) -> Vec<ConditionPart> {
+    if condition == "." {
+        return Vec::new();
+    }
+    let mut parts = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(c) = chars.next() {
+        parts.push(match c {
+            '.' => ConditionPart::Any,
+            '[' => {
+                let mut set: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                let negated = set.first() == Some(&'^');
+                if negated {
+                    set.remove(0);
+                }
+                ConditionPart::Set {
+                    chars: set,
+                    negated,
+                }
+            }
+            c => ConditionPart::Char(c),
+        });
+    }
+    parts
+}
+
+fn zero_as_empty(text: &str) -> String {
+    if text == "0" {
+        String::new()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Decodificar un archivo de diccionario (UTF-8 o, con `SET ISO8859-*`, Latin-1)
+fn decode(bytes: &[u8], encoding: Option<&str>) -> String {
+    match encoding {
+        Some(set) if set.starts_with("ISO8859") || set.starts_with("ISO-8859") => {
+            bytes.iter().map(|&b| b as char).collect()
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Divisiones `(inicio, final)` de una palabra, del final vacío al completo
+fn splits_from_end(word: &str) -> impl Iterator<Item = (&str, &str)> {
+    word.char_indices()
+        .map(|(i, _)| i)
+        .chain([word.len()])
+        .rev()
+        .map(move |i| (&word[..i], &word[i..]))
+}
+
+/// Divisiones `(inicio, resto)` con un inicio de al menos un carácter
+fn splits_from_start(word: &str) -> impl Iterator<Item = (&str, &str)> {
+    word.char_indices()
+        .map(|(i, _)| i)
+        .skip(1)
+        .chain([word.len()])
+        .map(move |i| (&word[..i], &word[i..]))
+}
+
+fn is_capitalized(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_uppercase)
+}
+
+fn is_all_uppercase(word: &str) -> bool {
+    word.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && word
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .all(char::is_uppercase)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwzáéíóúñ
+REP 1
+REP v b
+FORBIDDENWORD !
+
+SFX S Y 2
+SFX S 0 s [aeiou]
+SFX S 0 es [^aeiou]
+
+SFX M Y 1
+SFX M o a/S o
+
+PFX R Y 1
+PFX R 0 re .
+";
+
+    const DIC: &str = "6
+casa/S
+canción/S
+gato/MS
+hacer/R
+bien
+malo/!
+";
+
+    #[test]
+    fn test_affixes() {
+        let dictionary = Dictionary::parse(AFF, DIC);
+        assert_eq!(dictionary.len(), 6);
+
+        for word in [
+            "casa",
+            "casas",
+            "canciónes",
+            "gatos",
+            "gata",
+            "gatas",
+            "rehacer",
+            "Casa",
+            "BIEN",
+        ] {
+            assert!(dictionary.check(word), "{} debería ser correcta", word);
+        }
+        // Condición [^aeiou]: "casaes" no vale; "malo" está prohibida
+        for word in ["casaes", "caza", "malo", "rebien", "bienes"] {
+            assert!(!dictionary.check(word), "{} debería ser incorrecta", word);
+        }
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let dictionary = Dictionary::parse(AFF, DIC);
+        assert_eq!(
+            dictionary.suggest("caas").first().map(String::as_str),
+            Some("casa")
+        );
+        assert!(dictionary.suggest("gatso").contains(&"gatos".to_string()));
+        assert!(dictionary.suggest("Cassa").contains(&"Casa".to_string()));
+        assert!(
+            dictionary
+                .suggest("casabien")
+                .contains(&"casa bien".to_string())
+        );
+    }
+
+    #[test]
+    fn test_checkable_words() {
+        let text = "---\nlang: es\n---\n# Título\nUna palabra #etiqueta y [[Nota Wiki]] con \
+                    [enlace](https://ejemplo.com/ruta) y `codigo` [estado::pendiente] NASA 2do.\n\
+                    ```\nfn main\n```\nfin l'amour";
+        let words: Vec<String> = checkable_words(text)
+            .into_iter()
+            .map(|(_, word)| word)
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                "Título", "Una", "palabra", "con", "enlace", "fin", "l'amour"
+            ]
+        );
+
+        // Los rangos están en caracteres, no en bytes
+        let ranges = checkable_words("ñandú café");
+        assert_eq!(ranges[1].0, 6..10);
+    }
+
+    #[test]
+    fn test_spell_checker() {
+        let dir = std::env::temp_dir().join("notnative_spell_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("es_ES.aff"), AFF).unwrap();
+        fs::write(dir.join("es_ES.dic"), DIC).unwrap();
+
+        assert!(find_dictionary(std::slice::from_ref(&dir), "es").is_some());
+        assert!(find_dictionary(std::slice::from_ref(&dir), "es-ES").is_some());
+        assert!(find_dictionary(std::slice::from_ref(&dir), "en").is_none());
+
+        let mut checker =
+            SpellChecker::new(vec![dir.clone()]).with_personal_dictionary(dir.join("personal.txt"));
+        let misspelled = checker.misspellings("casas y gatoz", "es");
+        assert_eq!(
+            misspelled,
+            vec![Misspelling {
+                range: 8..13,
+                word: "gatoz".into()
+            }]
+        );
+        // Sin diccionario no se marca nada
+        assert!(checker.misspellings("gatoz", "en").is_empty());
+
+        checker.add_to_personal("gatoz").unwrap();
+        assert!(checker.misspellings("casas y gatoz", "es").is_empty());
+        let reloaded =
+            SpellChecker::new(vec![dir.clone()]).with_personal_dictionary(dir.join("personal.txt"));
+        assert!(reloaded.is_personal("gatoz"));
+
+        assert_eq!(
+            note_language("---\nlang: en-US\n---\nHello").as_deref(),
+            Some("en_US")
+        );
+        assert_eq!(note_language("Sin frontmatter"), None);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,647 @@
+//! Edición de tablas GFM dentro de una nota
+//!
+//! - `MarkdownTable`: celdas y alineaciones de una tabla, con formateo alineado
+//!   (los anchos se miden en columnas de pantalla, así que CJK y emoji cuentan doble)
+//! - `find_table`: la tabla que contiene una línea y la celda bajo el cursor
+//! - `edit_table` / `move_to_cell`: operaciones de filas y columnas y Tab / Shift-Tab
+
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use thiserror::Error;
+
+/// Errores al editar una tabla
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum TableError {
+    #[error("Cursor is not inside a table")]
+    NotInTable,
+    #[error("The header row cannot be deleted or moved")]
+    HeaderRow,
+    #[error("A table needs at least one column")]
+    LastColumn,
+}
+
+pub type Result<T> = std::result::Result<T, TableError>;
+
+/// Alineación de una columna (fila separadora `:---`, `:---:`, `---:`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColumnAlignment {
+    #[default]
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// Operaciones sobre la tabla bajo el cursor (`:table ...`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableEdit {
+    /// Solo realinear las columnas
+    Format,
+    InsertRow {
+        above: bool,
+    },
+    DeleteRow,
+    MoveRow {
+        up: bool,
+    },
+    InsertColumn {
+        before: bool,
+    },
+    DeleteColumn,
+    MoveColumn {
+        left: bool,
+    },
+    /// Ordenar las filas por la columna del cursor (numérico si todos son números)
+    Sort {
+        descending: bool,
+    },
+    Align(ColumnAlignment),
+}
+
+/// Tabla GFM: cabecera, alineaciones y filas del cuerpo
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarkdownTable {
+    pub header: Vec<String>,
+    pub alignments: Vec<ColumnAlignment>,
+    pub rows: Vec<Vec<String>>,
+    /// Sangría de la primera línea, que se conserva al formatear
+    indent: String,
+}
+
+/// Tabla encontrada en un texto y celda bajo el cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableLocation {
+    pub table: MarkdownTable,
+    /// Líneas que ocupa la tabla (cabecera y separador incluidos)
+    pub lines: Range<usize>,
+    /// Fila lógica: 0 = cabecera, 1.. = filas del cuerpo
+    pub row: usize,
+    pub column: usize,
+}
+
+/// Texto tras editar una tabla y posición del cursor (línea y carácter)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableEditResult {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl MarkdownTable {
+    /// Interpretar las líneas de una tabla (cabecera, separador y filas)
+    pub fn parse(lines: &[&str]) -> Option<Self> {
+        let (header, rest) = lines.split_first()?;
+        let (separator, body) = rest.split_first()?;
+        let alignments = parse_separator(separator)?;
+
+        let indent: String = header.chars().take_while(|c| c.is_whitespace()).collect();
+        let mut table = Self {
+            header: split_row(header),
+            alignments,
+            rows: body.iter().map(|line| split_row(line)).collect(),
+            indent,
+        };
+        table.normalize();
+        Some(table)
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.header.len()
+    }
+
+    /// Igualar el número de celdas de todas las filas
+    fn normalize(&mut self) {
+        let columns = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .chain([self.header.len(), self.alignments.len(), 1])
+            .max()
+            .unwrap_or(1);
+        self.header.resize(columns, String::new());
+        self.alignments.resize(columns, ColumnAlignment::None);
+        for row in &mut self.rows {
+            row.resize(columns, String::new());
+        }
+    }
+
+    /// Líneas de la tabla con las columnas alineadas
+    pub fn to_lines(&self) -> Vec<String> {
+        let widths: Vec<usize> = (0..self.column_count())
+            .map(|column| {
+                std::iter::once(&self.header)
+                    .chain(&self.rows)
+                    .map(|row| display_width(&row[column]))
+                    .max()
+                    .unwrap_or(0)
+                    .max(3)
+            })
+            .collect();
+
+        let format_row = |cells: &[String]| {
+            let cells: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .zip(&self.alignments)
+                .map(|((cell, &width), &alignment)| pad_cell(cell, width, alignment))
+                .collect();
+            format!("{}| {} |", self.indent, cells.join(" | "))
+        };
+
+        let separator: Vec<String> = widths
+            .iter()
+            .zip(&self.alignments)
+            .map(|(&width, alignment)| match alignment {
+                ColumnAlignment::None => "-".repeat(width),
+                ColumnAlignment::Left => format!(":{}", "-".repeat(width - 1)),
+                ColumnAlignment::Center => format!(":{}:", "-".repeat(width - 2)),
+                ColumnAlignment::Right => format!("{}:", "-".repeat(width - 1)),
+            })
+            .collect();
+
+        let mut lines = vec![
+            format_row(&self.header),
+            format!("{}| {} |", self.indent, separator.join(" | ")),
+        ];
+        lines.extend(self.rows.iter().map(|row| format_row(row)));
+        lines
+    }
+
+    /// Aplicar una operación con el cursor en `(row, column)`; devuelve la nueva celda
+    pub fn apply(&mut self, edit: TableEdit, row: usize, column: usize) -> Result<(usize, usize)> {
+        let columns = self.column_count();
+        match edit {
+            TableEdit::Format => Ok((row, column)),
+            TableEdit::InsertRow { above } => {
+                if above && row == 0 {
+                    return Err(TableError::HeaderRow);
+                }
+                let index = if above { row } else { row + 1 };
+                self.rows.insert(index - 1, vec![String::new(); columns]);
+                Ok((index, column))
+            }
+            TableEdit::DeleteRow => {
+                if row == 0 {
+                    return Err(TableError::HeaderRow);
+                }
+                self.rows.remove(row - 1);
+                Ok((row.min(self.rows.len()), column))
+            }
+            TableEdit::MoveRow { up } => {
+                if row == 0 {
+                    return Err(TableError::HeaderRow);
+                }
+                let target = if up { row - 1 } else { row + 1 };
+                if target == 0 || target > self.rows.len() {
+                    return Ok((row, column));
+                }
+                self.rows.swap(row - 1, target - 1);
+                Ok((target, column))
+            }
+            TableEdit::InsertColumn { before } => {
+                let index = if before { column } else { column + 1 };
+                self.header.insert(index, String::new());
+                self.alignments.insert(index, ColumnAlignment::None);
+                for cells in &mut self.rows {
+                    cells.insert(index, String::new());
+                }
+                Ok((row, index))
+            }
+            TableEdit::DeleteColumn => {
+                if columns == 1 {
+                    return Err(TableError::LastColumn);
+                }
+                self.header.remove(column);
+                self.alignments.remove(column);
+                for cells in &mut self.rows {
+                    cells.remove(column);
+                }
+                Ok((row, column.min(columns - 2)))
+            }
+            TableEdit::MoveColumn { left } => {
+                let target = if left {
+                    column.checked_sub(1)
+                } else {
+                    Some(column + 1).filter(|&target| target < columns)
+                };
+                let Some(target) = target else {
+                    return Ok((row, column));
+                };
+                self.header.swap(column, target);
+                self.alignments.swap(column, target);
+                for cells in &mut self.rows {
+                    cells.swap(column, target);
+                }
+                Ok((row, target))
+            }
+            TableEdit::Sort { descending } => {
+                let numeric = self
+                    .rows
+                    .iter()
+                    .all(|cells| parse_number(&cells[column]).is_some());
+                self.rows.sort_by(|a, b| {
+                    let ordering = if numeric {
+                        let (a, b) = (parse_number(&a[column]), parse_number(&b[column]));
+                        a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+                    } else {
+                        a[column].to_lowercase().cmp(&b[column].to_lowercase())
+                    };
+                    if descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                });
+                Ok((row, column))
+            }
+            TableEdit::Align(alignment) => {
+                self.alignments[column] = alignment;
+                Ok((row, column))
+            }
+        }
+    }
+
+    /// Línea (relativa al inicio de la tabla) de una fila lógica
+    fn line_of_row(row: usize) -> usize {
+        if row == 0 { 0 } else { row + 1 }
+    }
+}
+
+/// Buscar la tabla que contiene la posición `(line, column)` del texto
+pub fn find_table(text: &str, line: usize, column: usize) -> Option<TableLocation> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    if line >= lines.len() || !is_table_line(lines[line]) || in_code_block(&lines, line) {
+        return None;
+    }
+
+    let start = (0..=line)
+        .rev()
+        .take_while(|&i| is_table_line(lines[i]))
+        .last()?;
+    let end = (line..lines.len())
+        .take_while(|&i| is_table_line(lines[i]))
+        .last()?
+        + 1;
+    let table = MarkdownTable::parse(&lines[start..end])?;
+
+    // El separador cuenta como cabecera
+    let row = match line - start {
+        0 | 1 => 0,
+        offset => offset - 1,
+    };
+    let column = cell_index(lines[line], column).min(table.column_count() - 1);
+    Some(TableLocation {
+        table,
+        lines: start..end,
+        row,
+        column,
+    })
+}
+
+/// Aplicar una operación a la tabla bajo el cursor y reformatearla
+pub fn edit_table(
+    text: &str,
+    line: usize,
+    column: usize,
+    edit: TableEdit,
+) -> Result<TableEditResult> {
+    let mut location = find_table(text, line, column).ok_or(TableError::NotInTable)?;
+    let (row, column) = location.table.apply(edit, location.row, location.column)?;
+    Ok(replace_table(text, &location, row, column))
+}
+
+/// Tab / Shift-Tab: reformatear la tabla y pasar a la celda siguiente o anterior
+///
+/// Con Tab en la última celda se añade una fila nueva. Devuelve `None` si el
+/// cursor no está en una tabla.
+pub fn move_to_cell(
+    text: &str,
+    line: usize,
+    column: usize,
+    backwards: bool,
+) -> Option<TableEditResult> {
+    let mut location = find_table(text, line, column)?;
+    let columns = location.table.column_count();
+    let (row, column) = (location.row, location.column);
+
+    let target = if backwards {
+        match (row, column) {
+            (0, 0) => (0, 0),
+            (row, 0) => (row - 1, columns - 1),
+            (row, column) => (row, column - 1),
+        }
+    } else if column + 1 < columns {
+        (row, column + 1)
+    } else {
+        if row == location.table.rows.len() {
+            location.table.rows.push(vec![String::new(); columns]);
+        }
+        (row + 1, 0)
+    };
+    Some(replace_table(text, &location, target.0, target.1))
+}
+
+/// Sustituir la tabla en el texto y calcular dónde queda la celda `(row, column)`
+fn replace_table(
+    text: &str,
+    location: &TableLocation,
+    row: usize,
+    column: usize,
+) -> TableEditResult {
+    let formatted = location.table.to_lines();
+    let line_offset = MarkdownTable::line_of_row(row);
+    let cursor_column = cell_start(&formatted[line_offset], column);
+
+    let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+    lines.splice(location.lines.clone(), formatted);
+    TableEditResult {
+        text: lines.join("\n"),
+        line: location.lines.start + line_offset,
+        column: cursor_column,
+    }
+}
+
+/// Ancho en columnas de pantalla (sin marcas combinantes, CJK y emoji a doble ancho)
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        // Marcas combinantes, espacios de ancho cero y selectores de variante
+        0x0300..=0x036F
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200B..=0x200F
+        | 0x20D0..=0x20FF
+        | 0x2060..=0x2064
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F
+        | 0xE0100..=0xE01EF => 0,
+        // Hangul, CJK, formas de ancho completo y emoji
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F680..=0x1F6FF
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+fn pad_cell(cell: &str, width: usize, alignment: ColumnAlignment) -> String {
+    let padding = width.saturating_sub(display_width(cell));
+    match alignment {
+        ColumnAlignment::Right => format!("{}{}", " ".repeat(padding), cell),
+        ColumnAlignment::Center => {
+            let left = padding / 2;
+            format!("{}{}{}", " ".repeat(left), cell, " ".repeat(padding - left))
+        }
+        ColumnAlignment::None | ColumnAlignment::Left => format!("{}{}", cell, " ".repeat(padding)),
+    }
+}
+
+/// Posiciones (en caracteres) de las barras `|` que separan celdas
+///
+/// Como en GFM, solo las escapadas (`\|`) no separan, incluso dentro de código inline.
+fn cell_separators(line: &str) -> Vec<usize> {
+    let mut separators = Vec::new();
+    let mut escaped = false;
+    for (i, c) in line.chars().enumerate() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '|' => separators.push(i),
+            _ => {}
+        }
+    }
+    separators
+}
+
+fn split_row(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut bounds = cell_separators(line);
+    let first = chars.iter().position(|c| !c.is_whitespace()).unwrap_or(0);
+    let last = chars.iter().rposition(|c| !c.is_whitespace()).unwrap_or(0);
+
+    // Las barras de los extremos son opcionales
+    let start = match bounds.first() {
+        Some(&i) if i == first => {
+            bounds.remove(0);
+            i + 1
+        }
+        _ => 0,
+    };
+    let end = match bounds.last() {
+        Some(&i) if i == last && i >= start => {
+            bounds.pop();
+            i
+        }
+        _ => chars.len(),
+    };
+
+    let mut cells = Vec::new();
+    let mut cell_start = start;
+    for bound in bounds.into_iter().chain([end]) {
+        let cell: String = chars[cell_start..bound.max(cell_start)].iter().collect();
+        cells.push(cell.trim().to_string());
+        cell_start = bound + 1;
+    }
+    cells
+}
+
+fn parse_separator(line: &str) -> Option<Vec<ColumnAlignment>> {
+    if !line.contains('-') {
+        return None;
+    }
+    split_row(line)
+        .iter()
+        .map(|cell| {
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => ColumnAlignment::Center,
+                (true, false) => ColumnAlignment::Left,
+                (false, true) => ColumnAlignment::Right,
+                (false, false) => ColumnAlignment::None,
+            })
+        })
+        .collect()
+}
+
+fn is_table_line(line: &str) -> bool {
+    !line.trim().is_empty() && !cell_separators(line).is_empty()
+}
+
+/// Si la línea está dentro de un bloque de código con vallas
+fn in_code_block(lines: &[&str], line: usize) -> bool {
+    lines[..line]
+        .iter()
+        .filter(|l| {
+            let l = l.trim_start();
+            l.starts_with("```") || l.starts_with("~~~")
+        })
+        .count()
+        % 2
+        == 1
+}
+
+/// Celda (empezando en 0) en la que cae el carácter `column` de una fila
+fn cell_index(line: &str, column: usize) -> usize {
+    let separators = cell_separators(line);
+    let before = separators.iter().filter(|&&i| i < column).count();
+    let leading_pipe = line.trim_start().starts_with('|');
+    if leading_pipe {
+        before.saturating_sub(1)
+    } else {
+        before
+    }
+}
+
+/// Carácter donde empieza el contenido de una celda en una fila ya formateada
+fn cell_start(line: &str, column: usize) -> usize {
+    let separators = cell_separators(line);
+    let (Some(&open), Some(&close)) = (separators.get(column), separators.get(column + 1)) else {
+        return line.chars().count();
+    };
+    let content = line
+        .chars()
+        .skip(open + 1)
+        .take(close - open - 1)
+        .position(|c| !c.is_whitespace());
+    match content {
+        Some(offset) => open + 1 + offset,
+        // Celda vacía: tras el espacio de relleno
+        None => open + 2,
+    }
+}
+
+fn parse_number(cell: &str) -> Option<f64> {
+    cell.trim().replace(',', "").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "Antes\n\n| Nombre | Edad |\n|:--|--:|\n| Ana | 30 |\n|Luis|7|\n\nDespués";
+
+    #[test]
+    fn test_parse_and_format() {
+        let location = find_table(TABLE, 4, 3).unwrap();
+        assert_eq!(location.lines, 2..6);
+        assert_eq!((location.row, location.column), (1, 0));
+        assert_eq!(
+            location.table.alignments,
+            vec![ColumnAlignment::Left, ColumnAlignment::Right]
+        );
+
+        assert_eq!(
+            location.table.to_lines(),
+            vec![
+                "| Nombre | Edad |",
+                "| :----- | ---: |",
+                "| Ana    |   30 |",
+                "| Luis   |    7 |",
+            ]
+        );
+
+        // Fuera de la tabla o dentro de un bloque de código no hay tabla
+        assert!(find_table(TABLE, 0, 0).is_none());
+        assert!(find_table("```\n| a | b |\n|---|---|\n```", 1, 1).is_none());
+    }
+
+    #[test]
+    fn test_unicode_and_escapes() {
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+
+        let text = "| a | b |\n|---|---|\n| 日本 | x \\| z |";
+        let table = find_table(text, 2, 0).unwrap().table;
+        assert_eq!(table.rows[0], vec!["日本", "x \\| z"]);
+        assert_eq!(
+            table.to_lines(),
+            vec![
+                "| a    | b      |",
+                "| ---- | ------ |",
+                "| 日本 | x \\| z |"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tab_navigation() {
+        let text = "| a | b |\n|---|---|\n| 1 | 2 |";
+
+        // Tab desde la cabecera `b` salta a la primera celda del cuerpo
+        let next = move_to_cell(text, 0, 6, false).unwrap();
+        assert_eq!((next.line, next.column), (2, 2));
+
+        // Tab en la última celda añade una fila
+        let next = move_to_cell(text, 2, 8, false).unwrap();
+        assert_eq!(next.text.lines().count(), 4);
+        assert_eq!((next.line, next.column), (3, 2));
+
+        // Shift-Tab vuelve a la última celda de la fila anterior
+        let previous = move_to_cell(text, 2, 2, true).unwrap();
+        assert_eq!((previous.line, previous.column), (0, 8));
+
+        assert!(move_to_cell("sin tabla", 0, 0, false).is_none());
+    }
+
+    #[test]
+    fn test_row_and_column_edits() {
+        let text = "| n | v |\n|---|---|\n| b | 10 |\n| a | 9 |\n| c | 100 |";
+
+        let sorted = edit_table(text, 2, 6, TableEdit::Sort { descending: false }).unwrap();
+        let cells: Vec<String> = sorted
+            .text
+            .lines()
+            .skip(2)
+            .map(|line| split_row(line)[1].clone())
+            .collect();
+        assert_eq!(cells, vec!["9", "10", "100"]);
+
+        let moved = edit_table(text, 2, 2, TableEdit::MoveRow { up: false }).unwrap();
+        assert!(moved.text.lines().nth(3).unwrap().contains("| b "));
+        assert_eq!(moved.line, 3);
+
+        let inserted = edit_table(text, 0, 2, TableEdit::InsertColumn { before: false }).unwrap();
+        assert_eq!(
+            split_row(inserted.text.lines().next().unwrap()),
+            vec!["n", "", "v"]
+        );
+        assert_eq!((inserted.line, inserted.column), (0, 8));
+
+        let moved = edit_table(text, 0, 2, TableEdit::MoveColumn { left: false }).unwrap();
+        assert!(moved.text.starts_with("| v   | n   |"));
+
+        let aligned = edit_table(text, 0, 2, TableEdit::Align(ColumnAlignment::Center)).unwrap();
+        assert!(aligned.text.lines().nth(1).unwrap().starts_with("| :-: |"));
+
+        assert_eq!(
+            edit_table(text, 0, 2, TableEdit::DeleteRow),
+            Err(TableError::HeaderRow)
+        );
+        assert_eq!(
+            edit_table("| a |\n|---|", 0, 2, TableEdit::DeleteColumn),
+            Err(TableError::LastColumn)
+        );
+        assert_eq!(
+            edit_table("texto", 0, 0, TableEdit::Format),
+            Err(TableError::NotInTable)
+        );
+    }
+}
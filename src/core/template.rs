@@ -0,0 +1,756 @@
+//! Motor de plantillas para notas nuevas
+//!
+//! Las plantillas son notas Markdown guardadas en la carpeta de plantillas
+//! (`Templates/` por defecto) con estas etiquetas:
+//!
+//! ```text
+//! {{title}}  {{folder}}             nombre y carpeta de la nota nueva
+//! {{date}}  {{date:DD/MM/YYYY}}     fecha (formato estilo Moment: YYYY, MM, DD, dddd, ww, GGGG...)
+//! {{time}}  {{time:HH:mm:ss}}       hora
+//! {{cursor}}                        posición del cursor al abrir la nota
+//! {{clipboard}}                     contenido del portapapeles
+//! {{prompt:Asistentes}}             campo que se pregunta al crear la nota
+//! {{prompt:Lugar|Oficina}}          campo con valor por defecto
+//! {{Asistentes}}                    valor de un campo ya preguntado
+//! {{#if Lugar}}...{{else}}...{{/if}}  condicional (también `!campo`, `campo == "x"`, `!=`)
+//! {{include:Cabecera}}              incluir otra plantilla
+//! ```
+//!
+//! Las etiquetas desconocidas se dejan tal cual, y las de bloque (`#if`, `else`,
+//! `/if`) que ocupan una línea entera no dejan líneas vacías.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+
+use chrono::{Datelike, Local, NaiveDateTime, Timelike};
+use regex::Regex;
+use thiserror::Error;
+
+/// Carpeta de plantillas por defecto (dentro del directorio de notas)
+pub const DEFAULT_TEMPLATES_FOLDER: &str = "Templates";
+
+/// Profundidad máxima de `{{include:...}}` anidados
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("Template not found: {0}")]
+    NotFound(String),
+    #[error("Template include cycle: {0}")]
+    IncludeCycle(String),
+    #[error("Unclosed #if block: {0}")]
+    UnclosedIf(String),
+    #[error("Unexpected tag: {0}")]
+    UnexpectedTag(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, TemplateError>;
+
+/// Campo que hay que preguntar al usuario antes de crear la nota
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptField {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+/// Datos con los que se rellena una plantilla
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub title: String,
+    pub folder: Option<String>,
+    pub date: NaiveDateTime,
+    pub clipboard: Option<String>,
+    /// Idioma de los nombres de meses y días (`es`, `en`)
+    pub language: String,
+    /// Valores de los campos preguntados y variables extra
+    pub values: HashMap<String, String>,
+}
+
+impl TemplateContext {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            folder: None,
+            date: Local::now().naive_local(),
+            clipboard: None,
+            language: "es".to_string(),
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn with_folder(mut self, folder: Option<String>) -> Self {
+        self.folder = folder.filter(|f| !f.is_empty());
+        self
+    }
+
+    pub fn with_date(mut self, date: NaiveDateTime) -> Self {
+        self.date = date;
+        self
+    }
+
+    pub fn with_clipboard(mut self, clipboard: Option<String>) -> Self {
+        self.clipboard = clipboard;
+        self
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    pub fn with_value(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.values.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn with_values(mut self, values: HashMap<String, String>) -> Self {
+        self.values.extend(values);
+        self
+    }
+
+    /// Valor de una variable por nombre (las integradas primero)
+    fn value(&self, name: &str) -> Option<String> {
+        match name {
+            "title" => Some(self.title.clone()),
+            "folder" => Some(self.folder.clone().unwrap_or_default()),
+            "clipboard" => Some(self.clipboard.clone().unwrap_or_default()),
+            "date" => Some(format_date(&self.date, "YYYY-MM-DD", &self.language)),
+            "time" => Some(format_date(&self.date, "HH:mm", &self.language)),
+            _ => self.values.get(name).cloned(),
+        }
+    }
+}
+
+/// Resultado de rellenar una plantilla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedTemplate {
+    pub content: String,
+    /// Posición de `{{cursor}}` en caracteres (si la plantilla lo usa)
+    pub cursor: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Condition {
+    name: String,
+    negated: bool,
+    /// `campo == "x"` (true) o `campo != "x"` (false)
+    comparison: Option<(bool, String)>,
+}
+
+impl Condition {
+    fn parse(expression: &str) -> Self {
+        let expression = expression.trim();
+        let (negated, expression) = match expression.strip_prefix('!') {
+            Some(rest) if !rest.starts_with('=') => (true, rest.trim()),
+            _ => (false, expression),
+        };
+        for (operator, equal) in [("==", true), ("!=", false)] {
+            if let Some((name, value)) = expression.split_once(operator) {
+                let value = value.trim().trim_matches('"').trim_matches('\'');
+                return Self {
+                    name: name.trim().to_string(),
+                    negated,
+                    comparison: Some((equal, value.to_string())),
+                };
+            }
+        }
+        Self {
+            name: expression.to_string(),
+            negated,
+            comparison: None,
+        }
+    }
+
+    fn evaluate(&self, context: &TemplateContext) -> bool {
+        let value = context.value(&self.name).unwrap_or_default();
+        let result = match &self.comparison {
+            Some((equal, expected)) => (value.trim() == expected) == *equal,
+            None => !value.trim().is_empty(),
+        };
+        result != self.negated
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Date(String),
+    Cursor,
+    Prompt(PromptField),
+    /// Variable con nombre; `raw` es la etiqueta original por si no existe
+    Variable {
+        name: String,
+        raw: String,
+    },
+    If {
+        condition: Condition,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// Plantilla ya interpretada (con los includes resueltos)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+static TAG_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{\{([^{}\n]*)\}\}").unwrap());
+
+/// Bloque `#if` abierto mientras se interpreta
+struct OpenBlock {
+    condition: Condition,
+    expression: String,
+    then: Vec<Node>,
+    otherwise: Vec<Node>,
+    in_else: bool,
+}
+
+impl Template {
+    /// Interpretar una plantilla sin includes (un `{{include}}` da `NotFound`)
+    pub fn parse(source: &str) -> Result<Self> {
+        Self::parse_with_includes(source, &|name| {
+            Err(TemplateError::NotFound(name.to_string()))
+        })
+    }
+
+    /// Interpretar una plantilla resolviendo los includes con `loader`
+    pub fn parse_with_includes(
+        source: &str,
+        loader: &dyn Fn(&str) -> Result<String>,
+    ) -> Result<Self> {
+        let nodes = parse_nodes(source, loader, &mut Vec::new())?;
+        Ok(Self { nodes })
+    }
+
+    /// Campos a preguntar, en orden de aparición y sin repetir
+    pub fn prompts(&self) -> Vec<PromptField> {
+        fn collect(nodes: &[Node], fields: &mut Vec<PromptField>) {
+            for node in nodes {
+                match node {
+                    Node::Prompt(field) if !fields.iter().any(|f| f.name == field.name) => {
+                        fields.push(field.clone());
+                    }
+                    Node::If {
+                        then, otherwise, ..
+                    } => {
+                        collect(then, fields);
+                        collect(otherwise, fields);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut fields = Vec::new();
+        collect(&self.nodes, &mut fields);
+        fields
+    }
+
+    /// Si la plantilla necesita el portapapeles (para leerlo solo cuando hace falta)
+    pub fn uses_clipboard(&self) -> bool {
+        fn uses(nodes: &[Node]) -> bool {
+            nodes.iter().any(|node| match node {
+                Node::Variable { name, .. } => name == "clipboard",
+                Node::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => condition.name == "clipboard" || uses(then) || uses(otherwise),
+                _ => false,
+            })
+        }
+        uses(&self.nodes)
+    }
+
+    /// Rellenar la plantilla
+    pub fn render(&self, context: &TemplateContext) -> RenderedTemplate {
+        let mut rendered = RenderedTemplate {
+            content: String::new(),
+            cursor: None,
+        };
+        render_nodes(&self.nodes, context, &mut rendered);
+        rendered
+    }
+}
+
+fn parse_nodes(
+    source: &str,
+    loader: &dyn Fn(&str) -> Result<String>,
+    include_stack: &mut Vec<String>,
+) -> Result<Vec<Node>> {
+    let mut root: Vec<Node> = Vec::new();
+    let mut blocks: Vec<OpenBlock> = Vec::new();
+    let mut last = 0;
+
+    fn current<'a>(root: &'a mut Vec<Node>, blocks: &'a mut [OpenBlock]) -> &'a mut Vec<Node> {
+        match blocks.last_mut() {
+            Some(block) if block.in_else => &mut block.otherwise,
+            Some(block) => &mut block.then,
+            None => root,
+        }
+    }
+
+    for captures in TAG_REGEX.captures_iter(source) {
+        let tag = captures.get(0).unwrap();
+        let expression = captures[1].trim();
+        let is_block =
+            expression.starts_with("#if ") || expression == "else" || expression == "/if";
+
+        // Las etiquetas de bloque solas en su línea se llevan la línea entera
+        let (text_end, next) = if is_block {
+            standalone_line(source, tag.start(), tag.end()).unwrap_or((tag.start(), tag.end()))
+        } else {
+            (tag.start(), tag.end())
+        };
+        if text_end > last {
+            current(&mut root, &mut blocks).push(Node::Text(source[last..text_end].to_string()));
+        }
+        last = next.max(last);
+
+        if let Some(condition) = expression.strip_prefix("#if ") {
+            blocks.push(OpenBlock {
+                condition: Condition::parse(condition),
+                expression: condition.trim().to_string(),
+                then: Vec::new(),
+                otherwise: Vec::new(),
+                in_else: false,
+            });
+        } else if expression == "else" {
+            match blocks.last_mut() {
+                Some(block) if !block.in_else => block.in_else = true,
+                _ => return Err(TemplateError::UnexpectedTag(expression.to_string())),
+            }
+        } else if expression == "/if" {
+            let Some(block) = blocks.pop() else {
+                return Err(TemplateError::UnexpectedTag(expression.to_string()));
+            };
+            let node = Node::If {
+                condition: block.condition,
+                then: block.then,
+                otherwise: block.otherwise,
+            };
+            current(&mut root, &mut blocks).push(node);
+        } else if let Some(name) = expression.strip_prefix("include:") {
+            let name = name.trim().to_string();
+            if include_stack.contains(&name) || include_stack.len() >= MAX_INCLUDE_DEPTH {
+                return Err(TemplateError::IncludeCycle(name));
+            }
+            let included = loader(&name)?;
+            include_stack.push(name);
+            let nodes = parse_nodes(&included, loader, include_stack)?;
+            include_stack.pop();
+            current(&mut root, &mut blocks).extend(nodes);
+        } else {
+            current(&mut root, &mut blocks).push(parse_tag(expression, tag.as_str()));
+        }
+    }
+
+    if let Some(block) = blocks.pop() {
+        return Err(TemplateError::UnclosedIf(block.expression));
+    }
+    if last < source.len() {
+        root.push(Node::Text(source[last..].to_string()));
+    }
+    Ok(root)
+}
+
+fn parse_tag(expression: &str, raw: &str) -> Node {
+    let (name, argument) = match expression.split_once(':') {
+        Some((name, argument)) => (name.trim(), Some(argument.trim())),
+        None => (expression, None),
+    };
+    match (name, argument) {
+        ("cursor", None) => Node::Cursor,
+        ("date", format) => Node::Date(format.unwrap_or("YYYY-MM-DD").to_string()),
+        ("time", format) => Node::Date(format.unwrap_or("HH:mm").to_string()),
+        ("prompt", Some(field)) => {
+            let (name, default) = match field.split_once('|') {
+                Some((name, default)) => (name.trim(), Some(default.trim().to_string())),
+                None => (field, None),
+            };
+            Node::Prompt(PromptField {
+                name: name.to_string(),
+                default,
+            })
+        }
+        _ => Node::Variable {
+            name: expression.to_string(),
+            raw: raw.to_string(),
+        },
+    }
+}
+
+/// Si `start..end` es lo único de su línea, devolver el rango de texto a conservar
+/// antes de la etiqueta y dónde continuar (tras el salto de línea)
+fn standalone_line(source: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = source[end..]
+        .find('\n')
+        .map(|i| end + i + 1)
+        .unwrap_or(source.len());
+    let before_blank = source[line_start..start].trim().is_empty();
+    let after_blank = source[end..line_end].trim().is_empty();
+    (before_blank && after_blank).then_some((line_start, line_end))
+}
+
+fn render_nodes(nodes: &[Node], context: &TemplateContext, rendered: &mut RenderedTemplate) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => rendered.content.push_str(text),
+            Node::Date(format) => {
+                rendered
+                    .content
+                    .push_str(&format_date(&context.date, format, &context.language))
+            }
+            Node::Cursor => {
+                if rendered.cursor.is_none() {
+                    rendered.cursor = Some(rendered.content.chars().count());
+                }
+            }
+            Node::Prompt(field) => {
+                let value = context
+                    .values
+                    .get(&field.name)
+                    .cloned()
+                    .or_else(|| field.default.clone())
+                    .unwrap_or_default();
+                rendered.content.push_str(&value);
+            }
+            Node::Variable { name, raw } => match context.value(name) {
+                Some(value) => rendered.content.push_str(&value),
+                None => rendered.content.push_str(raw),
+            },
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if condition.evaluate(context) {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, context, rendered);
+            }
+        }
+    }
+}
+
+const MONTHS_ES: [&str; 12] = [
+    "enero",
+    "febrero",
+    "marzo",
+    "abril",
+    "mayo",
+    "junio",
+    "julio",
+    "agosto",
+    "septiembre",
+    "octubre",
+    "noviembre",
+    "diciembre",
+];
+const MONTHS_EN: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const WEEKDAYS_ES: [&str; 7] = [
+    "lunes",
+    "martes",
+    "miércoles",
+    "jueves",
+    "viernes",
+    "sábado",
+    "domingo",
+];
+const WEEKDAYS_EN: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// Formatear una fecha con tokens estilo Moment (`YYYY-MM-DD`, `dddd D [de] MMMM`)
+///
+/// Tokens: `YYYY YY MMMM MMM MM M DD D dddd ddd HH H hh mm ss A ww Q`.
+/// El texto entre corchetes se copia literal.
+pub fn format_date(date: &NaiveDateTime, format: &str, language: &str) -> String {
+    let spanish = language.starts_with("es");
+    let month_name = if spanish { MONTHS_ES } else { MONTHS_EN }[date.month0() as usize];
+    let weekday_name = if spanish { WEEKDAYS_ES } else { WEEKDAYS_EN }
+        [date.weekday().num_days_from_monday() as usize];
+    let hour12 = match date.hour() % 12 {
+        0 => 12,
+        hour => hour,
+    };
+
+    const TOKENS: [&str; 20] = [
+        "YYYY", "GGGG", "MMMM", "dddd", "MMM", "ddd", "YY", "MM", "DD", "HH", "hh", "mm", "ss",
+        "ww", "WW", "M", "D", "H", "A", "Q",
+    ];
+
+    let mut output = String::new();
+    let mut rest = format;
+    while !rest.is_empty() {
+        if let Some(literal) = rest.strip_prefix('[')
+            && let Some(close) = literal.find(']')
+        {
+            output.push_str(&literal[..close]);
+            rest = &literal[close + 1..];
+            continue;
+        }
+        let Some(token) = TOKENS.iter().find(|token| rest.starts_with(**token)) else {
+            let c = rest.chars().next().unwrap();
+            output.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+        let value = match *token {
+            "YYYY" => format!("{:04}", date.year()),
+            "YY" => format!("{:02}", date.year() % 100),
+            "MMMM" => month_name.to_string(),
+            "MMM" => month_name.chars().take(3).collect(),
+            "MM" => format!("{:02}", date.month()),
+            "M" => date.month().to_string(),
+            "DD" => format!("{:02}", date.day()),
+            "D" => date.day().to_string(),
+            "dddd" => weekday_name.to_string(),
+            "ddd" => weekday_name.chars().take(3).collect(),
+            "HH" => format!("{:02}", date.hour()),
+            "H" => date.hour().to_string(),
+            "hh" => format!("{:02}", hour12),
+            "mm" => format!("{:02}", date.minute()),
+            "ss" => format!("{:02}", date.second()),
+            "A" => if date.hour() < 12 { "AM" } else { "PM" }.to_string(),
+            "ww" | "WW" => format!("{:02}", date.iso_week().week()),
+            "GGGG" => format!("{:04}", date.iso_week().year()),
+            "Q" => (date.month0() / 3 + 1).to_string(),
+            _ => unreachable!(),
+        };
+        output.push_str(&value);
+        rest = &rest[token.len()..];
+    }
+    output
+}
+
+/// Carpeta de plantillas (`.md`, con subcarpetas)
+#[derive(Debug, Clone)]
+pub struct TemplateLibrary {
+    dir: PathBuf,
+}
+
+impl TemplateLibrary {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Nombres de las plantillas disponibles (`Reunión`, `Trabajo/Informe`)
+    pub fn list(&self) -> Vec<String> {
+        fn scan(dir: &Path, prefix: &str, names: &mut Vec<String>) {
+            let Ok(entries) = fs::read_dir(dir) else {
+                return;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if file_name.starts_with('.') {
+                    continue;
+                }
+                if path.is_dir() {
+                    scan(&path, &format!("{}{}/", prefix, file_name), names);
+                } else if let Some(stem) = file_name.strip_suffix(".md") {
+                    names.push(format!("{}{}", prefix, stem));
+                }
+            }
+        }
+        let mut names = Vec::new();
+        scan(&self.dir, "", &mut names);
+        names.sort_by_key(|name| name.to_lowercase());
+        names
+    }
+
+    /// Texto de una plantilla por nombre (con o sin `.md`)
+    pub fn source(&self, name: &str) -> Result<String> {
+        let name = name.trim().trim_end_matches(".md");
+        let not_found = || TemplateError::NotFound(name.to_string());
+
+        // Solo rutas relativas simples (`Partes/Cabecera`): nada de `..`, `/` inicial ni prefijos
+        let relative = Path::new(name);
+        if name.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(not_found());
+        }
+
+        // Y que no salga de la carpeta de plantillas a través de un enlace simbólico
+        let path = self.dir.join(format!("{}.md", name));
+        let (Ok(path), Ok(dir)) = (path.canonicalize(), self.dir.canonicalize()) else {
+            return Err(not_found());
+        };
+        if !path.starts_with(&dir) || !path.is_file() {
+            return Err(not_found());
+        }
+        Ok(fs::read_to_string(path)?)
+    }
+
+    /// Cargar e interpretar una plantilla, con sus includes
+    pub fn load(&self, name: &str) -> Result<Template> {
+        let source = self.source(name)?;
+        Template::parse_with_includes(&source, &|include| self.source(include))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn date() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, 7)
+            .unwrap()
+            .and_hms_opt(9, 5, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_variables_and_dates() {
+        let template = Template::parse(
+            "# {{title}}\nCarpeta: {{folder}}\n{{date}} {{date:dddd D [de] MMMM, [semana] ww}} {{time}}\n\n{{cursor}}\n{{desconocida}}",
+        )
+        .unwrap();
+        let context = TemplateContext::new("Plan")
+            .with_folder(Some("Proyectos".into()))
+            .with_date(date());
+        let rendered = template.render(&context);
+        assert_eq!(
+            rendered.content,
+            "# Plan\nCarpeta: Proyectos\n2025-03-07 viernes 7 de marzo, semana 10 09:05\n\n\n{{desconocida}}"
+        );
+        assert_eq!(rendered.cursor, Some(74));
+
+        let english = format_date(&date(), "ddd, MMM D YYYY hh:mm A (Q)", "en");
+        assert_eq!(english, "Fri, Mar 7 2025 09:05 AM (1)");
+    }
+
+    #[test]
+    fn test_prompts_and_conditionals() {
+        let source = "---\ntype: reunión\n---\n\
+                      Asistentes: {{prompt:Asistentes}}\n\
+                      {{#if Lugar}}\n\
+                      Lugar: {{prompt:Lugar|Oficina}}\n\
+                      {{else}}\n\
+                      Online\n\
+                      {{/if}}\n\
+                      {{#if folder == \"Meetings\"}}Equipo{{/if}}{{#if !clipboard}}-{{/if}}\n";
+        let template = Template::parse(source).unwrap();
+        assert_eq!(
+            template.prompts(),
+            vec![
+                PromptField {
+                    name: "Asistentes".into(),
+                    default: None
+                },
+                PromptField {
+                    name: "Lugar".into(),
+                    default: Some("Oficina".into())
+                },
+            ]
+        );
+        assert!(template.uses_clipboard());
+
+        let context = TemplateContext::new("Sync")
+            .with_folder(Some("Meetings".into()))
+            .with_value("Asistentes", "Ana, Luis")
+            .with_value("Lugar", "Sala 2");
+        assert_eq!(
+            template.render(&context).content,
+            "---\ntype: reunión\n---\nAsistentes: Ana, Luis\nLugar: Sala 2\nEquipo-\n"
+        );
+
+        let context = TemplateContext::new("Sync").with_clipboard(Some("x".into()));
+        assert_eq!(
+            template.render(&context).content,
+            "---\ntype: reunión\n---\nAsistentes: \nOnline\n\n"
+        );
+
+        assert!(matches!(
+            Template::parse("{{#if a}}sin cerrar"),
+            Err(TemplateError::UnclosedIf(_))
+        ));
+        assert!(matches!(
+            Template::parse("{{/if}}"),
+            Err(TemplateError::UnexpectedTag(_))
+        ));
+    }
+
+    #[test]
+    fn test_library_includes() {
+        let dir = std::env::temp_dir().join("notnative_template_test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Partes")).unwrap();
+        fs::write(dir.join("Reunión.md"), "{{include:Partes/Cabecera}}\nNotas").unwrap();
+        fs::write(dir.join("Partes/Cabecera.md"), "# {{title}}\n").unwrap();
+        fs::write(dir.join("Bucle.md"), "{{include:Bucle}}").unwrap();
+
+        let library = TemplateLibrary::new(&dir);
+        assert_eq!(library.list(), vec!["Bucle", "Partes/Cabecera", "Reunión"]);
+
+        let rendered = library
+            .load("Reunión")
+            .unwrap()
+            .render(&TemplateContext::new("Lunes"));
+        assert_eq!(rendered.content, "# Lunes\n\nNotas");
+
+        assert!(matches!(
+            library.load("Bucle"),
+            Err(TemplateError::IncludeCycle(_))
+        ));
+        assert!(matches!(
+            library.load("../fuera"),
+            Err(TemplateError::NotFound(_))
+        ));
+        let absolute = dir.join("Reunión");
+        assert!(matches!(
+            library.load(absolute.to_str().unwrap()),
+            Err(TemplateError::NotFound(_))
+        ));
+
+        // Un enlace simbólico que apunta fuera de la carpeta tampoco se lee
+        #[cfg(unix)]
+        {
+            let outside = std::env::temp_dir().join("notnative_template_outside.md");
+            fs::write(&outside, "secreto").unwrap();
+            std::os::unix::fs::symlink(&outside, dir.join("Enlace.md")).unwrap();
+            assert!(matches!(
+                library.source("Enlace"),
+                Err(TemplateError::NotFound(_))
+            ));
+            let _ = fs::remove_file(&outside);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
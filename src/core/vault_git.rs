@@ -0,0 +1,832 @@
+//! Historial Git del vault
+//!
+//! Si se activa (`git.enabled` en la configuración o `:git init`), el
+//! directorio de notas es un repositorio Git que gestiona la aplicación:
+//!
+//! - Al guardar, los cambios se confirman tras una pausa (`debounce_secs`) con
+//!   un mensaje a partir de `message_template`, que admite las variables de las
+//!   plantillas (`{{date}}`, `{{time:HH:mm}}`...) más `{{notes}}`, `{{count}}`
+//!   y `{{title}}` (la primera nota cambiada)
+//! - Cada nota tiene su historial (`git log --follow`), el diff de cada versión
+//!   y la opción de restaurarla
+//! - `pull` y `push` con el remoto configurado; si la fusión toca notas en
+//!   conflicto, se resuelven una a una (la mía, la remota o editada a mano)
+//!
+//! La papelera, la carpeta `.history` y la base de datos SQLite quedan fuera
+//! del repositorio (`.gitignore`). Se usa el comando `git` del sistema.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::template::{Template, TemplateContext};
+
+/// Nombre del remoto que gestiona la aplicación
+pub const REMOTE_NAME: &str = "origin";
+
+/// Entradas que la aplicación añade al `.gitignore` del vault
+const IGNORED: &[&str] = &[
+    ".trash/",
+    ".history/",
+    "*.db",
+    "*.db-journal",
+    "*.db-wal",
+    "*.db-shm",
+];
+
+/// Cabecera del bloque de la aplicación en el `.gitignore`
+const IGNORE_HEADER: &str = "# NotNative";
+
+/// Notas que se nombran en el mensaje antes de resumir el resto
+const MESSAGE_MAX_NOTES: usize = 3;
+
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error("git is not installed or not in PATH")]
+    NotInstalled,
+    #[error("Not a git repository: {0}")]
+    NotARepository(PathBuf),
+    #[error("No remote configured")]
+    NoRemote,
+    #[error("Push rejected, pull first: {0}")]
+    PushRejected(String),
+    #[error("Unresolved conflicts: {0}")]
+    UnresolvedConflicts(String),
+    #[error("git {command} failed: {stderr}")]
+    Command { command: String, stderr: String },
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, GitError>;
+
+/// Configuración del historial Git (`git` en la configuración)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// Versionar el vault con Git
+    #[serde(default)]
+    pub enabled: bool,
+    /// Mensaje de los commits automáticos
+    #[serde(default = "default_message_template")]
+    pub message_template: String,
+    /// Segundos sin guardar antes del commit automático
+    #[serde(default = "default_debounce_secs")]
+    pub debounce_secs: u64,
+    /// Rama del remoto (por defecto, la rama actual)
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Hacer push tras cada commit automático
+    #[serde(default)]
+    pub push_on_commit: bool,
+}
+
+fn default_message_template() -> String {
+    "Actualizar {{notes}}".to_string()
+}
+
+fn default_debounce_secs() -> u64 {
+    30
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            message_template: default_message_template(),
+            debounce_secs: default_debounce_secs(),
+            branch: None,
+            push_on_commit: false,
+        }
+    }
+}
+
+/// Subcomandos de `:git`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitCommand {
+    /// `:git init` crear el repositorio y activar el historial
+    Init,
+    /// `:git commit [mensaje]` confirmar ya los cambios pendientes
+    Commit(Option<String>),
+    /// `:git log` (o `:history`) historial de la nota abierta
+    Log,
+    Pull,
+    Push,
+    /// `:git sync` commit, pull y push
+    Sync,
+    /// `:git remote URL`
+    Remote(String),
+    /// `:git conflicts` volver a abrir la resolución de conflictos
+    Conflicts,
+    /// `:git abort` cancelar la fusión en curso
+    Abort,
+}
+
+/// Una versión de una nota en el historial
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitInfo {
+    pub id: String,
+    pub short_id: String,
+    pub author: String,
+    pub date: DateTime<FixedOffset>,
+    pub message: String,
+    /// Ruta de la nota en ese commit (cambia si se renombró)
+    pub path: String,
+}
+
+/// Resultado de traer los cambios del remoto
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PullOutcome {
+    UpToDate,
+    /// Fusión sin conflictos; notas que cambiaron
+    Updated(Vec<String>),
+    /// La fusión quedó a medias: hay que resolver estas notas
+    Conflicts(Vec<Conflict>),
+}
+
+/// Fichero en conflicto tras una fusión
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Ruta relativa al vault
+    pub path: String,
+    /// Versión local (None si se borró en local)
+    pub ours: Option<String>,
+    /// Versión del remoto (None si se borró en el remoto)
+    pub theirs: Option<String>,
+    /// Fichero con las marcas de conflicto, para editarlo a mano
+    pub merged: String,
+}
+
+impl Conflict {
+    /// Nombre de la nota (sin `.md`), si el fichero es una nota
+    pub fn note_name(&self) -> Option<&str> {
+        self.path.strip_suffix(".md")
+    }
+}
+
+/// Cómo resolver un conflicto
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    /// Contenido editado a mano
+    Merged(String),
+}
+
+/// Mensaje de commit a partir de la plantilla y las notas cambiadas
+pub fn commit_message(template: &str, notes: &[String], date: NaiveDateTime) -> String {
+    let titles: Vec<&str> = notes
+        .iter()
+        .map(|note| note.rsplit('/').next().unwrap_or(note))
+        .collect();
+    let listed = match titles.len() {
+        0 => "el vault".to_string(),
+        n if n <= MESSAGE_MAX_NOTES => titles.join(", "),
+        n => format!(
+            "{} y {} más",
+            titles[..MESSAGE_MAX_NOTES].join(", "),
+            n - MESSAGE_MAX_NOTES
+        ),
+    };
+    let context = TemplateContext::new(titles.first().copied().unwrap_or_default())
+        .with_date(date)
+        .with_value("notes", listed)
+        .with_value("count", notes.len().to_string());
+    let message = match Template::parse(template) {
+        Ok(template) => template.render(&context).content,
+        Err(_) => template.to_string(),
+    };
+    match message.trim() {
+        "" => Template::parse(&default_message_template())
+            .map(|template| template.render(&context).content)
+            .unwrap_or_default(),
+        message => message.to_string(),
+    }
+}
+
+/// Ruta dentro del vault de una nota (`Carpeta/Nota` → `Carpeta/Nota.md`)
+pub fn note_path(note: &str) -> String {
+    format!("{}.md", note.trim_matches('/'))
+}
+
+/// Repositorio Git de un vault
+///
+/// Las copias comparten un cerrojo: las operaciones lanzadas con
+/// [`VaultGit::exclusive`] desde distintos hilos (commit automático, pull,
+/// push...) nunca se ejecutan a la vez ni chocan en `index.lock`.
+#[derive(Debug, Clone)]
+pub struct VaultGit {
+    root: PathBuf,
+    lock: Arc<Mutex<()>>,
+}
+
+impl VaultGit {
+    /// Si el vault ya es un repositorio
+    pub fn is_repository(root: &Path) -> bool {
+        root.join(".git").exists()
+    }
+
+    /// Abrir el repositorio de un vault
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        if !Self::is_repository(&root) {
+            return Err(GitError::NotARepository(root));
+        }
+        Ok(Self::new(root))
+    }
+
+    fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Crear el repositorio si no existe, dejar fuera la papelera, el historial
+    /// y la base de datos, y confirmar lo que ya hubiera en el vault
+    pub fn init(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        std::fs::create_dir_all(&root)?;
+        let git = Self::new(root);
+        if !Self::is_repository(&git.root) {
+            git.git(&["init", "--quiet"])?;
+        }
+        if git.git(&["config", "user.email"]).is_err() {
+            git.git(&["config", "user.name", "NotNative"])?;
+            git.git(&["config", "user.email", "notnative@localhost"])?;
+        }
+        git.ensure_ignored()?;
+        git.commit_all("Iniciar el historial del vault")?;
+        Ok(git)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Ejecutar `operation` sin ninguna otra operación exclusiva en curso
+    /// (espera a que termine la que haya, p. ej. un push lento)
+    pub fn exclusive<T>(&self, operation: impl FnOnce(&Self) -> T) -> T {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        operation(self)
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new("git");
+        command
+            .arg("-C")
+            .arg(&self.root)
+            .args(["-c", "core.quotepath=off", "-c", "commit.gpgsign=false"])
+            .args(args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("LC_ALL", "C")
+            .stdin(Stdio::null());
+        command
+    }
+
+    /// Ejecutar git sin comprobar el resultado
+    fn run(&self, args: &[&str]) -> Result<Output> {
+        self.command(args).output().map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => GitError::NotInstalled,
+            _ => GitError::Io(e),
+        })
+    }
+
+    /// Ejecutar git y devolver su salida (error si falla)
+    fn git(&self, args: &[&str]) -> Result<String> {
+        let output = self.run(args)?;
+        if !output.status.success() {
+            return Err(command_error(args, &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Si la referencia existe (`HEAD`, `MERGE_HEAD`, `origin/main`...)
+    fn has_ref(&self, reference: &str) -> bool {
+        self.run(&["rev-parse", "--quiet", "--verify", reference])
+            .is_ok_and(|output| output.status.success())
+    }
+
+    /// Añadir al `.gitignore` lo que no debe versionarse y sacarlo del índice si
+    /// ya estaba
+    pub fn ensure_ignored(&self) -> Result<()> {
+        let path = self.root.join(".gitignore");
+        let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+        let missing: Vec<&str> = IGNORED
+            .iter()
+            .copied()
+            .filter(|entry| !content.lines().any(|line| line.trim() == *entry))
+            .collect();
+        if !missing.is_empty() {
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            if !content.lines().any(|line| line.trim() == IGNORE_HEADER) {
+                if !content.is_empty() {
+                    content.push('\n');
+                }
+                content.push_str(IGNORE_HEADER);
+                content.push('\n');
+            }
+            for entry in missing {
+                content.push_str(entry);
+                content.push('\n');
+            }
+            std::fs::write(&path, content)?;
+        }
+
+        let mut args = vec!["rm", "-r", "-q", "--cached", "--ignore-unmatch", "--"];
+        args.extend(IGNORED.iter().map(|entry| entry.trim_end_matches('/')));
+        self.git(&args)?;
+        Ok(())
+    }
+
+    /// Rama actual
+    pub fn current_branch(&self) -> Result<String> {
+        Ok(self
+            .git(&["symbolic-ref", "--short", "HEAD"])?
+            .trim()
+            .to_string())
+    }
+
+    /// Si hay cambios sin confirmar
+    pub fn has_changes(&self) -> Result<bool> {
+        Ok(!self.git(&["status", "--porcelain"])?.trim().is_empty())
+    }
+
+    /// Confirmar todos los cambios del vault (None si no había ninguno)
+    pub fn commit_all(&self, message: &str) -> Result<Option<String>> {
+        self.git(&["add", "-A"])?;
+        if self.has_ref("HEAD") && self.staged_files()?.is_empty() {
+            return Ok(None);
+        }
+        let output = self.run(&["commit", "--quiet", "--allow-empty-message", "-m", message])?;
+        if !output.status.success() {
+            // Repositorio recién creado y vacío: nada que confirmar
+            if !self.has_ref("HEAD") {
+                return Ok(None);
+            }
+            return Err(command_error(&["commit"], &output));
+        }
+        Ok(Some(self.git(&["rev-parse", "HEAD"])?.trim().to_string()))
+    }
+
+    /// Commit automático: el mensaje sale de la plantilla con las notas cambiadas.
+    /// Con una fusión a medias no se hace nada, para no confirmar las marcas de
+    /// conflicto
+    pub fn auto_commit(&self, template: &str) -> Result<Option<String>> {
+        if self.is_merging() {
+            return Ok(None);
+        }
+        self.git(&["add", "-A"])?;
+        let notes: Vec<String> = self
+            .staged_files()?
+            .iter()
+            .filter_map(|path| path.strip_suffix(".md"))
+            .map(str::to_string)
+            .collect();
+        let message = commit_message(template, &notes, Local::now().naive_local());
+        self.commit_all(&message)
+    }
+
+    /// Ficheros preparados para el próximo commit
+    fn staged_files(&self) -> Result<Vec<String>> {
+        let output = if self.has_ref("HEAD") {
+            self.git(&["diff", "--cached", "--name-only", "-z", "--no-renames"])?
+        } else {
+            self.git(&["ls-files", "-z"])?
+        };
+        Ok(split_nul(&output))
+    }
+
+    /// Versiones de una nota, de la más reciente a la más antigua
+    pub fn note_log(&self, note: &str) -> Result<Vec<CommitInfo>> {
+        if !self.has_ref("HEAD") {
+            return Ok(Vec::new());
+        }
+        let path = note_path(note);
+        let output = self.git(&[
+            "log",
+            "--follow",
+            "--name-only",
+            "--format=%x1e%H%x1f%h%x1f%an%x1f%aI%x1f%s",
+            "--",
+            &path,
+        ])?;
+        let mut commits = Vec::new();
+        for record in output
+            .split('\x1e')
+            .filter(|record| !record.trim().is_empty())
+        {
+            let mut lines = record.lines();
+            let fields: Vec<&str> = lines.next().unwrap_or_default().split('\x1f').collect();
+            let [id, short_id, author, date, message] = fields[..] else {
+                continue;
+            };
+            let Ok(date) = DateTime::parse_from_rfc3339(date) else {
+                continue;
+            };
+            let commit_path = lines
+                .rev()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or(&path);
+            commits.push(CommitInfo {
+                id: id.to_string(),
+                short_id: short_id.to_string(),
+                author: author.to_string(),
+                date,
+                message: message.to_string(),
+                path: commit_path.to_string(),
+            });
+        }
+        Ok(commits)
+    }
+
+    /// Cambios que introdujo un commit en la nota (diff unificado)
+    pub fn note_diff(&self, commit: &CommitInfo) -> Result<String> {
+        self.git(&[
+            "show",
+            "--format=",
+            "--no-color",
+            "--no-ext-diff",
+            &commit.id,
+            "--",
+            &commit.path,
+        ])
+    }
+
+    /// Contenido de la nota en un commit
+    pub fn note_at(&self, commit: &CommitInfo) -> Result<String> {
+        self.git(&["show", &format!("{}:{}", commit.id, commit.path)])
+    }
+
+    /// Sobrescribir la nota con su contenido en un commit. El cambio queda
+    /// pendiente para el próximo commit
+    pub fn restore_note(&self, note: &str, commit: &CommitInfo) -> Result<String> {
+        let content = self.note_at(commit)?;
+        let path = self.root.join(note_path(note));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, &content)?;
+        Ok(content)
+    }
+
+    /// URL del remoto, si hay
+    pub fn remote_url(&self) -> Result<Option<String>> {
+        let output = self.run(&["remote", "get-url", REMOTE_NAME])?;
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    /// Configurar (o cambiar) el remoto
+    pub fn set_remote(&self, url: &str) -> Result<()> {
+        let action = if self.remote_url()?.is_some() {
+            "set-url"
+        } else {
+            "add"
+        };
+        self.git(&["remote", action, REMOTE_NAME, url])?;
+        Ok(())
+    }
+
+    /// Traer y fusionar los cambios del remoto. `branch` es la rama remota (por
+    /// defecto, la actual)
+    pub fn pull(&self, branch: Option<&str>) -> Result<PullOutcome> {
+        if self.remote_url()?.is_none() {
+            return Err(GitError::NoRemote);
+        }
+        let branch = match branch {
+            Some(branch) => branch.to_string(),
+            None => self.current_branch()?,
+        };
+        self.git(&["fetch", "--quiet", REMOTE_NAME])?;
+        let remote_ref = format!("{}/{}", REMOTE_NAME, branch);
+        if !self.has_ref(&format!("refs/remotes/{}", remote_ref)) {
+            // El remoto aún no tiene esa rama
+            return Ok(PullOutcome::UpToDate);
+        }
+
+        let before = self.git(&["rev-parse", "HEAD"])?.trim().to_string();
+        let output = self.run(&[
+            "merge",
+            "--no-edit",
+            "--allow-unrelated-histories",
+            &remote_ref,
+        ])?;
+        if !output.status.success() {
+            let conflicts = self.conflicts()?;
+            if conflicts.is_empty() {
+                return Err(command_error(&["merge"], &output));
+            }
+            return Ok(PullOutcome::Conflicts(conflicts));
+        }
+
+        let changed = self.git(&["diff", "--name-only", "-z", &before, "HEAD"])?;
+        let notes: Vec<String> = split_nul(&changed)
+            .iter()
+            .filter_map(|path| path.strip_suffix(".md"))
+            .map(str::to_string)
+            .collect();
+        if self.git(&["rev-parse", "HEAD"])?.trim() == before {
+            Ok(PullOutcome::UpToDate)
+        } else {
+            Ok(PullOutcome::Updated(notes))
+        }
+    }
+
+    /// Subir los commits al remoto (rama `branch` o la actual)
+    pub fn push(&self, branch: Option<&str>) -> Result<()> {
+        if self.remote_url()?.is_none() {
+            return Err(GitError::NoRemote);
+        }
+        let branch = match branch {
+            Some(branch) => branch.to_string(),
+            None => self.current_branch()?,
+        };
+        let refspec = format!("HEAD:refs/heads/{}", branch);
+        let output = self.run(&["push", "--quiet", REMOTE_NAME, &refspec])?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("[rejected]") || stderr.contains("non-fast-forward") {
+            return Err(GitError::PushRejected(branch));
+        }
+        Err(command_error(&["push"], &output))
+    }
+
+    /// Commit de lo pendiente, pull y push (si el pull no deja conflictos)
+    pub fn sync(&self, config: &GitConfig) -> Result<PullOutcome> {
+        self.auto_commit(&config.message_template)?;
+        let outcome = self.pull(config.branch.as_deref())?;
+        if !matches!(outcome, PullOutcome::Conflicts(_)) {
+            self.push(config.branch.as_deref())?;
+        }
+        Ok(outcome)
+    }
+
+    /// Si hay una fusión a medias
+    pub fn is_merging(&self) -> bool {
+        self.has_ref("MERGE_HEAD")
+    }
+
+    /// Ficheros en conflicto de la fusión en curso
+    pub fn conflicts(&self) -> Result<Vec<Conflict>> {
+        let output = self.git(&["diff", "--name-only", "-z", "--diff-filter=U"])?;
+        let mut conflicts = Vec::new();
+        for path in split_nul(&output) {
+            let stage = |number: u8| {
+                self.run(&["show", &format!(":{}:{}", number, path)])
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            };
+            conflicts.push(Conflict {
+                ours: stage(2),
+                theirs: stage(3),
+                merged: std::fs::read_to_string(self.root.join(&path)).unwrap_or_default(),
+                path,
+            });
+        }
+        Ok(conflicts)
+    }
+
+    /// Resolver un conflicto (el fichero queda listo para terminar la fusión)
+    pub fn resolve(&self, path: &str, resolution: &Resolution) -> Result<()> {
+        let side = match resolution {
+            Resolution::Ours => "--ours",
+            Resolution::Theirs => "--theirs",
+            Resolution::Merged(content) => {
+                std::fs::write(self.root.join(path), content)?;
+                self.git(&["add", "--", path])?;
+                return Ok(());
+            }
+        };
+        let stage = if side == "--ours" { 2 } else { 3 };
+        let exists = self
+            .run(&["cat-file", "-e", &format!(":{}:{}", stage, path)])?
+            .status
+            .success();
+        if exists {
+            self.git(&["checkout", side, "--", path])?;
+            self.git(&["add", "--", path])?;
+        } else {
+            // Esa versión borró el fichero
+            self.git(&["rm", "--quiet", "--", path])?;
+        }
+        Ok(())
+    }
+
+    /// Confirmar la fusión una vez resueltos todos los conflictos
+    pub fn finish_merge(&self) -> Result<String> {
+        let pending = self.conflicts()?;
+        if !pending.is_empty() {
+            let paths: Vec<&str> = pending.iter().map(|c| c.path.as_str()).collect();
+            return Err(GitError::UnresolvedConflicts(paths.join(", ")));
+        }
+        self.git(&["commit", "--quiet", "--no-edit"])?;
+        Ok(self.git(&["rev-parse", "HEAD"])?.trim().to_string())
+    }
+
+    /// Cancelar la fusión y volver al estado anterior al pull
+    pub fn abort_merge(&self) -> Result<()> {
+        self.git(&["merge", "--abort"])?;
+        Ok(())
+    }
+}
+
+fn command_error(args: &[&str], output: &Output) -> GitError {
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let stderr = match stderr.as_str() {
+        "" => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        _ => stderr,
+    };
+    GitError::Command {
+        command: args.first().copied().unwrap_or_default().to_string(),
+        stderr,
+    }
+}
+
+fn split_nul(output: &str) -> Vec<String> {
+    output
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_message() {
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 3, 7)
+            .unwrap()
+            .and_hms_opt(9, 5, 0)
+            .unwrap();
+        let notes: Vec<String> = ["Diario/2025-03-07", "Plan"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(
+            commit_message(&default_message_template(), &notes, date),
+            "Actualizar 2025-03-07, Plan"
+        );
+        assert_eq!(
+            commit_message("{{date}} {{time}}: {{count}} notas", &notes, date),
+            "2025-03-07 09:05: 2 notas"
+        );
+        let many: Vec<String> = (1..=5).map(|i| format!("N{}", i)).collect();
+        assert_eq!(
+            commit_message("{{notes}}", &many, date),
+            "N1, N2, N3 y 2 más"
+        );
+        assert_eq!(commit_message("", &[], date), "Actualizar el vault");
+    }
+
+    #[test]
+    fn test_exclusive_operations_do_not_overlap() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let git = VaultGit::new(std::env::temp_dir());
+        let running = Arc::new(AtomicUsize::new(0));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let git = git.clone();
+                let running = running.clone();
+                std::thread::spawn(move || {
+                    git.exclusive(|_| {
+                        assert_eq!(running.fetch_add(1, Ordering::SeqCst), 0);
+                        std::thread::sleep(std::time::Duration::from_millis(10));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_history_sync_and_conflicts() {
+        let root = std::env::temp_dir().join("notnative_vault_git_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let remote = root.join("remote.git");
+        std::fs::create_dir_all(&remote).unwrap();
+        let status = Command::new("git")
+            .args(["init", "--quiet", "--bare"])
+            .arg(&remote)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let remote_url = remote.to_string_lossy().to_string();
+
+        // Vault A: historial de una nota, sin papelera ni base de datos
+        let a_root = root.join("a");
+        std::fs::create_dir_all(a_root.join(".trash")).unwrap();
+        std::fs::write(a_root.join(".trash/Vieja.md"), "x").unwrap();
+        std::fs::write(a_root.join("notes.db"), "sqlite").unwrap();
+        std::fs::write(a_root.join("Plan.md"), "uno\n").unwrap();
+        let a = VaultGit::init(&a_root).unwrap();
+        let tracked = a.git(&["ls-files"]).unwrap();
+        assert_eq!(
+            tracked.lines().collect::<Vec<_>>(),
+            [".gitignore", "Plan.md"]
+        );
+
+        std::fs::write(a_root.join("Plan.md"), "uno\ndos\n").unwrap();
+        assert!(a.auto_commit("Actualizar {{notes}}").unwrap().is_some());
+        assert_eq!(a.auto_commit("x").unwrap(), None);
+        let log = a.note_log("Plan").unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].message, "Actualizar Plan");
+        assert!(a.note_diff(&log[0]).unwrap().contains("\n+dos\n"));
+        assert_eq!(a.restore_note("Plan", &log[1]).unwrap(), "uno\n");
+        // Lo devuelto es lo que quedó en disco (la app lo carga en el editor)
+        assert_eq!(
+            std::fs::read_to_string(a_root.join("Plan.md")).unwrap(),
+            "uno\n"
+        );
+        a.commit_all("Restaurar Plan").unwrap();
+
+        assert!(matches!(a.pull(None), Err(GitError::NoRemote)));
+        a.set_remote(&remote_url).unwrap();
+        assert_eq!(a.pull(None).unwrap(), PullOutcome::UpToDate);
+        a.push(None).unwrap();
+        let branch = a.current_branch().unwrap();
+
+        // Vault B con sus propias notas: recibe las de A
+        let b_root = root.join("b");
+        std::fs::create_dir_all(&b_root).unwrap();
+        std::fs::write(b_root.join("Ideas.md"), "idea\n").unwrap();
+        let b = VaultGit::init(&b_root).unwrap();
+        b.set_remote(&remote_url).unwrap();
+        assert_eq!(
+            b.pull(Some(&branch)).unwrap(),
+            PullOutcome::Updated(vec!["Plan".to_string()])
+        );
+        assert_eq!(
+            std::fs::read_to_string(b_root.join("Plan.md")).unwrap(),
+            "uno\n"
+        );
+        b.push(Some(&branch)).unwrap();
+
+        // Cambios a la vez en la misma línea: conflicto
+        std::fs::write(b_root.join("Plan.md"), "uno (B)\n").unwrap();
+        b.auto_commit("B").unwrap();
+        b.push(Some(&branch)).unwrap();
+        std::fs::write(a_root.join("Plan.md"), "uno (A)\n").unwrap();
+        a.auto_commit("A").unwrap();
+        assert!(matches!(a.push(None), Err(GitError::PushRejected(_))));
+
+        let PullOutcome::Conflicts(conflicts) = a.pull(None).unwrap() else {
+            panic!("se esperaba un conflicto");
+        };
+        assert!(a.is_merging());
+        assert_eq!(a.auto_commit("x").unwrap(), None);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].note_name(), Some("Plan"));
+        assert_eq!(conflicts[0].ours.as_deref(), Some("uno (A)\n"));
+        assert_eq!(conflicts[0].theirs.as_deref(), Some("uno (B)\n"));
+        assert!(conflicts[0].merged.contains("<<<<<<<"));
+        assert!(matches!(
+            a.finish_merge(),
+            Err(GitError::UnresolvedConflicts(_))
+        ));
+        a.resolve("Plan.md", &Resolution::Theirs).unwrap();
+        a.finish_merge().unwrap();
+        assert!(!a.is_merging());
+        assert_eq!(
+            std::fs::read_to_string(a_root.join("Plan.md")).unwrap(),
+            "uno (B)\n"
+        );
+        assert!(a_root.join("Ideas.md").exists());
+        a.push(None).unwrap();
+
+        // Sync: commit de lo pendiente, pull y push
+        let config = GitConfig {
+            branch: Some(branch.clone()),
+            ..GitConfig::default()
+        };
+        std::fs::write(b_root.join("Nueva.md"), "nueva\n").unwrap();
+        assert_eq!(b.sync(&config).unwrap(), PullOutcome::Updated(vec![]));
+        assert_eq!(
+            a.sync(&config).unwrap(),
+            PullOutcome::Updated(vec!["Nueva".to_string()])
+        );
+        assert_eq!(a.note_log("Nueva").unwrap()[0].message, "Actualizar Nueva");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
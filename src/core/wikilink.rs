@@ -0,0 +1,461 @@
+//! Enlaces internos con destino y transclusión
+//!
+//! ```text
+//! [[Nota]]                   nota completa
+//! [[Nota#Encabezado]]        sección de un encabezado
+//! [[Nota#^a1b2c3]]           bloque con ID estable (`texto ^a1b2c3`)
+//! [[Nota#Encabezado|texto]]  con texto alternativo
+//! ![[Nota#Encabezado]]       transclusión: el preview muestra el contenido
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+
+use super::note_file::{NoteFile, NotesDirectory};
+use super::outline::{Outline, frontmatter_lines};
+
+/// `[[...]]` y `![[...]]` (grupo 1: `!`, grupo 2: contenido)
+pub static WIKI_LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(!?)\[\[([^\[\]\n]+)\]\]").unwrap());
+
+/// ID de bloque al final de una línea (`texto ^a1b2c3`) o en una línea propia
+static BLOCK_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[ \t])\^([A-Za-z0-9-]+)[ \t]*$").unwrap());
+
+/// Longitud de los IDs de bloque generados
+const BLOCK_ID_LEN: usize = 6;
+
+/// Parte de la nota a la que apunta un enlace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    Heading(String),
+    Block(String),
+}
+
+impl LinkTarget {
+    /// Ancla para `ScrollToAnchor`: ID del encabezado o `^bloque`
+    pub fn anchor(&self) -> String {
+        match self {
+            LinkTarget::Heading(heading) => heading_id(heading),
+            LinkTarget::Block(id) => format!("^{}", id),
+        }
+    }
+}
+
+/// Enlace interno ya interpretado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    /// Nota destino (vacío = la propia nota, `[[#Encabezado]]`)
+    pub note: String,
+    pub target: Option<LinkTarget>,
+    pub alias: Option<String>,
+    /// `![[...]]`
+    pub embed: bool,
+}
+
+impl WikiLink {
+    /// Interpretar el contenido entre `[[` y `]]`
+    pub fn parse(inner: &str) -> Self {
+        let (link, alias) = match inner.split_once('|') {
+            Some((link, alias)) => (link, Some(alias.trim().to_string())),
+            None => (inner, None),
+        };
+        let (note, target) = match link.split_once('#') {
+            Some((note, target)) => {
+                let target = target.trim();
+                let target = match target.strip_prefix('^') {
+                    Some(id) => LinkTarget::Block(id.to_string()),
+                    None => LinkTarget::Heading(target.to_string()),
+                };
+                (note, Some(target))
+            }
+            None => (link, None),
+        };
+        Self {
+            note: note.trim().to_string(),
+            target,
+            alias: alias.filter(|alias| !alias.is_empty()),
+            embed: false,
+        }
+    }
+
+    /// Destino sin alias (`Nota#Encabezado`), como lo recibe `OpenNoteLink`
+    pub fn destination(&self) -> String {
+        match &self.target {
+            Some(LinkTarget::Heading(heading)) => format!("{}#{}", self.note, heading),
+            Some(LinkTarget::Block(id)) => format!("{}#^{}", self.note, id),
+            None => self.note.clone(),
+        }
+    }
+
+    /// Texto visible: el alias o `Nota › Encabezado`
+    pub fn label(&self) -> String {
+        if let Some(alias) = &self.alias {
+            return alias.clone();
+        }
+        match &self.target {
+            Some(LinkTarget::Heading(heading)) if self.note.is_empty() => heading.clone(),
+            Some(LinkTarget::Heading(heading)) => format!("{} › {}", self.note, heading),
+            Some(LinkTarget::Block(id)) if self.note.is_empty() => format!("^{}", id),
+            Some(LinkTarget::Block(id)) => format!("{} › ^{}", self.note, id),
+            None => self.note.clone(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let alias = self
+            .alias
+            .as_ref()
+            .map(|alias| format!("|{}", alias))
+            .unwrap_or_default();
+        let bang = if self.embed { "!" } else { "" };
+        format!("{}[[{}{}]]", bang, self.destination(), alias)
+    }
+}
+
+/// Enlaces de un texto con su rango en bytes (fuera de bloques de código)
+pub fn find_links(text: &str) -> Vec<(Range<usize>, WikiLink)> {
+    let mut links = Vec::new();
+    let mut offset = 0;
+    let mut in_code_block = false;
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block {
+            for caps in WIKI_LINK_RE.captures_iter(line) {
+                let whole = caps.get(0).unwrap();
+                let mut link = WikiLink::parse(&caps[2]);
+                link.embed = !caps[1].is_empty();
+                links.push((offset + whole.start()..offset + whole.end(), link));
+            }
+        }
+        offset += line.len();
+    }
+    links
+}
+
+/// ID de un encabezado para anclas: minúsculas, espacios a guiones y sin signos
+pub fn heading_id(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() {
+                Some(c)
+            } else if c.is_whitespace() || c == '-' {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// ID de bloque de una línea, si lo tiene
+pub fn block_id(line: &str) -> Option<&str> {
+    BLOCK_ID_RE
+        .captures(line.trim_end_matches(['\n', '\r']))
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str())
+}
+
+/// Línea (desde 0) de un ancla: `^bloque` o ID de encabezado
+pub fn anchor_line(text: &str, anchor: &str) -> Option<usize> {
+    match anchor.strip_prefix('^') {
+        Some(id) => text.lines().position(|line| block_id(line) == Some(id)),
+        None => {
+            let anchor = heading_id(anchor);
+            Outline::parse(text)
+                .headings()
+                .iter()
+                .find(|heading| heading_id(&heading.title) == anchor)
+                .map(|heading| heading.line)
+        }
+    }
+}
+
+/// Contenido al que apunta un enlace: la nota sin frontmatter, la sección del
+/// encabezado (con sus subsecciones) o el bloque. Sin los marcadores `^id`.
+pub fn extract(text: &str, target: Option<&LinkTarget>) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    let range = match target {
+        None => frontmatter_lines(text).min(lines.len())..lines.len(),
+        Some(LinkTarget::Heading(heading)) => {
+            let outline = Outline::parse(text);
+            let wanted = heading_id(heading);
+            let index = outline
+                .headings()
+                .iter()
+                .position(|h| heading_id(&h.title) == wanted)?;
+            let range = outline.section_lines(index)?;
+            range.start..range.end.min(lines.len())
+        }
+        Some(LinkTarget::Block(id)) => {
+            let line = lines
+                .iter()
+                .position(|line| block_id(line) == Some(id.as_str()))?;
+            block_range(&lines, line)
+        }
+    };
+
+    let content: Vec<String> = lines[range]
+        .iter()
+        .map(|line| match BLOCK_ID_RE.find(line) {
+            Some(marker) => line[..marker.start()].to_string(),
+            None => line.to_string(),
+        })
+        .collect();
+    Some(content.join("\n").trim_matches('\n').to_string())
+}
+
+/// Líneas del bloque marcado en `line`: el elemento de lista (con sus hijos), el
+/// párrafo que termina ahí o, si el ID va en una línea propia, el bloque anterior
+fn block_range(lines: &[&str], line: usize) -> Range<usize> {
+    let current = lines[line];
+    let is_blank = |line: &str| line.trim().is_empty();
+
+    if current.trim_start().starts_with('^') && line > 0 {
+        let start = (0..line)
+            .rev()
+            .take_while(|&i| !is_blank(lines[i]))
+            .last()
+            .unwrap_or(line);
+        return start..line;
+    }
+
+    if is_list_item(current) {
+        let indent = indentation(current);
+        let end = (line + 1..lines.len())
+            .find(|&i| is_blank(lines[i]) || indentation(lines[i]) <= indent)
+            .unwrap_or(lines.len());
+        return line..end;
+    }
+
+    let start = (0..line)
+        .rev()
+        .take_while(|&i| !is_blank(lines[i]) && !is_list_item(lines[i]))
+        .last()
+        .unwrap_or(line);
+    start..line + 1
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    ["- ", "* ", "+ "].iter().any(|p| trimmed.starts_with(p))
+        || trimmed
+            .split_once(". ")
+            .is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Destino para enlazar la línea `line`: el encabezado si lo es, o su bloque.
+/// Si la línea no tenía ID se le añade uno y se devuelve el texto nuevo.
+pub fn reference_at(text: &str, line: usize) -> Option<(LinkTarget, Option<String>)> {
+    let outline = Outline::parse(text);
+    if let Some(index) = outline.heading_at(line) {
+        let title = outline.headings()[index].title.clone();
+        return Some((LinkTarget::Heading(title), None));
+    }
+
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let current = *lines.get(line)?;
+    if current.trim().is_empty() {
+        return None;
+    }
+    if let Some(id) = block_id(current) {
+        return Some((LinkTarget::Block(id.to_string()), None));
+    }
+
+    let id = new_block_id(text, line);
+    let (body, newline) = match current.strip_suffix('\n') {
+        Some(body) => (body.trim_end(), "\n"),
+        None => (current.trim_end(), ""),
+    };
+    let mut updated = String::with_capacity(text.len() + BLOCK_ID_LEN + 2);
+    for (i, l) in lines.iter().enumerate() {
+        if i == line {
+            updated.push_str(&format!("{} ^{}{}", body, id, newline));
+        } else {
+            updated.push_str(l);
+        }
+    }
+    Some((LinkTarget::Block(id), Some(updated)))
+}
+
+/// ID de bloque nuevo que no se repite en la nota (`a1b2c3`)
+fn new_block_id(text: &str, line: usize) -> String {
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    (0u64..)
+        .map(|attempt| {
+            let mut hasher = DefaultHasher::new();
+            (text, line, nanos, attempt).hash(&mut hasher);
+            let mut value = hasher.finish();
+            (0..BLOCK_ID_LEN)
+                .map(|_| {
+                    let c = ALPHABET[(value % ALPHABET.len() as u64) as usize] as char;
+                    value /= ALPHABET.len() as u64;
+                    c
+                })
+                .collect::<String>()
+        })
+        .find(|id| !text.lines().any(|l| block_id(l) == Some(id.as_str())))
+        .unwrap()
+}
+
+/// Reescribir los enlaces a una nota renombrada (`Carpeta/Vieja` → `Carpeta/Nueva`).
+///
+/// Los enlaces con carpeta se actualizan con la ruta nueva y los que solo usan el
+/// nombre, con el nombre nuevo. Devuelve None si el texto no enlaza a la nota.
+pub fn rename_links(text: &str, old_name: &str, new_name: &str) -> Option<String> {
+    let base = |name: &str| name.rsplit('/').next().unwrap_or(name).to_string();
+    let (old_base, new_base) = (base(old_name), base(new_name));
+
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for (range, mut link) in find_links(text) {
+        let renamed = if link.note == old_name {
+            new_name.to_string()
+        } else if !link.note.contains('/') && link.note == old_base {
+            new_base.clone()
+        } else {
+            continue;
+        };
+        link.note = renamed;
+        result.push_str(&text[last..range.start]);
+        result.push_str(&link.to_markdown());
+        last = range.end;
+    }
+    if last == 0 {
+        return None;
+    }
+    result.push_str(&text[last..]);
+    Some(result)
+}
+
+/// Actualizar los enlaces de todas las notas tras renombrar una.
+/// Devuelve las notas modificadas (ya guardadas) con su contenido nuevo.
+pub fn update_links_after_rename(
+    notes_dir: &NotesDirectory,
+    old_name: &str,
+    new_name: &str,
+) -> anyhow::Result<Vec<(NoteFile, String)>> {
+    let mut changed = Vec::new();
+    for note in notes_dir.list_notes()? {
+        let Ok(text) = note.read() else {
+            continue;
+        };
+        if let Some(updated) = rename_links(&text, old_name, new_name) {
+            note.write(&updated)?;
+            changed.push((note, updated));
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntags: [acta]\n---\n# Reunión\n\nIntro.\n\n## Decisiones clave\n\nUsar Rust.\nSin excepciones. ^dec001\n\n- [ ] Migrar ^task01\n  - detalle\n- Otro\n\n| a | b |\n^tabla1\n\n## Siguiente\n\nFin.\n";
+
+    #[test]
+    fn test_parse_and_format() {
+        let link = WikiLink::parse("Actas/Reunión#Decisiones clave|decisiones");
+        assert_eq!(link.note, "Actas/Reunión");
+        assert_eq!(
+            link.target,
+            Some(LinkTarget::Heading("Decisiones clave".into()))
+        );
+        assert_eq!(link.label(), "decisiones");
+        assert_eq!(link.target.as_ref().unwrap().anchor(), "decisiones-clave");
+        assert_eq!(
+            link.to_markdown(),
+            "[[Actas/Reunión#Decisiones clave|decisiones]]"
+        );
+
+        let block = WikiLink::parse("Reunión#^dec001");
+        assert_eq!(block.target, Some(LinkTarget::Block("dec001".into())));
+        assert_eq!(block.label(), "Reunión › ^dec001");
+        assert_eq!(block.target.unwrap().anchor(), "^dec001");
+
+        let links = find_links("Ver ![[Reunión#Siguiente]] y [[#Intro]]\n```\n[[no]]\n```\n");
+        assert_eq!(links.len(), 2);
+        assert!(links[0].1.embed);
+        assert_eq!(links[0].0, 4..27);
+        assert_eq!(links[1].1.note, "");
+        assert_eq!(heading_id("¿Qué   pasó? (v2)"), "qué-pasó-v2");
+    }
+
+    #[test]
+    fn test_anchors_and_extract() {
+        assert_eq!(anchor_line(NOTE, "decisiones-clave"), Some(7));
+        assert_eq!(anchor_line(NOTE, "Decisiones clave"), Some(7));
+        assert_eq!(anchor_line(NOTE, "^task01"), Some(12));
+        assert_eq!(anchor_line(NOTE, "^nada"), None);
+
+        let heading = LinkTarget::Heading("Decisiones clave".into());
+        assert_eq!(
+            extract(NOTE, Some(&heading)).unwrap(),
+            "## Decisiones clave\n\nUsar Rust.\nSin excepciones.\n\n- [ ] Migrar\n  - detalle\n- Otro\n\n| a | b |"
+        );
+        let block = |id: &str| extract(NOTE, Some(&LinkTarget::Block(id.into())));
+        assert_eq!(block("dec001").unwrap(), "Usar Rust.\nSin excepciones.");
+        assert_eq!(block("task01").unwrap(), "- [ ] Migrar\n  - detalle");
+        assert_eq!(block("tabla1").unwrap(), "| a | b |");
+        assert!(block("nada").is_none());
+        assert!(
+            extract(NOTE, None)
+                .unwrap()
+                .starts_with("# Reunión\n\nIntro.")
+        );
+    }
+
+    #[test]
+    fn test_reference_at() {
+        let (target, updated) = reference_at(NOTE, 7).unwrap();
+        assert_eq!(target, LinkTarget::Heading("Decisiones clave".into()));
+        assert!(updated.is_none());
+
+        let (target, updated) = reference_at(NOTE, 12).unwrap();
+        assert_eq!(target, LinkTarget::Block("task01".into()));
+        assert!(updated.is_none());
+
+        let (target, updated) = reference_at(NOTE, 5).unwrap();
+        let LinkTarget::Block(id) = target else {
+            panic!("se esperaba un bloque");
+        };
+        assert_eq!(id.len(), BLOCK_ID_LEN);
+        let updated = updated.unwrap();
+        assert_eq!(
+            updated.lines().nth(5),
+            Some(format!("Intro. ^{}", id).as_str())
+        );
+        assert_eq!(anchor_line(&updated, &format!("^{}", id)), Some(5));
+        assert!(reference_at(NOTE, 4).is_none());
+    }
+
+    #[test]
+    fn test_rename_links() {
+        let text = "[[Reunión]], [[Actas/Reunión#^dec001|acuerdo]], ![[Reunión#Decisiones clave]], [[Reuniones]], [[Otra/Reunión]]";
+        assert_eq!(
+            rename_links(text, "Actas/Reunión", "Actas/Reunión 2025").unwrap(),
+            "[[Reunión 2025]], [[Actas/Reunión 2025#^dec001|acuerdo]], ![[Reunión 2025#Decisiones clave]], [[Reuniones]], [[Otra/Reunión]]"
+        );
+        assert!(rename_links(text, "Nada", "Algo").is_none());
+    }
+}
@@ -104,10 +104,29 @@ impl I18n {
         );
         translations.insert("create", ("Crear", "Create"));
         translations.insert("cancel", ("Cancelar", "Cancel"));
+        translations.insert("save", ("Guardar", "Save"));
         translations.insert("rename", ("Renombrar", "Rename"));
         translations.insert("delete", ("Eliminar", "Delete"));
         translations.insert("change_icon", ("Cambiar icono", "Change icon"));
         translations.insert("view_history", ("Ver historial", "View history"));
+        translations.insert(
+            "saved_search_created",
+            ("Búsqueda guardada:", "Search saved:"),
+        );
+        translations.insert(
+            "saved_search_dialog_title",
+            ("Búsqueda guardada", "Saved search"),
+        );
+        translations.insert("saved_search_name", ("Nombre", "Name"));
+        translations.insert("saved_search_query", ("Búsqueda", "Search"));
+        translations.insert("edit_saved_search", ("Editar búsqueda", "Edit search"));
+        translations.insert(
+            "saved_search_exists",
+            (
+                "Ya existe una búsqueda guardada con ese nombre",
+                "A saved search with that name already exists",
+            ),
+        );
         translations.insert(
             "open_in_file_manager",
             ("Abrir en explorador", "Open in file manager"),
@@ -1248,6 +1267,196 @@ impl I18n {
             "base_export_xlsx_error",
             ("Error al exportar", "Export error"),
         );
+        translations.insert(
+            "base_bulk_applied",
+            ("{} notas modificadas", "{} notes updated"),
+        );
+        translations.insert(
+            "base_bulk_undone",
+            (
+                "Deshecho: {} notas restauradas",
+                "Undone: {} notes restored",
+            ),
+        );
+        translations.insert(
+            "base_bulk_nothing_to_undo",
+            ("Nada que deshacer", "Nothing to undo"),
+        );
+        translations.insert(
+            "base_bulk_error",
+            ("Error en la operación masiva", "Bulk edit error"),
+        );
+        translations.insert("vim_recording", ("grabando @{}", "recording @{}"));
+        translations.insert(
+            "vim_macro_recorded",
+            ("Macro guardada en @{}", "Macro saved to @{}"),
+        );
+        translations.insert("ex_error", ("Error en el comando", "Command error"));
+        translations.insert("outline", ("Esquema", "Outline"));
+        translations.insert(
+            "outline_no_headings",
+            ("La nota no tiene encabezados", "This note has no headings"),
+        );
+        translations.insert(
+            "folder_template",
+            ("Plantilla de la carpeta…", "Folder template…"),
+        );
+        translations.insert(
+            "folder_template_hint",
+            (
+                "Las notas nuevas de '{}' (y de sus subcarpetas) se crearán con esta plantilla.",
+                "New notes in '{}' (and its subfolders) will be created from this template.",
+            ),
+        );
+        translations.insert("no_template", ("Sin plantilla", "No template"));
+        translations.insert(
+            "templates_location",
+            ("Plantillas en: {}", "Templates folder: {}"),
+        );
+        translations.insert(
+            "template_fields",
+            ("Campos de la plantilla", "Template fields"),
+        );
+        translations.insert(
+            "template_error",
+            ("Error en la plantilla: {}", "Template error: {}"),
+        );
+        translations.insert(
+            "spell_no_suggestions",
+            ("Sin sugerencias", "No suggestions"),
+        );
+        translations.insert(
+            "spell_add_to_dictionary",
+            ("Añadir al diccionario", "Add to dictionary"),
+        );
+        translations.insert("ex_changes", ("{} cambios", "{} changes"));
+        translations.insert(
+            "ex_note_not_found",
+            ("No existe la nota: {}", "Note not found: {}"),
+        );
+        translations.insert(
+            "ex_base_not_found",
+            ("No existe la base: {}", "Base not found: {}"),
+        );
+        translations.insert(
+            "periodic_not_open",
+            (
+                "La nota abierta no es una nota periódica",
+                "The open note is not a periodic note",
+            ),
+        );
+        translations.insert(
+            "periodic_error",
+            ("Error en la nota periódica: {}", "Periodic note error: {}"),
+        );
+        translations.insert("link_copied", ("Enlace copiado: {}", "Link copied: {}"));
+        translations.insert(
+            "copylink_empty_line",
+            (
+                "No hay nada que enlazar en esta línea",
+                "Nothing to link on this line",
+            ),
+        );
+        translations.insert(
+            "note_exported",
+            ("Nota exportada a {}", "Note exported to {}"),
+        );
+        translations.insert(
+            "git_enabled",
+            (
+                "Historial Git activado en el vault",
+                "Git history enabled for the vault",
+            ),
+        );
+        translations.insert(
+            "git_not_enabled",
+            (
+                "El historial Git no está activado (:git init)",
+                "Git history is not enabled (:git init)",
+            ),
+        );
+        translations.insert("git_committed", ("Commit {} creado", "Commit {} created"));
+        translations.insert(
+            "git_nothing_to_commit",
+            ("No hay cambios que confirmar", "Nothing to commit"),
+        );
+        translations.insert(
+            "git_remote_set",
+            ("Remoto configurado: {}", "Remote set: {}"),
+        );
+        translations.insert(
+            "git_syncing",
+            (
+                "Sincronizando con el remoto...",
+                "Syncing with the remote...",
+            ),
+        );
+        translations.insert("git_synced", ("Vault sincronizado", "Vault synced"));
+        translations.insert(
+            "git_pulled",
+            (
+                "{} notas actualizadas desde el remoto",
+                "{} notes updated from the remote",
+            ),
+        );
+        translations.insert(
+            "git_conflicts",
+            ("{} ficheros en conflicto", "{} files in conflict"),
+        );
+        translations.insert(
+            "git_no_conflicts",
+            (
+                "No hay ninguna fusión con conflictos",
+                "No merge with conflicts in progress",
+            ),
+        );
+        translations.insert(
+            "git_conflicts_title",
+            ("Resolver conflictos", "Resolve conflicts"),
+        );
+        translations.insert("git_ours", ("Mi versión", "My version"));
+        translations.insert("git_theirs", ("Versión remota", "Remote version"));
+        translations.insert("git_merged", ("Resultado (editable)", "Result (editable)"));
+        translations.insert("git_deleted", ("(borrada)", "(deleted)"));
+        translations.insert("git_use_ours", ("Usar la mía", "Use mine"));
+        translations.insert("git_use_theirs", ("Usar la remota", "Use remote"));
+        translations.insert("git_use_merged", ("Guardar edición", "Save edit"));
+        translations.insert("git_finish_merge", ("Terminar fusión", "Finish merge"));
+        translations.insert("git_abort_merge", ("Cancelar fusión", "Abort merge"));
+        translations.insert(
+            "git_merge_finished",
+            (
+                "Fusión terminada (:git push para subirla)",
+                "Merge finished (:git push to upload it)",
+            ),
+        );
+        translations.insert("git_merge_aborted", ("Fusión cancelada", "Merge aborted"));
+        translations.insert(
+            "git_restored",
+            (
+                "Nota restaurada a la versión {}",
+                "Note restored to version {}",
+            ),
+        );
+        translations.insert("git_view_diff", ("Ver cambios", "View changes"));
+        translations.insert(
+            "git_restore_version",
+            ("Restaurar esta versión", "Restore this version"),
+        );
+        translations.insert(
+            "git_no_versions",
+            (
+                "Esta nota aún no tiene versiones en el historial Git.",
+                "This note has no versions in the Git history yet.",
+            ),
+        );
+        translations.insert(
+            "links_updated",
+            (
+                "Enlaces actualizados en {} notas",
+                "Links updated in {} notes",
+            ),
+        );
         translations.insert(
             "base_show_graph",
             ("Mostrar grafo de relaciones", "Show relationships graph"),
@@ -140,6 +140,129 @@ fn load_theme_css() -> (String, bool) {
     (combined_css, theme_loaded)
 }
 
+const PUBLISH_USAGE: &str =
+    "Uso: notnative-app publish <carpeta> <salida> [--title TÍTULO] [--base-url URL] [--vault DIR]";
+
+/// `notnative-app publish`: publicar una carpeta del vault como sitio estático
+fn run_publish(args: &[String]) -> anyhow::Result<()> {
+    let mut positional = Vec::new();
+    let mut title = None;
+    let mut base_url = None;
+    let mut vault = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = match arg.as_str() {
+            "--title" => &mut title,
+            "--base-url" => &mut base_url,
+            "--vault" => &mut vault,
+            "-h" | "--help" => {
+                println!("{}", PUBLISH_USAGE);
+                return Ok(());
+            }
+            _ => {
+                positional.push(arg.clone());
+                continue;
+            }
+        };
+        *value = Some(
+            iter.next()
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Falta el valor de {}\n{}", arg, PUBLISH_USAGE))?,
+        );
+    }
+    let [folder, output] = positional.as_slice() else {
+        anyhow::bail!("{}", PUBLISH_USAGE);
+    };
+
+    let notes_root = match vault {
+        Some(vault) => std::path::PathBuf::from(vault),
+        None => crate::core::NotesDirectory::default().root().to_path_buf(),
+    };
+    let report = crate::core::SitePublisher::new(notes_root, folder.as_str())
+        .with_title(title)
+        .with_base_url(base_url)
+        .publish(std::path::Path::new(output))?;
+
+    println!(
+        "✅ Sitio publicado en {}: {} notas, {} tags",
+        report.output_dir.display(),
+        report.pages,
+        report.tags
+    );
+    if !report.excluded.is_empty() {
+        println!(
+            "   Excluidas (publish: false): {}",
+            report.excluded.join(", ")
+        );
+    }
+    Ok(())
+}
+
+const IMPORT_USAGE: &str = "Uso: notnative-app import <obsidian|notion|evernote|keep> <origen> [--folder CARPETA] [--vault DIR]";
+
+/// `notnative-app import`: importar notas de otra aplicación en el vault
+fn run_import(args: &[String]) -> anyhow::Result<()> {
+    let mut positional = Vec::new();
+    let mut folder = None;
+    let mut vault = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = match arg.as_str() {
+            "--folder" => &mut folder,
+            "--vault" => &mut vault,
+            "-h" | "--help" => {
+                println!("{}", IMPORT_USAGE);
+                return Ok(());
+            }
+            _ => {
+                positional.push(arg.clone());
+                continue;
+            }
+        };
+        *value = Some(
+            iter.next()
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Falta el valor de {}\n{}", arg, IMPORT_USAGE))?,
+        );
+    }
+    let [source, path] = positional.as_slice() else {
+        anyhow::bail!("{}", IMPORT_USAGE);
+    };
+    let source = crate::core::ImportSource::parse(source)?;
+
+    let notes_root = match vault {
+        Some(vault) => std::path::PathBuf::from(vault),
+        None => crate::core::NotesDirectory::default().root().to_path_buf(),
+    };
+    let folder = folder.unwrap_or_else(|| source.name().to_string());
+    let mut report = source.import(std::path::Path::new(path), &notes_root, Some(&folder))?;
+    if !report.bases.is_empty() {
+        let notes_dir = crate::core::NotesDirectory::new(&notes_root)?;
+        let db = crate::core::NotesDatabase::new(&notes_dir.db_path())?;
+        report.register_bases(&db)?;
+    }
+    let report_note = report.write_note(&notes_root, &folder, source)?;
+
+    println!(
+        "✅ {} notas importadas de {} en '{}' ({} adjuntos copiados)",
+        report.notes.len(),
+        source.name(),
+        folder,
+        report.attachments
+    );
+    for base in &report.bases {
+        println!("   Base '{}' creada en '{}'", base.name, base.folder);
+    }
+    if !report.issues.is_empty() {
+        println!(
+            "   {} elementos sin convertir: ver la nota '{}'",
+            report.issues.len(),
+            report_note
+        );
+    }
+    Ok(())
+}
+
 fn main() -> anyhow::Result<()> {
     // Build timestamp to verify fresh compilation
     println!(
@@ -147,6 +270,15 @@ fn main() -> anyhow::Result<()> {
         env!("BUILD_TIMESTAMP")
     );
 
+    // Subcomandos de línea de comandos: se ejecutan sin abrir la interfaz
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("publish") {
+        return run_publish(&args[1..]);
+    }
+    if args.first().map(String::as_str) == Some("import") {
+        return run_import(&args[1..]);
+    }
+
     // Single instance detection
     let lock_file_path = "/tmp/notnative.lock";
     let control_file_path = "/tmp/notnative.control";
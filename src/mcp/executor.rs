@@ -1,13 +1,37 @@
 use anyhow::Result;
 use serde_json::json;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 
-use crate::core::{NotesConfig, NotesDatabase, NotesDirectory};
+use crate::core::periodic::parse_date;
+use crate::core::wikilink::update_links_after_rename;
+use crate::core::{
+    BookCompiler, BookFormat, BookOptions, ExportError, ExportFormat, ExportNote, ImportSource,
+    Margins, MarkupMode, NoteExporter, NotesConfig, NotesDatabase, NotesDirectory, PageSetup,
+    PageSize, Period, PeriodicNote, Template, TemplateContext, order_notes,
+};
 use crate::i18n::I18n;
-use crate::mcp::tools::{MCPToolCall, MCPToolResult};
+use crate::mcp::tools::{ExportPageArgs, MCPToolCall, MCPToolResult};
+
+/// Exportación de una nota ya leída y con la ruta resuelta: `run` no toca el
+/// estado del ejecutor, así que se puede lanzar fuera del hilo de la UI
+pub struct NoteExportJob {
+    exporter: NoteExporter,
+    note: ExportNote,
+    format: ExportFormat,
+    output: PathBuf,
+}
+
+impl NoteExportJob {
+    /// Renderizar y escribir el fichero (el PDF puede tardar varios segundos)
+    pub fn run(self) -> Result<PathBuf, ExportError> {
+        self.exporter
+            .export_note(&self.note, self.format, &self.output)
+    }
+}
 
 /// Ejecutor de herramientas MCP
 #[derive(Debug, Clone)]
@@ -56,7 +80,24 @@ impl MCPToolExecutor {
                 name,
                 content,
                 folder,
-            } => self.create_note(&name, &content, folder.as_deref()),
+            } => {
+                // Sin contenido, la nota recibe la plantilla de su carpeta (si tiene)
+                let (note_folder, _) = split_note_name(&name, folder.as_deref());
+                let folder_template = self
+                    .notes_config
+                    .borrow()
+                    .template_for_folder(note_folder)
+                    .map(str::to_string);
+                match folder_template {
+                    Some(template) if content.trim().is_empty() => self.create_note_from_template(
+                        &template,
+                        &name,
+                        folder.as_deref(),
+                        &Default::default(),
+                    ),
+                    _ => self.create_note(&name, &content, folder.as_deref()),
+                }
+            }
             MCPToolCall::ReadNote { name } => self.read_note(&name),
             MCPToolCall::UpdateNote { name, content } => self.update_note(&name, &content),
             MCPToolCall::AppendToNote { name, content } => self.append_to_note(&name, &content),
@@ -67,6 +108,97 @@ impl MCPToolExecutor {
             }
             MCPToolCall::DuplicateNote { name, new_name } => self.duplicate_note(&name, &new_name),
 
+            // === Exportación ===
+            MCPToolCall::ExportNote {
+                name,
+                format,
+                output_path,
+                page,
+            } => self.export_note(&name, &format, output_path.as_deref(), &page),
+            MCPToolCall::ExportMultipleNotes {
+                note_names,
+                format,
+                output_dir,
+                page,
+            } => {
+                let mut notes = Vec::with_capacity(note_names.len());
+                for name in &note_names {
+                    match self.notes_dir.find_note(name)? {
+                        Some(note) => notes.push(ExportNote::new(note.name(), note.read()?)),
+                        None => {
+                            return Ok(MCPToolResult::error(format!(
+                                "Nota '{}' no encontrada",
+                                name
+                            )));
+                        }
+                    }
+                }
+                self.export_notes(notes, &format, output_dir.as_deref(), &page)
+            }
+            MCPToolCall::ExportFolder {
+                folder,
+                format,
+                output_dir,
+                page,
+            } => {
+                let folder = folder.trim_matches('/');
+                let mut notes = Vec::new();
+                for note in self.notes_dir.list_notes()? {
+                    let in_folder = folder.is_empty()
+                        || note
+                            .name()
+                            .strip_prefix(folder)
+                            .is_some_and(|rest| rest.starts_with('/'));
+                    if in_folder {
+                        notes.push(ExportNote::new(note.name(), note.read()?));
+                    }
+                }
+                if notes.is_empty() {
+                    return Ok(MCPToolResult::error(format!(
+                        "La carpeta '{}' no tiene notas",
+                        folder
+                    )));
+                }
+                self.export_notes(notes, &format, output_dir.as_deref(), &page)
+            }
+            MCPToolCall::ExportBook {
+                notes,
+                folder,
+                format,
+                output_path,
+                title,
+                author,
+                language,
+                keep_tags,
+                keep_properties,
+                keep_reminders,
+                toc,
+            } => {
+                let mut options = BookOptions::default()
+                    .with_author(author)
+                    .with_tags(MarkupMode::from_keep(keep_tags))
+                    .with_properties(MarkupMode::from_keep(keep_properties))
+                    .with_reminders(MarkupMode::from_keep(keep_reminders))
+                    .with_toc(toc.unwrap_or(true));
+                if let Some(language) = language {
+                    options = options.with_language(language);
+                }
+                self.export_book(
+                    &notes,
+                    folder.as_deref(),
+                    &format,
+                    output_path.as_deref(),
+                    title,
+                    options,
+                )
+            }
+
+            MCPToolCall::ImportNotes {
+                source,
+                path,
+                folder,
+            } => self.import_notes(&source, &path, folder.as_deref()),
+
             // === Búsqueda ===
             MCPToolCall::SearchNotes { query } => self.search_notes(&query),
             MCPToolCall::GetNotesWithTag { tag } => self.get_notes_with_tag(&tag),
@@ -124,6 +256,21 @@ impl MCPToolExecutor {
             MCPToolCall::CreateDailyNote { template } => {
                 self.create_daily_note(template.as_deref())
             }
+            MCPToolCall::CreateNoteFromTemplate {
+                template,
+                name,
+                folder,
+                values,
+            } => self.create_note_from_template(&template, &name, folder.as_deref(), &values),
+            MCPToolCall::ListTemplates { .. } => self.list_templates(),
+            MCPToolCall::CreatePeriodicNote {
+                period,
+                date,
+                offset,
+            } => self.create_periodic_note(&period, date.as_deref(), offset.unwrap_or(0)),
+            MCPToolCall::ListPeriodicNotes { period, limit } => {
+                self.list_periodic_notes(&period, limit.unwrap_or(20))
+            }
             MCPToolCall::FindAndReplace {
                 find,
                 replace,
@@ -214,7 +361,21 @@ impl MCPToolExecutor {
                 base_name,
                 view_name,
                 view_type,
-            } => self.add_base_view(&base_name, &view_name, view_type.as_deref()),
+                chart_type,
+                x_property,
+                y_property,
+                aggregate,
+            } => {
+                let chart = x_property.map(|x| {
+                    Self::build_chart_config(
+                        chart_type.as_deref(),
+                        &x,
+                        y_property.as_deref(),
+                        aggregate.as_deref(),
+                    )
+                });
+                self.add_base_view(&base_name, &view_name, view_type.as_deref(), chart)
+            }
 
             MCPToolCall::SetBaseColumns { base_name, columns } => {
                 self.set_base_columns(&base_name, &columns)
@@ -224,6 +385,37 @@ impl MCPToolExecutor {
                 self.discover_properties(folder.as_deref())
             }
 
+            // === Búsquedas guardadas ===
+            MCPToolCall::CreateSavedSearch {
+                name,
+                query,
+                text,
+                tags,
+                folder,
+                properties,
+            } => {
+                // Los campos explícitos completan/sobrescriben la query en texto
+                let mut search = crate::core::SearchQuery::parse(query.as_deref().unwrap_or(""));
+                if text.is_some() {
+                    search.text = text;
+                }
+                search.tags.extend(
+                    tags.into_iter()
+                        .map(|t| t.trim_start_matches('#').to_lowercase()),
+                );
+                if folder.is_some() {
+                    search.folder = folder;
+                }
+                search.properties.extend(properties);
+                self.create_saved_search(&name, &search)
+            }
+
+            MCPToolCall::ListSavedSearches { .. } => self.list_saved_searches(),
+
+            MCPToolCall::GetSavedSearchNotes { name } => self.get_saved_search_notes(&name),
+
+            MCPToolCall::DeleteSavedSearch { name } => self.delete_saved_search(&name),
+
             // === UI - DESHABILITADAS (pendiente de implementar) ===
             // MCPToolCall::OpenNote { .. }
             // | MCPToolCall::ShowNotification { .. }
@@ -252,23 +444,7 @@ impl MCPToolExecutor {
         content: &str,
         folder: Option<&str>,
     ) -> Result<MCPToolResult> {
-        // Quitar extensión .md si ya existe (create_note la agrega automáticamente)
-        let clean_name = name.strip_suffix(".md").unwrap_or(name);
-
-        // Si el nombre ya contiene la ruta de carpeta (ej: "test/nota"), extraer solo el nombre
-        let (actual_folder, base_name) = if clean_name.contains('/') {
-            let parts: Vec<&str> = clean_name.rsplitn(2, '/').collect();
-            if parts.len() == 2 {
-                (Some(parts[1]), parts[0])
-            } else {
-                (None, clean_name)
-            }
-        } else {
-            (None, clean_name)
-        };
-
-        // Determinar la carpeta final: priorizar la extraída del nombre
-        let final_folder = actual_folder.or(folder);
+        let (final_folder, base_name) = split_note_name(name, folder);
 
         // Si se especifica una carpeta, crear el archivo directamente en esa carpeta
         let file_path = if let Some(folder_name) = final_folder {
@@ -880,6 +1056,30 @@ impl MCPToolExecutor {
             eprintln!("⚠️ Error actualizando BD después de renombrar nota: {}", e);
         }
 
+        // Reescribir los [[enlaces]] que apuntaban a la nota renombrada
+        let full_name = |name: &str| match folder {
+            Some(folder) => format!("{}/{}", folder, name),
+            None => name.to_string(),
+        };
+        let old_base = old_name.rsplit('/').next().unwrap_or(old_name);
+        let updated_notes = update_links_after_rename(
+            &self.notes_dir,
+            &full_name(old_base.trim_end_matches(".md")),
+            &full_name(db_name),
+        )?;
+        for (note, content) in &updated_notes {
+            let note_folder = self.notes_dir.relative_folder(note.path());
+            if let Err(e) = self.notes_db.borrow().index_note(
+                note.name(),
+                note.path().to_str().unwrap_or(""),
+                content,
+                note_folder.as_deref(),
+            ) {
+                eprintln!("⚠️ Error reindexando '{}': {}", note.name(), e);
+            }
+        }
+        let updated_links: Vec<&str> = updated_notes.iter().map(|(n, _)| n.name()).collect();
+
         let result_message = if unique_new_name != new_name {
             format!(
                 "Nota renombrada de '{}' a '{}' (el nombre '{}' ya existía)",
@@ -894,7 +1094,8 @@ impl MCPToolExecutor {
         Ok(MCPToolResult::success(json!({
             "message": result_message,
             "old_name": old_name,
-            "new_name": db_name
+            "new_name": db_name,
+            "updated_links": updated_links
         })))
     }
 
@@ -1108,6 +1309,8 @@ impl MCPToolExecutor {
         name: &str,
         max_level: Option<i32>,
     ) -> Result<MCPToolResult> {
+        use crate::core::Outline;
+
         let max_level = max_level.unwrap_or(3);
         let note = self
             .notes_dir
@@ -1118,15 +1321,13 @@ impl MCPToolExecutor {
         let mut toc = String::new();
         toc.push_str("## Tabla de Contenidos\n\n");
 
-        for line in content.lines() {
-            if line.starts_with('#') {
-                let level = line.chars().take_while(|c| *c == '#').count();
-                if level as i32 <= max_level {
-                    let title = line.trim_start_matches('#').trim();
-                    let indent = "  ".repeat(level - 1);
-                    let anchor = title.to_lowercase().replace(' ', "-");
-                    toc.push_str(&format!("{}- [{}](#{})\n", indent, title, anchor));
-                }
+        // Mismos encabezados que el panel de outline (sin #tags ni código)
+        let outline = Outline::parse(&content);
+        for heading in outline.headings() {
+            if heading.level as i32 <= max_level {
+                let indent = "  ".repeat(heading.level as usize - 1);
+                let anchor = heading.title.to_lowercase().replace(' ', "-");
+                toc.push_str(&format!("{}- [{}](#{})\n", indent, heading.title, anchor));
             }
         }
 
@@ -1232,19 +1433,226 @@ impl MCPToolExecutor {
     fn create_daily_note(&self, template: Option<&str>) -> Result<MCPToolResult> {
         use chrono::Local;
 
+        // Sin plantilla explícita es la nota diaria de las notas periódicas
+        let Some(tmpl) = template else {
+            return self.create_periodic_note("daily", None, 0);
+        };
+
         let today = Local::now().format("%Y-%m-%d").to_string();
-        let name = format!("{}.md", today);
+        let note = self
+            .notes_config
+            .borrow()
+            .periodic_notes
+            .today(Period::Daily);
+
+        let content = {
+            // `{date}` es la sintaxis antigua; el resto lo resuelve el motor de plantillas
+            let rendered = match Template::parse(tmpl) {
+                Ok(parsed) => {
+                    let context = TemplateContext::new(today.clone())
+                        .with_language(self.i18n.borrow().current_language().code());
+                    parsed.render(&context).content
+                }
+                Err(_) => tmpl.to_string(),
+            };
+            rendered.replace("{date}", &today)
+        };
 
-        let content = if let Some(tmpl) = template {
-            tmpl.replace("{date}", &today)
-        } else {
-            format!(
-                "# Daily Note - {}\n\n## Tareas\n\n- [ ] \n\n## Notas\n\n",
-                today
+        self.create_note(&note.title, &content, note.folder.as_deref())
+    }
+
+    /// Abrir (creando si no existe) la nota diaria/semanal/mensual/anual de una fecha
+    fn create_periodic_note(
+        &self,
+        period: &str,
+        date: Option<&str>,
+        offset: i32,
+    ) -> Result<MCPToolResult> {
+        let period = match Period::parse(period) {
+            Ok(period) => period,
+            Err(e) => return Ok(MCPToolResult::error(e.to_string())),
+        };
+        let date = match date {
+            Some(date) => match parse_date(date) {
+                Ok(date) => date,
+                Err(e) => return Ok(MCPToolResult::error(e.to_string())),
+            },
+            None => chrono::Local::now().date_naive(),
+        };
+
+        let config = self.notes_config.borrow();
+        let periodic = &config.periodic_notes;
+        let note = match periodic.shifted(&periodic.note(period, date), offset) {
+            Ok(note) => note,
+            Err(e) => return Ok(MCPToolResult::error(e.to_string())),
+        };
+        let library = config.template_library(self.notes_dir.root());
+        let language = self.i18n.borrow().current_language().code().to_string();
+        let ensured = match periodic.ensure(self.notes_dir.root(), &library, &note, &language) {
+            Ok(ensured) => ensured,
+            Err(e) => return Ok(MCPToolResult::error(e.to_string())),
+        };
+
+        if ensured.created
+            && let Err(e) = self.notes_db.borrow().index_note(
+                &note.title,
+                ensured.path.to_str().unwrap_or(""),
+                &ensured.content,
+                note.folder.as_deref(),
             )
+        {
+            eprintln!("Error indexando nota: {}", e);
+        }
+
+        let name = note.name();
+        let message = if ensured.created {
+            self.i18n
+                .borrow()
+                .t("mcp_note_created")
+                .replace("{}", &name)
+        } else {
+            format!("✓ {}", name)
         };
+        Ok(MCPToolResult::success(json!({
+            "note_name": name,
+            "message": message,
+            "period": period.as_str(),
+            "start_date": note.date.format("%Y-%m-%d").to_string(),
+            "created": ensured.created,
+            "path": ensured.path.to_str().unwrap_or(""),
+            "content": ensured.content,
+            "previous": periodic.shifted(&note, -1).ok().map(|previous| previous.name()),
+            "next": periodic.shifted(&note, 1).ok().map(|next| next.name()),
+            "parent": periodic.parent(&note).map(|parent| parent.name()),
+        })))
+    }
 
-        self.create_note(&name, &content, None)
+    /// Notas periódicas existentes de un tipo, de la más reciente a la más antigua
+    fn list_periodic_notes(&self, period: &str, limit: usize) -> Result<MCPToolResult> {
+        let period = match Period::parse(period) {
+            Ok(period) => period,
+            Err(e) => return Ok(MCPToolResult::error(e.to_string())),
+        };
+
+        let config = self.notes_config.borrow();
+        let periodic = &config.periodic_notes;
+        let folder = periodic.get(period).folder.trim_matches('/').to_string();
+        let mut notes: Vec<PeriodicNote> = std::fs::read_dir(self.notes_dir.root().join(&folder))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| {
+                        let file_name = entry.file_name().to_string_lossy().to_string();
+                        let stem = file_name.strip_suffix(".md")?;
+                        let name = if folder.is_empty() {
+                            stem.to_string()
+                        } else {
+                            format!("{}/{}", folder, stem)
+                        };
+                        periodic
+                            .note_for_name(&name)
+                            .filter(|note| note.period == period)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        notes.sort_by(|a, b| b.date.cmp(&a.date));
+        let total = notes.len();
+
+        let list: Vec<_> = notes
+            .iter()
+            .take(limit)
+            .map(|note| {
+                json!({
+                    "name": note.name(),
+                    "start_date": note.date.format("%Y-%m-%d").to_string(),
+                })
+            })
+            .collect();
+
+        Ok(MCPToolResult::success(json!({
+            "message": format!("✓ {} notas ({})", total, period.as_str()),
+            "period": period.as_str(),
+            "folder": folder,
+            "notes": list,
+            "total": total
+        })))
+    }
+
+    fn create_note_from_template(
+        &self,
+        template: &str,
+        name: &str,
+        folder: Option<&str>,
+        values: &BTreeMap<String, String>,
+    ) -> Result<MCPToolResult> {
+        let library = self
+            .notes_config
+            .borrow()
+            .template_library(self.notes_dir.root());
+        let parsed = match library.load(template) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                return Ok(MCPToolResult::error(format!(
+                    "No se pudo cargar la plantilla '{}': {}",
+                    template, e
+                )));
+            }
+        };
+
+        let (note_folder, base_name) = split_note_name(name, folder);
+        let context = TemplateContext::new(base_name)
+            .with_folder(note_folder.map(str::to_string))
+            .with_language(self.i18n.borrow().current_language().code())
+            .with_values(values.clone().into_iter().collect());
+        let rendered = parsed.render(&context);
+
+        let unfilled: Vec<String> = parsed
+            .prompts()
+            .into_iter()
+            .filter(|field| field.default.is_none() && !values.contains_key(&field.name))
+            .map(|field| field.name)
+            .collect();
+
+        let mut result = self.create_note(name, &rendered.content, folder)?;
+        if let Some(data) = result.data.as_mut() {
+            data["template"] = json!(template);
+            data["unfilled_fields"] = json!(unfilled);
+        }
+        Ok(result)
+    }
+
+    fn list_templates(&self) -> Result<MCPToolResult> {
+        let config = self.notes_config.borrow();
+        let library = config.template_library(self.notes_dir.root());
+
+        let templates: Vec<_> = library
+            .list()
+            .into_iter()
+            .map(|name| {
+                let fields: Vec<_> = library
+                    .load(&name)
+                    .map(|t| t.prompts())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|field| json!({ "name": field.name, "default": field.default }))
+                    .collect();
+                let folders: Vec<_> = config
+                    .folder_templates
+                    .iter()
+                    .filter(|(_, template)| **template == name)
+                    .map(|(folder, _)| folder.clone())
+                    .collect();
+                json!({ "name": name, "fields": fields, "folders": folders })
+            })
+            .collect();
+
+        Ok(MCPToolResult::success(json!({
+            "message": format!("✓ {} plantillas", templates.len()),
+            "templates_folder": library.dir().display().to_string(),
+            "templates": templates,
+            "total": templates.len()
+        })))
     }
 
     fn find_and_replace(
@@ -2507,7 +2915,8 @@ impl MCPToolExecutor {
                             "name": v.name,
                             "type": format!("{:?}", v.view_type),
                             "filters": v.filter.filters.len(),
-                            "columns": v.columns.iter().map(|c| &c.property).collect::<Vec<_>>()
+                            "columns": v.columns.iter().map(|c| &c.property).collect::<Vec<_>>(),
+                            "chart": v.chart
                         })
                     })
                     .collect();
@@ -2527,11 +2936,42 @@ impl MCPToolExecutor {
         }
     }
 
+    /// Construir la configuración de un gráfico a partir de los argumentos de AddBaseView
+    fn build_chart_config(
+        chart_type: Option<&str>,
+        x_property: &str,
+        y_property: Option<&str>,
+        aggregate: Option<&str>,
+    ) -> crate::core::ChartConfig {
+        use crate::core::{ChartAggregation, ChartConfig, ChartKind};
+
+        let kind = match chart_type {
+            Some("line") => ChartKind::Line,
+            Some("pie") => ChartKind::Pie,
+            Some("scatter") => ChartKind::Scatter,
+            _ => ChartKind::Bar,
+        };
+
+        let aggregation = match aggregate {
+            Some("sum") => ChartAggregation::Sum,
+            Some("avg") => ChartAggregation::Avg,
+            Some("min") => ChartAggregation::Min,
+            Some("max") => ChartAggregation::Max,
+            _ => ChartAggregation::Count,
+        };
+
+        let mut config = ChartConfig::new(kind, x_property);
+        config.y = y_property.map(|y| y.to_string());
+        config.aggregate = aggregation;
+        config
+    }
+
     fn add_base_view(
         &self,
         base_name: &str,
         view_name: &str,
         view_type: Option<&str>,
+        chart: Option<crate::core::ChartConfig>,
     ) -> Result<MCPToolResult> {
         use crate::core::{Base, BaseView, ViewType};
 
@@ -2546,11 +2986,14 @@ impl MCPToolExecutor {
                     Some("list") => ViewType::List,
                     Some("board") => ViewType::Board,
                     Some("gallery") => ViewType::Gallery,
+                    Some("chart") => ViewType::Chart,
+                    _ if chart.is_some() => ViewType::Chart,
                     _ => ViewType::Table,
                 };
 
                 let mut new_view = BaseView::new(view_name);
                 new_view.view_type = vt;
+                new_view.chart = chart;
 
                 base.add_view(new_view);
 
@@ -2618,6 +3061,339 @@ impl MCPToolExecutor {
         })))
     }
 
+    fn create_saved_search(
+        &self,
+        name: &str,
+        query: &crate::core::SearchQuery,
+    ) -> Result<MCPToolResult> {
+        if query.is_empty() {
+            return Ok(MCPToolResult::error(
+                "La búsqueda guardada necesita al menos un filtro (texto, tags, carpeta o propiedades)"
+                    .to_string(),
+            ));
+        }
+
+        let db = self.notes_db.borrow();
+        if db.get_saved_search(name)?.is_some() {
+            return Ok(MCPToolResult::error(format!(
+                "Ya existe una búsqueda guardada llamada '{}'",
+                name
+            )));
+        }
+
+        let id = db.create_saved_search(name, query)?;
+        let count = db.search(query)?.len();
+
+        Ok(MCPToolResult::success(json!({
+            "message": format!("✓ Búsqueda '{}' guardada ({} notas)", name, count),
+            "id": id,
+            "name": name,
+            "query": query,
+            "count": count
+        })))
+    }
+
+    fn list_saved_searches(&self) -> Result<MCPToolResult> {
+        let db = self.notes_db.borrow();
+        let searches = db.list_saved_searches()?;
+
+        let searches_json: Vec<_> = searches
+            .iter()
+            .map(|search| {
+                json!({
+                    "id": search.id,
+                    "name": search.name,
+                    "query": search.query,
+                    "count": db.search(&search.query).map(|r| r.len()).unwrap_or(0)
+                })
+            })
+            .collect();
+
+        Ok(MCPToolResult::success(json!({
+            "message": format!("✓ {} búsquedas guardadas", searches_json.len()),
+            "saved_searches": searches_json,
+            "total": searches_json.len()
+        })))
+    }
+
+    fn get_saved_search_notes(&self, name: &str) -> Result<MCPToolResult> {
+        let db = self.notes_db.borrow();
+        if db.get_saved_search(name)?.is_none() {
+            return Ok(MCPToolResult::error(format!(
+                "Búsqueda guardada '{}' no encontrada",
+                name
+            )));
+        }
+
+        let results = db.run_saved_search(name)?;
+        let notes_json: Vec<_> = results
+            .iter()
+            .map(|r| {
+                json!({
+                    "name": r.note_name,
+                    "path": r.note_path,
+                    "snippet": r.snippet
+                })
+            })
+            .collect();
+
+        Ok(MCPToolResult::success(json!({
+            "message": format!("✓ {} notas en '{}'", notes_json.len(), name),
+            "name": name,
+            "notes": notes_json,
+            "total": notes_json.len()
+        })))
+    }
+
+    fn delete_saved_search(&self, name: &str) -> Result<MCPToolResult> {
+        let db = self.notes_db.borrow();
+        if db.get_saved_search(name)?.is_none() {
+            return Ok(MCPToolResult::error(format!(
+                "Búsqueda guardada '{}' no encontrada",
+                name
+            )));
+        }
+
+        db.delete_saved_search(name)?;
+        Ok(MCPToolResult::success(json!({
+            "message": format!("✓ Búsqueda guardada '{}' eliminada", name),
+            "name": name
+        })))
+    }
+
+    /// Exportador con la página pedida y acceso a la BD (bloques de Bases y queries)
+    fn exporter(&self, page: &ExportPageArgs) -> Result<NoteExporter, ExportError> {
+        let mut setup = PageSetup::default()
+            .with_landscape(page.landscape.unwrap_or(false))
+            .with_header(page.header.clone());
+        if let Some(size) = &page.page_size {
+            setup = setup.with_size(PageSize::parse(size)?);
+        }
+        if let Some(margin) = page.margin_mm {
+            setup = setup.with_margins(Margins::uniform(margin.max(0.0)));
+        }
+        if page.footer.is_some() {
+            setup = setup.with_footer(page.footer.clone());
+        }
+        Ok(NoteExporter::new(self.notes_dir.root())
+            .with_page(setup)
+            .with_database(self.notes_db.borrow().clone_connection()))
+    }
+
+    /// Ruta de salida indicada (con `~` expandido) o la carpeta de exportaciones
+    fn export_target(&self, output: Option<&str>) -> PathBuf {
+        match output.map(str::trim).filter(|o| !o.is_empty()) {
+            Some(path) => match path.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+                None => PathBuf::from(path),
+            },
+            None => dirs::document_dir()
+                .or_else(dirs::home_dir)
+                .unwrap_or_else(|| self.notes_dir.root().to_path_buf())
+                .join("NotNative Exports"),
+        }
+    }
+
+    /// Preparar la exportación de una nota: leerla y resolver la ruta de salida
+    pub fn prepare_note_export(
+        &self,
+        name: &str,
+        format: &str,
+        output_path: Option<&str>,
+        page: &ExportPageArgs,
+    ) -> Result<NoteExportJob> {
+        let note = self
+            .notes_dir
+            .find_note(name)?
+            .ok_or_else(|| anyhow::anyhow!("Nota '{}' no encontrada", name))?;
+        let note = ExportNote::new(note.name(), note.read()?);
+        let format = ExportFormat::parse(format)?;
+
+        // Sin extensión, la ruta es una carpeta
+        let mut output = self.export_target(output_path);
+        if output.extension().is_none() {
+            output = output.join(format!("{}.{}", note.title(), format.extension()));
+        }
+        Ok(NoteExportJob {
+            exporter: self.exporter(page)?,
+            note,
+            format,
+            output,
+        })
+    }
+
+    /// Exportar una nota a HTML autocontenido o PDF
+    fn export_note(
+        &self,
+        name: &str,
+        format: &str,
+        output_path: Option<&str>,
+        page: &ExportPageArgs,
+    ) -> Result<MCPToolResult> {
+        let result = self
+            .prepare_note_export(name, format, output_path, page)
+            .and_then(|job| {
+                let note = job.note.name.clone();
+                Ok((note, job.run()?))
+            });
+
+        match result {
+            Ok((note, path)) => Ok(MCPToolResult::success(json!({
+                "message": format!("✓ '{}' exportada a {}", note, path.display()),
+                "note": note,
+                "format": format.to_lowercase(),
+                "path": path.to_string_lossy(),
+            }))),
+            Err(e) => Ok(MCPToolResult::error(e.to_string())),
+        }
+    }
+
+    /// Exportar varias notas a una carpeta, con los enlaces entre ellas reescritos
+    fn export_notes(
+        &self,
+        notes: Vec<ExportNote>,
+        format: &str,
+        output_dir: Option<&str>,
+        page: &ExportPageArgs,
+    ) -> Result<MCPToolResult> {
+        let output_dir = self.export_target(output_dir);
+        let result = ExportFormat::parse(format).and_then(|format| {
+            self.exporter(page)?
+                .export_notes(&notes, format, &output_dir)
+        });
+
+        match result {
+            Ok(paths) => Ok(MCPToolResult::success(json!({
+                "message": format!("✓ {} notas exportadas a {}", notes.len(), output_dir.display()),
+                "output_dir": output_dir.to_string_lossy(),
+                "files": paths.iter().map(|p| p.to_string_lossy()).collect::<Vec<_>>(),
+            }))),
+            Err(e) => Ok(MCPToolResult::error(e.to_string())),
+        }
+    }
+
+    /// Compilar notas ordenadas (o una carpeta) en un libro EPUB o DOCX
+    fn export_book(
+        &self,
+        names: &[String],
+        folder: Option<&str>,
+        format: &str,
+        output_path: Option<&str>,
+        title: Option<String>,
+        options: BookOptions,
+    ) -> Result<MCPToolResult> {
+        let format = match BookFormat::parse(format) {
+            Ok(format) => format,
+            Err(e) => return Ok(MCPToolResult::error(e.to_string())),
+        };
+
+        let mut notes = Vec::new();
+        let folder = folder.map(|f| f.trim_matches('/')).unwrap_or_default();
+        if !names.is_empty() {
+            for name in names {
+                match self.notes_dir.find_note(name)? {
+                    Some(note) => notes.push(ExportNote::new(note.name(), note.read()?)),
+                    None => {
+                        return Ok(MCPToolResult::error(format!(
+                            "Nota '{}' no encontrada",
+                            name
+                        )));
+                    }
+                }
+            }
+        } else {
+            for note in self.notes_dir.list_notes()? {
+                let in_folder = folder.is_empty()
+                    || note
+                        .name()
+                        .strip_prefix(folder)
+                        .is_some_and(|rest| rest.starts_with('/'));
+                if in_folder {
+                    notes.push(ExportNote::new(note.name(), note.read()?));
+                }
+            }
+            order_notes(&mut notes, &self.notes_config.borrow());
+        }
+        if notes.is_empty() {
+            return Ok(MCPToolResult::error(
+                "Indica 'notes' o una 'folder' con notas".to_string(),
+            ));
+        }
+
+        // Título: el indicado, el de la carpeta o el de la primera nota
+        let title = title
+            .filter(|t| !t.trim().is_empty())
+            .or_else(|| {
+                folder
+                    .rsplit('/')
+                    .next()
+                    .filter(|f| !f.is_empty())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| notes[0].title().to_string());
+        let options = options.with_title(title.clone());
+
+        // Sin extensión, la ruta es una carpeta
+        let mut output = self.export_target(output_path);
+        if output.extension().is_none() {
+            output = output.join(format!("{}.{}", title, format.extension()));
+        }
+        let chapters = notes.len();
+        let result = BookCompiler::new(self.notes_dir.root())
+            .with_options(options)
+            .compile(&notes, format, &output);
+
+        match result {
+            Ok(path) => Ok(MCPToolResult::success(json!({
+                "message": format!("✓ Libro '{}' ({} notas) compilado en {}", title, chapters, path.display()),
+                "format": format.extension(),
+                "notes": notes.iter().map(|n| n.name.as_str()).collect::<Vec<_>>(),
+                "path": path.to_string_lossy(),
+            }))),
+            Err(e) => Ok(MCPToolResult::error(e.to_string())),
+        }
+    }
+
+    /// Importar notas de otra aplicación y guardar el informe como nota
+    fn import_notes(
+        &self,
+        source: &str,
+        path: &str,
+        folder: Option<&str>,
+    ) -> Result<MCPToolResult> {
+        let source = match ImportSource::parse(source) {
+            Ok(source) => source,
+            Err(e) => return Ok(MCPToolResult::error(e.to_string())),
+        };
+        let path = match path.trim().strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => PathBuf::from(path.trim()),
+        };
+        let folder = folder.unwrap_or(source.name());
+        let root = self.notes_dir.root();
+
+        let mut report = match source.import(&path, root, Some(folder)) {
+            Ok(report) => report,
+            Err(e) => return Ok(MCPToolResult::error(e.to_string())),
+        };
+        report.register_bases(&self.notes_db.borrow())?;
+        let report_note = report.write_note(root, folder, source)?;
+        Ok(MCPToolResult::success(json!({
+            "message": format!(
+                "✓ {} notas importadas de {} ({} adjuntos, {} sin convertir)",
+                report.notes.len(),
+                source.name(),
+                report.attachments,
+                report.issues.len()
+            ),
+            "notes": report.notes,
+            "attachments": report.attachments,
+            "bases": report.bases,
+            "issues": report.issues,
+            "report_note": report_note,
+        })))
+    }
+
     pub fn get_notes_dir(&self) -> &NotesDirectory {
         &self.notes_dir
     }
@@ -2638,3 +3414,14 @@ impl MCPToolExecutor {
         self.note_memory.clone()
     }
 }
+
+/// Separar `"Carpeta/nota.md"` en carpeta y nombre base (la carpeta del nombre
+/// tiene prioridad sobre la indicada aparte)
+fn split_note_name<'a>(name: &'a str, folder: Option<&'a str>) -> (Option<&'a str>, &'a str) {
+    // Quitar extensión .md si ya existe (create_note la agrega automáticamente)
+    let clean_name = name.strip_suffix(".md").unwrap_or(name);
+    match clean_name.rsplit_once('/') {
+        Some((actual_folder, base_name)) => (Some(actual_folder), base_name),
+        None => (folder, clean_name),
+    }
+}
@@ -196,6 +196,12 @@ async fn call_tool(
                     | MCPToolCall::DuplicateNote { .. }
                     | MCPToolCall::MoveNote { .. }
                     | MCPToolCall::CreateFolder { .. }
+                    | MCPToolCall::CreateNoteFromTemplate { .. }
+                    | MCPToolCall::CreateDailyNote { .. }
+                    | MCPToolCall::CreatePeriodicNote { .. }
+                    | MCPToolCall::CreateSavedSearch { .. }
+                    | MCPToolCall::DeleteSavedSearch { .. }
+                    | MCPToolCall::ImportNotes { .. }
             );
 
             // Ejecutar la herramienta
@@ -741,18 +741,96 @@ pub fn get_all_tool_definitions() -> Vec<MCPTool> {
         // ==================== AUTOMATIZACIÓN ====================
         MCPTool {
             name: "create_daily_note".to_string(),
-            description: "Crea una nota diaria con la fecha actual (formato YYYY-MM-DD)"
+            description: "Crea la nota diaria de hoy en la carpeta y con el nombre configurados para las notas periódicas (por defecto Daily/YYYY-MM-DD)"
                 .to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
                     "template": {
                         "type": "string",
-                        "description": "Plantilla de contenido para la nota diaria (opcional)"
+                        "description": "Plantilla de contenido para la nota diaria (opcional, admite {{date:YYYY-MM-DD}} y el resto de variables de plantilla)"
                     }
                 }
             }),
         },
+        MCPTool {
+            name: "create_periodic_note".to_string(),
+            description: "Abre la nota diaria, semanal, mensual o anual de una fecha y la crea si no existe (con su plantilla, enlace al periodo superior y, si está activado, las tareas pendientes del día anterior). Devuelve el contenido y los nombres de las notas anterior, siguiente y superior.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "period": {
+                        "type": "string",
+                        "enum": ["daily", "weekly", "monthly", "yearly"],
+                        "description": "Tipo de nota periódica"
+                    },
+                    "date": {
+                        "type": "string",
+                        "description": "Fecha dentro del periodo (YYYY-MM-DD, opcional, por defecto hoy)"
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "description": "Periodos a desplazarse desde la fecha (-1 = anterior, 1 = siguiente, opcional)"
+                    }
+                },
+                "required": ["period"]
+            }),
+        },
+        MCPTool {
+            name: "list_periodic_notes".to_string(),
+            description: "Lista las notas periódicas existentes de un tipo, de la más reciente a la más antigua".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "period": {
+                        "type": "string",
+                        "enum": ["daily", "weekly", "monthly", "yearly"],
+                        "description": "Tipo de nota periódica"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Máximo de notas a devolver (opcional, por defecto 20)"
+                    }
+                },
+                "required": ["period"]
+            }),
+        },
+        MCPTool {
+            name: "create_note_from_template".to_string(),
+            description: "Crea una nota a partir de una plantilla de la carpeta de plantillas. Las plantillas admiten {{title}}, {{folder}}, {{date:FORMATO}}, {{time}}, campos {{prompt:Campo}}, condicionales {{#if Campo}}...{{/if}} e {{include:Otra}}.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "template": {
+                        "type": "string",
+                        "description": "Nombre de la plantilla (ver list_templates)"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "Nombre de la nota nueva (puede incluir carpeta: 'Reuniones/Sprint 12')"
+                    },
+                    "folder": {
+                        "type": "string",
+                        "description": "Carpeta donde crear la nota (opcional)"
+                    },
+                    "values": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" },
+                        "description": "Valores de los campos {{prompt:...}} de la plantilla, campo -> valor (opcional)"
+                    }
+                },
+                "required": ["template", "name"]
+            }),
+        },
+        MCPTool {
+            name: "list_templates".to_string(),
+            description: "Lista las plantillas disponibles con sus campos a rellenar y las carpetas que las usan por defecto".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {},
+                "required": []
+            }),
+        },
         MCPTool {
             name: "find_and_replace".to_string(),
             description: "Busca y reemplaza texto en una o múltiples notas".to_string(),
@@ -776,6 +854,172 @@ pub fn get_all_tool_definitions() -> Vec<MCPTool> {
                 "required": ["find", "replace"]
             }),
         },
+        // ==================== EXPORTACIÓN ====================
+        MCPTool {
+            name: "export_note".to_string(),
+            description: "Exporta una nota a un HTML autocontenido (CSS en línea, imágenes incrustadas, fórmulas y diagramas ya renderizados) o a PDF generado a partir de ese HTML".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Nombre de la nota"
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["html", "pdf"],
+                        "description": "Formato de salida"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Fichero o carpeta de salida (opcional, por defecto ~/Documents/NotNative Exports)"
+                    },
+                    "page_size": {
+                        "type": "string",
+                        "description": "Tamaño de página del PDF: A4, A3, A5, Letter, Legal o ANCHOxALTOmm (opcional, por defecto A4)"
+                    },
+                    "landscape": {
+                        "type": "boolean",
+                        "description": "Página apaisada (opcional)"
+                    },
+                    "margin_mm": {
+                        "type": "number",
+                        "description": "Margen de página en milímetros (opcional, por defecto 20)"
+                    },
+                    "header": {
+                        "type": "string",
+                        "description": "Cabecera de cada página; admite {title}, {date}, {page} y {pages} (opcional)"
+                    },
+                    "footer": {
+                        "type": "string",
+                        "description": "Pie de cada página (opcional, por defecto '{page} / {pages}')"
+                    }
+                },
+                "required": ["name", "format"]
+            }),
+        },
+        MCPTool {
+            name: "export_folder".to_string(),
+            description: "Exporta todas las notas de una carpeta (y subcarpetas) a HTML o PDF conservando la estructura; en HTML los enlaces entre notas siguen funcionando y se genera un index.html".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "folder": {
+                        "type": "string",
+                        "description": "Carpeta a exportar ('' o '/' para todas las notas)"
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["html", "pdf"],
+                        "description": "Formato de salida"
+                    },
+                    "output_dir": {
+                        "type": "string",
+                        "description": "Carpeta de salida (opcional, por defecto ~/Documents/NotNative Exports)"
+                    },
+                    "page_size": {
+                        "type": "string",
+                        "description": "Tamaño de página del PDF: A4, A3, A5, Letter, Legal o ANCHOxALTOmm (opcional, por defecto A4)"
+                    },
+                    "landscape": {
+                        "type": "boolean",
+                        "description": "Página apaisada (opcional)"
+                    },
+                    "margin_mm": {
+                        "type": "number",
+                        "description": "Margen de página en milímetros (opcional, por defecto 20)"
+                    },
+                    "header": {
+                        "type": "string",
+                        "description": "Cabecera de cada página; admite {title}, {date}, {page} y {pages} (opcional)"
+                    },
+                    "footer": {
+                        "type": "string",
+                        "description": "Pie de cada página (opcional, por defecto '{page} / {pages}')"
+                    }
+                },
+                "required": ["folder", "format"]
+            }),
+        },
+        MCPTool {
+            name: "export_book".to_string(),
+            description: "Compila varias notas en un libro EPUB 3 o DOCX: cada encabezado # es un capítulo, con índice generado, imágenes incrustadas y notas al pie. Usa 'notes' en el orden dado o una 'folder' ordenada como en la barra lateral".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "notes": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Notas en el orden del libro (opcional si se indica 'folder')"
+                    },
+                    "folder": {
+                        "type": "string",
+                        "description": "Carpeta a compilar, en el orden de la barra lateral (opcional)"
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["epub", "docx"],
+                        "description": "Formato del libro"
+                    },
+                    "output_path": {
+                        "type": "string",
+                        "description": "Fichero o carpeta de salida (opcional, por defecto ~/Documents/NotNative Exports)"
+                    },
+                    "title": {
+                        "type": "string",
+                        "description": "Título del libro (opcional, por defecto el nombre de la carpeta)"
+                    },
+                    "author": {
+                        "type": "string",
+                        "description": "Autor (opcional)"
+                    },
+                    "language": {
+                        "type": "string",
+                        "description": "Idioma del libro, p. ej. 'es' o 'en' (opcional, por defecto 'es')"
+                    },
+                    "keep_tags": {
+                        "type": "boolean",
+                        "description": "Conservar los #tags como texto (opcional, por defecto se quitan)"
+                    },
+                    "keep_properties": {
+                        "type": "boolean",
+                        "description": "Mostrar las propiedades inline como 'clave: valor' (opcional, por defecto se quitan)"
+                    },
+                    "keep_reminders": {
+                        "type": "boolean",
+                        "description": "Mostrar los recordatorios como texto (opcional, por defecto se quitan)"
+                    },
+                    "toc": {
+                        "type": "boolean",
+                        "description": "Incluir índice al principio (opcional, por defecto true)"
+                    }
+                },
+                "required": ["format"]
+            }),
+        },
+        MCPTool {
+            name: "import_notes".to_string(),
+            description: "Importa notas de otra aplicación a una carpeta del workspace, convierte enlaces, adjuntos y propiedades al formato de NotNative y guarda un informe con lo que no se pudo convertir. Las bases de datos de Notion se convierten en carpetas de notas con su Base".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "source": {
+                        "type": "string",
+                        "enum": ["obsidian", "notion", "evernote", "keep"],
+                        "description": "Aplicación de origen"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Ruta del vault o exportación a importar (Notion: carpeta o .zip de la exportación Markdown & CSV; Evernote: fichero .enex o carpeta con varios; Google Keep: carpeta o .zip de Takeout)"
+                    },
+                    "folder": {
+                        "type": "string",
+                        "description": "Carpeta de destino en el workspace (opcional, por defecto el nombre de la aplicación; '' para la raíz)"
+                    }
+                },
+                "required": ["source", "path"]
+            }),
+        },
         // ==================== SISTEMA ====================
         MCPTool {
             name: "get_app_info".to_string(),
@@ -1062,6 +1306,47 @@ pub fn get_all_tool_definitions() -> Vec<MCPTool> {
                 "required": []
             }),
         },
+        MCPTool {
+            name: "AddBaseView".to_string(),
+            description: "Añade una vista a una Base. Con view_type 'chart' dibuja un gráfico (barras, líneas, tarta o dispersión) a partir de las propiedades de las notas.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "base_name": {
+                        "type": "string",
+                        "description": "Nombre de la Base"
+                    },
+                    "view_name": {
+                        "type": "string",
+                        "description": "Nombre de la nueva vista"
+                    },
+                    "view_type": {
+                        "type": "string",
+                        "enum": ["table", "list", "board", "gallery", "chart"],
+                        "description": "Tipo de vista (default: table)"
+                    },
+                    "chart_type": {
+                        "type": "string",
+                        "enum": ["bar", "line", "pie", "scatter"],
+                        "description": "Tipo de gráfico (solo para view_type 'chart')"
+                    },
+                    "x_property": {
+                        "type": "string",
+                        "description": "Propiedad del eje X (ej: 'status', 'date')"
+                    },
+                    "y_property": {
+                        "type": "string",
+                        "description": "Propiedad numérica del eje Y (ej: 'hours'). Si se omite se cuentan notas"
+                    },
+                    "aggregate": {
+                        "type": "string",
+                        "enum": ["count", "sum", "avg", "min", "max"],
+                        "description": "Agregación de Y por cada valor de X (default: count)"
+                    }
+                },
+                "required": ["base_name", "view_name"]
+            }),
+        },
         MCPTool {
             name: "SetBaseColumns".to_string(),
             description: "Configura las columnas (propiedades) que se muestran en una Base.".to_string(),
@@ -1081,6 +1366,80 @@ pub fn get_all_tool_definitions() -> Vec<MCPTool> {
                 "required": ["base_name", "columns"]
             }),
         },
+        // === Búsquedas guardadas (carpetas virtuales) ===
+        MCPTool {
+            name: "CreateSavedSearch".to_string(),
+            description: "Guarda una búsqueda con nombre como carpeta virtual del sidebar (ej: 'Inbox', 'Borradores'). Su contenido se recalcula cada vez que se consulta.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Nombre de la carpeta virtual"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "Búsqueda en la sintaxis de la barra de búsqueda: texto libre, #tag, folder:Carpeta, clave::valor (ej: '#inbox status::draft')"
+                    },
+                    "text": {
+                        "type": "string",
+                        "description": "Texto a buscar en el nombre o contenido (opcional)"
+                    },
+                    "tags": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Tags que deben tener las notas (opcional)"
+                    },
+                    "folder": {
+                        "type": "string",
+                        "description": "Carpeta a la que limitar la búsqueda (opcional)"
+                    },
+                    "properties": {
+                        "type": "object",
+                        "additionalProperties": { "type": "string" },
+                        "description": "Propiedades inline requeridas, clave -> valor (valor vacío = solo que exista)"
+                    }
+                },
+                "required": ["name"]
+            }),
+        },
+        MCPTool {
+            name: "ListSavedSearches".to_string(),
+            description: "Lista las búsquedas guardadas (carpetas virtuales) con su query y número de notas.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {},
+                "required": []
+            }),
+        },
+        MCPTool {
+            name: "GetSavedSearchNotes".to_string(),
+            description: "Devuelve las notas que contiene ahora mismo una carpeta virtual (búsqueda guardada).".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Nombre de la búsqueda guardada"
+                    }
+                },
+                "required": ["name"]
+            }),
+        },
+        MCPTool {
+            name: "DeleteSavedSearch".to_string(),
+            description: "Elimina una búsqueda guardada (no borra ninguna nota).".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "Nombre de la búsqueda guardada a eliminar"
+                    }
+                },
+                "required": ["name"]
+            }),
+        },
     ]
 }
 
@@ -1,9 +1,25 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
+use std::collections::BTreeMap;
 
 use crate::mcp::protocol::MCPTool;
 
+/// Página de las exportaciones a PDF (todas opcionales)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportPageArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<String>, // "A4", "Letter", "210x297mm"...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub landscape: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin_mm: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footer: Option<String>,
+}
+
 /// Enum con todas las herramientas disponibles
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "tool", content = "args")]
@@ -224,20 +240,61 @@ pub enum MCPToolCall {
     // === Exportación e Importación ===
     ExportNote {
         name: String,
-        format: String, // "html", "pdf", "json", "txt"
+        format: String, // "html", "pdf"
         #[serde(skip_serializing_if = "Option::is_none")]
         output_path: Option<String>,
+        #[serde(flatten)]
+        page: ExportPageArgs,
     },
     ExportMultipleNotes {
         note_names: Vec<String>,
         format: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         output_dir: Option<String>,
+        #[serde(flatten)]
+        page: ExportPageArgs,
+    },
+    ExportFolder {
+        folder: String, // "" o "/" = todas las notas
+        format: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output_dir: Option<String>,
+        #[serde(flatten)]
+        page: ExportPageArgs,
+    },
+    ExportBook {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        notes: Vec<String>, // en este orden; si está vacío se usa `folder`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        folder: Option<String>, // ordenada como en la barra lateral
+        format: String, // "epub", "docx"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_path: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        author: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        #[serde(default)]
+        keep_tags: bool,
+        #[serde(default)]
+        keep_properties: bool,
+        #[serde(default)]
+        keep_reminders: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        toc: Option<bool>,
     },
     BackupNotes {
         #[serde(skip_serializing_if = "Option::is_none")]
         output_path: Option<String>,
     },
+    ImportNotes {
+        source: String, // "obsidian" | "notion" | "evernote" | "keep"
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        folder: Option<String>,
+    },
     ImportFromUrl {
         url: String,
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -264,6 +321,30 @@ pub enum MCPToolCall {
         #[serde(skip_serializing_if = "Option::is_none")]
         template: Option<String>,
     },
+    CreateNoteFromTemplate {
+        template: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        folder: Option<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        values: BTreeMap<String, String>, // Campos {{prompt:...}} y variables extra
+    },
+    ListTemplates {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        _dummy: Option<()>,
+    },
+    CreatePeriodicNote {
+        period: String, // daily, weekly, monthly, yearly
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        date: Option<String>, // YYYY-MM-DD (por defecto hoy)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        offset: Option<i32>, // -1 = periodo anterior, 1 = siguiente
+    },
+    ListPeriodicNotes {
+        period: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<usize>,
+    },
     BatchRename {
         pattern: String,
         replacement: String,
@@ -370,7 +451,15 @@ pub enum MCPToolCall {
         base_name: String,
         view_name: String,
         #[serde(skip_serializing_if = "Option::is_none")]
-        view_type: Option<String>, // "table", "list", "board"
+        view_type: Option<String>, // "table", "list", "board", "gallery", "chart"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        chart_type: Option<String>, // "bar", "line", "pie", "scatter"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        x_property: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        y_property: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        aggregate: Option<String>, // "count", "sum", "avg", "min", "max"
     },
     SetBaseColumns {
         base_name: String,
@@ -380,6 +469,31 @@ pub enum MCPToolCall {
         #[serde(skip_serializing_if = "Option::is_none")]
         folder: Option<String>,
     },
+
+    // === Búsquedas guardadas (carpetas virtuales) ===
+    CreateSavedSearch {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query: Option<String>, // Sintaxis de la barra de búsqueda: "texto #tag folder:X clave::valor"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        folder: Option<String>,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        properties: BTreeMap<String, String>,
+    },
+    ListSavedSearches {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        _dummy: Option<()>,
+    },
+    GetSavedSearchNotes {
+        name: String,
+    },
+    DeleteSavedSearch {
+        name: String,
+    },
 }
 
 /// Resultado de la ejecución de una herramienta